@@ -1,18 +1,35 @@
 #[macro_use]
 extern crate rocket;
 
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::path::PathBuf;
+use std::time::Instant;
 
+use rocket::data::{Data, ToByteUnit};
+use rocket::fairing::AdHoc;
 use rocket::form::Form;
 use rocket::fs::{relative, FileServer, NamedFile};
-use rocket::http::{Method, Status};
+use rocket::http::{ContentType, Method, Status};
 use rocket::response::{Flash, Redirect};
 use rocket::serde::{json::Json, Deserialize, Serialize};
 use rocket::State;
 use rocket_cors::{AllowedHeaders, AllowedOrigins, Cors, CorsOptions};
 
-use lbf::lbf_run::solve_json;
+use lbf::io::error::IoError;
+use lbf::io::png_export;
+use lbf::lbf_run::{solve_json_report, solve_zip_report, SolveReport};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tracing::{field, info_span};
+
+use crate::telemetry::Telemetry;
+
+mod telemetry;
+
+/// Number of threads shared by all requests, so a single large instance cannot occupy every core
+const N_THREADS: usize = 4;
+
+/// Largest zip archive that is accepted as upload, see [`lbf::serve::MAX_BODY_SIZE`]
+const MAX_ARCHIVE_SIZE: usize = lbf::serve::MAX_BODY_SIZE;
 
 type SvgFiles = Mutex<Vec<String>>; // Define a type alias for shared state.
 
@@ -23,14 +40,55 @@ pub struct InputData {
 }
 
 #[post("/json", format = "json", data = "<input_data>")]
-async fn json(input_data: Json<InputData>, svg_state: &State<SvgFiles>) -> Result<Json<Vec<Vec<String>>>, String> {
+async fn json(input_data: Json<InputData>, svg_state: &State<SvgFiles>, thread_pool: &State<Arc<ThreadPool>>, telemetry: &State<Telemetry>) -> Result<Json<Vec<Vec<String>>>, String> {
     let json = input_data.into_inner();
 
     if json.input.is_empty() {
         return Err("JSON cannot be empty".to_string());
     }
 
-    let mut svg_files = solve_json(json.config, json.input.clone(), "static/solutions/".to_string());
+    let thread_pool = Some(Arc::clone(thread_pool.inner()));
+    run_job(telemetry, || solve_json_report(json.config, json.input.clone(), "static/solutions/".to_string(), thread_pool))
+}
+
+/// Solves an instance uploaded as a zip archive, with the instance JSON and the DXF/SVG files it references.
+/// The config (JSON, the default config if absent) is passed in the query
+#[post("/zip?<config>", data = "<archive>")]
+async fn zip(config: Option<String>, archive: Data<'_>, thread_pool: &State<Arc<ThreadPool>>, telemetry: &State<Telemetry>) -> Result<Json<Vec<Vec<String>>>, String> {
+    let archive = archive
+        .open(MAX_ARCHIVE_SIZE.bytes())
+        .into_bytes()
+        .await
+        .map_err(|err| err.to_string())?;
+    if !archive.is_complete() {
+        return Err(format!("archive is larger than {} bytes", MAX_ARCHIVE_SIZE));
+    }
+    if archive.is_empty() {
+        return Err("archive cannot be empty".to_string());
+    }
+
+    let thread_pool = Some(Arc::clone(thread_pool.inner()));
+    run_job(telemetry, || solve_zip_report(config.unwrap_or_default(), &archive, "static/solutions/".to_string(), thread_pool))
+}
+
+/// Runs a solve job in its own span and records its outcome, returns the paths of the SVG and JSON files of the solution
+fn run_job(telemetry: &Telemetry, solve: impl FnOnce() -> Result<SolveReport, IoError>) -> Result<Json<Vec<Vec<String>>>, String> {
+    let job_span = info_span!("job", outcome = field::Empty, usage = field::Empty);
+    let _job_guard = job_span.enter();
+    let start = Instant::now();
+    let result = solve();
+    let outcome = match &result {
+        Ok(report) if report.files[0].is_empty() => "no_solution",
+        Ok(_) => "ok",
+        Err(_) => "invalid_input",
+    };
+    job_span.record("outcome", outcome);
+    if let Ok(report) = &result {
+        job_span.record("usage", report.usage);
+    }
+    telemetry.record_job(outcome, start.elapsed(), result.as_ref().ok());
+
+    let svg_files = result.map_err(|err| err.to_string())?.files;
     if svg_files.is_empty() {
         return Err("No solution found.".to_string());
     } else {
@@ -54,6 +112,18 @@ async fn file(path: String) -> Result<NamedFile, Status> {
         .map_err(|_| Status::InternalServerError)
 }
 
+/// Rasterizes an SVG of a solution to a PNG image, for clients which cannot render SVG
+#[get("/png?<path>&<width>")]
+async fn png(path: String, width: Option<u32>) -> Result<(ContentType, Vec<u8>), (Status, String)> {
+    let file_path = PathBuf::from(path);
+    if !file_path.is_file() || file_path.extension().map_or(true, |ext| ext != "svg") {
+        return Err((Status::NotFound, "not found".to_string()));
+    }
+    let svg = std::fs::read(&file_path).map_err(|_| (Status::InternalServerError, "could not read file".to_string()))?;
+    let png = png_export::svg_to_png(&svg, width.unwrap_or(png_export::DEFAULT_PNG_WIDTH)).map_err(|err| (Status::BadRequest, err))?;
+    Ok((ContentType::PNG, png))
+}
+
 // #[get("/file")]
 // fn sol(svg_state: &State<SvgFiles>) {
 //     let svg_files = svg_state.lock().expect("State lock poisoned");
@@ -86,9 +156,25 @@ fn rocket() -> _ {
 
     // TODO: fix the path to the static files
     // Prevent access to all files in ./static
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(N_THREADS)
+        .build()
+        .expect("could not build thread pool");
+
     rocket::build()
         .manage(SvgFiles::default()) // Initialize shared state.
-        .mount("/", routes![json, file])
+        .manage(Arc::new(thread_pool))
+        .mount("/", routes![json, zip, file, png])
         .mount("/", FileServer::from(relative!("./")))
         .attach(cors)
+        .attach(AdHoc::on_ignite("Telemetry", |rocket| async {
+            rocket.manage(Telemetry::from_env())
+        }))
+        .attach(AdHoc::on_shutdown("Telemetry", |rocket| {
+            Box::pin(async move {
+                if let Some(telemetry) = rocket.state::<Telemetry>() {
+                    telemetry.shutdown().await;
+                }
+            })
+        }))
 }
@@ -0,0 +1,194 @@
+//! Monitoring of the solve jobs of the backend.
+//!
+//! With the `otlp` feature, the metrics of every job and the spans of the solver are exported over OTLP (gRPC)
+//! as soon as `OTEL_EXPORTER_OTLP_ENDPOINT` is set, e.g. to `http://localhost:4317`.
+//! The other `OTEL_*` variables of the OpenTelemetry SDK (`OTEL_SERVICE_NAME`, `OTEL_RESOURCE_ATTRIBUTES`, ...) apply as well.
+//! Otherwise, nothing is exported and recording a job is a no-op.
+//!
+//! Metrics:
+//! - `nesting.job.duration` (s): duration of a job, by `outcome` (`ok`, `no_solution` or `invalid_input`)
+//! - `nesting.phase.duration` (s): duration of the phases of a job, by `phase` (`parse`, `solve`, `post_process` and `write`)
+//! - `nesting.usage` (1): usage achieved by the solution of a job
+//! - `nesting.items` ({item}): items demanded by the jobs, by `placed` (`true` or `false`)
+
+use std::time::Duration;
+
+use lbf::lbf_run::SolveReport;
+
+/// Name of the service, unless overridden by `OTEL_SERVICE_NAME`
+#[cfg(feature = "otlp")]
+const SERVICE_NAME: &str = "jagua-rs-gui";
+
+pub struct Telemetry {
+    #[cfg(feature = "otlp")]
+    otlp: Option<otlp::OtlpTelemetry>,
+}
+
+impl Telemetry {
+    /// Starts exporting if the `otlp` feature is enabled and an endpoint is configured.
+    /// Must be called within the Tokio runtime, which runs the batch exporters
+    pub fn from_env() -> Self {
+        #[cfg(feature = "otlp")]
+        {
+            if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_none() {
+                return Self { otlp: None };
+            }
+            match otlp::OtlpTelemetry::init() {
+                Ok(otlp) => {
+                    println!("Exporting telemetry over OTLP");
+                    Self { otlp: Some(otlp) }
+                }
+                Err(err) => {
+                    eprintln!("Could not set up OTLP export: {}", err);
+                    Self { otlp: None }
+                }
+            }
+        }
+        #[cfg(not(feature = "otlp"))]
+        Self {}
+    }
+
+    /// Records the metrics of a finished job, the report is absent if the input was invalid
+    #[cfg_attr(not(feature = "otlp"), allow(unused_variables))]
+    pub fn record_job(&self, outcome: &'static str, duration: Duration, report: Option<&SolveReport>) {
+        #[cfg(feature = "otlp")]
+        if let Some(otlp) = &self.otlp {
+            otlp.record_job(outcome, duration, report);
+        }
+    }
+
+    /// Exports everything which is still buffered
+    pub async fn shutdown(&self) {
+        #[cfg(feature = "otlp")]
+        if let Some(otlp) = &self.otlp {
+            //the providers block until their exporters are done
+            let otlp = otlp.clone();
+            let _ = rocket::tokio::task::spawn_blocking(move || otlp.shutdown()).await;
+        }
+    }
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use std::time::Duration;
+
+    use lbf::lbf_run::SolveReport;
+    use opentelemetry::metrics::{Counter, Histogram};
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::{global, KeyValue};
+    use opentelemetry_otlp::{MetricExporter, SpanExporter};
+    use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+    use opentelemetry_sdk::runtime::Tokio;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use opentelemetry_sdk::Resource;
+    use tracing_subscriber::filter::LevelFilter;
+    use tracing_subscriber::prelude::*;
+
+    use super::SERVICE_NAME;
+
+    #[derive(Clone)]
+    pub struct OtlpTelemetry {
+        tracer_provider: TracerProvider,
+        meter_provider: SdkMeterProvider,
+        job_duration: Histogram<f64>,
+        phase_duration: Histogram<f64>,
+        usage: Histogram<f64>,
+        items: Counter<u64>,
+    }
+
+    impl OtlpTelemetry {
+        pub fn init() -> Result<Self, Box<dyn std::error::Error>> {
+            let resource = match std::env::var_os("OTEL_SERVICE_NAME") {
+                Some(_) => Resource::default(),
+                None => Resource::default().merge(&Resource::new([KeyValue::new(
+                    "service.name",
+                    SERVICE_NAME,
+                )])),
+            };
+
+            let tracer_provider = TracerProvider::builder()
+                .with_batch_exporter(SpanExporter::builder().with_tonic().build()?, Tokio)
+                .with_resource(resource.clone())
+                .build();
+            let meter_provider = SdkMeterProvider::builder()
+                .with_reader(
+                    PeriodicReader::builder(MetricExporter::builder().with_tonic().build()?, Tokio)
+                        .build(),
+                )
+                .with_resource(resource)
+                .build();
+            global::set_meter_provider(meter_provider.clone());
+
+            //the spans of the parser and the optimizer (`parse`, `solve`, `place_item`, ...) become part of the trace of their job
+            let tracer = tracer_provider.tracer("jagua-rs");
+            tracing_subscriber::registry()
+                .with(
+                    tracing_opentelemetry::layer()
+                        .with_tracer(tracer)
+                        .with_filter(LevelFilter::INFO),
+                )
+                .try_init()?;
+
+            let meter = global::meter("jagua-rs");
+            Ok(Self {
+                tracer_provider,
+                meter_provider,
+                job_duration: meter
+                    .f64_histogram("nesting.job.duration")
+                    .with_unit("s")
+                    .with_description("Duration of a solve job")
+                    .build(),
+                phase_duration: meter
+                    .f64_histogram("nesting.phase.duration")
+                    .with_unit("s")
+                    .with_description("Duration of a phase of a solve job")
+                    .build(),
+                usage: meter
+                    .f64_histogram("nesting.usage")
+                    .with_unit("1")
+                    .with_description("Usage achieved by the solution of a solve job")
+                    .build(),
+                items: meter
+                    .u64_counter("nesting.items")
+                    .with_unit("{item}")
+                    .with_description("Items demanded by the solve jobs")
+                    .build(),
+            })
+        }
+
+        pub fn record_job(
+            &self,
+            outcome: &'static str,
+            duration: Duration,
+            report: Option<&SolveReport>,
+        ) {
+            self.job_duration.record(
+                duration.as_secs_f64(),
+                &[KeyValue::new("outcome", outcome)],
+            );
+            if let Some(report) = report {
+                for (phase, duration) in &report.phases {
+                    self.phase_duration
+                        .record(duration.as_secs_f64(), &[KeyValue::new("phase", *phase)]);
+                }
+                self.usage.record(report.usage as f64, &[]);
+                let n_unplaced = report.n_items_demanded.saturating_sub(report.n_items_placed);
+                self.items.add(
+                    report.n_items_placed as u64,
+                    &[KeyValue::new("placed", true)],
+                );
+                self.items
+                    .add(n_unplaced as u64, &[KeyValue::new("placed", false)]);
+            }
+        }
+
+        pub fn shutdown(&self) {
+            if let Err(err) = self.tracer_provider.shutdown() {
+                eprintln!("Could not export the remaining spans: {}", err);
+            }
+            if let Err(err) = self.meter_provider.shutdown() {
+                eprintln!("Could not export the remaining metrics: {}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Fuzzing harnesses for the parser and the collision detection engine.
+//! Both take raw bytes, so the same inputs can be replayed by the fuzz targets and the regression tests.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use jagua_rs::entities::bin::Bin;
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::entities::layout::Layout;
+use jagua_rs::fsize;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
+use jagua_rs::io::json_instance::{JsonInstance, JsonShape};
+use jagua_rs::io::parser::Parser;
+use jagua_rs::util::config::{CDEConfig, GeometricFidelity, SPSurrogateConfig};
+use jagua_rs::util::polygon_simplification::PolySimplConfig;
+use jagua_rs::PI;
+
+/// Instances with more items are skipped, to keep the fuzzer fast
+const MAX_ITEMS: usize = 32;
+/// Instances with more points in total are skipped, to keep the fuzzer fast
+const MAX_POINTS: usize = 2000;
+
+/// Instance in which the items are placed by [`place_items`]
+const PLACEMENT_INSTANCE: &str = include_str!("../../../assets/albano.json");
+
+pub fn cde_config() -> CDEConfig {
+    CDEConfig {
+        quadtree_depth: 5,
+        hpg_n_cells: 2000,
+        item_surrogate_config: SPSurrogateConfig {
+            pole_coverage_goal: 0.9,
+            max_poles: 10,
+            n_ff_poles: 2,
+            n_ff_piers: 0,
+        },
+    }
+}
+
+pub fn parser() -> Parser {
+    Parser::new(
+        PolySimplConfig::Enabled { tolerance: 0.001 },
+        cde_config(),
+        true,
+        PathBuf::new(),
+        GeometricFidelity::default(),
+    )
+}
+
+/// Parses the bytes as a JSON instance.
+/// Bytes which are not valid JSON, or instances which refer to DXF files or are too large, are ignored.
+/// Malformed instances should be rejected with a `ParseError`, only panics are reported.
+pub fn parse_instance(data: &[u8]) {
+    let Ok(json_instance) = serde_json::from_slice::<JsonInstance>(data) else {
+        return;
+    };
+    let shapes = json_instance
+        .items
+        .iter()
+        .map(|item| item.shape.as_ref())
+        .chain(
+            json_instance
+                .bins
+                .iter()
+                .flatten()
+                .map(|bin| bin.shape.as_ref()),
+        );
+
+    let mut n_points = 0;
+    for shape in shapes {
+        match shape {
+            //the shape is defined in a DXF file
+            None => return,
+            Some(shape) => n_points += count_points(shape),
+        }
+    }
+    if json_instance.items.len() > MAX_ITEMS || n_points > MAX_POINTS {
+        return;
+    }
+
+    let _ = parser().parse(&json_instance);
+}
+
+/// A single modification of a layout, or a query on its CDE
+#[derive(Arbitrary, Debug)]
+pub enum PlacementOp {
+    /// Places the item if it does not collide with anything.
+    /// The position is relative to the bin (`u16::MAX` maps to its width or height), the rotation to a full turn.
+    Place {
+        item: u8,
+        rotation: u16,
+        x: u16,
+        y: u16,
+    },
+    /// Removes the n-th placed item
+    Remove { index: u8, commit_instant: bool },
+    /// Stores a snapshot of the layout, replacing the previous one
+    Snapshot,
+    /// Restores the stored snapshot
+    Restore,
+    /// Commits all pending changes to the CDE
+    Flush,
+}
+
+/// Interprets the bytes as a sequence of [`PlacementOp`]s and applies them to a layout of a fixed instance.
+/// The debug assertions of the layout and CDE verify their state after every modification.
+pub fn place_items(data: &[u8]) {
+    let Ok(ops) = Vec::<PlacementOp>::arbitrary_take_rest(Unstructured::new(data)) else {
+        return;
+    };
+    let instance = placement_instance();
+    let Instance::SP(sp_instance) = instance else {
+        panic!("placement instance is not a strip packing instance");
+    };
+    let strip_width = 1.5 * sp_instance.item_area / sp_instance.strip_height;
+    let rect = AARectangle::new(0.0, 0.0, strip_width, sp_instance.strip_height);
+    let mut layout = Layout::new(0, Bin::from_strip(rect, cde_config()));
+    let mut snapshot = None;
+
+    for op in ops {
+        match op {
+            PlacementOp::Place {
+                item,
+                rotation,
+                x,
+                y,
+            } => {
+                let item = instance.item(item as usize % instance.items().len());
+                let bbox = layout.bin.bbox();
+                let d_transf = DTransformation::new(
+                    fraction(rotation) * 2.0 * PI,
+                    (
+                        bbox.x_min + fraction(x) * bbox.width(),
+                        bbox.y_min + fraction(y) * bbox.height(),
+                    ),
+                );
+                let mut buffer = item.shape.as_ref().clone();
+                let collides = layout.cde().surrogate_or_poly_collides(
+                    &item.shape,
+                    &d_transf.compose(),
+                    &mut buffer,
+                    &[],
+                );
+                if !collides {
+                    layout.place_item(item, None, d_transf);
+                }
+            }
+            PlacementOp::Remove {
+                index,
+                commit_instant,
+            } => {
+                let n_placed = layout.placed_items().len();
+                if n_placed > 0 {
+                    let key = layout
+                        .placed_items()
+                        .keys()
+                        .nth(index as usize % n_placed)
+                        .unwrap();
+                    layout.remove_item(key, commit_instant);
+                }
+            }
+            PlacementOp::Snapshot => snapshot = Some(layout.create_snapshot()),
+            PlacementOp::Restore => {
+                if let Some(snapshot) = &snapshot {
+                    layout.restore(snapshot);
+                }
+            }
+            PlacementOp::Flush => layout.flush_changes(),
+        }
+    }
+}
+
+fn placement_instance() -> &'static Instance {
+    static INSTANCE: OnceLock<Instance> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let json_instance = serde_json::from_str(PLACEMENT_INSTANCE).unwrap();
+        parser().parse(&json_instance).unwrap()
+    })
+}
+
+fn fraction(value: u16) -> fsize {
+    value as fsize / u16::MAX as fsize
+}
+
+fn count_points(shape: &JsonShape) -> usize {
+    match shape {
+        JsonShape::Rectangle { .. } => 4,
+        JsonShape::SimplePolygon(sp) => sp.0.len(),
+        JsonShape::Polygon(p) => p.outer.0.len() + p.inner.iter().map(|i| i.0.len()).sum::<usize>(),
+        JsonShape::MultiPolygon(mp) => mp
+            .iter()
+            .map(|p| p.outer.0.len() + p.inner.iter().map(|i| i.0.len()).sum::<usize>())
+            .sum(),
+        JsonShape::Wkt(wkt) => wkt.matches(',').count() + 1,
+    }
+}
@@ -0,0 +1,27 @@
+use std::fs;
+use std::path::Path;
+
+/// Replays every input in `regressions/<target>` through the harness of the fuzz target
+fn replay(target: &str, harness: fn(&[u8])) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("regressions")
+        .join(target);
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.file_name().is_some_and(|n| n == ".gitkeep") {
+            continue;
+        }
+        println!("replaying {}", path.display());
+        harness(&fs::read(&path).unwrap());
+    }
+}
+
+#[test]
+fn parse_instance_regressions() {
+    replay("parse_instance", jagua_rs_fuzz::parse_instance);
+}
+
+#[test]
+fn place_items_regressions() {
+    replay("place_items", jagua_rs_fuzz::place_items);
+}
@@ -1,12 +1,19 @@
+use std::sync::Arc;
+
+use bitvec::vec::BitVec;
 use indexmap::IndexSet;
+use tracing::debug_span;
 use tribool::Tribool;
 
+use crate::collision_detection::cd_trace::{self, CDStage};
 use crate::collision_detection::hazard::Hazard;
 use crate::collision_detection::hazard::HazardEntity;
 use crate::collision_detection::hpg::grid::Grid;
 use crate::collision_detection::hpg::hazard_proximity_grid::{DirtyState, HazardProximityGrid};
 use crate::collision_detection::hpg::hpg_cell::HPGCell;
-use crate::collision_detection::quadtree::qt_node::QTNode;
+use crate::collision_detection::quadtree::qt_hazard::QTHazPresence;
+use crate::collision_detection::quadtree::qt_node::{QTCollision, QTNode};
+use crate::collision_detection::quadtree::qt_stats::QTStats;
 use crate::collision_detection::quadtree::qt_traits::QTQueryable;
 use crate::fsize;
 use crate::geometry::fail_fast::sp_surrogate::SPSurrogate;
@@ -15,11 +22,13 @@ use crate::geometry::geo_traits::{CollidesWith, Shape, Transformable, Transforma
 use crate::geometry::primitives::aa_rectangle::AARectangle;
 use crate::geometry::primitives::circle::Circle;
 use crate::geometry::primitives::edge::Edge;
+use crate::geometry::primitives::oriented_rectangle::OrientedRectangle;
 use crate::geometry::primitives::point::Point;
 use crate::geometry::primitives::simple_polygon::SimplePolygon;
 use crate::geometry::transformation::Transformation;
-use crate::util::assertions;
+use crate::util::assertions::{self, debug_assert_with_svg};
 use crate::util::config::CDEConfig;
+use crate::util::debug_svg;
 
 /// The Collision Detection Engine (CDE).
 /// The CDE can resolve a range of collision queries
@@ -33,6 +42,9 @@ pub struct CDEngine {
     config: CDEConfig,
     bbox: AARectangle,
     uncommitted_deregisters: Vec<Hazard>,
+    /// Whether all hazards ever registered are axis-aligned rectangles.
+    /// If so, collisions of axis-aligned rectangles are resolved by comparing the bounding boxes of the hazards found in the quadtree.
+    rect_mode: bool,
 }
 
 /// Snapshot of the state of [CDEngine] at a given time.
@@ -60,6 +72,8 @@ impl CDEngine {
             qt_root.register_hazard(haz.into());
         }
 
+        let rect_mode = static_hazards.iter().all(|h| h.shape.is_aa_rectangle());
+
         CDEngine {
             quadtree: qt_root,
             static_hazards,
@@ -68,6 +82,7 @@ impl CDEngine {
             config,
             bbox,
             uncommitted_deregisters: vec![],
+            rect_mode,
         }
     }
 
@@ -85,6 +100,8 @@ impl CDEngine {
             .iter()
             .position(|h| h.entity == hazard.entity);
 
+        let shape = hazard.shape.clone();
+        self.rect_mode &= shape.is_aa_rectangle();
         let hazard = match hazard_in_uncommitted_deregs {
             Some(index) => {
                 let unc_hazard = self.uncommitted_deregisters.swap_remove(index);
@@ -101,7 +118,10 @@ impl CDEngine {
         }
         self.dynamic_hazards.push(hazard);
 
-        debug_assert!(assertions::qt_contains_no_dangling_hazards(self));
+        debug_assert_with_svg!(
+            assertions::qt_contains_no_dangling_hazards(self),
+            debug_svg::write_cde_svg(self, Some(&shape))
+        );
     }
 
     /// Removes a hazard from the CDE.
@@ -122,6 +142,7 @@ impl CDEngine {
             .expect("Hazard not found");
 
         let hazard = self.dynamic_hazards.swap_remove(haz_index);
+        let shape = hazard.shape.clone();
 
         match commit_instant {
             true => self.quadtree.deregister_hazard(hazard_entity),
@@ -133,7 +154,10 @@ impl CDEngine {
         if let Some(hpg) = self.haz_prox_grid.as_mut() {
             hpg.deregister_hazard(hazard_entity, self.dynamic_hazards.iter(), commit_instant)
         }
-        debug_assert!(assertions::qt_contains_no_dangling_hazards(self));
+        debug_assert_with_svg!(
+            assertions::qt_contains_no_dangling_hazards(self),
+            debug_svg::write_cde_svg(self, Some(&shape))
+        );
     }
 
     pub fn create_snapshot(&mut self) -> CDESnapshot {
@@ -141,7 +165,7 @@ impl CDEngine {
         assert!(self
             .haz_prox_grid
             .as_ref()
-            .map_or(true, |hpg| !hpg.is_dirty()));
+            .is_none_or(|hpg| !hpg.is_dirty()));
         CDESnapshot {
             dynamic_hazards: self.dynamic_hazards.clone(),
             grid: self.haz_prox_grid.as_ref().map(|hpg| hpg.grid.clone()),
@@ -194,6 +218,7 @@ impl CDEngine {
         }
 
         for hazard in hazards_to_add {
+            self.rect_mode &= hazard.shape.is_aa_rectangle();
             self.quadtree.register_hazard((&hazard).into());
             self.dynamic_hazards.push(hazard);
         }
@@ -225,6 +250,11 @@ impl CDEngine {
         1 + self.quadtree.get_number_of_children()
     }
 
+    /// Statistics on the structure of the quadtree, see [`QTStats`]
+    pub fn quadtree_stats(&self) -> QTStats {
+        self.quadtree.stats()
+    }
+
     pub fn bbox(&self) -> &AARectangle {
         &self.bbox
     }
@@ -304,7 +334,8 @@ impl CDEngine {
         }
     }
 
-    ///Checks whether a simple polygon collides with any of the (relevant) hazards
+    ///Checks whether a simple polygon collides with any of the (relevant) hazards.
+    ///Convex shapes are tested against convex hazards with the separating axis theorem.
     /// # Arguments
     /// * `shape` - The shape (already transformed) to be checked for collisions
     /// * `irrelevant_hazards` - entities to be ignored during the check
@@ -313,14 +344,164 @@ impl CDEngine {
         shape: &SimplePolygon,
         irrelevant_hazards: &[HazardEntity],
     ) -> bool {
+        let _span = debug_span!("poly_collides").entered();
+        let stage = self.explain_poly_collision(shape, irrelevant_hazards);
+        cd_trace::record(stage);
+        stage.collides()
+    }
+
+    /// Resolves the same query as [`Self::poly_collides`], returning the stage which decided it.
+    pub fn explain_poly_collision(
+        &self,
+        shape: &SimplePolygon,
+        irrelevant_hazards: &[HazardEntity],
+    ) -> CDStage {
+        self.explain_poly_collision_in(&self.quadtree, shape, irrelevant_hazards)
+    }
+
+    /// [`Self::explain_poly_collision`] with the quadtree queries starting from `qt_node`,
+    /// which has to strictly contain the shape (see [`QTNode::deepest_enclosing`]) or be the root.
+    fn explain_poly_collision_in(
+        &self,
+        qt_node: &QTNode,
+        shape: &SimplePolygon,
+        irrelevant_hazards: &[HazardEntity],
+    ) -> CDStage {
         match self.bbox.relation_to(&shape.bbox()) {
             //Not fully inside bbox => definite collision
-            GeoRelation::Disjoint | GeoRelation::Enclosed | GeoRelation::Intersecting => true,
+            GeoRelation::Disjoint | GeoRelation::Enclosed | GeoRelation::Intersecting => {
+                CDStage::BBoxRejected
+            }
             GeoRelation::Surrounding => {
-                self.poly_collides_by_edge_intersection(shape, irrelevant_hazards)
-                    || self.poly_collides_by_containment(shape, irrelevant_hazards)
+                if self.rect_mode && shape.is_aa_rectangle() {
+                    match rect_collides_in(qt_node, &shape.bbox, irrelevant_hazards) {
+                        true => CDStage::RectRejected,
+                        false => CDStage::RectAccepted,
+                    }
+                } else if shape.convex {
+                    self.explain_convex_collision_in(qt_node, shape, irrelevant_hazards)
+                } else {
+                    match poly_collides_by_edge_intersection(qt_node, shape, irrelevant_hazards) {
+                        Some(QTCollision::Entire) => CDStage::QuadtreeEntireRejected,
+                        Some(QTCollision::Partial) => CDStage::EdgeTestRejected,
+                        None => {
+                            match self.poly_collides_by_containment(shape, irrelevant_hazards) {
+                                true => CDStage::ContainmentRejected,
+                                false => CDStage::ExactAccepted,
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolves [`Self::explain_poly_collision_in`] for a convex shape, visiting the hazards in the quadtree nodes around it.
+    /// Convex hazards are tested against the shape with the separating axis theorem, once per hazard.
+    /// Touching the exterior of the bin is allowed, touching any other convex hazard is considered a collision.
+    /// Non-convex hazards are tested on the edges they have in each node, followed by a containment test if none intersect.
+    fn explain_convex_collision_in(
+        &self,
+        qt_node: &QTNode,
+        shape: &SimplePolygon,
+        irrelevant_hazards: &[HazardEntity],
+    ) -> CDStage {
+        debug_assert!(shape.convex);
+        let mut sat_tested: Vec<HazardEntity> = vec![];
+        let mut edge_tested: Vec<(HazardEntity, Arc<SimplePolygon>)> = vec![];
+
+        let stage = qt_node.find_hazard_near(
+            &shape.bbox,
+            irrelevant_hazards,
+            &mut |node_bbox, hz| match &hz.presence {
+                QTHazPresence::None => None,
+                QTHazPresence::Entire => shape
+                    .convex_collides_with_rect(node_bbox)
+                    .then_some(CDStage::QuadtreeEntireRejected),
+                QTHazPresence::Partial(p_haz) => {
+                    let haz_shape = p_haz.shape_arc();
+                    if haz_shape.convex {
+                        if sat_tested.contains(&hz.entity) {
+                            return None;
+                        }
+                        sat_tested.push(hz.entity);
+                        let collides = match hz.entity.position() {
+                            GeoPosition::Exterior => !haz_shape.convex_contains(shape),
+                            GeoPosition::Interior => {
+                                haz_shape.bbox.collides_with(&shape.bbox)
+                                    && haz_shape.convex_collides_with(shape)
+                            }
+                        };
+                        collides.then_some(CDStage::ConvexRejected)
+                    } else {
+                        if !edge_tested.iter().any(|(e, _)| *e == hz.entity) {
+                            edge_tested.push((hz.entity, haz_shape));
+                        }
+                        shape
+                            .edge_iter()
+                            .any(|e| p_haz.collides_with(&e))
+                            .then_some(CDStage::EdgeTestRejected)
+                    }
+                }
+            },
+        );
+
+        stage.unwrap_or_else(|| {
+            //no edges intersect, the non-convex hazards might still contain the shape or be contained by it
+            let contained = edge_tested.iter().any(|(entity, haz_shape)| {
+                self.poly_or_hazard_are_contained(shape, haz_shape, *entity)
+            });
+            match contained {
+                true => CDStage::ContainmentRejected,
+                false => CDStage::ConvexAccepted,
             }
+        })
+    }
+
+    /// Cheap pre-test which can prove that a shape does not collide with any of the (relevant) hazards, using oriented bounding boxes.
+    /// Returns `true` if the (transformed) OBB of the shape lies within all convex exterior hazards
+    /// and is separated from the OBBs of all interior hazards near it in the quadtree.
+    /// `false` means the shape might collide and a full check is still required.
+    pub fn obb_excludes_collision(
+        &self,
+        obb: &OrientedRectangle,
+        irrelevant_hazards: &[HazardEntity],
+    ) -> bool {
+        let obb_bbox = obb.bbox();
+        if self.bbox.relation_to(&obb_bbox) != GeoRelation::Surrounding {
+            return false;
         }
+        let excluded = self
+            .quadtree
+            .find_hazard_near(&obb_bbox, irrelevant_hazards, &mut |node_bbox, hz| {
+                let collides = match (hz.entity.position(), &hz.presence) {
+                    (_, QTHazPresence::None) => false,
+                    //the hazard covers the entire node
+                    (_, QTHazPresence::Entire) => {
+                        OrientedRectangle::from(node_bbox.clone()).collides_with(obb)
+                    }
+                    (GeoPosition::Exterior, QTHazPresence::Partial(p_haz)) => {
+                        let shape = p_haz.shape_arc();
+                        !(shape.convex && shape.convex_contains_points(&obb.corners))
+                    }
+                    (GeoPosition::Interior, QTHazPresence::Partial(p_haz)) => {
+                        let shape = p_haz.shape_arc();
+                        shape.bbox.collides_with(&obb_bbox) && shape.obb.collides_with(obb)
+                    }
+                };
+                collides.then_some(())
+            })
+            .is_none();
+        if excluded {
+            cd_trace::record(CDStage::ObbAccepted);
+        }
+        excluded
+    }
+
+    /// Whether collisions of axis-aligned rectangles are resolved by comparing bounding boxes,
+    /// which is the case as long as all hazards registered in the CDE are axis-aligned rectangles.
+    pub fn rect_mode(&self) -> bool {
+        self.rect_mode
     }
 
     /// Checks whether a surrogate collides with any of the (relevant) hazards.
@@ -334,27 +515,80 @@ impl CDEngine {
         transform: &Transformation,
         irrelevant_hazards: &[HazardEntity],
     ) -> bool {
-        for pole in base_surrogate.ff_poles() {
-            let t_pole = pole.transform_clone(transform);
-            if self
-                .quadtree
-                .collides(&t_pole, irrelevant_hazards)
-                .is_some()
-            {
-                return true;
-            }
-        }
-        for pier in base_surrogate.ff_piers() {
-            let t_pier = pier.transform_clone(transform);
-            if self
-                .quadtree
-                .collides(&t_pier, irrelevant_hazards)
-                .is_some()
-            {
-                return true;
-            }
-        }
-        false
+        let _span = debug_span!("surrogate_collides").entered();
+        let stage = self.explain_surrogate_collision(base_surrogate, transform, irrelevant_hazards);
+        cd_trace::record(stage);
+        stage.collides()
+    }
+
+    /// Resolves the same query as [`Self::surrogate_collides`], returning the stage which decided it.
+    pub fn explain_surrogate_collision(
+        &self,
+        base_surrogate: &SPSurrogate,
+        transform: &Transformation,
+        irrelevant_hazards: &[HazardEntity],
+    ) -> CDStage {
+        surrogate_stage_in(
+            &self.quadtree,
+            base_surrogate,
+            transform,
+            irrelevant_hazards,
+        )
+    }
+
+    /// Checks many transformations of the same shape for collisions, which is how samplers query the CDE.
+    /// Bit `i` of the result is set if the shape transformed by `transforms[i]` collides,
+    /// the same outcome as [`Self::surrogate_or_poly_collides`] for each transformation separately.
+    /// <br>
+    /// Work shared by the queries is done once: the circle around the pole of inaccessibility enclosing the shape,
+    /// the quadtree node enclosing all candidates and the buffer for the transformed shape.
+    /// Each candidate then descends further to the deepest node enclosing it,
+    /// from which the quadtree is queried for all its poles, piers and edges.
+    pub fn collides_any(
+        &self,
+        shape: &SimplePolygon,
+        transforms: &[Transformation],
+        irrelevant_hazards: &[HazardEntity],
+    ) -> BitVec {
+        let _span = debug_span!("collides_any", n_transforms = transforms.len()).entered();
+        //rigid transformations keep the shape within this radius of its (transformed) pole of inaccessibility
+        let reference = shape.poi.center;
+        let radius = shape
+            .points
+            .iter()
+            .map(|p| reference.distance(*p))
+            .fold(0.0, fsize::max);
+        let enclosing_bbox = |transform: &Transformation| {
+            let Point(x, y) = reference.transform_clone(transform);
+            AARectangle::new(x - radius, y - radius, x + radius, y + radius)
+        };
+        let batch_node = transforms
+            .iter()
+            .map(enclosing_bbox)
+            .reduce(|a, b| AARectangle::bounding_rectangle(&a, &b))
+            .map_or(&self.quadtree, |bbox| {
+                self.quadtree.deepest_enclosing(&bbox)
+            });
+
+        let surrogate = shape.surrogate();
+        let mut buffer = shape.clone();
+        buffer.surrogate = None;
+
+        transforms
+            .iter()
+            .map(|transform| {
+                let qt_node = batch_node.deepest_enclosing(&enclosing_bbox(transform));
+                let mut stage =
+                    surrogate_stage_in(qt_node, surrogate, transform, irrelevant_hazards);
+                if !stage.collides() {
+                    cd_trace::record(stage);
+                    buffer.transform_from(shape, transform);
+                    stage = self.explain_poly_collision_in(qt_node, &buffer, irrelevant_hazards);
+                }
+                cd_trace::record(stage);
+                stage.collides()
+            })
+            .collect()
     }
 
     /// Checks whether a point definitely collides with any of the (relevant) hazards.
@@ -394,16 +628,6 @@ impl CDEngine {
         }
     }
 
-    fn poly_collides_by_edge_intersection(
-        &self,
-        shape: &SimplePolygon,
-        irrelevant_hazards: &[HazardEntity],
-    ) -> bool {
-        shape
-            .edge_iter()
-            .any(|e| self.quadtree.collides(&e, irrelevant_hazards).is_some())
-    }
-
     fn poly_collides_by_containment(
         &self,
         shape: &SimplePolygon,
@@ -412,14 +636,18 @@ impl CDEngine {
         //collect all active and non-ignored hazards
         self.all_hazards()
             .filter(|h| h.active && !irrelevant_hazards.contains(&h.entity))
-            .any(|haz| self.poly_or_hazard_are_contained(shape, haz))
+            .any(|haz| self.poly_or_hazard_are_contained(shape, &haz.shape, haz.entity))
     }
 
-    fn poly_or_hazard_are_contained(&self, shape: &SimplePolygon, haz: &Hazard) -> bool {
+    fn poly_or_hazard_are_contained(
+        &self,
+        shape: &SimplePolygon,
+        haz_shape: &SimplePolygon,
+        haz_entity: HazardEntity,
+    ) -> bool {
         //Due to possible fp issues, we check if the bboxes are "almost" related
         //"almost" meaning that, when edges are very close together, they are considered equal.
         //Some relations which would normally be seen as Intersecting are now being considered Enclosed/Surrounding
-        let haz_shape = haz.shape.as_ref();
         let bbox_relation = haz_shape.bbox().almost_relation_to(&shape.bbox());
 
         let (s_mu, s_omega) = match bbox_relation {
@@ -427,7 +655,7 @@ impl CDEngine {
             GeoRelation::Enclosed => (haz_shape, shape),    //inclusion possible
             GeoRelation::Disjoint | GeoRelation::Intersecting => {
                 //no inclusion is possible
-                return match haz.entity.position() {
+                return match haz_entity.position() {
                     GeoPosition::Interior => false,
                     GeoPosition::Exterior => true,
                 };
@@ -439,7 +667,7 @@ impl CDEngine {
             //maybe the quadtree can help us.
             if let Ok(collides) = self
                 .quadtree
-                .definitely_collides_with(&s_mu.poi.center, haz.entity)
+                .definitely_collides_with(&s_mu.poi.center, haz_entity)
                 .try_into()
             {
                 return collides;
@@ -447,7 +675,7 @@ impl CDEngine {
         }
         let inclusion = s_omega.collides_with(&s_mu.poi.center);
 
-        match haz.entity.position() {
+        match haz_entity.position() {
             GeoPosition::Interior => inclusion,
             GeoPosition::Exterior => !inclusion,
         }
@@ -503,7 +731,9 @@ impl CDEngine {
         //collect all colliding entities due to containment
         //TODO: check if gathering the hazards inside the bbox using the quadtree is faster
         self.all_hazards().filter(|h| h.active).for_each(|h| {
-            if !detected.contains(&h.entity) && self.poly_or_hazard_are_contained(shape, h) {
+            if !detected.contains(&h.entity)
+                && self.poly_or_hazard_are_contained(shape, &h.shape, h.entity)
+            {
                 detected.push(h.entity);
             }
         });
@@ -539,3 +769,328 @@ impl CDEngine {
         detected.drain(irrelevant_range);
     }
 }
+
+fn surrogate_stage_in(
+    qt_node: &QTNode,
+    base_surrogate: &SPSurrogate,
+    transform: &Transformation,
+    irrelevant_hazards: &[HazardEntity],
+) -> CDStage {
+    for pole in base_surrogate.ff_poles() {
+        let t_pole = pole.transform_clone(transform);
+        if qt_node.collides(&t_pole, irrelevant_hazards).is_some() {
+            return CDStage::PoleRejected;
+        }
+    }
+    for pier in base_surrogate.ff_piers() {
+        let t_pier = pier.transform_clone(transform);
+        if qt_node.collides(&t_pier, irrelevant_hazards).is_some() {
+            return CDStage::PierRejected;
+        }
+    }
+    CDStage::SurrogateAccepted
+}
+
+/// Fast path of [`CDEngine::poly_collides`] when both the shape and all hazards are axis-aligned rectangles.
+/// Only the hazards present in the quadtree nodes around the rectangle are compared to it, on their bounding boxes.
+/// Touching the exterior of the bin is allowed, touching any other hazard is considered a collision.
+fn rect_collides_in(
+    qt_node: &QTNode,
+    rect: &AARectangle,
+    irrelevant_hazards: &[HazardEntity],
+) -> bool {
+    let overlaps_interior = |a: &AARectangle, b: &AARectangle| {
+        fsize::max(a.x_min, b.x_min) < fsize::min(a.x_max, b.x_max)
+            && fsize::max(a.y_min, b.y_min) < fsize::min(a.y_max, b.y_max)
+    };
+    qt_node
+        .find_hazard_near(rect, irrelevant_hazards, &mut |node_bbox, hz| {
+            let collides = match (hz.entity.position(), &hz.presence) {
+                (_, QTHazPresence::None) => false,
+                //the hazard covers the entire node
+                (GeoPosition::Interior, QTHazPresence::Entire) => node_bbox.collides_with(rect),
+                (GeoPosition::Exterior, QTHazPresence::Entire) => {
+                    overlaps_interior(node_bbox, rect)
+                }
+                (GeoPosition::Interior, QTHazPresence::Partial(p_haz)) => {
+                    p_haz.shape_arc().bbox.collides_with(rect)
+                }
+                (GeoPosition::Exterior, QTHazPresence::Partial(p_haz)) => {
+                    p_haz.shape_arc().bbox.relation_to(rect) != GeoRelation::Surrounding
+                }
+            };
+            collides.then_some(())
+        })
+        .is_some()
+}
+
+fn poly_collides_by_edge_intersection(
+    qt_node: &QTNode,
+    shape: &SimplePolygon,
+    irrelevant_hazards: &[HazardEntity],
+) -> Option<QTCollision> {
+    shape.edge_iter().find_map(|e| {
+        qt_node
+            .collides_by(&e, irrelevant_hazards)
+            .map(|(_, by)| by)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "io")]
+    use itertools::Itertools;
+    use rand::prelude::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::geometry::d_transformation::DTransformation;
+    use crate::util::config::SPSurrogateConfig;
+    #[cfg(feature = "io")]
+    use crate::{
+        entities::instances::instance_generic::InstanceGeneric,
+        entities::problems::problem_generic::ProblemGeneric,
+        io::json_instance::{JsonInstance, JsonItem, JsonShape, JsonStrip},
+        util::test_fixtures,
+        PI,
+    };
+
+    fn rect(x_min: fsize, y_min: fsize, x_max: fsize, y_max: fsize) -> SimplePolygon {
+        SimplePolygon::new(vec![
+            Point(x_min, y_min),
+            Point(x_max, y_min),
+            Point(x_max, y_max),
+            Point(x_min, y_max),
+        ])
+    }
+
+    /// CDE spanning [0, 100]², of which only the left half [0, 50] x [0, 100] is part of the bin
+    fn half_bin_cde() -> CDEngine {
+        let config = CDEConfig {
+            quadtree_depth: 5,
+            hpg_n_cells: 0,
+            item_surrogate_config: SPSurrogateConfig::none(),
+        };
+        let exterior = Hazard::new(
+            HazardEntity::BinExterior,
+            Arc::new(rect(0.0, 0.0, 50.0, 100.0)),
+        );
+        CDEngine::new(
+            AARectangle::new(0.0, 0.0, 100.0, 100.0),
+            vec![exterior],
+            config,
+        )
+    }
+
+    fn register_item(cde: &mut CDEngine, id: usize, shape: SimplePolygon) {
+        let entity = HazardEntity::PlacedItem {
+            id,
+            dt: DTransformation::empty(),
+        };
+        cde.register_hazard(Hazard::new(entity, Arc::new(shape)));
+    }
+
+    #[test]
+    fn test_rect_collides_in_descends_quadtree() {
+        let mut cde = half_bin_cde();
+        //small items deep in the quadtree, far from each other
+        register_item(&mut cde, 0, rect(10.0, 10.0, 12.0, 12.0));
+        register_item(&mut cde, 1, rect(30.0, 70.0, 33.0, 72.0));
+        assert!(cde.rect_mode());
+
+        //the items are only partially present in the root, so the overlaps are only found in its descendants
+        let root = &cde.quadtree;
+        assert!(rect_collides_in(
+            root,
+            &AARectangle::new(11.0, 11.0, 13.0, 13.0),
+            &[]
+        ));
+        assert!(rect_collides_in(
+            root,
+            &AARectangle::new(31.0, 60.0, 32.0, 70.5),
+            &[]
+        ));
+        //touching an item is a collision, being next to it in the same node is not
+        assert!(rect_collides_in(
+            root,
+            &AARectangle::new(12.0, 10.0, 14.0, 12.0),
+            &[]
+        ));
+        assert!(!rect_collides_in(
+            root,
+            &AARectangle::new(12.5, 10.0, 14.0, 12.0),
+            &[]
+        ));
+        //irrelevant hazards are ignored
+        let item_0 = HazardEntity::PlacedItem {
+            id: 0,
+            dt: DTransformation::empty(),
+        };
+        assert!(!rect_collides_in(
+            root,
+            &AARectangle::new(11.0, 11.0, 13.0, 13.0),
+            &[item_0]
+        ));
+
+        //the quadtree agrees with comparing the rectangle to the bounding boxes of all hazards
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let (x, y) = (rng.gen_range(0.0..45.0), rng.gen_range(0.0..95.0));
+            let (w, h) = (rng.gen_range(0.1..5.0), rng.gen_range(0.1..5.0));
+            let query = AARectangle::new(x, y, x + w, y + h);
+            let expected = cde.all_hazards().any(|h| match h.entity.position() {
+                GeoPosition::Exterior => {
+                    h.shape.bbox.relation_to(&query) != GeoRelation::Surrounding
+                }
+                GeoPosition::Interior => h.shape.bbox.collides_with(&query),
+            });
+            assert_eq!(rect_collides_in(root, &query, &[]), expected, "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn test_rect_collides_in_exterior_entire() {
+        let cde = half_bin_cde();
+        let root = &cde.quadtree;
+        //nodes in the right half lie entirely outside the bin
+        assert!(rect_collides_in(
+            root,
+            &AARectangle::new(80.0, 10.0, 90.0, 20.0),
+            &[]
+        ));
+        assert!(rect_collides_in(
+            root,
+            &AARectangle::new(45.0, 10.0, 55.0, 20.0),
+            &[]
+        ));
+        //touching the exterior from inside the bin is allowed
+        assert!(!rect_collides_in(
+            root,
+            &AARectangle::new(40.0, 10.0, 50.0, 20.0),
+            &[]
+        ));
+        assert_eq!(
+            cde.explain_poly_collision(&rect(80.0, 10.0, 90.0, 20.0), &[]),
+            CDStage::RectRejected
+        );
+        assert_eq!(
+            cde.explain_poly_collision(&rect(40.0, 10.0, 50.0, 20.0), &[]),
+            CDStage::RectAccepted
+        );
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_rect_mode() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let items = (0..20)
+            .map(|_| JsonItem {
+                demand: Some(rng.gen_range(1..4)),
+                dxf: None,
+                dxf_layer: None,
+                dxf_block: None,
+                svg: None,
+                svg_id: None,
+                allowed_orientations: Some(vec![0.0, 90.0]),
+                allowed_transformations: None,
+                copies: None,
+                shape: Some(JsonShape::Rectangle {
+                    width: rng.gen_range(5..40) as fsize,
+                    height: rng.gen_range(5..40) as fsize,
+                }),
+                value: None,
+                shortfall_cost: None,
+                base_quality: None,
+                material: None,
+                name: None,
+                surrogate: None,
+            })
+            .collect();
+        let json_instance = JsonInstance {
+            name: "rectangles".to_string(),
+            items,
+            bins: None,
+            strip: Some(JsonStrip { height: 100.0 }),
+            units: None,
+            assets: Default::default(),
+        };
+        let instance = test_fixtures::parser().parse(&json_instance).unwrap();
+        let problem = test_fixtures::random_problem(&instance, &mut rng);
+
+        let layout = &problem.layouts()[0];
+        assert!(layout.cde().rect_mode());
+        assert!(layout.placed_items().len() > 1);
+        //placed rectangles may touch, but not overlap
+        for ((_, pi_1), (_, pi_2)) in layout.placed_items().iter().tuple_combinations() {
+            let (r1, r2) = (pi_1.shape.bbox(), pi_2.shape.bbox());
+            let overlap_x = r1.x_max.min(r2.x_max) - r1.x_min.max(r2.x_min);
+            let overlap_y = r1.y_max.min(r2.y_max) - r1.y_min.max(r2.y_min);
+            assert!(overlap_x <= 1e-3 || overlap_y <= 1e-3);
+        }
+    }
+
+    /// Compares [`CDEngine::collides_any`] to separate queries, in a layout of the instance with random placements
+    #[cfg(feature = "io")]
+    fn check_collides_any(file_name: &str) {
+        let instance = test_fixtures::parse_asset(file_name);
+        let mut rng = SmallRng::seed_from_u64(0);
+        let problem = test_fixtures::random_problem(&instance, &mut rng);
+
+        let layout = &problem.layouts()[0];
+        let bbox = layout.bin.outer.bbox();
+        for (item, _) in instance.items() {
+            let mut buffer = (*item.shape).clone();
+            //candidates spread over the whole bin, and clustered around a single point (as during local search)
+            let (cx, cy) = (
+                rng.gen_range(bbox.x_min..bbox.x_max),
+                rng.gen_range(bbox.y_min..bbox.y_max),
+            );
+            let spread = bbox.width().max(bbox.height()) * 0.05;
+            let transforms = (0..200)
+                .map(|i| {
+                    let (x, y) = match i < 100 {
+                        true => (
+                            rng.gen_range(bbox.x_min..bbox.x_max),
+                            rng.gen_range(bbox.y_min..bbox.y_max),
+                        ),
+                        false => (
+                            cx + rng.gen_range(-spread..spread),
+                            cy + rng.gen_range(-spread..spread),
+                        ),
+                    };
+                    DTransformation::new(rng.gen_range(0.0..2.0 * PI), (x, y)).compose()
+                })
+                .collect_vec();
+
+            let collides = layout.cde().collides_any(&item.shape, &transforms, &[]);
+            assert_eq!(collides.len(), transforms.len());
+            for (transform, collides) in transforms.iter().zip(collides.iter().by_vals()) {
+                let expected = layout.cde().surrogate_or_poly_collides(
+                    &item.shape,
+                    transform,
+                    &mut buffer,
+                    &[],
+                );
+                assert_eq!(collides, expected);
+            }
+        }
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_collides_any_swim() {
+        check_collides_any("swim.json");
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_collides_any_shirts() {
+        check_collides_any("shirts.json");
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_collides_any_baldacci1() {
+        check_collides_any("baldacci1.json");
+    }
+}
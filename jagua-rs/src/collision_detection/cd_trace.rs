@@ -0,0 +1,153 @@
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Whether collision queries are being traced, disabled by default
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Number of collision queries decided by each [`CDStage`], aggregated over all CDEs of the process
+static COUNTERS: [AtomicU64; CDStage::ALL.len()] =
+    [const { AtomicU64::new(0) }; CDStage::ALL.len()];
+
+/// Stage of a collision query in the [`CDEngine`](crate::collision_detection::cd_engine::CDEngine) which decided its outcome.
+/// Queries on surrogates, polygons and oriented bounding boxes each pass through their own stages,
+/// in the order of this enum, until one of them decides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CDStage {
+    /// Surrogate query: one of the fail-fast poles collides
+    PoleRejected,
+    /// Surrogate query: one of the fail-fast piers collides
+    PierRejected,
+    /// Surrogate query: none of the fail-fast poles or piers collide
+    SurrogateAccepted,
+    /// Polygon query: the shape is not contained in the bounding box of the CDE
+    BBoxRejected,
+    /// Polygon query: resolved by comparing bounding boxes (all hazards are axis-aligned rectangles), collision
+    RectRejected,
+    /// Polygon query: resolved by comparing bounding boxes (all hazards are axis-aligned rectangles), no collision
+    RectAccepted,
+    /// Polygon query: a convex shape collides with a convex hazard, resolved with the separating axis theorem
+    ConvexRejected,
+    /// Polygon query: a convex shape collides with none of the hazards around it, convex hazards were tested with the separating axis theorem
+    ConvexAccepted,
+    /// Polygon query: an edge of the shape lies in a quadtree node which is entirely covered by a hazard
+    QuadtreeEntireRejected,
+    /// Polygon query: an edge of the shape intersects an edge of a hazard
+    EdgeTestRejected,
+    /// Polygon query: the shape contains a hazard, is contained by one, or lies outside an exterior hazard
+    ContainmentRejected,
+    /// Polygon query: none of the edge and containment tests detected a collision
+    ExactAccepted,
+    /// OBB pre-test: the oriented bounding box of the shape proves it does not collide
+    ObbAccepted,
+}
+
+impl CDStage {
+    pub const ALL: [CDStage; 13] = [
+        CDStage::PoleRejected,
+        CDStage::PierRejected,
+        CDStage::SurrogateAccepted,
+        CDStage::BBoxRejected,
+        CDStage::RectRejected,
+        CDStage::RectAccepted,
+        CDStage::ConvexRejected,
+        CDStage::ConvexAccepted,
+        CDStage::QuadtreeEntireRejected,
+        CDStage::EdgeTestRejected,
+        CDStage::ContainmentRejected,
+        CDStage::ExactAccepted,
+        CDStage::ObbAccepted,
+    ];
+
+    /// Whether the query was decided as a collision
+    pub fn collides(&self) -> bool {
+        match self {
+            CDStage::PoleRejected
+            | CDStage::PierRejected
+            | CDStage::BBoxRejected
+            | CDStage::RectRejected
+            | CDStage::ConvexRejected
+            | CDStage::QuadtreeEntireRejected
+            | CDStage::EdgeTestRejected
+            | CDStage::ContainmentRejected => true,
+            CDStage::SurrogateAccepted
+            | CDStage::RectAccepted
+            | CDStage::ConvexAccepted
+            | CDStage::ExactAccepted
+            | CDStage::ObbAccepted => false,
+        }
+    }
+}
+
+/// Starts counting the stages which decide collision queries.
+/// Tracing costs an atomic increment per query, and is meant to guide the tuning of the [`CDEConfig`](crate::util::config::CDEConfig).
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Sets all counters back to zero
+pub fn reset() {
+    COUNTERS.iter().for_each(|c| c.store(0, Ordering::Relaxed));
+}
+
+/// Current value of the counters
+pub fn counts() -> CDTrace {
+    CDTrace {
+        counts: COUNTERS.each_ref().map(|c| c.load(Ordering::Relaxed)),
+    }
+}
+
+#[inline(always)]
+pub(crate) fn record(stage: CDStage) {
+    if ENABLED.load(Ordering::Relaxed) {
+        COUNTERS[stage as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Number of collision queries decided by each [`CDStage`] while tracing was enabled
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CDTrace {
+    counts: [u64; CDStage::ALL.len()],
+}
+
+impl CDTrace {
+    pub fn count(&self, stage: CDStage) -> u64 {
+        self.counts[stage as usize]
+    }
+
+    /// Total number of traced queries
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Stages which decided at least one query, with their counts
+    pub fn iter(&self) -> impl Iterator<Item = (CDStage, u64)> + '_ {
+        CDStage::ALL
+            .into_iter()
+            .map(|stage| (stage, self.count(stage)))
+            .filter(|(_, count)| *count > 0)
+    }
+}
+
+impl Display for CDTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = self.total().max(1) as f64;
+        for (stage, count) in self.iter() {
+            writeln!(
+                f,
+                "{:<24} {:>12} ({:5.1}%)",
+                format!("{:?}", stage),
+                count,
+                count as f64 / total * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
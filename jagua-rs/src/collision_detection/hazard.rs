@@ -37,6 +37,8 @@ pub enum HazardEntity {
     BinHole { id: usize },
     /// Represents a zone in the bin with a specific quality level that is inferior to the base quality.
     InferiorQualityZone { quality: usize, id: usize },
+    /// Represents a zone of the bin which is only a hazard during part of the placement sequence, see [`TemporaryZone`](crate::entities::temporary_zone::TemporaryZone)
+    TemporaryZone { id: usize },
 }
 
 impl HazardEntity {
@@ -47,6 +49,7 @@ impl HazardEntity {
             HazardEntity::BinExterior => GeoPosition::Exterior,
             HazardEntity::BinHole { .. } => GeoPosition::Interior,
             HazardEntity::InferiorQualityZone { .. } => GeoPosition::Interior,
+            HazardEntity::TemporaryZone { .. } => GeoPosition::Interior,
         }
     }
 
@@ -57,6 +60,7 @@ impl HazardEntity {
             HazardEntity::BinExterior => false,
             HazardEntity::BinHole { .. } => false,
             HazardEntity::InferiorQualityZone { .. } => false,
+            HazardEntity::TemporaryZone { .. } => true,
         }
     }
 
@@ -67,6 +71,7 @@ impl HazardEntity {
             HazardEntity::BinExterior => true,
             HazardEntity::BinHole { .. } => true,
             HazardEntity::InferiorQualityZone { .. } => false,
+            HazardEntity::TemporaryZone { .. } => true,
         }
     }
 }
@@ -45,6 +45,7 @@ impl HazardFilter for BinHazardFilter {
             HazardEntity::BinExterior => true,
             HazardEntity::BinHole { .. } => true,
             HazardEntity::InferiorQualityZone { .. } => true,
+            HazardEntity::TemporaryZone { .. } => true,
         }
     }
 }
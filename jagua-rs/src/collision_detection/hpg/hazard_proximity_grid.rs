@@ -2,6 +2,7 @@ use std::fmt::{Display, Formatter};
 use std::iter;
 
 use itertools::Itertools;
+use ordered_float::NotNan;
 
 use crate::collision_detection::hazard::Hazard;
 use crate::collision_detection::hazard::HazardEntity;
@@ -13,7 +14,9 @@ use crate::fsize;
 use crate::geometry::geo_enums::GeoPosition;
 use crate::geometry::geo_traits::Shape;
 use crate::geometry::primitives::aa_rectangle::AARectangle;
-use crate::util::assertions;
+use crate::geometry::primitives::point::Point;
+use crate::util::assertions::{self, debug_assert_with_svg};
+use crate::util::debug_svg;
 
 /// Grid of cells which store information about hazards in their vicinity.
 /// The grid is a part of the CDE and is thus automatically updated when hazards are registered or deregistered.
@@ -103,10 +106,15 @@ impl HazardProximityGrid {
                 }
             }
         }
-        debug_assert!(assertions::hpg_update_no_affected_cells_remain(
-            to_register,
-            self,
-        ));
+        debug_assert_with_svg!(
+            assertions::hpg_update_no_affected_cells_remain(to_register, self),
+            debug_svg::write_svg(
+                &self.bbox,
+                iter::empty(),
+                iter::empty(),
+                Some(&to_register.shape)
+            )
+        );
     }
 
     pub fn deregister_hazard<'a, I>(
@@ -144,6 +152,97 @@ impl HazardProximityGrid {
     pub fn is_dirty(&self) -> bool {
         !self.uncommitted_deregisters.is_empty()
     }
+
+    /// Iterator over all cells which could accommodate a circle with the given radius (see [`HPGCell::could_accommodate`]).
+    pub fn eligible_cells(
+        &self,
+        radius: fsize,
+        quality_level: Option<usize>,
+    ) -> impl Iterator<Item = &HPGCell> {
+        self.grid
+            .cells
+            .iter()
+            .flatten()
+            .filter(move |cell| cell.could_accommodate(radius, quality_level))
+    }
+
+    /// Same as [`Self::eligible_cells`], but also yields the index of each cell in the grid.
+    pub fn eligible_cells_indexed(
+        &self,
+        radius: fsize,
+        quality_level: Option<usize>,
+    ) -> impl Iterator<Item = (usize, &HPGCell)> {
+        self.grid
+            .cells
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cell)| cell.as_ref().map(|cell| (i, cell)))
+            .filter(move |(_, cell)| cell.could_accommodate(radius, quality_level))
+    }
+
+    /// Centroids of all cells which could accommodate a circle with the given radius,
+    /// in order of increasing score as determined by `score`.
+    /// <br>
+    /// For example, `|cell| cell.centroid.0` yields candidate positions from left to right.
+    pub fn candidate_positions<F>(
+        &self,
+        radius: fsize,
+        quality_level: Option<usize>,
+        score: F,
+    ) -> impl Iterator<Item = Point>
+    where
+        F: Fn(&HPGCell) -> fsize,
+    {
+        self.eligible_cells(radius, quality_level)
+            .map(|cell| (cell.centroid, score(cell)))
+            .sorted_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).expect("score is NaN"))
+            .map(|(centroid, _)| centroid)
+    }
+
+    /// Returns the proximity of the closest relevant hazard at an arbitrary point,
+    /// interpolated bilinearly between the centroids of the four surrounding cells.
+    /// Points beyond the outermost centroids are clamped to the edge of the grid.
+    /// Cells which are not part of the grid (completely outside the bin) count as a proximity of zero.
+    /// <br>
+    /// Allows samplers to bias candidate positions towards regions with enough clearance,
+    /// for example by comparing the value against the radius of an item's pole of inaccessibility.
+    pub fn hazard_proximity_at(&self, point: Point, quality_level: Option<usize>) -> fsize {
+        let Point(x, y) = point;
+        let (row, ty) = interpolation_interval(&self.grid.rows, y);
+        let (col, tx) = interpolation_interval(&self.grid.cols, x);
+
+        let prox = |row: usize, col: usize| {
+            let idx = self.grid.to_index(row, col).expect("index out of bounds");
+            self.grid.cells[idx]
+                .as_ref()
+                .map_or(0.0, |cell| cell.hazard_proximity(quality_level))
+        };
+        let (next_row, next_col) = (
+            usize::min(row + 1, self.grid.n_rows - 1),
+            usize::min(col + 1, self.grid.n_cols - 1),
+        );
+
+        let bottom = prox(row, col) * (1.0 - tx) + prox(row, next_col) * tx;
+        let top = prox(next_row, col) * (1.0 - tx) + prox(next_row, next_col) * tx;
+        bottom * (1.0 - ty) + top * ty
+    }
+}
+
+/// Index of the coordinate at the start of the interval containing `value`, together with the relative position inside the interval.
+/// Values outside the range of coordinates are clamped.
+fn interpolation_interval(coords: &[NotNan<fsize>], value: fsize) -> (usize, fsize) {
+    let n = coords.len();
+    let value = NotNan::new(value).expect("value is NaN");
+    if n == 1 || value <= coords[0] {
+        return (0, 0.0);
+    }
+    if value >= coords[n - 1] {
+        return (n - 1, 0.0);
+    }
+    //index of the first coordinate larger than the value
+    let upper = coords.partition_point(|c| *c <= value);
+    let (start, end) = (coords[upper - 1], coords[upper]);
+    (upper - 1, ((value - start) / (end - start)).into_inner())
 }
 
 /// Error type for when the `HazardProximityGrid` is in a dirty state.
@@ -39,11 +39,7 @@ impl HPGCell {
         let mut qz_prox = [fsize::MAX; N_QUALITIES];
 
         for hazard in static_hazards {
-            let (pos, distance) = hazard.shape.distance_from_border(&centroid);
-            let prox = match pos == hazard.entity.position() {
-                true => 0.0, //cells centroid is inside the hazard
-                false => distance,
-            };
+            let prox = exact_proximity(&centroid, hazard);
             match &hazard.entity {
                 HazardEntity::BinExterior | HazardEntity::BinHole { .. } => {
                     if prox < static_uni_prox.0 {
@@ -216,18 +212,67 @@ impl HPGCell {
     }
 
     pub fn could_accommodate_item(&self, item: &Item) -> bool {
-        let poi_d = item.shape.poi.radius;
-        if self.radius > poi_d {
-            //impossible to give any guarantees if the cell radius is larger than the Item's POI
+        self.could_accommodate(item.shape.poi.radius, item.base_quality)
+    }
+
+    /// Returns false if a circle with the given radius, centered anywhere in the cell, is guaranteed to collide with a relevant hazard
+    pub fn could_accommodate(&self, radius: fsize, quality_level: Option<usize>) -> bool {
+        if self.radius > radius {
+            //impossible to give any guarantees if the cell radius is larger than the circle
             true
         } else {
             //distance of closest relevant hazard
-            let haz_prox = self.hazard_proximity(item.base_quality);
+            let haz_prox = self.hazard_proximity(quality_level);
 
-            poi_d < haz_prox + self.radius
+            radius < haz_prox + self.radius
         }
     }
 
+    /// Proximity of the closest universal hazard (bin exterior, hole or placed item) and the hazard itself, zero if the centroid is inside it
+    pub fn universal_proximity(&self) -> (fsize, HazardEntity) {
+        self.uni_prox
+    }
+
+    /// Proximity of the closest quality zone of the given quality, zero if the centroid is inside it.
+    /// `None` if the bin has no zone of that quality.
+    pub fn quality_zone_proximity(&self, quality: usize) -> Option<fsize> {
+        self.qz_prox
+            .get(quality)
+            .copied()
+            .filter(|&prox| prox != fsize::MAX)
+    }
+
+    /// Proximity of the closest zone of every quality the bin has zones of, as (quality, proximity), in increasing order of quality
+    pub fn quality_zone_proximities(&self) -> impl Iterator<Item = (usize, fsize)> + '_ {
+        (0..N_QUALITIES).filter_map(|q| self.quality_zone_proximity(q).map(|prox| (q, prox)))
+    }
+
+    /// Up to `n` of the `hazards` closest to the centroid of the cell, with their proximity (zero if the centroid is inside), closest first.
+    /// The cell only keeps track of the closest hazard, the others are measured on demand, e.g. from [`CDEngine::all_hazards`](crate::collision_detection::cd_engine::CDEngine::all_hazards).
+    /// Proximities are measured as the grid does: exactly for the bin and its zones, to the surrogate poles for placed items (an overestimation).
+    /// Inactive hazards are ignored.
+    pub fn nearest_hazards<'a>(
+        &self,
+        hazards: impl IntoIterator<Item = &'a Hazard>,
+        n: usize,
+    ) -> Vec<(HazardEntity, fsize)> {
+        hazards
+            .into_iter()
+            .filter(|haz| haz.active)
+            .map(|haz| {
+                let prox = match haz.entity {
+                    HazardEntity::PlacedItem { .. } => {
+                        distance_to_surrogate_poles_border(self, &haz.shape.surrogate().poles)
+                    }
+                    _ => exact_proximity(&self.centroid, haz),
+                };
+                (haz.entity, prox)
+            })
+            .sorted_by(|(_, p1), (_, p2)| p1.total_cmp(p2))
+            .take(n)
+            .collect()
+    }
+
     pub fn hazard_proximity(&self, quality_level: Option<usize>) -> fsize {
         //calculate the minimum distance to either bin, item or qz
         let mut haz_prox = self.uni_prox.0;
@@ -243,6 +288,15 @@ impl HPGCell {
     }
 }
 
+/// Distance from the point to the border of the hazard, zero if the point is inside the hazard
+fn exact_proximity(point: &Point, hazard: &Hazard) -> fsize {
+    let (pos, distance) = hazard.shape.distance_from_border(point);
+    match pos == hazard.entity.position() {
+        true => 0.0, //the point is inside the hazard
+        false => distance,
+    }
+}
+
 pub fn distance_to_surrogate_poles_border(hp_cell: &HPGCell, poles: &[Circle]) -> fsize {
     poles
         .iter()
@@ -265,3 +319,65 @@ pub enum HPGCellUpdate {
     ///Update did not affect the cell and its neighbors are also guaranteed to be unaffected
     NeighborsNotAffected,
 }
+
+#[cfg(all(test, feature = "io"))]
+mod tests {
+    use rand::prelude::SmallRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::entities::layout::Layout;
+    use crate::entities::problems::problem_generic::ProblemGeneric;
+    use crate::util::test_fixtures;
+
+    #[test]
+    fn test_hpg_cell_queries() {
+        let instance = test_fixtures::parse_asset("baldacci1.json");
+        let solution = test_fixtures::random_problem(&instance, &mut SmallRng::seed_from_u64(0))
+            .create_solution(None);
+        let layout = Layout::from_snapshot(&solution.layout_snapshots[0]);
+        let cde = layout.cde();
+        let hpg = cde.haz_prox_grid().unwrap();
+        let n_hazards = cde.all_hazards().filter(|h| h.active).count();
+        let zone_qualities = layout
+            .bin
+            .quality_zones
+            .iter()
+            .flatten()
+            .filter(|qz| !qz.zones.is_empty())
+            .map(|qz| qz.quality)
+            .collect_vec();
+        assert!(!zone_qualities.is_empty());
+
+        for cell in hpg.grid.cells.iter().flatten() {
+            let nearest = cell.nearest_hazards(cde.all_hazards(), usize::MAX);
+            assert_eq!(nearest.len(), n_hazards);
+            assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+            assert_eq!(cell.nearest_hazards(cde.all_hazards(), 3), nearest[..3]);
+
+            //the closest universal hazard is the one tracked by the cell
+            let (uni_prox, _) = cell.universal_proximity();
+            let closest_universal = nearest
+                .iter()
+                .find(|(entity, _)| entity.is_universal())
+                .unwrap();
+            assert!(almost::equal_with(closest_universal.1, uni_prox, 1e-3));
+
+            for quality in 0..N_QUALITIES {
+                let closest_zone = nearest.iter().find(|(entity, _)| {
+                    matches!(entity, HazardEntity::InferiorQualityZone { quality: q, .. } if *q == quality)
+                });
+                assert_eq!(
+                    cell.quality_zone_proximity(quality),
+                    closest_zone.map(|(_, prox)| *prox)
+                );
+            }
+            assert_eq!(
+                cell.quality_zone_proximities()
+                    .map(|(q, _)| q)
+                    .collect_vec(),
+                zone_qualities
+            );
+        }
+    }
+}
@@ -1,5 +1,5 @@
-pub mod boundary_fill;
-pub mod grid;
-pub mod grid_generator;
+pub(crate) mod boundary_fill;
+pub(crate) mod grid;
+pub(crate) mod grid_generator;
 pub mod hazard_proximity_grid;
-pub mod hpg_cell;
+pub(crate) mod hpg_cell;
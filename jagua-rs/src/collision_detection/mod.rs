@@ -1,5 +1,7 @@
 /// Collision detection engine itself
 pub mod cd_engine;
+/// Opt-in tracing of the stages which decide collision queries
+pub mod cd_trace;
 pub mod hazard;
 pub mod hazard_filter;
 
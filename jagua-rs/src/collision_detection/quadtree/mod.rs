@@ -1,5 +1,6 @@
-pub mod qt_hazard;
+pub(crate) mod qt_hazard;
 mod qt_hazard_vec;
-pub mod qt_node;
-pub mod qt_partial_hazard;
-pub mod qt_traits;
+pub(crate) mod qt_node;
+pub(crate) mod qt_partial_hazard;
+pub mod qt_stats;
+pub(crate) mod qt_traits;
@@ -25,7 +25,7 @@ pub struct QTHazard {
 pub enum QTHazPresence {
     /// The hazard is entirely absent from the node
     None,
-    /// The hazard is present in the node, but only partially, defined by a [PartialQTHaz]
+    /// The hazard is present in the node, but only partially, defined by a `PartialQTHaz`
     Partial(PartialQTHaz),
     /// The hazard is entirely present in the node
     Entire,
@@ -176,9 +176,9 @@ impl QTHazard {
     }
 }
 
-impl Into<u8> for &QTHazPresence {
-    fn into(self) -> u8 {
-        match self {
+impl From<&QTHazPresence> for u8 {
+    fn from(val: &QTHazPresence) -> Self {
+        match val {
             QTHazPresence::None => 0,
             QTHazPresence::Partial(_) => 1,
             QTHazPresence::Entire => 2,
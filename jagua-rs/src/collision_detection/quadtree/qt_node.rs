@@ -4,10 +4,20 @@ use crate::collision_detection::hazard::HazardEntity;
 use crate::collision_detection::quadtree::qt_hazard::QTHazPresence;
 use crate::collision_detection::quadtree::qt_hazard::QTHazard;
 use crate::collision_detection::quadtree::qt_hazard_vec::QTHazardVec;
+use crate::collision_detection::quadtree::qt_stats::QTStats;
 use crate::collision_detection::quadtree::qt_traits::QTQueryable;
 use crate::geometry::geo_traits::CollidesWith;
 use crate::geometry::primitives::aa_rectangle::AARectangle;
 
+/// How a collision with a hazard was detected in the quadtree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QTCollision {
+    /// The entity lies (partly) in a node which is entirely covered by the hazard
+    Entire,
+    /// The entity collides with one of the edges of a hazard which is partially present in a leaf node
+    Partial,
+}
+
 /// A node in the quadtree
 #[derive(Clone, Debug)]
 pub struct QTNode {
@@ -123,6 +133,11 @@ impl QTNode {
         self.children.is_some()
     }
 
+    /// Statistics on the structure of the tree rooted at this node
+    pub fn stats(&self) -> QTStats {
+        QTStats::from_root(self)
+    }
+
     /// Used to detect collisions in a binary fashion: either there is a collision or there isn't.
     /// Returns `None` if no collision between the entity and any hazard is detected,
     /// otherwise the first encountered hazard that collides with the entity is returned.
@@ -131,6 +146,19 @@ impl QTNode {
         entity: &T,
         irrelevant_hazards: &[HazardEntity],
     ) -> Option<&HazardEntity>
+    where
+        T: QTQueryable,
+    {
+        self.collides_by(entity, irrelevant_hazards)
+            .map(|(entity, _)| entity)
+    }
+
+    /// Same as [`Self::collides`], but also returns how the collision was detected.
+    pub fn collides_by<T>(
+        &self,
+        entity: &T,
+        irrelevant_hazards: &[HazardEntity],
+    ) -> Option<(&HazardEntity, QTCollision)>
     where
         T: QTQueryable,
     {
@@ -140,13 +168,13 @@ impl QTNode {
                 false => None,
                 true => match strongest_hazard.presence {
                     QTHazPresence::None => None,
-                    QTHazPresence::Entire => Some(&strongest_hazard.entity),
+                    QTHazPresence::Entire => Some((&strongest_hazard.entity, QTCollision::Entire)),
                     QTHazPresence::Partial(_) => match &self.children {
                         Some(children) => {
                             //Check if any of the children intersect with the entity
                             children
                                 .iter()
-                                .map(|child| child.collides(entity, irrelevant_hazards))
+                                .map(|child| child.collides_by(entity, irrelevant_hazards))
                                 .find(|x| x.is_some())
                                 .flatten()
                         }
@@ -166,7 +194,7 @@ impl QTNode {
                                     }
                                     QTHazPresence::Partial(p_haz) => p_haz.collides_with(entity),
                                 })
-                                .map(|hz| &hz.entity)
+                                .map(|hz| (&hz.entity, QTCollision::Partial))
                         }
                     },
                 },
@@ -174,6 +202,52 @@ impl QTNode {
         }
     }
 
+    /// Deepest descendant whose bounding box strictly contains `bbox`, or self if none of the children does.
+    /// Queries on entities within `bbox` give the same result when started from this node instead of the root,
+    /// since they cannot touch any node outside its subtree.
+    pub fn deepest_enclosing(&self, bbox: &AARectangle) -> &QTNode {
+        let encloses = |node: &QTNode| {
+            node.bbox.x_min < bbox.x_min
+                && node.bbox.y_min < bbox.y_min
+                && node.bbox.x_max > bbox.x_max
+                && node.bbox.y_max > bbox.y_max
+        };
+        match &self.children {
+            Some(children) => match children.iter().find(|c| encloses(c)) {
+                Some(child) => child.deepest_enclosing(bbox),
+                None => self,
+            },
+            None => self,
+        }
+    }
+
+    /// Visits the hazards present in the nodes overlapping `bbox`, and returns the first value `f` returns for any of them.
+    /// Nodes in which the strongest hazard is partially present are descended into, so `f` is called with the relevant active hazards
+    /// of the deepest nodes overlapping `bbox`, together with the bounding box of that node.
+    /// A hazard present in several of these nodes is visited once for each of them.
+    pub fn find_hazard_near<T>(
+        &self,
+        bbox: &AARectangle,
+        irrelevant_hazards: &[HazardEntity],
+        f: &mut impl FnMut(&AARectangle, &QTHazard) -> Option<T>,
+    ) -> Option<T> {
+        if !bbox.collides_with(&self.bbox) {
+            return None;
+        }
+        let strongest_hazard = self.hazards.strongest(irrelevant_hazards)?;
+        match (&strongest_hazard.presence, &self.children) {
+            (QTHazPresence::Partial(_), Some(children)) => children
+                .iter()
+                .find_map(|child| child.find_hazard_near(bbox, irrelevant_hazards, f)),
+            _ => self
+                .hazards
+                .active_hazards()
+                .iter()
+                .filter(|hz| !irrelevant_hazards.contains(&hz.entity))
+                .find_map(|hz| f(&self.bbox, hz)),
+        }
+    }
+
     /// Gathers all hazards that collide with the entity and stores them in the `detected` vector.
     /// All hazards already present in the `detected` vector are ignored.
     pub fn collect_collisions<T>(&self, entity: &T, detected: &mut Vec<HazardEntity>)
@@ -0,0 +1,104 @@
+use std::mem::size_of;
+
+use crate::collision_detection::quadtree::qt_hazard::{QTHazPresence, QTHazard};
+use crate::collision_detection::quadtree::qt_node::QTNode;
+use crate::collision_detection::quadtree::qt_partial_hazard::RelevantEdges;
+use crate::fsize;
+
+/// Statistics on the structure of a quadtree, see [`QTNode::stats`].
+/// Useful to judge whether the `quadtree_depth` of the [`CDEConfig`](crate::util::config::CDEConfig) suits an instance.
+/// <br>
+/// A tree with many saturated leaves is under-resolving: its leaves at the maximum depth still contain edges of hazards,
+/// which have to be checked one by one. A tree that never reaches its maximum depth, or has many empty leaves, is over-resolving.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QTStats {
+    /// Total number of nodes, including the root
+    pub n_nodes: usize,
+    /// Number of nodes without children
+    pub n_leaves: usize,
+    /// Number of nodes at each depth, index 0 being the root
+    pub depth_histogram: Vec<usize>,
+    /// Number of leaves with a given number of active hazards, index 0 being the empty leaves
+    pub leaf_hazard_histogram: Vec<usize>,
+    /// Number of leaves at the maximum depth in which a hazard is only partially present
+    pub n_saturated_leaves: usize,
+    /// Estimate of the memory used by the tree, in bytes
+    pub memory_estimate: usize,
+}
+
+impl QTStats {
+    pub fn from_root(root: &QTNode) -> Self {
+        let mut stats = QTStats {
+            depth_histogram: vec![0; root.level as usize + 1],
+            ..QTStats::default()
+        };
+        stats.visit(root, 0);
+        stats
+    }
+
+    fn visit(&mut self, node: &QTNode, depth: usize) {
+        self.n_nodes += 1;
+        self.depth_histogram[depth] += 1;
+        self.memory_estimate +=
+            size_of::<QTNode>() + node_hazards_memory(node.hazards.all_hazards());
+
+        match &node.children {
+            Some(children) => children.iter().for_each(|c| self.visit(c, depth + 1)),
+            None => {
+                let active = node.hazards.active_hazards();
+                if self.leaf_hazard_histogram.len() <= active.len() {
+                    self.leaf_hazard_histogram.resize(active.len() + 1, 0);
+                }
+                self.leaf_hazard_histogram[active.len()] += 1;
+                self.n_leaves += 1;
+                if node.level == 0
+                    && active
+                        .iter()
+                        .any(|h| matches!(h.presence, QTHazPresence::Partial(_)))
+                {
+                    self.n_saturated_leaves += 1;
+                }
+            }
+        }
+    }
+
+    /// Deepest level of the tree containing nodes, 0 if the root has no children
+    pub fn max_depth(&self) -> usize {
+        self.depth_histogram
+            .iter()
+            .rposition(|n| *n > 0)
+            .unwrap_or(0)
+    }
+
+    /// Average number of active hazards in a leaf
+    pub fn mean_hazards_per_leaf(&self) -> fsize {
+        let total = self
+            .leaf_hazard_histogram
+            .iter()
+            .enumerate()
+            .map(|(n_hazards, n_leaves)| n_hazards * n_leaves)
+            .sum::<usize>();
+        total as fsize / self.n_leaves as fsize
+    }
+
+    /// Largest number of active hazards in a single leaf
+    pub fn max_hazards_per_leaf(&self) -> usize {
+        self.leaf_hazard_histogram.len().saturating_sub(1)
+    }
+}
+
+fn node_hazards_memory(hazards: &[QTHazard]) -> usize {
+    hazards
+        .iter()
+        .map(|h| {
+            let edges = match &h.presence {
+                QTHazPresence::Partial(p) => match &p.edges {
+                    RelevantEdges::Some(indices) => indices.len() * size_of::<usize>(),
+                    RelevantEdges::All => 0,
+                },
+                _ => 0,
+            };
+            size_of::<QTHazard>() + edges
+        })
+        .sum()
+}
@@ -5,8 +5,10 @@ use itertools::Itertools;
 use crate::collision_detection::cd_engine::CDEngine;
 use crate::collision_detection::hazard::Hazard;
 use crate::collision_detection::hazard::HazardEntity;
+use crate::entities::item::Item;
 use crate::entities::quality_zone::InferiorQualityZone;
 use crate::entities::quality_zone::N_QUALITIES;
+use crate::entities::temporary_zone::TemporaryZone;
 use crate::fsize;
 use crate::geometry::geo_traits::Shape;
 use crate::geometry::primitives::aa_rectangle::AARectangle;
@@ -18,6 +20,15 @@ use crate::util::config::CDEConfig;
 #[derive(Clone, Debug)]
 pub struct Bin {
     pub id: usize,
+    /// Name of the bin as given in the input, if any
+    pub name: Option<String>,
+    /// Id of the bin of which this bin is a rotated variant, and with which it shares its stock.
+    /// `None` for the bins of the input
+    pub variant_of: Option<usize>,
+    /// Maximum usage of the bin, the fraction of its area which can be covered by items. `None` if unrestricted
+    pub max_usage: Option<fsize>,
+    /// Material of the bin, `None` if it only accepts items without a material
+    pub material: Option<String>,
     /// The contour of the bin
     pub outer: Arc<SimplePolygon>,
     /// The cost of using the bin
@@ -28,9 +39,15 @@ pub struct Bin {
     pub holes: Vec<Arc<SimplePolygon>>,
     /// Zones of different qualities in the bin, stored per quality.
     pub quality_zones: [Option<InferiorQualityZone>; N_QUALITIES],
+    /// Zones which are only hazards during part of the placement sequence, see [`TemporaryZone`]
+    pub temporary_zones: Vec<TemporaryZone>,
     /// The starting state of the `CDEngine` for this bin.
     pub base_cde: Arc<CDEngine>,
+    /// Area of the (simplified) contour, minus the area of the holes
     pub area: fsize,
+    /// Area of the bin as defined in the input, before simplification.
+    /// Equal to `area` for bins which are not created by the parser.
+    pub original_area: fsize,
 }
 
 impl Bin {
@@ -74,16 +91,44 @@ impl Bin {
 
         Self {
             id,
+            name: None,
+            variant_of: None,
+            max_usage: None,
+            material: None,
             outer,
             value,
             pretransform,
             holes,
             quality_zones,
+            temporary_zones: vec![],
             base_cde,
             area,
+            original_area: area,
         }
     }
 
+    /// Id of the bin of the input this bin originates from, see `variant_of`
+    pub fn base_id(&self) -> usize {
+        self.variant_of.unwrap_or(self.id)
+    }
+
+    /// Whether the item can be placed in the bin: items without a material fit in every bin,
+    /// items with a material only in bins of the same material.
+    pub fn accepts(&self, item: &Item) -> bool {
+        item.material.is_none() || item.material == self.material
+    }
+
+    /// Area of the bin used for collision detection, see `area`
+    pub fn simplified_area(&self) -> fsize {
+        self.area
+    }
+
+    /// Difference between the simplified and the original area.
+    /// Negative, because simplification can only shrink bins.
+    pub fn area_delta(&self) -> fsize {
+        self.simplified_area() - self.original_area
+    }
+
     /// Create a new `Bin` for a strip-packing problem. Instead of a shape, the bin is always rectangular.
     pub fn from_strip(rect: AARectangle, cde_config: CDEConfig) -> Self {
         let id = 0;
@@ -99,6 +144,20 @@ impl Bin {
     pub fn bbox(&self) -> AARectangle {
         self.outer.bbox()
     }
+
+    /// Copy of the bin with its base `CDEngine` rebuilt according to another configuration
+    pub fn with_cde_config(&self, cde_config: CDEConfig) -> Self {
+        let bin_hazards = generate_bin_hazards(&self.outer, &self.holes, &self.quality_zones);
+        let base_cde = CDEngine::new(
+            self.outer.bbox().inflate_to_square(),
+            bin_hazards,
+            cde_config,
+        );
+        Self {
+            base_cde: Arc::new(base_cde),
+            ..self.clone()
+        }
+    }
 }
 
 fn generate_bin_hazards(
@@ -4,6 +4,7 @@ use crate::entities::item::Item;
 use crate::fsize;
 use crate::geometry::geo_traits::Shape;
 use crate::util::assertions;
+use crate::util::units::Units;
 
 /// Bin-packing problem instance: a set of items to be packed into a set of bins.
 /// The items are to be packed in such a way that the total cost of the bins used is minimized.
@@ -15,6 +16,8 @@ pub struct BPInstance {
     pub item_area: fsize,
     /// Set of bins available to pack the items, along with their quantities
     pub bins: Vec<(Bin, usize)>,
+    /// Units of the input, see [`Units`]
+    pub units: Option<Units>,
 }
 
 impl BPInstance {
@@ -30,10 +33,22 @@ impl BPInstance {
             items,
             item_area,
             bins,
+            units: None,
         }
     }
 }
 
+impl BPInstance {
+    /// Ids of the bins which share their stock with the bin (including itself): the bin of the input and its rotated variants
+    pub fn bins_sharing_stock(&self, bin_id: usize) -> impl Iterator<Item = usize> + '_ {
+        let base_id = self.bins[bin_id].0.base_id();
+        self.bins
+            .iter()
+            .filter(move |(bin, _)| bin.base_id() == base_id)
+            .map(|(bin, _)| bin.id)
+    }
+}
+
 impl InstanceGeneric for BPInstance {
     fn items(&self) -> &[(Item, usize)] {
         &self.items
@@ -42,4 +57,8 @@ impl InstanceGeneric for BPInstance {
     fn item_area(&self) -> fsize {
         self.item_area
     }
+
+    fn units(&self) -> Option<Units> {
+        self.units
+    }
 }
@@ -3,6 +3,8 @@ use crate::entities::instances::instance_generic::InstanceGeneric;
 use crate::entities::instances::strip_packing::SPInstance;
 use crate::entities::item::Item;
 use crate::fsize;
+use crate::util::config::CDEConfig;
+use crate::util::units::Units;
 
 /// An `Instance` is the static (unmodifiable) representation of a problem instance.
 /// This enum contains all variants of an instance.
@@ -13,6 +15,28 @@ pub enum Instance {
     BP(BPInstance),
 }
 
+impl Instance {
+    /// Copy of the instance in which the base `CDEngine` of every bin is rebuilt according to another configuration.
+    /// The surrogates of the items are left unchanged. Strip-packing instances have no bins,
+    /// the `CDEngine` of the strip is built by the problem.
+    pub fn with_cde_config(&self, cde_config: CDEConfig) -> Instance {
+        match self {
+            Instance::SP(instance) => Instance::SP(instance.clone()),
+            Instance::BP(instance) => {
+                let bins = instance
+                    .bins
+                    .iter()
+                    .map(|(bin, qty)| (bin.with_cde_config(cde_config), *qty))
+                    .collect();
+                Instance::BP(BPInstance {
+                    units: instance.units,
+                    ..BPInstance::new(instance.items.clone(), bins)
+                })
+            }
+        }
+    }
+}
+
 impl InstanceGeneric for Instance {
     fn items(&self) -> &[(Item, usize)] {
         match self {
@@ -27,6 +51,13 @@ impl InstanceGeneric for Instance {
             Instance::BP(instance) => instance.item_area(),
         }
     }
+
+    fn units(&self) -> Option<Units> {
+        match self {
+            Instance::SP(instance) => instance.units(),
+            Instance::BP(instance) => instance.units(),
+        }
+    }
 }
 
 impl From<SPInstance> for Instance {
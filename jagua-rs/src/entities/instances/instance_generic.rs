@@ -1,5 +1,6 @@
 use crate::entities::item::Item;
 use crate::fsize;
+use crate::util::units::Units;
 
 /// Trait for shared functionality of all instance variants.
 pub trait InstanceGeneric {
@@ -14,4 +15,10 @@ pub trait InstanceGeneric {
         self.items().iter().map(|(_, qty)| qty).sum()
     }
     fn item_area(&self) -> fsize;
+    /// Units of the input the instance was parsed from, in which its solutions are expressed
+    fn units(&self) -> Option<Units>;
+    /// Internal length of one unit of the input, see [`Units`]
+    fn unit_scale(&self) -> fsize {
+        Units::scale(self.units())
+    }
 }
@@ -3,6 +3,7 @@ use crate::entities::item::Item;
 use crate::fsize;
 use crate::geometry::geo_traits::Shape;
 use crate::util::assertions;
+use crate::util::units::Units;
 
 /// Strip-packing problem instance: a set of items to be packed into a single strip.
 /// The items are to be packed in such a way that the total width of the strip used is minimized.
@@ -14,6 +15,8 @@ pub struct SPInstance {
     pub item_area: fsize,
     /// The (fixed) height of the strip
     pub strip_height: fsize,
+    /// Units of the input, see [`Units`]
+    pub units: Option<Units>,
 }
 
 impl SPInstance {
@@ -29,6 +32,7 @@ impl SPInstance {
             items,
             item_area,
             strip_height,
+            units: None,
         }
     }
 }
@@ -41,4 +45,8 @@ impl InstanceGeneric for SPInstance {
     fn item_area(&self) -> fsize {
         self.item_area
     }
+
+    fn units(&self) -> Option<Units> {
+        self.units
+    }
 }
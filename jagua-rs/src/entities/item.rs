@@ -1,28 +1,51 @@
 use std::sync::Arc;
 
 use crate::collision_detection::hazard_filter::QZHazardFilter;
+use crate::entities::item_copy::CopyAttributes;
+use crate::fsize;
 use crate::geometry::geo_enums::AllowedRotation;
+use crate::geometry::geo_traits::Shape;
+use crate::geometry::primitives::arc_polygon::ArcPolygon;
 use crate::geometry::primitives::simple_polygon::SimplePolygon;
 use crate::geometry::transformation::Transformation;
 use crate::util::config::SPSurrogateConfig;
+use crate::PI;
 
 /// Item to be placed in a Layout
 #[derive(Clone, Debug)]
 pub struct Item {
     pub id: usize,
+    /// Name of the item as given in the input, if any
+    pub name: Option<String>,
     /// Contour of the item
     pub shape: Arc<SimplePolygon>,
+    /// Area of the contour as defined in the input, before simplification.
+    /// Equal to the area of `shape` for items which are not created by the parser.
+    pub original_area: fsize,
+    /// Exact contour of the item including circular arcs, if the input defined any.
+    /// Only used for export, `shape` is a tessellation of it.
+    pub arc_shape: Option<Arc<ArcPolygon>>,
+    /// Distance by which the contour as defined in the input can lie outside of `shape`, due to the flattening of curves
+    /// (splines, Bézier curves and elliptical arcs). Zero for items which are not read from a DXF or SVG file.
+    pub curve_deviation: fsize,
     /// Possible rotations in which to place the item
     pub allowed_rotation: AllowedRotation,
     /// The quality of the item, if `None` the item requires full quality
     pub base_quality: Option<usize>,
     pub value: u64,
+    /// Penalty for every demanded copy of the item which is not placed, zero if undefined
+    pub shortfall_cost: u64,
     /// Transformation applied to the shape with respect to the original shape in the input file (for example to center it).
     pub pretransform: Transformation,
     /// Filter for hazards that the item is unaffected by
     pub hazard_filter: Option<QZHazardFilter>,
     /// Configuration for the surrogate generation
     pub surrogate_config: SPSurrogateConfig,
+    /// Attributes of every copy of the item, indexed by copy.
+    /// Empty if all copies are interchangeable
+    pub copy_attributes: Vec<CopyAttributes>,
+    /// Material the item has to be cut from, `None` if it can be placed in any bin
+    pub material: Option<String>,
 }
 
 impl Item {
@@ -36,17 +59,76 @@ impl Item {
         surrogate_config: SPSurrogateConfig,
     ) -> Item {
         shape.generate_surrogate(surrogate_config);
-        let shape = Arc::new(shape);
+        Item::from_preprocessed(
+            id,
+            Arc::new(shape),
+            allowed_rotation,
+            base_quality,
+            value,
+            pretransform,
+            surrogate_config,
+        )
+    }
+
+    /// Creates an item from a shape of which the surrogate was already generated with `surrogate_config`,
+    /// for example one restored from a [`ShapeCache`](crate::io::shape_cache::ShapeCache).
+    pub fn from_preprocessed(
+        id: usize,
+        shape: Arc<SimplePolygon>,
+        allowed_rotation: AllowedRotation,
+        base_quality: Option<usize>,
+        value: u64,
+        pretransform: Transformation,
+        surrogate_config: SPSurrogateConfig,
+    ) -> Item {
+        assert!(shape.surrogate.is_some(), "shape has no surrogate");
+        let original_area = shape.area();
         let hazard_filter = base_quality.map(QZHazardFilter);
         Item {
             id,
+            name: None,
             shape,
+            original_area,
+            arc_shape: None,
+            curve_deviation: 0.0,
             allowed_rotation,
             base_quality,
             value,
+            shortfall_cost: 0,
             pretransform,
             hazard_filter,
             surrogate_config,
+            copy_attributes: vec![],
+            material: None,
         }
     }
+
+    /// Whether the item is an axis-aligned rectangle which can only be rotated by multiples of 90°,
+    /// so it remains an axis-aligned rectangle in every placement.
+    pub fn is_rectangular(&self) -> bool {
+        let right_angle = |angle: &fsize| {
+            let quarter_turns = angle / (PI / 2.0);
+            (quarter_turns - quarter_turns.round()).abs() < 1e-4
+        };
+        self.shape.is_aa_rectangle()
+            && match &self.allowed_rotation {
+                AllowedRotation::None => true,
+                AllowedRotation::Continuous => false,
+                AllowedRotation::Discrete(angles) => angles.iter().all(right_angle),
+                AllowedRotation::Explicit(transfs) => {
+                    transfs.iter().all(|t| right_angle(&t.rotation()))
+                }
+            }
+    }
+
+    /// Area of the (simplified) contour used for collision detection
+    pub fn simplified_area(&self) -> fsize {
+        self.shape.area()
+    }
+
+    /// Difference between the simplified and the original area.
+    /// Positive, because simplification can only inflate items.
+    pub fn area_delta(&self) -> fsize {
+        self.simplified_area() - self.original_area
+    }
 }
@@ -0,0 +1,86 @@
+use crate::entities::bin::Bin;
+use crate::entities::item::Item;
+use crate::fsize;
+use crate::geometry::d_transformation::DTransformation;
+use crate::geometry::transformation::Transformation;
+use crate::PI;
+
+/// Maximum distance, relative to the largest side of the bin, between a placement and the position a copy is locked at
+const LOCK_TOLERANCE: fsize = 1e-4;
+
+/// Attributes of a single copy of an item, which further constrain where that copy can be placed.
+/// The copies of an item without attributes are interchangeable.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CopyAttributes {
+    /// Id of the bin the copy has to be placed in (or one of its rotated variants)
+    pub bin_id: Option<usize>,
+    /// Transformation at which the copy has to be placed, in the coordinates of the input (like the transformations in a solution)
+    pub locked_transf: Option<DTransformation>,
+}
+
+impl CopyAttributes {
+    /// Whether a placement in `bin` with `d_transf` (in internal coordinates) satisfies the attributes
+    pub fn satisfied_by(&self, item: &Item, bin: &Bin, d_transf: &DTransformation) -> bool {
+        let bin_ok = self.bin_id.is_none_or(|bin_id| bin_id == bin.base_id());
+        let lock_ok = match self.locked_internal_transf(item, bin) {
+            None => true,
+            Some(locked) => {
+                let bbox = bin.bbox();
+                let tolerance = LOCK_TOLERANCE * bbox.width().max(bbox.height());
+                let (lx, ly) = locked.translation();
+                let (tx, ty) = d_transf.translation();
+                let r_diff = (locked.rotation() - d_transf.rotation()).rem_euclid(2.0 * PI);
+                let r_diff = r_diff.min(2.0 * PI - r_diff);
+                r_diff < 1e-3 && (lx - tx).hypot(ly - ty) <= tolerance
+            }
+        };
+        bin_ok && lock_ok
+    }
+
+    /// Transformation at which the copy is locked, converted to the internal coordinates of `bin`.
+    /// Same conversion as `absolute_to_internal_transform` of the parser
+    pub fn locked_internal_transf(&self, item: &Item, bin: &Bin) -> Option<DTransformation> {
+        self.locked_transf.as_ref().map(|abs_transf| {
+            Transformation::empty()
+                .transform(&item.pretransform.clone().inverse())
+                .transform_from_decomposed(abs_transf)
+                .transform(&bin.pretransform)
+                .decompose()
+        })
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_transf.is_some()
+    }
+}
+
+/// Copy of the item to which a placement in `bin` with `d_transf` is assigned, out of the copies which are not placed yet.
+/// The most constrained copy satisfied by the placement is chosen: locked copies first, then copies restricted to the bin,
+/// then copies without attributes. Constrained copies are thus not taken by placements which could do without.
+/// Returns `None` if the item has no per-copy attributes, or if the placement satisfies none of the remaining copies.
+pub fn assign_copy(
+    item: &Item,
+    placed_copies: &[usize],
+    bin: &Bin,
+    d_transf: &DTransformation,
+) -> Option<usize> {
+    item.copy_attributes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !placed_copies.contains(i))
+        .filter(|(_, attr)| attr.satisfied_by(item, bin, d_transf))
+        .min_by_key(|(i, attr)| (!attr.is_locked(), attr.bin_id.is_none(), *i))
+        .map(|(i, _)| i)
+}
+
+/// Whether a copy of the item which is not locked at a position is still available for a placement in the bin with `bin_id`.
+/// Always true for items without per-copy attributes
+pub fn unlocked_copy_available(item: &Item, placed_copies: &[usize], bin_id: usize) -> bool {
+    item.copy_attributes.is_empty()
+        || item
+            .copy_attributes
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !placed_copies.contains(i))
+            .any(|(_, attr)| !attr.is_locked() && attr.bin_id.is_none_or(|id| id == bin_id))
+}
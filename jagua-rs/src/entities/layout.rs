@@ -6,9 +6,13 @@ use crate::entities::placed_item::{PItemKey, PlacedItem};
 use crate::fsize;
 use crate::geometry::d_transformation::DTransformation;
 use crate::geometry::geo_traits::Shape;
-use crate::util::assertions;
+use crate::util::assertions::{self, debug_assert_with_svg};
+use crate::util::debug_svg;
 use slotmap::SlotMap;
 
+/// Margin by which the usage of a layout may exceed the maximum usage of its bin, to absorb rounding errors
+pub const USAGE_TOLERANCE: fsize = 1e-6;
+
 ///A Layout is made out of a [Bin] with a set of [Item]s positioned inside of it in a specific way.
 ///It is a mutable representation, and can be modified by placing or removing items.
 ///
@@ -24,17 +28,22 @@ pub struct Layout {
     pub placed_items: SlotMap<PItemKey, PlacedItem>,
     /// The collision detection engine for this layout
     cde: CDEngine,
+    /// Total area of the placed items, maintained on every placement and removal
+    placed_item_area: fsize,
 }
 
 impl Layout {
     pub fn new(id: usize, bin: Bin) -> Self {
         let cde = bin.base_cde.as_ref().clone();
-        Layout {
+        let mut layout = Layout {
             id,
             bin,
             placed_items: SlotMap::with_key(),
             cde,
-        }
+            placed_item_area: 0.0,
+        };
+        layout.update_temporary_zones(true);
+        layout
     }
 
     pub fn from_snapshot(ls: &LayoutSnapshot) -> Self {
@@ -52,6 +61,7 @@ impl Layout {
             let hazard = Hazard::new(pi.into(), pi.shape.clone());
             self.cde.register_hazard(hazard);
         }
+        self.update_temporary_zones(true);
     }
 
     pub fn create_snapshot(&mut self) -> LayoutSnapshot {
@@ -68,9 +78,13 @@ impl Layout {
         assert_eq!(self.id, layout_snapshot.id);
 
         self.placed_items = layout_snapshot.placed_items.clone();
+        self.placed_item_area = total_area(&self.placed_items);
         self.cde.restore(&layout_snapshot.cde_snapshot);
 
-        debug_assert!(assertions::layout_qt_matches_fresh_qt(self));
+        debug_assert_with_svg!(
+            assertions::layout_qt_matches_fresh_qt(self),
+            debug_svg::write_cde_svg(&self.cde, None)
+        );
         debug_assert!(assertions::layouts_match(self, layout_snapshot))
     }
 
@@ -78,14 +92,27 @@ impl Layout {
         Layout { id, ..self.clone() }
     }
 
-    pub fn place_item(&mut self, item: &Item, d_transformation: DTransformation) -> PItemKey {
-        let pi = PlacedItem::new(item, d_transformation);
+    /// Places a copy of the item, see [`PlacedItem::copy`]
+    pub fn place_item(
+        &mut self,
+        item: &Item,
+        copy: Option<usize>,
+        d_transformation: DTransformation,
+    ) -> PItemKey {
+        let pi = PlacedItem::new(item, copy, d_transformation);
         let hazard = Hazard::new(HazardEntity::from(&pi), pi.shape.clone());
+        let shape = pi.shape.clone();
 
+        self.placed_item_area += shape.area();
         let pik = self.placed_items.insert(pi);
         self.cde.register_hazard(hazard);
+        self.update_temporary_zones(true);
 
-        debug_assert!(assertions::layout_qt_matches_fresh_qt(self));
+        debug_assert_with_svg!(
+            assertions::layout_qt_matches_fresh_qt(self),
+            debug_svg::write_cde_svg(&self.cde, Some(&shape))
+        );
+        debug_assert!(assertions::layout_placed_item_area_correct(self));
 
         pik
     }
@@ -96,15 +123,47 @@ impl Layout {
             .remove(key)
             .expect("key is not valid anymore");
 
+        self.placed_item_area = match self.placed_items.is_empty() {
+            //reset to avoid accumulating rounding errors
+            true => 0.0,
+            false => self.placed_item_area - pi.shape.area(),
+        };
+
         // update the collision detection engine
         self.cde
             .deregister_hazard(HazardEntity::from(&pi), commit_instant);
+        self.update_temporary_zones(commit_instant);
 
-        debug_assert!(assertions::layout_qt_matches_fresh_qt(self));
+        debug_assert_with_svg!(
+            assertions::layout_qt_matches_fresh_qt(self),
+            debug_svg::write_cde_svg(&self.cde, Some(&pi.shape))
+        );
+        debug_assert!(assertions::layout_placed_item_area_correct(self));
 
         pi
     }
 
+    /// Registers the temporary zones of the bin which are active for the current number of placed items,
+    /// and deregisters the ones which are not.
+    fn update_temporary_zones(&mut self, commit_instant: bool) {
+        let n_placed = self.placed_items.len();
+        for (id, zone) in self.bin.temporary_zones.iter().enumerate() {
+            let entity = HazardEntity::TemporaryZone { id };
+            let registered = self
+                .cde
+                .dynamic_hazards()
+                .iter()
+                .any(|h| h.entity == entity);
+            match (zone.is_active(n_placed), registered) {
+                (true, false) => self
+                    .cde
+                    .register_hazard(Hazard::new(entity, zone.shape.clone())),
+                (false, true) => self.cde.deregister_hazard(entity, commit_instant),
+                _ => {}
+            }
+        }
+    }
+
     /// True if no items are placed
     pub fn is_empty(&self) -> bool {
         self.placed_items.is_empty()
@@ -124,14 +183,21 @@ impl Layout {
     /// Returns the usage of the bin with the items placed.
     /// It is the ratio of the area of the items placed to the area of the bin.
     pub fn usage(&self) -> fsize {
-        let bin_area = self.bin.area;
-        let item_area = self
-            .placed_items
-            .iter()
-            .map(|(_, pi)| pi.shape.area())
-            .sum::<fsize>();
+        self.placed_item_area / self.bin.area
+    }
 
-        item_area / bin_area
+    /// Whether placing the item keeps the usage of the layout within the maximum usage of the bin, if it has one.
+    /// Uses the simplified areas (like [`Self::usage`]), which overestimate the usage rather than underestimate it
+    pub fn usage_allows(&self, item: &Item) -> bool {
+        self.bin.max_usage.is_none_or(|max_usage| {
+            (self.placed_item_area + item.shape.area()) / self.bin.area
+                <= max_usage + USAGE_TOLERANCE
+        })
+    }
+
+    /// Total area of the items placed in the layout
+    pub fn placed_item_area(&self) -> fsize {
+        self.placed_item_area
     }
 
     pub fn id(&self) -> usize {
@@ -149,6 +215,10 @@ impl Layout {
     }
 }
 
+fn total_area(placed_items: &SlotMap<PItemKey, PlacedItem>) -> fsize {
+    placed_items.values().map(|pi| pi.shape.area()).sum()
+}
+
 /// Immutable and compact representation of a [Layout].
 /// `Layout`s can create `LayoutSnapshot`s, and revert back themselves to a previous state using them.
 #[derive(Clone, Debug)]
@@ -1,9 +1,11 @@
 pub mod bin;
 pub mod instances;
 pub mod item;
+pub mod item_copy;
 pub mod layout;
 pub mod placed_item;
 pub mod placing_option;
 pub mod problems;
 pub mod quality_zone;
 pub mod solution;
+pub mod temporary_zone;
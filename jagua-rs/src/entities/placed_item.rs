@@ -16,6 +16,8 @@ new_key_type! {
 pub struct PlacedItem {
     /// ID of the type of `Item` that was placed
     pub item_id: usize,
+    /// Which copy of the `Item` was placed, only defined for items with per-copy attributes
+    pub copy: Option<usize>,
     /// The transformation that was applied to the `Item` before it was placed
     pub d_transf: DTransformation,
     /// The filter for hazards that the `Item` is unaffected by
@@ -25,13 +27,14 @@ pub struct PlacedItem {
 }
 
 impl PlacedItem {
-    pub fn new(item: &Item, d_transf: DTransformation) -> Self {
+    pub fn new(item: &Item, copy: Option<usize>, d_transf: DTransformation) -> Self {
         let transf = d_transf.compose();
         let shape = Arc::new(item.shape.transform_clone(&transf));
         let qz_haz_filter = item.hazard_filter.clone();
 
         PlacedItem {
             item_id: item.id,
+            copy,
             d_transf,
             shape,
             hazard_filter: qz_haz_filter,
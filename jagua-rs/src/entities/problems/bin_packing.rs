@@ -94,13 +94,18 @@ impl BPProblem {
         self.unmodified_layouts_ref_solution = Some(ref_solution_id);
     }
 
+    /// Takes a bin from the stock, rotated variants of a bin draw from the same stock
     fn register_bin(&mut self, bin_id: usize) {
         assert!(self.bin_qtys[bin_id] > 0);
-        self.bin_qtys[bin_id] -= 1
+        for id in self.instance.bins_sharing_stock(bin_id) {
+            self.bin_qtys[id] -= 1
+        }
     }
 
     fn deregister_bin(&mut self, bin_id: usize) {
-        self.bin_qtys[bin_id] += 1
+        for id in self.instance.bins_sharing_stock(bin_id) {
+            self.bin_qtys[id] += 1
+        }
     }
 
     fn layout_has_changed(&mut self, l_id: usize) {
@@ -113,6 +118,7 @@ impl BPProblem {
 
 impl ProblemGeneric for BPProblem {
     fn place_item(&mut self, p_opt: PlacingOption) -> (LayoutIndex, PItemKey) {
+        let copy = self.copy_for_placement(&p_opt);
         let layout_index = match &p_opt.layout_idx {
             LayoutIndex::Real(i) => LayoutIndex::Real(*i),
             LayoutIndex::Template(i) => {
@@ -128,7 +134,21 @@ impl ProblemGeneric for BPProblem {
             LayoutIndex::Template(_) => unreachable!("cannot place item in template layout"),
         };
         let item = self.instance.item(p_opt.item_id);
-        let pik = layout.place_item(item, p_opt.d_transf);
+        assert!(
+            layout.bin.accepts(item),
+            "item {} of material {:?} cannot be placed in bin {} of material {:?}",
+            item.id,
+            item.material,
+            layout.bin.id,
+            layout.bin.material
+        );
+        assert!(
+            layout.usage_allows(item),
+            "placing item {} exceeds the maximum usage of bin {}",
+            item.id,
+            layout.bin.id
+        );
+        let pik = layout.place_item(item, copy, p_opt.d_transf);
         let layout_id = layout.id();
 
         self.register_included_item(p_opt.item_id);
@@ -0,0 +1,56 @@
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::layout::Layout;
+use crate::entities::placed_item::PItemKey;
+use crate::entities::placing_option::PlacingOption;
+use crate::entities::problems::problem_generic::LayoutIndex;
+use crate::entities::solution::Solution;
+
+/// Object-safe trait for problem variants defined outside of this crate (for example multi-strip or knapsack variants).
+/// Wrap an implementation in [`Problem::Custom`](crate::entities::problems::problem::Problem::Custom)
+/// to use it wherever a `Problem` is expected.
+/// <br>
+/// The methods mirror [`ProblemGeneric`](crate::entities::problems::problem_generic::ProblemGeneric), see there for their contracts.
+/// Calls are dispatched dynamically, the built-in variants are unaffected.
+pub trait CustomProblem: Send + Sync {
+    fn place_item(&mut self, p_opt: PlacingOption) -> (LayoutIndex, PItemKey);
+
+    fn remove_item(
+        &mut self,
+        layout_index: LayoutIndex,
+        pik: PItemKey,
+        commit_instantly: bool,
+    ) -> PlacingOption;
+
+    fn create_solution(&mut self, old_solution: Option<&Solution>) -> Solution;
+
+    fn restore_to_solution(&mut self, solution: &Solution);
+
+    fn layouts(&self) -> &[Layout];
+
+    fn layouts_mut(&mut self) -> &mut [Layout];
+
+    fn template_layouts(&self) -> &[Layout];
+
+    fn missing_item_qtys(&self) -> &[isize];
+
+    fn missing_item_qtys_mut(&mut self) -> &mut [isize];
+
+    fn bin_qtys(&self) -> &[usize];
+
+    fn instance(&self) -> &dyn InstanceGeneric;
+
+    /// Returns a new unique identifier for a `Solution`
+    fn next_solution_id(&mut self) -> usize;
+
+    /// Returns a new unique identifier for a `Layout`
+    fn next_layout_id(&mut self) -> usize;
+
+    /// Clones the problem into a new box, required to make `Problem` cloneable
+    fn clone_box(&self) -> Box<dyn CustomProblem>;
+}
+
+impl Clone for Box<dyn CustomProblem> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
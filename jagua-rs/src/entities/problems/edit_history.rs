@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use crate::entities::placed_item::PItemKey;
+use crate::entities::placing_option::PlacingOption;
+use crate::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+
+/// Log of the placements and removals made to a problem, which can be undone and redone one by one.
+/// Meant for interactive editing, where stepping back through [`Solution`](crate::entities::solution::Solution) snapshots would be too coarse.
+/// <br>
+/// Every edit stores only what changed: the item and its [`PlacingOption`].
+/// Undoing a placement removes the item again, undoing a removal places it back in the same configuration.
+/// When this empties a layout it is closed, as usual. Placing an item back into a closed layout opens a new one of the same bin,
+/// which then takes the place of the closed layout for all edits in the history.
+/// <br>
+/// The problem must only be modified through the history while it is in use,
+/// any other modification (including changing the width of a strip) requires a [`Self::clear`].
+#[derive(Clone, Debug, Default)]
+pub struct EditHistory {
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+    /// Current layout id and key of every edited item that is placed, by handle
+    placed: HashMap<usize, (usize, PItemKey)>,
+    /// Ids of closed layouts, mapped to the id of the layout which replaced them
+    replaced_layouts: HashMap<usize, usize>,
+    handle_counter: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditKind {
+    Place,
+    Remove,
+}
+
+/// A single placement or removal of an item
+#[derive(Clone, Debug)]
+pub struct Edit {
+    pub kind: EditKind,
+    /// Handle of the item, stable over undo and redo (unlike its [`PItemKey`])
+    handle: usize,
+    /// Id of the layout in which the item was placed or from which it was removed
+    layout_id: usize,
+    /// Id of the bin of that layout, to open a new layout if it has been closed
+    bin_id: usize,
+    /// Configuration of the item, with the index of its layout at the time of the edit
+    pub p_opt: PlacingOption,
+}
+
+impl EditHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Places an item in the problem and records the placement, see [`ProblemGeneric::place_item`]
+    pub fn place_item(
+        &mut self,
+        problem: &mut impl ProblemGeneric,
+        p_opt: PlacingOption,
+    ) -> (LayoutIndex, PItemKey) {
+        let (layout_idx, pik) = problem.place_item(p_opt);
+        let layout = problem.get_layout(layout_idx);
+        let handle = self.next_handle();
+        self.placed.insert(handle, (layout.id(), pik));
+        self.record(Edit {
+            kind: EditKind::Place,
+            handle,
+            layout_id: layout.id(),
+            bin_id: layout.bin.id,
+            p_opt: PlacingOption {
+                layout_idx,
+                ..p_opt
+            },
+        });
+        (layout_idx, pik)
+    }
+
+    /// Removes a placed item from the problem (instantly committed) and records the removal, see [`ProblemGeneric::remove_item`]
+    pub fn remove_item(
+        &mut self,
+        problem: &mut impl ProblemGeneric,
+        layout_idx: LayoutIndex,
+        pik: PItemKey,
+    ) -> PlacingOption {
+        let layout = problem.get_layout(layout_idx);
+        let (layout_id, bin_id) = (layout.id(), layout.bin.id);
+        //items which were placed outside the history receive a handle now
+        let handle = self
+            .placed
+            .iter()
+            .find(|(_, loc)| **loc == (layout_id, pik))
+            .map(|(handle, _)| *handle)
+            .unwrap_or_else(|| self.next_handle());
+        self.placed.remove(&handle);
+
+        let p_opt = problem.remove_item(layout_idx, pik, true);
+        self.record(Edit {
+            kind: EditKind::Remove,
+            handle,
+            layout_id,
+            bin_id,
+            p_opt,
+        });
+        p_opt
+    }
+
+    /// Reverts the last edit, returns it (if any)
+    pub fn undo(&mut self, problem: &mut impl ProblemGeneric) -> Option<&Edit> {
+        let edit = self.undo_stack.pop()?;
+        match edit.kind {
+            EditKind::Place => self.unplace(problem, &edit),
+            EditKind::Remove => self.replace(problem, &edit),
+        }
+        self.redo_stack.push(edit);
+        self.redo_stack.last()
+    }
+
+    /// Reapplies the last undone edit, returns it (if any)
+    pub fn redo(&mut self, problem: &mut impl ProblemGeneric) -> Option<&Edit> {
+        let edit = self.redo_stack.pop()?;
+        match edit.kind {
+            EditKind::Place => self.replace(problem, &edit),
+            EditKind::Remove => self.unplace(problem, &edit),
+        }
+        self.undo_stack.push(edit);
+        self.undo_stack.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Edits that can be undone, the most recent one last
+    pub fn edits(&self) -> &[Edit] {
+        &self.undo_stack
+    }
+
+    /// Forgets all edits, the current state of the problem becomes the new starting point
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn record(&mut self, edit: Edit) {
+        self.undo_stack.push(edit);
+        self.redo_stack.clear();
+    }
+
+    /// Places the item of the edit back in its layout, or in a new layout of the same bin if it has been closed
+    fn replace(&mut self, problem: &mut impl ProblemGeneric, edit: &Edit) {
+        let layout_id = self.current_layout_id(edit.layout_id);
+        let layout_idx = match problem.layouts().iter().position(|l| l.id() == layout_id) {
+            Some(i) => LayoutIndex::Real(i),
+            None => LayoutIndex::Template(edit.bin_id),
+        };
+        let (layout_idx, pik) = problem.place_item(PlacingOption {
+            layout_idx,
+            ..edit.p_opt
+        });
+        let new_layout_id = problem.get_layout(layout_idx).id();
+        if new_layout_id != layout_id {
+            self.replaced_layouts.insert(layout_id, new_layout_id);
+        }
+        self.placed.insert(edit.handle, (new_layout_id, pik));
+    }
+
+    /// Removes the item of the edit from wherever it is currently placed
+    fn unplace(&mut self, problem: &mut impl ProblemGeneric, edit: &Edit) {
+        let (layout_id, pik) = self
+            .placed
+            .remove(&edit.handle)
+            .expect("edited item is not placed, was the problem modified outside of the history?");
+        let layout_idx = problem
+            .layouts()
+            .iter()
+            .position(|l| l.id() == layout_id)
+            .map(LayoutIndex::Real)
+            .expect(
+                "layout of edited item not found, was the problem modified outside of the history?",
+            );
+        problem.remove_item(layout_idx, pik, true);
+    }
+
+    fn current_layout_id(&self, mut layout_id: usize) -> usize {
+        while let Some(&replacement) = self.replaced_layouts.get(&layout_id) {
+            layout_id = replacement;
+        }
+        layout_id
+    }
+
+    fn next_handle(&mut self) -> usize {
+        self.handle_counter += 1;
+        self.handle_counter
+    }
+}
+
+#[cfg(all(test, feature = "io"))]
+mod tests {
+    use itertools::Itertools;
+    use rand::prelude::SmallRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::entities::problems::problem::Problem;
+    use crate::util::test_fixtures;
+
+    /// Bins and placements of all layouts, independent of layout ids and item keys
+    fn state(problem: &Problem) -> Vec<(usize, Vec<String>)> {
+        problem
+            .layouts()
+            .iter()
+            .map(|l| {
+                let placements = l
+                    .placed_items()
+                    .values()
+                    .map(|pi| format!("{} {}", pi.item_id, pi.d_transf))
+                    .sorted()
+                    .collect_vec();
+                (l.bin.id, placements)
+            })
+            .sorted()
+            .collect_vec()
+    }
+
+    fn check_edit_history(file_name: &str) {
+        let instance = test_fixtures::parse_asset(file_name);
+        let problem =
+            &mut test_fixtures::random_problem(&instance, &mut SmallRng::seed_from_u64(0));
+        let initial_state = state(problem);
+
+        let mut history = EditHistory::new();
+        //empty the last layout, then place its first item back and remove it again
+        let last_idx = LayoutIndex::Real(problem.layouts().len() - 1);
+        let removed_bin = problem.get_layout(last_idx).bin.id;
+        let removed = (0..problem.get_layout(last_idx).placed_items().len())
+            .map(|_| {
+                let pik = problem
+                    .get_layout(last_idx)
+                    .placed_items()
+                    .keys()
+                    .next()
+                    .unwrap();
+                history.remove_item(problem, last_idx, pik)
+            })
+            .collect_vec();
+        //in bin packing the emptied layout is closed, a new one has to be opened
+        let target = match problem {
+            Problem::BP(_) => LayoutIndex::Template(removed_bin),
+            _ => last_idx,
+        };
+        let (l_idx, pik) = history.place_item(
+            problem,
+            PlacingOption {
+                layout_idx: target,
+                ..removed[0]
+            },
+        );
+        history.remove_item(problem, l_idx, pik);
+        problem.flush_changes();
+        let edited_state = state(problem);
+        assert_ne!(edited_state, initial_state);
+        assert_eq!(history.edits().len(), removed.len() + 2);
+
+        while history.undo(problem).is_some() {}
+        assert!(!history.can_undo());
+        assert_eq!(state(problem), initial_state);
+
+        while history.redo(problem).is_some() {}
+        assert!(!history.can_redo());
+        assert_eq!(state(problem), edited_state);
+
+        //a new edit discards the undone ones
+        history.undo(problem);
+        history.undo(problem);
+        history.place_item(
+            problem,
+            PlacingOption {
+                layout_idx: target,
+                ..removed[0]
+            },
+        );
+        assert!(!history.can_redo());
+        while history.undo(problem).is_some() {}
+        assert_eq!(state(problem), initial_state);
+    }
+
+    #[test]
+    fn test_edit_history_bin_packing() {
+        check_edit_history("baldacci1.json");
+    }
+
+    #[test]
+    fn test_edit_history_strip_packing() {
+        check_edit_history("swim.json");
+    }
+}
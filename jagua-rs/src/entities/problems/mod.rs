@@ -1,4 +1,7 @@
 pub mod bin_packing;
+pub mod custom_problem;
+pub mod edit_history;
 pub mod problem;
 pub mod problem_generic;
+pub mod problem_view;
 pub mod strip_packing;
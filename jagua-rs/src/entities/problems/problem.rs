@@ -3,6 +3,7 @@ use crate::entities::layout::Layout;
 use crate::entities::placed_item::PItemKey;
 use crate::entities::placing_option::PlacingOption;
 use crate::entities::problems::bin_packing::BPProblem;
+use crate::entities::problems::custom_problem::CustomProblem;
 use crate::entities::problems::problem_generic::private::ProblemGenericPrivate;
 use crate::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
 use crate::entities::problems::strip_packing::SPProblem;
@@ -15,12 +16,23 @@ use crate::entities::solution::Solution;
 /// <br>
 /// Also enables the use of match statements on the `Problem` enum when variant-specific behavior is required,
 /// When a new variant is added, compile errors will be generated everywhere specific behaviour is required.
+/// <br>
+/// Problem variants defined outside of this crate can be used through the `Custom` variant, see [`CustomProblem`].
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Problem {
     /// Bin Packing Problem
     BP(BPProblem),
     /// Strip Packing Problem
     SP(SPProblem),
+    /// Externally defined problem variant
+    Custom(Box<dyn CustomProblem>),
+}
+
+impl Problem {
+    pub fn custom(problem: impl CustomProblem + 'static) -> Self {
+        Problem::Custom(Box::new(problem))
+    }
 }
 
 impl ProblemGeneric for Problem {
@@ -28,6 +40,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.place_item(p_opt),
             Problem::SP(sp) => sp.place_item(p_opt),
+            Problem::Custom(c) => c.place_item(p_opt),
         }
     }
 
@@ -40,6 +53,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.remove_item(layout_index, pik, commit_instantly),
             Problem::SP(sp) => sp.remove_item(layout_index, pik, commit_instantly),
+            Problem::Custom(c) => c.remove_item(layout_index, pik, commit_instantly),
         }
     }
 
@@ -47,6 +61,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.create_solution(old_solution),
             Problem::SP(sp) => sp.create_solution(old_solution),
+            Problem::Custom(c) => c.create_solution(old_solution),
         }
     }
 
@@ -54,6 +69,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.restore_to_solution(solution),
             Problem::SP(sp) => sp.restore_to_solution(solution),
+            Problem::Custom(c) => c.restore_to_solution(solution),
         }
     }
 
@@ -61,6 +77,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.layouts(),
             Problem::SP(sp) => sp.layouts(),
+            Problem::Custom(c) => c.layouts(),
         }
     }
 
@@ -68,6 +85,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.layouts_mut(),
             Problem::SP(sp) => sp.layouts_mut(),
+            Problem::Custom(c) => c.layouts_mut(),
         }
     }
 
@@ -75,6 +93,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.template_layouts(),
             Problem::SP(sp) => sp.template_layouts(),
+            Problem::Custom(c) => c.template_layouts(),
         }
     }
 
@@ -82,6 +101,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.missing_item_qtys(),
             Problem::SP(sp) => sp.missing_item_qtys(),
+            Problem::Custom(c) => c.missing_item_qtys(),
         }
     }
 
@@ -89,6 +109,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.bin_qtys(),
             Problem::SP(sp) => sp.bin_qtys(),
+            Problem::Custom(c) => c.bin_qtys(),
         }
     }
 
@@ -96,6 +117,7 @@ impl ProblemGeneric for Problem {
         match self {
             Problem::BP(bp) => bp.instance(),
             Problem::SP(sp) => sp.instance(),
+            Problem::Custom(c) => c.instance(),
         }
     }
 }
@@ -105,6 +127,7 @@ impl ProblemGenericPrivate for Problem {
         match self {
             Problem::BP(bp) => bp.next_solution_id(),
             Problem::SP(sp) => sp.next_solution_id(),
+            Problem::Custom(c) => c.next_solution_id(),
         }
     }
 
@@ -112,6 +135,7 @@ impl ProblemGenericPrivate for Problem {
         match self {
             Problem::BP(bp) => bp.next_layout_id(),
             Problem::SP(sp) => sp.next_layout_id(),
+            Problem::Custom(c) => c.next_layout_id(),
         }
     }
 
@@ -119,6 +143,7 @@ impl ProblemGenericPrivate for Problem {
         match self {
             Problem::BP(bp) => bp.missing_item_qtys_mut(),
             Problem::SP(sp) => sp.missing_item_qtys_mut(),
+            Problem::Custom(c) => c.missing_item_qtys_mut(),
         }
     }
 }
@@ -1,10 +1,12 @@
 use std::borrow::Borrow;
 
 use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::item_copy;
 use crate::entities::layout::Layout;
 use crate::entities::placed_item::PItemKey;
 use crate::entities::placing_option::PlacingOption;
 use crate::entities::problems::problem_generic::private::ProblemGenericPrivate;
+use crate::entities::problems::problem_view::ProblemView;
 use crate::entities::solution::Solution;
 use crate::fsize;
 
@@ -42,53 +44,75 @@ pub trait ProblemGeneric: ProblemGenericPrivate {
     /// The quantity of each item that is requested but currently missing in the problem instance, indexed by item id.
     fn missing_item_qtys(&self) -> &[isize];
 
+    /// Read-only view of the current state of the problem, see [`ProblemView`]
+    fn view(&self) -> ProblemView<'_> {
+        ProblemView::new(
+            self.layouts(),
+            self.template_layouts(),
+            self.missing_item_qtys(),
+            self.bin_qtys(),
+            self.instance(),
+        )
+    }
+
     /// The quantity of each item that is currently placed in the problem instance, indexed by item id.
     fn placed_item_qtys(&self) -> impl Iterator<Item = usize> {
-        self.missing_item_qtys()
-            .iter()
-            .enumerate()
-            .map(|(i, missing_qty)| (self.instance().item_qty(i) as isize - missing_qty) as usize)
+        self.view().placed_item_qtys()
     }
 
-    fn usage(&mut self) -> fsize {
-        let (total_bin_area, total_used_area) =
-            self.layouts_mut().iter_mut().fold((0.0, 0.0), |acc, l| {
-                let bin_area = l.bin.area;
-                let used_area = bin_area * l.usage();
-                (acc.0 + bin_area, acc.1 + used_area)
-            });
-        total_used_area / total_bin_area
+    /// Ratio of the total area of the placed items to the total area of the bins in use
+    fn usage(&self) -> fsize {
+        self.view().usage()
     }
 
     fn used_bin_cost(&self) -> u64 {
-        self.layouts().iter().map(|l| l.bin.value).sum()
+        self.view().used_bin_cost()
     }
 
     /// Returns the `LayoutIndex` of all layouts.
     fn layout_indices(&self) -> impl Iterator<Item = LayoutIndex> {
-        (0..self.layouts().len()).map(LayoutIndex::Real)
+        self.view().layout_indices()
     }
 
     /// Returns the `LayoutIndex` of all template layouts that have remaining stock.
     fn template_layout_indices_with_stock(&self) -> impl Iterator<Item = LayoutIndex> {
-        self.template_layouts()
-            .iter()
-            .enumerate()
-            .filter_map(|(i, l)| match self.bin_qtys()[l.bin.id] {
-                0 => None,
-                _ => Some(LayoutIndex::Template(i)),
-            })
+        self.view().template_layout_indices_with_stock()
     }
 
     fn get_layout(&self, index: impl Borrow<LayoutIndex>) -> &Layout {
-        match index.borrow() {
-            LayoutIndex::Real(i) => &self.layouts()[*i],
-            LayoutIndex::Template(i) => &self.template_layouts()[*i],
-        }
+        self.view().get_layout(index)
     }
 
     fn bin_qtys(&self) -> &[usize];
 
+    /// Copies of the item which are currently placed, only tracked for items with per-copy attributes
+    fn placed_copies(&self, item_id: usize) -> Vec<usize> {
+        self.layouts()
+            .iter()
+            .flat_map(|l| l.placed_items().values())
+            .filter(|pi| pi.item_id == item_id)
+            .filter_map(|pi| pi.copy)
+            .collect()
+    }
+
+    /// Copy of the item to which the placement would be assigned, see [`item_copy::assign_copy`].
+    /// Panics if the item has per-copy attributes, but the placement satisfies none of its remaining copies
+    fn copy_for_placement(&self, p_opt: &PlacingOption) -> Option<usize> {
+        let item = self.instance().item(p_opt.item_id);
+        if item.copy_attributes.is_empty() {
+            return None;
+        }
+        let bin = &self.get_layout(p_opt.layout_idx).bin;
+        let copy = item_copy::assign_copy(item, &self.placed_copies(item.id), bin, &p_opt.d_transf);
+        assert!(
+            copy.is_some(),
+            "placement of item {} in bin {} satisfies none of its remaining copies",
+            item.id,
+            bin.id
+        );
+        copy
+    }
+
     /// Makes sure that the all collision detection engines are completely updated with the changes made to the layouts.
     fn flush_changes(&mut self) {
         self.layouts_mut()
@@ -127,9 +151,9 @@ pub enum LayoutIndex {
     Template(usize),
 }
 
-impl Into<usize> for LayoutIndex {
-    fn into(self) -> usize {
-        match self {
+impl From<LayoutIndex> for usize {
+    fn from(val: LayoutIndex) -> Self {
+        match val {
             LayoutIndex::Real(i) | LayoutIndex::Template(i) => i,
         }
     }
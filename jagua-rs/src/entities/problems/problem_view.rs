@@ -0,0 +1,106 @@
+use std::borrow::Borrow;
+
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::layout::Layout;
+use crate::entities::problems::problem_generic::LayoutIndex;
+use crate::fsize;
+
+/// Read-only view of the state of a problem, obtained with [`ProblemGeneric::view`](crate::entities::problems::problem_generic::ProblemGeneric::view).
+/// <br>
+/// Only requires a shared reference to the problem, so evaluation code and observers can query
+/// the layouts, quantities and usage while the optimizer owns the problem.
+/// Cheap to copy, as it only holds references.
+#[derive(Clone, Copy)]
+pub struct ProblemView<'a> {
+    layouts: &'a [Layout],
+    template_layouts: &'a [Layout],
+    missing_item_qtys: &'a [isize],
+    bin_qtys: &'a [usize],
+    instance: &'a dyn InstanceGeneric,
+}
+
+impl<'a> ProblemView<'a> {
+    pub fn new(
+        layouts: &'a [Layout],
+        template_layouts: &'a [Layout],
+        missing_item_qtys: &'a [isize],
+        bin_qtys: &'a [usize],
+        instance: &'a dyn InstanceGeneric,
+    ) -> Self {
+        Self {
+            layouts,
+            template_layouts,
+            missing_item_qtys,
+            bin_qtys,
+            instance,
+        }
+    }
+
+    pub fn layouts(&self) -> &'a [Layout] {
+        self.layouts
+    }
+
+    /// See [`ProblemGeneric::template_layouts`](crate::entities::problems::problem_generic::ProblemGeneric::template_layouts)
+    pub fn template_layouts(&self) -> &'a [Layout] {
+        self.template_layouts
+    }
+
+    /// The quantity of each item that is requested but currently missing, indexed by item id.
+    pub fn missing_item_qtys(&self) -> &'a [isize] {
+        self.missing_item_qtys
+    }
+
+    /// The quantity of each item that is currently placed, indexed by item id.
+    pub fn placed_item_qtys(&self) -> impl Iterator<Item = usize> + 'a {
+        let instance = self.instance;
+        self.missing_item_qtys
+            .iter()
+            .enumerate()
+            .map(move |(i, missing_qty)| (instance.item_qty(i) as isize - missing_qty) as usize)
+    }
+
+    /// The quantity of each bin that is still in stock, indexed by bin id.
+    pub fn bin_qtys(&self) -> &'a [usize] {
+        self.bin_qtys
+    }
+
+    pub fn instance(&self) -> &'a dyn InstanceGeneric {
+        self.instance
+    }
+
+    /// Ratio of the total area of the placed items to the total area of the bins in use
+    pub fn usage(&self) -> fsize {
+        let (total_bin_area, total_used_area) = self.layouts.iter().fold((0.0, 0.0), |acc, l| {
+            (acc.0 + l.bin.area, acc.1 + l.placed_item_area())
+        });
+        total_used_area / total_bin_area
+    }
+
+    pub fn used_bin_cost(&self) -> u64 {
+        self.layouts.iter().map(|l| l.bin.value).sum()
+    }
+
+    /// Returns the `LayoutIndex` of all layouts.
+    pub fn layout_indices(&self) -> impl Iterator<Item = LayoutIndex> {
+        (0..self.layouts.len()).map(LayoutIndex::Real)
+    }
+
+    /// Returns the `LayoutIndex` of all template layouts that have remaining stock.
+    pub fn template_layout_indices_with_stock(&self) -> impl Iterator<Item = LayoutIndex> + 'a {
+        let bin_qtys = self.bin_qtys;
+        self.template_layouts
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, l)| match bin_qtys[l.bin.id] {
+                0 => None,
+                _ => Some(LayoutIndex::Template(i)),
+            })
+    }
+
+    pub fn get_layout(&self, index: impl Borrow<LayoutIndex>) -> &'a Layout {
+        match index.borrow() {
+            LayoutIndex::Real(i) => &self.layouts[*i],
+            LayoutIndex::Template(i) => &self.template_layouts[*i],
+        }
+    }
+}
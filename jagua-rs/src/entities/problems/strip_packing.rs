@@ -12,10 +12,11 @@ use crate::entities::problems::problem_generic::ProblemGeneric;
 use crate::entities::problems::problem_generic::{LayoutIndex, STRIP_LAYOUT_IDX};
 use crate::entities::solution::Solution;
 use crate::fsize;
+use crate::geometry::geo_enums::GeoRelation;
 use crate::geometry::geo_traits::{Shape, Transformable};
 use crate::geometry::primitives::aa_rectangle::AARectangle;
 use crate::util::assertions;
-use crate::util::config::CDEConfig;
+use crate::util::config::{CDEConfig, StripGrowth};
 use crate::util::fpa::FPA;
 use itertools::Itertools;
 use log::error;
@@ -88,9 +89,26 @@ impl SPProblem {
         self.modify_strip(new_strip_shape);
     }
 
+    /// Grows the strip in the back according to the `growth` policy and returns the new width.
+    pub fn grow_strip(&mut self, growth: StripGrowth) -> fsize {
+        let new_width = growth.grow(self.strip_width());
+        self.modify_strip_in_back(new_width);
+        new_width
+    }
+
     /// Modifies the shape of the strip to a new rectangle.
     /// All items that fit in the new strip are kept, the rest are removed.
     pub fn modify_strip(&mut self, rect: AARectangle) {
+        let bin_config = self.layout.bin.base_cde.config();
+        if let GeoRelation::Surrounding = rect.relation_to(&self.layout.bin.outer.bbox()) {
+            //The strip only expands, all items remain valid.
+            //Their transformed shapes are reused and registered in the new CDE without any collision checks.
+            self.layout.change_bin(Bin::from_strip(rect, bin_config));
+            //the bin changed, so the layout can no longer be restored from snapshots of the old one
+            self.layout.id = self.next_layout_id();
+            return;
+        }
+
         let placed_items = self
             .layout
             .placed_items()
@@ -105,10 +123,7 @@ impl SPProblem {
             .for_each(|(i, qty)| *qty = self.instance.item_qty(i) as isize);
 
         //Modifying the width causes the bin to change, so the layout must be replaced
-        self.layout = Layout::new(
-            self.next_layout_id(),
-            Bin::from_strip(rect, self.layout.bin.base_cde.config()),
-        );
+        self.layout = Layout::new(self.next_layout_id(), Bin::from_strip(rect, bin_config));
 
         //place the items back in the new layout
         for (item_id, d_transf) in placed_items {
@@ -144,7 +159,11 @@ impl SPProblem {
     }
 
     /// Shrinks the strip to the minimum width that fits all items.
+    /// An empty strip is left unchanged.
     pub fn fit_strip(&mut self) {
+        if self.layout.placed_items().is_empty() {
+            return;
+        }
         let n_items_in_old_strip = self.layout.placed_items().len();
 
         let fitted_width = self.occupied_width() * (1.0 + FPA::tolerance()); //add some tolerance to avoid rounding errors or false collision positives
@@ -157,6 +176,26 @@ impl SPProblem {
         );
     }
 
+    /// Shrinks the strip to the minimum width that fits all items, only removing width at the back.
+    /// The front of the strip, and with it the origin of the coordinates of the solution, is left unchanged.
+    /// An empty strip is left unchanged.
+    pub fn fit_strip_in_back(&mut self) {
+        let Some((_, x_max)) = self.occupied_range() else {
+            return;
+        };
+        let n_items_in_old_strip = self.layout.placed_items().len();
+
+        let x_min = self.layout.bin.outer.bbox().x_min;
+        let fitted_width = (x_max - x_min) * (1.0 + FPA::tolerance());
+        self.modify_strip_in_back(fitted_width);
+
+        assert_eq!(
+            n_items_in_old_strip,
+            self.layout.placed_items().len(),
+            "fitting the strip should not remove any items"
+        );
+    }
+
     /// Returns the horizontal range occupied by the placed items. If no items are placed, returns None.
     pub fn occupied_range(&self) -> Option<(fsize, fsize)> {
         occupied_range(&self.layout)
@@ -183,8 +222,9 @@ impl ProblemGeneric for SPProblem {
             "Strip packing problems only have a single layout"
         );
         let item_id = p_opt.item_id;
+        let copy = self.copy_for_placement(&p_opt);
         let item = self.instance.item(item_id);
-        let placed_item_key = self.layout.place_item(item, p_opt.d_transf);
+        let placed_item_key = self.layout.place_item(item, copy, p_opt.d_transf);
 
         self.register_included_item(item_id);
         (STRIP_LAYOUT_IDX, placed_item_key)
@@ -10,7 +10,8 @@ pub const N_QUALITIES: usize = 10;
 pub struct InferiorQualityZone {
     /// Higher quality is better
     pub quality: usize,
-    /// The outer shapes of all zones of this quality
+    /// Simple polygons covering all zones of this quality.
+    /// Overlapping zones are merged and zones with holes are cut into multiple polygons by the parser
     pub zones: Vec<Arc<SimplePolygon>>,
 }
 
@@ -7,6 +7,7 @@ use crate::entities::instances::instance_generic::InstanceGeneric;
 use crate::entities::layout::LayoutSnapshot;
 use crate::fsize;
 use crate::geometry::geo_traits::Shape;
+use crate::util::layout_metrics;
 
 /// Represents a snapshot of a `Problem` at a specific moment.
 /// Solutions can be used to restore the state of a `Problem` to a previous state.
@@ -68,6 +69,15 @@ impl Solution {
         included_item_area / total_item_area
     }
 
+    /// Total shortfall cost of the copies of the items which are demanded but not placed
+    pub fn shortfall_cost(&self, instance: &Instance) -> u64 {
+        self.missing_item_qtys(instance)
+            .iter()
+            .enumerate()
+            .map(|(i, qty)| instance.item(i).shortfall_cost * (*qty).max(0) as u64)
+            .sum()
+    }
+
     /// Returns the quantities of the items that still need to be placed to reach a complete solution.
     pub fn missing_item_qtys(&self, instance: &Instance) -> Vec<isize> {
         debug_assert!(instance.items().len() == self.placed_item_qtys.len());
@@ -78,6 +88,21 @@ impl Solution {
             .collect_vec()
     }
 
+    /// Usage of the solution computed with the original areas of the items and bins, before simplification
+    pub fn original_usage(&self, instance: &Instance) -> fsize {
+        let total_bin_area = self
+            .layout_snapshots
+            .iter()
+            .map(|sl| sl.bin.original_area)
+            .sum::<fsize>();
+        let total_item_area = self
+            .layout_snapshots
+            .iter()
+            .map(|sl| layout_metrics::original_item_area(&sl.placed_items, instance))
+            .sum::<fsize>();
+        total_item_area / total_bin_area
+    }
+
     pub fn n_items_placed(&self) -> usize {
         self.placed_item_qtys.iter().sum()
     }
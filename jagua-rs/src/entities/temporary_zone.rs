@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+
+/// Zone of the `Bin` in which no items can be placed during part of the placement sequence,
+/// for example the position of a clamp which is moved once the first items are on the sheet.
+/// The schedule is expressed in the number of items placed in the layout:
+/// the zone is a hazard while at least `active_from`, but less than `active_until`, items are placed.
+/// <br>
+/// Items which are placed while the zone is inactive can overlap it.
+/// Removing items can activate the zone again, over items which were placed while it was inactive.
+#[derive(Clone, Debug)]
+pub struct TemporaryZone {
+    pub shape: Arc<SimplePolygon>,
+    pub active_from: usize,
+    /// `None` if the zone remains active once activated
+    pub active_until: Option<usize>,
+}
+
+impl TemporaryZone {
+    pub fn new(shape: SimplePolygon, active_from: usize, active_until: Option<usize>) -> Self {
+        assert!(
+            shape.surrogate.is_some(),
+            "shape of temporary zone has no surrogate"
+        );
+        assert!(
+            active_until.is_none_or(|until| until > active_from),
+            "temporary zone is never active"
+        );
+        Self {
+            shape: Arc::new(shape),
+            active_from,
+            active_until,
+        }
+    }
+
+    /// Whether the zone is a hazard for the next item placed in a layout with `n_placed` items
+    pub fn is_active(&self, n_placed: usize) -> bool {
+        n_placed >= self.active_from && self.active_until.is_none_or(|until| n_placed < until)
+    }
+}
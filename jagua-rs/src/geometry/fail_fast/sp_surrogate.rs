@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::fsize;
 use crate::geometry::convex_hull;
 use crate::geometry::fail_fast::{piers, poi};
@@ -8,7 +10,7 @@ use crate::geometry::primitives::simple_polygon::SimplePolygon;
 use crate::geometry::transformation::Transformation;
 use crate::util::config::SPSurrogateConfig;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 /// Surrogate representation of a [SimplePolygon] for fail-fast purposes
 pub struct SPSurrogate {
     /// Indices of the points in the [SimplePolygon] that form the convex hull
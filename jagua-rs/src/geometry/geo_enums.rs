@@ -1,4 +1,5 @@
 use crate::fsize;
+use crate::geometry::d_transformation::DTransformation;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum GeoPosition {
@@ -28,4 +29,7 @@ pub enum AllowedRotation {
     Continuous,
     /// Only a limited set of rotations is allowed
     Discrete(Vec<fsize>),
+    /// Only the listed transformations are allowed, which fix the position of the item as well (for example parts held by a fixture).
+    /// Defined in the coordinates of the input, like the transformations in a solution
+    Explicit(Vec<DTransformation>),
 }
@@ -5,10 +5,11 @@ use crate::geometry::primitives::edge::Edge;
 use crate::geometry::primitives::point::Point;
 use crate::util::fpa::FPA;
 use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
 ///Geometric primitive representing an axis-aligned rectangle
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AARectangle {
     pub x_min: fsize,
     pub y_min: fsize,
@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fsize;
+use crate::geometry::geo_traits::Transformable;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::transformation::Transformation;
+use crate::util::polygon_simplification::PolySimplMode;
+use crate::PI;
+
+/// Polygon of which the edges can be either straight lines or circular arcs.
+/// Used to preserve the exact geometry of the input (for example DXF files) for export,
+/// the collision detection engine operates on a tessellated [`SimplePolygon`](crate::geometry::primitives::simple_polygon::SimplePolygon).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArcPolygon {
+    /// Start points of all edges, the last edge connects the last point with the first one
+    pub points: Vec<Point>,
+    /// Bulge of every edge, using the DXF convention: the tangent of a quarter of the included angle of the arc.
+    /// Zero for straight edges, positive for counterclockwise arcs and negative for clockwise arcs.
+    pub bulges: Vec<fsize>,
+}
+
+impl ArcPolygon {
+    pub fn new(points: Vec<Point>, bulges: Vec<fsize>) -> Self {
+        assert_eq!(
+            points.len(),
+            bulges.len(),
+            "every edge of an arc polygon should have a bulge"
+        );
+        assert!(points.len() >= 2, "arc polygon must have at least 2 points");
+        Self { points, bulges }
+    }
+
+    pub fn number_of_edges(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns the start point, end point and bulge of edge `i`
+    pub fn get_edge(&self, i: usize) -> (Point, Point, fsize) {
+        let j = (i + 1) % self.points.len();
+        (self.points[i], self.points[j], self.bulges[i])
+    }
+
+    pub fn has_arcs(&self) -> bool {
+        self.bulges.iter().any(|b| *b != 0.0)
+    }
+
+    /// Signed area of the arc polygon, positive if the contour is counterclockwise
+    pub fn signed_area(&self) -> fsize {
+        (0..self.number_of_edges())
+            .map(|i| {
+                let (start, end, bulge) = self.get_edge(i);
+                //shoelace contribution of the chord
+                let chord_area = (start.0 * end.1 - end.0 * start.1) / 2.0;
+                let segment_area = match bulge {
+                    0.0 => 0.0,
+                    _ => bulge.signum() * CircularArc::from_bulge(start, end, bulge).segment_area(),
+                };
+                chord_area + segment_area
+            })
+            .sum()
+    }
+
+    /// Length of the contour, measured along the arcs
+    pub fn perimeter(&self) -> fsize {
+        (0..self.number_of_edges())
+            .map(|i| match self.get_edge(i) {
+                (start, end, 0.0) => start.distance(end),
+                (start, end, bulge) => CircularArc::from_bulge(start, end, bulge).length(),
+            })
+            .sum()
+    }
+
+    /// Approximates the arc polygon by a set of points, where every arc is replaced by a sequence of segments.
+    /// No point on the arcs deviates more than `max_deviation` from the segments.
+    /// With [`PolySimplMode::Inflate`] the result fully contains the arc polygon, with [`PolySimplMode::Deflate`] it is fully contained by it.
+    pub fn tessellate(&self, max_deviation: fsize, mode: PolySimplMode) -> Vec<Point> {
+        let orientation = self.signed_area().signum();
+        let mut points = vec![];
+        for i in 0..self.number_of_edges() {
+            let (start, end, bulge) = self.get_edge(i);
+            points.push(start);
+            if bulge != 0.0 {
+                let arc = CircularArc::from_bulge(start, end, bulge);
+                //chords lie on the side of the center, tangent segments on the opposite side
+                let bulges_outward = bulge.signum() == orientation;
+                let use_tangents = match mode {
+                    PolySimplMode::Inflate => bulges_outward,
+                    PolySimplMode::Deflate => !bulges_outward,
+                };
+                match use_tangents {
+                    true => points.extend(arc.tangent_points(max_deviation)),
+                    false => points.extend(arc.chord_points(max_deviation)),
+                }
+            }
+        }
+        points
+    }
+}
+
+impl Transformable for ArcPolygon {
+    /// Bulges are invariant under rotations and translations, only the points need to be transformed.
+    fn transform(&mut self, t: &Transformation) -> &mut Self {
+        self.points.iter_mut().for_each(|p| {
+            p.transform(t);
+        });
+        self
+    }
+}
+
+/// Circular arc, defined by its center, radius, start angle and (signed) included angle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CircularArc {
+    pub center: Point,
+    pub radius: fsize,
+    pub start_angle: fsize,
+    /// Positive for counterclockwise arcs, negative for clockwise arcs
+    pub sweep_angle: fsize,
+}
+
+impl CircularArc {
+    pub fn from_bulge(start: Point, end: Point, bulge: fsize) -> Self {
+        assert_ne!(bulge, 0.0, "a bulge of zero defines a straight line");
+        let sweep_angle = 4.0 * bulge.atan();
+        let chord = (end.0 - start.0, end.1 - start.1);
+        let chord_length = start.distance(end);
+        let mid = Point((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+
+        //signed distance from the midpoint of the chord to the center, measured to the left of the chord
+        let h = chord_length / (2.0 * (sweep_angle / 2.0).tan());
+        let left_normal = (-chord.1 / chord_length, chord.0 / chord_length);
+        let center = Point(mid.0 + h * left_normal.0, mid.1 + h * left_normal.1);
+
+        let radius = center.distance(start);
+        let start_angle = (start.1 - center.1).atan2(start.0 - center.0);
+
+        Self {
+            center,
+            radius,
+            start_angle,
+            sweep_angle,
+        }
+    }
+
+    /// Point on the arc at fraction `t` (between 0 and 1) of the sweep
+    pub fn point_at(&self, t: fsize) -> Point {
+        let angle = self.start_angle + t * self.sweep_angle;
+        Point(
+            self.center.0 + self.radius * angle.cos(),
+            self.center.1 + self.radius * angle.sin(),
+        )
+    }
+
+    /// Number of segments needed to approximate the arc, if every segment can span at most `max_angle`
+    fn n_segments(&self, max_angle: fsize) -> usize {
+        //at least one segment per quarter circle, to keep the tangent segments well-defined
+        let min_n_segments = (self.sweep_angle.abs() / (PI / 2.0)).ceil() as usize;
+        let n_segments = match max_angle > 0.0 {
+            true => (self.sweep_angle.abs() / max_angle).ceil() as usize,
+            //no deviation allowed, fall back to a segment per degree
+            false => self.sweep_angle.abs().to_degrees().ceil() as usize,
+        };
+        n_segments.max(min_n_segments).max(1)
+    }
+
+    /// Intermediate points of a sequence of chords approximating the arc, without deviating more than `max_deviation`.
+    /// The chords lie between the arc and its center.
+    pub fn chord_points(&self, max_deviation: fsize) -> Vec<Point> {
+        //a chord spanning an angle `a` deviates `r * (1 - cos(a/2))` from the arc
+        let cos_half_angle = (1.0 - max_deviation / self.radius).clamp(-1.0, 1.0);
+        let n_segments = self.n_segments(2.0 * cos_half_angle.acos());
+        (1..n_segments)
+            .map(|k| self.point_at(k as fsize / n_segments as fsize))
+            .collect()
+    }
+
+    /// Intermediate points of a sequence of segments tangent to the arc, without deviating more than `max_deviation`.
+    /// The segments lie on the side of the arc opposite to its center.
+    pub fn tangent_points(&self, max_deviation: fsize) -> Vec<Point> {
+        //tangent segments spanning an angle `a` deviate `r * (1 / cos(a/2) - 1)` from the arc
+        let cos_half_angle = self.radius / (self.radius + max_deviation);
+        let n_segments = self.n_segments(2.0 * cos_half_angle.acos());
+        let half_angle = self.sweep_angle / (2 * n_segments) as fsize;
+        //the tangents at two consecutive points intersect at a distance `r / cos(a/2)` from the center
+        let radius = self.radius / half_angle.cos();
+        (0..n_segments)
+            .map(|k| {
+                let angle = self.start_angle + (2 * k + 1) as fsize * half_angle;
+                Point(
+                    self.center.0 + radius * angle.cos(),
+                    self.center.1 + radius * angle.sin(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn length(&self) -> fsize {
+        self.radius * self.sweep_angle.abs()
+    }
+
+    /// Area between the arc and its chord
+    pub fn segment_area(&self) -> fsize {
+        let angle = self.sweep_angle.abs();
+        self.radius.powi(2) / 2.0 * (angle - angle.sin())
+    }
+
+    /// Whether the arc spans more than half a circle
+    pub fn is_large(&self) -> bool {
+        self.sweep_angle.abs() > PI
+    }
+}
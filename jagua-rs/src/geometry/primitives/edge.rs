@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::fsize;
 use crate::geometry::geo_enums::GeoPosition;
 use crate::geometry::geo_traits::{
@@ -8,7 +10,7 @@ use crate::geometry::primitives::point::Point;
 use crate::geometry::transformation::Transformation;
 
 /// Geometric primitive representing a line segment
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Edge {
     pub start: Point,
     pub end: Point,
@@ -1,5 +1,7 @@
 pub mod aa_rectangle;
+pub mod arc_polygon;
 pub mod circle;
 pub mod edge;
+pub mod oriented_rectangle;
 pub mod point;
 pub mod simple_polygon;
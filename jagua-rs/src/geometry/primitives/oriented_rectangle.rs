@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fsize;
+use crate::geometry::convex_hull::convex_hull_from_points;
+use crate::geometry::geo_traits::{CollidesWith, Transformable, TransformableFrom};
+use crate::geometry::primitives::aa_rectangle::AARectangle;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::transformation::Transformation;
+
+/// Margin, relative to the largest side, by which an enclosing rectangle is grown to compensate for rounding errors.
+/// Points on the boundary of the hull should never end up outside the rectangle.
+const ENCLOSING_MARGIN: fsize = 1e-4;
+
+/// Geometric primitive representing a rectangle with an arbitrary orientation,
+/// defined by its corners in counterclockwise order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrientedRectangle {
+    pub corners: [Point; 4],
+}
+
+impl OrientedRectangle {
+    /// Minimum-area oriented rectangle enclosing all points.
+    /// Such a rectangle always has a side collinear with an edge of the convex hull of the points,
+    /// all edges are evaluated in linear time with rotating calipers.
+    pub fn enclosing(points: &[Point]) -> Self {
+        let hull = convex_hull_from_points(points.to_vec());
+        let n = hull.len();
+        let project = |i: usize, (dx, dy): (fsize, fsize)| hull[i % n].0 * dx + hull[i % n].1 * dy;
+
+        //indices of the hull points furthest along u, along v and against u, advancing monotonically with the edges
+        let (mut j_u_max, mut j_v_max, mut j_u_min) = (0, 0, 0);
+        let mut best: Option<(fsize, (fsize, fsize))> = None;
+        //the projections along a direction are unimodal around a convex hull, so the maximum is found by walking uphill
+        let advance_caliper = |j: &mut usize, dir: (fsize, fsize)| {
+            for _ in 0..n {
+                if project(*j + 1, dir) <= project(*j, dir) {
+                    break;
+                }
+                *j = (*j + 1) % n;
+            }
+        };
+        for i in 0..n {
+            let Some((u, v)) = edge_axes(hull[i], hull[(i + 1) % n]) else {
+                continue;
+            };
+            let first = best.is_none();
+            if first {
+                //u_max, v_max and u_min are encountered in this order walking counterclockwise from the edge
+                j_u_max = (i + 1) % n;
+            }
+            advance_caliper(&mut j_u_max, u);
+            if first {
+                j_v_max = j_u_max;
+            }
+            advance_caliper(&mut j_v_max, v);
+            if first {
+                j_u_min = j_v_max;
+            }
+            advance_caliper(&mut j_u_min, (-u.0, -u.1));
+            let width = project(j_u_max, u) - project(j_u_min, u);
+            let height = project(j_v_max, v) - project(i, v);
+            let area = width * height;
+            if best.is_none_or(|(best_area, _)| area < best_area) {
+                best = Some((area, u));
+            }
+        }
+
+        let (_, u) = best.expect("no oriented rectangle found, degenerate convex hull");
+        let v = (-u.1, u.0);
+
+        //the extents of the chosen orientation are determined over all hull points, robust against rounding in the calipers
+        let (mut u_min, mut u_max, mut v_min, mut v_max) =
+            (fsize::MAX, fsize::MIN, fsize::MAX, fsize::MIN);
+        for Point(x, y) in hull.iter() {
+            let (pu, pv) = (x * u.0 + y * u.1, x * v.0 + y * v.1);
+            (u_min, u_max) = (u_min.min(pu), u_max.max(pu));
+            (v_min, v_max) = (v_min.min(pv), v_max.max(pv));
+        }
+        let margin = ENCLOSING_MARGIN * (u_max - u_min).max(v_max - v_min);
+        let (u_min, u_max, v_min, v_max) = (
+            u_min - margin,
+            u_max + margin,
+            v_min - margin,
+            v_max + margin,
+        );
+        let corner = |pu: fsize, pv: fsize| Point(pu * u.0 + pv * v.0, pu * u.1 + pv * v.1);
+        OrientedRectangle {
+            corners: [
+                corner(u_min, v_min),
+                corner(u_max, v_min),
+                corner(u_max, v_max),
+                corner(u_min, v_max),
+            ],
+        }
+    }
+
+    pub fn area(&self) -> fsize {
+        let [p0, p1, _, p3] = self.corners;
+        p0.distance(p1) * p0.distance(p3)
+    }
+
+    pub fn bbox(&self) -> AARectangle {
+        let (mut x_min, mut y_min, mut x_max, mut y_max) =
+            (fsize::MAX, fsize::MAX, fsize::MIN, fsize::MIN);
+        for Point(x, y) in self.corners.iter() {
+            (x_min, x_max) = (x_min.min(*x), x_max.max(*x));
+            (y_min, y_max) = (y_min.min(*y), y_max.max(*y));
+        }
+        AARectangle::new(x_min, y_min, x_max, y_max)
+    }
+
+    /// Whether the projections of both rectangles on the normal of a side of `self` are disjoint
+    fn separated_by_side_of(&self, other: &OrientedRectangle) -> bool {
+        //two sides suffice, the other two are parallel
+        [
+            (self.corners[0], self.corners[1]),
+            (self.corners[1], self.corners[2]),
+        ]
+        .iter()
+        .any(|(Point(x0, y0), Point(x1, y1))| {
+            let axis = (x1 - x0, y1 - y0);
+            let project = |corners: &[Point; 4]| {
+                corners
+                    .iter()
+                    .map(|Point(x, y)| x * axis.0 + y * axis.1)
+                    .fold((fsize::MAX, fsize::MIN), |(min, max), p| {
+                        (min.min(p), max.max(p))
+                    })
+            };
+            let ((s_min, s_max), (o_min, o_max)) =
+                (project(&self.corners), project(&other.corners));
+            s_max < o_min || o_max < s_min
+        })
+    }
+}
+
+/// Orthonormal axes aligned with the edge from `p0` to `p1`, the second one pointing to its left.
+/// `None` for a degenerate edge.
+fn edge_axes(
+    Point(x0, y0): Point,
+    Point(x1, y1): Point,
+) -> Option<((fsize, fsize), (fsize, fsize))> {
+    let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+    if length == 0.0 {
+        return None;
+    }
+    let u = ((x1 - x0) / length, (y1 - y0) / length);
+    Some((u, (-u.1, u.0)))
+}
+
+impl From<AARectangle> for OrientedRectangle {
+    fn from(r: AARectangle) -> Self {
+        OrientedRectangle {
+            corners: [
+                Point(r.x_min, r.y_min),
+                Point(r.x_max, r.y_min),
+                Point(r.x_max, r.y_max),
+                Point(r.x_min, r.y_max),
+            ],
+        }
+    }
+}
+
+impl CollidesWith<OrientedRectangle> for OrientedRectangle {
+    /// Separating axis theorem, rectangles which only touch are considered colliding.
+    fn collides_with(&self, other: &OrientedRectangle) -> bool {
+        !self.separated_by_side_of(other) && !other.separated_by_side_of(self)
+    }
+}
+
+impl Transformable for OrientedRectangle {
+    fn transform(&mut self, t: &Transformation) -> &mut Self {
+        self.corners.iter_mut().for_each(|c| {
+            c.transform(t);
+        });
+        self
+    }
+}
+
+impl TransformableFrom for OrientedRectangle {
+    fn transform_from(&mut self, reference: &Self, t: &Transformation) -> &mut Self {
+        for (c, ref_c) in self.corners.iter_mut().zip(reference.corners.iter()) {
+            c.transform_from(ref_c, t);
+        }
+        self
+    }
+}
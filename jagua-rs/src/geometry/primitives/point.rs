@@ -1,11 +1,13 @@
 use std::hash::{Hash, Hasher};
 
+use serde::{Deserialize, Serialize};
+
 use crate::fsize;
 use crate::geometry::geo_traits::{CollidesWith, Transformable, TransformableFrom};
 use crate::geometry::transformation::Transformation;
 
 /// Geometric primitive representing a point: (x, y)
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub struct Point(pub fsize, pub fsize);
 
 impl Transformable for Point {
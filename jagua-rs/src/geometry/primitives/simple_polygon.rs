@@ -3,6 +3,8 @@ use std::borrow::Borrow;
 use itertools::Itertools;
 use num_integer::Integer;
 use ordered_float::NotNan;
+use serde::{Deserialize, Serialize};
+use tracing::debug_span;
 
 use crate::fsize;
 use crate::geometry::convex_hull::convex_hull_from_points;
@@ -15,13 +17,18 @@ use crate::geometry::geo_traits::{
 use crate::geometry::primitives::aa_rectangle::AARectangle;
 use crate::geometry::primitives::circle::Circle;
 use crate::geometry::primitives::edge::Edge;
+use crate::geometry::primitives::oriented_rectangle::OrientedRectangle;
 use crate::geometry::primitives::point::Point;
 use crate::geometry::transformation::Transformation;
 use crate::util::config::SPSurrogateConfig;
 use crate::util::fpa::FPA;
 
+/// Maximum relative difference between the area of a polygon and its bounding box to be considered an axis-aligned rectangle.
+/// Allows for the rounding errors of rotating by a multiple of 90°.
+const AA_RECTANGLE_AREA_TOLERANCE: fsize = 1e-5;
+
 /// Geometric primitive representing a simple polygon: <https://en.wikipedia.org/wiki/Simple_polygon>
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SimplePolygon {
     /// Set of bounds describing the polygon
     pub points: Vec<Point>,
@@ -30,6 +37,10 @@ pub struct SimplePolygon {
     pub area: fsize,
     /// Maximum distance between any two points in the polygon
     pub diameter: fsize,
+    /// Whether the polygon is convex
+    pub convex: bool,
+    /// Minimum-area oriented bounding box
+    pub obb: OrientedRectangle,
     /// Pole of inaccessibility
     pub poi: Circle,
     /// Surrogate representation (subset of the simple polygon)
@@ -38,20 +49,34 @@ pub struct SimplePolygon {
 
 impl SimplePolygon {
     /// Create a new simple polygon from a set of points, expensive operations are performed here! Use [Self::clone()] or [Self::transform()] to avoid recomputation.
-    pub fn new(mut points: Vec<Point>) -> Self {
-        assert!(
-            points.len() >= 3,
-            "simple polygon must have at least 3 points"
-        );
-        assert_eq!(
-            points.iter().unique().count(),
-            points.len(),
-            "simple polygon should not contain duplicate points: {:?}",
-            points
-        );
+    /// Panics if the points do not describe a polygon, see [Self::try_new()] for input which is not known to be valid.
+    pub fn new(points: Vec<Point>) -> Self {
+        SimplePolygon::try_new(points).unwrap_or_else(|reason| panic!("{}", reason))
+    }
+
+    /// Same as [Self::new()], but fails if there are fewer than 3 points, duplicate or non-finite points, or the polygon has no area
+    pub fn try_new(mut points: Vec<Point>) -> Result<Self, String> {
+        if points.len() < 3 {
+            return Err(format!(
+                "simple polygon must have at least 3 points, got {}",
+                points.len()
+            ));
+        }
+        if points.iter().any(|p| !p.0.is_finite() || !p.1.is_finite()) {
+            return Err(format!(
+                "simple polygon has non-finite coordinates: {:?}",
+                points
+            ));
+        }
+        if points.iter().unique().count() != points.len() {
+            return Err(format!(
+                "simple polygon should not contain duplicate points: {:?}",
+                points
+            ));
+        }
 
         let area = match SimplePolygon::calculate_area(&points) {
-            area if area == 0.0 => panic!("simple polygon has no area: {:?}", points),
+            0.0 => return Err(format!("simple polygon has no area: {:?}", points)),
             area if area < 0.0 => {
                 //edges should always be ordered counterclockwise (positive area)
                 points.reverse();
@@ -63,21 +88,33 @@ impl SimplePolygon {
         let diameter = SimplePolygon::calculate_diameter(points.clone());
         let bbox = SimplePolygon::generate_bounding_box(&points);
         let poi = SimplePolygon::calculate_poi(&points, diameter);
+        let convex = SimplePolygon::calculate_convexity(&points);
+        let obb = OrientedRectangle::enclosing(&points);
 
-        SimplePolygon {
+        Ok(SimplePolygon {
             points,
             bbox,
             area,
             diameter,
+            convex,
+            obb,
             poi,
             surrogate: None,
-        }
+        })
     }
 
     pub fn generate_surrogate(&mut self, config: SPSurrogateConfig) {
+        let _span = debug_span!("generate_surrogate", n_points = self.points.len()).entered();
         self.surrogate = Some(SPSurrogate::new(self, config));
     }
 
+    /// Whether the polygon is an axis-aligned rectangle, up to floating point precision.
+    /// If so, the polygon can be represented by its bounding box.
+    pub fn is_aa_rectangle(&self) -> bool {
+        self.number_of_points() == 4
+            && self.bbox.area() - self.area <= AA_RECTANGLE_AREA_TOLERANCE * self.area
+    }
+
     pub fn get_point(&self, i: usize) -> Point {
         self.points[i]
     }
@@ -144,6 +181,68 @@ impl SimplePolygon {
         0.5 * sigma
     }
 
+    /// Whether the polygon, with its points ordered counterclockwise, is convex.
+    /// Collinear points are allowed.
+    pub fn calculate_convexity(points: &[Point]) -> bool {
+        let n = points.len();
+        (0..n).all(|i| {
+            let (Point(x0, y0), Point(x1, y1), Point(x2, y2)) =
+                (points[i], points[(i + 1) % n], points[(i + 2) % n]);
+            (x1 - x0) * (y2 - y1) - (y1 - y0) * (x2 - x1) >= 0.0
+        })
+    }
+
+    /// Whether two convex polygons collide, using the separating axis theorem.
+    /// Polygons which only touch are considered colliding.
+    pub fn convex_collides_with(&self, other: &SimplePolygon) -> bool {
+        debug_assert!(self.convex && other.convex);
+        let separated_by_edge_of = |a: &SimplePolygon, b: &SimplePolygon| {
+            a.edge_iter().any(|edge| {
+                //outward normal of the edge, points are ordered counterclockwise
+                let (nx, ny) = (edge.end.1 - edge.start.1, edge.start.0 - edge.end.0);
+                let offset = nx * edge.start.0 + ny * edge.start.1;
+                //all points of a project at or below the offset, so b is separated if all its points project above it
+                b.points.iter().all(|p| nx * p.0 + ny * p.1 > offset)
+            })
+        };
+        !separated_by_edge_of(self, other) && !separated_by_edge_of(other, self)
+    }
+
+    /// Whether a convex polygon collides with an axis-aligned rectangle, using the separating axis theorem.
+    /// Touching the rectangle is considered colliding.
+    pub fn convex_collides_with_rect(&self, rect: &AARectangle) -> bool {
+        debug_assert!(self.convex);
+        let corners = [
+            Point(rect.x_min, rect.y_min),
+            Point(rect.x_max, rect.y_min),
+            Point(rect.x_max, rect.y_max),
+            Point(rect.x_min, rect.y_max),
+        ];
+        //the axes of the rectangle are checked by its bounding box, the others by the edges of the polygon
+        self.bbox.collides_with(rect)
+            && !self.edge_iter().any(|edge| {
+                let (nx, ny) = (edge.end.1 - edge.start.1, edge.start.0 - edge.end.0);
+                let offset = nx * edge.start.0 + ny * edge.start.1;
+                corners.iter().all(|p| nx * p.0 + ny * p.1 > offset)
+            })
+    }
+
+    /// Whether the convex polygon fully contains another polygon, touching its boundary is allowed.
+    pub fn convex_contains(&self, other: &SimplePolygon) -> bool {
+        self.convex_contains_points(&other.points)
+    }
+
+    /// Whether the convex polygon contains all points, points on its boundary are allowed.
+    pub fn convex_contains_points(&self, points: &[Point]) -> bool {
+        debug_assert!(self.convex);
+        self.edge_iter().all(|edge| {
+            let (dx, dy) = (edge.end.0 - edge.start.0, edge.end.1 - edge.start.1);
+            points
+                .iter()
+                .all(|p| dx * (p.1 - edge.start.1) - dy * (p.0 - edge.start.0) >= 0.0)
+        })
+    }
+
     pub fn calculate_poi(points: &[Point], diameter: fsize) -> Circle {
         //need to make a dummy simple polygon, because the pole generation algorithm
         //relies on many of the methods provided by the simple polygon struct
@@ -151,12 +250,15 @@ impl SimplePolygon {
             let bbox = SimplePolygon::generate_bounding_box(points);
             let area = SimplePolygon::calculate_area(points);
             let dummy_poi = Circle::new(Point(fsize::MAX, fsize::MAX), fsize::MAX);
+            let dummy_obb = OrientedRectangle::from(bbox.clone());
 
             SimplePolygon {
                 points: points.to_vec(),
                 bbox,
                 area,
                 diameter,
+                convex: false,
+                obb: dummy_obb,
                 poi: dummy_poi,
                 surrogate: None,
             }
@@ -222,6 +324,8 @@ impl Transformable for SimplePolygon {
             bbox,
             area: _,
             diameter: _,
+            convex: _,
+            obb,
             poi,
             surrogate,
         } = self;
@@ -231,6 +335,7 @@ impl Transformable for SimplePolygon {
             p.transform(t);
         });
 
+        obb.transform(t);
         poi.transform(t);
 
         //transform the surrogate
@@ -253,6 +358,8 @@ impl TransformableFrom for SimplePolygon {
             bbox,
             area: _,
             diameter: _,
+            convex: _,
+            obb,
             poi,
             surrogate,
         } = self;
@@ -261,6 +368,7 @@ impl TransformableFrom for SimplePolygon {
             p.transform_from(ref_p, t);
         }
 
+        obb.transform_from(&reference.obb, t);
         poi.transform_from(&reference.poi, t);
 
         //transform the surrogate
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Resolves the paths of the DXF and SVG files referenced by the items of an instance.
+/// <br>
+/// Before resolving, environment variables in a path (`$VAR` or `${VAR}`) are expanded,
+/// so instances can refer to a shared drive which is mounted in a different place on every machine (e.g. `${PARTS}/bracket.dxf`).
+/// Absolute paths are used as they are.
+/// Relative paths are looked up in the search folders, in order, and the first folder containing the file is used.
+/// <br>
+/// Files can also be embedded: their contents are then used instead of a file on disk,
+/// so instances can be parsed without filesystem access (e.g. in a web service or a WASM build).
+#[derive(Clone, Debug, Default)]
+pub struct AssetPaths {
+    search_folders: Vec<PathBuf>,
+    embedded: BTreeMap<String, Arc<[u8]>>,
+}
+
+impl AssetPaths {
+    /// Resolves relative paths against `assets_folder` only
+    pub fn new(assets_folder: PathBuf) -> Self {
+        Self {
+            search_folders: vec![assets_folder],
+            embedded: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a folder to search after the existing ones
+    pub fn with_search_folder(mut self, folder: PathBuf) -> Self {
+        self.search_folders.push(folder);
+        self
+    }
+
+    /// Uses `contents` for the file referred to by `name` (exactly as in the `Dxf` or `Svg` of an item),
+    /// instead of looking it up on disk
+    pub fn with_embedded_asset(mut self, name: String, contents: Arc<[u8]>) -> Self {
+        self.embedded.insert(name, contents);
+        self
+    }
+
+    pub fn search_folders(&self) -> &[PathBuf] {
+        &self.search_folders
+    }
+
+    /// The contents of the file referred to by `name`, if it is embedded
+    pub fn embedded_asset(&self, name: &str) -> Option<&Arc<[u8]>> {
+        self.embedded.get(name)
+    }
+
+    /// The contents of the file referred to by `path`: the embedded contents, or those of the file it resolves to
+    pub fn read(&self, path: &str) -> io::Result<Arc<[u8]>> {
+        match self.embedded_asset(path) {
+            Some(contents) => Ok(contents.clone()),
+            None => std::fs::read(self.resolve(path)?).map(Arc::from),
+        }
+    }
+
+    /// The path of the file referred to by `path`.
+    /// If a relative path exists in none of the search folders, the error mentions the path in the first one
+    pub fn resolve(&self, path: &str) -> io::Result<PathBuf> {
+        let path = PathBuf::from(expand_env_vars(path)?);
+        if path.is_absolute() {
+            return Ok(path);
+        }
+        let candidates = match self.search_folders.is_empty() {
+            true => vec![path.clone()],
+            false => self.search_folders.iter().map(|f| f.join(&path)).collect(),
+        };
+        match candidates.iter().find(|c| c.is_file()) {
+            Some(found) => Ok(found.clone()),
+            None if candidates.len() == 1 => Ok(candidates[0].clone()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "{} not found in any of the search folders {}",
+                    path.display(),
+                    display_folders(&self.search_folders)
+                ),
+            )),
+        }
+    }
+
+    /// The path `path` would resolve to if it only had to be looked up in the first search folder.
+    /// When [`Self::resolve`] returns another path, the file was found elsewhere
+    pub fn default_path(&self, path: &str) -> PathBuf {
+        match self.search_folders.first() {
+            Some(folder) => folder.join(path),
+            None => PathBuf::from(path),
+        }
+    }
+}
+
+impl From<&Path> for AssetPaths {
+    fn from(assets_folder: &Path) -> Self {
+        Self::new(assets_folder.to_path_buf())
+    }
+}
+
+/// Replaces `$VAR` and `${VAR}` by the value of the environment variable `VAR`.
+/// A `$` which is not followed by a variable name is kept.
+/// Fails if a variable is not defined, rather than silently resolving to another file
+pub fn expand_env_vars(path: &str) -> io::Result<String> {
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("unclosed ${{ in {}", path),
+                    ))
+                }
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        if name.is_empty() {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let value = std::env::var(name).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("environment variable {} in {} is not defined", name, path),
+            )
+        })?;
+        expanded.push_str(&value);
+        rest = &after[len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn display_folders(folders: &[PathBuf]) -> String {
+    folders
+        .iter()
+        .map(|f| f.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,134 @@
+use dxf::entities::{Entity, EntityType, LwPolyline};
+use dxf::enums::AcadVersion;
+use dxf::tables::Layer;
+use dxf::{Drawing, LwPolylineVertex};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::instances::instance::Instance;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::item::Item;
+use crate::entities::layout::LayoutSnapshot;
+use crate::fsize;
+use crate::geometry::geo_traits::Transformable;
+use crate::geometry::primitives::arc_polygon::ArcPolygon;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+use crate::geometry::transformation::Transformation;
+use crate::util::units::{self, Units};
+
+/// Layer on which the contour of the bin and its holes are drawn
+pub const BIN_LAYER: &str = "BIN";
+
+/// How the placed items of an exported layout are divided over layers
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DxfLayering {
+    /// Every item has its own layer, `ITEM_<id>`
+    #[default]
+    PerItem,
+    /// Items are grouped by the quality they require, `QUALITY_<q>`, or `QUALITY_FULL` for items requiring full quality
+    PerQuality,
+}
+
+impl DxfLayering {
+    /// Name of the layer on which `item` is drawn
+    pub fn layer(&self, item: &Item) -> String {
+        match (self, item.base_quality) {
+            (DxfLayering::PerItem, _) => format!("ITEM_{}", item.id),
+            (DxfLayering::PerQuality, Some(q)) => format!("QUALITY_{}", q),
+            (DxfLayering::PerQuality, None) => "QUALITY_FULL".to_string(),
+        }
+    }
+}
+
+/// Draws a layout as a DXF drawing in the coordinates (and units) of the input, so it can be loaded into CAM software.
+/// Every placed item is a closed polyline at its absolute transformation, using the exact contour (with arcs) of the item if available.
+/// The contour of the bin and its holes are drawn on [`BIN_LAYER`].
+pub fn layout_to_dxf(
+    s_layout: &LayoutSnapshot,
+    instance: &Instance,
+    layering: DxfLayering,
+) -> Drawing {
+    let mut drawing = Drawing::new();
+    //lightweight polylines require at least R14
+    drawing.header.version = AcadVersion::R2000;
+    drawing.header.default_drawing_units = match instance.units() {
+        Some(Units::Millimeter) => dxf::enums::Units::Millimeters,
+        Some(Units::Inch) => dxf::enums::Units::Inches,
+        Some(Units::Scale(_)) | None => dxf::enums::Units::Unitless,
+    };
+    let to_input = 1.0 / instance.unit_scale();
+
+    let inv_bin_transf = s_layout.bin.pretransform.clone().inverse();
+
+    let mut layers = vec![BIN_LAYER.to_string()];
+    let bin_contours = [&s_layout.bin.outer]
+        .into_iter()
+        .chain(s_layout.bin.holes.iter());
+    for contour in bin_contours {
+        let contour = contour.transform_clone(&inv_bin_transf);
+        drawing.add_entity(lw_polyline_entity(
+            &units::scaled_arc_polygon(&simple_polygon_to_arc_polygon(&contour), to_input),
+            BIN_LAYER,
+        ));
+    }
+
+    for pi in s_layout.placed_items.values() {
+        let item = instance.item(pi.item_id);
+        //from the internal coordinates of the item to the coordinates of the input
+        let transf = Transformation::from_dt(&pi.d_transf).transform(&inv_bin_transf);
+        let contour = match &item.arc_shape {
+            Some(arc_shape) => arc_shape.transform_clone(&transf),
+            None => simple_polygon_to_arc_polygon(&item.shape.transform_clone(&transf)),
+        };
+        let contour = units::scaled_arc_polygon(&contour, to_input);
+        let layer = layering.layer(item);
+        drawing.add_entity(lw_polyline_entity(&contour, &layer));
+        if !layers.contains(&layer) {
+            layers.push(layer);
+        }
+    }
+
+    for name in layers {
+        drawing.add_layer(Layer {
+            name,
+            ..Default::default()
+        });
+    }
+
+    drawing
+}
+
+fn lw_polyline_entity(contour: &ArcPolygon, layer: &str) -> Entity {
+    let mut lw_polyline = LwPolyline {
+        vertices: contour
+            .points
+            .iter()
+            .zip(contour.bulges.iter())
+            .map(|(p, bulge)| LwPolylineVertex {
+                x: dxf_float(p.0),
+                y: dxf_float(p.1),
+                bulge: dxf_float(*bulge),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    lw_polyline.set_is_closed(true);
+
+    let mut entity = Entity::new(EntityType::LwPolyline(lw_polyline));
+    entity.common.layer = layer.to_string();
+    entity
+}
+
+/// DXF files always hold double precision values, which is a no-op conversion if [`fsize`] is `f64`
+#[cfg_attr(feature = "double-precision", allow(clippy::useless_conversion))]
+fn dxf_float(value: fsize) -> f64 {
+    f64::from(value)
+}
+
+fn simple_polygon_to_arc_polygon(polygon: &SimplePolygon) -> ArcPolygon {
+    ArcPolygon::new(
+        polygon.points.clone(),
+        vec![0.0; polygon.number_of_points()],
+    )
+}
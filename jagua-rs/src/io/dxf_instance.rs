@@ -21,7 +21,6 @@ pub struct DxfInstance {
     pub strip: Option<DxfStrip>,
 }
 
-
 /// The JSON representation of a bin
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
@@ -99,4 +98,4 @@ pub struct DxfQualityZone {
     pub quality: usize,
     /// The polygon shape of this zone
     pub shape: EntityType,
-}
\ No newline at end of file
+}
@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Maps the layers of DXF files to the role their contours play, e.g. for drawings in which the cut contour is on `CUT`,
+/// the holes on `HOLE` and the defects of a sheet on `DEFECT`.
+/// Layer names are compared case-insensitively, like in CAD software.
+/// <br>
+/// The default mapping treats all layers as outline layers: the largest contour is the outline and the contours inside it are holes.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct DxfLayerMapping {
+    /// Layers of the outlines of items and bins. If empty, all layers which are not mapped to another role
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outline: Vec<String>,
+    /// Layers of the holes of items and bins, in addition to the contours inside the outline on the outline layers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holes: Vec<String>,
+    /// Layers of the inferior quality zones of bins, with the quality of their zones (e.g. `DEFECT` to 0, `Q1` to 1).
+    /// Ignored for items
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub quality_zones: BTreeMap<String, usize>,
+}
+
+/// Role of the contours on a layer, see [`DxfLayerMapping`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerRole {
+    Outline,
+    Hole,
+    QualityZone(usize),
+    Ignored,
+}
+
+impl DxfLayerMapping {
+    /// Role of the contours on `layer`.
+    /// If `outline_layer` is defined (the `DxfLayer` of an item), it is the only outline layer
+    pub fn role(&self, layer: &str, outline_layer: Option<&str>) -> LayerRole {
+        let matches = |name: &String| name.eq_ignore_ascii_case(layer);
+        if self.holes.iter().any(matches) {
+            return LayerRole::Hole;
+        }
+        if let Some((_, quality)) = self.quality_zones.iter().find(|(name, _)| matches(name)) {
+            return LayerRole::QualityZone(*quality);
+        }
+        let is_outline = match outline_layer {
+            Some(outline_layer) => outline_layer == layer,
+            None => self.outline.is_empty() || self.outline.iter().any(matches),
+        };
+        match is_outline {
+            true => LayerRole::Outline,
+            false => LayerRole::Ignored,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::fsize;
+
+/// Name of the XDATA application under which item metadata can be stored in a DXF file
+pub const XDATA_APPLICATION_NAME: &str = "JAGUA";
+
+/// Metadata of an item which is embedded in a DXF file.
+/// Can be defined in the layer name of the entity (e.g. `PART_qty5_rot90_val3`)
+/// or as XDATA strings (e.g. `qty=5`, `rot=90`, `val=3`) under the [`XDATA_APPLICATION_NAME`] application.
+/// A single rotation defines the step between allowed orientations (`rot90` allows 0, 90, 180 and 270 degrees), which should evenly divide 360°,
+/// multiple comma separated rotations define the allowed orientations explicitly (`rot0,180`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DxfItemMetadata {
+    pub demand: Option<u64>,
+    pub allowed_orientations: Option<Vec<fsize>>,
+    pub value: Option<u64>,
+}
+
+/// Maximum number of orientations a single rotation step can define (a step of 0.1°)
+pub const MAX_ROTATION_STEPS: usize = 3600;
+
+impl DxfItemMetadata {
+    /// Fails if the layer name defines a rotation step which does not evenly divide 360° in at most [`MAX_ROTATION_STEPS`] steps
+    pub fn from_layer_name(layer: &str) -> Result<Self, String> {
+        let mut metadata = Self::default();
+        for token in layer.split('_') {
+            let split_idx = token
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(token.len());
+            let (key, value) = token.split_at(split_idx);
+            if !value.is_empty() {
+                metadata.set(key, value)?;
+            }
+        }
+        Ok(metadata)
+    }
+
+    pub(crate) fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key.to_ascii_lowercase().as_str() {
+            "qty" | "demand" => self.demand = parse_metadata_value(key, value),
+            "val" | "value" => self.value = parse_metadata_value(key, value),
+            "rot" | "rotation" => {
+                let angles: Option<Vec<fsize>> = value
+                    .split(',')
+                    .map(|a| parse_metadata_value(key, a.trim()))
+                    .collect();
+                self.allowed_orientations = match angles.as_deref() {
+                    Some([step]) if *step > 0.0 => Some(rotation_steps(*step)?),
+                    _ => angles,
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Orientations spaced `step` degrees apart, the step should evenly divide 360° in at most [`MAX_ROTATION_STEPS`] steps
+fn rotation_steps(step: fsize) -> Result<Vec<fsize>, String> {
+    let n_steps = (360.0 / step).round();
+    if !(1.0..=MAX_ROTATION_STEPS as fsize).contains(&n_steps) {
+        return Err(format!(
+            "rotation step of {}° should define between 1 and {} orientations",
+            step, MAX_ROTATION_STEPS
+        ));
+    }
+    if (n_steps * step - 360.0).abs() > 1e-3 {
+        return Err(format!(
+            "rotation step of {}° does not evenly divide 360°",
+            step
+        ));
+    }
+    let n_steps = n_steps as usize;
+    Ok((0..n_steps).map(|i| i as fsize * step).collect())
+}
+
+fn parse_metadata_value<T: std::str::FromStr>(key: &str, value: &str) -> Option<T> {
+    match value.parse() {
+        Ok(v) => Some(v),
+        Err(_) => {
+            warn!(
+                "[PARSE] could not parse value \"{}\" of DXF metadata key \"{}\"",
+                value, key
+            );
+            None
+        }
+    }
+}
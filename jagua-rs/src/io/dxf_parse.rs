@@ -1,13 +1,23 @@
-use dxf::Drawing;
 use dxf::entities::*;
+use dxf::{Drawing, XData, XDataItem};
 
+use crate::io::dxf_layers::{DxfLayerMapping, LayerRole};
+use crate::io::json_instance::JsonBin;
 use crate::io::json_instance::JsonInstance;
 use crate::io::json_instance::JsonStrip;
-use crate::io::json_instance::JsonBin;
+use crate::io::parser;
 
+use itertools::Itertools;
 use log::error;
 
 use crate::fsize;
+use crate::geometry::primitives::arc_polygon::ArcPolygon;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+pub use crate::io::dxf_metadata::{DxfItemMetadata, XDATA_APPLICATION_NAME};
+use crate::util::config::GeometricFidelity;
+use crate::util::polygon_holes;
+use crate::PI;
 
 pub struct DxfInstance {
     /// The name of the instance
@@ -22,7 +32,7 @@ pub struct DxfInstance {
 
 pub struct DxfItem {
     /// Number of times this item should be produced
-    pub demand: u64,
+    pub demand: Option<u64>,
     /// List of allowed orientations angles (in degrees). If none any orientation is allowed
     pub allowed_orientations: Option<Vec<fsize>>,
     /// Polygon shape of the item
@@ -33,8 +43,6 @@ pub struct DxfItem {
     pub base_quality: Option<usize>,
 }
 
-
-
 pub fn parse_dxf(json_with_dxf_instance: &JsonInstance) -> DxfInstance {
     let mut dxf_items = Vec::new();
 
@@ -63,7 +71,7 @@ pub fn parse_dxf(json_with_dxf_instance: &JsonInstance) -> DxfInstance {
         for e in drawing.entities() {
             println!("found entity on layer {}", e.common.layer);
             let dxf_item = DxfItem {
-                demand: demand,
+                demand,
                 allowed_orientations: allowed_orientations.clone(),
                 shape: e.specific.clone(),
                 value: item.value,
@@ -71,13 +79,503 @@ pub fn parse_dxf(json_with_dxf_instance: &JsonInstance) -> DxfInstance {
             };
 
             dxf_items.push(dxf_item);
-        }  
+        }
     }
-    
+
     DxfInstance {
         name: json_with_dxf_instance.name.clone(),
         items: dxf_items,
         bins: json_with_dxf_instance.bins.clone(),
         strip: json_with_dxf_instance.strip.clone(),
     }
-}
\ No newline at end of file
+}
+
+/// Finds the first polyline in a DXF drawing, optionally restricted to a specific layer and/or block.
+/// Returns the polyline together with the entity containing it.
+pub fn find_poly_line<'a>(
+    drawing: &'a Drawing,
+    layer: Option<&str>,
+    block: Option<&str>,
+) -> Option<(&'a Entity, &'a LwPolyline)> {
+    let entities: Box<dyn Iterator<Item = &Entity>> = match block {
+        Some(block) => Box::new(drawing.blocks().find(|b| b.name == block)?.entities.iter()),
+        None => Box::new(drawing.entities()),
+    };
+
+    entities
+        .filter(|e| layer.is_none_or(|layer| e.common.layer == layer))
+        .find_map(|e| match &e.specific {
+            EntityType::LwPolyline(lwp) => Some((e, lwp)),
+            _ => None,
+        })
+}
+
+/// Relative distance (to the extent of the curves) within which the ends of two curve entities are considered connected
+const CHAIN_TOLERANCE: fsize = 1e-5;
+
+/// Number of millimeters in one unit of the drawing, as declared by its `$INSUNITS` header.
+/// None for unitless drawings, and for units which make no sense for parts (astronomical units, ...)
+pub fn mm_per_unit(drawing: &Drawing) -> Option<fsize> {
+    use dxf::enums::Units;
+    match drawing.header.default_drawing_units {
+        Units::Inches | Units::USSurveyInch => Some(25.4),
+        Units::Feet | Units::USSurveyFeet => Some(304.8),
+        Units::Yards | Units::USSurveyYard => Some(914.4),
+        Units::Mils => Some(0.0254),
+        Units::Microinches => Some(2.54e-5),
+        Units::Millimeters => Some(1.0),
+        Units::Centimeters => Some(10.0),
+        Units::Decimeters => Some(100.0),
+        Units::Meters => Some(1000.0),
+        Units::Microns => Some(1e-3),
+        Units::Nanometers => Some(1e-6),
+        _ => None,
+    }
+}
+
+/// Finds the outline of an item or bin in a DXF drawing, among the entities on the outline layers of the mapping
+/// (only `layer`, if defined), optionally restricted to a block.
+/// The first `LwPolyline` is used if there is one, otherwise the closed contour enclosing the largest area
+/// among the `Polyline` and `Circle` entities and the contours formed by connecting `Line`, `Arc` and `Spline` entities end to end.
+/// Returns the contour together with the entity its metadata is read from (the first entity of a connected contour).
+pub fn find_outline<'a>(
+    drawing: &'a Drawing,
+    mapping: &DxfLayerMapping,
+    layer: Option<&str>,
+    block: Option<&str>,
+    fidelity: GeometricFidelity,
+) -> Option<(&'a Entity, ArcPolygon)> {
+    let entities = filtered_entities(drawing, block, |e| {
+        mapping.role(&e.common.layer, layer) == LayerRole::Outline
+    })?;
+    if let Some((entity, lwp)) = entities.iter().find_map(|e| match &e.specific {
+        EntityType::LwPolyline(lwp) => Some((*e, lwp)),
+        _ => None,
+    }) {
+        return Some((entity, parser::dxf_poly_line_to_arc_polygon(lwp)));
+    }
+
+    let (contours, _) = closed_contours(entities.into_iter(), fidelity);
+    //contours with a NaN area (from malformed coordinates) are never the outline
+    contours
+        .into_iter()
+        .filter(|(_, c)| !c.signed_area().is_nan())
+        .max_by(|(_, a), (_, b)| a.signed_area().abs().total_cmp(&b.signed_area().abs()))
+}
+
+/// Finds the holes of an outline found by [`find_outline`] (with the same mapping, layer and block):
+/// the other closed contours on the outline layers and the contours on the hole layers, including `LwPolyline`s,
+/// which lie inside the outline but not inside another hole.
+pub fn find_holes(
+    drawing: &Drawing,
+    mapping: &DxfLayerMapping,
+    layer: Option<&str>,
+    block: Option<&str>,
+    (outline_entity, outline): (&Entity, &ArcPolygon),
+    fidelity: GeometricFidelity,
+) -> Vec<ArcPolygon> {
+    let Some(entities) = filtered_entities(drawing, block, |e| {
+        matches!(
+            mapping.role(&e.common.layer, layer),
+            LayerRole::Outline | LayerRole::Hole
+        )
+    }) else {
+        return vec![];
+    };
+    let contours = all_contours(&entities, fidelity)
+        .into_iter()
+        .filter(|(e, _)| !std::ptr::eq(*e, outline_entity))
+        .map(|(_, contour)| contour)
+        .collect_vec();
+
+    let contour_points = contours.iter().map(|c| c.points.clone()).collect_vec();
+    let hole_indices = polygon_holes::holes_of(&outline.points, &contour_points);
+    contours
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| hole_indices.contains(i))
+        .map(|(_, contour)| contour)
+        .collect()
+}
+
+/// Finds the closed contours on the quality zone layers of the mapping, including `LwPolyline`s, with the quality of their layer
+pub fn find_quality_zones(
+    drawing: &Drawing,
+    mapping: &DxfLayerMapping,
+    block: Option<&str>,
+    fidelity: GeometricFidelity,
+) -> Vec<(usize, ArcPolygon)> {
+    let Some(entities) = filtered_entities(drawing, block, |e| {
+        matches!(
+            mapping.role(&e.common.layer, None),
+            LayerRole::QualityZone(_)
+        )
+    }) else {
+        return vec![];
+    };
+    all_contours(&entities, fidelity)
+        .into_iter()
+        .filter_map(|(e, contour)| match mapping.role(&e.common.layer, None) {
+            LayerRole::QualityZone(quality) => Some((quality, contour)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Entities of the drawing, or of one of its blocks, which satisfy `keep`. `None` if the block does not exist
+fn filtered_entities<'a>(
+    drawing: &'a Drawing,
+    block: Option<&str>,
+    keep: impl Fn(&Entity) -> bool,
+) -> Option<Vec<&'a Entity>> {
+    let entities: Vec<&Entity> = match block {
+        Some(block) => drawing
+            .blocks()
+            .find(|b| b.name == block)?
+            .entities
+            .iter()
+            .collect(),
+        None => drawing.entities().collect(),
+    };
+    Some(entities.into_iter().filter(|e| keep(e)).collect())
+}
+
+/// The closed contours of [`closed_contours`], followed by those of the `LwPolyline`s
+fn all_contours<'a>(
+    entities: &[&'a Entity],
+    fidelity: GeometricFidelity,
+) -> Vec<(&'a Entity, ArcPolygon)> {
+    let poly_lines = entities.iter().filter_map(|e| match &e.specific {
+        EntityType::LwPolyline(lwp) if lwp.vertices.len() >= 3 => {
+            Some((*e, parser::dxf_poly_line_to_arc_polygon(lwp)))
+        }
+        _ => None,
+    });
+    let (contours, _) = closed_contours(entities.iter().copied(), fidelity);
+    contours.into_iter().chain(poly_lines).collect()
+}
+
+/// All closed contours defined by `Polyline` and `Circle` entities, or by connecting `Line`, `Arc` and `Spline` entities end to end.
+/// Splines are tessellated by chords which deviate at most as much from the curve as the `fidelity` allows.
+/// Also returns the curve entities which are not part of a closed contour.
+pub fn closed_contours<'a>(
+    entities: impl Iterator<Item = &'a Entity>,
+    fidelity: GeometricFidelity,
+) -> (Vec<(&'a Entity, ArcPolygon)>, Vec<&'a Entity>) {
+    let mut contours = vec![];
+    let mut curves = vec![];
+    for entity in entities {
+        match &entity.specific {
+            EntityType::Polyline(pl) => {
+                let (points, bulges) = pl
+                    .vertices()
+                    .map(|v| {
+                        (
+                            Point(v.location.x as fsize, v.location.y as fsize),
+                            v.bulge as fsize,
+                        )
+                    })
+                    .unzip::<_, _, Vec<_>, Vec<_>>();
+                if let Some(contour) = closed_arc_polygon(points, bulges) {
+                    contours.push((entity, contour));
+                }
+            }
+            EntityType::Circle(circle) if circle.radius > 0.0 => {
+                //four quarter arcs
+                let Point(cx, cy) = ocs_point(circle.center.x, circle.center.y, &circle.normal);
+                let r = circle.radius as fsize;
+                let points = vec![
+                    Point(cx + r, cy),
+                    Point(cx, cy + r),
+                    Point(cx - r, cy),
+                    Point(cx, cy - r),
+                ];
+                let bulge = (PI / 8.0).tan();
+                contours.push((entity, ArcPolygon::new(points, vec![bulge; 4])));
+            }
+            _ => {
+                if let Some(curve) = Curve::from_entity(entity, fidelity) {
+                    curves.push(curve);
+                }
+            }
+        }
+    }
+
+    let extent = curves
+        .iter()
+        .flat_map(|c| c.points.iter().copied())
+        .collect_vec();
+    let tolerance = match extent.is_empty() {
+        true => 0.0,
+        false => {
+            let bbox = SimplePolygon::generate_bounding_box(&extent);
+            CHAIN_TOLERANCE * fsize::max(bbox.width(), bbox.height())
+        }
+    };
+    let (chains, loose) = connect_curves(curves, tolerance);
+    contours.extend(chains);
+    (contours, loose)
+}
+
+/// Curve entity (`Line`, `Arc` or `Spline`) as a sequence of points, connected by straight edges or arcs
+struct Curve<'a> {
+    entity: &'a Entity,
+    points: Vec<Point>,
+    /// Bulge of the edge between every point and the next one, see [`ArcPolygon`]
+    bulges: Vec<fsize>,
+}
+
+impl<'a> Curve<'a> {
+    fn from_entity(entity: &'a Entity, fidelity: GeometricFidelity) -> Option<Self> {
+        let (points, bulges) = match &entity.specific {
+            EntityType::Line(line) => (
+                vec![
+                    Point(line.p1.x as fsize, line.p1.y as fsize),
+                    Point(line.p2.x as fsize, line.p2.y as fsize),
+                ],
+                vec![0.0],
+            ),
+            EntityType::Arc(arc) if arc.radius > 0.0 => {
+                //arcs run counterclockwise from the start to the end angle
+                let sweep = match (arc.end_angle - arc.start_angle).rem_euclid(360.0) {
+                    0.0 => 360.0,
+                    sweep => sweep,
+                };
+                let (start, sweep) = (arc.start_angle.to_radians(), sweep.to_radians());
+                //split in arcs of at most a quarter turn
+                let n_parts = (sweep / std::f64::consts::FRAC_PI_2 - 1e-9).ceil().max(1.0) as usize;
+                let points = (0..=n_parts)
+                    .map(|i| {
+                        let angle = start + sweep * i as f64 / n_parts as f64;
+                        ocs_point(
+                            arc.center.x + arc.radius * angle.cos(),
+                            arc.center.y + arc.radius * angle.sin(),
+                            &arc.normal,
+                        )
+                    })
+                    .collect_vec();
+                //arcs in a mirrored coordinate system run clockwise
+                let orientation = if arc.normal.z < 0.0 { -1.0 } else { 1.0 };
+                let bulge = orientation * (sweep as fsize / n_parts as fsize / 4.0).tan();
+                (points, vec![bulge; n_parts])
+            }
+            EntityType::Spline(spline) => {
+                let points = spline_points(spline, fidelity)?;
+                let bulges = vec![0.0; points.len() - 1];
+                (points, bulges)
+            }
+            _ => return None,
+        };
+        Some(Curve {
+            entity,
+            points,
+            bulges,
+        })
+    }
+
+    fn start(&self) -> Point {
+        self.points[0]
+    }
+
+    fn end(&self) -> Point {
+        self.points[self.points.len() - 1]
+    }
+
+    fn reverse(&mut self) {
+        self.points.reverse();
+        self.bulges.reverse();
+        self.bulges.iter_mut().for_each(|b| *b = -*b);
+    }
+}
+
+/// Connects curves end to end (reversing them where needed) into closed contours.
+/// Curves which cannot be part of a closed contour are returned separately
+fn connect_curves(
+    mut curves: Vec<Curve<'_>>,
+    tolerance: fsize,
+) -> (Vec<(&Entity, ArcPolygon)>, Vec<&Entity>) {
+    let mut contours = vec![];
+    let mut loose = vec![];
+    while !curves.is_empty() {
+        let mut chain = vec![curves.swap_remove(0)];
+        loop {
+            let (start, end) = (chain[0].start(), chain[chain.len() - 1].end());
+            let closable = chain.len() > 1 || chain[0].points.len() > 2;
+            if closable && start.distance(end) <= tolerance {
+                break;
+            }
+            let next = curves.iter().position(|c| {
+                c.start().distance(end) <= tolerance || c.end().distance(end) <= tolerance
+            });
+            match next {
+                Some(i) => {
+                    let mut curve = curves.swap_remove(i);
+                    if curve.start().distance(end) > tolerance {
+                        curve.reverse();
+                    }
+                    chain.push(curve);
+                }
+                None => {
+                    loose.extend(chain.iter().map(|c| c.entity));
+                    chain.clear();
+                    break;
+                }
+            }
+        }
+        if chain.is_empty() {
+            continue;
+        }
+        //every curve contributes all its points except the last, which is the first of the next one
+        let (points, bulges) = chain
+            .iter()
+            .flat_map(|c| {
+                let n = c.points.len() - 1;
+                c.points[..n].iter().copied().zip(c.bulges.iter().copied())
+            })
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+        match closed_arc_polygon(points, bulges) {
+            Some(contour) => contours.push((chain[0].entity, contour)),
+            None => loose.extend(chain.iter().map(|c| c.entity)),
+        }
+    }
+    (contours, loose)
+}
+
+/// Arc polygon of a closed sequence of points and bulges, if it encloses an area.
+/// The last point is dropped if it coincides with the first
+fn closed_arc_polygon(mut points: Vec<Point>, mut bulges: Vec<fsize>) -> Option<ArcPolygon> {
+    if points.len() > 1 && points[0] == points[points.len() - 1] {
+        points.pop();
+        bulges.pop();
+    }
+    let n_arcs = bulges.iter().filter(|b| **b != 0.0).count();
+    if points.len() + n_arcs < 3 && !(points.len() == 2 && n_arcs > 0) {
+        return None;
+    }
+    let contour = ArcPolygon::new(points, bulges);
+    (contour.signed_area() != 0.0).then_some(contour)
+}
+
+/// Converts a point in the object coordinate system of an entity to world coordinates.
+/// Only the common case of 2D drawings is supported: the extrusion direction is either +Z or -Z (mirrored)
+fn ocs_point(x: f64, y: f64, normal: &dxf::Vector) -> Point {
+    match normal.z < 0.0 {
+        true => Point(-x as fsize, y as fsize),
+        false => Point(x as fsize, y as fsize),
+    }
+}
+
+/// Points on a spline, with chords which do not deviate more from the curve than the `fidelity` allows (relative to the extent of its control points).
+/// Splines without control points are approximated by their fit points
+fn spline_points(spline: &Spline, fidelity: GeometricFidelity) -> Option<Vec<Point>> {
+    let to_point = |p: &dxf::Point| Point(p.x as fsize, p.y as fsize);
+    let control_points = spline.control_points.iter().map(to_point).collect_vec();
+    let degree = spline.degree_of_curve.max(1) as usize;
+    let knots = spline.knot_values.iter().map(|k| *k as fsize).collect_vec();
+    if control_points.len() <= degree || knots.len() != control_points.len() + degree + 1 {
+        let fit_points = spline.fit_points.iter().map(to_point).collect_vec();
+        return (fit_points.len() >= 2).then_some(fit_points);
+    }
+
+    let bbox = SimplePolygon::generate_bounding_box(&control_points);
+    let max_deviation = fidelity.max_deviation(fsize::max(bbox.width(), bbox.height()));
+    let eval = |t: fsize| de_boor(&control_points, &knots, degree, t);
+    let (t_start, t_end) = (knots[degree], knots[control_points.len()]);
+
+    //every span between knots is subdivided until the chords are close enough to the curve
+    let mut points = vec![eval(t_start)];
+    for (&k0, &k1) in knots[degree..=control_points.len()].iter().tuple_windows() {
+        if k1 > k0 {
+            subdivide(&eval, (k0, k1), max_deviation, 0, &mut points);
+        }
+    }
+    debug_assert!(points[points.len() - 1].distance(eval(t_end)) <= max_deviation);
+    points.dedup();
+    (points.len() >= 2).then_some(points)
+}
+
+/// Maximum depth of the subdivision of a spline span
+const MAX_SUBDIVISION_DEPTH: usize = 12;
+
+/// Appends the points of the curve `eval` in `(t0, t1]`, halving the interval until the midpoint of the curve is within `max_deviation` of the chord.
+/// Spans are split at least twice, so inflections within a span are not missed
+fn subdivide(
+    eval: &impl Fn(fsize) -> Point,
+    (t0, t1): (fsize, fsize),
+    max_deviation: fsize,
+    depth: usize,
+    points: &mut Vec<Point>,
+) {
+    let (p0, p1) = (eval(t0), eval(t1));
+    let t_mid = (t0 + t1) / 2.0;
+    let mid = eval(t_mid);
+    let chord_mid = Point((p0.0 + p1.0) / 2.0, (p0.1 + p1.1) / 2.0);
+    if depth < MAX_SUBDIVISION_DEPTH && (depth < 2 || mid.distance(chord_mid) > max_deviation) {
+        subdivide(eval, (t0, t_mid), max_deviation, depth + 1, points);
+        subdivide(eval, (t_mid, t1), max_deviation, depth + 1, points);
+    } else {
+        points.push(p1);
+    }
+}
+
+/// Evaluates a (non-rational) B-spline at parameter `t` with de Boor's algorithm
+fn de_boor(control_points: &[Point], knots: &[fsize], degree: usize, t: fsize) -> Point {
+    let n = control_points.len();
+    //index of the knot span containing t, the last span includes its end
+    let k = (degree..n)
+        .rev()
+        .find(|&i| knots[i] <= t && knots[i] < knots[i + 1])
+        .unwrap_or(degree);
+    let mut d = (0..=degree)
+        .map(|j| control_points[j + k - degree])
+        .collect_vec();
+    for r in 1..=degree {
+        for j in (r..=degree).rev() {
+            let (k0, k1) = (knots[j + k - degree], knots[j + 1 + k - r]);
+            let alpha = match k1 > k0 {
+                true => (t - k0) / (k1 - k0),
+                false => 0.0,
+            };
+            d[j] = Point(
+                (1.0 - alpha) * d[j - 1].0 + alpha * d[j].0,
+                (1.0 - alpha) * d[j - 1].1 + alpha * d[j].1,
+            );
+        }
+    }
+    d[degree]
+}
+
+impl DxfItemMetadata {
+    /// Reads the metadata from the layer name and XDATA of an entity, XDATA takes precedence over the layer name.
+    /// Fails if either defines an invalid rotation step, see [`DxfItemMetadata::from_layer_name`].
+    pub fn from_entity(entity: &Entity) -> Result<Self, String> {
+        let from_layer = Self::from_layer_name(&entity.common.layer)?;
+        let from_xdata = Self::from_x_data(&entity.common.x_data)?;
+        Ok(Self {
+            demand: from_xdata.demand.or(from_layer.demand),
+            allowed_orientations: from_xdata
+                .allowed_orientations
+                .or(from_layer.allowed_orientations),
+            value: from_xdata.value.or(from_layer.value),
+        })
+    }
+
+    pub fn from_x_data(x_data: &[XData]) -> Result<Self, String> {
+        let mut metadata = Self::default();
+        for item in x_data
+            .iter()
+            .filter(|xd| {
+                xd.application_name
+                    .eq_ignore_ascii_case(XDATA_APPLICATION_NAME)
+            })
+            .flat_map(|xd| xd.items.iter())
+        {
+            if let XDataItem::Str(s) = item {
+                if let Some((key, value)) = s.split_once('=') {
+                    metadata.set(key.trim(), value.trim())?;
+                }
+            }
+        }
+        Ok(metadata)
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+use log::{log, Level};
+
+use crate::fsize;
+use crate::geometry::geo_traits::Shape;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+use crate::io::json_instance::{
+    JsonBin, JsonInstance, JsonItem, JsonShape, JsonSimplePoly, JsonStrip,
+};
+use crate::io::xml_scan::{self, xml_events, XmlEvent};
+
+/// Piece of the `<boards>` or `<lot>` of an ESICUP problem
+struct EsicupPiece {
+    id: String,
+    quantity: u64,
+    /// Allowed orientations in degrees, empty if the piece can be rotated freely
+    orientations: Vec<fsize>,
+    /// Polygon id and offset of every component
+    components: Vec<(String, (fsize, fsize))>,
+}
+
+/// Builds an instance from a nesting problem in the XML format of the ESICUP datasets
+/// (ALBANO, DAGLI, FU, JAKOBS, MAO, MARQUES, SHAPES, SHIRTS, SWIM, TROUSERS, ...).
+/// Every piece of the `<lot>` becomes an item with its quantity as demand and the angles of its `<orientation>` as allowed orientations (any rotation if it lists none).
+/// A single board with quantity 1 defines a strip: its shorter side is the height of the strip.
+/// Boards of which the shorter side is horizontal are transposed (together with all items),
+/// so the length of the board always runs along the width of the strip.
+/// Otherwise, every board becomes a bin with its quantity as stock, at a cost equal to its area.
+/// Returns a description of the problem if the document is malformed or uses unsupported features,
+/// such as pieces composed of multiple polygons.
+pub fn esicup_instance(xml: &str) -> Result<JsonInstance, String> {
+    let mut name = None;
+    let mut boards = vec![];
+    let mut lot = vec![];
+    let mut polygons: HashMap<String, Vec<Point>> = HashMap::new();
+
+    //names of the open elements
+    let mut stack: Vec<&str> = vec![];
+    let mut piece: Option<EsicupPiece> = None;
+    let mut polygon: Option<(String, Vec<(usize, Point)>)> = None;
+    for event in xml_events(xml)? {
+        match event {
+            XmlEvent::Start {
+                name: tag,
+                attributes,
+                self_closing,
+            } => {
+                let tag = local_name(tag);
+                let attr = |key: &str| {
+                    xml_scan::attribute(&attributes, key)
+                        .ok_or_else(|| format!("<{}> without {} attribute", tag, key))
+                };
+                let number = |key: &str| {
+                    let value = attr(key)?;
+                    value
+                        .trim()
+                        .parse::<fsize>()
+                        .map_err(|_| format!("invalid {} of <{}>: {}", key, tag, value))
+                };
+                match (tag, piece.as_mut(), polygon.as_mut()) {
+                    ("piece", None, _) => {
+                        piece = Some(EsicupPiece {
+                            id: attr("id")?.to_string(),
+                            quantity: number("quantity")? as u64,
+                            orientations: vec![],
+                            components: vec![],
+                        });
+                    }
+                    ("enumeration", Some(p), _) => p.orientations.push(number("angle")?),
+                    ("component", Some(p), _) => {
+                        let offset = (
+                            xml_scan::attribute(&attributes, "xOffset")
+                                .map_or(Ok(0.0), |_| number("xOffset"))?,
+                            xml_scan::attribute(&attributes, "yOffset")
+                                .map_or(Ok(0.0), |_| number("yOffset"))?,
+                        );
+                        p.components.push((attr("idPolygon")?.to_string(), offset));
+                    }
+                    ("polygon", _, None) => polygon = Some((attr("id")?.to_string(), vec![])),
+                    ("segment", _, Some((_, vertices))) => {
+                        let n = number("n")? as usize;
+                        vertices.push((n, Point(number("x0")?, number("y0")?)));
+                    }
+                    _ => {}
+                }
+                if !self_closing {
+                    stack.push(tag);
+                }
+            }
+            XmlEvent::End { name: tag } => {
+                let tag = local_name(tag);
+                if stack.pop() != Some(tag) {
+                    return Err(format!("unbalanced closing tag: </{}>", tag));
+                }
+                match tag {
+                    "piece" => {
+                        let p = piece.take().ok_or("</piece> without <piece>")?;
+                        match stack.contains(&"boards") {
+                            true => boards.push(p),
+                            false => lot.push(p),
+                        }
+                    }
+                    "polygon" => {
+                        let (id, mut vertices) =
+                            polygon.take().ok_or("</polygon> without <polygon>")?;
+                        vertices.sort_by_key(|(n, _)| *n);
+                        polygons.insert(id, vertices.into_iter().map(|(_, p)| p).collect());
+                    }
+                    _ => {}
+                }
+            }
+            XmlEvent::Text(text) => {
+                if stack.len() == 2 && stack[1] == "name" {
+                    name = Some(text.to_string());
+                }
+            }
+        }
+    }
+    if let Some(tag) = stack.last() {
+        return Err(format!("unclosed element <{}>", tag));
+    }
+
+    let shape = |piece: &EsicupPiece| -> Result<Vec<Point>, String> {
+        let [(polygon_id, (dx, dy))] = &piece.components[..] else {
+            return Err(format!(
+                "piece {} consists of {} polygons, only pieces of a single polygon are supported",
+                piece.id,
+                piece.components.len()
+            ));
+        };
+        let vertices = polygons.get(polygon_id).ok_or_else(|| {
+            format!(
+                "piece {} refers to unknown polygon {}",
+                piece.id, polygon_id
+            )
+        })?;
+        match vertices.len() {
+            0..=2 => Err(format!("polygon {} has less than 3 vertices", polygon_id)),
+            _ => Ok(vertices.iter().map(|p| Point(p.0 + dx, p.1 + dy)).collect()),
+        }
+    };
+
+    let board_shapes = boards.iter().map(shape).collect::<Result<Vec<_>, _>>()?;
+    let strip_board = match (&boards[..], &board_shapes[..]) {
+        ([board], [board_shape]) if board.quantity == 1 => {
+            Some(SimplePolygon::generate_bounding_box(board_shape))
+        }
+        _ => None,
+    };
+    //a strip runs along the x-axis, so a board which is longer along the y-axis is transposed
+    let transposed = strip_board
+        .as_ref()
+        .is_some_and(|bbox| bbox.width() < bbox.height());
+    let to_json = |points: &[Point]| {
+        JsonSimplePoly(
+            points
+                .iter()
+                .map(|p| match transposed {
+                    true => (p.1, p.0),
+                    false => (p.0, p.1),
+                })
+                .collect(),
+        )
+    };
+
+    let items = lot
+        .iter()
+        .map(|piece| {
+            Ok(JsonItem {
+                name: Some(piece.id.clone()),
+                demand: Some(piece.quantity),
+                dxf: None,
+                dxf_layer: None,
+                dxf_block: None,
+                svg: None,
+                svg_id: None,
+                //transposing mirrors the items, which reverses the direction of rotations
+                allowed_orientations: match piece.orientations.is_empty() {
+                    true => None,
+                    false => Some(
+                        piece
+                            .orientations
+                            .iter()
+                            .map(|a| match transposed {
+                                true => (-a).rem_euclid(360.0),
+                                false => *a,
+                            })
+                            .collect(),
+                    ),
+                },
+                allowed_transformations: None,
+                shape: Some(JsonShape::SimplePolygon(to_json(&shape(piece)?))),
+                value: None,
+                shortfall_cost: None,
+                base_quality: None,
+                material: None,
+                surrogate: None,
+                copies: None,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let (bins, strip) = match strip_board {
+        Some(bbox) => {
+            let height = fsize::min(bbox.width(), bbox.height());
+            (None, Some(JsonStrip { height }))
+        }
+        None => {
+            let bins = boards
+                .iter()
+                .zip(board_shapes.iter())
+                .map(|(board, board_shape)| JsonBin {
+                    name: Some(board.id.clone()),
+                    material: None,
+                    max_usage: None,
+                    cost: SimplePolygon::new(board_shape.clone()).area().round() as u64,
+                    stock: Some(board.quantity),
+                    shape: Some(JsonShape::SimplePolygon(to_json(board_shape))),
+                    zones: vec![],
+                    temporary_zones: vec![],
+                })
+                .collect_vec();
+            (Some(bins), None)
+        }
+    };
+    if bins.as_ref().is_some_and(|b| b.is_empty()) {
+        return Err("no boards defined".into());
+    }
+
+    let name = name.unwrap_or_else(|| "esicup".to_string());
+    log!(
+        Level::Info,
+        "[ESICUP] instance {}: {} pieces ({} unique), {}",
+        name,
+        items.iter().filter_map(|i| i.demand).sum::<u64>(),
+        items.len(),
+        match &strip {
+            Some(strip) => format!("strip of height {}", strip.height),
+            None => format!("{} boards", boards.len()),
+        }
+    );
+
+    Ok(JsonInstance {
+        name,
+        items,
+        bins,
+        strip,
+        units: None,
+        assets: BTreeMap::new(),
+    })
+}
+
+/// Name of a tag without its namespace prefix
+fn local_name(tag: &str) -> &str {
+    tag.rsplit(':').next().unwrap_or(tag)
+}
@@ -0,0 +1,225 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::instances::instance::Instance;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::layout::LayoutSnapshot;
+use crate::entities::placed_item::PItemKey;
+use crate::fsize;
+use crate::geometry::geo_traits::Transformable;
+use crate::geometry::primitives::arc_polygon::{ArcPolygon, CircularArc};
+use crate::geometry::primitives::point::Point;
+use crate::geometry::transformation::Transformation;
+use crate::util::units::{self, Units};
+
+/// Configuration of the toolpath and G-code of a layout, see [`layout_toolpath`] and [`toolpath_to_gcode`]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct GcodeConfig {
+    /// Order in which the parts are cut, and where their contours are pierced
+    #[serde(default)]
+    pub travel: TravelOptimization,
+    /// Feed rate of the cutting moves, in units of the input per minute
+    pub feed_rate: fsize,
+    /// Number of decimals of the coordinates
+    pub precision: usize,
+}
+
+impl Default for GcodeConfig {
+    fn default() -> Self {
+        Self {
+            travel: TravelOptimization::default(),
+            feed_rate: 1000.0,
+            precision: 3,
+        }
+    }
+}
+
+/// How the travel moves between the parts of a layout are kept short
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TravelOptimization {
+    /// Parts are cut in the order of the placed items of the layout (as in the JSON solution), every contour is pierced at its first vertex
+    PlacementOrder,
+    /// Every next part is the one with the vertex closest to the end of the previous contour, and its contour is pierced at that vertex
+    #[default]
+    NearestNeighbour,
+}
+
+/// The closed contour of a placed item, in the coordinates (and units) of the input, starting and ending at the point where it is pierced
+#[derive(Clone, Debug)]
+pub struct ToolpathContour {
+    pub placed_item: PItemKey,
+    pub item_id: usize,
+    pub contour: ArcPolygon,
+}
+
+/// Ordered contours to cut a layout, starting from the origin of the bin
+#[derive(Clone, Debug)]
+pub struct Toolpath {
+    pub contours: Vec<ToolpathContour>,
+}
+
+impl Toolpath {
+    /// Total length of the travel moves: from the origin to the first contour, between consecutive contours and back to the origin
+    pub fn travel_distance(&self) -> fsize {
+        let pierce_points = self.contours.iter().map(|c| c.contour.points[0]);
+        let origin = Point(0.0, 0.0);
+        std::iter::once(origin)
+            .chain(pierce_points)
+            .chain(std::iter::once(origin))
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|w| w[0].distance(w[1]))
+            .sum()
+    }
+
+    /// Total length of the cutting moves
+    pub fn cut_distance(&self) -> fsize {
+        self.contours.iter().map(|c| c.contour.perimeter()).sum()
+    }
+}
+
+/// Orders the contours of the placed items of a layout for cutting, in the coordinates (and units) of the input, using the exact contour (with arcs) of the items if available.
+/// If `part_order` is defined (indices in the placed items of the layout, e.g. the `cut_order` of a JSON layout for thermal cutting), the parts are cut in that order,
+/// and only the pierce points are chosen by the `travel` optimization. The bin and its holes are not cut.
+pub fn layout_toolpath(
+    s_layout: &LayoutSnapshot,
+    instance: &Instance,
+    travel: TravelOptimization,
+    part_order: Option<&[usize]>,
+) -> Toolpath {
+    let to_input = 1.0 / instance.unit_scale();
+    let inv_bin_transf = s_layout.bin.pretransform.clone().inverse();
+    let mut remaining = s_layout
+        .placed_items
+        .iter()
+        .map(|(pk, pi)| {
+            let item = instance.item(pi.item_id);
+            let transf = Transformation::from_dt(&pi.d_transf).transform(&inv_bin_transf);
+            let contour = match &item.arc_shape {
+                Some(arc_shape) => arc_shape.transform_clone(&transf),
+                None => {
+                    let shape = item.shape.transform_clone(&transf);
+                    let n_points = shape.number_of_points();
+                    ArcPolygon::new(shape.points, vec![0.0; n_points])
+                }
+            };
+            Some(ToolpathContour {
+                placed_item: pk,
+                item_id: pi.item_id,
+                contour: units::scaled_arc_polygon(&contour, to_input),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(order) = part_order {
+        assert_eq!(
+            order.len(),
+            remaining.len(),
+            "part order should contain every placed item once"
+        );
+    }
+
+    let mut contours: Vec<ToolpathContour> = Vec::with_capacity(remaining.len());
+    let mut position = Point(0.0, 0.0);
+    for i in 0..remaining.len() {
+        let (idx, start) = match (part_order, travel) {
+            (Some(order), TravelOptimization::PlacementOrder) => (order[i], 0),
+            (None, TravelOptimization::PlacementOrder) => (i, 0),
+            (Some(order), TravelOptimization::NearestNeighbour) => {
+                let contour = &remaining[order[i]].as_ref().unwrap().contour;
+                (order[i], closest_vertex(contour, position).0)
+            }
+            (None, TravelOptimization::NearestNeighbour) => remaining
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, c)| {
+                    c.as_ref()
+                        .map(|c| (idx, closest_vertex(&c.contour, position)))
+                })
+                .min_by(|(_, (_, d1)), (_, (_, d2))| d1.total_cmp(d2))
+                .map(|(idx, (start, _))| (idx, start))
+                .unwrap(),
+        };
+        let mut tc = remaining[idx]
+            .take()
+            .expect("part order should contain every placed item once");
+        tc.contour.points.rotate_left(start);
+        tc.contour.bulges.rotate_left(start);
+        position = tc.contour.points[0];
+        contours.push(tc);
+    }
+    Toolpath { contours }
+}
+
+/// Writes a toolpath as G-code: absolute coordinates, a rapid move (`G0`) to the pierce point of every contour,
+/// after which the tool is switched on (`M3`), the contour is cut with linear (`G1`) and circular (`G2`/`G3`) moves and the tool is switched off (`M5`).
+/// The program ends at the origin (`M2`). Millimeter (`G21`) or inch (`G20`) mode follows the units of the instance, unitless instances are written in `G21`.
+pub fn toolpath_to_gcode(toolpath: &Toolpath, instance: &Instance, config: &GcodeConfig) -> String {
+    let prec = config.precision;
+    let mut gcode = String::new();
+    let mut line = |l: String| {
+        gcode.push_str(&l);
+        gcode.push('\n');
+    };
+    line(format!(
+        "; {} parts, cut length {:.prec$}, travel length {:.prec$}",
+        toolpath.contours.len(),
+        toolpath.cut_distance(),
+        toolpath.travel_distance()
+    ));
+    line(
+        match instance.units() {
+            Some(Units::Inch) => "G20",
+            _ => "G21",
+        }
+        .to_string(),
+    );
+    line("G90".to_string());
+
+    for tc in &toolpath.contours {
+        let contour = &tc.contour;
+        let start = contour.points[0];
+        line(format!("; item {}", tc.item_id));
+        line(format!("G0 X{:.prec$} Y{:.prec$}", start.0, start.1));
+        line("M3".to_string());
+        for i in 0..contour.number_of_edges() {
+            let (from, to, bulge) = contour.get_edge(i);
+            let mut mv = match bulge == 0.0 {
+                true => format!("G1 X{:.prec$} Y{:.prec$}", to.0, to.1),
+                false => {
+                    //a positive bulge is a counterclockwise arc, the center is relative to its start
+                    let arc = CircularArc::from_bulge(from, to, bulge);
+                    format!(
+                        "{} X{:.prec$} Y{:.prec$} I{:.prec$} J{:.prec$}",
+                        if bulge > 0.0 { "G3" } else { "G2" },
+                        to.0,
+                        to.1,
+                        arc.center.0 - from.0,
+                        arc.center.1 - from.1
+                    )
+                }
+            };
+            if i == 0 {
+                write!(mv, " F{}", config.feed_rate).unwrap();
+            }
+            line(mv);
+        }
+        line("M5".to_string());
+    }
+    line(format!("G0 X{:.prec$} Y{:.prec$}", 0.0, 0.0));
+    line("M2".to_string());
+    gcode
+}
+
+/// Index of the vertex of the contour closest to `point`, and its distance
+fn closest_vertex(contour: &ArcPolygon, point: Point) -> (usize, fsize) {
+    contour
+        .points
+        .iter()
+        .map(|p| p.distance(point))
+        .enumerate()
+        .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+        .unwrap()
+}
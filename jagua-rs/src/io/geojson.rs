@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde_json::{json, Map, Value};
+
+use crate::entities::instances::instance::Instance;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::solution::Solution;
+use crate::fsize;
+use crate::geometry::geo_traits::Transformable;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+use crate::geometry::transformation::Transformation;
+use crate::io::json_instance::{
+    JsonBin, JsonInstance, JsonItem, JsonPoly, JsonShape, JsonSimplePoly, JsonStrip,
+};
+use crate::io::parser;
+
+/// Builds an instance from a GeoJSON `FeatureCollection` of `Polygon` and `MultiPolygon` features.
+/// Every feature is an item, unless its `Kind` property is `"Bin"`. The other properties of a feature are read as the fields
+/// of an item or bin in the JSON instance (e.g. `Demand`, `AllowedOrientations`, `Stock`, `Cost`), its geometry as the `Shape`.
+/// The `Name` and `Strip` (e.g. `{"Height": 100}`) of the instance are read from members of the collection itself.
+/// Coordinates beyond x and y are ignored, and the closing position of every ring is removed.
+pub fn geojson_instance(geojson: &str) -> Result<JsonInstance, String> {
+    let collection: Value =
+        serde_json::from_str(geojson).map_err(|err| format!("invalid json: {}", err))?;
+    if collection["type"] != "FeatureCollection" {
+        return Err("not a FeatureCollection".into());
+    }
+    let features = collection["features"]
+        .as_array()
+        .ok_or("FeatureCollection without features")?;
+
+    let mut items = vec![];
+    let mut bins = vec![];
+    for (i, feature) in features.iter().enumerate() {
+        let invalid = |reason: String| format!("feature {}: {}", i, reason);
+        let mut properties = match &feature["properties"] {
+            Value::Object(properties) => properties.clone(),
+            Value::Null => Map::new(),
+            _ => return Err(invalid("properties are not an object".into())),
+        };
+        let kind = properties.remove("Kind");
+        let shape = geometry_shape(&feature["geometry"]).map_err(invalid)?;
+        properties.insert(
+            "Shape".into(),
+            serde_json::to_value(shape).expect("could not serialize shape"),
+        );
+        let properties = Value::Object(properties);
+        match kind.as_ref().and_then(Value::as_str) {
+            None | Some("Item") => items.push(
+                serde_json::from_value::<JsonItem>(properties)
+                    .map_err(|err| invalid(format!("invalid item properties: {}", err)))?,
+            ),
+            Some("Bin") => bins.push(
+                serde_json::from_value::<JsonBin>(properties)
+                    .map_err(|err| invalid(format!("invalid bin properties: {}", err)))?,
+            ),
+            Some(other) => return Err(invalid(format!("unknown kind: {}", other))),
+        }
+    }
+
+    let strip = match collection.get("Strip") {
+        Some(strip) => Some(
+            serde_json::from_value::<JsonStrip>(strip.clone())
+                .map_err(|err| format!("invalid strip: {}", err))?,
+        ),
+        None => None,
+    };
+    Ok(JsonInstance {
+        name: collection["Name"].as_str().unwrap_or("geojson").to_string(),
+        items,
+        bins: (!bins.is_empty()).then_some(bins),
+        strip,
+        units: None,
+        assets: BTreeMap::new(),
+    })
+}
+
+/// Draws a solution as a GeoJSON `FeatureCollection` in the coordinates of the input, for GIS and web mapping tools.
+/// Every layout contributes a `Polygon` feature for its bin (or strip) and one for every placed item, with their `Layout` as property,
+/// so the layouts (which overlap) can be filtered. Items carry the `Item` and `Copy` they are, and the `Rotation` (in degrees)
+/// and `Translation` at which they are placed, as in the JSON solution.
+/// The items are drawn with their internal shape, which may be simplified.
+pub fn solution_to_geojson(solution: &Solution, instance: &Instance) -> Value {
+    let to_input = 1.0 / instance.unit_scale();
+    let mut features = vec![];
+    for (l_idx, s_layout) in solution.layout_snapshots.iter().enumerate() {
+        let bin = &s_layout.bin;
+        let inv_bin_transf = bin.pretransform.clone().inverse();
+        let bin_rings = [&bin.outer]
+            .into_iter()
+            .chain(bin.holes.iter())
+            .map(|contour| ring(&contour.transform_clone(&inv_bin_transf), to_input))
+            .collect_vec();
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {"type": "Polygon", "coordinates": bin_rings},
+            "properties": {
+                "Kind": "Bin",
+                "Layout": l_idx,
+                "Bin": match instance {
+                    Instance::BP(_) => Some(bin.base_id()),
+                    Instance::SP(_) => None,
+                },
+                "Name": bin.name,
+            },
+        }));
+
+        for pi in s_layout.placed_items.values() {
+            let item = instance.item(pi.item_id);
+            //from the internal coordinates of the item to the coordinates of the input
+            let transf = Transformation::from_dt(&pi.d_transf).transform(&inv_bin_transf);
+            let shape = item.shape.transform_clone(&transf);
+            let abs_transf = parser::internal_to_absolute_transform(
+                &pi.d_transf,
+                &item.pretransform,
+                &bin.pretransform,
+            )
+            .decompose();
+            let (tx, ty) = abs_transf.translation();
+            features.push(json!({
+                "type": "Feature",
+                "geometry": {"type": "Polygon", "coordinates": [ring(&shape, to_input)]},
+                "properties": {
+                    "Kind": "Item",
+                    "Layout": l_idx,
+                    "Item": item.id,
+                    "Copy": pi.copy,
+                    "Name": item.name,
+                    "Rotation": abs_transf.rotation().to_degrees(),
+                    "Translation": (tx * to_input, ty * to_input),
+                },
+            }));
+        }
+    }
+    json!({"type": "FeatureCollection", "features": features})
+}
+
+/// Converts a `Polygon` or `MultiPolygon` geometry to a shape
+fn geometry_shape(geometry: &Value) -> Result<JsonShape, String> {
+    let coordinates = &geometry["coordinates"];
+    let polygons = match geometry["type"].as_str() {
+        Some("Polygon") => vec![polygon(coordinates)?],
+        Some("MultiPolygon") => coordinates
+            .as_array()
+            .ok_or("MultiPolygon coordinates are not an array")?
+            .iter()
+            .map(polygon)
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(other) => {
+            return Err(format!(
+                "{} geometries are not supported, only Polygon and MultiPolygon",
+                other
+            ))
+        }
+        None => return Err("no geometry".into()),
+    };
+    match polygons.is_empty() {
+        true => Err("empty MultiPolygon".into()),
+        false => Ok(JsonShape::from_polygons(polygons)),
+    }
+}
+
+fn polygon(coordinates: &Value) -> Result<JsonPoly, String> {
+    let mut rings = coordinates
+        .as_array()
+        .ok_or("Polygon coordinates are not an array")?
+        .iter()
+        .map(|ring| {
+            let positions = ring.as_array().ok_or("ring is not an array")?;
+            let mut points = positions
+                .iter()
+                .map(|position| match position.as_array().map(Vec::as_slice) {
+                    Some([x, y, ..]) => match (x.as_f64(), y.as_f64()) {
+                        (Some(x), Some(y)) => Ok((x as fsize, y as fsize)),
+                        _ => Err(format!("invalid position: {}", position)),
+                    },
+                    _ => Err(format!("invalid position: {}", position)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            match points.len() {
+                0..=2 => Err(format!("ring with {} distinct positions", points.len())),
+                _ => Ok(JsonSimplePoly(points)),
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?
+        .into_iter();
+    Ok(JsonPoly {
+        outer: rings.next().ok_or("Polygon without rings")?,
+        inner: rings.collect(),
+    })
+}
+
+/// Closed linear ring of a polygon, as GeoJSON positions, with all coordinates multiplied by `scale`
+fn ring(polygon: &SimplePolygon, scale: fsize) -> Vec<[fsize; 2]> {
+    polygon
+        .points
+        .iter()
+        .chain(polygon.points.first())
+        .map(|p| [p.0 * scale, p.1 * scale])
+        .collect()
+}
@@ -1,6 +1,12 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::fsize;
+use crate::io::json_solution::JsonTransformation;
+use crate::util::units::Units;
 
 /// The JSON representation of a problem instance
 #[derive(Serialize, Deserialize, Clone)]
@@ -19,12 +25,61 @@ pub struct JsonInstance {
     #[serde(rename = "Strip")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub strip: Option<JsonStrip>,
+    /// Unit of all lengths in the instance, including those of the solutions to it.
+    /// If not present, the coordinates are used as they are, see [`Units`]
+    #[serde(rename = "Units")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<Units>,
+    /// DXF and SVG files embedded in the instance, by the name its items refer to them with (in `Dxf` or `Svg`).
+    /// They are used instead of files on disk, so the instance can be parsed without filesystem access
+    #[serde(rename = "Assets")]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assets: BTreeMap<String, JsonAsset>,
+}
+
+/// The contents of an embedded file, base64 encoded in JSON.
+/// A data URL prefix (`data:application/dxf;base64,`) and whitespace are ignored when reading
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonAsset(pub Arc<[u8]>);
+
+impl Serialize for JsonAsset {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonAsset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let encoded = match encoded.split_once(";base64,") {
+            Some((prefix, data)) if prefix.starts_with("data:") => data,
+            _ => &encoded,
+        };
+        let encoded = encoded
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>();
+        BASE64_STANDARD
+            .decode(encoded)
+            .map(|contents| JsonAsset(contents.into()))
+            .map_err(|err| serde::de::Error::custom(format!("invalid base64 asset: {}", err)))
+    }
 }
 
 /// The JSON representation of a bin
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct JsonBin {
+    /// Name of the bin for the operator, e.g. "Sheet A 3mm steel", carried over to the layouts in the solution
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+    /// Material of the bin, only items of the same material (or without one) can be placed in it
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub material: Option<String>,
+    /// Maximum fraction of the area of the bin which can be covered by items, e.g. 0.9 to keep 10% of the sheet free for clamping.
+    /// If not present, the entire bin can be used
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_usage: Option<fsize>,
     /// The cost of using this bin
     pub cost: u64,
     /// Number of this bin available, if not present, it is assumed to be unlimited
@@ -34,6 +89,9 @@ pub struct JsonBin {
     /// A list of zones with different quality levels
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub zones: Vec<JsonQualityZone>,
+    /// Zones in which no items can be placed during part of the placement sequence, e.g. clamp positions which move
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub temporary_zones: Vec<JsonTemporaryZone>,
 }
 
 /// The JSON representation of a strip with fixed height and variable width
@@ -47,19 +105,86 @@ pub struct JsonStrip {
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct JsonItem {
-    /// Number of times this item should be produced
-    pub demand: u64,
+    /// Name of the item for the operator, e.g. a part number, used in the reports of the solution.
+    /// Defaults to the file name of the item's DXF or SVG file, if any
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+    /// Number of times this item should be produced.
+    /// Can be omitted if it is defined in the metadata of the item's DXF file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub demand: Option<u64>,
     /// Dxf file path to the item
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub dxf: Option<String>,
+    /// Layer of the DXF file containing the shape of the item, for files containing multiple items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dxf_layer: Option<String>,
+    /// Block of the DXF file containing the shape of the item, for files containing multiple items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dxf_block: Option<String>,
+    /// SVG file path to the item, for items without `dxf` file.
+    /// The outline is the largest contour of the `<path>` and `<polygon>` elements in the file
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub svg: Option<String>,
+    /// Id of the element of the SVG file containing the shape of the item, for files containing multiple items
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub svg_id: Option<String>,
     /// List of allowed orientations angles (in degrees). If none any orientation is allowed
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allowed_orientations: Option<Vec<fsize>>,
+    /// Exhaustive list of transformations (rotation in radians) the item can be placed with, in the coordinates of the input.
+    /// Fixes the position of the item as well, cannot be combined with `allowed_orientations`.
+    /// In strip packing, translations are relative to the front of the strip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_transformations: Option<Vec<JsonTransformation>>,
     /// Polygon shape of the item
     pub shape: Option<JsonShape>,
     /// The value of the item (for knapsack problems)
     pub value: Option<u64>,
+    /// Penalty for every copy of the item which is demanded but not placed.
+    /// When not all items fit, the ones with the highest penalty per area are placed first
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub shortfall_cost: Option<u64>,
     /// The quality required for the entire item, if not defined maximum quality is required
     pub base_quality: Option<usize>,
+    /// Material the item has to be cut from, if defined the item can only be placed in bins of the same material
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub material: Option<String>,
+    /// Overrides of the surrogate configuration for this item, for shapes which need more (or fewer) poles than the rest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surrogate: Option<JsonSurrogateConfig>,
+    /// Attributes of individual copies of the item, copies which are not listed can be placed anywhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copies: Option<Vec<JsonItemCopy>>,
+}
+
+/// Attributes of a single copy of an item
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonItemCopy {
+    /// Index of the copy, below the demand of the item
+    pub copy: usize,
+    /// Index of the bin the copy has to be placed in. Bin packing only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bin: Option<usize>,
+    /// Transformation the copy is locked at, in the coordinates of the input.
+    /// In strip packing, translations are relative to the front of the strip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transformation: Option<JsonTransformation>,
+}
+
+/// Overrides of the fields of the item surrogate configuration, undefined fields keep the configured value
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonSurrogateConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pole_coverage_goal: Option<fsize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_poles: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n_ff_poles: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n_ff_piers: Option<usize>,
 }
 
 /// Different ways to represent a shape
@@ -75,6 +200,20 @@ pub enum JsonShape {
     Polygon(JsonPoly),
     /// Multiple disjoint polygons
     MultiPolygon(Vec<JsonPoly>),
+    /// `POLYGON` or `MULTIPOLYGON` in Well-Known Text, converted to one of the other shapes when parsed, see [`wkt_shape`](crate::io::wkt::wkt_shape)
+    Wkt(String),
+}
+
+impl JsonShape {
+    /// The simplest shape covering the polygons: a simple polygon for a single polygon without holes,
+    /// a polygon for a single polygon with holes, and a multipolygon otherwise
+    pub fn from_polygons(mut polygons: Vec<JsonPoly>) -> JsonShape {
+        match (polygons.len(), polygons.first().map(|p| p.inner.is_empty())) {
+            (1, Some(true)) => JsonShape::SimplePolygon(polygons.remove(0).outer),
+            (1, Some(false)) => JsonShape::Polygon(polygons.remove(0)),
+            _ => JsonShape::MultiPolygon(polygons),
+        }
+    }
 }
 
 /// A polygon represented as an outer boundary and a list of holes
@@ -92,12 +231,28 @@ pub struct JsonPoly {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct JsonSimplePoly(pub Vec<(fsize, fsize)>);
 
+/// A zone which is only a hazard during part of the placement sequence.
+/// The schedule is expressed in the number of items placed in the bin
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonTemporaryZone {
+    /// The shape of this zone, a rectangle or a simple polygon
+    pub shape: JsonShape,
+    /// Number of items which have to be placed in the bin before the zone becomes active, 0 if not present
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub active_from: Option<usize>,
+    /// Number of items placed in the bin after which the zone is no longer active. If not present, the zone remains active
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub active_until: Option<usize>,
+}
+
 /// A zone with a specific quality level
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct JsonQualityZone {
     /// The quality level of this zone
     pub quality: usize,
-    /// The polygon shape of this zone
+    /// The shape of this zone. Polygons can have holes, in which the material is not of this quality.
+    /// Zones of the same quality which overlap are merged
     pub shape: JsonShape,
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::fsize;
+use crate::util::objectives::Objective;
 
 /// Representation of a solution
 #[derive(Serialize, Deserialize, Clone)]
@@ -8,10 +9,55 @@ use crate::fsize;
 pub struct JsonSolution {
     /// Sum of the area of the produced items divided by the sum of the area of the containers
     pub usage: fsize,
+    /// Same as `usage`, but computed with the areas of the items and containers as defined in the input, before simplification
+    #[serde(default)]
+    pub original_usage: fsize,
     /// The time it took to generate the solution in seconds
     pub run_time_sec: u64,
     /// Layouts which compose the solution
     pub layouts: Vec<JsonLayout>,
+    /// Smallest clearance between any two items, or between an item and the boundary of its container, over all layouts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_clearance: Option<fsize>,
+    /// Total penalty of the demanded copies which are not placed, see the `ShortfallCost` of the items.
+    /// Only present if the instance defines shortfall costs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortfall_cost: Option<u64>,
+    /// Values of the solution on the configured objectives
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objectives: Vec<JsonObjectiveValue>,
+    /// Weighted sum of the objectives, where minimized objectives contribute negatively. Higher is better
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<fsize>,
+    /// Provenance of the solution, filled in by the solver which produced it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<JsonSolutionMetadata>,
+}
+
+/// Information to trace a solution back to exactly what produced it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonSolutionMetadata {
+    /// Name of the solver
+    pub solver: String,
+    /// Version of the solver
+    pub solver_version: String,
+    /// Git commit the solver was built from, suffixed with `-dirty` if the working tree had uncommitted changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_version: Option<String>,
+    /// Hash of the configuration of the solver
+    pub config_hash: String,
+    /// Hash of the instance
+    pub instance_hash: String,
+}
+
+/// Value of a solution on a single objective
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonObjectiveValue {
+    pub objective: Objective,
+    pub weight: fsize,
+    pub value: fsize,
 }
 
 /// Representation how a set of items are placed in a certain container
@@ -20,8 +66,14 @@ pub struct JsonSolution {
 pub struct JsonLayout {
     /// The container that was used
     pub container: JsonContainer,
+    /// Name of the bin of the container, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// The items placed in the container and where they were placed
     pub placed_items: Vec<JsonPlacedItem>,
+    /// Order in which the placed items are cut, as indices in `placed_items`. Empty if no order was determined
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cut_order: Vec<usize>,
     /// Some statistics about the layout
     pub statistics: JsonLayoutStats,
 }
@@ -32,6 +84,9 @@ pub struct JsonLayout {
 pub struct JsonPlacedItem {
     /// The index of the item in the instance
     pub index: usize,
+    /// Which copy of the item was placed, only defined for items with per-copy attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy: Option<usize>,
     /// The transformation applied to the item to place it in the container
     pub transformation: JsonTransformation,
 }
@@ -47,11 +102,74 @@ pub struct JsonTransformation {
 }
 
 /// Some statistics about the layout
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "PascalCase")]
 pub struct JsonLayoutStats {
     /// The percentage of the container that is packed with items
     pub usage: fsize,
+    /// Same as `usage`, but computed with the areas of the items and container as defined in the input, before simplification
+    #[serde(default)]
+    pub original_usage: fsize,
+    /// The percentage of the bounding box of the placed items that is packed with items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox_usage: Option<fsize>,
+    /// The percentage of the convex hull of the placed items that is packed with items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub convex_hull_usage: Option<fsize>,
+    /// Frontier of the placed items in a strip, as (y, x) pairs: the largest x-coordinate occupied by an item at height y.
+    /// Only present for strip packing layouts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skyline: Vec<(fsize, fsize)>,
+    /// Smallest clearance between any two items, or between an item and the boundary of the container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_clearance: Option<fsize>,
+    /// All clearances below the configured threshold
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clearance_violations: Vec<JsonClearance>,
+    /// All webs of the skeleton narrower than the configured minimum web width
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thin_webs: Vec<JsonThinWeb>,
+}
+
+/// Clearance between a placed item and another entity in the layout
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonClearance {
+    /// The index of the placed item in the layout
+    pub placed_item: usize,
+    /// The entity to which the clearance was measured
+    pub other: JsonClearanceEntity,
+    /// The distance between the two
+    pub distance: fsize,
+    /// Lower bound on the distance between the two as defined in the input, accounting for flattened curves
+    #[serde(default)]
+    pub lower_bound: fsize,
+}
+
+/// Web of the skeleton (the material of the container left over after cutting) narrower than the minimum web width
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonThinWeb {
+    /// The index of the placed item on one side of the web
+    pub placed_item: usize,
+    /// The entity on the other side of the web
+    pub other: JsonClearanceEntity,
+    /// The width of the web at its narrowest point
+    pub width: fsize,
+    /// The center of the web at its narrowest point (x, y)
+    pub location: (fsize, fsize),
+}
+
+/// Entity to which a clearance was measured
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "Type", content = "Index")]
+pub enum JsonClearanceEntity {
+    /// Another placed item, with its index in the layout
+    PlacedItem(usize),
+    /// The contour of the container
+    Exterior,
+    /// A hole in the container, with its index in the bin
+    Hole(usize),
 }
 
 /// Type of container that was used
@@ -1,6 +1,23 @@
-pub mod json_instance;
-pub mod json_solution;
+pub mod asset_paths;
+#[cfg(feature = "dxf")]
+pub mod dxf_export;
+#[cfg(feature = "dxf")]
 pub mod dxf_instance;
-pub mod dxf_solution;
+pub mod dxf_layers;
+pub mod dxf_metadata;
+#[cfg(feature = "dxf")]
 pub mod dxf_parse;
+#[cfg(feature = "dxf")]
+pub(crate) mod dxf_solution;
+pub mod esicup;
+pub mod gcode;
+pub mod geojson;
+pub mod json_instance;
+pub mod json_solution;
+pub mod parse_error;
 pub mod parser;
+pub mod shape_cache;
+pub mod svg_import;
+pub mod wkt;
+pub(crate) mod xml_scan;
+pub(crate) mod zone_union;
@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Reason why an instance or solution could not be parsed.
+/// Items and bins are identified by their index in the input.
+#[derive(Debug)]
+pub enum ParseError {
+    /// A file referenced by the input could not be read
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The DXF or SVG files referenced by some items could not be opened, all of them are listed
+    MissingAssets(Vec<MissingAsset>),
+    /// The instance is not valid JSON, or could not be read
+    Json(serde_json::Error),
+    /// A DXF or SVG file referenced by the input does not define a usable shape
+    InvalidFile { path: PathBuf, reason: String },
+    /// The definition of an item is invalid
+    InvalidItem { item: usize, reason: String },
+    /// The definition of a bin is invalid
+    InvalidBin { bin: usize, reason: String },
+    /// The instance as a whole is invalid, for example when it defines neither bins nor a strip
+    InvalidInstance(String),
+    /// The layouts of a solution do not match the instance
+    InvalidSolution(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Io { path, source } => {
+                write!(f, "could not read file {}: {}", path.display(), source)
+            }
+            ParseError::MissingAssets(missing) => {
+                write!(f, "{} asset files could not be opened: ", missing.len())?;
+                let missing = missing.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", missing.join("; "))
+            }
+            ParseError::Json(source) => write!(f, "invalid JSON: {}", source),
+            ParseError::InvalidFile { path, reason } => {
+                write!(f, "invalid file {}: {}", path.display(), reason)
+            }
+            ParseError::InvalidItem { item, reason } => {
+                write!(f, "invalid item {}: {}", item, reason)
+            }
+            ParseError::InvalidBin { bin, reason } => write!(f, "invalid bin {}: {}", bin, reason),
+            ParseError::InvalidInstance(reason) => write!(f, "invalid instance: {}", reason),
+            ParseError::InvalidSolution(reason) => write!(f, "invalid solution: {}", reason),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io { source, .. } => Some(source),
+            ParseError::Json(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// An item of which the DXF or SVG file could not be opened
+#[derive(Debug)]
+pub struct MissingAsset {
+    pub item: usize,
+    pub path: PathBuf,
+    pub source: std::io::Error,
+}
+
+impl Display for MissingAsset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "item {}: {} ({})",
+            self.item,
+            self.path.display(),
+            self.source
+        )
+    }
+}
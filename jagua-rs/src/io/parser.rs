@@ -1,13 +1,28 @@
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "dxf")]
+use std::sync::OnceLock;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+#[cfg(feature = "dxf")]
+use dxf::entities::LwPolyline;
+#[cfg(feature = "dxf")]
+use dxf::Drawing;
+
 use crate::entities::bin::Bin;
 use crate::entities::instances::bin_packing::BPInstance;
 use crate::entities::instances::instance::Instance;
 use crate::entities::instances::instance_generic::InstanceGeneric;
 use crate::entities::instances::strip_packing::SPInstance;
 use crate::entities::item::Item;
+use crate::entities::item_copy::CopyAttributes;
+use crate::entities::layout::USAGE_TOLERANCE;
+use crate::entities::placed_item::{PItemKey, PlacedItem};
 use crate::entities::placing_option::PlacingOption;
 use crate::entities::problems::bin_packing::BPProblem;
 use crate::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric, STRIP_LAYOUT_IDX};
@@ -15,34 +30,90 @@ use crate::entities::problems::strip_packing::SPProblem;
 use crate::entities::quality_zone::InferiorQualityZone;
 use crate::entities::quality_zone::N_QUALITIES;
 use crate::entities::solution::Solution;
+use crate::entities::temporary_zone::TemporaryZone;
 use crate::fsize;
 use crate::geometry::d_transformation::DTransformation;
 use crate::geometry::geo_enums::AllowedRotation;
 use crate::geometry::geo_traits::{Shape, Transformable};
 use crate::geometry::primitives::aa_rectangle::AARectangle;
+use crate::geometry::primitives::arc_polygon::ArcPolygon;
 use crate::geometry::primitives::point::Point;
 use crate::geometry::primitives::simple_polygon::SimplePolygon;
 use crate::geometry::transformation::Transformation;
-use crate::io::dxf_instance::DxfInstance;
-use crate::io::json_instance::{JsonBin, JsonInstance, JsonItem, JsonShape, JsonSimplePoly};
+use crate::io::asset_paths::AssetPaths;
+use crate::io::dxf_layers::DxfLayerMapping;
+use crate::io::dxf_metadata::DxfItemMetadata;
+#[cfg(feature = "dxf")]
+use crate::io::dxf_parse;
+use crate::io::json_instance::{
+    JsonAsset, JsonBin, JsonInstance, JsonItem, JsonPoly, JsonQualityZone, JsonShape,
+    JsonSimplePoly, JsonStrip, JsonTemporaryZone,
+};
 use crate::io::json_solution::{
-    JsonContainer, JsonLayout, JsonLayoutStats, JsonPlacedItem, JsonSolution, JsonTransformation,
+    JsonClearance, JsonClearanceEntity, JsonContainer, JsonLayout, JsonLayoutStats,
+    JsonObjectiveValue, JsonPlacedItem, JsonSolution, JsonThinWeb, JsonTransformation,
 };
-use crate::util::config::CDEConfig;
+use crate::io::parse_error::{MissingAsset, ParseError};
+use crate::io::shape_cache::{content_hash, BinGeometry, ItemGeometry, ShapeCache};
+use crate::io::svg_import;
+use crate::io::wkt;
+use crate::io::zone_union::{self, ZoneShape};
+use crate::util::clearance;
+use crate::util::clearance::ClearanceEntity;
+use crate::util::config::{CDEConfig, GeometricFidelity, SPSurrogateConfig};
+use crate::util::layout_metrics;
+use crate::util::objectives::{Objective, ObjectiveVector, WeightedObjective};
+use crate::util::polygon_cleanup;
+use crate::util::polygon_holes;
 use crate::util::polygon_simplification;
 use crate::util::polygon_simplification::{PolySimplConfig, PolySimplMode};
+use crate::util::units::{self, Units};
+use crate::util::web_width;
+use crate::PI;
 use itertools::Itertools;
 use log::{log, Level};
-use rayon::iter::IndexedParallelIterator;
-use rayon::iter::ParallelIterator;
-use rayon::prelude::IntoParallelRefIterator;
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
+use serde::de::{SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use slotmap::SlotMap;
+use tracing::{debug_span, info_span, Span};
+
+/// Number of horizontal lines at which the skyline of a strip is exported
+const SKYLINE_RESOLUTION: usize = 100;
+
+/// What to do with items of which the DXF or SVG file cannot be opened, see [`apply_missing_asset_policy`]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MissingAssetPolicy {
+    /// Fail, listing all missing files at once
+    #[default]
+    Fail,
+    /// Leave the items out of the instance, with a warning for each of them
+    Skip,
+}
 
 /// Parses a `JsonInstance` into an `Instance`.
 pub struct Parser {
     poly_simpl_config: PolySimplConfig,
     cde_config: CDEConfig,
     center_polygons: bool,
-    path_assets_folder: PathBuf,
+    //resolves the files of items defined by dxf or svg files
+    asset_paths: AssetPaths,
+    //role of the layers of dxf files
+    dxf_layer_mapping: DxfLayerMapping,
+    #[cfg_attr(not(feature = "dxf"), allow(dead_code))]
+    fidelity: GeometricFidelity,
+    //dedicated pool to parse in, the global rayon pool is used if undefined
+    #[cfg(feature = "parallel")]
+    thread_pool: Option<Arc<ThreadPool>>,
+    //preprocessed shapes are reused across runs if defined
+    shape_cache: Option<ShapeCache>,
+    //whether a rotated variant of every non-square bin is added
+    rotated_bins: bool,
+    //whether the holes of items are filled, so no item can be placed inside another
+    part_in_part_forbidden: bool,
 }
 
 impl Parser {
@@ -51,40 +122,286 @@ impl Parser {
         cde_config: CDEConfig,
         center_polygons: bool,
         path_assets_folder: PathBuf,
+        fidelity: GeometricFidelity,
     ) -> Parser {
         Parser {
             poly_simpl_config,
             cde_config,
             center_polygons,
-            path_assets_folder,
+            asset_paths: AssetPaths::new(path_assets_folder),
+            dxf_layer_mapping: DxfLayerMapping::default(),
+            fidelity,
+            #[cfg(feature = "parallel")]
+            thread_pool: None,
+            shape_cache: None,
+            rotated_bins: false,
+            part_in_part_forbidden: false,
         }
     }
 
+    /// Parses in a dedicated rayon `ThreadPool` instead of the global one.
+    /// Limits the number of cores a single instance can occupy, for example when parsing inside a web service.
+    #[cfg(feature = "parallel")]
+    pub fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    /// Resolves the DXF and SVG files of items with `asset_paths` instead of only relative to the assets folder,
+    /// see [`AssetPaths`] for the search folders, absolute paths and environment variables.
+    pub fn with_asset_paths(mut self, asset_paths: AssetPaths) -> Self {
+        self.asset_paths = asset_paths;
+        self
+    }
+
+    /// Reads the outlines and holes of items defined by DXF files from the layers `dxf_layer_mapping` assigns to them,
+    /// instead of from all layers. The `DxfLayer` of an item replaces the outline layers of the mapping.
+    pub fn with_dxf_layer_mapping(mut self, dxf_layer_mapping: DxfLayerMapping) -> Self {
+        self.dxf_layer_mapping = dxf_layer_mapping;
+        self
+    }
+
+    /// Reuses preprocessed shapes (simplified, centered and with their surrogate generated) stored in `shape_cache`,
+    /// and stores the ones it had to preprocess itself.
+    pub fn with_shape_cache(mut self, shape_cache: ShapeCache) -> Self {
+        self.shape_cache = Some(shape_cache);
+        self
+    }
+
+    /// Adds a variant of every bin which is not square, rotated by 90°, sharing the stock of the original.
+    /// Lets the optimizer choose the orientation of each sheet, the solution is still expressed in the coordinates of the original bin.
+    pub fn with_rotated_bins(mut self, rotated_bins: bool) -> Self {
+        self.rotated_bins = rotated_bins;
+        self
+    }
+
+    /// Forbids items from being placed inside the holes of other items, for machines on which such parts would drop through.
+    /// The holes of all items are filled, after which any placement inside one is rejected by the containment check of the CDE.
+    /// Items keep the area of their holes, so the usage of a layout includes it.
+    pub fn with_part_in_part_forbidden(mut self, forbidden: bool) -> Self {
+        self.part_in_part_forbidden = forbidden;
+        self
+    }
+
+    pub fn asset_paths(&self) -> &AssetPaths {
+        &self.asset_paths
+    }
+
+    pub fn dxf_layer_mapping(&self) -> &DxfLayerMapping {
+        &self.dxf_layer_mapping
+    }
+
+    pub fn shape_cache(&self) -> Option<&ShapeCache> {
+        self.shape_cache.as_ref()
+    }
+
     /// Parses a `JsonInstance` into an `Instance`.
-    pub fn parse(&self, json_instance: &JsonInstance) -> Instance {
-        let items = json_instance
-            .items
-            .par_iter()
-            .enumerate()
-            .map(|(item_id, json_item)| {
-                self.parse_item(json_item, item_id, &self.path_assets_folder)
-            })
-            .collect();
+    /// The result does not depend on the number of threads used when the `parallel` feature is enabled.
+    /// Returns an error if the instance is malformed or a file it references cannot be read.
+    pub fn parse(&self, json_instance: &JsonInstance) -> Result<Instance, ParseError> {
+        #[cfg(feature = "parallel")]
+        if let Some(thread_pool) = &self.thread_pool {
+            return thread_pool.install(|| self.parse_instance(json_instance));
+        }
+        self.parse_instance(json_instance)
+    }
+
+    fn parse_instance(&self, json_instance: &JsonInstance) -> Result<Instance, ParseError> {
+        //items and bins are parsed on other threads, which do not inherit the current span
+        let span = info_span!("parse", instance = %json_instance.name).entered();
+        //all missing files are reported at once, instead of only the first one encountered
+        let missing_assets = find_missing_assets(json_instance, &self.asset_paths);
+        if !missing_assets.is_empty() {
+            return Err(ParseError::MissingAssets(missing_assets));
+        }
+        let json_instance = &*resolve_wkt_shapes(json_instance)?;
+        let json_instance = &*instance_in_mm(json_instance);
+        let asset_paths = &*self.instance_asset_paths(&json_instance.assets);
+        let files = &ItemFiles::new(asset_paths);
+
+        let mut shapes = ItemShapes::default();
+        let mut shape_defs: Vec<(usize, &JsonItem)> = vec![];
+        let mut shape_ids = vec![];
+        for (item_id, json_item) in json_instance.items.iter().enumerate() {
+            let (shape_id, first) = shapes.intern(json_item, |id| shape_defs[id].1);
+            if first {
+                shape_defs.push((item_id, json_item));
+            }
+            shape_ids.push(shape_id);
+        }
+
+        self.assemble_instance(
+            &span,
+            &json_instance.name,
+            &json_instance.items,
+            (&shape_defs, &shape_ids),
+            json_instance.bins.as_deref(),
+            json_instance.strip.as_ref(),
+            json_instance.units,
+            files,
+        )
+    }
+
+    /// Parses an instance while it is being read from `reader`, without ever holding the entire `JsonInstance` in memory.
+    /// Every distinct shape definition is kept only once: items which repeat the shape (or file) of an earlier item
+    /// are stored without it, and share its preprocessed geometry.
+    /// For instances with many copies of the same parts, this bounds the memory to the distinct shapes instead of all items.
+    /// The result is the same as reading the instance and calling [`Self::parse`],
+    /// except that items which share a missing file or an invalid shape are reported by the first one of them.
+    pub fn parse_reader<R: Read>(&self, reader: R) -> Result<Instance, ParseError> {
+        let streamed: StreamedInstance =
+            serde_json::from_reader(BufReader::new(reader)).map_err(ParseError::Json)?;
+        #[cfg(feature = "parallel")]
+        if let Some(thread_pool) = &self.thread_pool {
+            return thread_pool.install(|| self.parse_streamed(streamed));
+        }
+        self.parse_streamed(streamed)
+    }
+
+    fn parse_streamed(&self, streamed: StreamedInstance) -> Result<Instance, ParseError> {
+        let span = info_span!("parse", instance = %streamed.name).entered();
+        let StreamedItems {
+            mut items,
+            shape_defs,
+            shape_ids,
+        } = streamed.items;
+
+        //only the first item with every shape definition still has it, errors are attributed to that item
+        let first_items = shape_defs.iter().map(|(item_id, _)| *item_id).collect_vec();
+        let defs_instance = JsonInstance {
+            name: streamed.name,
+            items: shape_defs
+                .into_iter()
+                .map(|(_, json_item)| json_item)
+                .collect(),
+            bins: streamed.bins,
+            strip: streamed.strip,
+            units: streamed.units,
+            assets: streamed.assets,
+        };
+        let to_item_id = |err: ParseError| match err {
+            ParseError::InvalidItem { item, reason } => ParseError::InvalidItem {
+                item: first_items[item],
+                reason,
+            },
+            ParseError::MissingAssets(missing) => ParseError::MissingAssets(
+                missing
+                    .into_iter()
+                    .map(|m| MissingAsset {
+                        item: first_items[m.item],
+                        ..m
+                    })
+                    .collect(),
+            ),
+            err => err,
+        };
+        let missing_assets = find_missing_assets(&defs_instance, &self.asset_paths);
+        if !missing_assets.is_empty() {
+            return Err(to_item_id(ParseError::MissingAssets(missing_assets)));
+        }
+        let defs_instance = &*resolve_wkt_shapes(&defs_instance).map_err(to_item_id)?;
+        let defs_instance = &*instance_in_mm(defs_instance);
+        let asset_paths = &*self.instance_asset_paths(&defs_instance.assets);
+        let files = &ItemFiles::new(asset_paths);
+        let scale = Units::scale(defs_instance.units);
+        if scale != 1.0 {
+            items
+                .iter_mut()
+                .for_each(|json_item| scale_json_item(json_item, scale));
+        }
+
+        let shape_defs = first_items
+            .iter()
+            .copied()
+            .zip(defs_instance.items.iter())
+            .collect_vec();
+        self.assemble_instance(
+            &span,
+            &defs_instance.name,
+            &items,
+            (&shape_defs, &shape_ids),
+            defs_instance.bins.as_deref(),
+            defs_instance.strip.as_ref(),
+            defs_instance.units,
+            files,
+        )
+    }
+
+    /// Builds the instance from its items, of which the shapes are defined by `shape_defs` (the first item with every distinct shape definition)
+    /// and `shape_ids` (the index in `shape_defs` of every item), and its bins or strip.
+    /// All of them are in millimeters if the instance has `units`. The files of items are read from `files`
+    #[allow(clippy::too_many_arguments)]
+    fn assemble_instance(
+        &self,
+        span: &Span,
+        name: &str,
+        json_items: &[JsonItem],
+        (shape_defs, shape_ids): (&[(usize, &JsonItem)], &[usize]),
+        json_bins: Option<&[JsonBin]>,
+        json_strip: Option<&JsonStrip>,
+        units: Option<Units>,
+        files: &ItemFiles,
+    ) -> Result<Instance, ParseError> {
+        let cache_counts_before = self
+            .shape_cache
+            .as_ref()
+            .map(|c| (c.n_hits(), c.n_misses()));
+
+        //every distinct shape is preprocessed once, all items with the same definition share the result
+        let geometries = map_indexed(shape_defs, |_, (item_id, json_item)| {
+            let _span = debug_span!(parent: span, "item_geometry", item_id).entered();
+            self.shared_item_geometry(json_item, *item_id, units, files)
+        })?;
+        let items = map_indexed(json_items, |item_id, json_item| {
+            let _span = debug_span!(parent: span, "parse_item", item_id).entered();
+            self.parse_item(json_item, item_id, &geometries[shape_ids[item_id]])
+        })?;
+        if geometries.len() < items.len() {
+            log!(
+                Level::Info,
+                "[PARSE] {} items share {} distinct shapes",
+                items.len(),
+                geometries.len()
+            );
+        }
 
-        let instance: Instance = match (json_instance.bins.as_ref(), json_instance.strip.as_ref()) {
+        let instance: Instance = match (json_bins, json_strip) {
             (Some(json_bins), None) => {
-                let bins: Vec<(Bin, usize)> = json_bins
-                    .par_iter()
-                    .enumerate()
-                    .map(|(bin_id, json_bin)| self.parse_bin(json_bin, bin_id))
-                    .collect();
-                BPInstance::new(items, bins).into()
-            }
-            (None, Some(json_strip)) => SPInstance::new(items, json_strip.height).into(),
+                let bins: Vec<(Bin, usize)> = map_indexed(json_bins, |bin_id, json_bin| {
+                    let _span = debug_span!(parent: span, "parse_bin", bin_id).entered();
+                    self.parse_bin(json_bin, bin_id)
+                })?;
+                check_copy_bins(&items, bins.len())?;
+                check_item_materials(&items, &bins)?;
+                let bins = match self.rotated_bins {
+                    true => add_rotated_bins(bins),
+                    false => bins,
+                };
+                BPInstance {
+                    units,
+                    ..BPInstance::new(items, bins)
+                }
+                .into()
+            }
+            (None, Some(json_strip)) => {
+                check_copy_bins(&items, 0)?;
+                check_item_materials(&items, &[])?;
+                SPInstance {
+                    units,
+                    ..SPInstance::new(items, json_strip.height)
+                }
+                .into()
+            }
             (Some(_), Some(_)) => {
-                panic!("Both bins and strip packing specified, has to be one or the other")
+                return Err(ParseError::InvalidInstance(
+                    "both bins and strip packing specified, has to be one or the other".into(),
+                ))
+            }
+            (None, None) => {
+                return Err(ParseError::InvalidInstance(
+                    "neither bins or strip specified".into(),
+                ))
             }
-            (None, None) => panic!("Neither bins or strips specified"),
         };
 
         match &instance {
@@ -92,7 +409,7 @@ impl Parser {
                 log!(
                     Level::Info,
                     "[PARSE] strip packing instance \"{}\": {} items ({} unique), {} strip height",
-                    json_instance.name,
+                    name,
                     spi.total_item_qty(),
                     spi.items.len(),
                     spi.strip_height
@@ -102,16 +419,62 @@ impl Parser {
                 log!(
                     Level::Info,
                     "[PARSE] bin packing instance \"{}\": {} items ({} unique), {} bins ({} unique)",
-                    json_instance.name,
+                    name,
                     bpi.total_item_qty(),
                     bpi.items.len(),
-                    bpi.bins.iter().map(|(_, qty)| *qty).sum::<usize>(),
-                    bpi.bins.len()
+                    bpi.bins
+                        .iter()
+                        .filter(|(bin, _)| bin.variant_of.is_none())
+                        .map(|(_, qty)| *qty)
+                        .sum::<usize>(),
+                    bpi.bins.iter().filter(|(bin, _)| bin.variant_of.is_none()).count()
                 );
             }
         }
 
-        instance
+        if let (Some(cache), Some((hits_before, misses_before))) =
+            (&self.shape_cache, cache_counts_before)
+        {
+            log!(
+                Level::Info,
+                "[PARSE] shape cache {}: {} shapes restored, {} preprocessed and stored",
+                cache.folder().display(),
+                cache.n_hits() - hits_before,
+                cache.n_misses() - misses_before
+            );
+        }
+
+        if is_rectangular(&instance) {
+            log!(
+                Level::Info,
+                "[PARSE] all items and bins are axis-aligned rectangles, collisions are resolved on bounding boxes"
+            );
+        } else if is_convex(&instance) {
+            log!(
+                Level::Info,
+                "[PARSE] all items and bins are convex, collisions are resolved with the separating axis theorem"
+            );
+        }
+
+        let (original_item_area, simplified_item_area) = instance
+            .items()
+            .iter()
+            .map(|(item, qty)| {
+                (
+                    item.original_area * *qty as fsize,
+                    item.simplified_area() * *qty as fsize,
+                )
+            })
+            .fold((0.0, 0.0), |(o, s), (io, is)| (o + io, s + is));
+        log!(
+            Level::Info,
+            "[PARSE] simplification changed the total item area by {:.3}% (from {:.3} to {:.3})",
+            (simplified_item_area / original_item_area - 1.0) * 100.0,
+            original_item_area,
+            simplified_item_area
+        );
+
+        Ok(instance)
     }
 
     /// Parses a `JsonInstance` and accompanying `JsonLayout`s into an `Instance` and `Solution`.
@@ -119,154 +482,737 @@ impl Parser {
         &self,
         json_instance: &JsonInstance,
         json_layouts: &[JsonLayout],
-    ) -> (Instance, Solution) {
-        let instance = Arc::new(self.parse(json_instance));
-        let solution = build_solution_from_json(instance.as_ref(), json_layouts, self.cde_config);
+    ) -> Result<(Instance, Solution), ParseError> {
+        let instance = Arc::new(self.parse(json_instance)?);
+        let solution = build_solution_from_json(instance.as_ref(), json_layouts, self.cde_config)?;
         let instance =
             Arc::try_unwrap(instance).expect("Cannot unwrap instance, strong references present");
-        (instance, solution)
+        Ok((instance, solution))
     }
 
-    fn parse_item(&self, json_item: &JsonItem, item_id: usize) -> (Item, usize) {
-        let shape = match &json_item.shape {
-            JsonShape::Rectangle { width, height } => {
-                SimplePolygon::from(AARectangle::new(0.0, 0.0, *width, *height))
-            }
-            JsonShape::SimplePolygon(sp) => {
-                convert_json_simple_poly(sp, self.poly_simpl_config, PolySimplMode::Inflate)
-            }
-            JsonShape::Polygon(_) => {
-                unimplemented!("No support for polygon shapes yet")
-            }
-            JsonShape::MultiPolygon(_) => {
-                unimplemented!("No support for multipolygon shapes yet")
-            }
+    /// Preprocessed geometry of an item, restored from the shape cache if possible, ready to be shared between items
+    fn shared_item_geometry(
+        &self,
+        json_item: &JsonItem,
+        item_id: usize,
+        units: Option<Units>,
+        files: &ItemFiles,
+    ) -> Result<SharedItemGeometry, ParseError> {
+        let geometry = match &self.shape_cache {
+            Some(cache) => cache.try_get_or_insert_with(
+                &self.item_cache_key(json_item, item_id, units, files),
+                || self.item_geometry(json_item, item_id, units, files),
+            )?,
+            None => self.item_geometry(json_item, item_id, units, files)?,
+        };
+        Ok(SharedItemGeometry {
+            shape: Arc::new(geometry.shape),
+            arc_shape: geometry.arc_shape.map(Arc::new),
+            original_area: geometry.original_area,
+            centering: geometry.centering,
+            dxf_metadata: geometry.dxf_metadata,
+            curve_deviation: geometry.curve_deviation,
+        })
+    }
+
+    /// Creates an item from its definition, of which the shape is already preprocessed in `geometry`
+    fn parse_item(
+        &self,
+        json_item: &JsonItem,
+        item_id: usize,
+        geometry: &SharedItemGeometry,
+    ) -> Result<(Item, usize), ParseError> {
+        let invalid = |reason: String| ParseError::InvalidItem {
+            item: item_id,
+            reason,
         };
+        let SharedItemGeometry {
+            shape,
+            arc_shape,
+            original_area,
+            centering,
+            dxf_metadata,
+            curve_deviation,
+        } = geometry;
 
-        let item_value = json_item.value.unwrap_or(0);
+        //values defined in the JSON take precedence over the ones embedded in the DXF file
+        let demand = json_item
+            .demand
+            .or(dxf_metadata.demand)
+            .ok_or_else(|| invalid("no demand specified".into()))?;
+
+        let item_value = json_item.value.or(dxf_metadata.value).unwrap_or(0);
 
         let base_quality = json_item.base_quality;
 
-        let allowed_orientations = match json_item.allowed_orientations.as_ref() {
-            Some(a_o) => {
+        if json_item.allowed_transformations.is_some() && json_item.allowed_orientations.is_some() {
+            return Err(invalid(
+                "both allowed orientations and allowed transformations defined".into(),
+            ));
+        }
+
+        let allowed_orientations = match (
+            &json_item.allowed_transformations,
+            json_item
+                .allowed_orientations
+                .as_ref()
+                .or(dxf_metadata.allowed_orientations.as_ref()),
+        ) {
+            (Some(a_t), _) => {
+                if a_t.is_empty() {
+                    return Err(invalid("empty list of allowed transformations".into()));
+                }
+                AllowedRotation::Explicit(
+                    a_t.iter()
+                        .map(|t| DTransformation::new(t.rotation, t.translation))
+                        .collect(),
+                )
+            }
+            (None, Some(a_o)) => {
                 if a_o.is_empty() || (a_o.len() == 1 && a_o[0] == 0.0) {
                     AllowedRotation::None
                 } else {
                     AllowedRotation::Discrete(a_o.iter().map(|angle| angle.to_radians()).collect())
                 }
             }
-            None => AllowedRotation::Continuous,
+            (None, None) => AllowedRotation::Continuous,
         };
 
-        let base_item = Item::new(
+        let pretransform = match centering {
+            Some(translation) => Transformation::empty()
+                .transform(&DTransformation::new(0.0, *translation).compose()),
+            None => Transformation::empty(),
+        };
+
+        let mut item = Item::from_preprocessed(
             item_id,
-            shape,
+            Arc::clone(shape),
             allowed_orientations,
             base_quality,
             item_value,
-            Transformation::empty(),
-            self.cde_config.item_surrogate_config,
+            pretransform,
+            self.item_surrogate_config(json_item),
         );
+        item.arc_shape = arc_shape.clone();
+        item.original_area = *original_area;
+        item.curve_deviation = *curve_deviation;
+        item.name = json_item.name.clone().or_else(|| {
+            let file_path = Path::new(json_item.dxf.as_ref().or(json_item.svg.as_ref())?);
+            Some(file_path.file_stem()?.to_string_lossy().into_owned())
+        });
+        item.material = json_item.material.clone();
+        item.shortfall_cost = json_item.shortfall_cost.unwrap_or(0);
+        if let Some(json_copies) = &json_item.copies {
+            item.copy_attributes = vec![CopyAttributes::default(); demand as usize];
+            for json_copy in json_copies {
+                if json_copy.copy >= demand as usize {
+                    return Err(invalid(format!(
+                        "copy {} exceeds its demand of {}",
+                        json_copy.copy, demand
+                    )));
+                }
+                item.copy_attributes[json_copy.copy] = CopyAttributes {
+                    bin_id: json_copy.bin,
+                    locked_transf: json_copy
+                        .transformation
+                        .as_ref()
+                        .map(|t| DTransformation::new(t.rotation, t.translation)),
+                };
+            }
+        }
+
+        Ok((item, demand as usize))
+    }
+
+    /// Preprocesses the shape of an item: everything which only depends on its definition in the input
+    fn item_geometry(
+        &self,
+        json_item: &JsonItem,
+        item_id: usize,
+        units: Option<Units>,
+        files: &ItemFiles,
+    ) -> Result<ItemGeometry, ParseError> {
+        //only assigned for items defined by a dxf file
+        #[cfg_attr(not(feature = "dxf"), allow(unused_mut))]
+        let mut dxf_metadata = DxfItemMetadata::default();
+        let mut arc_shape: Option<ArcPolygon> = None;
+        //only assigned for items defined by a dxf or svg file
+        let mut file_original_area = 0.0;
+        let invalid = |reason: String| ParseError::InvalidItem {
+            item: item_id,
+            reason,
+        };
+        let mut shape = match (&json_item.shape, &json_item.dxf) {
+            (Some(JsonShape::Rectangle { width, height }), _) => {
+                rectangle(*width, *height).map_err(invalid)?
+            }
+            (Some(JsonShape::SimplePolygon(sp)), _) => {
+                convert_json_simple_poly(sp, self.poly_simpl_config, PolySimplMode::Inflate)
+                    .map_err(invalid)?
+            }
+            (Some(JsonShape::Polygon(jp)), _) => self
+                .item_polygon(jp, self.poly_simpl_config)
+                .map_err(invalid)?,
+            (Some(JsonShape::MultiPolygon(jps)), _) => self.multi_polygon_item(jps, item_id)?,
+            (Some(JsonShape::Wkt(_)), _) => unreachable!("{}", WKT_RESOLVED),
+            #[cfg(feature = "dxf")]
+            (None, Some(dxf_path)) => {
+                let file = files.get(self, item_id, dxf_path)?;
+                let (outline, holes, metadata, dxf_mm_per_unit) = convert_dxf_file(
+                    &file.path,
+                    file.drawing()?,
+                    &self.dxf_layer_mapping,
+                    json_item.dxf_layer.as_deref(),
+                    json_item.dxf_block.as_deref(),
+                    self.fidelity,
+                )?;
+                //drawings which declare their units are converted from those, instead of the units of the instance
+                let scale = match (units, dxf_mm_per_unit) {
+                    (Some(_), Some(mm_per_unit)) => mm_per_unit,
+                    _ => Units::scale(units),
+                };
+                let (outline, holes) = scale_contours(outline, holes, scale);
+                let (shape, dxf_arc_shape, original_area) =
+                    self.file_item_shape(&file.path, "dxf", outline, holes)?;
+                dxf_metadata = metadata;
+                arc_shape = dxf_arc_shape;
+                file_original_area = original_area;
+                shape
+            }
+            #[cfg(not(feature = "dxf"))]
+            (None, Some(_)) => {
+                return Err(ParseError::InvalidItem {
+                    item: item_id,
+                    reason: "defined by a dxf file, which requires the dxf feature".into(),
+                });
+            }
+            (None, None) => {
+                let svg_path = json_item
+                    .svg
+                    .as_ref()
+                    .ok_or_else(|| ParseError::InvalidItem {
+                        item: item_id,
+                        reason: "no shape, dxf or svg file specified".into(),
+                    })?;
+                let file = files.get(self, item_id, svg_path)?;
+                let (outline, holes) = convert_svg_file(
+                    &file.path,
+                    &file.contents,
+                    json_item.svg_id.as_deref(),
+                    self.fidelity,
+                )?;
+                let (outline, holes) = scale_contours(outline, holes, Units::scale(units));
+                let (shape, svg_arc_shape, original_area) =
+                    self.file_item_shape(&file.path, "svg", outline, holes)?;
+                arc_shape = svg_arc_shape;
+                file_original_area = original_area;
+                shape
+            }
+        };
+
+        let original_area = match &json_item.shape {
+            Some(json_shape) => json_shape_area(json_shape),
+            None => file_original_area,
+        };
+        //only the curves in dxf and svg files are flattened, circular arcs are tessellated conservatively
+        let curve_deviation = match &json_item.shape {
+            Some(_) => 0.0,
+            None => {
+                let bbox = shape.bbox();
+                self.fidelity
+                    .max_deviation(fsize::max(bbox.width(), bbox.height()))
+            }
+        };
+
+        //the surrogate is generated after centering, so it matches the one of a pretransformed item exactly
+        let centering = self.center_polygons.then(|| {
+            let centering_transform = centering_transformation(&shape);
+            let transform = centering_transform.compose();
+            shape.transform(&transform);
+            if let Some(arc_shape) = arc_shape.as_mut() {
+                arc_shape.transform(&transform);
+            }
+            centering_transform.translation()
+        });
+        shape.generate_surrogate(self.item_surrogate_config(json_item));
+
+        Ok(ItemGeometry {
+            shape,
+            arc_shape,
+            original_area,
+            centering,
+            dxf_metadata,
+            curve_deviation,
+        })
+    }
+
+    /// Contour of an item with holes: a keyhole polygon if other items may be placed in its holes, otherwise its outer contour
+    fn item_polygon(
+        &self,
+        jp: &JsonPoly,
+        simpl_config: PolySimplConfig,
+    ) -> Result<SimplePolygon, String> {
+        if self.part_in_part_forbidden {
+            //filled holes cannot contain other items
+            return convert_json_simple_poly(&jp.outer, simpl_config, PolySimplMode::Inflate);
+        }
+        //holes are shrunk by simplification, so the item only grows
+        let outer = convert_json_simple_poly(&jp.outer, simpl_config, PolySimplMode::Inflate)?;
+        let holes = jp
+            .inner
+            .iter()
+            .map(|jsp| {
+                convert_json_simple_poly(jsp, simpl_config, PolySimplMode::Deflate)
+                    .map(|hole| hole.points)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        SimplePolygon::try_new(polygon_holes::merge_holes(outer.points, holes)?)
+    }
+
+    /// Contour of an item consisting of several disjoint parts, which are placed together as a rigid compound.
+    /// The parts are joined by narrow channels, see [`polygon_holes::bridge_parts`]
+    fn multi_polygon_item(
+        &self,
+        jps: &[JsonPoly],
+        item_id: usize,
+    ) -> Result<SimplePolygon, ParseError> {
+        let bridge = |simpl_config| {
+            let parts = jps
+                .iter()
+                .map(|jp| self.item_polygon(jp, simpl_config))
+                .collect::<Result<Vec<_>, _>>()?;
+            polygon_holes::bridge_parts(parts)
+        };
+        let points = match bridge(self.poly_simpl_config) {
+            Ok(points) => points,
+            //parts which lie close together can overlap once they are inflated by simplification
+            Err(_) if !matches!(self.poly_simpl_config, PolySimplConfig::Disabled) => {
+                log!(
+                    Level::Warn,
+                    "[PARSE] parts of item {} overlap after simplification, the item is not simplified",
+                    item_id
+                );
+                bridge(PolySimplConfig::Disabled).map_err(|reason| ParseError::InvalidItem {
+                    item: item_id,
+                    reason,
+                })?
+            }
+            Err(reason) => {
+                return Err(ParseError::InvalidItem {
+                    item: item_id,
+                    reason,
+                })
+            }
+        };
+        SimplePolygon::try_new(points).map_err(|reason| ParseError::InvalidItem {
+            item: item_id,
+            reason,
+        })
+    }
+
+    /// The asset paths with which the files of the items of an instance are read: the ones of the parser,
+    /// with the `assets` embedded in the instance added
+    fn instance_asset_paths(&self, assets: &BTreeMap<String, JsonAsset>) -> Cow<'_, AssetPaths> {
+        match assets.is_empty() {
+            true => Cow::Borrowed(&self.asset_paths),
+            false => Cow::Owned(assets.iter().fold(
+                self.asset_paths.clone(),
+                |asset_paths, (name, asset)| {
+                    asset_paths.with_embedded_asset(name.clone(), asset.0.clone())
+                },
+            )),
+        }
+    }
+
+    /// Contents of the file of an item, together with its path (the name of an embedded file)
+    fn read_asset(
+        &self,
+        asset_paths: &AssetPaths,
+        item_id: usize,
+        file: &str,
+    ) -> Result<(PathBuf, Arc<[u8]>), ParseError> {
+        if let Some(contents) = asset_paths.embedded_asset(file) {
+            return Ok((PathBuf::from(file), contents.clone()));
+        }
+        let path = self.resolve_asset(asset_paths, item_id, file)?;
+        let contents = std::fs::read(&path).map_err(|source| ParseError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        Ok((path, contents.into()))
+    }
+
+    /// Path of the file of an item, logged if it is not the one in the assets folder
+    fn resolve_asset(
+        &self,
+        asset_paths: &AssetPaths,
+        item_id: usize,
+        file: &str,
+    ) -> Result<PathBuf, ParseError> {
+        let path = asset_paths.resolve(file).map_err(|source| ParseError::Io {
+            path: PathBuf::from(file),
+            source,
+        })?;
+        if path != asset_paths.default_path(file) {
+            log!(
+                Level::Info,
+                "[PARSE] item {}: {} resolved to {}",
+                item_id,
+                file,
+                path.display()
+            );
+        }
+        Ok(path)
+    }
+
+    /// Shape of an item read from a (`kind`) file, from its outline and holes:
+    /// a keyhole polygon as for [`Self::item_polygon`], or the outline alone if other items may not be placed in its holes.
+    /// Also returns the exact outline if it has arcs and no holes are merged into the shape, and the area of the contours.
+    fn file_item_shape(
+        &self,
+        path: &Path,
+        kind: &str,
+        outline: ArcPolygon,
+        holes: Vec<ArcPolygon>,
+    ) -> Result<(SimplePolygon, Option<ArcPolygon>, fsize), ParseError> {
+        let invalid = |reason: String| ParseError::InvalidFile {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let original_area = outline.signed_area().abs()
+            - holes.iter().map(|h| h.signed_area().abs()).sum::<fsize>();
+        let contour = |arc_polygon: &ArcPolygon, mode: PolySimplMode| {
+            let points = arc_polygon_to_points(arc_polygon, self.fidelity, mode);
+            let min_edge_length = polygon_cleanup::min_edge_length(&points);
+            let (points, cleanup_stats) = polygon_cleanup::clean_points(points, min_edge_length);
+            if cleanup_stats.total() > 0 {
+                log!(
+                    Level::Info,
+                    "[PARSE] cleaned {} polygon {}: removed {} duplicate, {} short edge and {} collinear vertices",
+                    kind,
+                    path.display(),
+                    cleanup_stats.n_duplicates,
+                    cleanup_stats.n_short_edges,
+                    cleanup_stats.n_collinear
+                );
+            }
+            points
+        };
 
-        let item = match self.center_polygons {
-            false => base_item,
+        //holes are shrunk by tessellation and simplification, so the item only grows
+        let outer = simplify_poly(
+            SimplePolygon::try_new(contour(&outline, PolySimplMode::Inflate)).map_err(invalid)?,
+            self.poly_simpl_config,
+            PolySimplMode::Inflate,
+        );
+        let holes = match self.part_in_part_forbidden {
+            true => vec![],
+            false => holes
+                .iter()
+                .map(|hole| contour(hole, PolySimplMode::Deflate))
+                .filter(|points| points.len() >= 3 && SimplePolygon::calculate_area(points) != 0.0)
+                .map(|points| {
+                    simplify_poly(
+                        SimplePolygon::new(points),
+                        self.poly_simpl_config,
+                        PolySimplMode::Deflate,
+                    )
+                    .points
+                })
+                .collect_vec(),
+        };
+        match holes.is_empty() {
             true => {
-                let centering_transform = centering_transformation(&base_item.shape);
-                pretransform_item(&base_item, &centering_transform.compose())
+                let arc_shape = outline.has_arcs().then_some(outline);
+                Ok((outer, arc_shape, original_area))
+            }
+            false => {
+                //the exact outline would omit the holes
+                let shape = polygon_holes::merge_holes(outer.points, holes)
+                    .and_then(SimplePolygon::try_new)
+                    .map_err(invalid)?;
+                Ok((shape, None, original_area))
+            }
+        }
+    }
+
+    /// Everything which determines the outcome of [`Self::item_geometry`]
+    fn item_cache_key<'a>(
+        &self,
+        json_item: &'a JsonItem,
+        item_id: usize,
+        units: Option<Units>,
+        files: &ItemFiles,
+    ) -> impl Serialize + 'a {
+        //items defined by a dxf or svg file are keyed by the contents of the file (embedded or not), not its path
+        let file_hash = |path: &str| files.get(self, item_id, path).ok().map(|file| file.hash);
+        #[cfg(feature = "dxf")]
+        let dxf_hash = json_item.dxf.as_deref().map(file_hash);
+        #[cfg(not(feature = "dxf"))]
+        let dxf_hash: Option<Option<u64>> = None;
+        let svg_hash = json_item.svg.as_deref().map(file_hash);
+        (
+            "item",
+            &json_item.shape,
+            dxf_hash,
+            &json_item.dxf_layer,
+            &json_item.dxf_block,
+            self.dxf_layer_mapping.clone(),
+            svg_hash,
+            &json_item.svg_id,
+            //files are scaled to the units of the instance
+            units,
+            self.poly_simpl_config,
+            self.fidelity,
+            self.item_surrogate_config(json_item),
+            self.center_polygons,
+            self.part_in_part_forbidden,
+        )
+    }
+
+    /// Surrogate configuration of an item: the configured one, with the overrides of the item applied
+    fn item_surrogate_config(&self, json_item: &JsonItem) -> SPSurrogateConfig {
+        let config = self.cde_config.item_surrogate_config;
+        match json_item.surrogate {
+            Some(overrides) => SPSurrogateConfig {
+                pole_coverage_goal: overrides
+                    .pole_coverage_goal
+                    .unwrap_or(config.pole_coverage_goal),
+                max_poles: overrides.max_poles.unwrap_or(config.max_poles),
+                n_ff_poles: overrides.n_ff_poles.unwrap_or(config.n_ff_poles),
+                n_ff_piers: overrides.n_ff_piers.unwrap_or(config.n_ff_piers),
+            },
+            None => config,
+        }
+    }
+
+    fn parse_bin(&self, json_bin: &JsonBin, bin_id: usize) -> Result<(Bin, usize), ParseError> {
+        let BinGeometry {
+            outer,
+            holes,
+            quality_zones,
+            original_area,
+            centering,
+            material_value,
+        } = match &self.shape_cache {
+            Some(cache) => cache.try_get_or_insert_with(&self.bin_cache_key(json_bin), || {
+                self.bin_geometry(json_bin, bin_id)
+            })?,
+            None => self.bin_geometry(json_bin, bin_id)?,
+        };
+
+        let pretransform = match centering {
+            Some(translation) => {
+                Transformation::empty().transform(&DTransformation::new(0.0, translation).compose())
             }
+            None => Transformation::empty(),
         };
 
-        (item, json_item.demand as usize)
+        let quality_zones = quality_zones
+            .into_iter()
+            .enumerate()
+            .map(|(quality, zones)| InferiorQualityZone::new(quality, zones))
+            .collect_vec();
+
+        let mut bin = Bin::new(
+            bin_id,
+            outer,
+            material_value,
+            pretransform,
+            holes,
+            quality_zones,
+            self.cde_config,
+        );
+        bin.original_area = original_area;
+        bin.temporary_zones = json_bin
+            .temporary_zones
+            .iter()
+            .map(|json_zone| self.parse_temporary_zone(json_zone, &bin.pretransform, bin_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        bin.name = json_bin.name.clone();
+        bin.material = json_bin.material.clone();
+        if let Some(max_usage) = json_bin.max_usage {
+            if !(max_usage > 0.0 && max_usage <= 1.0) {
+                return Err(ParseError::InvalidBin {
+                    bin: bin_id,
+                    reason: format!("maximum usage should be in (0, 1], got {}", max_usage),
+                });
+            }
+            bin.max_usage = Some(max_usage);
+        }
+
+        let stock = json_bin.stock.unwrap_or(u64::MAX) as usize;
+
+        Ok((bin, stock))
     }
 
-    fn parse_bin(&self, json_bin: &JsonBin, bin_id: usize) -> (Bin, usize) {
-        let bin_outer = match &json_bin.shape {
+    /// Converts a temporary zone to the coordinates of its bin (with `bin_pretransform` applied)
+    fn parse_temporary_zone(
+        &self,
+        json_zone: &JsonTemporaryZone,
+        bin_pretransform: &Transformation,
+        bin_id: usize,
+    ) -> Result<TemporaryZone, ParseError> {
+        let invalid = |reason: String| ParseError::InvalidBin {
+            bin: bin_id,
+            reason: format!("temporary zone: {}", reason),
+        };
+        let shape = match &json_zone.shape {
             JsonShape::Rectangle { width, height } => {
-                SimplePolygon::from(AARectangle::new(0.0, 0.0, *width, *height))
+                rectangle(*width, *height).map_err(invalid)?
             }
             JsonShape::SimplePolygon(jsp) => {
+                convert_json_simple_poly(jsp, self.poly_simpl_config, PolySimplMode::Inflate)
+                    .map_err(invalid)?
+            }
+            JsonShape::Polygon(_) | JsonShape::MultiPolygon(_) => {
+                return Err(ParseError::InvalidBin {
+                    bin: bin_id,
+                    reason: "temporary zones have to be rectangles or simple polygons".into(),
+                });
+            }
+            JsonShape::Wkt(_) => unreachable!("{}", WKT_RESOLVED),
+        };
+        let mut shape = shape.transform_clone(bin_pretransform);
+        shape.generate_surrogate(self.cde_config.item_surrogate_config);
+        Ok(TemporaryZone::new(
+            shape,
+            json_zone.active_from.unwrap_or(0),
+            json_zone.active_until,
+        ))
+    }
+
+    /// Preprocesses the shapes of a bin: everything which only depends on its definition in the input
+    fn bin_geometry(&self, json_bin: &JsonBin, bin_id: usize) -> Result<BinGeometry, ParseError> {
+        let invalid = |reason: &str| ParseError::InvalidBin {
+            bin: bin_id,
+            reason: reason.into(),
+        };
+        let invalid_shape = |reason: String| ParseError::InvalidBin {
+            bin: bin_id,
+            reason,
+        };
+        let mut outer = match &json_bin.shape {
+            Some(JsonShape::Rectangle { width, height }) => {
+                rectangle(*width, *height).map_err(invalid_shape)?
+            }
+            Some(JsonShape::SimplePolygon(jsp)) => {
                 convert_json_simple_poly(jsp, self.poly_simpl_config, PolySimplMode::Deflate)
+                    .map_err(invalid_shape)?
             }
-            JsonShape::Polygon(jp) => {
+            Some(JsonShape::Polygon(jp)) => {
                 convert_json_simple_poly(&jp.outer, self.poly_simpl_config, PolySimplMode::Deflate)
+                    .map_err(invalid_shape)?
             }
-            JsonShape::MultiPolygon(_) => {
-                unimplemented!("No support for multipolygon shapes yet")
+            Some(JsonShape::MultiPolygon(_)) => {
+                return Err(invalid("no support for multipolygon shapes yet"))
             }
-            None => panic!("No shape specified for bin"),
+            Some(JsonShape::Wkt(_)) => unreachable!("{}", WKT_RESOLVED),
+            None => return Err(invalid("no shape specified")),
         };
 
-        let bin_holes = match &json_bin.shape {
+        let mut holes = match &json_bin.shape {
             Some(JsonShape::SimplePolygon(_)) | Some(JsonShape::Rectangle { .. }) => vec![],
             Some(JsonShape::Polygon(jp)) => jp
                 .inner
                 .iter()
                 .map(|jsp| {
                     convert_json_simple_poly(jsp, self.poly_simpl_config, PolySimplMode::Inflate)
+                        .map_err(invalid_shape)
                 })
-                .collect_vec(),
+                .collect::<Result<Vec<_>, _>>()?,
             Some(JsonShape::MultiPolygon(_)) => {
-                unimplemented!("No support for multipolygon shapes yet")
+                return Err(invalid("no support for multipolygon shapes yet"))
             }
-            None => panic!("No shape specified for bin"),
+            Some(JsonShape::Wkt(_)) => unreachable!("{}", WKT_RESOLVED),
+            None => return Err(invalid("no shape specified")),
         };
 
         let material_value =
-            (bin_outer.area() - bin_holes.iter().map(|hole| hole.area()).sum::<fsize>()) as u64;
-
-        assert!(
-            json_bin.zones.iter().all(|zone| zone.quality < N_QUALITIES),
-            "Quality must be less than N_QUALITIES"
-        );
+            (outer.area() - holes.iter().map(|hole| hole.area()).sum::<fsize>()) as u64;
+
+        if let Some(zone) = json_bin.zones.iter().find(|z| z.quality >= N_QUALITIES) {
+            return Err(ParseError::InvalidBin {
+                bin: bin_id,
+                reason: format!(
+                    "quality {} of a zone should be less than {}",
+                    zone.quality, N_QUALITIES
+                ),
+            });
+        }
 
-        let quality_zones = (0..N_QUALITIES)
+        //overlapping zones of the same quality are merged, and zones with holes decomposed into simple polygons
+        let mut quality_zones = (0..N_QUALITIES)
             .map(|quality| {
-                let zones = json_bin
+                let zone_shapes = json_bin
                     .zones
                     .iter()
                     .filter(|zone| zone.quality == quality)
-                    .map(|zone| match &zone.shape {
-                        JsonShape::Rectangle { width, height } => {
-                            SimplePolygon::from(AARectangle::new(0.0, 0.0, *width, *height))
+                    .flat_map(|zone| match &zone.shape {
+                        JsonShape::Rectangle { width, height } => vec![ZoneShape {
+                            outer: SimplePolygon::from(AARectangle::new(0.0, 0.0, *width, *height))
+                                .points,
+                            holes: vec![],
+                        }],
+                        JsonShape::SimplePolygon(jsp) => vec![ZoneShape {
+                            outer: json_simple_poly_to_points(jsp),
+                            holes: vec![],
+                        }],
+                        JsonShape::Polygon(jp) => vec![json_poly_to_zone_shape(jp)],
+                        JsonShape::MultiPolygon(jps) => {
+                            jps.iter().map(json_poly_to_zone_shape).collect_vec()
                         }
-                        JsonShape::SimplePolygon(jsp) => convert_json_simple_poly(
-                            jsp,
+                        JsonShape::Wkt(_) => unreachable!("{}", WKT_RESOLVED),
+                    })
+                    .collect_vec();
+                zone_union::union_zones(zone_shapes)
+                    .into_iter()
+                    .map(|points| {
+                        let zone = SimplePolygon::try_new(points).map_err(|reason| {
+                            invalid_shape(format!("zone of quality {}: {}", quality, reason))
+                        })?;
+                        Ok(simplify_poly(
+                            zone,
                             self.poly_simpl_config,
                             PolySimplMode::Inflate,
-                        ),
-                        JsonShape::Polygon(_) => {
-                            unimplemented!("No support for polygon to simplepolygon conversion yet")
-                        }
-                        JsonShape::MultiPolygon(_) => {
-                            unimplemented!("No support for multipolygon shapes yet")
-                        }
+                        ))
                     })
-                    .collect_vec();
-                InferiorQualityZone::new(quality, zones)
+                    .collect::<Result<Vec<_>, ParseError>>()
             })
-            .collect_vec();
-
-        let base_bin = Bin::new(
-            bin_id,
-            bin_outer,
-            material_value,
-            Transformation::empty(),
-            bin_holes,
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let centering = self.center_polygons.then(|| {
+            let centering_transform = centering_transformation(&outer);
+            let transform = centering_transform.compose();
+            outer.transform(&transform);
+            holes
+                .iter_mut()
+                .chain(quality_zones.iter_mut().flatten())
+                .for_each(|shape| {
+                    shape.transform(&transform);
+                });
+            centering_transform.translation()
+        });
+
+        Ok(BinGeometry {
+            outer,
+            holes,
             quality_zones,
-            self.cde_config,
-        );
-
-        let bin = match self.center_polygons {
-            false => base_bin,
-            true => {
-                let centering_transform = centering_transformation(&base_bin.outer);
-                pretransform_bin(&base_bin, &centering_transform.compose())
-            }
-        };
-
-        let stock = json_bin.stock.unwrap_or(u64::MAX) as usize;
+            original_area: json_bin.shape.as_ref().map_or(0.0, json_shape_area),
+            centering,
+            material_value,
+        })
+    }
 
-        (bin, stock)
+    /// Everything which determines the outcome of [`Self::bin_geometry`]
+    fn bin_cache_key<'a>(&self, json_bin: &'a JsonBin) -> impl Serialize + 'a {
+        (
+            "bin",
+            &json_bin.shape,
+            &json_bin.zones,
+            self.poly_simpl_config,
+            self.center_polygons,
+        )
     }
 
     // pub fn parse_dxf(&self, dxf_instance: &DxfInstance) -> Instance {
@@ -322,17 +1268,21 @@ impl Parser {
 }
 
 /// Builds a `Solution` from a set of `JsonLayout`s and an `Instance`.
+/// Returns an error if the layouts do not match the instance, the placements themselves are not checked for feasibility.
 pub fn build_solution_from_json(
     instance: &Instance,
     json_layouts: &[JsonLayout],
     cde_config: CDEConfig,
-) -> Solution {
+) -> Result<Solution, ParseError> {
     match instance {
         Instance::BP(bp_i) => build_bin_packing_solution(bp_i, json_layouts),
-        Instance::SP(sp_i) => {
-            assert_eq!(json_layouts.len(), 1);
-            build_strip_packing_solution(sp_i, &json_layouts[0], cde_config)
-        }
+        Instance::SP(sp_i) => match json_layouts {
+            [json_layout] => build_strip_packing_solution(sp_i, json_layout, cde_config),
+            _ => Err(ParseError::InvalidSolution(format!(
+                "strip packing solution should contain exactly one layout, found {}",
+                json_layouts.len()
+            ))),
+        },
     }
 }
 
@@ -340,22 +1290,21 @@ pub fn build_strip_packing_solution(
     instance: &SPInstance,
     json_layout: &JsonLayout,
     cde_config: CDEConfig,
-) -> Solution {
-    let mut problem = match json_layout.container {
-        JsonContainer::Bin { .. } => {
-            panic!("Strip packing solution should not contain layouts with references to an Object")
-        }
-        JsonContainer::Strip { width, height: _ } => {
-            SPProblem::new(instance.clone(), width, cde_config)
-        }
-    };
+) -> Result<Solution, ParseError> {
+    let mut problem =
+        match json_layout.container {
+            JsonContainer::Bin { .. } => return Err(ParseError::InvalidSolution(
+                "strip packing solution should not contain layouts with references to an Object"
+                    .into(),
+            )),
+            JsonContainer::Strip { width, height: _ } => {
+                SPProblem::new(instance.clone(), width * instance.unit_scale(), cde_config)
+            }
+        };
 
     for json_item in json_layout.placed_items.iter() {
-        let item = instance.item(json_item.index);
-        let json_rotation = json_item.transformation.rotation;
-        let json_translation = json_item.transformation.translation;
-
-        let abs_transform = DTransformation::new(json_rotation, json_translation);
+        let item = solution_item(instance, json_item.index)?;
+        let abs_transform = json_transformation(&json_item.transformation, instance);
         let transform = absolute_to_internal_transform(
             &abs_transform,
             &item.pretransform,
@@ -374,37 +1323,79 @@ pub fn build_strip_packing_solution(
         problem.flush_changes();
     }
 
-    problem.create_solution(None)
+    Ok(problem.create_solution(None))
 }
 
-pub fn build_bin_packing_solution(instance: &BPInstance, json_layouts: &[JsonLayout]) -> Solution {
+pub fn build_bin_packing_solution(
+    instance: &BPInstance,
+    json_layouts: &[JsonLayout],
+) -> Result<Solution, ParseError> {
     let mut problem = BPProblem::new(instance.clone());
 
     for json_layout in json_layouts {
-        let bin = match json_layout.container {
-            JsonContainer::Bin { index } => &instance.bins[index].0,
-            JsonContainer::Strip { .. } => {
-                panic!("Bin packing solution should not contain layouts with references to a Strip")
+        let bin =
+            match json_layout.container {
+                JsonContainer::Bin { index } => match instance.bins.get(index) {
+                    Some((bin, _)) => bin,
+                    None => {
+                        return Err(ParseError::InvalidSolution(format!(
+                            "bin {} does not exist",
+                            index
+                        )))
+                    }
+                },
+                JsonContainer::Strip { .. } => return Err(ParseError::InvalidSolution(
+                    "bin packing solution should not contain layouts with references to a Strip"
+                        .into(),
+                )),
+            };
+        for json_item in &json_layout.placed_items {
+            let item = solution_item(instance, json_item.index)?;
+            if !bin.accepts(item) {
+                return Err(ParseError::InvalidSolution(format!(
+                    "item {} of material {:?} placed in bin {} of material {:?}",
+                    item.id, item.material, bin.id, bin.material
+                )));
             }
-        };
+        }
+        if let Some(max_usage) = bin.max_usage {
+            let item_area = json_layout
+                .placed_items
+                .iter()
+                .map(|json_item| instance.item(json_item.index).shape.area())
+                .sum::<fsize>();
+            if item_area / bin.area > max_usage + USAGE_TOLERANCE {
+                return Err(ParseError::InvalidSolution(format!(
+                    "maximum usage of bin {} exceeded: {:.3} > {:.3}",
+                    bin.id,
+                    item_area / bin.area,
+                    max_usage
+                )));
+            }
+        }
         //Create the layout by inserting the first item
 
+        if problem.bin_qtys()[bin.id] == 0 {
+            return Err(ParseError::InvalidSolution(format!(
+                "more layouts in bin {} than its stock of {}",
+                bin.id, instance.bins[bin.id].1
+            )));
+        }
+
         //Find the template layout matching the bin id in the JSON solution
         let template_index = problem
             .template_layouts()
             .iter()
             .position(|tl| tl.bin.id == bin.id)
-            .expect("no template layout found for bin");
+            .ok_or_else(|| {
+                ParseError::InvalidSolution(format!("no template layout found for bin {}", bin.id))
+            })?;
 
-        let json_first_item = json_layout
-            .placed_items
-            .first()
-            .expect("no items in layout");
+        let json_first_item = json_layout.placed_items.first().ok_or_else(|| {
+            ParseError::InvalidSolution(format!("layout in bin {} has no items", bin.id))
+        })?;
         let first_item = instance.item(json_first_item.index);
-        let abs_transform = DTransformation::new(
-            json_first_item.transformation.rotation,
-            json_first_item.transformation.translation,
-        );
+        let abs_transform = json_transformation(&json_first_item.transformation, instance);
 
         let transform = absolute_to_internal_transform(
             &abs_transform,
@@ -424,10 +1415,7 @@ pub fn build_bin_packing_solution(instance: &BPInstance, json_layouts: &[JsonLay
         //Insert the rest of the items
         for json_item in json_layout.placed_items.iter().skip(1) {
             let item = instance.item(json_item.index);
-            let json_rotation = json_item.transformation.rotation;
-            let json_translation = json_item.transformation.translation;
-
-            let abs_transform = DTransformation::new(json_rotation, json_translation);
+            let abs_transform = json_transformation(&json_item.transformation, instance);
             let transform = absolute_to_internal_transform(
                 &abs_transform,
                 &item.pretransform,
@@ -446,24 +1434,145 @@ pub fn build_bin_packing_solution(instance: &BPInstance, json_layouts: &[JsonLay
         }
     }
 
-    problem.create_solution(None)
+    Ok(problem.create_solution(None))
+}
+
+/// Transformation of a placement of a solution, in internal units
+fn json_transformation(
+    json_transformation: &JsonTransformation,
+    instance: &impl InstanceGeneric,
+) -> DTransformation {
+    let (tx, ty) = json_transformation.translation;
+    let scale = instance.unit_scale();
+    DTransformation::new(json_transformation.rotation, (tx * scale, ty * scale))
+}
+
+/// The item a placement of a solution refers to
+fn solution_item(instance: &impl InstanceGeneric, index: usize) -> Result<&Item, ParseError> {
+    match index < instance.items().len() {
+        true => Ok(instance.item(index)),
+        false => Err(ParseError::InvalidSolution(format!(
+            "item {} does not exist",
+            index
+        ))),
+    }
+}
+
+/// Composes a `JsonInstance` from an `Instance` and the `JsonInstance` it was parsed from.
+/// All shapes are replaced by the ones in the `Instance` (for example after simplification),
+/// expressed in the coordinates (and units) of the original input file.
+pub fn compose_json_instance(json_instance: &JsonInstance, instance: &Instance) -> JsonInstance {
+    let to_input = 1.0 / instance.unit_scale();
+    let items = json_instance
+        .items
+        .iter()
+        .zip(instance.items().iter())
+        .map(|(json_item, (item, _))| {
+            let shape = match &json_item.shape {
+                Some(JsonShape::Rectangle { .. }) => json_item.shape.clone(),
+                _ => {
+                    let inv_pretransf = item.pretransform.clone().inverse();
+                    let shape = item.shape.transform_clone(&inv_pretransf);
+                    let shape = units::scaled_simple_polygon(&shape, to_input);
+                    Some(JsonShape::SimplePolygon(simple_poly_to_json(&shape)))
+                }
+            };
+            JsonItem {
+                dxf: None,
+                dxf_layer: None,
+                dxf_block: None,
+                svg: None,
+                svg_id: None,
+                shape,
+                ..json_item.clone()
+            }
+        })
+        .collect_vec();
+
+    let bins = match (instance, json_instance.bins.as_ref()) {
+        (Instance::BP(bpi), Some(json_bins)) => Some(
+            json_bins
+                .iter()
+                .zip(bpi.bins.iter())
+                .map(|(json_bin, (bin, _))| compose_json_bin(json_bin, bin, to_input))
+                .collect_vec(),
+        ),
+        _ => json_instance.bins.clone(),
+    };
+
+    JsonInstance {
+        name: json_instance.name.clone(),
+        items,
+        bins,
+        strip: json_instance.strip.clone(),
+        units: json_instance.units,
+        //the items are defined by their shapes, instead of by the embedded files
+        assets: BTreeMap::new(),
+    }
+}
+
+fn compose_json_bin(json_bin: &JsonBin, bin: &Bin, to_input: fsize) -> JsonBin {
+    let inv_pretransf = bin.pretransform.clone().inverse();
+    let to_json = |sp: &SimplePolygon| {
+        simple_poly_to_json(&units::scaled_simple_polygon(
+            &sp.transform_clone(&inv_pretransf),
+            to_input,
+        ))
+    };
+
+    let shape = match &json_bin.shape {
+        Some(JsonShape::Rectangle { .. }) => json_bin.shape.clone(),
+        _ => match bin.holes.is_empty() {
+            true => Some(JsonShape::SimplePolygon(to_json(&bin.outer))),
+            false => Some(JsonShape::Polygon(JsonPoly {
+                outer: to_json(&bin.outer),
+                inner: bin.holes.iter().map(|h| to_json(h)).collect_vec(),
+            })),
+        },
+    };
+
+    let zones = bin
+        .quality_zones
+        .iter()
+        .flatten()
+        .flat_map(|qz| {
+            qz.zones.iter().map(|z| JsonQualityZone {
+                quality: qz.quality,
+                shape: JsonShape::SimplePolygon(to_json(z)),
+            })
+        })
+        .collect_vec();
+
+    JsonBin {
+        shape,
+        zones,
+        ..json_bin.clone()
+    }
 }
 
 /// Composes a `JsonSolution` from a `Solution` and an `Instance`.
+/// The clearances of all layouts are verified, all clearances below `clearance_threshold` are flagged.
+/// The solution is evaluated on all `objectives`, if any.
+/// All lengths are expressed in the units of the input, see [`Units`].
 pub fn compose_json_solution(
     solution: &Solution,
     instance: &Instance,
     epoch: Instant,
+    clearance_threshold: fsize,
+    objectives: &[WeightedObjective],
 ) -> JsonSolution {
+    let to_input = 1.0 / instance.unit_scale();
     let layouts = solution
         .layout_snapshots
         .iter()
         .map(|sl| {
             let container = match &instance {
-                Instance::BP(_bpi) => JsonContainer::Bin { index: sl.bin.id },
+                Instance::BP(_bpi) => JsonContainer::Bin {
+                    index: sl.bin.base_id(),
+                },
                 Instance::SP(spi) => JsonContainer::Strip {
-                    width: sl.bin.bbox().width(),
-                    height: spi.strip_height,
+                    width: sl.bin.bbox().width() * to_input,
+                    height: spi.strip_height * to_input,
                 },
             };
 
@@ -481,27 +1590,103 @@ pub fn compose_json_solution(
                     )
                     .decompose();
 
+                    let (tx, ty) = abs_transf.translation();
                     JsonPlacedItem {
                         index: item_index,
+                        copy: placed_item.copy,
                         transformation: JsonTransformation {
                             rotation: abs_transf.rotation(),
-                            translation: abs_transf.translation(),
+                            translation: (tx * to_input, ty * to_input),
                         },
                     }
                 })
                 .collect::<Vec<JsonPlacedItem>>();
-            let statistics = JsonLayoutStats { usage: sl.usage };
+            let clearance_report = clearance::verify_clearances(
+                instance,
+                &sl.bin,
+                &sl.placed_items,
+                clearance_threshold,
+            );
+            let pi_indices = placed_item_indices(&sl.placed_items);
+            let clearance_violations = clearance_report
+                .violations
+                .iter()
+                .map(|c| JsonClearance {
+                    placed_item: pi_indices[&c.item],
+                    other: json_clearance_entity(c.other, &pi_indices),
+                    distance: c.distance * to_input,
+                    lower_bound: c.lower_bound * to_input,
+                })
+                .collect_vec();
+            let skyline = match &instance {
+                Instance::BP(_) => vec![],
+                Instance::SP(_) => {
+                    layout_metrics::skyline(&sl.bin, &sl.placed_items, SKYLINE_RESOLUTION)
+                        .into_iter()
+                        .map(|(y, x)| (y * to_input, x * to_input))
+                        .collect()
+                }
+            };
+            let statistics = JsonLayoutStats {
+                usage: sl.usage,
+                original_usage: layout_metrics::original_usage(&sl.bin, &sl.placed_items, instance),
+                bbox_usage: layout_metrics::bbox_usage(&sl.placed_items),
+                convex_hull_usage: layout_metrics::convex_hull_usage(&sl.placed_items),
+                skyline,
+                min_clearance: clearance_report
+                    .min_clearance
+                    .map(|c| c.distance * to_input),
+                clearance_violations,
+                thin_webs: vec![],
+            };
             JsonLayout {
                 container,
+                name: sl.bin.name.clone(),
                 placed_items,
+                cut_order: vec![],
                 statistics,
             }
         })
         .collect::<Vec<JsonLayout>>();
 
+    let min_clearance = layouts
+        .iter()
+        .filter_map(|l| l.statistics.min_clearance)
+        .reduce(fsize::min);
+
+    let mut objective_vector = ObjectiveVector::evaluate(solution, instance, objectives);
+    for (wo, value) in objectives.iter().zip(objective_vector.values.iter_mut()) {
+        if wo.objective == Objective::CutLength {
+            *value *= to_input;
+        }
+    }
+    let score = (!objectives.is_empty()).then(|| objective_vector.score());
+    let objectives = objectives
+        .iter()
+        .zip(objective_vector.values.iter())
+        .map(|(wo, value)| JsonObjectiveValue {
+            objective: wo.objective,
+            weight: wo.weight,
+            value: *value,
+        })
+        .collect_vec();
+
+    //only reported if the instance defines shortfall costs
+    let shortfall_cost = instance
+        .items()
+        .iter()
+        .any(|(item, _)| item.shortfall_cost > 0)
+        .then(|| solution.shortfall_cost(instance));
+
     JsonSolution {
         layouts,
+        min_clearance,
+        shortfall_cost,
+        objectives,
+        score,
+        metadata: None,
         usage: solution.usage,
+        original_usage: solution.original_usage(instance),
         run_time_sec: solution.time_stamp.duration_since(epoch).as_secs(),
     }
 }
@@ -510,46 +1695,598 @@ fn convert_json_simple_poly(
     s_json_shape: &JsonSimplePoly,
     simpl_config: PolySimplConfig,
     simpl_mode: PolySimplMode,
-) -> SimplePolygon {
-    let shape = SimplePolygon::new(json_simple_poly_to_points(s_json_shape));
+) -> Result<SimplePolygon, String> {
+    Ok(simplify_poly(
+        SimplePolygon::try_new(json_simple_poly_to_points(s_json_shape))?,
+        simpl_config,
+        simpl_mode,
+    ))
+}
 
-    let shape = match simpl_config {
+/// Rectangle shape of `width` by `height`, which should both be positive
+fn rectangle(width: fsize, height: fsize) -> Result<SimplePolygon, String> {
+    match width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite() {
+        true => Ok(SimplePolygon::from(AARectangle::new(
+            0.0, 0.0, width, height,
+        ))),
+        false => Err(format!(
+            "rectangle should have a positive width and height, got {} x {}",
+            width, height
+        )),
+    }
+}
+
+fn simplify_poly(
+    shape: SimplePolygon,
+    simpl_config: PolySimplConfig,
+    simpl_mode: PolySimplMode,
+) -> SimplePolygon {
+    match simpl_config {
         PolySimplConfig::Enabled { tolerance } => {
             polygon_simplification::simplify_shape(&shape, simpl_mode, tolerance)
         }
+        PolySimplConfig::Bounded { max_deviation } => {
+            let bbox = shape.bbox();
+            let extent = fsize::max(bbox.width(), bbox.height());
+            polygon_simplification::simplify_shape_bounded(
+                &shape,
+                simpl_mode,
+                max_deviation * extent,
+            )
+        }
         PolySimplConfig::Disabled => shape,
-    };
+    }
+}
+
+#[cfg(feature = "dxf")]
+fn convert_dxf_file(
+    path: &Path,
+    drawing: &Drawing,
+    mapping: &DxfLayerMapping,
+    layer: Option<&str>,
+    block: Option<&str>,
+    fidelity: GeometricFidelity,
+) -> Result<(ArcPolygon, Vec<ArcPolygon>, DxfItemMetadata, Option<fsize>), ParseError> {
+    let (entity, outline) = dxf_parse::find_outline(drawing, mapping, layer, block, fidelity)
+        .ok_or_else(|| ParseError::InvalidFile {
+            path: path.to_path_buf(),
+            reason: format!(
+                "no closed contour found (layer: {:?}, block: {:?})",
+                layer, block
+            ),
+        })?;
+    let holes = dxf_parse::find_holes(drawing, mapping, layer, block, (entity, &outline), fidelity);
+
+    let metadata =
+        DxfItemMetadata::from_entity(entity).map_err(|reason| ParseError::InvalidFile {
+            path: path.to_path_buf(),
+            reason,
+        })?;
+
+    Ok((outline, holes, metadata, dxf_parse::mm_per_unit(drawing)))
+}
 
-    shape
+fn convert_svg_file(
+    path: &Path,
+    contents: &[u8],
+    element_id: Option<&str>,
+    fidelity: GeometricFidelity,
+) -> Result<(ArcPolygon, Vec<ArcPolygon>), ParseError> {
+    let svg = std::str::from_utf8(contents).map_err(|err| ParseError::InvalidFile {
+        path: path.to_path_buf(),
+        reason: err.to_string(),
+    })?;
+    svg_import::svg_outline(svg, element_id, fidelity).map_err(|reason| ParseError::InvalidFile {
+        path: path.to_path_buf(),
+        reason,
+    })
 }
 
-fn dxf_poly_line_to_points(dpl: &LwPolyline) -> Vec<Point> {
+#[cfg(feature = "dxf")]
+pub fn dxf_poly_line_to_arc_polygon(dpl: &LwPolyline) -> ArcPolygon {
+    let first = &dpl.vertices[0];
+    let last = &dpl.vertices[dpl.vertices.len() - 1];
     //Strip the last vertex if it is the same as the first one
-    let n_vertices = match dpl.vertices[0].x == dpl.vertices[dpl.vertices.len() - 1].x {
+    let n_vertices = match first.x == last.x && first.y == last.y {
         true => dpl.vertices.len() - 1,
         false => dpl.vertices.len(),
     };
 
-    (0..n_vertices)
-        .map(|i| Point::from(dpl.vertices[i].x, dpl.vertices[i].y))
-        .collect_vec()
+    let points = (0..n_vertices)
+        .map(|i| Point::from((dpl.vertices[i].x as fsize, dpl.vertices[i].y as fsize)))
+        .collect_vec();
+    let bulges = (0..n_vertices)
+        .map(|i| dpl.vertices[i].bulge as fsize)
+        .collect_vec();
+
+    ArcPolygon::new(points, bulges)
 }
 
-fn convert_json_simple_poly(
-    s_json_shape: &JsonSimplePoly,
-    simpl_config: PolySimplConfig,
-    simpl_mode: PolySimplMode,
-) -> SimplePolygon {
-    let shape = SimplePolygon::new(json_simple_poly_to_points(s_json_shape));
+/// Converts an `ArcPolygon` to a set of points, tessellating its arcs (if any) conservatively according to `mode`
+pub fn arc_polygon_to_points(
+    arc_polygon: &ArcPolygon,
+    fidelity: GeometricFidelity,
+    mode: PolySimplMode,
+) -> Vec<Point> {
+    match arc_polygon.has_arcs() {
+        true => {
+            let bbox = SimplePolygon::generate_bounding_box(&arc_polygon.points);
+            let max_deviation = fidelity.max_deviation(fsize::max(bbox.width(), bbox.height()));
+            arc_polygon.tessellate(max_deviation, mode)
+        }
+        false => arc_polygon.points.clone(),
+    }
+}
 
-    let shape = match simpl_config {
-        PolySimplConfig::Enabled { tolerance } => {
-            polygon_simplification::simplify_shape(&shape, simpl_mode, tolerance)
+/// Whether all items and bins are axis-aligned rectangles and items can only be rotated by multiples of 90°.
+/// The CDE of such instances resolves all collisions on bounding boxes, see [`CDEngine::rect_mode`](crate::collision_detection::cd_engine::CDEngine::rect_mode).
+fn is_rectangular(instance: &Instance) -> bool {
+    let items_rectangular = instance
+        .items()
+        .iter()
+        .all(|(item, _)| item.is_rectangular());
+    let bins_rectangular = match instance {
+        Instance::SP(_) => true,
+        Instance::BP(bpi) => bpi.bins.iter().all(|(bin, _)| {
+            bin.outer.is_aa_rectangle()
+                && bin.holes.iter().all(|h| h.is_aa_rectangle())
+                && bin
+                    .quality_zones
+                    .iter()
+                    .flatten()
+                    .flat_map(|qz| qz.zones.iter())
+                    .all(|z| z.is_aa_rectangle())
+        }),
+    };
+    items_rectangular && bins_rectangular
+}
+
+/// Whether all items and bins (including their holes and quality zones) are convex.
+/// The CDE resolves all collisions of such instances with the separating axis theorem, see [`CDEngine::poly_collides`](crate::collision_detection::cd_engine::CDEngine::poly_collides).
+fn is_convex(instance: &Instance) -> bool {
+    let items_convex = instance.items().iter().all(|(item, _)| item.shape.convex);
+    let bins_convex = match instance {
+        Instance::SP(_) => true,
+        Instance::BP(bpi) => bpi.bins.iter().all(|(bin, _)| {
+            bin.outer.convex
+                && bin.holes.iter().all(|h| h.convex)
+                && bin
+                    .quality_zones
+                    .iter()
+                    .flatten()
+                    .flat_map(|qz| qz.zones.iter())
+                    .all(|z| z.convex)
+        }),
+    };
+    items_convex && bins_convex
+}
+
+/// Area of a shape as defined in the JSON, before any simplification
+fn json_shape_area(json_shape: &JsonShape) -> fsize {
+    let simple_poly_area = |jsp: &JsonSimplePoly| {
+        SimplePolygon::calculate_area(&json_simple_poly_to_points(jsp)).abs()
+    };
+    let poly_area = |jp: &JsonPoly| {
+        simple_poly_area(&jp.outer) - jp.inner.iter().map(simple_poly_area).sum::<fsize>()
+    };
+    match json_shape {
+        JsonShape::Rectangle { width, height } => width * height,
+        JsonShape::SimplePolygon(jsp) => simple_poly_area(jsp),
+        JsonShape::Polygon(jp) => poly_area(jp),
+        JsonShape::MultiPolygon(jps) => jps.iter().map(poly_area).sum(),
+        JsonShape::Wkt(wkt) => wkt::wkt_shape(wkt).map_or(0.0, |shape| json_shape_area(&shape)),
+    }
+}
+
+const WKT_RESOLVED: &str = "WKT shapes are resolved before the instance is parsed";
+
+/// The items of which the DXF or SVG file (resolved with `asset_paths`) cannot be opened, and which is not embedded.
+/// Only the files which are used are checked: not the ones of items defined by a `Shape`, nor the SVG of an item defined by a DXF file
+pub fn find_missing_assets(
+    json_instance: &JsonInstance,
+    asset_paths: &AssetPaths,
+) -> Vec<MissingAsset> {
+    json_instance
+        .items
+        .iter()
+        .enumerate()
+        .filter(|(_, json_item)| json_item.shape.is_none())
+        .filter_map(|(item, json_item)| {
+            let file = json_item.dxf.as_ref().or(json_item.svg.as_ref())?;
+            if json_instance.assets.contains_key(file) || asset_paths.embedded_asset(file).is_some()
+            {
+                return None;
+            }
+            let (path, source) = match asset_paths.resolve(file) {
+                Ok(path) => {
+                    let source = std::fs::File::open(&path).err()?;
+                    (path, source)
+                }
+                Err(source) => (PathBuf::from(file), source),
+            };
+            Some(MissingAsset { item, path, source })
+        })
+        .collect()
+}
+
+/// Applies the `policy` to the items of which the DXF or SVG file cannot be opened.
+/// With [`MissingAssetPolicy::Skip`], the instance is returned without those items, so one bad reference does not fail an entire job.
+/// The remaining items are renumbered, so the solution refers to the returned instance and not the original one.
+pub fn apply_missing_asset_policy<'a>(
+    json_instance: &'a JsonInstance,
+    asset_paths: &AssetPaths,
+    policy: MissingAssetPolicy,
+) -> Result<Cow<'a, JsonInstance>, ParseError> {
+    let missing_assets = find_missing_assets(json_instance, asset_paths);
+    if missing_assets.is_empty() {
+        return Ok(Cow::Borrowed(json_instance));
+    }
+    match policy {
+        MissingAssetPolicy::Fail => Err(ParseError::MissingAssets(missing_assets)),
+        MissingAssetPolicy::Skip => {
+            for missing in &missing_assets {
+                log!(
+                    Level::Warn,
+                    "[PARSE] skipping item {}, its file {} could not be opened: {}",
+                    missing.item,
+                    missing.path.display(),
+                    missing.source
+                );
+            }
+            let mut remaining = json_instance.clone();
+            remaining.items = json_instance
+                .items
+                .iter()
+                .enumerate()
+                .filter(|(item, _)| !missing_assets.iter().any(|m| m.item == *item))
+                .map(|(_, json_item)| json_item.clone())
+                .collect();
+            Ok(Cow::Owned(remaining))
         }
-        PolySimplConfig::Disabled => shape,
+    }
+}
+
+/// The DXF and SVG files of the items of an instance, each read once per parse however many items are defined by it.
+/// DXF files are also loaded once, all items of the same file share the [`Drawing`]
+struct ItemFiles<'a> {
+    asset_paths: &'a AssetPaths,
+    /// Every file by its path as referred to by the items, read on first use
+    files: Mutex<HashMap<String, FileSlot>>,
+}
+
+/// A file of [`ItemFiles`], `None` until it is read successfully
+type FileSlot = Arc<Mutex<Option<Arc<ItemFile>>>>;
+
+impl<'a> ItemFiles<'a> {
+    fn new(asset_paths: &'a AssetPaths) -> Self {
+        Self {
+            asset_paths,
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The file referred to by `file`, which is read by the first item requesting it.
+    /// Failed reads are not kept, every item requesting the file then reports the error
+    fn get(
+        &self,
+        parser: &Parser,
+        item_id: usize,
+        file: &str,
+    ) -> Result<Arc<ItemFile>, ParseError> {
+        let slot = Arc::clone(
+            self.files
+                .lock()
+                .unwrap()
+                .entry(file.to_string())
+                .or_default(),
+        );
+        //only items of the same file wait for each other
+        let mut slot = slot.lock().unwrap();
+        if let Some(item_file) = &*slot {
+            return Ok(Arc::clone(item_file));
+        }
+        let (path, contents) = parser.read_asset(self.asset_paths, item_id, file)?;
+        let item_file = Arc::new(ItemFile {
+            hash: content_hash(&contents),
+            path,
+            contents,
+            #[cfg(feature = "dxf")]
+            drawing: OnceLock::new(),
+        });
+        *slot = Some(Arc::clone(&item_file));
+        Ok(item_file)
+    }
+}
+
+/// DXF or SVG file of items, see [`ItemFiles`]
+struct ItemFile {
+    path: PathBuf,
+    contents: Arc<[u8]>,
+    /// Hash of the contents, by which the items of the file are keyed in the shape cache
+    hash: u64,
+    #[cfg(feature = "dxf")]
+    drawing: OnceLock<Result<Drawing, String>>,
+}
+
+impl ItemFile {
+    /// The contents loaded as a DXF drawing, loaded once
+    #[cfg(feature = "dxf")]
+    fn drawing(&self) -> Result<&Drawing, ParseError> {
+        self.drawing
+            .get_or_init(|| {
+                Drawing::load(&mut std::io::Cursor::new(&self.contents[..]))
+                    .map_err(|err| err.to_string())
+            })
+            .as_ref()
+            .map_err(|reason| ParseError::InvalidFile {
+                path: self.path.clone(),
+                reason: reason.clone(),
+            })
+    }
+}
+
+/// Preprocessed geometry of an item, shared by all items with the same shape definition
+struct SharedItemGeometry {
+    shape: Arc<SimplePolygon>,
+    arc_shape: Option<Arc<ArcPolygon>>,
+    original_area: fsize,
+    centering: Option<(fsize, fsize)>,
+    dxf_metadata: DxfItemMetadata,
+    curve_deviation: fsize,
+}
+
+/// Assigns an id to every distinct shape definition of the items: their shape, or the file (and layer, block or element) they are read from,
+/// together with the surrogate configuration. Items with the same id have identical preprocessed geometry
+#[derive(Default)]
+struct ItemShapes {
+    /// Ids of the shape definitions by the hash of their serialization, only the hashes are kept to save memory
+    ids: HashMap<u64, Vec<usize>>,
+    n_shapes: usize,
+}
+
+impl ItemShapes {
+    /// Id of the shape definition of the item, and whether it is the first item with it.
+    /// `shape_def` returns the item which first had the definition with an id, to rule out hash collisions
+    fn intern<'a>(
+        &mut self,
+        json_item: &JsonItem,
+        shape_def: impl Fn(usize) -> &'a JsonItem,
+    ) -> (usize, bool) {
+        let key = shape_key(json_item);
+        let candidates = self.ids.entry(content_hash(&key)).or_default();
+        match candidates
+            .iter()
+            .find(|&&id| shape_key(shape_def(id)) == key)
+        {
+            Some(&id) => (id, false),
+            None => {
+                let id = self.n_shapes;
+                candidates.push(id);
+                self.n_shapes += 1;
+                (id, true)
+            }
+        }
+    }
+}
+
+fn shape_key(json_item: &JsonItem) -> Vec<u8> {
+    serde_json::to_vec(&(
+        &json_item.shape,
+        &json_item.dxf,
+        &json_item.dxf_layer,
+        &json_item.dxf_block,
+        &json_item.svg,
+        &json_item.svg_id,
+        &json_item.surrogate,
+    ))
+    .expect("could not serialize shape definition")
+}
+
+/// A `JsonInstance` of which the items are deduplicated while they are read, see [`Parser::parse_reader`]
+#[derive(Deserialize)]
+struct StreamedInstance {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Items")]
+    items: StreamedItems,
+    #[serde(rename = "Objects")]
+    bins: Option<Vec<JsonBin>>,
+    #[serde(rename = "Strip")]
+    strip: Option<JsonStrip>,
+    #[serde(rename = "Units", default)]
+    units: Option<Units>,
+    #[serde(rename = "Assets", default)]
+    assets: BTreeMap<String, JsonAsset>,
+}
+
+struct StreamedItems {
+    /// All items, without their shape
+    items: Vec<JsonItem>,
+    /// First item with every distinct shape definition, including the shape
+    shape_defs: Vec<(usize, JsonItem)>,
+    /// Index in `shape_defs` of every item
+    shape_ids: Vec<usize>,
+}
+
+impl<'de> Deserialize<'de> for StreamedItems {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ItemsVisitor;
+
+        impl<'de> Visitor<'de> for ItemsVisitor {
+            type Value = StreamedItems;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of items")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<StreamedItems, A::Error> {
+                let mut shapes = ItemShapes::default();
+                let mut streamed = StreamedItems {
+                    items: vec![],
+                    shape_defs: vec![],
+                    shape_ids: vec![],
+                };
+                while let Some(mut json_item) = seq.next_element::<JsonItem>()? {
+                    let item_id = streamed.items.len();
+                    let (shape_id, first) =
+                        shapes.intern(&json_item, |id| &streamed.shape_defs[id].1);
+                    let shape = json_item.shape.take();
+                    if first {
+                        let mut shape_def = json_item.clone();
+                        shape_def.shape = shape;
+                        streamed.shape_defs.push((item_id, shape_def));
+                    }
+                    streamed.items.push(json_item);
+                    streamed.shape_ids.push(shape_id);
+                }
+                Ok(streamed)
+            }
+        }
+
+        deserializer.deserialize_seq(ItemsVisitor)
+    }
+}
+
+/// Replaces all shapes defined in WKT by the shapes they describe, see [`wkt::wkt_shape`].
+/// Borrows the instance if it contains no such shapes
+fn resolve_wkt_shapes(json_instance: &JsonInstance) -> Result<Cow<'_, JsonInstance>, ParseError> {
+    let is_wkt = |shape: &JsonShape| matches!(shape, JsonShape::Wkt(_));
+    let bin_shapes = |json_bin: &JsonBin| {
+        json_bin
+            .shape
+            .iter()
+            .chain(json_bin.zones.iter().map(|zone| &zone.shape))
+            .chain(json_bin.temporary_zones.iter().map(|zone| &zone.shape))
+            .any(is_wkt)
+    };
+    let has_wkt = json_instance
+        .items
+        .iter()
+        .any(|i| i.shape.as_ref().is_some_and(is_wkt))
+        || json_instance.bins.iter().flatten().any(bin_shapes);
+    if !has_wkt {
+        return Ok(Cow::Borrowed(json_instance));
+    }
+
+    let resolve = |shape: &mut JsonShape| -> Result<(), String> {
+        if let JsonShape::Wkt(wkt) = shape {
+            *shape = wkt::wkt_shape(wkt).map_err(|reason| format!("invalid WKT: {}", reason))?;
+        }
+        Ok(())
+    };
+    let mut resolved = json_instance.clone();
+    for (item, json_item) in resolved.items.iter_mut().enumerate() {
+        if let Some(shape) = json_item.shape.as_mut() {
+            resolve(shape).map_err(|reason| ParseError::InvalidItem { item, reason })?;
+        }
+    }
+    for (bin, json_bin) in resolved.bins.iter_mut().flatten().enumerate() {
+        json_bin
+            .shape
+            .iter_mut()
+            .chain(json_bin.zones.iter_mut().map(|zone| &mut zone.shape))
+            .chain(
+                json_bin
+                    .temporary_zones
+                    .iter_mut()
+                    .map(|zone| &mut zone.shape),
+            )
+            .try_for_each(resolve)
+            .map_err(|reason| ParseError::InvalidBin { bin, reason })?;
+    }
+    Ok(Cow::Owned(resolved))
+}
+
+/// Converts all lengths of an instance with units to millimeters, see [`Units`].
+/// Borrows the instance if it has no units, or if they are millimeters. WKT shapes have to be resolved first
+fn instance_in_mm(json_instance: &JsonInstance) -> Cow<'_, JsonInstance> {
+    let scale = Units::scale(json_instance.units);
+    if scale == 1.0 {
+        return Cow::Borrowed(json_instance);
+    }
+    let mut scaled = json_instance.clone();
+    for json_item in scaled.items.iter_mut() {
+        scale_json_item(json_item, scale);
+    }
+    for json_bin in scaled.bins.iter_mut().flatten() {
+        json_bin
+            .shape
+            .iter_mut()
+            .chain(json_bin.zones.iter_mut().map(|zone| &mut zone.shape))
+            .chain(
+                json_bin
+                    .temporary_zones
+                    .iter_mut()
+                    .map(|zone| &mut zone.shape),
+            )
+            .for_each(|shape| scale_json_shape(shape, scale));
+    }
+    if let Some(strip) = scaled.strip.as_mut() {
+        strip.height *= scale;
+    }
+    Cow::Owned(scaled)
+}
+
+/// Scales the shape of an item and the translations of its allowed and locked transformations
+fn scale_json_item(json_item: &mut JsonItem, scale: fsize) {
+    if let Some(shape) = json_item.shape.as_mut() {
+        scale_json_shape(shape, scale);
+    }
+    let locked = json_item
+        .copies
+        .iter_mut()
+        .flatten()
+        .filter_map(|copy| copy.transformation.as_mut());
+    for transformation in json_item
+        .allowed_transformations
+        .iter_mut()
+        .flatten()
+        .chain(locked)
+    {
+        let (x, y) = transformation.translation;
+        transformation.translation = (x * scale, y * scale);
+    }
+}
+
+fn scale_json_shape(json_shape: &mut JsonShape, scale: fsize) {
+    let scale_simple_poly = |jsp: &mut JsonSimplePoly| {
+        jsp.0.iter_mut().for_each(|(x, y)| {
+            *x *= scale;
+            *y *= scale;
+        })
     };
+    let scale_poly = |jp: &mut JsonPoly| {
+        scale_simple_poly(&mut jp.outer);
+        jp.inner.iter_mut().for_each(scale_simple_poly);
+    };
+    match json_shape {
+        JsonShape::Rectangle { width, height } => {
+            *width *= scale;
+            *height *= scale;
+        }
+        JsonShape::SimplePolygon(jsp) => scale_simple_poly(jsp),
+        JsonShape::Polygon(jp) => scale_poly(jp),
+        JsonShape::MultiPolygon(jps) => jps.iter_mut().for_each(scale_poly),
+        JsonShape::Wkt(_) => unreachable!("{}", WKT_RESOLVED),
+    }
+}
 
-    shape
+/// Scales the contours read from a file
+fn scale_contours(
+    outline: ArcPolygon,
+    holes: Vec<ArcPolygon>,
+    scale: fsize,
+) -> (ArcPolygon, Vec<ArcPolygon>) {
+    match scale == 1.0 {
+        true => (outline, holes),
+        false => (
+            units::scaled_arc_polygon(&outline, scale),
+            holes
+                .iter()
+                .map(|h| units::scaled_arc_polygon(h, scale))
+                .collect(),
+        ),
+    }
 }
 
 fn json_simple_poly_to_points(jsp: &JsonSimplePoly) -> Vec<Point> {
@@ -562,6 +2299,139 @@ fn json_simple_poly_to_points(jsp: &JsonSimplePoly) -> Vec<Point> {
     (0..n_vertices).map(|i| Point::from(jsp.0[i])).collect_vec()
 }
 
+fn json_poly_to_zone_shape(jp: &JsonPoly) -> ZoneShape {
+    ZoneShape {
+        outer: json_simple_poly_to_points(&jp.outer),
+        holes: jp
+            .inner
+            .iter()
+            .map(json_simple_poly_to_points)
+            .collect_vec(),
+    }
+}
+
+fn simple_poly_to_json(sp: &SimplePolygon) -> JsonSimplePoly {
+    JsonSimplePoly(sp.points.iter().map(|p| (p.0, p.1)).collect_vec())
+}
+
+/// Checks whether the bins which copies of items are restricted to exist
+fn check_copy_bins(items: &[(Item, usize)], n_bins: usize) -> Result<(), ParseError> {
+    for (item, _) in items {
+        for (copy, attr) in item.copy_attributes.iter().enumerate() {
+            match attr.bin_id {
+                Some(bin_id) if bin_id >= n_bins => {
+                    return Err(ParseError::InvalidItem {
+                        item: item.id,
+                        reason: format!(
+                            "copy {} is restricted to bin {}, which does not exist",
+                            copy, bin_id
+                        ),
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Appends a variant rotated by 90° of every bin which is not square, with the same stock
+fn add_rotated_bins(mut bins: Vec<(Bin, usize)>) -> Vec<(Bin, usize)> {
+    let rotation = Transformation::from_rotation(PI / 2.0);
+    let variants = bins
+        .iter()
+        .filter(|(bin, _)| {
+            let bbox = bin.outer.bbox();
+            (bbox.width() - bbox.height()).abs() > 1e-4 * bbox.width().max(bbox.height())
+        })
+        .enumerate()
+        .map(|(i, (bin, stock))| {
+            let mut variant = pretransform_bin(bin, &rotation);
+            variant.id = bins.len() + i;
+            variant.variant_of = Some(bin.id);
+            (variant, *stock)
+        })
+        .collect_vec();
+    log!(
+        Level::Info,
+        "[PARSE] added {} rotated bin variants",
+        variants.len()
+    );
+    bins.extend(variants);
+    bins
+}
+
+/// Checks whether every item with a material can be placed in at least one of the bins
+fn check_item_materials(items: &[(Item, usize)], bins: &[(Bin, usize)]) -> Result<(), ParseError> {
+    for (item, _) in items {
+        if let Some(material) = &item.material {
+            if !bins.iter().any(|(bin, _)| bin.accepts(item)) {
+                return Err(ParseError::InvalidItem {
+                    item: item.id,
+                    reason: format!(
+                        "of material {}, but there is no bin of this material",
+                        material
+                    ),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Flags all webs of the skeleton narrower than `min_web_width` (see [`web_width::thin_webs`]) in the statistics of the layouts of `json_solution`,
+/// which must have been composed from `solution`
+pub fn add_thin_webs(
+    json_solution: &mut JsonSolution,
+    solution: &Solution,
+    instance: &Instance,
+    min_web_width: fsize,
+) {
+    let to_input = 1.0 / instance.unit_scale();
+    assert_eq!(json_solution.layouts.len(), solution.layout_snapshots.len());
+    for (json_layout, sl) in json_solution
+        .layouts
+        .iter_mut()
+        .zip(solution.layout_snapshots.iter())
+    {
+        let pi_indices = placed_item_indices(&sl.placed_items);
+        let inv_bin_pretransf = sl.bin.pretransform.clone().inverse();
+        json_layout.statistics.thin_webs =
+            web_width::thin_webs(instance, &sl.bin, &sl.placed_items, min_web_width)
+                .into_iter()
+                .map(|w| {
+                    let location = w.location.transform_clone(&inv_bin_pretransf);
+                    JsonThinWeb {
+                        placed_item: pi_indices[&w.item],
+                        other: json_clearance_entity(w.other, &pi_indices),
+                        width: w.width * to_input,
+                        location: (location.0 * to_input, location.1 * to_input),
+                    }
+                })
+                .collect_vec();
+    }
+}
+
+/// Placed items are referred to by their index in the layout
+fn placed_item_indices(placed_items: &SlotMap<PItemKey, PlacedItem>) -> HashMap<PItemKey, usize> {
+    placed_items
+        .keys()
+        .enumerate()
+        .map(|(i, pk)| (pk, i))
+        .collect()
+}
+
+fn json_clearance_entity(
+    entity: ClearanceEntity,
+    pi_indices: &HashMap<PItemKey, usize>,
+) -> JsonClearanceEntity {
+    match entity {
+        ClearanceEntity::PlacedItem(pk) => JsonClearanceEntity::PlacedItem(pi_indices[&pk]),
+        ClearanceEntity::BinExterior => JsonClearanceEntity::Exterior,
+        ClearanceEntity::BinHole(i) => JsonClearanceEntity::Hole(i),
+    }
+}
+
 pub fn internal_to_absolute_transform(
     placed_item_transf: &DTransformation,
     item_pretransf: &Transformation,
@@ -595,22 +2465,28 @@ pub fn absolute_to_internal_transform(
 pub fn pretransform_bin(bin: &Bin, extra_pretransf: &Transformation) -> Bin {
     let Bin {
         id,
+        name,
+        variant_of,
+        max_usage,
+        material,
         outer,
         value,
         pretransform,
         holes,
         quality_zones,
+        temporary_zones,
+        original_area,
         ..
     } = bin;
 
-    Bin::new(
+    let mut pretransformed_bin = Bin::new(
         *id,
-        outer.transform_clone(&extra_pretransf),
+        outer.transform_clone(extra_pretransf),
         *value,
-        pretransform.clone().transform(&extra_pretransf),
+        pretransform.clone().transform(extra_pretransf),
         holes
             .iter()
-            .map(|h| h.transform_clone(&extra_pretransf))
+            .map(|h| h.transform_clone(extra_pretransf))
             .collect(),
         quality_zones
             .iter()
@@ -620,28 +2496,52 @@ pub fn pretransform_bin(bin: &Bin, extra_pretransf: &Transformation) -> Bin {
                     qz.quality,
                     qz.zones
                         .iter()
-                        .map(|z| z.transform_clone(&extra_pretransf))
+                        .map(|z| z.transform_clone(extra_pretransf))
                         .collect(),
                 )
             })
             .collect(),
         bin.base_cde.config(),
-    )
+    );
+    pretransformed_bin.original_area = *original_area;
+    pretransformed_bin.temporary_zones = temporary_zones
+        .iter()
+        .map(|tz| {
+            TemporaryZone::new(
+                tz.shape.transform_clone(extra_pretransf),
+                tz.active_from,
+                tz.active_until,
+            )
+        })
+        .collect();
+    pretransformed_bin.name = name.clone();
+    pretransformed_bin.material = material.clone();
+    pretransformed_bin.variant_of = *variant_of;
+    pretransformed_bin.max_usage = *max_usage;
+
+    pretransformed_bin
 }
 
 pub fn pretransform_item(item: &Item, extra_pretransf: &Transformation) -> Item {
     let Item {
         id,
         shape,
+        arc_shape,
+        curve_deviation,
         allowed_rotation,
         base_quality,
         value,
         pretransform,
         surrogate_config,
+        original_area,
+        copy_attributes,
+        name,
+        material,
+        shortfall_cost,
         ..
     } = item;
 
-    Item::new(
+    let mut pretransformed_item = Item::new(
         *id,
         shape.transform_clone(extra_pretransf),
         allowed_rotation.clone(),
@@ -649,10 +2549,87 @@ pub fn pretransform_item(item: &Item, extra_pretransf: &Transformation) -> Item
         *value,
         pretransform.clone().transform(extra_pretransf),
         *surrogate_config,
-    )
+    );
+    pretransformed_item.arc_shape = arc_shape
+        .as_ref()
+        .map(|arc_shape| Arc::new(arc_shape.transform_clone(extra_pretransf)));
+    pretransformed_item.original_area = *original_area;
+    pretransformed_item.curve_deviation = *curve_deviation;
+    pretransformed_item.copy_attributes = copy_attributes.clone();
+    pretransformed_item.name = name.clone();
+    pretransformed_item.material = material.clone();
+    pretransformed_item.shortfall_cost = *shortfall_cost;
+
+    pretransformed_item
 }
 
 pub fn centering_transformation(shape: &SimplePolygon) -> DTransformation {
     let Point(cx, cy) = shape.centroid();
     DTransformation::new(0.0, (-cx, -cy))
 }
+
+/// Applies `f` to every element and its index, in parallel if the `parallel` feature is enabled.
+/// Every element is processed independently and the results are collected in the order of the input,
+/// so the output is identical regardless of the number of threads or how the work is scheduled.
+/// Fails if `f` fails for any element.
+fn map_indexed<T, R, F>(elements: &[T], f: F) -> Result<Vec<R>, ParseError>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &T) -> Result<R, ParseError> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    let elements = elements.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let elements = elements.iter();
+    elements.enumerate().map(|(i, e)| f(i, e)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_fixtures;
+
+    #[test]
+    fn test_parse_reader_curve_deviation() {
+        let mut json_instance: JsonInstance = serde_json::from_value(serde_json::json!({
+            "Name": "curves",
+            "Items": [
+                {
+                    "Demand": 2,
+                    "Shape": {"Type": "Rectangle", "Data": {"Width": 20.0, "Height": 10.0}}
+                },
+                {"Demand": 2, "Svg": "disc.svg"}
+            ],
+            "Strip": {"Height": 100.0}
+        }))
+        .unwrap();
+        json_instance.assets.insert(
+            "disc.svg".into(),
+            JsonAsset(
+                br#"<svg><path d="M0 10 A10 10 0 0 1 20 10 A10 10 0 0 1 0 10 Z"/></svg>"#
+                    .as_slice()
+                    .into(),
+            ),
+        );
+        let parser = test_fixtures::parser();
+        let curve_deviations = |instance: &Instance| {
+            instance
+                .items()
+                .iter()
+                .map(|(item, _)| item.curve_deviation)
+                .collect_vec()
+        };
+        let parsed = curve_deviations(&parser.parse(&json_instance).unwrap());
+        let streamed = curve_deviations(
+            &parser
+                .parse_reader(serde_json::to_vec(&json_instance).unwrap().as_slice())
+                .unwrap(),
+        );
+
+        //only the flattened curves of the file deviate from the input, whichever way the instance is read
+        assert_eq!(parsed, streamed);
+        assert_eq!(parsed[0], 0.0);
+        assert!(parsed[1] > 0.0);
+    }
+}
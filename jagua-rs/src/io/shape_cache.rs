@@ -0,0 +1,199 @@
+use std::convert::Infallible;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::{log, Level};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::fsize;
+use crate::geometry::primitives::arc_polygon::ArcPolygon;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+use crate::io::dxf_metadata::DxfItemMetadata;
+
+/// Distinguishes the temporary files of concurrent writes within the same process
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Entries written by other versions of the library are never read, the preprocessing might have changed
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Version of the preprocessing and of the layout of the entries, part of every key.
+/// Has to be incremented whenever either changes, as the package version is not bumped for every change.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// On-disk cache of preprocessed shapes, which persists across runs.
+/// <br>
+/// Preprocessing a shape (tessellation, simplification, centering and generation of the surrogate)
+/// only depends on its definition in the input and the configuration of the parser.
+/// The result is stored under a content hash of both, so repeated runs on the same part library skip it entirely.
+/// Every entry also holds its full key, which is compared on read, so colliding hashes are treated as misses.
+/// None of the precomputations depend on the rotation of a shape, so rotations are not part of the key.
+/// <br>
+/// Entries are written atomically, so multiple processes can share the same cache folder.
+/// Unreadable entries are ignored and overwritten.
+#[derive(Debug)]
+pub struct ShapeCache {
+    folder: PathBuf,
+    n_hits: AtomicUsize,
+    n_misses: AtomicUsize,
+}
+
+impl ShapeCache {
+    /// Opens the cache in `folder`, which is created if it does not exist yet
+    pub fn new(folder: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&folder).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!(
+                    "could not create shape cache folder: {}, {}",
+                    folder.display(),
+                    err
+                ),
+            )
+        })?;
+        Ok(Self {
+            folder,
+            n_hits: AtomicUsize::new(0),
+            n_misses: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the cached value for `key`, or computes and stores it
+    pub fn get_or_insert_with<K, V, F>(&self, key: &K, compute: F) -> V
+    where
+        K: Serialize,
+        V: Serialize + DeserializeOwned,
+        F: FnOnce() -> V,
+    {
+        match self.try_get_or_insert_with(key, || Ok::<V, Infallible>(compute())) {
+            Ok(value) => value,
+        }
+    }
+
+    /// Returns the cached value for `key`, or computes and stores it.
+    /// Errors of `compute` are returned as is, and not stored
+    pub fn try_get_or_insert_with<K, V, E, F>(&self, key: &K, compute: F) -> Result<V, E>
+    where
+        K: Serialize,
+        V: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<V, E>,
+    {
+        let key = serialize_key(key);
+        let path = self
+            .folder
+            .join(format!("{:016x}.json", content_hash(key.as_bytes())));
+        if let Some(value) = read_entry(&path, &key) {
+            self.n_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+        self.n_misses.fetch_add(1, Ordering::Relaxed);
+        let value = compute()?;
+        write_entry(&path, &Entry { key, value: &value });
+        Ok(value)
+    }
+
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    pub fn n_hits(&self) -> usize {
+        self.n_hits.load(Ordering::Relaxed)
+    }
+
+    pub fn n_misses(&self) -> usize {
+        self.n_misses.load(Ordering::Relaxed)
+    }
+}
+
+/// Contents of a file in the cache
+#[derive(Serialize, Deserialize)]
+struct Entry<V> {
+    /// Full key of the entry, see [`serialize_key`]
+    key: String,
+    value: V,
+}
+
+/// Serialization of a key, together with everything besides the key on which the preprocessing depends
+fn serialize_key<K: Serialize>(key: &K) -> String {
+    serde_json::to_string(&(CACHE_VERSION, FORMAT_VERSION, size_of::<fsize>(), key))
+        .expect("could not serialize shape cache key")
+}
+
+/// Preprocessed geometry of an item, as derived from its definition in the input
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ItemGeometry {
+    /// Final (simplified and centered) shape, including its surrogate
+    pub shape: SimplePolygon,
+    pub arc_shape: Option<ArcPolygon>,
+    pub original_area: fsize,
+    /// Translation applied to center the shape, if any
+    pub centering: Option<(fsize, fsize)>,
+    pub dxf_metadata: DxfItemMetadata,
+    /// Max deviation of the flattened curves from the ones in the file, 0 if the shape is not read from a file
+    pub curve_deviation: fsize,
+}
+
+/// Preprocessed geometry of a bin, as derived from its definition in the input
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BinGeometry {
+    /// Final (simplified and centered) contour
+    pub outer: SimplePolygon,
+    pub holes: Vec<SimplePolygon>,
+    /// Shapes of the quality zones, per quality
+    pub quality_zones: Vec<Vec<SimplePolygon>>,
+    pub original_area: fsize,
+    /// Translation applied to center the shape, if any
+    pub centering: Option<(fsize, fsize)>,
+    pub material_value: u64,
+}
+
+/// 64-bit FNV-1a hash, which (unlike the hashers of the standard library) is stable across platforms and compiler versions
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn read_entry<V: DeserializeOwned>(path: &Path, key: &str) -> Option<V> {
+    let bytes = fs::read(path).ok()?;
+    match serde_json::from_slice::<Entry<V>>(&bytes) {
+        Ok(entry) if entry.key == key => Some(entry.value),
+        Ok(_) => {
+            log!(
+                Level::Debug,
+                "[CACHE] entry {} belongs to another key with the same hash",
+                path.display()
+            );
+            None
+        }
+        Err(err) => {
+            log!(
+                Level::Warn,
+                "[CACHE] ignoring unreadable entry {}: {}",
+                path.display(),
+                err
+            );
+            None
+        }
+    }
+}
+
+fn write_entry<V: Serialize>(path: &Path, value: &V) {
+    //write to a temporary file first, so concurrent readers never see a partially written entry
+    let tmp_id = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp_path = path.with_extension(format!("{}.{}.tmp", std::process::id(), tmp_id));
+    let result = serde_json::to_vec(value)
+        .map_err(io::Error::from)
+        .and_then(|bytes| fs::write(&tmp_path, bytes))
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if let Err(err) = result {
+        log!(
+            Level::Warn,
+            "[CACHE] could not write entry {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
@@ -0,0 +1,720 @@
+use itertools::Itertools;
+use log::{log, Level};
+
+use crate::fsize;
+use crate::geometry::primitives::arc_polygon::ArcPolygon;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+use crate::io::xml_scan::{self, xml_events, XmlEvent};
+use crate::util::config::GeometricFidelity;
+use crate::util::polygon_holes;
+use crate::PI;
+
+/// Relative tolerance below which the axes of an arc are considered equal and perpendicular, making it circular
+const CIRCULAR_TOLERANCE: fsize = 1e-4;
+
+/// Elements whose contents are not drawn
+const HIDDEN_ELEMENTS: [&str; 6] = ["defs", "clipPath", "mask", "marker", "pattern", "symbol"];
+
+/// Reads the outline of an item from the contents of an SVG file.
+/// The contours of all `<path>` and `<polygon>` elements are considered
+/// (only those of the element with id `element_id`, if defined), of which the one enclosing the largest area is the outline.
+/// The contours inside the outline are its holes, except those inside another hole.
+/// Coordinates are the user units of the file (the y-axis pointing down), with the `transform` attributes of the elements and their groups applied.
+/// Circular arcs are preserved, Bézier curves and elliptical arcs are flattened without deviating more than the `fidelity` allows.
+/// Returns a description of the problem if the file is malformed or does not contain a closed contour.
+pub fn svg_outline(
+    svg: &str,
+    element_id: Option<&str>,
+    fidelity: GeometricFidelity,
+) -> Result<(ArcPolygon, Vec<ArcPolygon>), String> {
+    let mut contours = vec![];
+    for e in parse_elements(svg)?
+        .iter()
+        .filter(|e| element_id.is_none() || e.id.as_deref() == element_id)
+    {
+        let element_contours = match &e.kind {
+            ElementKind::Path(d) => parse_path_data(d)?,
+            ElementKind::Polygon(points) => parse_polygon_points(points)?,
+        };
+        contours.extend(element_contours.iter().map(|c| c.transform(&e.transform)));
+    }
+
+    let bbox_points = contours
+        .iter()
+        .flat_map(|c| c.hull_points())
+        .collect::<Vec<_>>();
+    if bbox_points.is_empty() {
+        return Err(format!(
+            "no <path> or <polygon> found in svg (id: {:?})",
+            element_id
+        ));
+    }
+    let bbox = SimplePolygon::generate_bounding_box(&bbox_points);
+    let max_deviation = fidelity.max_deviation(fsize::max(bbox.width(), bbox.height()));
+
+    let mut outlines = contours
+        .iter()
+        .map(|c| c.to_arc_polygon(max_deviation))
+        .filter(|ap| {
+            let area = ap.signed_area();
+            ap.number_of_edges() >= 2 && area != 0.0 && area.is_finite()
+        })
+        .collect::<Vec<_>>();
+    if outlines.is_empty() {
+        return Err(format!(
+            "no closed contour with an area found in svg (id: {:?})",
+            element_id
+        ));
+    }
+    let largest = (0..outlines.len())
+        .max_by(|&i, &j| {
+            let (a_i, a_j) = (
+                outlines[i].signed_area().abs(),
+                outlines[j].signed_area().abs(),
+            );
+            a_i.total_cmp(&a_j)
+        })
+        .expect("outlines should not be empty");
+    let outline = outlines.swap_remove(largest);
+
+    let contour_points = outlines.iter().map(|c| c.points.clone()).collect_vec();
+    let hole_indices = polygon_holes::holes_of(&outline.points, &contour_points);
+    if outlines.len() > hole_indices.len() {
+        log!(
+            Level::Warn,
+            "[PARSE] svg contains {} contours outside the outline of the item (or inside its holes), which are ignored",
+            outlines.len() - hole_indices.len()
+        );
+    }
+    let holes = outlines
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| hole_indices.contains(i))
+        .map(|(_, contour)| contour)
+        .collect();
+    Ok((outline, holes))
+}
+
+/// Drawn element of an SVG document which defines contours
+struct Element {
+    id: Option<String>,
+    kind: ElementKind,
+    /// Transformation of the element, including the ones of its ancestors
+    transform: Affine,
+}
+
+enum ElementKind {
+    /// `d` attribute of a `<path>`
+    Path(String),
+    /// `points` attribute of a `<polygon>`
+    Polygon(String),
+}
+
+/// Scans the document for `<path>` and `<polygon>` elements, keeping track of the transformations of the enclosing groups
+fn parse_elements(svg: &str) -> Result<Vec<Element>, String> {
+    let mut elements = vec![];
+    //transformation and whether the contents are hidden, for every open element
+    let mut stack: Vec<(Affine, bool)> = vec![(Affine::IDENTITY, false)];
+    for event in xml_events(svg).map_err(|err| format!("{} in svg", err))? {
+        let (name, attributes, self_closing) = match event {
+            XmlEvent::Start {
+                name,
+                attributes,
+                self_closing,
+            } => (name, attributes, self_closing),
+            XmlEvent::End { name } => {
+                if stack.len() <= 1 {
+                    return Err(format!("unbalanced closing tag in svg: </{}>", name));
+                }
+                stack.pop();
+                continue;
+            }
+            XmlEvent::Text(_) => continue,
+        };
+        let attribute = |key: &str| xml_scan::attribute(&attributes, key).map(|v| v.to_string());
+
+        let (parent_transform, parent_hidden) = *stack.last().unwrap();
+        let transform = match attribute("transform") {
+            Some(t) => parent_transform.then(&parse_transform(&t)?),
+            None => parent_transform,
+        };
+        let hidden = parent_hidden || HIDDEN_ELEMENTS.contains(&name);
+
+        let kind = match name {
+            "path" => attribute("d").map(ElementKind::Path),
+            "polygon" => attribute("points").map(ElementKind::Polygon),
+            _ => None,
+        };
+        if let (Some(kind), false) = (kind, hidden) {
+            elements.push(Element {
+                id: attribute("id"),
+                kind,
+                transform,
+            });
+        }
+        if !self_closing {
+            stack.push((transform, hidden));
+        }
+    }
+    Ok(elements)
+}
+
+/// Affine transformation `(x, y) -> (a x + c y + e, b x + d y + f)`, as in SVG
+#[derive(Clone, Copy, Debug)]
+struct Affine {
+    a: fsize,
+    b: fsize,
+    c: fsize,
+    d: fsize,
+    e: fsize,
+    f: fsize,
+}
+
+impl Affine {
+    const IDENTITY: Affine = Affine::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    const fn new(a: fsize, b: fsize, c: fsize, d: fsize, e: fsize, f: fsize) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    /// The transformation which first applies `other` and then `self`
+    fn then(&self, other: &Affine) -> Affine {
+        Affine::new(
+            self.a * other.a + self.c * other.b,
+            self.b * other.a + self.d * other.b,
+            self.a * other.c + self.c * other.d,
+            self.b * other.c + self.d * other.d,
+            self.a * other.e + self.c * other.f + self.e,
+            self.b * other.e + self.d * other.f + self.f,
+        )
+    }
+
+    fn apply(&self, Point(x, y): Point) -> Point {
+        Point(
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Applies only the linear part, for vectors
+    fn apply_linear(&self, Point(x, y): Point) -> Point {
+        Point(self.a * x + self.c * y, self.b * x + self.d * y)
+    }
+}
+
+/// Parses a `transform` attribute, a list of transformations which are applied from right to left
+fn parse_transform(s: &str) -> Result<Affine, String> {
+    let mut transform = Affine::IDENTITY;
+    let mut rest = s;
+    while let Some(open) = rest.find('(') {
+        let name = rest[..open].trim_matches(|c: char| c.is_whitespace() || c == ',');
+        let close = rest[open..]
+            .find(')')
+            .ok_or_else(|| format!("unterminated transform in svg: {}", s))?
+            + open;
+        let args = parse_numbers(&rest[open + 1..close])?;
+        let arg = |i: usize| args.get(i).copied();
+        let t = match (name, args.len()) {
+            ("matrix", 6) => Affine::new(args[0], args[1], args[2], args[3], args[4], args[5]),
+            ("translate", 1 | 2) => Affine::new(1.0, 0.0, 0.0, 1.0, args[0], arg(1).unwrap_or(0.0)),
+            ("scale", 1 | 2) => Affine::new(args[0], 0.0, 0.0, arg(1).unwrap_or(args[0]), 0.0, 0.0),
+            ("rotate", 1 | 3) => {
+                let (sin, cos) = args[0].to_radians().sin_cos();
+                let rotation = Affine::new(cos, sin, -sin, cos, 0.0, 0.0);
+                let (cx, cy) = (arg(1).unwrap_or(0.0), arg(2).unwrap_or(0.0));
+                Affine::new(1.0, 0.0, 0.0, 1.0, cx, cy)
+                    .then(&rotation)
+                    .then(&Affine::new(1.0, 0.0, 0.0, 1.0, -cx, -cy))
+            }
+            ("skewX", 1) => Affine::new(1.0, 0.0, args[0].to_radians().tan(), 1.0, 0.0, 0.0),
+            ("skewY", 1) => Affine::new(1.0, args[0].to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            _ => return Err(format!("unsupported transform in svg: {}", &rest[..=close])),
+        };
+        transform = transform.then(&t);
+        rest = &rest[close + 1..];
+    }
+    Ok(transform)
+}
+
+/// Parses all numbers in a list separated by whitespace and/or commas
+fn parse_numbers(s: &str) -> Result<Vec<fsize>, String> {
+    let mut lexer = Lexer::new(s);
+    let mut numbers = vec![];
+    while let Some(n) = lexer.number() {
+        numbers.push(n);
+    }
+    match lexer.at_end() {
+        true => Ok(numbers),
+        false => Err(format!("invalid number list in svg: {}", s)),
+    }
+}
+
+/// Closed contour, a start point followed by a sequence of segments
+#[derive(Clone, Debug)]
+struct Contour {
+    start: Point,
+    segments: Vec<Segment>,
+}
+
+/// Segment of a contour, starting at the end of the previous segment
+#[derive(Clone, Debug)]
+enum Segment {
+    Line(Point),
+    /// Control point and end point
+    Quadratic(Point, Point),
+    /// Two control points and end point
+    Cubic(Point, Point, Point),
+    Arc(EllipticalArc),
+}
+
+/// Arc of an ellipse: the points `center + u cos(t) + v sin(t)` for `t` from `start_angle` to `start_angle + sweep`.
+/// Unlike the representation of SVG, this one is closed under affine transformations
+#[derive(Clone, Debug)]
+struct EllipticalArc {
+    center: Point,
+    u: Point,
+    v: Point,
+    start_angle: fsize,
+    sweep: fsize,
+}
+
+impl EllipticalArc {
+    /// Converts the endpoint parametrization of SVG to the center parametrization,
+    /// see <https://www.w3.org/TR/SVG11/implnote.html#ArcConversionEndpointToCenter>.
+    /// Returns `None` if the arc degenerates to a line segment
+    fn from_svg(
+        start: Point,
+        (rx, ry): (fsize, fsize),
+        x_axis_rotation: fsize,
+        large_arc: bool,
+        sweep: bool,
+        end: Point,
+    ) -> Option<Self> {
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if rx == 0.0 || ry == 0.0 || start == end {
+            return None;
+        }
+        let (sin_phi, cos_phi) = x_axis_rotation.to_radians().sin_cos();
+        let (dx, dy) = ((start.0 - end.0) / 2.0, (start.1 - end.1) / 2.0);
+        let x1 = cos_phi * dx + sin_phi * dy;
+        let y1 = -sin_phi * dx + cos_phi * dy;
+
+        //radii which are too small are scaled up until the arc fits
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coef = fsize::max(num / den, 0.0).sqrt();
+        if large_arc == sweep {
+            coef = -coef;
+        }
+        let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+        let center = Point(
+            cos_phi * cx1 - sin_phi * cy1 + (start.0 + end.0) / 2.0,
+            sin_phi * cx1 + cos_phi * cy1 + (start.1 + end.1) / 2.0,
+        );
+
+        let angle = |x: fsize, y: fsize| y.atan2(x);
+        let start_angle = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let end_angle = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+        let mut delta = end_angle - start_angle;
+        if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        } else if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        }
+
+        Some(EllipticalArc {
+            center,
+            u: Point(rx * cos_phi, rx * sin_phi),
+            v: Point(-ry * sin_phi, ry * cos_phi),
+            start_angle,
+            sweep: delta,
+        })
+    }
+
+    fn point_at(&self, t: fsize) -> Point {
+        let (sin, cos) = t.sin_cos();
+        Point(
+            self.center.0 + self.u.0 * cos + self.v.0 * sin,
+            self.center.1 + self.u.1 * cos + self.v.1 * sin,
+        )
+    }
+
+    fn transform(&self, t: &Affine) -> Self {
+        EllipticalArc {
+            center: t.apply(self.center),
+            u: t.apply_linear(self.u),
+            v: t.apply_linear(self.v),
+            ..self.clone()
+        }
+    }
+
+    /// Bulge of the arc (see [`ArcPolygon`]), if it is circular
+    fn bulge(&self) -> Option<fsize> {
+        let (len_u, len_v) = (norm(self.u), norm(self.v));
+        let dot = self.u.0 * self.v.0 + self.u.1 * self.v.1;
+        let circular = (len_u - len_v).abs() <= CIRCULAR_TOLERANCE * len_u
+            && dot.abs() <= CIRCULAR_TOLERANCE * len_u * len_v;
+        //when v is rotated clockwise from u, increasing angles run clockwise
+        let orientation = (self.u.0 * self.v.1 - self.u.1 * self.v.0).signum();
+        (circular && self.sweep.abs() < 2.0 * PI).then(|| orientation * (self.sweep / 4.0).tan())
+    }
+
+    /// Intermediate points of a sequence of chords which do not deviate more than `max_deviation` from the arc
+    fn flatten(&self, max_deviation: fsize) -> Vec<Point> {
+        let radius = fsize::max(norm(self.u), norm(self.v));
+        let max_angle = match max_deviation < radius {
+            true => 2.0 * (1.0 - max_deviation / radius).acos(),
+            false => PI / 2.0,
+        };
+        let n_segments = fsize::max((self.sweep.abs() / max_angle).ceil(), 1.0) as usize;
+        (1..n_segments)
+            .map(|i| {
+                self.point_at(self.start_angle + self.sweep * i as fsize / n_segments as fsize)
+            })
+            .collect()
+    }
+}
+
+impl Contour {
+    fn transform(&self, t: &Affine) -> Contour {
+        let segments = self
+            .segments
+            .iter()
+            .map(|s| match s {
+                Segment::Line(p) => Segment::Line(t.apply(*p)),
+                Segment::Quadratic(c, p) => Segment::Quadratic(t.apply(*c), t.apply(*p)),
+                Segment::Cubic(c1, c2, p) => {
+                    Segment::Cubic(t.apply(*c1), t.apply(*c2), t.apply(*p))
+                }
+                Segment::Arc(arc) => Segment::Arc(arc.transform(t)),
+            })
+            .collect();
+        Contour {
+            start: t.apply(self.start),
+            segments,
+        }
+    }
+
+    /// Points of which the bounding box contains the entire contour
+    fn hull_points(&self) -> Vec<Point> {
+        let mut points = vec![self.start];
+        for segment in &self.segments {
+            match segment {
+                Segment::Line(p) => points.push(*p),
+                Segment::Quadratic(c, p) => points.extend([*c, *p]),
+                Segment::Cubic(c1, c2, p) => points.extend([*c1, *c2, *p]),
+                Segment::Arc(arc) => {
+                    let Point(cx, cy) = arc.center;
+                    let (ex, ey) = (norm(Point(arc.u.0, arc.v.0)), norm(Point(arc.u.1, arc.v.1)));
+                    points.extend([Point(cx - ex, cy - ey), Point(cx + ex, cy + ey)]);
+                }
+            }
+        }
+        points
+    }
+
+    fn to_arc_polygon(&self, max_deviation: fsize) -> ArcPolygon {
+        let mut points = vec![];
+        let mut bulges = vec![];
+        let mut current = self.start;
+        for segment in &self.segments {
+            let (end, bulge, intermediate) = match segment {
+                Segment::Line(p) => (*p, 0.0, vec![]),
+                Segment::Quadratic(c, p) => {
+                    //cubic with the same curve
+                    let c1 = lerp(current, *c, 2.0 / 3.0);
+                    let c2 = lerp(*p, *c, 2.0 / 3.0);
+                    (*p, 0.0, flatten_cubic(current, c1, c2, *p, max_deviation))
+                }
+                Segment::Cubic(c1, c2, p) => {
+                    (*p, 0.0, flatten_cubic(current, *c1, *c2, *p, max_deviation))
+                }
+                Segment::Arc(arc) => {
+                    let end = arc.point_at(arc.start_angle + arc.sweep);
+                    match arc.bulge() {
+                        Some(bulge) => (end, bulge, vec![]),
+                        None => (end, 0.0, arc.flatten(max_deviation)),
+                    }
+                }
+            };
+            if end == current && intermediate.is_empty() {
+                continue;
+            }
+            points.push(current);
+            bulges.push(bulge);
+            for p in intermediate {
+                points.push(p);
+                bulges.push(0.0);
+            }
+            current = end;
+        }
+        //the contour is closed by a straight edge if it does not end at its start
+        if current != self.start && !points.is_empty() {
+            points.push(current);
+            bulges.push(0.0);
+        }
+        ArcPolygon::new(points, bulges)
+    }
+}
+
+/// Intermediate points of a sequence of chords which do not deviate more than `max_deviation` from the cubic Bézier curve
+fn flatten_cubic(p0: Point, p1: Point, p2: Point, p3: Point, max_deviation: fsize) -> Vec<Point> {
+    //the deviation of n uniform chords is bounded by max |B''| / (8 n²), with |B''| <= 6 max |p_i - 2 p_i+1 + p_i+2|
+    let second_diff =
+        |a: Point, b: Point, c: Point| norm(Point(a.0 - 2.0 * b.0 + c.0, a.1 - 2.0 * b.1 + c.1));
+    let m = fsize::max(second_diff(p0, p1, p2), second_diff(p1, p2, p3));
+    let n_segments = fsize::max((0.75 * m / max_deviation).sqrt().ceil(), 1.0) as usize;
+    (1..n_segments)
+        .map(|i| {
+            let t = i as fsize / n_segments as fsize;
+            let mt = 1.0 - t;
+            let (w0, w1, w2, w3) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            Point(
+                w0 * p0.0 + w1 * p1.0 + w2 * p2.0 + w3 * p3.0,
+                w0 * p0.1 + w1 * p1.1 + w2 * p2.1 + w3 * p3.1,
+            )
+        })
+        .collect()
+}
+
+fn lerp(a: Point, b: Point, t: fsize) -> Point {
+    Point(a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+fn norm(Point(x, y): Point) -> fsize {
+    (x * x + y * y).sqrt()
+}
+
+/// Parses the `points` attribute of a `<polygon>`
+fn parse_polygon_points(s: &str) -> Result<Vec<Contour>, String> {
+    let numbers = parse_numbers(s)?;
+    let points = numbers
+        .chunks_exact(2)
+        .map(|c| Point(c[0], c[1]))
+        .collect::<Vec<_>>();
+    match points.split_first() {
+        Some((start, rest)) => Ok(vec![Contour {
+            start: *start,
+            segments: rest.iter().map(|p| Segment::Line(*p)).collect(),
+        }]),
+        None => Ok(vec![]),
+    }
+}
+
+/// Parses the `d` attribute of a `<path>`, every subpath is a contour
+fn parse_path_data(d: &str) -> Result<Vec<Contour>, String> {
+    let mut lexer = Lexer::new(d);
+    let mut contours: Vec<Contour> = vec![];
+    let mut current = Point(0.0, 0.0);
+    //control point of the previous curve, for the smooth curve commands
+    let mut prev_control: Option<(char, Point)> = None;
+    let mut command = None;
+
+    let invalid = || format!("invalid path data in svg: {}", d);
+    while !lexer.at_end() {
+        let cmd = match lexer.command() {
+            Some(c) => c,
+            //repeated parameters continue the previous command, a moveto is continued by linetos
+            None => match command {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some(c) => c,
+                None => return Err(invalid()),
+            },
+        };
+        command = Some(cmd);
+        let relative = cmd.is_ascii_lowercase();
+        let offset = |p: Point, current: Point| match relative {
+            true => Point(p.0 + current.0, p.1 + current.1),
+            false => p,
+        };
+        let number = |lexer: &mut Lexer| lexer.number().ok_or_else(invalid);
+        let point = |lexer: &mut Lexer| Ok::<_, String>(Point(number(lexer)?, number(lexer)?));
+
+        if !matches!(cmd.to_ascii_uppercase(), 'M' | 'Z') && contours.is_empty() {
+            return Err(invalid());
+        }
+        let segment = match cmd.to_ascii_uppercase() {
+            'M' => {
+                current = offset(point(&mut lexer)?, current);
+                contours.push(Contour {
+                    start: current,
+                    segments: vec![],
+                });
+                prev_control = None;
+                continue;
+            }
+            'Z' => {
+                if let Some(contour) = contours.last() {
+                    current = contour.start;
+                }
+                command = None;
+                prev_control = None;
+                continue;
+            }
+            'L' => Segment::Line(offset(point(&mut lexer)?, current)),
+            'H' => {
+                let x = number(&mut lexer)?;
+                Segment::Line(Point(if relative { current.0 + x } else { x }, current.1))
+            }
+            'V' => {
+                let y = number(&mut lexer)?;
+                Segment::Line(Point(current.0, if relative { current.1 + y } else { y }))
+            }
+            'C' | 'S' => {
+                let c1 = match cmd.to_ascii_uppercase() {
+                    'C' => offset(point(&mut lexer)?, current),
+                    _ => reflected_control(prev_control, 'C', current),
+                };
+                let c2 = offset(point(&mut lexer)?, current);
+                let end = offset(point(&mut lexer)?, current);
+                Segment::Cubic(c1, c2, end)
+            }
+            'Q' | 'T' => {
+                let c = match cmd.to_ascii_uppercase() {
+                    'Q' => offset(point(&mut lexer)?, current),
+                    _ => reflected_control(prev_control, 'Q', current),
+                };
+                let end = offset(point(&mut lexer)?, current);
+                Segment::Quadratic(c, end)
+            }
+            'A' => {
+                let rx = number(&mut lexer)?;
+                let ry = number(&mut lexer)?;
+                let x_axis_rotation = number(&mut lexer)?;
+                let large_arc = lexer.flag().ok_or_else(invalid)?;
+                let sweep = lexer.flag().ok_or_else(invalid)?;
+                let end = offset(point(&mut lexer)?, current);
+                match EllipticalArc::from_svg(
+                    current,
+                    (rx, ry),
+                    x_axis_rotation,
+                    large_arc,
+                    sweep,
+                    end,
+                ) {
+                    Some(arc) => Segment::Arc(arc),
+                    None => Segment::Line(end),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+
+        prev_control = match &segment {
+            Segment::Cubic(_, c2, _) => Some(('C', *c2)),
+            Segment::Quadratic(c, _) => Some(('Q', *c)),
+            _ => None,
+        };
+        current = match &segment {
+            Segment::Line(p) | Segment::Quadratic(_, p) | Segment::Cubic(_, _, p) => *p,
+            Segment::Arc(arc) => arc.point_at(arc.start_angle + arc.sweep),
+        };
+        contours.last_mut().unwrap().segments.push(segment);
+    }
+    Ok(contours)
+}
+
+/// First control point of a smooth curve: the reflection of the last control point of the previous curve of the same kind
+fn reflected_control(prev_control: Option<(char, Point)>, kind: char, current: Point) -> Point {
+    match prev_control {
+        Some((k, c)) if k == kind => Point(2.0 * current.0 - c.0, 2.0 * current.1 - c.1),
+        _ => current,
+    }
+}
+
+/// Tokenizer for path data and number lists, where separators are optional in many places (e.g. `M10-5.5.5`)
+struct Lexer<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            s: s.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.pos < self.s.len()
+            && (self.s[self.pos].is_ascii_whitespace() || self.s[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos == self.s.len()
+    }
+
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = *self.s.get(self.pos)? as char;
+        match c.is_ascii_alphabetic() && !matches!(c, 'e' | 'E') {
+            true => {
+                self.pos += 1;
+                Some(c)
+            }
+            false => None,
+        }
+    }
+
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.s.get(self.pos)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+
+    fn number(&mut self) -> Option<fsize> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |lexer: &mut Self| {
+            let start = lexer.pos;
+            while lexer.pos < lexer.s.len() && lexer.s[lexer.pos].is_ascii_digit() {
+                lexer.pos += 1;
+            }
+            lexer.pos > start
+        };
+        let sign = |lexer: &mut Self| {
+            if matches!(lexer.s.get(lexer.pos), Some(b'+' | b'-')) {
+                lexer.pos += 1;
+            }
+        };
+
+        sign(self);
+        let mut has_digits = digits(self);
+        if self.s.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            self.pos = start;
+            return None;
+        }
+        if matches!(self.s.get(self.pos), Some(b'e' | b'E')) {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            sign(self);
+            if !digits(self) {
+                self.pos = mantissa_end;
+            }
+        }
+        std::str::from_utf8(&self.s[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+}
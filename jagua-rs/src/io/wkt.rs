@@ -0,0 +1,132 @@
+use crate::fsize;
+use crate::io::json_instance::{JsonPoly, JsonShape, JsonSimplePoly};
+
+/// Converts a `POLYGON` or `MULTIPOLYGON` in Well-Known Text (e.g. `POLYGON ((0 0, 10 0, 10 10, 0 10, 0 0))`) to the equivalent shape.
+/// The polygons are converted with [`JsonShape::from_polygons`].
+/// Z and M coordinates are ignored, as is the `SRID=<srid>;` prefix of Extended WKT.
+/// The closing point of every ring is removed.
+pub fn wkt_shape(wkt: &str) -> Result<JsonShape, String> {
+    let wkt = match wkt.trim_start().get(..5) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("SRID=") => {
+            let (_, rest) = wkt.split_once(';').ok_or("SRID without ';'")?;
+            rest
+        }
+        _ => wkt,
+    };
+    let mut scanner = WktScanner { rest: wkt };
+    let geometry_type = scanner.word().to_ascii_uppercase();
+    let dimensions = match scanner.word().to_ascii_uppercase().as_str() {
+        "" => 2,
+        "Z" | "M" => 3,
+        "ZM" => 4,
+        "EMPTY" => return Err(format!("empty {}", geometry_type)),
+        other => return Err(format!("unexpected '{}' after {}", other, geometry_type)),
+    };
+    if scanner.word().eq_ignore_ascii_case("EMPTY") {
+        return Err(format!("empty {}", geometry_type));
+    }
+
+    let polygons = match geometry_type.as_str() {
+        "POLYGON" => vec![scanner.polygon(dimensions)?],
+        "MULTIPOLYGON" => scanner.list(|s| s.polygon(dimensions))?,
+        "" => return Err("no geometry type".into()),
+        other => {
+            return Err(format!(
+                "{} is not supported, only POLYGON and MULTIPOLYGON",
+                other
+            ))
+        }
+    };
+    if !scanner.rest.trim().is_empty() {
+        return Err(format!("unexpected '{}'", scanner.rest.trim()));
+    }
+
+    Ok(JsonShape::from_polygons(polygons))
+}
+
+/// Scans the text of a WKT geometry from front to back
+struct WktScanner<'a> {
+    rest: &'a str,
+}
+
+impl<'a> WktScanner<'a> {
+    /// Consumes the next word (letters only), empty if the text continues with something else
+    fn word(&mut self) -> &'a str {
+        self.rest = self.rest.trim_start();
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        word
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                Ok(())
+            }
+            None => Err(match self.rest.chars().next() {
+                Some(found) => format!("expected '{}', found '{}'", c, found),
+                None => format!("expected '{}', found the end of the text", c),
+            }),
+        }
+    }
+
+    /// Parses a parenthesized, comma separated list of elements
+    fn list<T>(
+        &mut self,
+        mut element: impl FnMut(&mut Self) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        self.expect('(')?;
+        let mut elements = vec![element(self)?];
+        while self.expect(',').is_ok() {
+            elements.push(element(self)?);
+        }
+        self.expect(')')?;
+        Ok(elements)
+    }
+
+    fn polygon(&mut self, dimensions: usize) -> Result<JsonPoly, String> {
+        let mut rings = self.list(|s| s.ring(dimensions))?.into_iter();
+        Ok(JsonPoly {
+            outer: rings.next().unwrap(),
+            inner: rings.collect(),
+        })
+    }
+
+    fn ring(&mut self, dimensions: usize) -> Result<JsonSimplePoly, String> {
+        let mut points = self.list(|s| s.point(dimensions))?;
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        match points.len() {
+            0..=2 => Err(format!("ring with {} distinct points", points.len())),
+            _ => Ok(JsonSimplePoly(points)),
+        }
+    }
+
+    /// Parses a point of the given number of dimensions, of which only x and y are kept
+    fn point(&mut self, dimensions: usize) -> Result<(fsize, fsize), String> {
+        let coordinates = (0..dimensions)
+            .map(|_| self.number())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((coordinates[0], coordinates[1]))
+    }
+
+    fn number(&mut self) -> Result<fsize, String> {
+        self.rest = self.rest.trim_start();
+        let end = self
+            .rest
+            .find(|c: char| c.is_whitespace() || c == ',' || c == ')')
+            .unwrap_or(self.rest.len());
+        let (number, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        number
+            .parse::<fsize>()
+            .map_err(|_| format!("invalid coordinate '{}'", number))
+    }
+}
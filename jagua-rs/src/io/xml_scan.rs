@@ -0,0 +1,113 @@
+/// Markup of an XML document, as scanned by [`xml_events`]
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum XmlEvent<'a> {
+    /// Opening tag, without contents or closing tag if it is `self_closing`
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, &'a str)>,
+        self_closing: bool,
+    },
+    /// Closing tag
+    End { name: &'a str },
+    /// Text between two tags, excluding whitespace-only text
+    Text(&'a str),
+}
+
+/// Scans an XML document into a flat list of tags and text, in document order.
+/// Comments, processing instructions, declarations and CDATA sections are skipped, and entities are not decoded.
+/// Only checks whether every tag is terminated, not whether the opening and closing tags match.
+pub(crate) fn xml_events(xml: &str) -> Result<Vec<XmlEvent<'_>>, String> {
+    let mut events = vec![];
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let start = rest.find('<').unwrap_or(rest.len());
+        let text = rest[..start].trim();
+        if !text.is_empty() {
+            events.push(XmlEvent::Text(text));
+        }
+        rest = &rest[start..];
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(skipped) = skip_markup(rest) {
+            rest = skipped;
+            continue;
+        }
+        let end = tag_end(rest).ok_or("unterminated tag")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            events.push(XmlEvent::End { name: name.trim() });
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        events.push(XmlEvent::Start {
+            name: &tag[..name_end],
+            attributes: parse_attributes(&tag[name_end..]),
+            self_closing,
+        });
+    }
+    Ok(events)
+}
+
+/// Value of the attribute `key` of a tag
+pub(crate) fn attribute<'a>(attributes: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    attributes.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// Skips comments, processing instructions, declarations and CDATA sections, if `s` starts with one
+fn skip_markup(s: &str) -> Option<&str> {
+    let terminator = [
+        ("<!--", "-->"),
+        ("<![CDATA[", "]]>"),
+        ("<?", "?>"),
+        ("<!", ">"),
+    ]
+    .into_iter()
+    .find(|(opening, _)| s.starts_with(opening))
+    .map(|(_, terminator)| terminator)?;
+    match s.find(terminator) {
+        Some(end) => Some(&s[end + terminator.len()..]),
+        None => Some(""),
+    }
+}
+
+/// Index of the `>` closing the tag at the start of `s`, ignoring those in quoted attribute values
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses the `key="value"` pairs of a tag
+fn parse_attributes(s: &str) -> Vec<(&str, &str)> {
+    let mut attributes = vec![];
+    let mut rest = s;
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim();
+        let value_part = rest[eq + 1..].trim_start();
+        let Some(quote) = value_part
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+        else {
+            break;
+        };
+        let Some(value_end) = value_part[1..].find(quote) else {
+            break;
+        };
+        attributes.push((key, &value_part[1..value_end + 1]));
+        rest = &value_part[value_end + 2..];
+    }
+    attributes
+}
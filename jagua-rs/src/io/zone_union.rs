@@ -0,0 +1,91 @@
+use geo::{BooleanOps, BoundingRect, Coord, Intersects, LineString, MultiPolygon, Polygon, Rect};
+use itertools::Itertools;
+
+use crate::fsize;
+use crate::geometry::primitives::point::Point;
+
+/// Shape of a quality zone as defined in the input: an outer boundary and (possibly) holes.
+/// Holes are regions inside the zone where the material is not of the inferior quality.
+#[derive(Clone, Debug)]
+pub struct ZoneShape {
+    pub outer: Vec<Point>,
+    pub holes: Vec<Vec<Point>>,
+}
+
+/// Converts the zones of a single quality into simple polygons (as lists of points) which cover exactly the same region.
+/// <br>
+/// Zones which overlap (or touch) are merged into a single shape, and shapes with holes are cut into pieces without holes.
+/// Zones without holes which do not overlap any other zone are returned as they are.
+pub fn union_zones(zones: Vec<ZoneShape>) -> Vec<Vec<Point>> {
+    let polygons = zones.iter().map(to_geo_polygon).collect_vec();
+
+    //group the zones which (transitively) overlap each other
+    let mut group = (0..zones.len()).collect_vec();
+    for (i, j) in (0..zones.len()).tuple_combinations() {
+        if polygons[i].intersects(&polygons[j]) {
+            let (gi, gj) = (group[i], group[j]);
+            group.iter_mut().filter(|g| **g == gj).for_each(|g| *g = gi);
+        }
+    }
+
+    let mut simple_polygons = vec![];
+    for g in group.iter().unique() {
+        let members = (0..zones.len()).filter(|i| group[*i] == *g).collect_vec();
+        match members.as_slice() {
+            [i] if zones[*i].holes.is_empty() => simple_polygons.push(zones[*i].outer.clone()),
+            _ => {
+                let union = members.iter().fold(MultiPolygon::new(vec![]), |union, i| {
+                    union.union(&polygons[*i])
+                });
+                simple_polygons.extend(
+                    union
+                        .into_iter()
+                        .flat_map(cut_holes)
+                        .map(|p| from_geo_ring(p.exterior())),
+                );
+            }
+        }
+    }
+    simple_polygons
+}
+
+/// Cuts a polygon into pieces without holes.
+/// Every cut is a vertical line through a hole, which opens up the hole (and any other hole it crosses)
+fn cut_holes(polygon: Polygon<fsize>) -> Vec<Polygon<fsize>> {
+    let Some(hole) = polygon.interiors().first() else {
+        return vec![polygon];
+    };
+    let (hole_bbox, bbox) = (
+        hole.bounding_rect().expect("hole has no points"),
+        polygon.bounding_rect().expect("polygon has no points"),
+    );
+    //the line through the center of the bounding box of the hole always passes through its interior
+    let x_cut = hole_bbox.center().x;
+    let (min, max) = (bbox.min(), bbox.max());
+    let halves = [
+        Rect::new(Coord { x: min.x, y: min.y }, Coord { x: x_cut, y: max.y }),
+        Rect::new(Coord { x: x_cut, y: min.y }, Coord { x: max.x, y: max.y }),
+    ];
+    halves
+        .into_iter()
+        .flat_map(|half| polygon.intersection(&half.to_polygon()))
+        .flat_map(cut_holes)
+        .collect()
+}
+
+fn to_geo_polygon(zone: &ZoneShape) -> Polygon<fsize> {
+    let ring = |points: &[Point]| LineString::from(points.iter().map(|p| (p.0, p.1)).collect_vec());
+    Polygon::new(
+        ring(&zone.outer),
+        zone.holes.iter().map(|h| ring(h)).collect(),
+    )
+}
+
+fn from_geo_ring(ring: &LineString<fsize>) -> Vec<Point> {
+    //geo rings are closed, the last point is the same as the first
+    let mut points = ring.coords().map(|c| Point(c.x, c.y)).dedup().collect_vec();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
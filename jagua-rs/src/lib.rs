@@ -3,6 +3,15 @@
 //!
 //!
 //! This crate can be configured to use single or double precision for floating points (see [fsize]).
+//!
+//! The [prelude] re-exports the types and traits most optimizers need.
+//!
+//! Features (all enabled by default except **double-precision**):
+//! - **io**: parser and JSON (de)serialization of instances and solutions
+//! - **dxf**: items defined in DXF files (implies **io**)
+//! - **parallel**: parses instances in parallel using rayon, the parsed instance does not depend on the number of threads
+//!
+//! Disabling the default features leaves only the geometry and collision detection engine, for minimal (e.g. WASM or FFI) builds.
 
 /// Everything collision detection engine related
 pub mod collision_detection;
@@ -13,12 +22,15 @@ pub mod entities;
 /// Geometric primitives and base algorithms
 pub mod geometry;
 
-/// Parser and JSON (de)serialization
+/// Parser and JSON (de)serialization, requires feature **io**
+#[cfg(feature = "io")]
 pub mod io;
 
 /// Helper functions
 pub mod util;
 
+pub mod prelude;
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "double-precision")] {
         /// The floating point type used in jagua-rs.
@@ -0,0 +1,55 @@
+//! Re-exports of the types and traits a typical optimizer built on top of jagua-rs needs.
+//!
+//! ```
+//! use jagua_rs::prelude::*;
+//! ```
+//!
+//! Paths within the prelude are part of the stable public API,
+//! deeper module paths may be reorganized between minor versions.
+
+pub use crate::collision_detection::cd_engine::CDEngine;
+pub use crate::collision_detection::hazard::{Hazard, HazardEntity};
+pub use crate::collision_detection::hazard_filter::{
+    generate_irrelevant_hazards, BinHazardFilter, CombinedHazardFilter, EntityHazardFilter,
+    HazardFilter, QZHazardFilter,
+};
+pub use crate::collision_detection::hpg::hpg_cell::HPGCell;
+pub use crate::collision_detection::quadtree::qt_hazard::QTHazPresence;
+pub use crate::collision_detection::quadtree::qt_node::QTNode;
+pub use crate::collision_detection::quadtree::qt_traits::QTQueryable;
+pub use crate::entities::bin::Bin;
+pub use crate::entities::instances::bin_packing::BPInstance;
+pub use crate::entities::instances::instance::Instance;
+pub use crate::entities::instances::instance_generic::InstanceGeneric;
+pub use crate::entities::instances::strip_packing::SPInstance;
+pub use crate::entities::item::Item;
+pub use crate::entities::layout::Layout;
+pub use crate::entities::placed_item::{PItemKey, PlacedItem};
+pub use crate::entities::placing_option::PlacingOption;
+pub use crate::entities::problems::bin_packing::BPProblem;
+pub use crate::entities::problems::custom_problem::CustomProblem;
+pub use crate::entities::problems::problem::Problem;
+pub use crate::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+pub use crate::entities::problems::strip_packing::SPProblem;
+pub use crate::entities::quality_zone::InferiorQualityZone;
+pub use crate::entities::solution::Solution;
+pub use crate::geometry::d_transformation::DTransformation;
+pub use crate::geometry::geo_enums::AllowedRotation;
+pub use crate::geometry::geo_traits::{
+    CollidesWith, DistanceFrom, Shape, Transformable, TransformableFrom,
+};
+pub use crate::geometry::primitives::aa_rectangle::AARectangle;
+pub use crate::geometry::primitives::circle::Circle;
+pub use crate::geometry::primitives::edge::Edge;
+pub use crate::geometry::primitives::point::Point;
+pub use crate::geometry::primitives::simple_polygon::SimplePolygon;
+pub use crate::geometry::transformation::Transformation;
+#[cfg(feature = "io")]
+pub use crate::io::json_instance::JsonInstance;
+#[cfg(feature = "io")]
+pub use crate::io::json_solution::JsonSolution;
+#[cfg(feature = "io")]
+pub use crate::io::parser::Parser;
+pub use crate::util::config::{CDEConfig, GeometricFidelity, SPSurrogateConfig};
+pub use crate::util::polygon_simplification::PolySimplConfig;
+pub use crate::{fsize, PI};
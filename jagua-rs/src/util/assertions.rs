@@ -19,6 +19,7 @@ use crate::entities::layout::Layout;
 use crate::entities::layout::LayoutSnapshot;
 use crate::entities::problems::problem_generic::ProblemGeneric;
 use crate::entities::solution::Solution;
+use crate::fsize;
 use crate::geometry::geo_traits::{Shape, Transformable};
 use crate::geometry::primitives::aa_rectangle::AARectangle;
 use crate::geometry::transformation::Transformation;
@@ -27,6 +28,29 @@ use crate::util;
 //Various checks to verify correctness of the state of the system
 //Used in debug_assertion!() blocks
 
+/// Same as `debug_assert!`, but when the condition fails, `$svg` is evaluated to write an SVG of the offending state
+/// (see [`debug_svg`](crate::util::debug_svg)), of which the path is included in the panic message.
+macro_rules! debug_assert_with_svg {
+    ($cond:expr, $svg:expr) => {
+        if cfg!(debug_assertions) && !$cond {
+            let svg: std::io::Result<std::path::PathBuf> = $svg;
+            match svg {
+                Ok(path) => panic!(
+                    "assertion failed: {}, state written to {}",
+                    stringify!($cond),
+                    path.display()
+                ),
+                Err(err) => panic!(
+                    "assertion failed: {}, could not write state to svg: {}",
+                    stringify!($cond),
+                    err
+                ),
+            }
+        }
+    };
+}
+pub(crate) use debug_assert_with_svg;
+
 pub fn instance_item_bin_ids_correct(items: &[(Item, usize)], bins: &[(Bin, usize)]) -> bool {
     items
         .iter()
@@ -143,6 +167,26 @@ pub fn layout_is_collision_free(layout: &Layout) -> bool {
     true
 }
 
+/// Checks if the incrementally maintained area of the placed items matches a fresh summation
+pub fn layout_placed_item_area_correct(layout: &Layout) -> bool {
+    let fresh_area = layout
+        .placed_items()
+        .values()
+        .map(|pi| pi.shape.area())
+        .sum::<fsize>();
+    let tolerance = 1e-4 * layout.bin.area;
+    if (layout.placed_item_area() - fresh_area).abs() > tolerance {
+        error!(
+            "placed item area of layout {} is {}, but the fresh area is {}",
+            layout.id(),
+            layout.placed_item_area(),
+            fresh_area
+        );
+        return false;
+    }
+    true
+}
+
 pub fn qt_node_contains_no_deactivated_hazards<'a>(
     node: &'a QTNode,
     mut stacktrace: Vec<&'a QTNode>,
@@ -260,6 +304,13 @@ pub fn layout_qt_matches_fresh_qt(layout: &Layout) -> bool {
         let hazard = Hazard::new(pi.into(), pi.shape.clone());
         fresh_cde.register_hazard(hazard);
     }
+    let n_placed = layout.placed_items().len();
+    for (id, zone) in bin.temporary_zones.iter().enumerate() {
+        if zone.is_active(n_placed) {
+            let hazard = Hazard::new(HazardEntity::TemporaryZone { id }, zone.shape.clone());
+            fresh_cde.register_hazard(hazard);
+        }
+    }
 
     qt_nodes_match(Some(layout.cde().quadtree()), Some(fresh_cde.quadtree()))
         && hazards_match(layout.cde().dynamic_hazards(), fresh_cde.dynamic_hazards())
@@ -325,7 +376,7 @@ fn qt_nodes_match(qn1: Option<&QTNode>, qn2: Option<&QTNode>) -> bool {
     ) {
         (None, None) => true,
         (Some(c1), None) => {
-            let qn1_has_partial_hazards = qn1.map_or(false, |qn| {
+            let qn1_has_partial_hazards = qn1.is_some_and(|qn| {
                 qn.hazards
                     .active_hazards()
                     .iter()
@@ -341,7 +392,7 @@ fn qt_nodes_match(qn1: Option<&QTNode>, qn2: Option<&QTNode>) -> bool {
             true
         }
         (None, Some(c2)) => {
-            let qn2_has_partial_hazards = qn2.map_or(false, |qn| {
+            let qn2_has_partial_hazards = qn2.is_some_and(|qn| {
                 qn.hazards
                     .active_hazards()
                     .iter()
@@ -0,0 +1,165 @@
+use itertools::Itertools;
+use ordered_float::NotNan;
+use slotmap::{SecondaryMap, SlotMap};
+
+use crate::entities::bin::Bin;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::placed_item::{PItemKey, PlacedItem};
+use crate::fsize;
+use crate::geometry::geo_traits::{CollidesWith, DistanceFrom, Shape};
+use crate::geometry::primitives::aa_rectangle::AARectangle;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+use crate::util::fpa::FPA;
+
+/// Entity between which a clearance is measured
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClearanceEntity {
+    PlacedItem(PItemKey),
+    /// The contour of the bin
+    BinExterior,
+    /// A hole in the bin, with its index in [`Bin::holes`]
+    BinHole(usize),
+}
+
+/// Clearance between a placed item and another entity
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clearance {
+    pub item: PItemKey,
+    pub other: ClearanceEntity,
+    /// Distance between the contours of the two entities, zero if they intersect
+    pub distance: fsize,
+    /// Lower bound on the distance between the contours as defined in the input:
+    /// `distance` minus the [`curve_deviation`](crate::entities::item::Item::curve_deviation) of the items and rounding, at least zero
+    pub lower_bound: fsize,
+}
+
+/// Result of verifying the clearances of all items in a layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClearanceReport {
+    /// Smallest clearance in the layout, `None` if the layout contains no items
+    pub min_clearance: Option<Clearance>,
+    /// All clearances of which the lower bound is below the threshold, sorted from smallest to largest
+    pub violations: Vec<Clearance>,
+}
+
+/// Computes the minimum clearance between every pair of adjacent placed items and between the items and the bin boundary.
+/// Pairs of which the bounding boxes are further apart than both the threshold and the current minimum are skipped,
+/// as their clearance cannot be relevant.
+///
+/// Distances are measured on the shapes of the placed items (see [`GeometricFidelity`](crate::util::config::GeometricFidelity)).
+/// Tessellated arcs and simplification only grow items and shrink bins, but flattened curves can lie inside the input geometry.
+/// Therefore every clearance also carries a lower bound on the real clearance, which is what the threshold is checked against.
+pub fn verify_clearances(
+    instance: &impl InstanceGeneric,
+    bin: &Bin,
+    placed_items: &SlotMap<PItemKey, PlacedItem>,
+    threshold: fsize,
+) -> ClearanceReport {
+    //maximum distance by which the input geometry of every placed item can lie outside of its shape
+    let deviations = placed_items
+        .iter()
+        .map(|(pk, pi)| {
+            let bbox = pi.shape.bbox();
+            let rounding = FPA::tolerance() * fsize::max(bbox.width(), bbox.height());
+            (pk, instance.item(pi.item_id).curve_deviation + rounding)
+        })
+        .collect::<SecondaryMap<PItemKey, fsize>>();
+    let max_deviation = deviations.values().cloned().fold(0.0, fsize::max);
+
+    let mut clearances = vec![];
+
+    //clearances between the items and the boundary of the bin, always computed
+    for (pk, pi) in placed_items.iter() {
+        let clearance = |other, distance: fsize| Clearance {
+            item: pk,
+            other,
+            distance,
+            lower_bound: fsize::max(0.0, distance - deviations[pk]),
+        };
+        clearances.push(clearance(
+            ClearanceEntity::BinExterior,
+            contour_distance(&pi.shape, &bin.outer),
+        ));
+        for (i, hole) in bin.holes.iter().enumerate() {
+            clearances.push(clearance(
+                ClearanceEntity::BinHole(i),
+                contour_distance(&pi.shape, hole),
+            ));
+        }
+    }
+
+    //clearances between pairs of items, in order of increasing distance between their bounding boxes
+    let candidate_pairs = placed_items
+        .iter()
+        .tuple_combinations()
+        .map(|((pk1, pi1), (pk2, pi2))| {
+            let bbox_distance = bbox_distance(&pi1.shape.bbox(), &pi2.shape.bbox());
+            (pk1, pk2, bbox_distance)
+        })
+        .sorted_by_key(|(_, _, d)| NotNan::new(*d).expect("bbox distance is NaN"))
+        .collect_vec();
+
+    let mut min_distance = clearances
+        .iter()
+        .map(|c| c.distance)
+        .fold(fsize::INFINITY, fsize::min);
+
+    for (pk1, pk2, bbox_distance) in candidate_pairs {
+        if bbox_distance > fsize::max(threshold + 2.0 * max_deviation, min_distance) {
+            //all remaining pairs are even further apart
+            break;
+        }
+        let distance = contour_distance(&placed_items[pk1].shape, &placed_items[pk2].shape);
+        min_distance = fsize::min(min_distance, distance);
+        clearances.push(Clearance {
+            item: pk1,
+            other: ClearanceEntity::PlacedItem(pk2),
+            distance,
+            lower_bound: fsize::max(0.0, distance - deviations[pk1] - deviations[pk2]),
+        });
+    }
+
+    let min_clearance = clearances
+        .iter()
+        .min_by_key(|c| NotNan::new(c.distance).expect("clearance is NaN"))
+        .copied();
+
+    let violations = clearances
+        .into_iter()
+        .filter(|c| c.lower_bound < threshold)
+        .sorted_by_key(|c| NotNan::new(c.lower_bound).expect("clearance is NaN"))
+        .collect_vec();
+
+    ClearanceReport {
+        min_clearance,
+        violations,
+    }
+}
+
+/// Minimum distance between the contours of two polygons, zero if they intersect
+pub fn contour_distance(p1: &SimplePolygon, p2: &SimplePolygon) -> fsize {
+    let intersecting = p1
+        .edge_iter()
+        .cartesian_product(p2.edge_iter().collect_vec())
+        .any(|(e1, e2)| e1.collides_with(&e2));
+
+    match intersecting {
+        true => 0.0,
+        false => {
+            //for non-intersecting contours, the closest pair of points always includes a vertex of one of them
+            let vertex_distance = |from: &SimplePolygon, to: &SimplePolygon| {
+                from.points
+                    .iter()
+                    .flat_map(|p| to.edge_iter().map(move |e| e.sq_distance(p)))
+                    .fold(fsize::INFINITY, fsize::min)
+            };
+            fsize::min(vertex_distance(p1, p2), vertex_distance(p2, p1)).sqrt()
+        }
+    }
+}
+
+fn bbox_distance(a: &AARectangle, b: &AARectangle) -> fsize {
+    let dx = fsize::max(0.0, fsize::max(a.x_min - b.x_max, b.x_min - a.x_max));
+    let dy = fsize::max(0.0, fsize::max(a.y_min - b.y_max, b.y_min - a.y_max));
+    (dx.powi(2) + dy.powi(2)).sqrt()
+}
@@ -1,6 +1,11 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
 use crate::fsize;
+use crate::util::fpa::FPA;
+use crate::util::polygon_simplification::PolySimplConfig;
 
 ///Configuration of the Collision Detection Engine
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
@@ -13,6 +18,82 @@ pub struct CDEConfig {
     pub item_surrogate_config: SPSurrogateConfig,
 }
 
+impl CDEConfig {
+    /// Configuration for the given preset, see [`Preset`]
+    pub fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Fast => Self {
+                quadtree_depth: 5,
+                hpg_n_cells: 1000,
+                item_surrogate_config: SPSurrogateConfig {
+                    pole_coverage_goal: 0.8,
+                    max_poles: 6,
+                    n_ff_poles: 2,
+                    n_ff_piers: 0,
+                },
+            },
+            Preset::Balanced => Self {
+                quadtree_depth: 5,
+                hpg_n_cells: 2000,
+                item_surrogate_config: SPSurrogateConfig {
+                    pole_coverage_goal: 0.9,
+                    max_poles: 10,
+                    n_ff_poles: 2,
+                    n_ff_piers: 0,
+                },
+            },
+            Preset::Quality => Self {
+                quadtree_depth: 5,
+                hpg_n_cells: 4000,
+                item_surrogate_config: SPSurrogateConfig {
+                    pole_coverage_goal: 0.95,
+                    max_poles: 16,
+                    n_ff_poles: 2,
+                    n_ff_piers: 0,
+                },
+            },
+        }
+    }
+}
+
+/// Named set of parameters, trading off speed against quality.
+/// Intended as a starting point for users who do not want to tune the individual parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    Fast,
+    #[default]
+    Balanced,
+    Quality,
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fast" => Ok(Preset::Fast),
+            "balanced" => Ok(Preset::Balanced),
+            "quality" => Ok(Preset::Quality),
+            _ => Err(format!(
+                "unknown preset: {}, expected one of fast, balanced or quality",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Preset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Preset::Fast => "fast",
+            Preset::Balanced => "balanced",
+            Preset::Quality => "quality",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct SPSurrogateConfig {
     ///Poles will stop being generated when the surrogate covers this fraction of the shape's area
@@ -35,3 +116,101 @@ impl SPSurrogateConfig {
         }
     }
 }
+
+/// Single parameter which determines how closely the internal geometry follows the input geometry,
+/// expressed as a fraction of the extent of each shape (the largest dimension of its bounding box).
+///
+/// It consistently drives:
+/// * **Arc tessellation**: circular arcs are replaced by segments which deviate at most `fidelity * extent` from the arc.
+///   The tessellation is conservative: items only grow, bins only shrink.
+/// * **Curve flattening**: splines (DXF), Bézier curves and elliptical arcs (SVG) are replaced by chords which deviate at most `fidelity * extent` from the curve.
+///   Unlike the tessellation of circular arcs, the chords can lie on either side of the curve.
+/// * **Polygon simplification**: the boundary of the simplified polygon stays within `fidelity * extent` of the boundary of the tessellated one,
+///   see [`PolySimplConfig::Bounded`]. Simplification is conservative as well: items are inflated, bins are deflated.
+/// * **CDE epsilon**: the collision detection engine itself is exact up to floating point rounding,
+///   so the overlap of the input geometry it can miss is the deviation of flattened curves plus rounding, see [`GeometricFidelity::cde_epsilon`].
+///
+/// # Clearance bound
+/// For two items with extents `e1` and `e2` which touch in the internal representation,
+/// the real clearance between the exported parts lies in:
+///
+/// `[-(fidelity + ε) * (e1 + e2), (2 * fidelity + ε) * (e1 + e2)]`
+///
+/// The upper bound adds up the tessellation (or flattening) and the simplification of both items.
+/// The lower bound is only negative by more than the rounding error `ε` ([`FPA::tolerance`]) for shapes with flattened curves,
+/// as these are the only preprocessing step which can shrink an item. Bins are deflated, which only widens the clearance to the bin.
+///
+/// See [`GeometricFidelity::clearance_bound`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(transparent)]
+pub struct GeometricFidelity(pub fsize);
+
+impl GeometricFidelity {
+    /// Maximum distance between a shape with extent `extent` and its internal representation, per preprocessing step
+    pub fn max_deviation(&self, extent: fsize) -> fsize {
+        self.0 * extent
+    }
+
+    /// Polygon simplification configuration corresponding to this fidelity:
+    /// the boundary of a simplified polygon stays within [`Self::max_deviation`] of the original one
+    pub fn poly_simpl_config(&self) -> PolySimplConfig {
+        PolySimplConfig::Bounded {
+            max_deviation: self.0,
+        }
+    }
+
+    /// Maximum overlap of the input geometry of two shapes with a combined extent `extent`, which the collision detection engine
+    /// accepts as collision-free when working on their internal representation at this fidelity.
+    /// The engine itself is exact up to the rounding of the coordinates ([`FPA::tolerance`]),
+    /// but flattened curves can lie up to [`Self::max_deviation`] inside the input shapes.
+    pub fn cde_epsilon(&self, extent: fsize) -> fsize {
+        self.max_deviation(extent) + FPA::tolerance() * extent
+    }
+
+    /// Lower and upper bound on the real clearance between the exported parts of two items with extents `e1` and `e2`,
+    /// which are touching in the internal representation.
+    /// A negative lower bound indicates an overlap, which can only be caused by flattened curves and rounding.
+    pub fn clearance_bound(&self, e1: fsize, e2: fsize) -> (fsize, fsize) {
+        //arcs and curves are tessellated or flattened, after which the polygons are simplified
+        let tessellation = self.max_deviation(e1 + e2);
+        let simplification = self.max_deviation(e1 + e2);
+        let rounding = FPA::tolerance() * (e1 + e2);
+        let min = -self.cde_epsilon(e1 + e2);
+        let max = tessellation + simplification + rounding;
+        (min, max)
+    }
+}
+
+impl Default for GeometricFidelity {
+    fn default() -> Self {
+        Self(1e-3)
+    }
+}
+
+/// Policy by which the width of a strip is increased, when more room is required
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "mode", content = "params")]
+pub enum StripGrowth {
+    /// The width is increased by a fixed amount
+    #[serde(rename = "additive")]
+    Additive { step: fsize },
+    /// The width is multiplied by a factor
+    #[serde(rename = "multiplicative")]
+    Multiplicative { factor: fsize },
+}
+
+impl StripGrowth {
+    /// The width of a strip of width `width` after growing once
+    pub fn grow(&self, width: fsize) -> fsize {
+        match self {
+            StripGrowth::Additive { step } => width + step,
+            StripGrowth::Multiplicative { factor } => width * factor,
+        }
+    }
+}
+
+impl Default for StripGrowth {
+    fn default() -> Self {
+        StripGrowth::Multiplicative { factor: 1.1 }
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::collision_detection::cd_engine::CDEngine;
+use crate::fsize;
+use crate::geometry::primitives::aa_rectangle::AARectangle;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+
+/// Number of SVGs written by this process, to give every file a unique name
+static SVG_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes an SVG of all hazards in the CDE to a temporary file and returns its path.
+/// Static hazards are drawn as outlines, dynamic hazards are filled and the `offending` shape is highlighted in red.
+/// Intended to annotate failed debug assertions, see [`debug_assert_with_svg`](crate::util::assertions::debug_assert_with_svg).
+pub fn write_cde_svg(cde: &CDEngine, offending: Option<&SimplePolygon>) -> io::Result<PathBuf> {
+    let static_shapes = cde.static_hazards().iter().map(|h| h.shape.as_ref());
+    let dynamic_shapes = cde.dynamic_hazards().iter().map(|h| h.shape.as_ref());
+    write_svg(cde.bbox(), static_shapes, dynamic_shapes, offending)
+}
+
+/// Writes an SVG of the shapes to a temporary file and returns its path, see [`write_cde_svg`].
+pub fn write_svg<'a>(
+    bbox: &AARectangle,
+    static_shapes: impl Iterator<Item = &'a SimplePolygon>,
+    dynamic_shapes: impl Iterator<Item = &'a SimplePolygon>,
+    offending: Option<&SimplePolygon>,
+) -> io::Result<PathBuf> {
+    let stroke_width = fsize::max(bbox.width(), bbox.height()) * 0.002;
+
+    //flip the y-axis, so the layout is drawn with the origin in the bottom-left corner
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n<g transform=\"scale(1 -1)\">\n",
+        bbox.x_min - stroke_width,
+        -bbox.y_max - stroke_width,
+        bbox.width() + 2.0 * stroke_width,
+        bbox.height() + 2.0 * stroke_width
+    );
+    for shape in static_shapes {
+        push_path(&mut svg, shape, "none", "black", stroke_width);
+    }
+    for shape in dynamic_shapes {
+        push_path(&mut svg, shape, "lightgrey", "black", stroke_width);
+    }
+    if let Some(shape) = offending {
+        push_path(&mut svg, shape, "red", "darkred", stroke_width);
+    }
+    svg.push_str("</g>\n</svg>\n");
+
+    let file_name = format!(
+        "jagua_rs_{}_{}.svg",
+        std::process::id(),
+        SVG_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let path = std::env::temp_dir().join(file_name);
+    fs::write(&path, svg)?;
+    Ok(path)
+}
+
+fn push_path(
+    svg: &mut String,
+    shape: &SimplePolygon,
+    fill: &str,
+    stroke: &str,
+    stroke_width: fsize,
+) {
+    let data = shape
+        .points
+        .iter()
+        .enumerate()
+        .fold(String::new(), |mut data, (i, p)| {
+            let command = if i == 0 { 'M' } else { 'L' };
+            write!(data, "{} {} {} ", command, p.0, p.1).unwrap();
+            data
+        });
+    writeln!(
+        svg,
+        "<path d=\"{}Z\" fill=\"{}\" fill-opacity=\"0.5\" stroke=\"{}\" stroke-width=\"{}\"/>",
+        data, fill, stroke, stroke_width
+    )
+    .unwrap();
+}
@@ -0,0 +1,91 @@
+use slotmap::SlotMap;
+
+use crate::entities::bin::Bin;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::placed_item::{PItemKey, PlacedItem};
+use crate::fsize;
+use crate::geometry::convex_hull::convex_hull_from_shapes;
+use crate::geometry::geo_traits::Shape;
+use crate::geometry::primitives::aa_rectangle::AARectangle;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+
+/// Usage of the bin computed with the original areas of the items and the bin, before simplification.
+/// See [`Item::original_area`](crate::entities::item::Item::original_area).
+pub fn original_usage(
+    bin: &Bin,
+    placed_items: &SlotMap<PItemKey, PlacedItem>,
+    instance: &dyn InstanceGeneric,
+) -> fsize {
+    original_item_area(placed_items, instance) / bin.original_area
+}
+
+/// Sum of the original areas of the placed items, before simplification
+pub fn original_item_area(
+    placed_items: &SlotMap<PItemKey, PlacedItem>,
+    instance: &dyn InstanceGeneric,
+) -> fsize {
+    placed_items
+        .values()
+        .map(|pi| instance.item(pi.item_id).original_area)
+        .sum()
+}
+
+/// Fraction of the bounding box of all placed items which is covered by the items.
+/// Returns `None` if no items are placed.
+pub fn bbox_usage(placed_items: &SlotMap<PItemKey, PlacedItem>) -> Option<fsize> {
+    let bbox = placed_items
+        .values()
+        .map(|pi| pi.shape.bbox())
+        .reduce(|a, b| AARectangle::bounding_rectangle(&a, &b))?;
+    Some(item_area(placed_items) / bbox.area())
+}
+
+/// Fraction of the convex hull of all placed items which is covered by the items.
+/// Returns `None` if no items are placed.
+pub fn convex_hull_usage(placed_items: &SlotMap<PItemKey, PlacedItem>) -> Option<fsize> {
+    if placed_items.is_empty() {
+        return None;
+    }
+    let hull = convex_hull_from_shapes(placed_items.values().map(|pi| pi.shape.as_ref()));
+    let hull_area = SimplePolygon::calculate_area(&hull).abs();
+    Some(item_area(placed_items) / hull_area)
+}
+
+/// Skyline of a strip: the frontier of the placed items, seen from the back of the strip.
+/// For `resolution` horizontal lines, spread evenly over the height of the bin,
+/// returns the height of the line and the largest x-coordinate occupied by an item on it.
+/// Both are relative to the left-bottom corner of the bin, lines without any item have an x-coordinate of zero.
+pub fn skyline(
+    bin: &Bin,
+    placed_items: &SlotMap<PItemKey, PlacedItem>,
+    resolution: usize,
+) -> Vec<(fsize, fsize)> {
+    let bbox = bin.bbox();
+    (0..resolution)
+        .map(|i| {
+            //lines are placed in the middle of equally sized bands
+            let y = bbox.y_min + bbox.height() * (i as fsize + 0.5) / resolution as fsize;
+            let x_max = placed_items
+                .values()
+                .filter(|pi| pi.shape.bbox().y_min <= y && y <= pi.shape.bbox().y_max)
+                .flat_map(|pi| pi.shape.edge_iter())
+                .filter_map(|e| {
+                    let (y_min, y_max) = (e.start.1.min(e.end.1), e.start.1.max(e.end.1));
+                    match (y_min..=y_max).contains(&y) {
+                        false => None,
+                        true if y_min == y_max => Some(e.start.0.max(e.end.0)),
+                        true => {
+                            let t = (y - e.start.1) / (e.end.1 - e.start.1);
+                            Some(e.start.0 + t * (e.end.0 - e.start.0))
+                        }
+                    }
+                })
+                .fold(bbox.x_min, fsize::max);
+            (y - bbox.y_min, x_max - bbox.x_min)
+        })
+        .collect()
+}
+
+fn item_area(placed_items: &SlotMap<PItemKey, PlacedItem>) -> fsize {
+    placed_items.values().map(|pi| pi.shape.area()).sum()
+}
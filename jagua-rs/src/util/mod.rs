@@ -3,14 +3,45 @@ use crate::entities::layout::Layout;
 /// Set of functions used throughout assure the correctness of the library.
 pub mod assertions;
 
+/// Verification of the clearances between placed items
+pub mod clearance;
+
 /// Configuration options for the library
 pub mod config;
 
+/// Dumps the state of the CDE to SVG, to annotate failed debug assertions
+pub mod debug_svg;
+
 pub mod fpa;
 
+/// Quality metrics of layouts beyond area usage
+pub mod layout_metrics;
+
+/// Evaluation of solutions on multiple weighted objectives
+pub mod objectives;
+
+/// Functions to remove degenerate vertices from polygons in preprocessing
+pub mod polygon_cleanup;
+
+/// Functions to represent polygons with holes as simple polygons
+pub mod polygon_holes;
+
 /// Functions to simplify polygons in preprocessing
 pub mod polygon_simplification;
 
+/// Fixtures shared by the unit tests
+#[cfg(all(test, feature = "io"))]
+pub(crate) mod test_fixtures;
+
+/// Length units of instances
+pub mod units;
+
+/// Feasibility validation of solutions, e.g. of layouts produced by other tools
+pub mod validation;
+
+/// Detection of thin webs in the skeleton left over after cutting
+pub mod web_width;
+
 ///Prints code to recreate a layout. Intended for debugging purposes.
 pub fn print_layout(layout: &Layout) {
     println!(
@@ -27,8 +58,8 @@ pub fn print_layout(layout: &Layout) {
         };
 
         println!(
-            "layout.place_item(instance.item({}), {});",
-            pi.item_id, transformation_str
+            "layout.place_item(instance.item({}), {:?}, {});",
+            pi.item_id, pi.copy, transformation_str
         );
     }
 }
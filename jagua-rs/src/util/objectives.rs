@@ -0,0 +1,209 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::instances::instance::Instance;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::solution::Solution;
+use crate::fsize;
+use crate::geometry::geo_traits::Shape;
+
+/// Criterion on which a solution can be evaluated
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Objective {
+    /// Usage of the containers, computed with the areas as defined in the input (maximized)
+    Usage,
+    /// Fraction of the demanded item area which is placed (maximized)
+    Completeness,
+    /// Total length of the contours of the placed items, measured along the arcs if defined (minimized).
+    /// An upper bound on the cutting path, as edges shared between items are counted twice
+    CutLength,
+    /// Total value of the containers used (minimized)
+    BinCost,
+    /// Total shortfall cost of the items which are not placed (minimized)
+    ShortfallCost,
+}
+
+impl Objective {
+    pub fn is_maximized(&self) -> bool {
+        match self {
+            Objective::Usage | Objective::Completeness => true,
+            Objective::CutLength | Objective::BinCost | Objective::ShortfallCost => false,
+        }
+    }
+
+    pub fn evaluate(&self, solution: &Solution, instance: &Instance) -> fsize {
+        match self {
+            Objective::Usage => solution.original_usage(instance),
+            Objective::Completeness => solution.completeness(instance),
+            Objective::CutLength => solution
+                .placed_item_qtys
+                .iter()
+                .enumerate()
+                .map(|(i, qty)| {
+                    let item = instance.item(i);
+                    let perimeter = match &item.arc_shape {
+                        Some(arc_shape) => arc_shape.perimeter(),
+                        None => item.shape.edge_iter().map(|e| e.diameter()).sum(),
+                    };
+                    perimeter * *qty as fsize
+                })
+                .sum(),
+            Objective::BinCost => solution
+                .layout_snapshots
+                .iter()
+                .map(|sl| sl.bin.value as fsize)
+                .sum(),
+            Objective::ShortfallCost => solution.shortfall_cost(instance) as fsize,
+        }
+    }
+}
+
+/// An [`Objective`] and its weight in the score of a solution
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WeightedObjective {
+    pub objective: Objective,
+    pub weight: fsize,
+}
+
+/// Weight of every objective in the score of a solution.
+/// Objectives with a weight of zero are not evaluated.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct ObjectiveWeights {
+    pub usage: fsize,
+    pub completeness: fsize,
+    pub cut_length: fsize,
+    pub bin_cost: fsize,
+    pub shortfall_cost: fsize,
+}
+
+impl ObjectiveWeights {
+    pub fn weighted_objectives(&self) -> Vec<WeightedObjective> {
+        [
+            (Objective::Usage, self.usage),
+            (Objective::Completeness, self.completeness),
+            (Objective::CutLength, self.cut_length),
+            (Objective::BinCost, self.bin_cost),
+            (Objective::ShortfallCost, self.shortfall_cost),
+        ]
+        .into_iter()
+        .filter(|(_, weight)| *weight != 0.0)
+        .map(|(objective, weight)| WeightedObjective { objective, weight })
+        .collect()
+    }
+}
+
+/// Values of a solution on a set of weighted objectives
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectiveVector {
+    pub objectives: Vec<WeightedObjective>,
+    pub values: Vec<fsize>,
+}
+
+impl ObjectiveVector {
+    pub fn evaluate(
+        solution: &Solution,
+        instance: &Instance,
+        objectives: &[WeightedObjective],
+    ) -> Self {
+        let values = objectives
+            .iter()
+            .map(|wo| wo.objective.evaluate(solution, instance))
+            .collect_vec();
+        Self {
+            objectives: objectives.to_vec(),
+            values,
+        }
+    }
+
+    /// Weighted sum of the values, where minimized objectives contribute negatively. Higher is better.
+    /// The weights should account for the different scales of the objectives.
+    pub fn score(&self) -> fsize {
+        self.objectives
+            .iter()
+            .zip(self.values.iter())
+            .map(|(wo, v)| match wo.objective.is_maximized() {
+                true => wo.weight * v,
+                false => -wo.weight * v,
+            })
+            .sum()
+    }
+
+    /// Whether `self` is at least as good as `other` on all objectives, and strictly better on at least one
+    pub fn dominates(&self, other: &ObjectiveVector) -> bool {
+        debug_assert!(self.objectives == other.objectives);
+        let (mut strictly_better, mut worse) = (false, false);
+        for ((wo, s), o) in self
+            .objectives
+            .iter()
+            .zip(self.values.iter())
+            .zip(other.values.iter())
+        {
+            let (s, o) = match wo.objective.is_maximized() {
+                true => (*s, *o),
+                false => (-*s, -*o),
+            };
+            strictly_better |= s > o;
+            worse |= s < o;
+        }
+        strictly_better && !worse
+    }
+}
+
+/// Set of mutually non-dominated entries, for optimizers which generate multiple solutions (for example with restarts)
+/// and want to keep every trade-off between the objectives rather than a single best.
+#[derive(Clone, Debug)]
+pub struct ParetoSet<T> {
+    entries: Vec<(ObjectiveVector, T)>,
+}
+
+impl<T> ParetoSet<T> {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Inserts the entry if no other entry dominates it or has the same values, removing all entries it dominates.
+    /// Returns whether the entry was inserted.
+    pub fn insert(&mut self, vector: ObjectiveVector, value: T) -> bool {
+        if self
+            .entries
+            .iter()
+            .any(|(v, _)| v.dominates(&vector) || v.values == vector.values)
+        {
+            return false;
+        }
+        self.entries.retain(|(v, _)| !vector.dominates(v));
+        self.entries.push((vector, value));
+        true
+    }
+
+    /// Entry with the highest score, the first one inserted in case of a tie
+    pub fn best(&self) -> Option<&(ObjectiveVector, T)> {
+        self.entries
+            .iter()
+            .rev()
+            .max_by(|(a, _), (b, _)| a.score().total_cmp(&b.score()))
+    }
+
+    pub fn entries(&self) -> &[(ObjectiveVector, T)] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<(ObjectiveVector, T)> {
+        self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T> Default for ParetoSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,107 @@
+use crate::fsize;
+use crate::geometry::primitives::point::Point;
+use crate::util::fpa::FPA;
+
+/// Edges shorter than this fraction of the diagonal of the bounding box of the polygon are considered degenerate
+pub const MIN_EDGE_LENGTH_FRACTION: fsize = 1e-6;
+
+/// Number of vertices removed by [`clean_points`], per cause
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CleanupStats {
+    /// Vertices identical to the previous vertex
+    pub n_duplicates: usize,
+    /// Vertices closer to the previous vertex than the minimum edge length
+    pub n_short_edges: usize,
+    /// Vertices in the middle of a straight run of edges (or at the tip of a zero-width spike)
+    pub n_collinear: usize,
+}
+
+impl CleanupStats {
+    pub fn total(&self) -> usize {
+        self.n_duplicates + self.n_short_edges + self.n_collinear
+    }
+}
+
+/// Removes degenerate vertices from the contour of a polygon:
+/// consecutive duplicates, vertices which form edges shorter than `min_edge_length` and vertices in collinear runs.
+pub fn clean_points(points: Vec<Point>, min_edge_length: fsize) -> (Vec<Point>, CleanupStats) {
+    let mut stats = CleanupStats::default();
+
+    //remove consecutive duplicates (including the closing vertex)
+    let mut cleaned: Vec<Point> = Vec::with_capacity(points.len());
+    for p in points {
+        match cleaned.last() {
+            Some(last) if *last == p => stats.n_duplicates += 1,
+            _ => cleaned.push(p),
+        }
+    }
+    while cleaned.len() > 1 && cleaned.first() == cleaned.last() {
+        cleaned.pop();
+        stats.n_duplicates += 1;
+    }
+
+    //merge vertices which form an edge shorter than the minimum edge length
+    let mut points = cleaned;
+    let mut cleaned: Vec<Point> = Vec::with_capacity(points.len());
+    for p in points.drain(..) {
+        match cleaned.last() {
+            Some(last) if last.distance(p) < min_edge_length => stats.n_short_edges += 1,
+            _ => cleaned.push(p),
+        }
+    }
+    while cleaned.len() > 3 && cleaned[0].distance(cleaned[cleaned.len() - 1]) < min_edge_length {
+        cleaned.pop();
+        stats.n_short_edges += 1;
+    }
+
+    //remove collinear vertices, until none remain
+    let mut i = 0;
+    let mut n_checked_in_a_row = 0;
+    while cleaned.len() > 3 && n_checked_in_a_row < cleaned.len() {
+        let n = cleaned.len();
+        let (prev, curr, next) = (
+            cleaned[(i + n - 1) % n],
+            cleaned[i % n],
+            cleaned[(i + 1) % n],
+        );
+        if is_collinear(prev, curr, next) {
+            cleaned.remove(i % n);
+            stats.n_collinear += 1;
+            n_checked_in_a_row = 0;
+            //step back to recheck the previous vertex, which has a new neighbor
+            i = (i % n + n - 2) % (n - 1);
+        } else {
+            i = (i + 1) % n;
+            n_checked_in_a_row += 1;
+        }
+    }
+
+    (cleaned, stats)
+}
+
+/// Returns the minimum edge length to use for [`clean_points`] for a set of points
+pub fn min_edge_length(points: &[Point]) -> fsize {
+    let (x_min, y_min, x_max, y_max) = points.iter().fold(
+        (fsize::MAX, fsize::MAX, fsize::MIN, fsize::MIN),
+        |(x_min, y_min, x_max, y_max), p| {
+            (
+                x_min.min(p.0),
+                y_min.min(p.1),
+                x_max.max(p.0),
+                y_max.max(p.1),
+            )
+        },
+    );
+    let diagonal = Point(x_min, y_min).distance(Point(x_max, y_max));
+    diagonal * MIN_EDGE_LENGTH_FRACTION
+}
+
+fn is_collinear(p1: Point, p2: Point, p3: Point) -> bool {
+    let p1p2 = (p2.0 - p1.0, p2.1 - p1.1);
+    let p2p3 = (p3.0 - p2.0, p3.1 - p2.1);
+    let cross_prod = p1p2.0 * p2p3.1 - p1p2.1 * p2p3.0;
+    let lengths =
+        (p1p2.0.powi(2) + p1p2.1.powi(2)).sqrt() * (p2p3.0.powi(2) + p2p3.1.powi(2)).sqrt();
+    //the cross product relative to the edge lengths is the sine of the angle between the edges
+    cross_prod.abs() <= FPA::tolerance() * lengths
+}
@@ -0,0 +1,246 @@
+use itertools::Itertools;
+use ordered_float::NotNan;
+
+use crate::fsize;
+use crate::geometry::geo_traits::{CollidesWith, Shape};
+use crate::geometry::primitives::edge::Edge;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+
+/// Width of the channels connecting holes to the outer contour, as a fraction of the diagonal of the bounding box of the polygon
+pub const CHANNEL_WIDTH_FRACTION: fsize = 1e-5;
+
+/// Represents a polygon with holes as a single contour, by cutting a narrow channel from the outer contour to every hole
+/// (a "keyhole" polygon).
+///
+/// The interiors of the holes become part of the exterior of the resulting contour, meaning other items can be placed inside them
+/// (part-in-part), while the rest of the polygon is treated exactly the same as any other simple polygon.
+/// The channels are only `CHANNEL_WIDTH_FRACTION` wide, so no item can be placed inside them.
+/// Any edge crossing a channel intersects both of its walls and is therefore still detected as a collision.
+/// Fails if a hole does not lie strictly inside the outer contour or overlaps another hole,
+/// as the resulting contour would intersect itself.
+pub fn merge_holes(outer: Vec<Point>, holes: Vec<Vec<Point>>) -> Result<Vec<Point>, String> {
+    check_holes(&outer, &holes)?;
+
+    let bbox = SimplePolygon::generate_bounding_box(&outer);
+    let channel_width = Point(bbox.x_min, bbox.y_min).distance(Point(bbox.x_max, bbox.y_max))
+        * CHANNEL_WIDTH_FRACTION;
+
+    //the outer contour should be counterclockwise, the holes clockwise
+    let mut points = oriented(outer, true);
+    let mut holes = holes.into_iter().map(|h| oriented(h, false)).collect_vec();
+
+    while !holes.is_empty() {
+        let hole = holes.remove(0);
+        let (i, j) = find_channel(&points, &hole, &holes)
+            .ok_or("no channel could be cut from the outer contour to a hole")?;
+
+        points = cut_channel(&points, &hole, (i, j), channel_width);
+    }
+    Ok(points)
+}
+
+/// Checks that every hole lies inside the outer contour without touching it, and that no two holes touch or contain one another
+fn check_holes(outer: &[Point], holes: &[Vec<Point>]) -> Result<(), String> {
+    let contours_intersect = |a: &[Point], b: &[Point]| {
+        contour_edges(a)
+            .cartesian_product(contour_edges(b).collect_vec())
+            .any(|(e1, e2)| e1.collides_with(&e2))
+    };
+    if let Some(i) = holes.iter().position(|hole| hole.len() < 3) {
+        return Err(format!("hole {} has fewer than 3 points", i));
+    }
+    for (i, hole) in holes.iter().enumerate() {
+        if contours_intersect(outer, hole) || !encloses(outer, hole[0]) {
+            return Err(format!("hole {} does not lie inside the outer contour", i));
+        }
+        for (j, other) in holes.iter().enumerate().skip(i + 1) {
+            if contours_intersect(hole, other)
+                || encloses(hole, other[0])
+                || encloses(other, hole[0])
+            {
+                return Err(format!("holes {} and {} overlap", i, j));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Joins two disjoint polygons into a single contour, by cutting a narrow channel between the closest pair of vertices
+/// which can see each other. The resulting contour encloses both polygons and the channel,
+/// which is only `CHANNEL_WIDTH_FRACTION` (of the diagonal of their combined bounding box) wide.
+pub fn bridge_polygons(a: Vec<Point>, b: Vec<Point>) -> Vec<Point> {
+    let bbox =
+        SimplePolygon::generate_bounding_box(&a.iter().chain(b.iter()).copied().collect_vec());
+    let channel_width = Point(bbox.x_min, bbox.y_min).distance(Point(bbox.x_max, bbox.y_max))
+        * CHANNEL_WIDTH_FRACTION;
+
+    //both contours should be counterclockwise
+    let (a, b) = (oriented(a, true), oriented(b, true));
+    let (i, j) = find_channel(&a, &b, &[]).unwrap_or_else(|| {
+        panic!(
+            "no channel could be cut between polygons, do they overlap? {:?}, {:?}",
+            a, b
+        )
+    });
+    cut_channel(&a, &b, (i, j), channel_width)
+}
+
+/// Joins the disjoint parts of a multi-part shape into a single contour, by bridging them one by one to the largest part, as [`bridge_polygons`] does.
+/// No channel crosses a part which is not yet bridged.
+/// The space between the parts remains outside the contour, so the parts behave as a rigid compound.
+/// Parts which lie entirely inside another part (for example one of which the holes were filled) are dropped, as they are already covered.
+/// Fails if the contours of two parts intersect.
+pub fn bridge_parts(parts: Vec<SimplePolygon>) -> Result<Vec<Point>, String> {
+    let parts = parts
+        .into_iter()
+        .sorted_by(|a, b| b.area().partial_cmp(&a.area()).unwrap())
+        .collect_vec();
+
+    let mut disjoint: Vec<&SimplePolygon> = vec![];
+    for part in &parts {
+        let mut covered = false;
+        for other in &disjoint {
+            let intersecting = contour_edges(&part.points)
+                .cartesian_product(contour_edges(&other.points).collect_vec())
+                .any(|(e1, e2)| e1.collides_with(&e2));
+            if intersecting || part.collides_with(&other.points[0]) {
+                return Err("the contours of the parts intersect".into());
+            }
+            covered |= other.collides_with(&part.points[0]);
+        }
+        if !covered {
+            disjoint.push(part);
+        }
+    }
+
+    let all_points = disjoint
+        .iter()
+        .flat_map(|p| p.points.iter())
+        .copied()
+        .collect_vec();
+    let bbox = SimplePolygon::generate_bounding_box(&all_points);
+    let channel_width = Point(bbox.x_min, bbox.y_min).distance(Point(bbox.x_max, bbox.y_max))
+        * CHANNEL_WIDTH_FRACTION;
+
+    //all contours should be counterclockwise
+    let mut remaining = disjoint
+        .iter()
+        .map(|part| oriented(part.points.clone(), true))
+        .collect_vec();
+    let mut points = remaining.remove(0);
+    while !remaining.is_empty() {
+        let (k, channel) = (0..remaining.len())
+            .find_map(|k| {
+                let others = remaining
+                    .iter()
+                    .enumerate()
+                    .filter(|(l, _)| *l != k)
+                    .map(|(_, p)| p.clone())
+                    .collect_vec();
+                find_channel(&points, &remaining[k], &others).map(|c| (k, c))
+            })
+            .ok_or("no channel could be cut between the parts")?;
+        let part = remaining.remove(k);
+        points = cut_channel(&points, &part, channel, channel_width);
+    }
+    Ok(points)
+}
+
+/// Indices of the contours which are holes of the outline: those lying inside it, except the ones lying inside another such contour
+/// (islands within a hole). The contours are assumed not to intersect each other or the outline.
+pub fn holes_of(outline: &[Point], contours: &[Vec<Point>]) -> Vec<usize> {
+    let inside = (0..contours.len())
+        .filter(|&i| !contours[i].is_empty() && encloses(outline, contours[i][0]))
+        .collect_vec();
+    inside
+        .iter()
+        .copied()
+        .filter(|&i| {
+            !inside
+                .iter()
+                .any(|&j| j != i && encloses(&contours[j], contours[i][0]))
+        })
+        .collect()
+}
+
+/// Whether the point lies inside the contour, by the even-odd rule
+fn encloses(contour: &[Point], Point(x, y): Point) -> bool {
+    contour_edges(contour)
+        .filter(|e| (e.start.1 > y) != (e.end.1 > y))
+        .filter(|e| {
+            let x_crossing =
+                e.start.0 + (y - e.start.1) / (e.end.1 - e.start.1) * (e.end.0 - e.start.0);
+            x < x_crossing
+        })
+        .count()
+        % 2
+        == 1
+}
+
+/// Walks the contour up to vertex `i`, goes around the other contour starting at vertex `j`, and returns right next to where it left
+fn cut_channel(
+    points: &[Point],
+    other: &[Point],
+    (i, j): (usize, usize),
+    channel_width: fsize,
+) -> Vec<Point> {
+    let (p_i, p_next) = (points[i], points[(i + 1) % points.len()]);
+    let (o_j, o_prev) = (other[j], other[(j + other.len() - 1) % other.len()]);
+
+    let mut merged = points[..=i].to_vec();
+    merged.extend(other[j..].iter().chain(other[..j].iter()));
+    merged.push(move_towards(o_j, o_prev, channel_width));
+    merged.push(move_towards(p_i, p_next, channel_width));
+    merged.extend(points[i + 1..].iter());
+    merged
+}
+
+/// Finds the pair of a vertex of the contour and a vertex of the hole which are closest to each other
+/// and can be connected without intersecting any edge of the contour or the holes
+fn find_channel(
+    points: &[Point],
+    hole: &[Point],
+    other_holes: &[Vec<Point>],
+) -> Option<(usize, usize)> {
+    let edges = contour_edges(points)
+        .chain(contour_edges(hole))
+        .chain(other_holes.iter().flat_map(|h| contour_edges(h)))
+        .collect_vec();
+
+    (0..points.len())
+        .cartesian_product(0..hole.len())
+        .sorted_by_key(|(i, j)| NotNan::new(points[*i].sq_distance(hole[*j])).unwrap())
+        .find(|(i, j)| {
+            let channel = Edge::new(points[*i], hole[*j]);
+            edges
+                .iter()
+                .filter(|e| {
+                    ![e.start, e.end]
+                        .iter()
+                        .any(|p| *p == channel.start || *p == channel.end)
+                })
+                .all(|e| !channel.collides_with(e))
+        })
+}
+
+fn contour_edges(points: &[Point]) -> impl Iterator<Item = Edge> + '_ {
+    (0..points.len()).map(move |i| Edge::new(points[i], points[(i + 1) % points.len()]))
+}
+
+fn oriented(mut points: Vec<Point>, counterclockwise: bool) -> Vec<Point> {
+    if (SimplePolygon::calculate_area(&points) > 0.0) != counterclockwise {
+        points.reverse();
+    }
+    points
+}
+
+/// Moves `p` towards `target` by `d`, but never more than half of the distance between them
+fn move_towards(p: Point, target: Point, d: fsize) -> Point {
+    let length = p.distance(target);
+    let fraction = fsize::min(d / length, 0.5);
+    Point(
+        p.0 + (target.0 - p.0) * fraction,
+        p.1 + (target.1 - p.1) * fraction,
+    )
+}
@@ -6,7 +6,7 @@ use ordered_float::NotNan;
 use serde::{Deserialize, Serialize};
 
 use crate::fsize;
-use crate::geometry::geo_traits::{CollidesWith, Shape};
+use crate::geometry::geo_traits::{CollidesWith, DistanceFrom, Shape};
 use crate::geometry::primitives::edge::Edge;
 use crate::geometry::primitives::point::Point;
 use crate::geometry::primitives::simple_polygon::SimplePolygon;
@@ -21,6 +21,12 @@ pub enum PolySimplConfig {
         /// max deviation from the original polygon area as a fraction of the original area
         tolerance: fsize,
     },
+    #[serde(rename = "bounded")]
+    Bounded {
+        /// max distance between the simplified and the original polygon as a fraction of the extent of the original polygon
+        /// (the largest dimension of its bounding box)
+        max_deviation: fsize,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -93,9 +99,34 @@ pub fn simplify_shape(
     mode: PolySimplMode,
     max_area_delta: fsize,
 ) -> SimplePolygon {
+    simplify(shape, mode, SimplLimit::AreaDelta(max_area_delta))
+}
+
+/// Simplifies a shape (removing vertices) strictly inflating or deflating based on the mode.
+/// The number of edges is reduced by one at a time, until either the Hausdorff distance between the boundaries of the
+/// simplified and the original shape could exceed `max_deviation` or the number of edges would become less than 4.
+pub fn simplify_shape_bounded(
+    shape: &SimplePolygon,
+    mode: PolySimplMode,
+    max_deviation: fsize,
+) -> SimplePolygon {
+    simplify(shape, mode, SimplLimit::Deviation(max_deviation))
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SimplLimit {
+    /// max change in area as a fraction of the original area
+    AreaDelta(fsize),
+    /// max (absolute) distance between the boundaries of the simplified and the original shape
+    Deviation(fsize),
+}
+
+fn simplify(shape: &SimplePolygon, mode: PolySimplMode, limit: SimplLimit) -> SimplePolygon {
     let original_area = shape.area();
 
     let mut ref_points = shape.points.clone();
+    //upper bound on the distance between each edge (starting at the point with the same index) and the original boundary
+    let mut ref_deviations = vec![0.0; ref_points.len()];
 
     for _ in 0..shape.number_of_points() {
         let n_points = ref_points.len() as isize;
@@ -150,20 +181,36 @@ pub fn simplify_shape(
             })
             .find(|c| candidate_is_valid(&ref_points, c));
 
-        //if it is within the area change constraints, execute the candidate
+        //if it is within the constraints, execute the candidate
         if let Some(best_candidate) = best_candidate {
             let new_shape = execute_candidate(&ref_points, best_candidate);
-            let new_shape_area = SimplePolygon::calculate_area(&new_shape);
-            let area_delta = (new_shape_area - original_area).abs() / original_area;
-            if area_delta <= max_area_delta {
-                debug!(
-                    "Simplified {:?} causing {:.2}% area change",
-                    best_candidate,
-                    area_delta * 100.0
-                );
+            let new_deviations =
+                execute_candidate_deviations(&ref_points, &ref_deviations, best_candidate);
+            let within_limit = match limit {
+                SimplLimit::AreaDelta(max_area_delta) => {
+                    let new_shape_area = SimplePolygon::calculate_area(&new_shape);
+                    let area_delta = (new_shape_area - original_area).abs() / original_area;
+                    debug!(
+                        "Simplifying {:?} causes {:.2}% area change",
+                        best_candidate,
+                        area_delta * 100.0
+                    );
+                    area_delta <= max_area_delta
+                }
+                SimplLimit::Deviation(max_deviation) => {
+                    let deviation = new_deviations.iter().cloned().fold(0.0, fsize::max);
+                    debug!(
+                        "Simplifying {:?} causes a deviation of at most {:.3}",
+                        best_candidate, deviation
+                    );
+                    deviation <= max_deviation
+                }
+            };
+            if within_limit {
                 ref_points = new_shape;
+                ref_deviations = new_deviations;
             } else {
-                break; //area change too significant
+                break; //change too significant
             }
         } else {
             break; //no candidate found
@@ -181,7 +228,7 @@ pub fn simplify_shape(
             (simpl_shape.area() - shape.area()) / shape.area() * 100.0
         );
     } else {
-        info!("[PS] no simplification possible within area change constraints");
+        info!("[PS] no simplification possible within the constraints");
     }
 
     simpl_shape
@@ -280,6 +327,59 @@ fn execute_candidate(shape: &[Point], candidate: &Candidate) -> Vec<Point> {
     points
 }
 
+/// Deviations of the edges after executing the candidate, mirroring [`execute_candidate`].
+/// The edges replacing a chain of edges inherit the largest deviation of the chain, increased by the distance between the chain and its replacement.
+fn execute_candidate_deviations(
+    shape: &[Point],
+    deviations: &[fsize],
+    candidate: &Candidate,
+) -> Vec<fsize> {
+    let n = shape.len();
+    let mut deviations = deviations.to_vec();
+    match candidate {
+        Candidate::Collinear(c) | Candidate::Concave(c) => {
+            //edges (prev -> c.1) and (c.1 -> next) are replaced by (prev -> next)
+            let prev = (c.1 + n - 1) % n;
+            let step = chain_deviation(shape[c.0], shape[c.1], shape[c.2]);
+            deviations[prev] = deviations[prev].max(deviations[c.1]) + step;
+            deviations.remove(c.1);
+        }
+        Candidate::ConvexConvex(c1, c2) => {
+            //the edge between c1.1 and c2.1 and the two edges around it are replaced by two edges through the replacing vertex
+            let replacing_vertex = replacing_vertex_convex_convex_candidate(shape, (*c1, *c2))
+                .expect("invalid candidate cannot be executed");
+            let first = match (c1.1 + 1) % n == c2.1 {
+                true => c1.1,
+                false => c2.1,
+            };
+            let prev = (first + n - 1) % n;
+            let step = chain_deviation(shape[c1.1], replacing_vertex, shape[c2.1]);
+            let deviation = deviations[prev].max(deviations[c1.1]).max(deviations[c2.1]) + step;
+            deviations[prev] = deviation;
+            deviations[c2.1] = deviation;
+            deviations.remove(c1.1);
+        }
+    }
+    deviations
+}
+
+/// Upper bound on the Hausdorff distance between the chain `a -> b -> c` and the edge `a -> c`.
+/// Every point of the chain lies within `d(b, ac)` of the edge.
+/// Every point of the edge lies within `d(c, ab)` of edge `ab` and within `d(a, bc)` of edge `bc`, as the distance to an edge is convex.
+fn chain_deviation(a: Point, b: Point, c: Point) -> fsize {
+    let chain_to_edge = segment_distance(b, (a, c));
+    let edge_to_chain = fsize::min(segment_distance(c, (a, b)), segment_distance(a, (b, c)));
+    fsize::max(chain_to_edge, edge_to_chain)
+}
+
+/// Distance between a point and a segment, which may be degenerate
+fn segment_distance(p: Point, (start, end): (Point, Point)) -> fsize {
+    match start == end {
+        true => p.distance(start),
+        false => Edge::new(start, end).distance(&p),
+    }
+}
+
 fn replacing_vertex_convex_convex_candidate(
     shape: &[Point],
     (c1, c2): (Corner, Corner),
@@ -0,0 +1,114 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+use rand::prelude::{SliceRandom, SmallRng};
+use rand::Rng;
+
+use crate::collision_detection::hazard_filter;
+use crate::entities::instances::instance::Instance;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::item::Item;
+use crate::entities::layout::Layout;
+use crate::entities::placing_option::PlacingOption;
+use crate::entities::problems::bin_packing::BPProblem;
+use crate::entities::problems::problem::Problem;
+use crate::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+use crate::entities::problems::strip_packing::SPProblem;
+use crate::geometry::d_transformation::DTransformation;
+use crate::geometry::geo_enums::AllowedRotation;
+use crate::io::parser::Parser;
+use crate::util::config::{CDEConfig, GeometricFidelity, Preset};
+use crate::util::polygon_simplification::PolySimplConfig;
+use crate::PI;
+
+/// Number of random transformations tried per item and layout in [`random_problem`]
+const N_ATTEMPTS: usize = 200;
+
+pub fn parser() -> Parser {
+    Parser::new(
+        PolySimplConfig::Enabled { tolerance: 0.001 },
+        CDEConfig::preset(Preset::Balanced),
+        true,
+        PathBuf::new(),
+        GeometricFidelity::default(),
+    )
+}
+
+/// Parses an instance from the `assets` folder of the repository
+pub fn parse_asset(file_name: &str) -> Instance {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../assets")
+        .join(file_name);
+    parser().parse_reader(File::open(path).unwrap()).unwrap()
+}
+
+/// Problem in which every item is placed at the first of a number of random transformations which does not collide,
+/// in the first layout where one is found. Items for which none is found remain unplaced.
+pub fn random_problem(instance: &Instance, rng: &mut SmallRng) -> Problem {
+    let mut problem: Problem = match instance {
+        Instance::BP(bpi) => BPProblem::new(bpi.clone()).into(),
+        Instance::SP(spi) => {
+            let strip_width = instance.item_area() * 2.0 / spi.strip_height;
+            SPProblem::new(
+                spi.clone(),
+                strip_width,
+                CDEConfig::preset(Preset::Balanced),
+            )
+            .into()
+        }
+    };
+    for (item, qty) in instance.items() {
+        for _ in 0..*qty {
+            let layout_indices = (0..problem.layouts().len())
+                .map(LayoutIndex::Real)
+                .chain(problem.template_layout_indices_with_stock())
+                .collect_vec();
+            let p_opt = layout_indices.into_iter().find_map(|layout_idx| {
+                random_placement(problem.get_layout(layout_idx), item, rng).map(|d_transf| {
+                    PlacingOption {
+                        layout_idx,
+                        item_id: item.id,
+                        d_transf,
+                    }
+                })
+            });
+            if let Some(p_opt) = p_opt {
+                problem.place_item(p_opt);
+            }
+        }
+    }
+    problem.flush_changes();
+    problem
+}
+
+fn random_placement(layout: &Layout, item: &Item, rng: &mut SmallRng) -> Option<DTransformation> {
+    let cde = layout.cde();
+    let bbox = layout.bin.bbox();
+    let irrel_hazards = match item.hazard_filter.as_ref() {
+        None => vec![],
+        Some(hf) => hazard_filter::generate_irrelevant_hazards(hf, cde.all_hazards()),
+    };
+    let mut buffer = (*item.shape).clone();
+    (0..N_ATTEMPTS).find_map(|_| {
+        let rotation = match &item.allowed_rotation {
+            AllowedRotation::None => 0.0,
+            AllowedRotation::Continuous => rng.gen_range(0.0..2.0 * PI),
+            AllowedRotation::Discrete(rotations) => *rotations.choose(rng).unwrap(),
+            //explicit transformations are defined in input coordinates, these items are left unplaced
+            AllowedRotation::Explicit(_) => return None,
+        };
+        let translation = (
+            rng.gen_range(bbox.x_min..bbox.x_max),
+            rng.gen_range(bbox.y_min..bbox.y_max),
+        );
+        let d_transf = DTransformation::new(rotation, translation);
+        let collides = cde.surrogate_or_poly_collides(
+            &item.shape,
+            &d_transf.compose(),
+            &mut buffer,
+            &irrel_hazards,
+        );
+        (!collides).then_some(d_transf)
+    })
+}
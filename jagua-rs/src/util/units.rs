@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fsize;
+use crate::geometry::primitives::arc_polygon::ArcPolygon;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+
+/// Length unit of the coordinates of an instance.
+/// <br>
+/// An instance with units is parsed into millimeters: all its shapes (and those of the DXF and SVG files it refers to) are scaled,
+/// so lengths in the configuration (clearances, web widths, ...) are always expressed in millimeters.
+/// Solutions and exported layouts are converted back to the units of the instance.
+/// Instances without units are parsed as they are, in whatever unit their coordinates are.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Units {
+    Millimeter,
+    Inch,
+    /// Custom unit, the number of millimeters in one unit of the instance
+    Scale(fsize),
+}
+
+impl Units {
+    /// Number of millimeters in one unit
+    pub fn mm_per_unit(&self) -> fsize {
+        match self {
+            Units::Millimeter => 1.0,
+            Units::Inch => 25.4,
+            Units::Scale(factor) => *factor,
+        }
+    }
+
+    /// Factor by which the lengths of an instance in `units` are multiplied when parsed, 1 for an instance without units
+    pub fn scale(units: Option<Units>) -> fsize {
+        units.map_or(1.0, |u| u.mm_per_unit())
+    }
+}
+
+/// Polygon with all coordinates multiplied by `factor`, i.e. scaled about the origin
+pub fn scaled_simple_polygon(polygon: &SimplePolygon, factor: fsize) -> SimplePolygon {
+    SimplePolygon::new(
+        polygon
+            .points
+            .iter()
+            .map(|p| scaled_point(*p, factor))
+            .collect(),
+    )
+}
+
+/// Polygon with all coordinates multiplied by `factor`, i.e. scaled about the origin. The bulges of its arcs remain the same
+pub fn scaled_arc_polygon(polygon: &ArcPolygon, factor: fsize) -> ArcPolygon {
+    ArcPolygon::new(
+        polygon
+            .points
+            .iter()
+            .map(|p| scaled_point(*p, factor))
+            .collect(),
+        polygon.bulges.clone(),
+    )
+}
+
+pub fn scaled_point(Point(x, y): Point, factor: fsize) -> Point {
+    Point(x * factor, y * factor)
+}
@@ -0,0 +1,122 @@
+use crate::collision_detection::hazard::HazardEntity;
+use crate::collision_detection::hazard_filter::{
+    self, CombinedHazardFilter, EntityHazardFilter, HazardFilter,
+};
+use crate::entities::instances::instance::Instance;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::layout::{Layout, LayoutSnapshot};
+use crate::entities::placed_item::PItemKey;
+use crate::entities::solution::Solution;
+
+/// Reason why a placed item makes a solution infeasible
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViolationKind {
+    /// The item overlaps another placed item of the same layout
+    Overlap { other: PItemKey },
+    /// The item extends beyond the contour of the bin
+    OutsideBin,
+    /// The item overlaps a hole of the bin, with its index in [`Bin::holes`](crate::entities::bin::Bin::holes)
+    BinHole { hole: usize },
+    /// The item overlaps a zone of the bin with a lower quality than the item requires
+    QualityZone { quality: usize },
+}
+
+/// A violation by a placed item of a solution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlacementViolation {
+    /// Index of the layout in [`Solution::layout_snapshots`]
+    pub layout: usize,
+    /// Key of the placed item in the `placed_items` of the layout snapshot
+    pub placed_item: PItemKey,
+    pub item_id: usize,
+    pub kind: ViolationKind,
+}
+
+/// Checks whether all items of a solution (e.g. built from a layout produced by another tool) are placed feasibly,
+/// and reports every violation of every placed item: overlaps with other items, placements (partly) outside the bin or in one of its holes,
+/// and overlaps with zones of insufficient quality.
+/// <br>
+/// The layouts are rebuilt from scratch with the shapes of the items in the `instance`, so the collision detection engines of the snapshots are not trusted.
+/// Temporary zones are not checked, since they only apply during part of the placement sequence, which a solution does not record.
+pub fn validate_solution(instance: &Instance, solution: &Solution) -> Vec<PlacementViolation> {
+    solution
+        .layout_snapshots
+        .iter()
+        .enumerate()
+        .flat_map(|(l_idx, ls)| validate_layout(instance, ls, l_idx))
+        .collect()
+}
+
+/// Violations of the placed items of a single layout, see [`validate_solution`]
+pub fn validate_layout(
+    instance: &Instance,
+    layout_snapshot: &LayoutSnapshot,
+    l_idx: usize,
+) -> Vec<PlacementViolation> {
+    let mut violations = vec![];
+    let violation = |pk: PItemKey, kind: ViolationKind| PlacementViolation {
+        layout: l_idx,
+        placed_item: pk,
+        item_id: layout_snapshot.placed_items[pk].item_id,
+        kind,
+    };
+
+    //the keys of the placed items in the rebuilt layout, paired with those in the snapshot
+    let mut layout = Layout::new(layout_snapshot.id, layout_snapshot.bin.clone());
+    let mut keys: Vec<(PItemKey, PItemKey)> = vec![];
+    for (pk, pi) in layout_snapshot.placed_items.iter() {
+        //an exact duplicate of a placed item cannot be registered as a hazard of its own
+        let duplicate = keys.iter().find(|(_, other_pk)| {
+            let other = &layout_snapshot.placed_items[*other_pk];
+            other.item_id == pi.item_id && other.d_transf == pi.d_transf
+        });
+        match duplicate {
+            Some(&(_, other_pk)) => {
+                violations.push(violation(pk, ViolationKind::Overlap { other: other_pk }));
+                violations.push(violation(other_pk, ViolationKind::Overlap { other: pk }));
+            }
+            None => {
+                let item = instance.item(pi.item_id);
+                let new_pk = layout.place_item(item, pi.copy, pi.d_transf);
+                keys.push((new_pk, pk));
+            }
+        }
+    }
+
+    for &(new_pk, pk) in &keys {
+        let pi = &layout.placed_items()[new_pk];
+        let self_filter = EntityHazardFilter(vec![pi.into()]);
+        let mut filters: Vec<Box<&dyn HazardFilter>> = vec![Box::new(&self_filter)];
+        if let Some(qz_filter) = &pi.hazard_filter {
+            filters.push(Box::new(qz_filter));
+        }
+        let irrelevant_hazards = hazard_filter::generate_irrelevant_hazards(
+            &CombinedHazardFilter { filters },
+            layout.cde().all_hazards(),
+        );
+
+        let mut detected = vec![];
+        layout
+            .cde()
+            .collect_poly_collisions(&pi.shape, &irrelevant_hazards, &mut detected);
+        for hazard in detected {
+            let kind = match hazard {
+                HazardEntity::PlacedItem { .. } => {
+                    let other_new_pk = layout
+                        .hazard_to_p_item_key(&hazard)
+                        .expect("colliding item is not placed in the layout");
+                    let (_, other) = keys.iter().find(|(k, _)| *k == other_new_pk).unwrap();
+                    ViolationKind::Overlap { other: *other }
+                }
+                HazardEntity::BinExterior => ViolationKind::OutsideBin,
+                HazardEntity::BinHole { id } => ViolationKind::BinHole { hole: id },
+                HazardEntity::InferiorQualityZone { quality, .. } => {
+                    ViolationKind::QualityZone { quality }
+                }
+                HazardEntity::TemporaryZone { .. } => continue,
+            };
+            violations.push(violation(pk, kind));
+        }
+    }
+    violations
+}
@@ -0,0 +1,78 @@
+use itertools::Itertools;
+use slotmap::SlotMap;
+
+use crate::entities::bin::Bin;
+use crate::entities::instances::instance_generic::InstanceGeneric;
+use crate::entities::placed_item::{PItemKey, PlacedItem};
+use crate::fsize;
+use crate::geometry::primitives::point::Point;
+use crate::geometry::primitives::simple_polygon::SimplePolygon;
+use crate::util::clearance;
+use crate::util::clearance::ClearanceEntity;
+
+/// Web of the skeleton (the material of the bin which remains after the placed items are cut out)
+/// which is narrower than the minimum web width.
+/// Thin webs are prone to tipping up or warping during cutting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThinWeb {
+    pub item: PItemKey,
+    /// The entity on the other side of the web
+    pub other: ClearanceEntity,
+    /// Width of the web at its narrowest point
+    pub width: fsize,
+    /// Center of the web at its narrowest point
+    pub location: Point,
+}
+
+/// Detects all webs of the skeleton narrower than `min_web_width`, between two placed items or between an item and the boundary of the bin.
+/// Entities which touch (zero clearance) are separated by a common cut instead of a web, and are not flagged.
+/// A web is flagged as soon as the lower bound on its width (see [`Clearance::lower_bound`](clearance::Clearance::lower_bound)) is below `min_web_width`,
+/// sorted by that lower bound.
+pub fn thin_webs(
+    instance: &impl InstanceGeneric,
+    bin: &Bin,
+    placed_items: &SlotMap<PItemKey, PlacedItem>,
+    min_web_width: fsize,
+) -> Vec<ThinWeb> {
+    let report = clearance::verify_clearances(instance, bin, placed_items, min_web_width);
+    report
+        .violations
+        .iter()
+        .filter(|c| c.distance > 0.0)
+        .map(|c| {
+            let other_shape = match c.other {
+                ClearanceEntity::PlacedItem(pk) => &*placed_items[pk].shape,
+                ClearanceEntity::BinExterior => &*bin.outer,
+                ClearanceEntity::BinHole(i) => &*bin.holes[i],
+            };
+            let (p1, p2) = closest_points(&placed_items[c.item].shape, other_shape);
+            ThinWeb {
+                item: c.item,
+                other: c.other,
+                width: c.distance,
+                location: Point((p1.0 + p2.0) / 2.0, (p1.1 + p2.1) / 2.0),
+            }
+        })
+        .collect_vec()
+}
+
+/// Closest pair of points on the contours of two non-intersecting polygons
+pub fn closest_points(p1: &SimplePolygon, p2: &SimplePolygon) -> (Point, Point) {
+    //the closest pair of points always includes a vertex of one of the polygons
+    let vertex_to_edges = |from: &SimplePolygon, to: &SimplePolygon| {
+        from.points
+            .iter()
+            .flat_map(|p| {
+                to.edge_iter()
+                    .map(move |e| (*p, e.closest_point_on_edge(p)))
+            })
+            .collect_vec()
+    };
+    let forward = vertex_to_edges(p1, p2);
+    let backward = vertex_to_edges(p2, p1).into_iter().map(|(a, b)| (b, a));
+    forward
+        .into_iter()
+        .chain(backward)
+        .min_by(|(a1, b1), (a2, b2)| a1.sq_distance(*b1).total_cmp(&a2.sq_distance(*b2)))
+        .expect("polygons without vertices")
+}
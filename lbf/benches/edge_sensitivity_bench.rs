@@ -22,7 +22,9 @@ use jagua_rs::io::json_instance::JsonInstance;
 use lbf::io;
 use lbf::io::svg_util::SvgDrawOptions;
 use lbf::lbf_config::LBFConfig;
+use lbf::lbf_cost::PlacementBias;
 use lbf::samplers::hpg_sampler::HPGSampler;
+use lbf::samplers::infeasible_cells::InfeasibleCellCache;
 
 use crate::util::{N_ITEMS_REMOVED, SWIM_PATH};
 
@@ -100,8 +102,13 @@ fn edge_sensitivity_bench(config: LBFConfig, mut g: BenchmarkGroup<WallTime>) {
         };*/
 
         let samples = {
-            let mut hpg_sampler = HPGSampler::new(instance.item(0), layout)
-                .expect("should be able to create HPGSampler");
+            let mut hpg_sampler = HPGSampler::new(
+                instance.item(0),
+                layout,
+                &InfeasibleCellCache::new(None),
+                PlacementBias::default(),
+            )
+            .expect("should be able to create HPGSampler");
             (0..N_TOTAL_SAMPLES)
                 .map(|_| hpg_sampler.sample(&mut rng))
                 .collect_vec()
@@ -114,8 +121,7 @@ fn edge_sensitivity_bench(config: LBFConfig, mut g: BenchmarkGroup<WallTime>) {
 
         g.bench_function(BenchmarkId::from_parameter(edge_multiplier), |b| {
             b.iter(|| {
-                for i in 0..N_ITEMS_REMOVED {
-                    let pi_uid = &selected_pi_uids[i];
+                for pi_uid in selected_pi_uids.iter().take(N_ITEMS_REMOVED) {
                     let item = instance.item(pi_uid.item_id);
                     let mut buffer_shape = item.shape.as_ref().clone();
                     for transf in samples_cycler.next().unwrap() {
@@ -16,7 +16,9 @@ use jagua_rs::geometry::geo_traits::{Shape, TransformableFrom};
 use jagua_rs::geometry::primitives::circle::Circle;
 use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
 use jagua_rs::io::json_instance::JsonInstance;
+use lbf::lbf_cost::PlacementBias;
 use lbf::samplers::hpg_sampler::HPGSampler;
+use lbf::samplers::infeasible_cells::InfeasibleCellCache;
 
 use crate::util::{create_base_config, N_ITEMS_REMOVED, SWIM_PATH};
 
@@ -43,13 +45,12 @@ fn fast_fail_query_bench(c: &mut Criterion) {
 
     let config_combos = FF_POLES
         .iter()
-        .map(|n_ff_poles| {
+        .flat_map(|n_ff_poles| {
             FF_PIERS
                 .iter()
                 .map(|n_ff_piers| (*n_ff_poles, *n_ff_piers))
                 .collect_vec()
         })
-        .flatten()
         .collect_vec();
 
     let mut config = create_base_config();
@@ -77,7 +78,13 @@ fn fast_fail_query_bench(c: &mut Criterion) {
     let samples = ITEMS_ID_TO_TEST
         .iter()
         .map(|&item_id| {
-            let mut sampler = HPGSampler::new(instance.item(item_id), layout).unwrap();
+            let mut sampler = HPGSampler::new(
+                instance.item(item_id),
+                layout,
+                &InfeasibleCellCache::new(None),
+                PlacementBias::default(),
+            )
+            .unwrap();
             (0..N_TOTAL_SAMPLES)
                 .map(|_| sampler.sample(&mut rng))
                 .collect_vec()
@@ -128,7 +135,7 @@ fn fast_fail_query_bench(c: &mut Criterion) {
                             true => true,
                             false => {
                                 buffer_shape.transform_from(&item.shape, transf);
-                                layout.cde().poly_collides(&buffer_shape, &[])
+                                layout.cde().poly_collides(buffer_shape, &[])
                             }
                         };
                         match collides {
@@ -172,14 +179,12 @@ pub fn create_custom_surrogate(
     )
     .area();
 
-    let surrogate = SPSurrogate {
+    SPSurrogate {
         convex_hull_indices,
         poles,
         piers,
         poles_bounding_circle,
         n_ff_poles,
         convex_hull_area,
-    };
-
-    surrogate
+    }
 }
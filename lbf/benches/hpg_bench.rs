@@ -14,7 +14,9 @@ use jagua_rs::entities::problems::strip_packing::SPProblem;
 use jagua_rs::geometry::geo_traits::Shape;
 use jagua_rs::geometry::geo_traits::TransformableFrom;
 use jagua_rs::io::json_instance::JsonInstance;
+use lbf::lbf_cost::PlacementBias;
 use lbf::samplers::hpg_sampler::HPGSampler;
+use lbf::samplers::infeasible_cells::InfeasibleCellCache;
 
 use crate::util::{create_base_config, N_ITEMS_REMOVED, SWIM_PATH};
 
@@ -90,7 +92,13 @@ fn hpg_query_bench(c: &mut Criterion) {
         let layout = problem.get_layout(LayoutIndex::Real(0));
         let surrogate = item.shape.surrogate();
         let mut buffer_shape = item.shape.as_ref().clone();
-        let mut sampler = HPGSampler::new(item, layout).unwrap();
+        let mut sampler = HPGSampler::new(
+            item,
+            layout,
+            &InfeasibleCellCache::new(None),
+            PlacementBias::default(),
+        )
+        .unwrap();
         println!(
             "[{}] sampler coverage: {:.3}% with {} samplers",
             n_hpg_cells,
@@ -178,7 +186,13 @@ fn hpg_update_bench(c: &mut Criterion) {
         let layout = problem.get_layout(LayoutIndex::Real(0));
         let surrogate = item.shape.surrogate();
         let mut buffer_shape = item.shape.as_ref().clone();
-        let mut sampler = HPGSampler::new(item, layout).unwrap();
+        let mut sampler = HPGSampler::new(
+            item,
+            layout,
+            &InfeasibleCellCache::new(None),
+            PlacementBias::default(),
+        )
+        .unwrap();
         println!(
             "[{}] sampler coverage: {:.3}% with {} samplers",
             n_hpg_cells,
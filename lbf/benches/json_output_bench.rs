@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use jagua_rs::entities::problems::problem_generic::ProblemGeneric;
+use jagua_rs::io::json_instance::JsonInstance;
+use jagua_rs::io::parser;
+use lbf::io::json_output::{self, JsonFormat, JsonOutput};
+use lbf::EPOCH;
+
+use crate::util::{create_base_config, SWIM_PATH};
+
+criterion_main!(benches);
+criterion_group!(benches, json_output_bench);
+
+#[allow(dead_code)]
+mod util;
+
+/// Number of placed items in the written solutions
+const N_PLACEMENTS: &[usize] = &[1_000, 10_000, 100_000];
+
+/// Benchmark writing the JSON solution in both formats, for solutions with a growing number of placed items.
+/// The layout of the swim instance is repeated until the solution contains enough placed items.
+fn json_output_bench(c: &mut Criterion) {
+    let json_instance: JsonInstance =
+        serde_json::from_reader(BufReader::new(File::open(SWIM_PATH).unwrap())).unwrap();
+
+    let config = create_base_config();
+    let instance = util::create_instance(
+        &json_instance,
+        config.cde_config,
+        config.poly_simpl_tolerance,
+    );
+    let (mut problem, _) = util::create_blf_problem(instance.clone(), config, 0);
+    let solution = problem.create_solution(None);
+    let json_solution = parser::compose_json_solution(
+        &solution,
+        &instance,
+        *EPOCH,
+        config.clearance_threshold,
+        &config.objectives(),
+    );
+    let layout = json_solution.layouts[0].clone();
+
+    let mut group = c.benchmark_group("json_output_bench");
+    for &n_placements in N_PLACEMENTS {
+        let n_layouts = n_placements.div_ceil(layout.placed_items.len());
+        let mut json_output = JsonOutput {
+            instance: json_instance.clone(),
+            solution: json_solution.clone(),
+            config,
+        };
+        json_output.solution.layouts = vec![layout.clone(); n_layouts];
+
+        for format in [JsonFormat::Pretty, JsonFormat::Compact] {
+            let mut buffer = vec![];
+            json_output::write_json(&json_output, &mut buffer, format).unwrap();
+            println!(
+                "{:?} with {} placed items: {} bytes",
+                format,
+                n_layouts * layout.placed_items.len(),
+                buffer.len()
+            );
+            group.throughput(Throughput::Bytes(buffer.len() as u64));
+            group.bench_function(
+                BenchmarkId::new(format!("{:?}", format), n_placements),
+                |b| {
+                    b.iter(|| {
+                        buffer.clear();
+                        json_output::write_json(&json_output, &mut buffer, format).unwrap();
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
@@ -62,7 +62,7 @@ fn quadtree_update_bench(c: &mut Criterion) {
             b.iter(|| {
                 // Remove an item from the layout
                 let (pik, pi) = problem
-                    .get_layout(&layout_idx)
+                    .get_layout(layout_idx)
                     .placed_items()
                     .iter()
                     .choose(&mut rng)
@@ -204,7 +204,7 @@ fn quadtree_query_update_1000_1(c: &mut Criterion) {
                 let layout = problem.get_layout(LayoutIndex::Real(0));
                 let mut buffer_shape = item.shape.as_ref().clone();
                 for transf in sample_cycler.next().unwrap() {
-                    buffer_shape.transform_from(&item.shape, &transf);
+                    buffer_shape.transform_from(&item.shape, transf);
                     let collides = layout.cde().poly_collides(&buffer_shape, &[]);
                     criterion::black_box(collides); //prevent the compiler from optimizing the loop away
                 }
@@ -2,7 +2,7 @@ use itertools::Itertools;
 use log::info;
 use rand::prelude::{IteratorRandom, SmallRng};
 use rand::SeedableRng;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use jagua_rs::entities::instances::instance::Instance;
 use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
@@ -13,7 +13,7 @@ use jagua_rs::entities::problems::strip_packing::SPProblem;
 use jagua_rs::fsize;
 use jagua_rs::io::json_instance::JsonInstance;
 use jagua_rs::io::parser::Parser;
-use jagua_rs::util::config::{CDEConfig, SPSurrogateConfig};
+use jagua_rs::util::config::{CDEConfig, GeometricFidelity, SPSurrogateConfig};
 use jagua_rs::util::polygon_simplification::PolySimplConfig;
 use lbf::io;
 use lbf::io::svg_util::SvgDrawOptions;
@@ -32,8 +32,14 @@ pub fn create_instance(
         Some(tolerance) => PolySimplConfig::Enabled { tolerance },
         None => PolySimplConfig::Disabled,
     };
-    let parser = Parser::new(poly_simpl_config, cde_config, true);
-    parser.parse(json_instance)
+    let parser = Parser::new(
+        poly_simpl_config,
+        cde_config,
+        true,
+        PathBuf::new(),
+        GeometricFidelity::default(),
+    );
+    parser.parse(json_instance).unwrap()
 }
 
 /// Creates a Strip Packing Problem, fill the layout using with the LBF Optimizer and removes some items from the layout
@@ -45,7 +51,8 @@ pub fn create_blf_problem(
     n_items_removed: usize,
 ) -> (SPProblem, Vec<PlacingOption>) {
     assert!(matches!(&instance, &Instance::SP(_)));
-    let mut lbf_optimizer = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0));
+    let mut lbf_optimizer =
+        LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
     lbf_optimizer.solve();
 
     let mut problem = match lbf_optimizer.problem.clone() {
@@ -56,7 +63,7 @@ pub fn create_blf_problem(
     let mut rng = SmallRng::seed_from_u64(0);
     // Remove some items from the layout
     let placed_items_to_remove = problem
-        .get_layout(&STRIP_LAYOUT_IDX)
+        .get_layout(STRIP_LAYOUT_IDX)
         .placed_items()
         .iter()
         .map(|(k, _)| k)
@@ -97,7 +104,7 @@ pub fn create_blf_problem(
             ..SvgDrawOptions::default()
         };
         let svg = io::layout_to_svg::layout_to_svg(
-            problem.get_layout(&STRIP_LAYOUT_IDX),
+            problem.get_layout(STRIP_LAYOUT_IDX),
             &instance,
             draw_options,
         );
@@ -119,10 +126,38 @@ pub fn create_base_config() -> LBFConfig {
                 n_ff_piers: 0,
             },
         },
+        cde_calibration: None,
         poly_simpl_tolerance: Some(0.001),
+        geometric_fidelity: None,
+        clearance_threshold: 0.0,
+        min_web_width: None,
         prng_seed: Some(0),
+        item_prng_streams: false,
         n_samples: 5000,
         ls_frac: 0.2,
+        lattice_min_qty: None,
+        cluster_max_area_frac: None,
+        infeasible_cell_threshold: None,
+        exact_search: None,
+        bin_assignment: None,
+        rotated_bins: false,
+        forbid_part_in_part: false,
+        rotation_snap: None,
+        coarse_to_fine: None,
+        strip_width_factor: None,
+        strip_growth: Default::default(),
+        packing_direction: Default::default(),
+        gravity: Default::default(),
+        datum_tolerance: None,
+        strip_max_width: None,
+        objective_weights: None,
+        cut_order: None,
+        n_runs: None,
+        n_threads: None,
+        missing_assets: Default::default(),
+        export_precision: None,
+        gcode: Default::default(),
+        json_format: Default::default(),
         svg_draw_options: Default::default(),
     }
 }
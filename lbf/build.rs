@@ -0,0 +1,24 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Embeds the git version of the source tree in the `LBF_GIT_VERSION` environment variable, if it can be determined
+fn main() {
+    let git_version = Command::new("git")
+        .args(["describe", "--always", "--dirty", "--abbrev=40"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string());
+
+    if let Some(git_version) = git_version {
+        println!("cargo:rustc-env=LBF_GIT_VERSION={}", git_version);
+    }
+
+    //rerun when a commit is made or the sources change, so the version (and dirty flag) stays up to date
+    for path in ["../.git/HEAD", "../.git/index", "src", "../jagua-rs/src"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
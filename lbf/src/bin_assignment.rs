@@ -0,0 +1,255 @@
+use std::cmp::Reverse;
+use std::iter;
+
+use itertools::Itertools;
+use log::info;
+use ordered_float::NotNan;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::placing_option::PlacingOption;
+use jagua_rs::entities::problems::problem::Problem;
+use jagua_rs::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+use jagua_rs::entities::problems::problem_view::ProblemView;
+use jagua_rs::fsize;
+use jagua_rs::geometry::geo_traits::Shape;
+
+use crate::lbf_config::LBFConfig;
+use crate::lbf_optimizer;
+use crate::samplers::infeasible_cells::InfeasibleCellCache;
+
+/// Configuration of the assignment of items to bins, which precedes their geometric placement (bin packing only)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct BinAssignmentConfig {
+    /// Fraction of the area of each bin which can be assigned to items
+    pub max_fill: fsize,
+}
+
+/// Area-based plan of which items are placed in which bins.
+/// Copies of an item are only sampled in the bins they are assigned to,
+/// instead of in every open bin and every bin type in stock.
+#[derive(Debug, Clone)]
+pub struct BinAssignment {
+    pub config: BinAssignmentConfig,
+    pub plans: Vec<BinPlan>,
+}
+
+/// Items assigned to a single bin
+#[derive(Debug, Clone)]
+pub struct BinPlan {
+    /// Id of the type of bin
+    pub bin_id: usize,
+    /// Id of the layout realizing the plan, `None` if the bin is not opened yet
+    pub layout_id: Option<usize>,
+    /// Number of copies of each item assigned to the bin which still have to be placed
+    pub item_qtys: Vec<usize>,
+}
+
+impl BinAssignment {
+    /// Assigns all missing items of the problem to bins by first-fit-decreasing on their area
+    /// (after their shortfall cost per area, if defined).
+    /// Open layouts (except `closed_layouts`) are filled first.
+    /// New bins are taken from the type in stock with the lowest value per area which can hold the item.
+    /// Items which do not fit in any bin remain unassigned.
+    pub fn first_fit_decreasing(
+        problem: ProblemView,
+        config: BinAssignmentConfig,
+        closed_layouts: &[usize],
+    ) -> Self {
+        let n_items = problem.instance().items().len();
+        let new_plan = |bin_id: usize, layout_id: Option<usize>| BinPlan {
+            bin_id,
+            layout_id,
+            item_qtys: vec![0; n_items],
+        };
+
+        let mut plans = vec![];
+        //area which can still be assigned to each plan
+        let mut capacities = vec![];
+        for layout in problem.layouts() {
+            plans.push(new_plan(layout.bin.id, Some(layout.id())));
+            capacities.push(match closed_layouts.contains(&layout.id()) {
+                true => 0.0,
+                false => bin_capacity(problem, layout.bin.id, config) - layout.placed_item_area(),
+            });
+        }
+        let n_open_layouts = plans.len();
+        let mut stock = problem.bin_qtys().to_vec();
+
+        let copies = (0..n_items)
+            .flat_map(|i| iter::repeat_n(i, problem.missing_item_qtys()[i].max(0) as usize))
+            .sorted_by_cached_key(|i| {
+                let item = problem.instance().item(*i);
+                let area = NotNan::new(item.shape.area()).expect("item area is NaN");
+                (
+                    Reverse(lbf_optimizer::shortfall_density(item)),
+                    Reverse(area),
+                )
+            })
+            .collect_vec();
+
+        let mut n_unassigned = 0;
+        for item_id in copies {
+            let item = problem.instance().item(item_id);
+            let area = item.shape.area();
+            let accepts = |bin_id: usize| problem.template_layouts()[bin_id].bin.accepts(item);
+            let plan_idx = (0..plans.len())
+                .find(|p| capacities[*p] >= area && accepts(plans[*p].bin_id))
+                .or_else(|| {
+                    //open a new bin
+                    let bin_id = (0..stock.len())
+                        .filter(|b| stock[*b] > 0 && accepts(*b))
+                        .filter(|b| bin_capacity(problem, *b, config) >= area)
+                        .min_by_key(|b| {
+                            let bin = &problem.template_layouts()[*b].bin;
+                            NotNan::new(bin.value as fsize / bin.area).expect("bin value is NaN")
+                        })?;
+                    //rotated variants of a bin share its stock
+                    let base_id = problem.template_layouts()[bin_id].bin.base_id();
+                    for tl in problem.template_layouts() {
+                        if tl.bin.base_id() == base_id {
+                            stock[tl.bin.id] -= 1;
+                        }
+                    }
+                    plans.push(new_plan(bin_id, None));
+                    capacities.push(bin_capacity(problem, bin_id, config));
+                    Some(plans.len() - 1)
+                });
+            match plan_idx {
+                Some(idx) => {
+                    capacities[idx] -= area;
+                    plans[idx].item_qtys[item_id] += 1;
+                }
+                None => n_unassigned += 1,
+            }
+        }
+
+        info!(
+            "[ASSIGN] assigned items to {} open and {} new bins, {} items unassigned",
+            n_open_layouts,
+            plans.len() - n_open_layouts,
+            n_unassigned
+        );
+
+        Self { config, plans }
+    }
+
+    /// Layouts to which copies of the item are assigned, open layouts first
+    pub fn layouts_for(&self, problem: ProblemView, item_id: usize) -> Vec<LayoutIndex> {
+        self.plans
+            .iter()
+            .filter(|plan| plan.item_qtys[item_id] > 0)
+            .filter_map(|plan| match plan.layout_id {
+                Some(layout_id) => problem
+                    .layouts()
+                    .iter()
+                    .position(|l| l.id() == layout_id)
+                    .map(LayoutIndex::Real),
+                None => (problem.bin_qtys()[plan.bin_id] > 0)
+                    .then_some(LayoutIndex::Template(plan.bin_id)),
+            })
+            .unique()
+            .sorted_by_key(|l_idx| matches!(l_idx, LayoutIndex::Template(_)))
+            .collect()
+    }
+
+    /// Searches a placement for a copy of the item, in the layouts it is assigned to.
+    /// If none is found, the remaining items are reassigned, with the layouts which were tried closed.
+    /// If the item cannot be placed in its new assignment either, all remaining layouts are searched.
+    pub fn find_placement(
+        &mut self,
+        problem: &Problem,
+        item: &Item,
+        config: &LBFConfig,
+        rng: &mut impl Rng,
+        sample_counter: &mut usize,
+        cell_cache: &mut InfeasibleCellCache,
+    ) -> Option<PlacingOption> {
+        let mut tried = vec![];
+        for reassigned in [false, true] {
+            let layouts = self
+                .layouts_for(problem.view(), item.id)
+                .into_iter()
+                .filter(|l_idx| !tried.contains(l_idx))
+                .collect_vec();
+            tried.extend(layouts.iter().copied());
+            let p_opt = lbf_optimizer::find_lbf_placement_in(
+                problem,
+                layouts.into_iter(),
+                item,
+                config,
+                rng,
+                sample_counter,
+                cell_cache,
+            );
+            if p_opt.is_some() {
+                return p_opt;
+            }
+            if !reassigned {
+                info!(
+                    "[ASSIGN] no placement found for item with id {} in its assigned bins, reassigning",
+                    item.id
+                );
+                let closed_layouts = tried
+                    .iter()
+                    .filter_map(|l_idx| match l_idx {
+                        LayoutIndex::Real(i) => Some(problem.layouts()[*i].id()),
+                        LayoutIndex::Template(_) => None,
+                    })
+                    .collect_vec();
+                *self = Self::first_fit_decreasing(problem.view(), self.config, &closed_layouts);
+            }
+        }
+
+        let remaining_layouts = problem
+            .layout_indices()
+            .chain(problem.template_layout_indices_with_stock())
+            .filter(|l_idx| !tried.contains(l_idx))
+            .collect_vec();
+        lbf_optimizer::find_lbf_placement_in(
+            problem,
+            remaining_layouts.into_iter(),
+            item,
+            config,
+            rng,
+            sample_counter,
+            cell_cache,
+        )
+    }
+
+    /// Registers the placement of a copy of the item in a layout, consuming its assignment
+    pub fn register_placement(
+        &mut self,
+        problem: ProblemView,
+        item_id: usize,
+        l_index: LayoutIndex,
+    ) {
+        let layout = problem.get_layout(l_index);
+        let (layout_id, bin_id) = (layout.id(), layout.bin.id);
+        let base_id = |bin_id: usize| problem.template_layouts()[bin_id].bin.base_id();
+        let plan = self
+            .plans
+            .iter()
+            .position(|p| p.layout_id == Some(layout_id) && p.item_qtys[item_id] > 0)
+            .or_else(|| {
+                //the item opened a new bin, possibly a rotated variant of the one in the plan
+                self.plans.iter().position(|p| {
+                    p.layout_id.is_none()
+                        && base_id(p.bin_id) == base_id(bin_id)
+                        && p.item_qtys[item_id] > 0
+                })
+            });
+        //items placed outside their assignment are not tracked
+        if let Some(plan) = plan {
+            self.plans[plan].bin_id = bin_id;
+            self.plans[plan].layout_id = Some(layout_id);
+            self.plans[plan].item_qtys[item_id] -= 1;
+        }
+    }
+}
+
+fn bin_capacity(problem: ProblemView, bin_id: usize, config: BinAssignmentConfig) -> fsize {
+    let bin = &problem.template_layouts()[bin_id].bin;
+    bin.area * fsize::min(config.max_fill, bin.max_usage.unwrap_or(1.0))
+}
@@ -0,0 +1,137 @@
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+use log::info;
+use rand::prelude::SmallRng;
+use serde::{Deserialize, Serialize};
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::entities::problems::problem_generic::ProblemGeneric;
+use jagua_rs::util::config::CDEConfig;
+
+use crate::io::error::IoError;
+use crate::lbf_config::LBFConfig;
+use crate::lbf_optimizer::LBFOptimizer;
+
+/// Quadtree depths evaluated during calibration
+const QUADTREE_DEPTHS: [u8; 3] = [4, 5, 6];
+/// Target number of cells of the Hazard Proximity Grid evaluated during calibration
+const HPG_N_CELLS: [usize; 3] = [1000, 2000, 4000];
+
+/// Configuration of the calibration of the Collision Detection Engine
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct CalibrationConfig {
+    /// Number of placements of the probe run with every candidate configuration
+    pub n_placements: usize,
+}
+
+/// Result of probing a single candidate configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Probe {
+    pub cde_config: CDEConfig,
+    pub n_placed: usize,
+    pub duration: Duration,
+}
+
+/// Runs a short probe of the LBF heuristic with several quadtree depths and Hazard Proximity Grid resolutions
+/// (including the configured ones) and returns the fastest feasible configuration, along with the instance rebuilt for it.
+/// A probe is feasible if it places as many items as the best probe, so the speedup never comes at the cost of placements.
+/// <br>
+/// Every probe starts from the same PRNG state, the rest of the configuration is left unchanged.
+/// Fails if the config cannot be used for the instance, see [`LBFConfig::validate_for_instance`].
+pub fn calibrate(
+    instance: &Instance,
+    config: LBFConfig,
+    calib_config: CalibrationConfig,
+    rng: &SmallRng,
+) -> Result<(Instance, LBFConfig), IoError> {
+    let n_placements = calib_config.n_placements.min(instance.total_item_qty());
+    let candidates = candidate_cde_configs(config.cde_config);
+    info!(
+        "[CALIB] probing {} CDE configurations for {} placements",
+        candidates.len(),
+        n_placements
+    );
+
+    let probes = candidates
+        .into_iter()
+        .map(|cde_config| {
+            let probe_instance = instance.with_cde_config(cde_config);
+            let probe_config = LBFConfig {
+                cde_config,
+                ..config
+            };
+            let start = Instant::now();
+            let mut optimizer = LBFOptimizer::new(probe_instance, probe_config, rng.clone())?;
+            optimizer.placement_limit = n_placements;
+            optimizer.solve();
+            let probe = Probe {
+                cde_config,
+                n_placed: optimizer.problem.placed_item_qtys().sum(),
+                duration: start.elapsed(),
+            };
+            info!(
+                "[CALIB] quadtree depth {}, {} HPG cells: {} items placed in {:.3}s",
+                cde_config.quadtree_depth,
+                cde_config.hpg_n_cells,
+                probe.n_placed,
+                probe.duration.as_secs_f64()
+            );
+            Ok((probe, optimizer.instance))
+        })
+        .collect::<Result<Vec<_>, IoError>>()?;
+
+    let best = select(&probes.iter().map(|(p, _)| *p).collect_vec());
+    info!(
+        "[CALIB] selected quadtree depth {} and {} HPG cells ({:.3}s, configured: depth {} and {} cells)",
+        best.cde_config.quadtree_depth,
+        best.cde_config.hpg_n_cells,
+        best.duration.as_secs_f64(),
+        config.cde_config.quadtree_depth,
+        config.cde_config.hpg_n_cells
+    );
+
+    let config = LBFConfig {
+        cde_config: best.cde_config,
+        ..config
+    };
+    //the instance of the probe already has its bins built for the selected configuration
+    let (_, instance) = probes
+        .into_iter()
+        .find(|(p, _)| p.cde_config == best.cde_config)
+        .unwrap();
+    Ok((instance, config))
+}
+
+/// Fastest of the probes which placed the most items, the first one in case of a tie
+pub fn select(probes: &[Probe]) -> Probe {
+    let max_placed = probes
+        .iter()
+        .map(|p| p.n_placed)
+        .max()
+        .expect("no probes to select from");
+    probes
+        .iter()
+        .filter(|p| p.n_placed == max_placed)
+        .min_by_key(|p| p.duration)
+        .copied()
+        .unwrap()
+}
+
+/// Grid of quadtree depths and HPG resolutions, starting with the configured one
+fn candidate_cde_configs(base: CDEConfig) -> Vec<CDEConfig> {
+    let grid = QUADTREE_DEPTHS
+        .iter()
+        .cartesian_product(HPG_N_CELLS.iter())
+        .map(|(&quadtree_depth, &hpg_n_cells)| CDEConfig {
+            quadtree_depth,
+            hpg_n_cells,
+            ..base
+        });
+    [base]
+        .into_iter()
+        .chain(grid)
+        .unique_by(|c| (c.quadtree_depth, c.hpg_n_cells))
+        .collect()
+}
@@ -0,0 +1,260 @@
+use itertools::Itertools;
+use log::info;
+
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::placing_option::PlacingOption;
+use jagua_rs::fsize;
+use jagua_rs::geometry::convex_hull::convex_hull_from_points;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_enums::AllowedRotation;
+use jagua_rs::geometry::geo_traits::{Shape, Transformable};
+use jagua_rs::geometry::primitives::point::Point;
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+use jagua_rs::geometry::transformation::Transformation;
+use jagua_rs::util::polygon_holes;
+use jagua_rs::PI;
+
+use crate::lattice;
+
+/// Number of directions in which the second item is slid towards the first one, when searching for a pair
+const N_PAIR_DIRECTIONS: usize = 16;
+
+/// Composite "super-item": a pair of items placed in contact with each other, which is nested as a single shape.
+/// Once placed, the cluster is decomposed into its members.
+#[derive(Clone, Debug)]
+pub struct Cluster {
+    /// Item with the combined shape of the members.
+    /// Its id follows the ids of the items of the instance, so the caches and PRNG streams kept per item id are not shared with any member
+    pub item: Item,
+    /// Ids of the member items, with their transformations relative to the combined shape
+    pub members: Vec<(usize, DTransformation)>,
+    /// Number of copies of the cluster to place
+    pub qty: usize,
+}
+
+impl Cluster {
+    /// Placing options of the members, when the combined shape is placed according to `p_opt`
+    pub fn decompose(&self, p_opt: &PlacingOption) -> Vec<PlacingOption> {
+        let (rotation, (tx, ty)) = (p_opt.d_transf.rotation(), p_opt.d_transf.translation());
+        let (sin, cos) = rotation.sin_cos();
+        self.members
+            .iter()
+            .map(|(item_id, dt)| {
+                let (mx, my) = dt.translation();
+                let d_transf = DTransformation::new(
+                    rotation + dt.rotation(),
+                    (cos * mx - sin * my + tx, sin * mx + cos * my + ty),
+                );
+                PlacingOption {
+                    layout_idx: p_opt.layout_idx,
+                    item_id: *item_id,
+                    d_transf,
+                }
+            })
+            .collect()
+    }
+
+    /// Fraction of the convex hull of the cluster covered by its members
+    pub fn density(&self) -> fsize {
+        //the channel joining the members has a negligible area
+        self.item.shape.area() / convex_hull_area(&self.item.shape.points)
+    }
+}
+
+/// Greedily pairs copies of the small items into clusters.
+/// Items are small if their area is below `max_area_frac` of the area of the largest item.
+/// For every combination of two small items, the densest pair (relative to its convex hull) is searched by sliding one item into the other.
+/// Pairs are formed in order of decreasing density, as long as they are denser than both of their members on their own.
+/// `items` are all items of the instance, indexed by id. The clusters are numbered after them.
+pub fn generate_clusters(
+    items: &[&Item],
+    missing_qtys: &[isize],
+    max_area_frac: fsize,
+) -> Vec<Cluster> {
+    let max_area = items
+        .iter()
+        .map(|item| item.shape.area())
+        .fold(0.0, fsize::max);
+
+    let small_items = items
+        .iter()
+        .filter(|item| item.shape.area() <= max_area * max_area_frac)
+        .filter(|item| missing_qtys[item.id] > 0)
+        //items with fixed positions cannot be moved along with another item
+        .filter(|item| !matches!(item.allowed_rotation, AllowedRotation::Explicit(_)))
+        .filter(|item| item.copy_attributes.is_empty())
+        .collect_vec();
+
+    let candidates = small_items
+        .iter()
+        .tuple_combinations()
+        .chain(small_items.iter().map(|item| (item, item)))
+        .filter(|(a, b)| a.base_quality == b.base_quality && a.material == b.material)
+        .filter_map(|(a, b)| {
+            let cluster = find_pair(a, b)?;
+            let single_density = fsize::max(single_density(a), single_density(b));
+            (cluster.density() > single_density).then_some(cluster)
+        })
+        .sorted_by(|c1, c2| c2.density().partial_cmp(&c1.density()).unwrap())
+        .collect_vec();
+
+    let mut remaining_qtys = missing_qtys
+        .iter()
+        .map(|q| (*q).max(0) as usize)
+        .collect_vec();
+    let mut clusters = vec![];
+    for mut cluster in candidates {
+        let (id_a, id_b) = (cluster.members[0].0, cluster.members[1].0);
+        let qty = match id_a == id_b {
+            true => remaining_qtys[id_a] / 2,
+            false => usize::min(remaining_qtys[id_a], remaining_qtys[id_b]),
+        };
+        if qty > 0 {
+            remaining_qtys[id_a] -= qty;
+            remaining_qtys[id_b] -= qty;
+            info!(
+                "[CLUSTER] combined {} copies of items with id {} and {} (density: {:.3}%)",
+                qty,
+                id_a,
+                id_b,
+                cluster.density() * 100.0
+            );
+            cluster.qty = qty;
+            cluster.item.id = items.len() + clusters.len();
+            clusters.push(cluster);
+        }
+    }
+    clusters
+}
+
+/// Searches the densest pair of items `a` and `b`, by sliding `b` towards `a` in a range of directions and relative rotations
+fn find_pair(a: &Item, b: &Item) -> Option<Cluster> {
+    let relative_rotations = rotation_list(&a.allowed_rotation)
+        .into_iter()
+        .cartesian_product(rotation_list(&b.allowed_rotation))
+        .map(|(r_a, r_b)| (r_b - r_a).rem_euclid(2.0 * PI))
+        .sorted_by(|r1, r2| r1.partial_cmp(r2).unwrap())
+        .dedup_by(|r1, r2| (r1 - r2).abs() < 1e-3)
+        .collect_vec();
+
+    let center = |s: &SimplePolygon| {
+        let bbox = s.bbox();
+        (
+            (bbox.x_min + bbox.x_max) / 2.0,
+            (bbox.y_min + bbox.y_max) / 2.0,
+        )
+    };
+    let center_a = center(&a.shape);
+
+    let (b_transf, b_shape, allowed_rotation) = relative_rotations
+        .into_iter()
+        .filter_map(|r_b| {
+            let allowed_rotation = cluster_rotation(&a.allowed_rotation, &b.allowed_rotation, r_b)?;
+            Some((r_b, allowed_rotation))
+        })
+        .flat_map(|(r_b, allowed_rotation)| {
+            //start sliding with the centers of both shapes aligned
+            let rotated = b
+                .shape
+                .transform_clone(&DTransformation::new(r_b, (0.0, 0.0)).compose());
+            let center_b = center(&rotated);
+            let start = (center_a.0 - center_b.0, center_a.1 - center_b.1);
+            (0..N_PAIR_DIRECTIONS).filter_map(move |k| {
+                let angle = 2.0 * PI * k as fsize / N_PAIR_DIRECTIONS as fsize;
+                let direction = (angle.cos(), angle.sin());
+                let moving = rotated.transform_clone(&Transformation::from_translation(start));
+                let t = lattice::slide_until_free(&a.shape, &moving, direction)?;
+                let translation = (start.0 + t * direction.0, start.1 + t * direction.1);
+                let b_transf = DTransformation::new(r_b, translation);
+                let b_shape = b.shape.transform_clone(&b_transf.compose());
+                Some((b_transf, b_shape, allowed_rotation.clone()))
+            })
+        })
+        .min_by(|(_, s1, _), (_, s2, _)| {
+            let area = |s: &SimplePolygon| {
+                convex_hull_area(
+                    &a.shape
+                        .points
+                        .iter()
+                        .chain(s.points.iter())
+                        .copied()
+                        .collect_vec(),
+                )
+            };
+            area(s1).partial_cmp(&area(s2)).unwrap()
+        })?;
+
+    let points = polygon_holes::bridge_polygons(a.shape.points.clone(), b_shape.points);
+    //the cluster needs at least the poles of its most demanding member
+    let surrogate_config = match b.surrogate_config.max_poles > a.surrogate_config.max_poles {
+        true => b.surrogate_config,
+        false => a.surrogate_config,
+    };
+    //the id is assigned once the cluster is selected
+    let mut item = Item::new(
+        usize::MAX,
+        SimplePolygon::new(points),
+        allowed_rotation,
+        a.base_quality,
+        a.value + b.value,
+        Transformation::empty(),
+        surrogate_config,
+    );
+    item.original_area = a.original_area + b.original_area;
+    item.curve_deviation = fsize::max(a.curve_deviation, b.curve_deviation);
+    item.material = a.material.clone();
+    item.shortfall_cost = a.shortfall_cost + b.shortfall_cost;
+
+    Some(Cluster {
+        item,
+        members: vec![(a.id, DTransformation::empty()), (b.id, b_transf)],
+        qty: 0,
+    })
+}
+
+/// Rotations of the cluster for which both members are placed in an allowed rotation, if `b` is rotated by `r_b` relative to `a`
+fn cluster_rotation(
+    a: &AllowedRotation,
+    b: &AllowedRotation,
+    r_b: fsize,
+) -> Option<AllowedRotation> {
+    let rotations = match (a, b) {
+        (AllowedRotation::Continuous, AllowedRotation::Continuous) => {
+            return Some(AllowedRotation::Continuous)
+        }
+        (AllowedRotation::Continuous, _) => {
+            rotation_list(b).into_iter().map(|r| r - r_b).collect_vec()
+        }
+        _ => rotation_list(a)
+            .into_iter()
+            .filter(|r| match b {
+                AllowedRotation::None => (r + r_b).rem_euclid(2.0 * PI).abs() < 1e-3,
+                _ => lattice::rotation_allowed(b, r + r_b),
+            })
+            .collect_vec(),
+    };
+    match rotations.is_empty() {
+        true => None,
+        false => Some(AllowedRotation::Discrete(rotations)),
+    }
+}
+
+/// Discrete set of rotations to consider for an item, quarter turns for continuously rotating items
+pub(crate) fn rotation_list(allowed_rotation: &AllowedRotation) -> Vec<fsize> {
+    match allowed_rotation {
+        AllowedRotation::None => vec![0.0],
+        AllowedRotation::Continuous => (0..4).map(|k| k as fsize * PI / 2.0).collect(),
+        AllowedRotation::Discrete(rotations) => rotations.clone(),
+        AllowedRotation::Explicit(transfs) => {
+            transfs.iter().map(|t| t.rotation()).dedup().collect()
+        }
+    }
+}
+
+fn single_density(item: &Item) -> fsize {
+    item.shape.area() / convex_hull_area(&item.shape.points)
+}
+
+fn convex_hull_area(points: &[Point]) -> fsize {
+    SimplePolygon::calculate_area(&convex_hull_from_points(points.to_vec())).abs()
+}
@@ -0,0 +1,94 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::fsize;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::geometry::primitives::point::Point;
+use jagua_rs::geometry::transformation::Transformation;
+use jagua_rs::io::json_solution::{JsonLayout, JsonSolution};
+use jagua_rs::util::units;
+
+/// Configuration of the order in which the placed items are cut, for thermal cutting processes (laser, plasma, oxy-fuel).
+/// Consecutively cut parts are kept far apart, so the heat does not concentrate in one region of the sheet and distort it.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct CutOrderConfig {
+    /// Number of most recently cut parts which are considered to be still hot.
+    /// Every next part is the one furthest away from the closest of these
+    pub heat_memory: usize,
+}
+
+/// Determines the cut order of every layout of the solution, stored in its `cut_order`
+pub fn assign_cut_orders(solution: &mut JsonSolution, instance: &Instance, config: CutOrderConfig) {
+    for (i, layout) in solution.layouts.iter_mut().enumerate() {
+        let centers = part_centers(layout, instance);
+        let order = cut_order(&centers, config);
+        info!(
+            "[CUT] layout {}: minimum distance between consecutively cut parts: {:.3} -> {:.3}",
+            i,
+            min_consecutive_distance(&centers, &(0..centers.len()).collect::<Vec<_>>()),
+            min_consecutive_distance(&centers, &order)
+        );
+        layout.cut_order = order;
+    }
+}
+
+/// Order in which the parts with the given centers are cut, as indices in `centers`.
+/// Starts with the first part, after which the part furthest away from the `heat_memory` most recently cut ones is cut next.
+/// Ties are broken by index
+pub fn cut_order(centers: &[Point], config: CutOrderConfig) -> Vec<usize> {
+    assert!(config.heat_memory > 0);
+    let mut order = Vec::with_capacity(centers.len());
+    let mut remaining = (0..centers.len()).collect::<Vec<_>>();
+    while !remaining.is_empty() {
+        let hot = &order[order.len().saturating_sub(config.heat_memory)..];
+        let heat_distance = |i: usize| {
+            hot.iter()
+                .map(|&j: &usize| centers[i].distance(centers[j]))
+                .fold(fsize::INFINITY, fsize::min)
+        };
+        let (pos, _) = remaining.iter().enumerate().fold(
+            (0, fsize::NEG_INFINITY),
+            |(best_pos, best_d), (pos, &i)| {
+                let d = heat_distance(i);
+                match d > best_d {
+                    true => (pos, d),
+                    false => (best_pos, best_d),
+                }
+            },
+        );
+        order.push(remaining.remove(pos));
+    }
+    order
+}
+
+/// Smallest distance between the centers of two consecutively cut parts
+pub fn min_consecutive_distance(centers: &[Point], order: &[usize]) -> fsize {
+    order
+        .windows(2)
+        .map(|w| centers[w[0]].distance(centers[w[1]]))
+        .fold(fsize::INFINITY, fsize::min)
+}
+
+/// Centers of the poles of inaccessibility of the placed items (the bulk of each part), in the coordinates of the input
+fn part_centers(layout: &JsonLayout, instance: &Instance) -> Vec<Point> {
+    let to_input = 1.0 / instance.unit_scale();
+    layout
+        .placed_items
+        .iter()
+        .map(|placed_item| {
+            let item = instance.item(placed_item.index);
+            let t = &placed_item.transformation;
+            let abs_transf =
+                Transformation::from_dt(&DTransformation::new(t.rotation, t.translation));
+            let center = item
+                .shape
+                .poi
+                .center
+                .transform_clone(&item.pretransform.clone().inverse());
+            units::scaled_point(center, to_input).transform_clone(&abs_transf)
+        })
+        .collect()
+}
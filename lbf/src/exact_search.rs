@@ -0,0 +1,295 @@
+use itertools::Itertools;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use jagua_rs::collision_detection::cd_engine::CDEngine;
+use jagua_rs::collision_detection::hazard::HazardEntity;
+use jagua_rs::collision_detection::hazard_filter;
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::placed_item::PItemKey;
+use jagua_rs::entities::placing_option::PlacingOption;
+use jagua_rs::entities::problems::problem::Problem;
+use jagua_rs::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+use jagua_rs::fsize;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_traits::{Shape, Transformable, TransformableFrom};
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+
+use crate::clusters;
+use crate::lbf_cost::{LBFPlacingCost, PlacementBias};
+use crate::lbf_optimizer;
+use crate::samplers::explicit_sampler;
+
+/// Number of coarse steps when compacting a candidate position towards an edge of the bin
+const N_COMPACTION_STEPS: usize = 16;
+/// Number of bisection iterations to refine a compacted position
+const N_BISECTION_STEPS: usize = 8;
+
+/// Configuration of the exhaustive search for the placement of the last few items
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct ExactSearchConfig {
+    /// The search is started once at most this many items remain to be placed
+    pub max_items: usize,
+    /// Number of candidate positions along each axis of a bin
+    pub grid_resolution: usize,
+    /// Maximum number of nodes in the search tree, after which the best placement found so far is used
+    pub max_nodes: usize,
+}
+
+/// Searches the placement of all `items` in the existing layouts of the problem, minimizing the largest LBF cost among them.
+/// Candidate positions are a grid over each bin (for every allowed rotation, quarter turns for continuously rotating items),
+/// compacted towards the packing direction and pruned by the CDE. The search tree is explored depth-first with branch-and-bound.
+///
+/// If a placement for all items is found, they are placed in the problem and `true` is returned.
+/// Otherwise, the problem is left unchanged.
+pub fn place_exact(
+    problem: &mut Problem,
+    items: &[&Item],
+    config: &ExactSearchConfig,
+    bias: PlacementBias,
+) -> bool {
+    let mut search = Search {
+        problem,
+        items,
+        config,
+        bias,
+        n_nodes: 0,
+        current: vec![],
+        best: None,
+    };
+    search.branch(None);
+
+    let (n_nodes, best) = (search.n_nodes, search.best);
+    match best {
+        Some((_, p_opts)) => {
+            for p_opt in p_opts {
+                let (l_index, _) = problem.place_item(p_opt);
+                info!(
+                    "[EXACT] placing item with id {} at [{}] in Layout {:?}",
+                    p_opt.item_id, p_opt.d_transf, l_index
+                );
+            }
+            info!(
+                "[EXACT] placed the remaining {} items ({} nodes explored)",
+                items.len(),
+                n_nodes
+            );
+            true
+        }
+        None => {
+            info!(
+                "[EXACT] no placement found for the remaining {} items ({} nodes explored)",
+                items.len(),
+                n_nodes
+            );
+            false
+        }
+    }
+}
+
+struct Search<'a> {
+    problem: &'a mut Problem,
+    items: &'a [&'a Item],
+    config: &'a ExactSearchConfig,
+    bias: PlacementBias,
+    n_nodes: usize,
+    /// Items placed at the current node of the search tree
+    current: Vec<(LayoutIndex, PItemKey, PlacingOption)>,
+    /// Best complete placement found so far, with its cost
+    best: Option<(LBFPlacingCost, Vec<PlacingOption>)>,
+}
+
+impl Search<'_> {
+    fn branch(&mut self, cost: Option<LBFPlacingCost>) {
+        let depth = self.current.len();
+        if depth == self.items.len() {
+            let cost = cost.expect("no items placed");
+            if self.best.as_ref().is_none_or(|(best, _)| cost < *best) {
+                let p_opts = self.current.iter().map(|(_, _, p_opt)| *p_opt).collect();
+                self.best = Some((cost, p_opts));
+            }
+            return;
+        }
+        if self.n_nodes >= self.config.max_nodes {
+            return;
+        }
+        self.n_nodes += 1;
+
+        let item = self.items[depth];
+        let candidates = self
+            .problem
+            .layout_indices()
+            .collect_vec()
+            .into_iter()
+            .filter(|layout_idx| lbf_optimizer::can_place_in(self.problem, item, *layout_idx))
+            .flat_map(|layout_idx| {
+                candidates(self.problem, layout_idx, item, self.config, self.bias)
+            })
+            .sorted_by_key(|(_, c)| *c)
+            .collect_vec();
+
+        for (p_opt, c) in candidates {
+            let cost = cost.map_or(c, |cost| cost.max(c));
+            if matches!(&self.best, Some((best, _)) if cost >= *best) {
+                //candidates are sorted, none of the remaining ones can improve on the best
+                break;
+            }
+            let (l_index, pik) = self.problem.place_item(p_opt);
+            self.current.push((l_index, pik, p_opt));
+            self.branch(Some(cost));
+            let (l_index, pik, _) = self.current.pop().unwrap();
+            self.problem.remove_item(l_index, pik, true);
+        }
+    }
+}
+
+/// All valid placements of the item on a grid over the layout, compacted along the [`PlacementBias::compaction_directions`]
+fn candidates(
+    problem: &Problem,
+    layout_idx: LayoutIndex,
+    item: &Item,
+    config: &ExactSearchConfig,
+    bias: PlacementBias,
+) -> Vec<(PlacingOption, LBFPlacingCost)> {
+    let layout = problem.get_layout(layout_idx);
+    let cde = layout.cde();
+    let irrel_hazards = match item.hazard_filter.as_ref() {
+        None => vec![],
+        Some(hf) => hazard_filter::generate_irrelevant_hazards(hf, cde.all_hazards()),
+    };
+    let bin_bbox = layout.bin.bbox();
+    let mut buffer = (*item.shape).clone();
+    let resolution = config.grid_resolution.max(2);
+
+    //items restricted to a list of transformations are not moved over a grid
+    if let Some(transfs) = explicit_sampler::explicit_transformations(item, &layout.bin) {
+        return transfs
+            .into_iter()
+            .filter_map(|d_transf| {
+                if collides(cde, item, &mut buffer, &irrel_hazards, &d_transf) {
+                    return None;
+                }
+                buffer.transform_from(&item.shape, &d_transf.compose());
+                let p_opt = PlacingOption {
+                    layout_idx,
+                    item_id: item.id,
+                    d_transf,
+                };
+                Some((p_opt, LBFPlacingCost::from_shape(&buffer, bias, &bin_bbox)))
+            })
+            .sorted_by_key(|(_, c)| *c)
+            .collect();
+    }
+
+    let mut candidates = vec![];
+    for rotation in clusters::rotation_list(&item.allowed_rotation) {
+        //range of translations which keep the bounding box of the item inside the bin
+        let rotated_bbox = item
+            .shape
+            .transform_clone(&DTransformation::new(rotation, (0.0, 0.0)).compose())
+            .bbox();
+        let (x_min, x_max) = (
+            bin_bbox.x_min - rotated_bbox.x_min,
+            bin_bbox.x_max - rotated_bbox.x_max,
+        );
+        let (y_min, y_max) = (
+            bin_bbox.y_min - rotated_bbox.y_min,
+            bin_bbox.y_max - rotated_bbox.y_max,
+        );
+        if x_min > x_max || y_min > y_max {
+            continue;
+        }
+        let grid = |min: fsize, max: fsize, i: usize| {
+            min + (max - min) * i as fsize / (resolution - 1) as fsize
+        };
+
+        for (i, j) in (0..resolution).cartesian_product(0..resolution) {
+            let d_transf =
+                DTransformation::new(rotation, (grid(x_min, x_max, i), grid(y_min, y_max, j)));
+            if collides(cde, item, &mut buffer, &irrel_hazards, &d_transf) {
+                continue;
+            }
+            //compact the position: e.g. left, towards the gravity and left again
+            let d_transf =
+                bias.compaction_directions()
+                    .into_iter()
+                    .fold(d_transf, |dt, direction| {
+                        let (tx, ty) = dt.translation();
+                        let max_distance = match direction {
+                            (-1.0, _) => tx - x_min,
+                            (1.0, _) => x_max - tx,
+                            (_, -1.0) => ty - y_min,
+                            (_, 1.0) => y_max - ty,
+                            _ => 0.0,
+                        };
+                        let d = slide_to_contact(max_distance, |d| {
+                            collides(
+                                cde,
+                                item,
+                                &mut buffer,
+                                &irrel_hazards,
+                                &DTransformation::new(
+                                    rotation,
+                                    (tx + d * direction.0, ty + d * direction.1),
+                                ),
+                            )
+                        });
+                        DTransformation::new(rotation, (tx + d * direction.0, ty + d * direction.1))
+                    });
+            buffer.transform_from(&item.shape, &d_transf.compose());
+            let p_opt = PlacingOption {
+                layout_idx,
+                item_id: item.id,
+                d_transf,
+            };
+            candidates.push((p_opt, LBFPlacingCost::from_shape(&buffer, bias, &bin_bbox)));
+        }
+    }
+
+    //many grid positions are compacted to the same position
+    candidates
+        .into_iter()
+        .sorted_by_key(|(_, c)| *c)
+        .dedup_by(|(p1, c1), (p2, c2)| c1 == c2 && p1.d_transf.rotation() == p2.d_transf.rotation())
+        .collect()
+}
+
+/// Largest distance in `[0, max_distance]` (found by coarse steps and bisection) which can be travelled without colliding
+pub(crate) fn slide_to_contact(
+    max_distance: fsize,
+    mut collides: impl FnMut(fsize) -> bool,
+) -> fsize {
+    if max_distance <= 0.0 {
+        return 0.0;
+    }
+    let step = max_distance / N_COMPACTION_STEPS as fsize;
+    let first_colliding = (1..=N_COMPACTION_STEPS).find(|i| collides(*i as fsize * step));
+    match first_colliding {
+        None => max_distance,
+        Some(i) => {
+            let (mut lower, mut upper) = ((i - 1) as fsize * step, i as fsize * step);
+            for _ in 0..N_BISECTION_STEPS {
+                let mid = (lower + upper) / 2.0;
+                match collides(mid) {
+                    true => upper = mid,
+                    false => lower = mid,
+                }
+            }
+            lower
+        }
+    }
+}
+
+fn collides(
+    cde: &CDEngine,
+    item: &Item,
+    buffer: &mut SimplePolygon,
+    irrel_hazards: &[HazardEntity],
+    d_transf: &DTransformation,
+) -> bool {
+    let transform = d_transf.compose();
+    cde.surrogate_collides(item.shape.surrogate(), &transform, irrel_hazards) || {
+        buffer.transform_from(&item.shape, &transform);
+        cde.poly_collides(buffer, irrel_hazards)
+    }
+}
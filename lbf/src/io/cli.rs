@@ -1,22 +1,258 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
+use jagua_rs::fsize;
+use jagua_rs::util::config::Preset;
 use log::LevelFilter;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(
+    author,
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    arg_required_else_help = true
+)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub solve_args: Option<SolveArgs>,
+    #[arg(
+        short,
+        long,
+        global = true,
+        value_name = "[off, error, warn, info, debug, trace]",
+        default_value = "info"
+    )]
+    pub log_level: LevelFilter,
+    /// Records the tracing spans as folded stacks in this file, to draw a flamegraph of where the time is spent
+    #[arg(long, global = true, value_name = "FILE")]
+    pub trace_flame: Option<PathBuf>,
+    /// Most detailed level of the recorded spans: `info` for parsing and placements,
+    /// `debug` to include surrogate generation and every collision query (slow)
+    #[arg(
+        long,
+        global = true,
+        value_name = "[info, debug, trace]",
+        default_value = "info",
+        requires = "trace_flame"
+    )]
+    pub trace_level: tracing::Level,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Parses and simplifies an instance, and writes the simplified instance back to a file, without solving it
+    Simplify(SimplifyArgs),
+    /// Lists the contents of DXF files and previews the polygons that would be extracted from them
+    InspectDxf(InspectDxfArgs),
+    /// Writes an SVG per item overlaying its simplified contour on its original one, without solving the instance
+    PreviewSimplification(PreviewSimplificationArgs),
+    /// Checks the feasibility and usage of a solution, for example one produced by another nester, without solving the instance
+    Verify(VerifyArgs),
+    /// Solves instances with every combination of a set of config parameters, and writes the outcome of every run to a CSV file
+    Sweep(SweepArgs),
+    /// Serves the solver over HTTP, with the same solve API as the GUI backend, so it can run as a sidecar of another service
+    Serve(ServeArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SolveArgs {
+    /// JSON, GeoJSON or ESICUP XML instance, a zip archive of a JSON instance and its DXF/SVG files,
+    /// or a folder of DXF files (see the README for their conventions)
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        required_unless_present = "from_repro"
+    )]
+    pub input_file: Option<PathBuf>,
+    #[arg(short, long, value_name = "FOLDER")]
+    pub solution_folder: PathBuf,
     #[arg(short, long, value_name = "FILE")]
+    pub config_file: Option<PathBuf>,
+    /// Named configuration to use instead of a config file
+    #[arg(
+        short,
+        long,
+        value_name = "[fast, balanced, quality]",
+        conflicts_with = "config_file"
+    )]
+    pub preset: Option<Preset>,
+    /// Folder in which the DXF and SVG files of items are looked up when they are not next to the instance.
+    /// Can be given multiple times, the folders are searched in order
+    #[arg(long, value_name = "FOLDER")]
+    pub asset_folder: Vec<PathBuf>,
+    /// JSON file mapping the layers of DXF files to outlines, holes and quality zones of bins,
+    /// e.g. `{"Outline": ["CUT"], "Holes": ["HOLE"], "QualityZones": {"DEFECT": 0, "Q1": 1}}`
+    #[arg(long, value_name = "FILE")]
+    pub dxf_layers: Option<PathBuf>,
+    /// Folder in which preprocessed shapes are cached, so repeated runs on the same parts skip preprocessing
+    #[arg(long, value_name = "FOLDER")]
+    pub cache_folder: Option<PathBuf>,
+    /// Writes a zip archive with the instance, the config (with its seed), the referenced DXF files and the solver version,
+    /// from which the run can be reproduced with --from-repro
+    #[arg(long, value_name = "ZIP_FILE")]
+    pub export_repro: Option<PathBuf>,
+    /// Reruns the instance and config of an archive written by --export-repro.
+    /// Its contents are extracted to the `repro` subfolder of the solution folder
+    #[arg(
+        long,
+        value_name = "ZIP_FILE",
+        conflicts_with_all = ["input_file", "config_file", "preset"]
+    )]
+    pub from_repro: Option<PathBuf>,
+    /// Counts which stage of the collision detection engine decides every collision query,
+    /// and logs the totals after solving
+    #[arg(long)]
+    pub trace_collisions: bool,
+    /// Appends the statistics of the solve (run time, usage per layout, items placed and unplaced,
+    /// samples evaluated and collision queries) to this file. JSON lines if it ends with `.jsonl` or `.ndjson`, CSV otherwise
+    #[arg(long, value_name = "FILE")]
+    pub stats_file: Option<PathBuf>,
+    /// Records every placement sampled by the uniform and local search samplers,
+    /// and writes a heat map of their density and rejections per bin (`sol_<name>_heat_<bin>.svg`)
+    #[arg(long)]
+    pub sample_heat_map: bool,
+    /// Also writes the placements as a CSV file (one row per placed item), next to the JSON solution
+    #[arg(long)]
+    pub export_csv: bool,
+    /// Also writes a label record for every placed item, next to the JSON solution.
+    /// Uses a ZPL template unless `--label-template` is given
+    #[arg(long)]
+    pub export_labels: bool,
+    /// Text file with the template of a label record, see [`labels`](crate::io::label_export::labels) for the placeholders
+    #[arg(long, value_name = "FILE", requires = "export_labels")]
+    pub label_template: Option<PathBuf>,
+    /// Also writes the solution as a GeoJSON feature collection (`sol_<name>.geojson`), with a feature per bin and placed item
+    #[arg(long)]
+    pub export_geojson: bool,
+    /// Also writes every layout as a DXF drawing (`sol_<name>_<i>.dxf`), with every item on its own layer
+    #[arg(long)]
+    pub export_dxf: bool,
+    /// Groups the items in the DXF drawings on one layer per required quality, instead of one layer per item
+    #[arg(long, requires = "export_dxf")]
+    pub dxf_layer_per_quality: bool,
+    /// Also writes every layout as G-code (`sol_<name>_<i>.nc`) for laser and plasma cutters, configured by `gcode` in the config.
+    /// Parts are cut in the `cut_order` of the layout if one is assigned
+    #[arg(long)]
+    pub export_gcode: bool,
+    /// Also writes every layout as a PNG image of WIDTH pixels wide (`sol_<name>_<i>.png`, 1600 if no width is given),
+    /// for viewers which cannot render SVG
+    #[cfg(feature = "png")]
+    #[arg(long, value_name = "WIDTH", num_args = 0..=1, default_missing_value = "1600")]
+    pub export_png: Option<u32>,
+    /// Also writes a PDF report of the solution (`sol_<name>.pdf`): a summary page with the usage, item quantities and waste,
+    /// and a page with the drawing of every layout
+    #[cfg(feature = "pdf")]
+    #[arg(long)]
+    pub export_pdf: bool,
+    /// Imports the written solution back and validates every placed item against the instance
+    /// (overlaps, placements outside the bin or in its holes, and zones of insufficient quality).
+    /// Exits with an error after writing the outputs if the solution is infeasible
+    #[arg(long)]
+    pub validate: bool,
+    /// Folder of reference solutions (`<instance name>.json`), such as best known solutions from the literature.
+    /// Logs the gap between the solution and the reference solution of the instance, if there is one
+    #[arg(long, value_name = "FOLDER")]
+    pub reference_folder: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct SimplifyArgs {
+    /// Max deviation from the original polygon area as a fraction of the original area
+    #[arg(short, long)]
+    pub tolerance: fsize,
+    #[arg(value_name = "INPUT_FILE")]
     pub input_file: PathBuf,
+    #[arg(value_name = "OUTPUT_FILE")]
+    pub output_file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct InspectDxfArgs {
+    /// A DXF file or a folder containing DXF files
+    #[arg(value_name = "FILE_OR_FOLDER")]
+    pub path: PathBuf,
+    /// Folder to write an SVG preview of every extracted polygon to
     #[arg(short, long, value_name = "FOLDER")]
-    pub solution_folder: PathBuf,
+    pub svg_folder: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct PreviewSimplificationArgs {
+    #[arg(value_name = "INPUT_FILE")]
+    pub input_file: PathBuf,
+    /// Folder to write the SVG of every item to
+    #[arg(value_name = "SVG_FOLDER")]
+    pub svg_folder: PathBuf,
+    /// Config of which the simplification (`poly_simpl_tolerance` or `geometric_fidelity`) is previewed
     #[arg(short, long, value_name = "FILE")]
     pub config_file: Option<PathBuf>,
+    /// Named configuration to use instead of a config file
     #[arg(
         short,
         long,
-        value_name = "[off, error, warn, info, debug, trace]",
-        default_value = "info"
+        value_name = "[fast, balanced, quality]",
+        conflicts_with = "config_file"
     )]
-    pub log_level: LevelFilter,
+    pub preset: Option<Preset>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// JSON instance, or a folder of DXF files
+    #[arg(short, long, value_name = "FILE")]
+    pub instance: PathBuf,
+    /// Solution in our JSON schema, or a text file with one `<sheet> <bin> <item> <x> <y> <rotation>` placement per line
+    #[arg(short, long, value_name = "FILE")]
+    pub solution: PathBuf,
+    /// JSON file mapping the layers of DXF files, as used to solve the instance
+    #[arg(long, value_name = "FILE")]
+    pub dxf_layers: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct SweepArgs {
+    /// JSON instance, or a folder of DXF files. Can be repeated to sweep several instances
+    #[arg(short, long, value_name = "FILE", required = true)]
+    pub instance: Vec<PathBuf>,
+    /// JSON file with the values of every swept parameter, keyed by its path in the config
+    /// (e.g. `{"n_samples": [1000, 5000], "prng_seed": [0, 1, 2], "cde_config.quadtree_depth": [4, 5]}`)
+    #[arg(short = 'r', long, value_name = "FILE")]
+    pub parameters: PathBuf,
+    /// Base config to which the swept parameters are applied
+    #[arg(short, long, value_name = "FILE")]
+    pub config_file: Option<PathBuf>,
+    /// Named configuration to use as base config instead of a config file
+    #[arg(
+        short,
+        long,
+        value_name = "[fast, balanced, quality]",
+        conflicts_with = "config_file"
+    )]
+    pub preset: Option<Preset>,
+    /// CSV file to write the results to, one row per run
+    #[arg(short, long, value_name = "FILE")]
+    pub output: PathBuf,
+    /// Runs the configs of an instance in parallel, instead of one by one
+    #[arg(long)]
+    pub parallel: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Port to listen on
+    #[arg(long, default_value = "8000")]
+    pub port: u16,
+    /// Address to bind to, use 0.0.0.0 to accept connections from other hosts
+    #[arg(long, default_value = "127.0.0.1")]
+    pub address: std::net::IpAddr,
+    /// Folder the solutions are written to, in a subfolder per request
+    #[arg(short, long, value_name = "FOLDER", default_value = "solutions")]
+    pub solution_folder: PathBuf,
+    /// Number of threads shared by all requests
+    #[arg(long, default_value = "4")]
+    pub n_threads: usize,
 }
@@ -0,0 +1,46 @@
+use std::fmt::Write;
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::io::json_solution::{JsonContainer, JsonSolution};
+
+pub const CSV_HEADER: &str = "item_id,name,layout,bin,x,y,rotation,mirrored";
+
+/// Flat table of all placements in the solution, one row per placed item.
+/// Coordinates and rotations (in degrees) are those of the JSON solution, in the coordinates of the input.
+/// `bin` is the index of the bin in the input and empty for strip packing.
+/// `mirrored` is always `false`, items are never mirrored
+pub fn placements_csv(json_solution: &JsonSolution, instance: &Instance) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+    for (layout_idx, json_layout) in json_solution.layouts.iter().enumerate() {
+        let bin = match json_layout.container {
+            JsonContainer::Bin { index } => index.to_string(),
+            JsonContainer::Strip { .. } => String::new(),
+        };
+        for placed_item in &json_layout.placed_items {
+            let name = instance.item(placed_item.index).name.as_deref();
+            let (x, y) = placed_item.transformation.translation;
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},false",
+                placed_item.index,
+                escape(name.unwrap_or_default()),
+                layout_idx,
+                bin,
+                x,
+                y,
+                placed_item.transformation.rotation.to_degrees()
+            )
+            .unwrap();
+        }
+    }
+    csv
+}
+
+/// Quotes a field if it contains a separator, a quote or a line break
+pub(crate) fn escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
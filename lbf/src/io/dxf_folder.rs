@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use dxf::Drawing;
+use itertools::Itertools;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use jagua_rs::geometry::geo_traits::Shape;
+use jagua_rs::geometry::primitives::arc_polygon::ArcPolygon;
+use jagua_rs::geometry::primitives::point::Point;
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+use jagua_rs::io::dxf_layers::DxfLayerMapping;
+use jagua_rs::io::dxf_parse;
+use jagua_rs::io::json_instance::{
+    JsonBin, JsonInstance, JsonItem, JsonPoly, JsonQualityZone, JsonShape, JsonSimplePoly,
+    JsonStrip,
+};
+use jagua_rs::io::parser;
+use jagua_rs::util::config::GeometricFidelity;
+use jagua_rs::util::polygon_simplification::PolySimplMode;
+
+use crate::io;
+use crate::io::error::IoError;
+
+/// Optional manifest in a folder of DXF files
+pub const MANIFEST_FILE: &str = "instance.json";
+
+/// DXF files of which the name starts with one of these prefixes (case-insensitive) define a bin instead of an item
+pub const BIN_FILE_PREFIXES: [&str; 2] = ["bin", "sheet"];
+
+/// Partial instance which complements the DXF files of a folder, see [`folder_instance`]
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonFolderManifest {
+    /// Name of the instance, defaults to the name of the folder
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+    /// Bins in addition to the ones defined by DXF files
+    #[serde(rename = "Objects")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bins: Option<Vec<JsonBin>>,
+    /// Strip, for folders without bins
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub strip: Option<JsonStrip>,
+    /// Items replacing the ones derived from the DXF file they reference (e.g. to set the demand or allowed orientations,
+    /// or to read multiple items from a single file by layer), and items which are not defined by a DXF file of the folder
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub items: Vec<JsonItem>,
+}
+
+/// Builds an instance from a folder of DXF files, without a JSON instance referencing them.
+/// Every `<name>.dxf` file defines an item, except for files starting with one of the [`BIN_FILE_PREFIXES`], which define a bin.
+/// A `_x<n>` suffix in the file name sets the demand of an item (`bracket_x12.dxf`) or the stock of a bin (`sheet_x3.dxf`).
+/// The demand of items without suffix is read from the metadata of the DXF file by the `Parser`.
+/// Bins are the outline of their file (tessellated inwards), at a cost equal to their area.
+/// The `mapping` determines from which layers the outlines and holes of the bins are read, and which layers define their quality zones.
+/// Items are read by the `Parser`, which should be given the same mapping.
+/// A [`MANIFEST_FILE`] in the folder can name the instance, add bins or a strip and override items, see [`JsonFolderManifest`].
+/// The DXF paths of the instance are relative to the folder, which should be used as the assets folder of the `Parser`.
+pub fn folder_instance(
+    folder: &Path,
+    fidelity: GeometricFidelity,
+    mapping: &DxfLayerMapping,
+) -> Result<JsonInstance, IoError> {
+    let manifest_path = folder.join(MANIFEST_FILE);
+    let manifest: JsonFolderManifest = match manifest_path.is_file() {
+        true => io::read_json_file(&manifest_path)?,
+        false => JsonFolderManifest::default(),
+    };
+
+    let io_err = |source: std::io::Error| IoError::Io {
+        path: folder.to_path_buf(),
+        source,
+    };
+    let dxf_paths = fs::read_dir(folder)
+        .map_err(io_err)?
+        .map(|entry| entry.map(|e| e.path()).map_err(io_err))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("dxf"))
+        })
+        .sorted()
+        .collect_vec();
+
+    let (bin_paths, item_paths): (Vec<PathBuf>, Vec<PathBuf>) =
+        dxf_paths.into_iter().partition(|path| {
+            let stem = file_stem(path).to_ascii_lowercase();
+            BIN_FILE_PREFIXES
+                .iter()
+                .any(|prefix| stem.starts_with(prefix))
+        });
+
+    let mut items = vec![];
+    for path in &item_paths {
+        let file_name = file_name(path);
+        let overrides = manifest
+            .items
+            .iter()
+            .filter(|item| item.dxf.as_deref() == Some(file_name))
+            .cloned()
+            .collect_vec();
+        match overrides.is_empty() {
+            true => {
+                let (name, demand) = split_count_suffix(file_stem(path));
+                items.push(dxf_item(file_name, name, demand));
+            }
+            false => items.extend(overrides),
+        }
+    }
+    //items of the manifest which are not defined by a DXF file of the folder
+    items.extend(
+        manifest
+            .items
+            .iter()
+            .filter(|item| {
+                !item_paths
+                    .iter()
+                    .any(|path| item.dxf.as_deref() == Some(file_name(path)))
+            })
+            .cloned(),
+    );
+
+    let dxf_bins = bin_paths
+        .iter()
+        .map(|path| dxf_bin(path, fidelity, mapping))
+        .collect::<Result<Vec<_>, _>>()?;
+    let bins = match (dxf_bins.is_empty(), manifest.bins) {
+        (true, None) => None,
+        (_, manifest_bins) => Some(
+            dxf_bins
+                .into_iter()
+                .chain(manifest_bins.into_iter().flatten())
+                .collect_vec(),
+        ),
+    };
+    if bins.is_some() == manifest.strip.is_some() {
+        return Err(IoError::InvalidFile {
+            path: folder.to_path_buf(),
+            reason: format!(
+                "folder should define either bins (DXF files starting with {:?}, or `Objects` in {}) or a `Strip` in {}",
+                BIN_FILE_PREFIXES, MANIFEST_FILE, MANIFEST_FILE
+            ),
+        });
+    }
+
+    let name = manifest
+        .name
+        .unwrap_or_else(|| file_name(folder).to_string());
+    info!(
+        "[FOLDER] instance {} from {}: {} items from {} DXF files, {} bins from {} DXF files",
+        name,
+        folder.display(),
+        items.len(),
+        item_paths.len(),
+        bins.as_ref().map_or(0, |b| b.len()),
+        bin_paths.len()
+    );
+
+    Ok(JsonInstance {
+        name,
+        items,
+        bins,
+        strip: manifest.strip,
+        units: None,
+        assets: BTreeMap::new(),
+    })
+}
+
+/// Splits a `_x<n>` suffix off a file stem: `bracket_x12` into `bracket` and 12
+pub fn split_count_suffix(stem: &str) -> (&str, Option<u64>) {
+    let count = stem.rsplit_once('_').and_then(|(name, suffix)| {
+        let count = suffix.strip_prefix(['x', 'X'])?.parse().ok()?;
+        Some((name, count))
+    });
+    match count {
+        Some((name, count)) => (name, Some(count)),
+        None => (stem, None),
+    }
+}
+
+fn dxf_item(file_name: &str, name: &str, demand: Option<u64>) -> JsonItem {
+    JsonItem {
+        name: Some(name.to_string()),
+        demand,
+        dxf: Some(file_name.to_string()),
+        dxf_layer: None,
+        dxf_block: None,
+        svg: None,
+        svg_id: None,
+        allowed_orientations: None,
+        allowed_transformations: None,
+        shape: None,
+        value: None,
+        shortfall_cost: None,
+        base_quality: None,
+        material: None,
+        surrogate: None,
+        copies: None,
+    }
+}
+
+fn dxf_bin(
+    path: &Path,
+    fidelity: GeometricFidelity,
+    mapping: &DxfLayerMapping,
+) -> Result<JsonBin, IoError> {
+    let invalid = |reason: String| IoError::InvalidFile {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let drawing = Drawing::load_file(path).map_err(|err| invalid(err.to_string()))?;
+    let outline = dxf_parse::find_outline(&drawing, mapping, None, None, fidelity)
+        .ok_or_else(|| invalid("no closed contour found".into()))?;
+    let holes = dxf_parse::find_holes(
+        &drawing,
+        mapping,
+        None,
+        None,
+        (outline.0, &outline.1),
+        fidelity,
+    );
+    let zones = dxf_parse::find_quality_zones(&drawing, mapping, None, fidelity);
+
+    //the bin is deflated and its holes and zones inflated, so items placed in the tessellated bin are in the exact one
+    let to_json = |arc_polygon: &ArcPolygon, mode: PolySimplMode| {
+        let points = parser::arc_polygon_to_points(arc_polygon, fidelity, mode);
+        JsonSimplePoly(points.iter().map(|p| (p.0, p.1)).collect())
+    };
+    let outer = to_json(&outline.1, PolySimplMode::Deflate);
+    let inner = holes
+        .iter()
+        .map(|hole| to_json(hole, PolySimplMode::Inflate))
+        .collect_vec();
+    let area = SimplePolygon::new(outer.0.iter().map(|&(x, y)| Point(x, y)).collect()).area();
+    let (name, stock) = split_count_suffix(file_stem(path));
+
+    Ok(JsonBin {
+        name: Some(name.to_string()),
+        material: None,
+        max_usage: None,
+        cost: area.round() as u64,
+        stock,
+        shape: Some(match inner.is_empty() {
+            true => JsonShape::SimplePolygon(outer),
+            false => JsonShape::Polygon(JsonPoly { outer, inner }),
+        }),
+        zones: zones
+            .iter()
+            .map(|(quality, zone)| JsonQualityZone {
+                quality: *quality,
+                shape: JsonShape::SimplePolygon(to_json(zone, PolySimplMode::Inflate)),
+            })
+            .collect(),
+        temporary_zones: vec![],
+    })
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_else(|| panic!("invalid file name: {}", path.display()))
+}
+
+fn file_stem(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_else(|| panic!("invalid file name: {}", path.display()))
+}
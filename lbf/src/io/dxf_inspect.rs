@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use dxf::entities::EntityType;
+use dxf::enums::Units;
+use dxf::Drawing;
+use itertools::Itertools;
+use log::{info, warn};
+use svg::node::element::{Group, Title};
+use svg::Document;
+
+use jagua_rs::fsize;
+use jagua_rs::geometry::geo_traits::Shape;
+use jagua_rs::geometry::primitives::arc_polygon::ArcPolygon;
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+use jagua_rs::io::dxf_layers::DxfLayerMapping;
+use jagua_rs::io::dxf_parse::{self, DxfItemMetadata};
+use jagua_rs::io::parser;
+use jagua_rs::util::config::GeometricFidelity;
+use jagua_rs::util::polygon_cleanup;
+use jagua_rs::util::polygon_simplification::PolySimplMode;
+
+use crate::io::svg_export;
+
+/// Summary of the contents of a DXF file, and of the polygon that would be extracted from it by the `Parser`
+pub struct DxfInspection {
+    pub path: PathBuf,
+    pub units: Units,
+    /// Names of the entity types found in the file, grouped per layer
+    pub entities_per_layer: BTreeMap<String, Vec<String>>,
+    /// Number of polylines in every block which contains any, these can be referenced by items using `DxfBlock`
+    pub poly_lines_per_block: BTreeMap<String, usize>,
+    /// Descriptions of all contours in the file which are not closed
+    pub open_contours: Vec<String>,
+    /// The polygon extracted from the file (as by the `Parser`), if any could be constructed
+    pub polygon: Option<SimplePolygon>,
+    /// The exact contour of the extracted polygon, if it contains arcs
+    pub arc_shape: Option<ArcPolygon>,
+    /// Item metadata embedded in the entity of the extracted polygon
+    pub metadata: Option<DxfItemMetadata>,
+    /// Remarks about the extraction of the polygon
+    pub info: Vec<String>,
+    /// Reasons why the extracted polygon is missing or might not match the intended shape
+    pub issues: Vec<String>,
+}
+
+/// Inspects a single DXF file
+pub fn inspect_dxf(path: &Path) -> DxfInspection {
+    let drawing = Drawing::load_file(path)
+        .unwrap_or_else(|err| panic!("could not load dxf file: {}, {}", path.display(), err));
+
+    let mut entities_per_layer: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut open_contours = vec![];
+    let mut info = vec![];
+    let mut issues = vec![];
+
+    for (i, e) in drawing.entities().enumerate() {
+        entities_per_layer
+            .entry(e.common.layer.clone())
+            .or_default()
+            .push(entity_type_name(&e.specific));
+
+        match &e.specific {
+            EntityType::LwPolyline(lwp) => {
+                let closed_by_vertex = match (lwp.vertices.first(), lwp.vertices.last()) {
+                    (Some(f), Some(l)) => f.x == l.x && f.y == l.y,
+                    _ => false,
+                };
+                if !lwp.get_is_closed() && !closed_by_vertex {
+                    open_contours.push(format!(
+                        "entity #{} (LwPolyline, layer \"{}\", {} vertices)",
+                        i,
+                        e.common.layer,
+                        lwp.vertices.len()
+                    ));
+                }
+            }
+            EntityType::Polyline(pl) if !pl.get_is_closed() => {
+                open_contours.push(format!(
+                    "entity #{} (Polyline, layer \"{}\")",
+                    i, e.common.layer
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    //curves (lines, arcs and splines) are only open if they are not connected into a closed contour
+    let fidelity = GeometricFidelity::default();
+    let (_, loose_curves) = dxf_parse::closed_contours(drawing.entities(), fidelity);
+    for (i, e) in drawing.entities().enumerate() {
+        if loose_curves.iter().any(|c| std::ptr::eq(*c, e)) {
+            open_contours.push(format!(
+                "entity #{} ({}, layer \"{}\"), not connected to a closed contour",
+                i,
+                entity_type_name(&e.specific),
+                e.common.layer
+            ));
+        }
+    }
+
+    let poly_lines_per_block = drawing
+        .blocks()
+        .map(|b| {
+            let n_poly_lines = b
+                .entities
+                .iter()
+                .filter(|e| matches!(e.specific, EntityType::LwPolyline(_)))
+                .count();
+            (b.name.clone(), n_poly_lines)
+        })
+        .filter(|(_, n_poly_lines)| *n_poly_lines > 0)
+        .collect();
+
+    let poly_lines = drawing
+        .entities()
+        .filter_map(|e| match &e.specific {
+            EntityType::LwPolyline(lwp) => Some((e, lwp)),
+            _ => None,
+        })
+        .collect_vec();
+
+    if poly_lines.len() > 1 {
+        issues.push(format!(
+            "{} polylines found, unless items select one by layer or block, only the first one is used",
+            poly_lines.len()
+        ));
+    }
+
+    let outline =
+        dxf_parse::find_outline(&drawing, &DxfLayerMapping::default(), None, None, fidelity);
+    let metadata = match outline
+        .as_ref()
+        .map(|(e, _)| DxfItemMetadata::from_entity(e))
+    {
+        Some(Ok(metadata)) => Some(metadata),
+        Some(Err(reason)) => {
+            issues.push(format!("invalid item metadata: {}", reason));
+            None
+        }
+        None => None,
+    };
+
+    let mut arc_shape = None;
+    let polygon = match outline {
+        None => {
+            issues.push("no closed contour found, no polygon can be extracted".to_string());
+            None
+        }
+        Some((e, arc_polygon)) => {
+            if !matches!(e.specific, EntityType::LwPolyline(_)) {
+                info.push(format!(
+                    "no LwPolyline found, the largest closed contour is used (starting at a {} on layer \"{}\")",
+                    entity_type_name(&e.specific),
+                    e.common.layer
+                ));
+            }
+            let points =
+                parser::arc_polygon_to_points(&arc_polygon, fidelity, PolySimplMode::Inflate);
+            if arc_polygon.has_arcs() {
+                let bbox = SimplePolygon::generate_bounding_box(&arc_polygon.points);
+                info.push(format!(
+                    "contour contains {} arc segments, these are tessellated for collision detection (max deviation: {:.3})",
+                    arc_polygon.bulges.iter().filter(|b| **b != 0.0).count(),
+                    fidelity.max_deviation(fsize::max(bbox.width(), bbox.height()))
+                ));
+                arc_shape = Some(arc_polygon);
+            }
+            let min_edge_length = polygon_cleanup::min_edge_length(&points);
+            let (points, cleanup_stats) = polygon_cleanup::clean_points(points, min_edge_length);
+            if cleanup_stats.total() > 0 {
+                issues.push(format!(
+                    "removed {} duplicate, {} short edge and {} collinear vertices from the contour",
+                    cleanup_stats.n_duplicates,
+                    cleanup_stats.n_short_edges,
+                    cleanup_stats.n_collinear
+                ));
+            }
+            let n_unique = points.iter().unique().count();
+            if n_unique < 3 {
+                issues.push(format!(
+                    "contour has {} unique vertices, at least 3 are required",
+                    n_unique
+                ));
+                None
+            } else if n_unique != points.len() {
+                issues.push(format!(
+                    "contour contains {} non-consecutive duplicate vertices",
+                    points.len() - n_unique
+                ));
+                None
+            } else if SimplePolygon::calculate_area(&points) == 0.0 {
+                issues.push("contour encloses no area".to_string());
+                None
+            } else {
+                Some(SimplePolygon::new(points))
+            }
+        }
+    };
+
+    DxfInspection {
+        path: path.to_path_buf(),
+        units: drawing.header.default_drawing_units,
+        entities_per_layer,
+        poly_lines_per_block,
+        open_contours,
+        polygon,
+        arc_shape,
+        metadata,
+        info,
+        issues,
+    }
+}
+
+/// Logs the contents of a `DxfInspection`
+pub fn log_inspection(inspection: &DxfInspection) {
+    info!("[DXF] {}", inspection.path.display());
+    info!("[DXF] units: {:?}", inspection.units);
+    for (layer, entities) in inspection.entities_per_layer.iter() {
+        let counts = entities
+            .iter()
+            .counts()
+            .into_iter()
+            .sorted()
+            .map(|(name, count)| format!("{}x {}", count, name))
+            .join(", ");
+        info!("[DXF] layer \"{}\": {}", layer, counts);
+    }
+    for (block, n_poly_lines) in inspection.poly_lines_per_block.iter() {
+        info!("[DXF] block \"{}\": {}x LwPolyline", block, n_poly_lines);
+    }
+    match &inspection.polygon {
+        Some(polygon) => {
+            let bbox = polygon.bbox();
+            info!(
+                "[DXF] extracted polygon: {} vertices, area: {:.3}, extents: [x_min: {:.3}, y_min: {:.3}, x_max: {:.3}, y_max: {:.3}]",
+                polygon.number_of_points(),
+                polygon.area(),
+                bbox.x_min,
+                bbox.y_min,
+                bbox.x_max,
+                bbox.y_max
+            );
+        }
+        None => warn!("[DXF] no polygon could be extracted"),
+    }
+    if let Some(metadata) = &inspection.metadata {
+        info!(
+            "[DXF] item metadata: demand: {:?}, allowed orientations: {:?}, value: {:?}",
+            metadata.demand, metadata.allowed_orientations, metadata.value
+        );
+    }
+    for remark in inspection.info.iter() {
+        info!("[DXF] {}", remark);
+    }
+    for open_contour in inspection.open_contours.iter() {
+        warn!("[DXF] open contour: {}", open_contour);
+    }
+    for issue in inspection.issues.iter() {
+        warn!("[DXF] {}", issue);
+    }
+}
+
+/// Draws the polygon extracted from a DXF file, if any
+pub fn inspection_to_svg(inspection: &DxfInspection) -> Option<Document> {
+    let polygon = inspection.polygon.as_ref()?;
+    let vbox = polygon.bbox().scale(1.05);
+    let stroke_width = fsize::min(vbox.width(), vbox.height()) * 0.002;
+
+    let group = Group::new()
+        .add(svg_export::data_to_path(
+            match &inspection.arc_shape {
+                Some(arc_shape) => svg_export::arc_polygon_data(arc_shape),
+                None => svg_export::simple_polygon_data(polygon),
+            },
+            &[
+                ("fill", "#D3D3D3"),
+                ("stroke", "black"),
+                ("stroke-width", &*format!("{}", stroke_width)),
+            ],
+        ))
+        .add(Title::new(format!(
+            "{}, {} vertices",
+            inspection.path.display(),
+            polygon.number_of_points()
+        )));
+
+    let document = Document::new()
+        .set(
+            "viewBox",
+            (vbox.x_min, vbox.y_min, vbox.width(), vbox.height()),
+        )
+        .add(group);
+
+    Some(document)
+}
+
+fn entity_type_name(entity_type: &EntityType) -> String {
+    //the name of the variant is the part of the debug representation before the data
+    let debug = format!("{:?}", entity_type);
+    debug
+        .split(['(', ' ', '{'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
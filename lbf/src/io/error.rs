@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use jagua_rs::io::parse_error::ParseError;
+
+/// Reason why an input (instance, config, solution or repro bundle) could not be read
+#[derive(Debug)]
+pub enum IoError {
+    /// A file or folder could not be read
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A JSON file, or a JSON string if `path` is undefined, could not be deserialized
+    Json {
+        path: Option<PathBuf>,
+        source: serde_json::Error,
+    },
+    /// The contents of a file or folder are invalid
+    InvalidFile { path: PathBuf, reason: String },
+    /// The config cannot be used for the instance
+    InvalidConfig { reason: String },
+    /// The instance could not be parsed
+    Parse(ParseError),
+}
+
+impl Display for IoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IoError::Io { path, source } => {
+                write!(f, "could not read {}: {}", path.display(), source)
+            }
+            IoError::Json {
+                path: Some(path),
+                source,
+            } => write!(f, "could not parse {}: {}", path.display(), source),
+            IoError::Json { path: None, source } => write!(f, "could not parse json: {}", source),
+            IoError::InvalidFile { path, reason } => {
+                write!(f, "invalid file {}: {}", path.display(), reason)
+            }
+            IoError::InvalidConfig { reason } => write!(f, "invalid config: {}", reason),
+            IoError::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for IoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IoError::Io { source, .. } => Some(source),
+            IoError::Json { source, .. } => Some(source),
+            IoError::InvalidFile { .. } | IoError::InvalidConfig { .. } => None,
+            IoError::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<ParseError> for IoError {
+    fn from(err: ParseError) -> Self {
+        IoError::Parse(err)
+    }
+}
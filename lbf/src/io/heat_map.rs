@@ -0,0 +1,187 @@
+use svg::node::element::{Group, Rectangle, Title};
+use svg::Document;
+
+use jagua_rs::entities::bin::Bin;
+use jagua_rs::fsize;
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
+use jagua_rs::geometry::primitives::point::Point;
+
+use crate::io::svg_export;
+use crate::samplers::sample_trace::{SampleOutcome, SampleRecord};
+
+/// Number of cells of a heat map along the longest side of the bin
+pub const HEAT_MAP_RESOLUTION: usize = 100;
+
+/// Samples of a bin aggregated on a regular grid over its bounding box, in the internal coordinates of the bin
+#[derive(Clone, Debug)]
+pub struct HeatMap {
+    pub bin_id: usize,
+    pub bbox: AARectangle,
+    pub n_cols: usize,
+    pub n_rows: usize,
+    /// Number of samples of every outcome (in the order of [`SampleOutcome::ALL`]) per cell, row by row
+    pub cells: Vec<[u64; SampleOutcome::ALL.len()]>,
+    /// Samples which fell outside the bounding box, for example in a strip which was later shrunk
+    pub n_outside: u64,
+}
+
+impl HeatMap {
+    /// Aggregates all `records` sampled in `bin` on a grid of `resolution` cells along its longest side
+    pub fn new(bin: &Bin, records: &[SampleRecord], resolution: usize) -> Self {
+        let bbox = bin.bbox();
+        let cell_size = fsize::max(bbox.width(), bbox.height()) / resolution as fsize;
+        let n_cols = ((bbox.width() / cell_size).ceil() as usize).max(1);
+        let n_rows = ((bbox.height() / cell_size).ceil() as usize).max(1);
+        let mut heat_map = HeatMap {
+            bin_id: bin.id,
+            bbox,
+            n_cols,
+            n_rows,
+            cells: vec![[0; SampleOutcome::ALL.len()]; n_cols * n_rows],
+            n_outside: 0,
+        };
+        for record in records.iter().filter(|r| r.bin_id == bin.id) {
+            match heat_map.cell_index(record.position) {
+                Some(idx) => heat_map.cells[idx][record.outcome as usize] += 1,
+                None => heat_map.n_outside += 1,
+            }
+        }
+        heat_map
+    }
+
+    fn cell_index(&self, Point(x, y): Point) -> Option<usize> {
+        let col = ((x - self.bbox.x_min) / self.bbox.width() * self.n_cols as fsize).floor();
+        let row = ((y - self.bbox.y_min) / self.bbox.height() * self.n_rows as fsize).floor();
+        let in_range = |v: fsize, n: usize| v >= 0.0 && v <= n as fsize;
+        match in_range(col, self.n_cols) && in_range(row, self.n_rows) {
+            //samples exactly on the maximum edge belong to the last cell
+            true => {
+                let (col, row) = (
+                    usize::min(col as usize, self.n_cols - 1),
+                    usize::min(row as usize, self.n_rows - 1),
+                );
+                Some(row * self.n_cols + col)
+            }
+            false => None,
+        }
+    }
+
+    /// Bounds of the cell at `idx`
+    pub fn cell_bbox(&self, idx: usize) -> AARectangle {
+        let (col, row) = (idx % self.n_cols, idx / self.n_cols);
+        let (w, h) = (
+            self.bbox.width() / self.n_cols as fsize,
+            self.bbox.height() / self.n_rows as fsize,
+        );
+        let (x_min, y_min) = (
+            self.bbox.x_min + col as fsize * w,
+            self.bbox.y_min + row as fsize * h,
+        );
+        AARectangle::new(x_min, y_min, x_min + w, y_min + h)
+    }
+
+    /// Total number of samples of an outcome inside the bounding box
+    pub fn count(&self, outcome: SampleOutcome) -> u64 {
+        self.cells.iter().map(|c| c[outcome as usize]).sum()
+    }
+
+    /// Total number of samples inside the bounding box
+    pub fn total(&self) -> u64 {
+        self.cells.iter().flatten().sum()
+    }
+
+    /// Fraction of the samples inside the bounding box which were rejected by a collision
+    pub fn rejected_fraction(&self) -> fsize {
+        let rejected: u64 = SampleOutcome::ALL
+            .iter()
+            .filter(|o| o.rejected())
+            .map(|o| self.count(*o))
+            .sum();
+        rejected as fsize / self.total().max(1) as fsize
+    }
+}
+
+/// Draws the heat map over the contour of the bin.
+/// The opacity of a cell shows its number of samples relative to the busiest cell,
+/// its color the fraction of them rejected by a collision: green if none, red if all.
+pub fn heat_map_to_svg(bin: &Bin, heat_map: &HeatMap) -> Document {
+    let vbox = heat_map.bbox.clone().scale(1.05);
+    let stroke_width = fsize::min(vbox.width(), vbox.height()) * 0.001;
+
+    let bin_path = svg_export::data_to_path(
+        svg_export::simple_polygon_data(&bin.outer),
+        &[
+            ("fill", "none"),
+            ("stroke", "black"),
+            ("stroke-width", &*format!("{}", 2.0 * stroke_width)),
+        ],
+    );
+    let hole_paths = bin.holes.iter().map(|hole| {
+        svg_export::data_to_path(
+            svg_export::simple_polygon_data(hole),
+            &[
+                ("fill", "#D3D3D3"),
+                ("stroke", "black"),
+                ("stroke-width", &*format!("{}", stroke_width)),
+            ],
+        )
+    });
+
+    let max_count = heat_map.cells.iter().map(|c| c.iter().sum::<u64>()).max();
+    let mut cells_group = Group::new().set("id", "heat_map");
+    for (idx, cell) in heat_map.cells.iter().enumerate() {
+        let count: u64 = cell.iter().sum();
+        if count == 0 {
+            continue;
+        }
+        let rejected: u64 = SampleOutcome::ALL
+            .iter()
+            .filter(|o| o.rejected())
+            .map(|o| cell[*o as usize])
+            .sum();
+        let rejected_frac = rejected as fsize / count as fsize;
+        let color = format!(
+            "rgb({},{},0)",
+            (255.0 * rejected_frac) as u8,
+            (255.0 * (1.0 - rejected_frac)) as u8
+        );
+        let opacity = count as fsize / max_count.unwrap_or(1) as fsize;
+        let bbox = heat_map.cell_bbox(idx);
+        let title = SampleOutcome::ALL
+            .iter()
+            .map(|o| format!("{:?}: {}", o, cell[*o as usize]))
+            .collect::<Vec<_>>()
+            .join(", ");
+        cells_group = cells_group.add(
+            Rectangle::new()
+                .set("x", bbox.x_min)
+                .set("y", bbox.y_min)
+                .set("width", bbox.width())
+                .set("height", bbox.height())
+                .set("fill", color)
+                .set("fill-opacity", format!("{:.3}", opacity))
+                .add(Title::new(format!("{} samples ({})", count, title))),
+        );
+    }
+
+    let bin_group = hole_paths.fold(
+        Group::new()
+            .set("id", format!("bin_{}", bin.id))
+            .add(bin_path),
+        |group, hole_path| group.add(hole_path),
+    );
+
+    Document::new()
+        .set(
+            "viewBox",
+            (vbox.x_min, vbox.y_min, vbox.width(), vbox.height()),
+        )
+        .add(Title::new(format!(
+            "sample heat map of bin {}: {} samples, {:.1}% rejected",
+            bin.id,
+            heat_map.total(),
+            heat_map.rejected_fraction() * 100.0
+        )))
+        .add(cells_group)
+        .add(bin_group)
+}
@@ -1,9 +1,25 @@
+use std::f64::consts::SQRT_2;
+use std::io::Write;
+
+use log::info;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::fsize;
+use jagua_rs::geometry::geo_enums::AllowedRotation;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::geometry::primitives::point::Point;
 use jagua_rs::io::json_instance::JsonInstance;
-use jagua_rs::io::json_solution::JsonSolution;
+use jagua_rs::io::json_solution::{JsonSolution, JsonSolutionMetadata};
 
+use crate::io;
 use crate::lbf_config::LBFConfig;
+use crate::samplers::rotation_distr;
+
+/// Rotations (radians) within this distance of the snapping grid are considered to be on it
+pub const ROTATION_SNAP_TOLERANCE: fsize = 1e-4;
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
@@ -13,3 +29,120 @@ pub struct JsonOutput {
     pub solution: JsonSolution,
     pub config: LBFConfig,
 }
+
+/// Formatting of the written JSON. Numbers are written the same way in both (shortest round-trip representation, independent of the locale)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonFormat {
+    /// Indented, one value per line
+    #[default]
+    Pretty,
+    /// On a single line without whitespace: smaller, and faster to write and read for solutions with many placements
+    Compact,
+}
+
+/// Serializes `value` straight into the writer, without building the JSON in memory first
+pub fn write_json(
+    value: &impl Serialize,
+    writer: impl Write,
+    format: JsonFormat,
+) -> serde_json::Result<()> {
+    match format {
+        JsonFormat::Pretty => serde_json::to_writer_pretty(writer, value),
+        JsonFormat::Compact => serde_json::to_writer(writer, value),
+    }
+}
+
+/// Metadata identifying the build of the solver, the configuration and the instance which produced a solution.
+/// The hashes are hex-encoded SHA-256 digests of the compact JSON serialization of the config and the instance,
+/// so they can be recomputed from the `Config` and instance fields of a [`JsonOutput`].
+pub fn solution_metadata(json_instance: &JsonInstance, config: &LBFConfig) -> JsonSolutionMetadata {
+    JsonSolutionMetadata {
+        solver: env!("CARGO_PKG_NAME").to_string(),
+        solver_version: env!("CARGO_PKG_VERSION").to_string(),
+        git_version: option_env!("LBF_GIT_VERSION").map(String::from),
+        config_hash: json_hash(config),
+        instance_hash: json_hash(json_instance),
+    }
+}
+
+/// Replaces the rotations of continuously rotating items by the exact multiple of `step` (radians) they were sampled at.
+/// This only removes the numerical noise of converting the placements to the coordinates of the input:
+/// rotations further than [`ROTATION_SNAP_TOLERANCE`] from the grid (e.g. in a bin rotated by an angle which is not on the grid) are kept.
+pub fn snap_rotations(solution: &mut JsonSolution, instance: &Instance, step: fsize) {
+    for placed_item in solution
+        .layouts
+        .iter_mut()
+        .flat_map(|l| l.placed_items.iter_mut())
+    {
+        if let AllowedRotation::Continuous = instance.item(placed_item.index).allowed_rotation {
+            let rotation = &mut placed_item.transformation.rotation;
+            let snapped = rotation_distr::snap_rotation(*rotation, step);
+            if (snapped - *rotation).abs() < ROTATION_SNAP_TOLERANCE {
+                *rotation = snapped;
+            }
+        }
+    }
+}
+
+/// Largest distance a point of an item moves when its placement is rounded with [`round_transformations`] to `decimals` decimals:
+/// at most half a unit of the last decimal in x and y by the translation, and half a unit by the rotation.
+pub fn rounding_displacement(decimals: u32) -> fsize {
+    ((0.5 * SQRT_2 + 0.5) * 10f64.powi(-(decimals as i32))) as fsize
+}
+
+/// Rounds the translations of all placements to `decimals` decimals, and their rotations (radians) to as many more decimals as needed
+/// for the rounding of the rotation to move no point of an item more than half a unit of the last decimal of the translation.
+/// Every point of an item then moves at most [`rounding_displacement`], so items which did not overlap before rounding overlap by at most twice that.
+/// Returns the number of decimals of the rotations.
+pub fn round_transformations(
+    solution: &mut JsonSolution,
+    instance: &Instance,
+    decimals: u32,
+) -> u32 {
+    //the rotation is about the origin of the item in the input coordinates
+    let to_input = 1.0 / instance.unit_scale();
+    let max_radius = instance
+        .items()
+        .iter()
+        .flat_map(|(item, _)| {
+            let to_original = item.pretransform.clone().inverse();
+            item.shape
+                .points
+                .iter()
+                .map(move |p| p.transform_clone(&to_original))
+        })
+        .map(|Point(x, y)| x.hypot(y) * to_input)
+        .fold(0.0, fsize::max);
+    let rotation_decimals = decimals + (max_radius as f64).log10().ceil().max(0.0) as u32;
+
+    for transformation in solution
+        .layouts
+        .iter_mut()
+        .flat_map(|l| l.placed_items.iter_mut())
+        .map(|pi| &mut pi.transformation)
+    {
+        let (x, y) = transformation.translation;
+        transformation.translation = (
+            io::round_decimals(x, decimals),
+            io::round_decimals(y, decimals),
+        );
+        transformation.rotation = io::round_decimals(transformation.rotation, rotation_decimals);
+    }
+    info!(
+        "[EXPORT] placements rounded to {} decimals (rotations to {}), items move at most {:.1e} and overlap at most {:.1e} because of it",
+        decimals,
+        rotation_decimals,
+        rounding_displacement(decimals),
+        2.0 * rounding_displacement(decimals)
+    );
+    rotation_decimals
+}
+
+fn json_hash(value: &impl Serialize) -> String {
+    let json = serde_json::to_vec(value).expect("could not serialize to JSON");
+    Sha256::digest(json)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
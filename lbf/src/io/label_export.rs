@@ -0,0 +1,61 @@
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::entities::solution::Solution;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::io::parser;
+
+/// Template of a ZPL label with the name, sheet, sequence number and position of a part
+pub const DEFAULT_LABEL_TEMPLATE: &str = "^XA
+^FO30,30^A0N,40,40^FD{name}^FS
+^FO30,80^A0N,30,30^FDpart {part} - sheet {sheet} - #{seq}^FS
+^FO30,120^A0N,30,30^FDx: {x} y: {y}^FS
+^XZ
+";
+
+/// Creates one label record per placed item, by filling in the placeholders of the template:
+/// - `{part}`: index of the item in the input
+/// - `{name}`: name of the item, its index if it has none
+/// - `{copy}`: copy of the item, empty for items without per-copy attributes
+/// - `{sheet}`: number of the layout the item is placed in, starting at 1
+/// - `{seq}`: position of the item in its sheet, starting at 1. Items are listed in the order of the solution,
+///   which is the order in which they were placed
+/// - `{x}`, `{y}`: center of the largest circle inscribed in the placed item (where a label fits best), in the coordinates of the input
+/// - `{rotation}`: rotation of the item in degrees
+///
+/// Records are ordered by sheet and sequence number.
+pub fn labels(solution: &Solution, instance: &Instance, template: &str) -> String {
+    let to_input = 1.0 / instance.unit_scale();
+    let mut records = String::new();
+    for (sheet_idx, sl) in solution.layout_snapshots.iter().enumerate() {
+        let inv_bin_transf = sl.bin.pretransform.clone().inverse();
+        for (seq_idx, pi) in sl.placed_items.values().enumerate() {
+            let item = instance.item(pi.item_id);
+            let center = pi.shape.poi.center.transform_clone(&inv_bin_transf);
+            let abs_rotation = parser::internal_to_absolute_transform(
+                &pi.d_transf,
+                &item.pretransform,
+                &sl.bin.pretransform,
+            )
+            .decompose()
+            .rotation();
+            let name = match &item.name {
+                Some(name) => name.clone(),
+                None => pi.item_id.to_string(),
+            };
+            let record = template
+                .replace("{part}", &pi.item_id.to_string())
+                .replace("{name}", &name)
+                .replace("{copy}", &pi.copy.map_or(String::new(), |c| c.to_string()))
+                .replace("{sheet}", &(sheet_idx + 1).to_string())
+                .replace("{seq}", &(seq_idx + 1).to_string())
+                .replace("{x}", &format!("{:.1}", center.0 * to_input))
+                .replace("{y}", &format!("{:.1}", center.1 * to_input))
+                .replace(
+                    "{rotation}",
+                    &format!("{:.1}", abs_rotation.to_degrees().rem_euclid(360.0)),
+                );
+            records.push_str(&record);
+        }
+    }
+    records
+}
@@ -1,14 +1,20 @@
-use crate::io::svg_util::SvgDrawOptions;
-use crate::io::{svg_export, svg_util};
+use std::iter;
+
+use crate::io::svg_util::{LevelOfDetail, SvgDrawOptions};
+use crate::io::{self, svg_export, svg_util};
+use itertools::Itertools;
 use jagua_rs::entities::instances::instance::Instance;
 use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
 use jagua_rs::entities::layout::Layout;
 use jagua_rs::entities::layout::LayoutSnapshot;
 use jagua_rs::fsize;
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
 use jagua_rs::geometry::primitives::circle::Circle;
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
 use jagua_rs::geometry::transformation::Transformation;
 use jagua_rs::io::parser;
-use svg::node::element::{Definitions, Group, Title, Use};
+use svg::node::element::path::Data;
+use svg::node::element::{Definitions, Group, Text, Title, Use};
 use svg::Document;
 
 pub fn s_layout_to_svg(
@@ -21,30 +27,90 @@ pub fn s_layout_to_svg(
 }
 
 pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptions) -> Document {
+    layout_to_svg_lod(layout, instance, options, LevelOfDetail::Full)
+}
+
+/// Same as [`layout_to_svg_lod`], for a layout of a solution
+pub fn s_layout_to_svg_lod(
+    s_layout: &LayoutSnapshot,
+    instance: &Instance,
+    options: SvgDrawOptions,
+    level: LevelOfDetail,
+) -> Document {
+    let layout = Layout::from_snapshot(s_layout);
+    layout_to_svg_lod(&layout, instance, options, level)
+}
+
+/// Draws a layout at a level of detail. Thumbnails are small enough to show many large layouts at once (e.g. in a list of jobs):
+/// the contours are simplified to a pixel of the thumbnail, items are drawn without their arcs and titles,
+/// and the quadtree, hazard proximity grid, surrogates and parts list are left out.
+pub fn layout_to_svg_lod(
+    layout: &Layout,
+    instance: &Instance,
+    options: SvgDrawOptions,
+    level: LevelOfDetail,
+) -> Document {
     let internal_bin = &layout.bin;
     let inv_bin_transf = internal_bin.pretransform.clone().inverse();
     let bin = parser::pretransform_bin(internal_bin, &inv_bin_transf);
 
     let vbox = bin.bbox().clone().scale(1.05);
 
+    //size of a pixel of the thumbnail
+    let tolerance = match level {
+        LevelOfDetail::Full => None,
+        LevelOfDetail::Thumbnail { width } => Some(vbox.width() / width.max(1) as fsize),
+    };
+    let options = match tolerance {
+        None => options,
+        Some(_) => SvgDrawOptions {
+            quadtree: false,
+            haz_prox_grid: false,
+            surrogate: false,
+            parts_list: false,
+            ..options
+        },
+    };
+
     let theme = &options.theme;
+    //rounds the contours of the bin, its zones and the items, if a precision is set
+    let round = |data: Data| match options.precision {
+        Some(decimals) => svg_export::round_data(&data, decimals),
+        None => data,
+    };
+    let polygon_data = |polygon: &SimplePolygon| match tolerance {
+        None => round(svg_export::simple_polygon_data(polygon)),
+        Some(tolerance) => round(svg_export::points_data(&svg_export::simplify_contour(
+            &polygon.points,
+            tolerance,
+        ))),
+    };
 
     let stroke_width =
         fsize::min(vbox.width(), vbox.height()) * 0.001 * theme.stroke_width_multiplier;
 
     //draw bin
     let bin_group = {
-        let mut bin_group = Group::new().set("id", format!("bin_{}", bin.id));
+        let mut bin_group = Group::new().set("id", format!("bin_{}", bin.base_id()));
         let bbox = bin.bbox();
+        let name = match &bin.name {
+            Some(name) => format!("{name}, "),
+            None => String::new(),
+        };
         let title = Title::new(format!(
-            "bin, id: {}, bbox: [x_min: {:.3}, y_min: {:.3}, x_max: {:.3}, y_max: {:.3}]",
-            bin.id, bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max
+            "bin, {}id: {}, bbox: [x_min: {:.3}, y_min: {:.3}, x_max: {:.3}, y_max: {:.3}]",
+            name,
+            bin.base_id(),
+            bbox.x_min,
+            bbox.y_min,
+            bbox.x_max,
+            bbox.y_max
         ));
 
         //outer
         bin_group = bin_group
             .add(svg_export::data_to_path(
-                svg_export::simple_polygon_data(&bin.outer),
+                polygon_data(&bin.outer),
                 &[
                     ("fill", &*format!("{}", theme.bin_fill)),
                     ("stroke", "black"),
@@ -57,7 +123,7 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
         for (hole_idx, hole) in bin.holes.iter().enumerate() {
             bin_group = bin_group.add(
                 svg_export::data_to_path(
-                    svg_export::simple_polygon_data(hole),
+                    polygon_data(hole),
                     &[
                         ("fill", &*format!("{}", theme.hole_fill)),
                         ("stroke", "black"),
@@ -67,6 +133,29 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
                 .add(Title::new(format!("hole #{}", hole_idx))),
             );
         }
+
+        //temporary zones, outlined only since items can overlap them
+        for (tz_idx, tz) in bin.temporary_zones.iter().enumerate() {
+            let until = match tz.active_until {
+                Some(until) => format!("{until}"),
+                None => "end".to_string(),
+            };
+            bin_group = bin_group.add(
+                svg_export::data_to_path(
+                    polygon_data(&tz.shape),
+                    &[
+                        ("fill", "none"),
+                        ("stroke", "black"),
+                        ("stroke-width", &*format!("{}", 1.0 * stroke_width)),
+                        ("stroke-dasharray", &*format!("{}", 5.0 * stroke_width)),
+                    ],
+                )
+                .add(Title::new(format!(
+                    "temporary zone #{}, active from item {} until {}",
+                    tz_idx, tz.active_from, until
+                ))),
+            );
+        }
         bin_group
     };
 
@@ -80,7 +169,7 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
             for qz_shape in qz.zones.iter() {
                 qz_group = qz_group.add(
                     svg_export::data_to_path(
-                        svg_export::simple_polygon_data(qz_shape),
+                        polygon_data(qz_shape),
                         &[
                             ("fill", &*format!("{}", color)),
                             ("fill-opacity", "0.50"),
@@ -114,9 +203,14 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
                 None => theme.item_fill.to_owned(),
                 Some(q) => svg_util::blend_colors(theme.item_fill, theme.qz_fill[q]),
             };
+            //draw the exact contour if the item has arcs, otherwise the polygon
+            let shape_data = match (&item.arc_shape, tolerance) {
+                (Some(arc_shape), None) => round(svg_export::arc_polygon_data(arc_shape)),
+                _ => polygon_data(shape),
+            };
             item_defs = item_defs.add(Group::new().set("id", format!("item_{}", item.id)).add(
                 svg_export::data_to_path(
-                    svg_export::simple_polygon_data(shape),
+                    shape_data,
                     &[
                         ("fill", &*format!("{}", color)),
                         ("stroke-width", &*format!("{}", stroke_width)),
@@ -185,21 +279,29 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
                 &instance.item(pi.item_id).pretransform,
                 &internal_bin.pretransform,
             );
-            let title = Title::new(format!(
-                "item, id: {}, transf: [{}]",
-                pi.item_id,
-                abs_transf.decompose()
-            ));
             let pi_ref = Use::new()
-                .set("transform", transform_to_svg(&abs_transf))
-                .set("xlink:href", format!("#item_{}", pi.item_id))
-                .add(title);
+                .set(
+                    "transform",
+                    transform_to_svg(&abs_transf, options.precision),
+                )
+                .set("xlink:href", format!("#item_{}", pi.item_id));
+            let pi_ref = match tolerance {
+                None => pi_ref.add(Title::new(format!(
+                    "item, id: {}, transf: [{}]",
+                    pi.item_id,
+                    abs_transf.decompose()
+                ))),
+                Some(_) => pi_ref,
+            };
 
             items_group = items_group.add(pi_ref);
 
             if options.surrogate {
                 let pi_surr_ref = Use::new()
-                    .set("transform", transform_to_svg(&abs_transf))
+                    .set(
+                        "transform",
+                        transform_to_svg(&abs_transf, options.precision),
+                    )
                     .set("xlink:href", format!("#surrogate_{}", pi.item_id));
 
                 surrogate_group = surrogate_group.add(pi_surr_ref);
@@ -218,7 +320,7 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
             let qt_data = svg_export::quad_tree_data(layout.cde().quadtree(), &[]);
             let qt_group = Group::new()
                 .set("id", "quadtree")
-                .set("transform", transform_to_svg(&inv_bin_transf))
+                .set("transform", transform_to_svg(&inv_bin_transf, None))
                 .add(svg_export::data_to_path(
                     qt_data.0,
                     &[
@@ -257,7 +359,7 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
         true => {
             let mut hpg_group = Group::new()
                 .set("id", "haz_prox_grid")
-                .set("transform", transform_to_svg(&inv_bin_transf));
+                .set("transform", transform_to_svg(&inv_bin_transf, None));
             let hpg = layout.cde().haz_prox_grid().unwrap();
             for hp_cell in hpg.grid.cells.iter().flatten() {
                 let center = hp_cell.centroid;
@@ -279,6 +381,16 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
         }
     };
 
+    //the table of parts extends the drawing downwards
+    let (parts_list_group, vbox) = match options.parts_list {
+        false => (None, vbox),
+        true => {
+            let (group, height) = parts_list(layout, instance, &vbox);
+            let vbox = AARectangle::new(vbox.x_min, vbox.y_min, vbox.x_max, vbox.y_max + height);
+            (Some(group), vbox)
+        }
+    };
+
     let vbox_svg = (vbox.x_min, vbox.y_min, vbox.width(), vbox.height());
 
     let optionals = [surrogate_group, qt_group, hpg_group]
@@ -286,20 +398,107 @@ pub fn layout_to_svg(layout: &Layout, instance: &Instance, options: SvgDrawOptio
         .flatten()
         .fold(Group::new().set("id", "optionals"), |g, opt| g.add(opt));
 
-    Document::new()
+    let document = Document::new()
         .set("viewBox", vbox_svg)
-        .set("xmlns:xlink", "http://www.w3.org/1999/xlink")
+        .set("xmlns:xlink", "http://www.w3.org/1999/xlink");
+
+    //a title as first child names the whole document, e.g. in the tab of a browser
+    let document = match &bin.name {
+        Some(name) => document.add(Title::new(name.clone())),
+        None => document,
+    };
+
+    let document = document
         .add(bin_group)
         .add(items_group)
         .add(qz_group)
-        .add(optionals)
+        .add(optionals);
+
+    match parts_list_group {
+        Some(group) => document.add(group),
+        None => document,
+    }
+}
+
+/// Table of the parts placed in the layout, one row per item and rotation, followed by the totals.
+/// Drawn below `vbox`, returns the table and its height
+fn parts_list(layout: &Layout, instance: &Instance, vbox: &AARectangle) -> (Group, fsize) {
+    let font_size = fsize::max(vbox.width(), vbox.height()) * 0.02;
+    let columns = [0.0, 16.0 * font_size, 21.0 * font_size];
+
+    let rows = layout
+        .placed_items()
+        .values()
+        .map(|pi| {
+            let item = instance.item(pi.item_id);
+            let abs_transf = parser::internal_to_absolute_transform(
+                &pi.d_transf,
+                &item.pretransform,
+                &layout.bin.pretransform,
+            );
+            let rotation = abs_transf
+                .decompose()
+                .rotation()
+                .to_degrees()
+                .rem_euclid(360.0);
+            (pi.item_id, format!("{:.2}°", rotation))
+        })
+        .counts()
+        .into_iter()
+        .sorted()
+        .map(|((item_id, rotation), qty)| {
+            let name = match &instance.item(item_id).name {
+                Some(name) => name.clone(),
+                None => format!("item #{}", item_id),
+            };
+            [name, qty.to_string(), rotation]
+        })
+        .collect_vec();
+
+    let totals = [
+        "total".to_string(),
+        layout.placed_items().len().to_string(),
+        format!("usage: {:.1}%", layout.usage() * 100.0),
+    ];
+    let header = ["part", "qty", "rotation"].map(String::from);
+
+    let mut group = Group::new()
+        .set("id", "parts_list")
+        .set("font-family", "monospace")
+        .set("font-size", font_size);
+    let all_rows = iter::once((header, "bold"))
+        .chain(rows.into_iter().map(|row| (row, "normal")))
+        .chain(iter::once((totals, "bold")));
+    let mut n_rows = 0;
+    for (i, (row, weight)) in all_rows.enumerate() {
+        let y = vbox.y_max + (i + 1) as fsize * 1.5 * font_size;
+        for (text, x) in row.into_iter().zip(columns) {
+            group = group.add(
+                Text::new(text)
+                    .set("x", vbox.x_min + x)
+                    .set("y", y)
+                    .set("font-weight", weight),
+            );
+        }
+        n_rows += 1;
+    }
+    (group, (n_rows + 1) as fsize * 1.5 * font_size)
 }
 
-fn transform_to_svg(t: &Transformation) -> String {
+/// The translation is rounded to `precision` decimals, the rotation (degrees) to two more
+fn transform_to_svg(t: &Transformation, precision: Option<u32>) -> String {
     //https://developer.mozilla.org/en-US/docs/Web/SVG/Attribute/transform
     //operations are effectively applied from right to left
     let dt = t.decompose();
     let (tx, ty) = dt.translation();
     let r = dt.rotation().to_degrees();
+    let (tx, ty, r) = match precision {
+        Some(decimals) => (
+            io::round_decimals(tx, decimals),
+            io::round_decimals(ty, decimals),
+            io::round_decimals(r, decimals + 2),
+        ),
+        None => (tx, ty, r),
+    };
     format!("translate({tx} {ty}), rotate({r})")
 }
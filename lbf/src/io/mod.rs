@@ -1,55 +1,185 @@
 use std::fs;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
 
-use log::{info, log, Level, LevelFilter};
+use log::{info, log, warn, Level, LevelFilter};
+use serde::de::DeserializeOwned;
 use svg::Document;
+use tracing_flame::{FlameLayer, FlushGuard};
+use tracing_subscriber::filter;
+use tracing_subscriber::prelude::*;
 
+use dxf::Drawing;
+use jagua_rs::fsize;
 use jagua_rs::io::dxf_parse::parse_dxf;
 use jagua_rs::io::dxf_parse::DxfInstance;
+use jagua_rs::io::esicup::esicup_instance;
+use jagua_rs::io::geojson::geojson_instance;
 use jagua_rs::io::json_instance::JsonInstance;
+use jagua_rs::io::json_solution::{JsonClearanceEntity, JsonSolution};
 
+use crate::io::error::IoError;
 use crate::io::json_output::JsonOutput;
+use crate::io::run_stats::{RunStats, StatsFormat, STATS_CSV_HEADER};
 use crate::EPOCH;
 
 pub mod cli;
+pub mod csv_export;
+pub mod dxf_folder;
+pub mod dxf_inspect;
+pub mod error;
+pub mod heat_map;
 pub mod json_output;
+pub mod label_export;
 pub mod layout_to_svg;
+#[cfg(feature = "pdf")]
+pub mod pdf_report;
+pub mod placements;
+#[cfg(feature = "png")]
+pub mod png_export;
+pub mod repro;
+pub mod run_stats;
+pub mod simplification_preview;
 pub mod svg_export;
 pub mod svg_util;
+pub mod zip_instance;
 
 // Path
-pub fn read_json_instance(path: Option<&Path>, json_str: Option<&String>) -> JsonInstance {
-    if path.is_some() {
-        let path = path.unwrap();
-        let file = File::open(path).unwrap_or_else(|err| {
-            panic!("could not open instance file: {}, {}", path.display(), err)
-        });
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).unwrap_or_else(|err| {
-            panic!("could not parse instance file: {}, {}", path.display(), err)
-        })
-    } else if json_str.is_some() {
-        let json_str = json_str.unwrap();
-        serde_json::from_str(json_str)
-            .unwrap_or_else(|err| panic!("could not parse string: {}", err))
+pub fn read_json_instance(
+    path: Option<&Path>,
+    json_str: Option<&String>,
+) -> Result<JsonInstance, IoError> {
+    if let Some(path) = path {
+        read_json_file(path)
+    } else if let Some(json_str) = json_str {
+        serde_json::from_str(json_str).map_err(|source| IoError::Json { path: None, source })
     } else {
         panic!("No instance file or json string provided")
     }
 }
 
+/// Deserializes the contents of a JSON file
+pub fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T, IoError> {
+    let file = File::open(path).map_err(|source| IoError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_reader(BufReader::new(file)).map_err(|source| IoError::Json {
+        path: Some(path.to_path_buf()),
+        source,
+    })
+}
+
+/// Reads a nesting problem in the XML format of the ESICUP datasets, see [`esicup_instance`]
+pub fn read_esicup_instance(path: &Path) -> Result<JsonInstance, IoError> {
+    let xml = fs::read_to_string(path).map_err(|source| IoError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    esicup_instance(&xml).map_err(|reason| IoError::InvalidFile {
+        path: path.to_path_buf(),
+        reason,
+    })
+}
+
+/// Reads an instance from a GeoJSON feature collection, see [`geojson_instance`]
+pub fn read_geojson_instance(path: &Path) -> Result<JsonInstance, IoError> {
+    let geojson = fs::read_to_string(path).map_err(|source| IoError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    geojson_instance(&geojson).map_err(|reason| IoError::InvalidFile {
+        path: path.to_path_buf(),
+        reason,
+    })
+}
+
+/// Reads an instance from a zip archive with the instance JSON and the DXF and SVG files it references, see [`zip_instance`](zip_instance::zip_instance)
+pub fn read_zip_instance(path: &Path) -> Result<JsonInstance, IoError> {
+    let archive = fs::read(path).map_err(|source| IoError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    zip_instance::zip_instance(&archive).map_err(|reason| IoError::InvalidFile {
+        path: path.to_path_buf(),
+        reason,
+    })
+}
+
 // ! Wordt niet meer gebruikt
-pub fn read_dxf_instance(path: &Path) -> DxfInstance {
-    let file = File::open(path)
-        .unwrap_or_else(|err| panic!("could not open json file: {}, {}", path.display(), err));
-    let reader = BufReader::new(file);
+pub fn read_dxf_instance(path: &Path) -> Result<DxfInstance, IoError> {
+    let json_with_dxf_instance: JsonInstance = read_json_file(path)?;
+
+    Ok(parse_dxf(&json_with_dxf_instance))
+}
 
-    let json_with_dxf_instance: JsonInstance = serde_json::from_reader(reader)
-        .unwrap_or_else(|err| panic!("could not parse json file: {}, {}", path.display(), err));
+pub fn write_json_instance(json_instance: &JsonInstance, path: &Path) {
+    let file = File::create(path)
+        .unwrap_or_else(|_| panic!("could not open instance file: {}", path.display()));
+
+    let writer = BufWriter::new(file);
 
-    let dxf_instance = parse_dxf(&json_with_dxf_instance);
-    dxf_instance
+    serde_json::to_writer_pretty(writer, &json_instance)
+        .unwrap_or_else(|_| panic!("could not write instance file: {}", path.display()));
+
+    info!(
+        "Instance JSON written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+/// Logs the minimum clearance of a solution and warns about every clearance flagged as a violation
+pub fn log_clearances(json_solution: &JsonSolution) {
+    if let Some(min_clearance) = json_solution.min_clearance {
+        info!("[CLEARANCE] minimum clearance: {:.6}", min_clearance);
+    }
+    for (i, layout) in json_solution.layouts.iter().enumerate() {
+        for c in layout.statistics.clearance_violations.iter() {
+            warn!(
+                "[CLEARANCE] layout {}: clearance between placed item #{} and {} is only {:.6}",
+                i,
+                c.placed_item,
+                clearance_entity_name(&c.other),
+                c.distance
+            );
+        }
+    }
+}
+
+/// Warns about every web of the skeleton flagged as thinner than the minimum web width
+pub fn log_thin_webs(json_solution: &JsonSolution) {
+    for (i, layout) in json_solution.layouts.iter().enumerate() {
+        for w in layout.statistics.thin_webs.iter() {
+            warn!(
+                "[WEB] layout {}: web between placed item #{} and {} at ({:.3}, {:.3}) is only {:.6} wide",
+                i,
+                w.placed_item,
+                clearance_entity_name(&w.other),
+                w.location.0,
+                w.location.1,
+                w.width
+            );
+        }
+    }
+}
+
+fn clearance_entity_name(entity: &JsonClearanceEntity) -> String {
+    match entity {
+        JsonClearanceEntity::PlacedItem(j) => format!("placed item #{}", j),
+        JsonClearanceEntity::Exterior => "container exterior".to_string(),
+        JsonClearanceEntity::Hole(j) => format!("container hole #{}", j),
+    }
+}
+
+/// Rounds half away from zero to `decimals` decimals, computed in double precision so values just below a tie are not rounded up.
+/// Negative zero is written as zero
+pub fn round_decimals(value: fsize, decimals: u32) -> fsize {
+    let factor = 10f64.powi(decimals as i32);
+    ((value as f64 * factor).round() / factor) as fsize + 0.0
 }
 
 pub fn write_json_output(json_output: &JsonOutput, path: &Path) {
@@ -58,7 +188,7 @@ pub fn write_json_output(json_output: &JsonOutput, path: &Path) {
 
     let writer = BufWriter::new(file);
 
-    serde_json::to_writer_pretty(writer, &json_output)
+    json_output::write_json(json_output, writer, json_output.config.json_format)
         .unwrap_or_else(|_| panic!("could not write solution file: {}", path.display()));
 
     info!(
@@ -70,10 +200,121 @@ pub fn write_json_output(json_output: &JsonOutput, path: &Path) {
     );
 }
 
+pub fn write_geojson(geojson: &serde_json::Value, path: &Path) {
+    let file = File::create(path)
+        .unwrap_or_else(|_| panic!("could not open geojson file: {}", path.display()));
+    serde_json::to_writer(BufWriter::new(file), geojson)
+        .unwrap_or_else(|_| panic!("could not write geojson file: {}", path.display()));
+    info!(
+        "GeoJSON written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+pub fn write_csv(csv: &str, path: &Path) {
+    fs::write(path, csv).unwrap_or_else(|_| panic!("could not write csv file: {}", path.display()));
+    info!(
+        "CSV written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+/// Appends the stats of a solve to the file, in the format of its extension (see [`StatsFormat`]).
+/// The file is created if it does not exist yet, a CSV file starts with its header
+pub fn append_run_stats(stats: &RunStats, path: &Path) {
+    let format = StatsFormat::from_path(path);
+    let empty = fs::metadata(path).map_or(true, |m| m.len() == 0);
+    let mut records = String::new();
+    if empty && format == StatsFormat::Csv {
+        records.push_str(STATS_CSV_HEADER);
+        records.push('\n');
+    }
+    records.push_str(&stats.record(format));
+    records.push('\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(records.as_bytes()))
+        .unwrap_or_else(|_| panic!("could not write stats file: {}", path.display()));
+    info!(
+        "Run stats appended to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+pub fn write_gcode(gcode: &str, path: &Path) {
+    fs::write(path, gcode)
+        .unwrap_or_else(|_| panic!("could not write gcode file: {}", path.display()));
+    info!(
+        "G-code written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+pub fn write_labels(labels: &str, path: &Path) {
+    fs::write(path, labels)
+        .unwrap_or_else(|_| panic!("could not write label file: {}", path.display()));
+    info!(
+        "Labels written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
 pub fn write_svg(document: &Document, path: &Path) {
     svg::save(path, document).expect("failed to write svg file");
     info!(
-        "Solution SVG written to file://{}",
+        "SVG written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+pub fn write_dxf(drawing: &Drawing, path: &Path) {
+    drawing
+        .save_file(path)
+        .unwrap_or_else(|_| panic!("could not write dxf file: {}", path.display()));
+    info!(
+        "DXF written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+pub fn write_pdf(pdf: &[u8], path: &Path) {
+    fs::write(path, pdf).unwrap_or_else(|_| panic!("could not write pdf file: {}", path.display()));
+    info!(
+        "PDF written to file://{}",
+        fs::canonicalize(path)
+            .expect("could not canonicalize path")
+            .to_str()
+            .unwrap()
+    );
+}
+
+pub fn write_png(png: &[u8], path: &Path) {
+    fs::write(path, png).unwrap_or_else(|_| panic!("could not write png file: {}", path.display()));
+    info!(
+        "PNG written to file://{}",
         fs::canonicalize(path)
             .expect("could not canonicalize path")
             .to_str()
@@ -115,3 +356,29 @@ pub fn init_logger(level_filter: LevelFilter) {
         humantime::format_rfc3339_seconds(std::time::SystemTime::now())
     );
 }
+
+/// Records the tracing spans up to `level` (parsing, placements, and at debug level surrogate generation and collision queries)
+/// as folded stacks in `path`, from which a flamegraph can be drawn, e.g. with `inferno-flamegraph`.
+/// Stacks of all threads are merged. The file is only complete once the returned guard is dropped.
+pub fn init_tracing(
+    path: &Path,
+    level: tracing::Level,
+) -> Result<FlushGuard<BufWriter<File>>, IoError> {
+    let file = File::create(path).map_err(|source| IoError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let flame_layer = FlameLayer::new(BufWriter::new(file))
+        .with_threads_collapsed(true)
+        .with_file_and_line(false);
+    let guard = flame_layer.flush_on_drop();
+    tracing_subscriber::registry()
+        .with(flame_layer.with_filter(filter::LevelFilter::from_level(level)))
+        .init();
+    info!(
+        "[TRACE] recording spans up to {} level to {}",
+        level,
+        path.display()
+    );
+    Ok(guard)
+}
@@ -0,0 +1,351 @@
+use itertools::Itertools;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::entities::layout::LayoutSnapshot;
+use jagua_rs::entities::solution::Solution;
+use jagua_rs::fsize;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+use jagua_rs::geometry::transformation::Transformation;
+use jagua_rs::io::parser;
+
+use crate::io::svg_util::{self, Color, SvgDrawOptions};
+
+/// A4 in landscape, in points
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 40.0;
+const FONT_SIZE: f32 = 10.0;
+const LINE_HEIGHT: f32 = 1.5 * FONT_SIZE;
+
+const FONT: Name = Name(b"F1");
+const FONT_BOLD: Name = Name(b"F2");
+
+/// Writes a PDF report of a solution: a summary with the usage, the placed and demanded quantity of every item and the waste of every layout,
+/// followed by a page per layout with its drawing, in the colors of the `theme` of the options.
+/// Areas are in (square) units of the instance. Items with arcs are drawn with their polygon.
+pub fn solution_to_pdf(
+    solution: &Solution,
+    instance: &Instance,
+    title: &str,
+    options: SvgDrawOptions,
+) -> Vec<u8> {
+    let mut pages = summary_pages(solution, instance, title);
+    pages.extend(
+        solution
+            .layout_snapshots
+            .iter()
+            .enumerate()
+            .map(|(i, s_layout)| layout_page(i, s_layout, instance, options)),
+    );
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let font_bold_id = Ref::new(4);
+    let info_id = Ref::new(5);
+    //every page has a page and a content object
+    let page_ids = (0..pages.len())
+        .map(|i| Ref::new(6 + 2 * i as i32))
+        .collect_vec();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+    pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+    pdf.type1_font(font_bold_id)
+        .base_font(Name(b"Helvetica-Bold"));
+    pdf.document_info(info_id)
+        .title(TextStr(title))
+        .creator(TextStr("lbf"));
+
+    for (page_id, content) in page_ids.into_iter().zip(pages) {
+        let content_id = page_id.next();
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .parent(page_tree_id)
+            .contents(content_id);
+        page.resources()
+            .fonts()
+            .pair(FONT, font_id)
+            .pair(FONT_BOLD, font_bold_id);
+        page.finish();
+        pdf.stream(content_id, &content.finish());
+    }
+    pdf.finish()
+}
+
+/// Summary of the solution, continued on as many pages as the tables of the layouts and items need
+fn summary_pages(solution: &Solution, instance: &Instance, title: &str) -> Vec<Content> {
+    let to_input_area = instance.unit_scale().powi(-2);
+    let bin_area = solution
+        .layout_snapshots
+        .iter()
+        .map(|sl| sl.bin.area)
+        .sum::<fsize>();
+    let waste = |sl: &LayoutSnapshot| sl.bin.area * (1.0 - sl.usage) * to_input_area;
+    let n_placed = solution.placed_item_qtys.iter().sum::<usize>();
+    let n_demanded = solution.target_item_qtys.iter().sum::<usize>();
+
+    let mut writer = TextWriter::new();
+    writer.line(&format!("Nesting report: {}", title), FONT_BOLD, 16.0);
+    writer.skip();
+    writer.line(
+        &format!("Layouts: {}", solution.layout_snapshots.len()),
+        FONT,
+        FONT_SIZE,
+    );
+    writer.line(
+        &format!("Usage: {:.2}%", solution.usage * 100.0),
+        FONT,
+        FONT_SIZE,
+    );
+    writer.line(
+        &format!("Items placed: {} of {}", n_placed, n_demanded),
+        FONT,
+        FONT_SIZE,
+    );
+    writer.line(
+        &format!(
+            "Waste: {:.2} ({:.2}% of the bins)",
+            solution.layout_snapshots.iter().map(waste).sum::<fsize>(),
+            (1.0 - solution.usage) * 100.0
+        ),
+        FONT,
+        FONT_SIZE,
+    );
+    writer.line(
+        &format!("Bin area: {:.2}", bin_area * to_input_area),
+        FONT,
+        FONT_SIZE,
+    );
+    writer.skip();
+
+    let layout_columns = [0.0, 60.0, 260.0, 340.0, 420.0];
+    writer.row(
+        &["layout", "bin", "items", "usage", "waste"],
+        FONT_BOLD,
+        &layout_columns,
+    );
+    for (i, sl) in solution.layout_snapshots.iter().enumerate() {
+        writer.row(
+            &[
+                &i.to_string(),
+                &bin_label(sl),
+                &sl.placed_items.len().to_string(),
+                &format!("{:.2}%", sl.usage * 100.0),
+                &format!("{:.2}", waste(sl)),
+            ],
+            FONT,
+            &layout_columns,
+        );
+    }
+    writer.skip();
+
+    let item_columns = [0.0, 260.0, 340.0];
+    writer.row(&["item", "placed", "demanded"], FONT_BOLD, &item_columns);
+    for (item_id, (&placed, &demanded)) in solution
+        .placed_item_qtys
+        .iter()
+        .zip(solution.target_item_qtys.iter())
+        .enumerate()
+    {
+        let item = instance.item(item_id);
+        let name = match &item.name {
+            Some(name) => format!("{} (#{})", name, item_id),
+            None => format!("#{}", item_id),
+        };
+        writer.row(
+            &[&name, &placed.to_string(), &demanded.to_string()],
+            FONT,
+            &item_columns,
+        );
+    }
+    writer.finish()
+}
+
+/// Page with the drawing of a layout, scaled to fit below its title
+fn layout_page(
+    i: usize,
+    s_layout: &LayoutSnapshot,
+    instance: &Instance,
+    options: SvgDrawOptions,
+) -> Content {
+    let theme = &options.theme;
+    let inv_bin_transf = s_layout.bin.pretransform.clone().inverse();
+    let bin = parser::pretransform_bin(&s_layout.bin, &inv_bin_transf);
+
+    let mut writer = TextWriter::new();
+    writer.line(
+        &format!(
+            "Layout {}: {}, {} items, usage {:.2}%",
+            i,
+            bin_label(s_layout),
+            s_layout.placed_items.len(),
+            s_layout.usage * 100.0
+        ),
+        FONT_BOLD,
+        12.0,
+    );
+    let mut content = writer.finish().pop().unwrap();
+
+    //fit the bin below the title, the y-axis points down as in the SVG of the layout
+    let bbox = bin.bbox();
+    let (avail_width, avail_height) = (PAGE_WIDTH - 2.0 * MARGIN, PAGE_HEIGHT - 3.0 * MARGIN);
+    let scale = f32::min(
+        avail_width / bbox.width() as f32,
+        avail_height / bbox.height() as f32,
+    );
+    let x_offset = MARGIN + (avail_width - bbox.width() as f32 * scale) / 2.0;
+    let y_top = PAGE_HEIGHT - 2.0 * MARGIN;
+    content.save_state();
+    content.transform([
+        scale,
+        0.0,
+        0.0,
+        -scale,
+        x_offset - bbox.x_min as f32 * scale,
+        y_top + bbox.y_min as f32 * scale,
+    ]);
+    //line widths in points
+    let line_width = |points: f32| points / scale;
+
+    set_fill(&mut content, theme.bin_fill);
+    content.set_line_width(line_width(1.0));
+    polygon_path(&mut content, &bin.outer);
+    content.fill_nonzero_and_stroke();
+
+    set_fill(&mut content, theme.hole_fill);
+    content.set_line_width(line_width(0.5));
+    for hole in bin.holes.iter() {
+        polygon_path(&mut content, hole);
+        content.fill_nonzero_and_stroke();
+    }
+
+    //quality zones are outlined, items can be placed on top of them
+    content.set_dash_pattern([line_width(3.0)], 0.0);
+    for qz in bin.quality_zones.iter().rev().flatten() {
+        set_stroke(
+            &mut content,
+            svg_util::change_brightness(theme.qz_fill[qz.quality], 0.5),
+        );
+        for zone in qz.zones.iter() {
+            polygon_path(&mut content, zone);
+            content.stroke();
+        }
+    }
+    content.set_dash_pattern([], 0.0);
+    content.set_stroke_rgb(0.0, 0.0, 0.0);
+
+    content.set_line_width(line_width(0.3));
+    for pi in s_layout.placed_items.values() {
+        let item = instance.item(pi.item_id);
+        let transf = Transformation::from_dt(&pi.d_transf).transform(&inv_bin_transf);
+        let color = match item.base_quality {
+            None => theme.item_fill,
+            Some(q) => svg_util::blend_colors(theme.item_fill, theme.qz_fill[q]),
+        };
+        set_fill(&mut content, color);
+        polygon_path(&mut content, &item.shape.transform_clone(&transf));
+        content.fill_nonzero_and_stroke();
+    }
+    content.restore_state();
+    content
+}
+
+fn bin_label(s_layout: &LayoutSnapshot) -> String {
+    let bin = &s_layout.bin;
+    match &bin.name {
+        Some(name) => format!("bin {} ({})", bin.id, name),
+        None => format!("bin {}", bin.id),
+    }
+}
+
+fn polygon_path(content: &mut Content, polygon: &SimplePolygon) {
+    let points = &polygon.points;
+    content.move_to(points[0].0, points[0].1);
+    for p in &points[1..] {
+        content.line_to(p.0, p.1);
+    }
+    content.close_path();
+}
+
+fn set_fill(content: &mut Content, color: Color) {
+    let (r, g, b) = color.rgb();
+    content.set_fill_rgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+}
+
+fn set_stroke(content: &mut Content, color: Color) {
+    let (r, g, b) = color.rgb();
+    content.set_stroke_rgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+}
+
+/// Writes lines of text from the top of a page down, and starts a new page when one is full
+struct TextWriter {
+    pages: Vec<Content>,
+    y: f32,
+}
+
+impl TextWriter {
+    fn new() -> Self {
+        Self {
+            pages: vec![Content::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn line(&mut self, text: &str, font: Name, size: f32) {
+        self.cells(&[(text, 0.0)], font, size);
+    }
+
+    /// Writes the texts at the horizontal offsets of the columns
+    fn row(&mut self, texts: &[&str], font: Name, columns: &[f32]) {
+        let cells = texts
+            .iter()
+            .copied()
+            .zip(columns.iter().copied())
+            .collect_vec();
+        self.cells(&cells, font, FONT_SIZE);
+    }
+
+    /// Writes every text at its horizontal offset, on a new line
+    fn cells(&mut self, cells: &[(&str, f32)], font: Name, size: f32) {
+        let height = LINE_HEIGHT.max(1.5 * size);
+        if self.y - height < MARGIN {
+            self.pages.push(Content::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+        let content = self.pages.last_mut().unwrap();
+        for &(text, x) in cells {
+            content
+                .begin_text()
+                .set_font(font, size)
+                .next_line(MARGIN + x, self.y)
+                .show(Str(&encode(text)))
+                .end_text();
+        }
+    }
+
+    fn skip(&mut self) {
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn finish(self) -> Vec<Content> {
+        self.pages
+    }
+}
+
+/// Text in the encoding of the standard fonts, characters outside of ASCII are replaced by `?`
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c.is_ascii() && !c.is_ascii_control() {
+            true => c as u8,
+            false => b'?',
+        })
+        .collect()
+}
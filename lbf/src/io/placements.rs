@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::Path;
+
+use itertools::Itertools;
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::fsize;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_traits::{Shape, Transformable};
+use jagua_rs::geometry::transformation::Transformation;
+use jagua_rs::io::json_solution::{
+    JsonContainer, JsonLayout, JsonLayoutStats, JsonPlacedItem, JsonSolution, JsonTransformation,
+};
+use jagua_rs::io::parser;
+use jagua_rs::util::fpa::FPA;
+
+use crate::io::error::IoError;
+
+/// Reads the layouts of a solution file: a solution in our JSON schema (`.json`, either a bare `JsonSolution`
+/// or the output of `lbf` containing one under `Solution`), or placements in the line-based format of [`parse_line_placements`]
+pub fn read_solution_layouts(path: &Path, instance: &Instance) -> Result<Vec<JsonLayout>, IoError> {
+    let contents = fs::read_to_string(path).map_err(|source| IoError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let json_err = |source| IoError::Json {
+        path: Some(path.to_path_buf()),
+        source,
+    };
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    match is_json {
+        true => {
+            let mut value: serde_json::Value = serde_json::from_str(&contents).map_err(json_err)?;
+            if let Some(solution) = value.get_mut("Solution") {
+                value = solution.take();
+            }
+            let json_solution: JsonSolution = serde_json::from_value(value).map_err(json_err)?;
+            Ok(json_solution.layouts)
+        }
+        false => {
+            parse_line_placements(&contents, instance).map_err(|reason| IoError::InvalidFile {
+                path: path.to_path_buf(),
+                reason,
+            })
+        }
+    }
+}
+
+/// Parses placements in the line-based format of third-party nesters, one placement per line:
+/// `<sheet> <bin> <item> <x> <y> <rotation>`, separated by whitespace or commas.
+/// Placements with the same `sheet` (any label) form a layout in the bin with index `bin`, which is ignored in strip packing.
+/// `item` is the index of the item in the instance, `x` and `y` its translation and `rotation` its rotation in degrees,
+/// in the coordinates of the input like the transformations of a JSON solution.
+/// Empty lines and lines starting with `#` are skipped.
+/// The width of a strip is the extent of the items placed in it.
+/// Returns a description of the first malformed line, if any.
+pub fn parse_line_placements(
+    contents: &str,
+    instance: &Instance,
+) -> Result<Vec<JsonLayout>, String> {
+    let mut sheets: Vec<(String, usize, Vec<JsonPlacedItem>)> = vec![];
+    for (line_idx, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|f| !f.is_empty())
+            .collect_vec();
+        let [sheet, bin, item, x, y, rotation] = fields[..] else {
+            return Err(format!(
+                "line {}: expected 6 fields (sheet, bin, item, x, y, rotation), found {}: {}",
+                line_idx + 1,
+                fields.len(),
+                line
+            ));
+        };
+        let parse_num = |field: &str, name: &str| -> Result<fsize, String> {
+            field.parse().map_err(|err| {
+                format!("line {}: invalid {} {}: {}", line_idx + 1, name, field, err)
+            })
+        };
+        let parse_index = |field: &str, name: &str| -> Result<usize, String> {
+            field.parse().map_err(|err| {
+                format!("line {}: invalid {} {}: {}", line_idx + 1, name, field, err)
+            })
+        };
+        let bin = match instance {
+            Instance::BP(_) => parse_index(bin, "bin")?,
+            Instance::SP(_) => 0,
+        };
+        let placed_item = JsonPlacedItem {
+            index: parse_index(item, "item")?,
+            copy: None,
+            transformation: JsonTransformation {
+                rotation: parse_num(rotation, "rotation")?.to_radians(),
+                translation: (parse_num(x, "x")?, parse_num(y, "y")?),
+            },
+        };
+        match sheets.iter_mut().find(|(label, _, _)| label == sheet) {
+            Some((_, sheet_bin, placed_items)) => {
+                if *sheet_bin != bin {
+                    return Err(format!(
+                        "line {}: sheet {} was placed in bin {} before",
+                        line_idx + 1,
+                        sheet,
+                        sheet_bin
+                    ));
+                }
+                placed_items.push(placed_item);
+            }
+            None => sheets.push((sheet.to_string(), bin, vec![placed_item])),
+        }
+    }
+
+    let layouts = sheets
+        .into_iter()
+        .map(|(_, bin, placed_items)| {
+            let container = match instance {
+                Instance::BP(_) => JsonContainer::Bin { index: bin },
+                Instance::SP(sp_instance) => JsonContainer::Strip {
+                    width: strip_extent(&placed_items, instance),
+                    height: sp_instance.strip_height / instance.unit_scale(),
+                },
+            };
+            JsonLayout {
+                container,
+                name: None,
+                placed_items,
+                cut_order: vec![],
+                statistics: JsonLayoutStats::default(),
+            }
+        })
+        .collect_vec();
+    Ok(layouts)
+}
+
+/// Maximum x-coordinate of the placed items, in the coordinates of the input, plus the tolerance of a fitted strip
+fn strip_extent(placed_items: &[JsonPlacedItem], instance: &Instance) -> fsize {
+    let x_max = placed_items
+        .iter()
+        .filter(|jpi| jpi.index < instance.items().len())
+        .map(|jpi| {
+            let item = instance.item(jpi.index);
+            let (tx, ty) = jpi.transformation.translation;
+            let abs_transf = DTransformation::new(
+                jpi.transformation.rotation,
+                (tx * instance.unit_scale(), ty * instance.unit_scale()),
+            );
+            let transf = parser::absolute_to_internal_transform(
+                &abs_transf,
+                &item.pretransform,
+                &Transformation::empty(),
+            );
+            item.shape.transform_clone(&transf).bbox().x_max / instance.unit_scale()
+        })
+        .fold(0.0, fsize::max);
+    //like a fitted strip, so the last item does not touch its end
+    x_max * (1.0 + FPA::tolerance())
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{self, fontdb};
+use svg::Document;
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::layout::{Layout, LayoutSnapshot};
+
+use crate::io::layout_to_svg::{layout_to_svg, s_layout_to_svg, s_layout_to_svg_lod};
+use crate::io::svg_util::{LevelOfDetail, SvgDrawOptions};
+
+/// Widest image that is rendered, guards against huge allocations for widths requested over HTTP
+pub const MAX_PNG_WIDTH: u32 = 8192;
+
+/// Width of the images if none is requested
+pub const DEFAULT_PNG_WIDTH: u32 = 1600;
+
+/// Fonts of the system, for the text of the parts list. Loaded once, which takes a while on systems with many fonts
+static FONTS: Lazy<Arc<fontdb::Database>> = Lazy::new(|| {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+/// Rasterizes a layout to a PNG image `width` pixels wide, by rendering its SVG (see [`layout_to_svg`]).
+/// The height follows from the aspect ratio of the drawing.
+pub fn layout_to_png(
+    layout: &Layout,
+    instance: &Instance,
+    options: SvgDrawOptions,
+    width: u32,
+) -> Result<Vec<u8>, String> {
+    document_to_png(&layout_to_svg(layout, instance, options), width)
+}
+
+/// Same as [`layout_to_png`], for a layout of a solution
+pub fn s_layout_to_png(
+    s_layout: &LayoutSnapshot,
+    instance: &Instance,
+    options: SvgDrawOptions,
+    width: u32,
+) -> Result<Vec<u8>, String> {
+    document_to_png(&s_layout_to_svg(s_layout, instance, options), width)
+}
+
+/// Thumbnail of a layout of a solution, `width` pixels wide and drawn at the matching level of detail (see [`s_layout_to_svg_lod`])
+pub fn s_layout_to_png_thumbnail(
+    s_layout: &LayoutSnapshot,
+    instance: &Instance,
+    options: SvgDrawOptions,
+    width: u32,
+) -> Result<Vec<u8>, String> {
+    let level = LevelOfDetail::Thumbnail { width };
+    document_to_png(
+        &s_layout_to_svg_lod(s_layout, instance, options, level),
+        width,
+    )
+}
+
+/// Rasterizes an SVG document, see [`svg_to_png`]
+pub fn document_to_png(document: &Document, width: u32) -> Result<Vec<u8>, String> {
+    svg_to_png(document.to_string().as_bytes(), width)
+}
+
+/// Rasterizes an SVG (e.g. one written by the solver) to a PNG image `width` pixels wide, on a white background
+pub fn svg_to_png(svg: &[u8], width: u32) -> Result<Vec<u8>, String> {
+    if width == 0 || width > MAX_PNG_WIDTH {
+        return Err(format!(
+            "width should be between 1 and {} pixels, got {}",
+            MAX_PNG_WIDTH, width
+        ));
+    }
+    let options = usvg::Options {
+        fontdb: Arc::clone(&FONTS),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_data(svg, &options).map_err(|err| err.to_string())?;
+
+    let scale = width as f32 / tree.size().width();
+    let height = (tree.size().height() * scale).ceil() as u32;
+    if height > MAX_PNG_WIDTH {
+        return Err(format!(
+            "image of {} pixels wide would be {} pixels high, more than {}",
+            width, height, MAX_PNG_WIDTH
+        ));
+    }
+    let mut pixmap = Pixmap::new(width, height.max(1)).ok_or("could not allocate image")?;
+    pixmap.fill(resvg::tiny_skia::Color::WHITE);
+    resvg::render(
+        &tree,
+        Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap.encode_png().map_err(|err| err.to_string())
+}
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use jagua_rs::io::asset_paths::AssetPaths;
+use jagua_rs::io::json_instance::JsonInstance;
+use jagua_rs::io::json_solution::JsonSolutionMetadata;
+
+use crate::io;
+use crate::io::error::IoError;
+use crate::io::json_output;
+use crate::lbf_config::LBFConfig;
+
+/// Name of the manifest in a repro bundle
+const MANIFEST_FILE: &str = "repro.json";
+/// Name of the config in a repro bundle
+const CONFIG_FILE: &str = "config.json";
+/// Folder in a repro bundle of the files which are not referred to by a relative path
+pub const EXTERNAL_FOLDER: &str = "external";
+
+/// Describes the contents of a repro bundle
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReproManifest {
+    /// Name of the instance file in the bundle.
+    /// DXF and SVG files referenced by the instance are stored at their path relative to it
+    pub instance_file: String,
+    /// Version of the solver, and hashes of the config and instance in the bundle
+    pub metadata: JsonSolutionMetadata,
+}
+
+/// Run extracted from a repro bundle
+#[derive(Clone)]
+pub struct Repro {
+    /// Path of the extracted instance file
+    pub instance_path: PathBuf,
+    pub config: LBFConfig,
+    pub manifest: ReproManifest,
+}
+
+/// Writes a zip archive with everything required to reproduce a run: the instance, the config,
+/// the DXF files the instance refers to (resolved with `asset_paths`) and the version of the solver.
+/// Files which are not referred to by a plain relative path (absolute paths, paths with `..` or environment variables)
+/// are stored in [`EXTERNAL_FOLDER`] instead, and the bundled instance refers to them there.
+/// The config should have its PRNG seed set, otherwise the run cannot be reproduced exactly.
+pub fn export_repro(
+    bundle_path: &Path,
+    instance_file: &Path,
+    json_instance: &JsonInstance,
+    config: &LBFConfig,
+    asset_paths: &AssetPaths,
+) -> Result<(), IoError> {
+    if config.prng_seed.is_none() {
+        warn!(
+            "[REPRO] the config has no PRNG seed, the bundled run will not be reproduced exactly"
+        );
+    }
+    let instance_file = instance_file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| IoError::InvalidFile {
+            path: instance_file.to_path_buf(),
+            reason: "instance file has no valid file name".into(),
+        })?
+        .to_string();
+
+    //every DXF (or SVG) file is stored once, even if it contains multiple items. Embedded files are part of the instance
+    let dxf_paths = json_instance
+        .items
+        .iter()
+        .flat_map(|item| [item.dxf.as_deref(), item.svg.as_deref()])
+        .flatten()
+        .filter(|path| !json_instance.assets.contains_key(*path))
+        .collect::<BTreeSet<_>>();
+    let bundled_names = bundled_names(&dxf_paths);
+    let mut bundled_instance = json_instance.clone();
+    for item in bundled_instance.items.iter_mut() {
+        for path in [&mut item.dxf, &mut item.svg].into_iter().flatten() {
+            if let Some(name) = bundled_names.get(path.as_str()) {
+                *path = name.clone();
+            }
+        }
+    }
+
+    let manifest = ReproManifest {
+        instance_file: instance_file.clone(),
+        metadata: json_output::solution_metadata(&bundled_instance, config),
+    };
+
+    let bundle_err = |source: std::io::Error| IoError::Io {
+        path: bundle_path.to_path_buf(),
+        source,
+    };
+    let file = File::create(bundle_path).map_err(bundle_err)?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut add_file = |name: &str, bytes: &[u8]| {
+        zip.start_file(name, options)
+            .map_err(std::io::Error::from)
+            .and_then(|_| zip.write_all(bytes))
+            .map_err(bundle_err)
+    };
+    add_file(MANIFEST_FILE, &to_json(&manifest))?;
+    add_file(CONFIG_FILE, &to_json(config))?;
+    add_file(&instance_file, &to_json(&bundled_instance))?;
+
+    for dxf_path in &dxf_paths {
+        let path = asset_paths
+            .resolve(dxf_path)
+            .map_err(|source| IoError::Io {
+                path: asset_paths.default_path(dxf_path),
+                source,
+            })?;
+        let bytes = fs::read(&path).map_err(|source| IoError::Io { path, source })?;
+        add_file(&bundled_names[dxf_path], &bytes)?;
+    }
+
+    zip.finish().map_err(|err| bundle_err(err.into()))?;
+    info!(
+        "[REPRO] bundle with {} and {} DXF/SVG files written to file://{}",
+        instance_file,
+        dxf_paths.len(),
+        fs::canonicalize(bundle_path)
+            .map_err(bundle_err)?
+            .to_string_lossy()
+    );
+    Ok(())
+}
+
+/// Name in the bundle of every referenced file: its own path if it is a plain relative one,
+/// otherwise a generated one in [`EXTERNAL_FOLDER`]
+fn bundled_names<'a>(paths: &BTreeSet<&'a str>) -> BTreeMap<&'a str, String> {
+    let is_plain_relative = |path: &str| {
+        !path.contains('$')
+            && Path::new(path)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+    };
+    paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let name = match is_plain_relative(path) {
+                true => path.to_string(),
+                false => {
+                    let file_name = Path::new(path)
+                        .file_name()
+                        .map_or("file".into(), |name| name.to_string_lossy());
+                    format!("{}/{}/{}", EXTERNAL_FOLDER, i, file_name)
+                }
+            };
+            (*path, name)
+        })
+        .collect()
+}
+
+/// Extracts a bundle written by [`export_repro`] to `folder`, and reads its config and manifest.
+/// Warns if the bundle was created by another version of the solver.
+pub fn import_repro(bundle_path: &Path, folder: &Path) -> Result<Repro, IoError> {
+    let file = File::open(bundle_path).map_err(|source| IoError::Io {
+        path: bundle_path.to_path_buf(),
+        source,
+    })?;
+    let invalid_bundle = |err: ZipError| IoError::InvalidFile {
+        path: bundle_path.to_path_buf(),
+        reason: err.to_string(),
+    };
+    let mut archive = ZipArchive::new(BufReader::new(file)).map_err(invalid_bundle)?;
+    fs::create_dir_all(folder).map_err(|source| IoError::Io {
+        path: folder.to_path_buf(),
+        source,
+    })?;
+    archive.extract(folder).map_err(invalid_bundle)?;
+
+    let manifest: ReproManifest = io::read_json_file(&folder.join(MANIFEST_FILE))?;
+    let config: LBFConfig = io::read_json_file(&folder.join(CONFIG_FILE))?;
+
+    let (version, git_version) = (env!("CARGO_PKG_VERSION"), option_env!("LBF_GIT_VERSION"));
+    if manifest.metadata.solver_version != version
+        || manifest.metadata.git_version.as_deref() != git_version
+    {
+        warn!(
+            "[REPRO] bundle was created with {} {} ({}), running with version {} ({})",
+            manifest.metadata.solver,
+            manifest.metadata.solver_version,
+            manifest
+                .metadata
+                .git_version
+                .as_deref()
+                .unwrap_or("unknown commit"),
+            version,
+            git_version.unwrap_or("unknown commit"),
+        );
+    }
+    info!(
+        "[REPRO] extracted bundle with {} to {}",
+        manifest.instance_file,
+        folder.display()
+    );
+
+    Ok(Repro {
+        instance_path: folder.join(&manifest.instance_file),
+        config,
+        manifest,
+    })
+}
+
+fn to_json(value: &impl Serialize) -> Vec<u8> {
+    serde_json::to_vec_pretty(value).expect("could not serialize to JSON")
+}
@@ -0,0 +1,117 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use jagua_rs::collision_detection::cd_trace::CDTrace;
+use jagua_rs::entities::solution::Solution;
+use jagua_rs::fsize;
+
+use crate::io::csv_export::escape;
+
+pub const STATS_CSV_HEADER: &str = "instance,prng_seed,run_time_ms,n_layouts,usage,layout_usages,n_placed,n_unplaced,n_samples,n_cd_queries,n_cd_collisions";
+
+/// Statistics of a single solve, one record of the file written by `--stats-file`
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RunStats {
+    pub instance: String,
+    pub prng_seed: Option<u64>,
+    pub run_time_ms: u128,
+    pub usage: fsize,
+    /// Usage of every layout, in the order of the solution
+    pub layout_usages: Vec<fsize>,
+    pub n_placed: usize,
+    pub n_unplaced: usize,
+    /// Placements evaluated by the samplers, over all runs
+    pub n_samples: usize,
+    /// Collision queries of all CDEs, including those of the calibration probes (if any)
+    pub n_cd_queries: u64,
+    /// Collision queries which were decided as a collision
+    pub n_cd_collisions: u64,
+}
+
+/// Format of a stats file, derived from its extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsFormat {
+    /// One row per solve, with a header
+    Csv,
+    /// One JSON object per line (`.jsonl` or `.ndjson`)
+    JsonLines,
+}
+
+impl StatsFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl" | "ndjson") => StatsFormat::JsonLines,
+            _ => StatsFormat::Csv,
+        }
+    }
+}
+
+impl RunStats {
+    pub fn new(
+        instance: &str,
+        prng_seed: Option<u64>,
+        solution: &Solution,
+        run_time: Duration,
+        n_samples: usize,
+        cd_trace: &CDTrace,
+    ) -> Self {
+        let n_placed = solution.placed_item_qtys.iter().sum::<usize>();
+        let n_demanded = solution.target_item_qtys.iter().sum::<usize>();
+        Self {
+            instance: instance.to_string(),
+            prng_seed,
+            run_time_ms: run_time.as_millis(),
+            usage: solution.usage,
+            layout_usages: solution
+                .layout_snapshots
+                .iter()
+                .map(|sl| sl.usage)
+                .collect(),
+            n_placed,
+            n_unplaced: n_demanded - n_placed,
+            n_samples,
+            n_cd_queries: cd_trace.total(),
+            n_cd_collisions: cd_trace
+                .iter()
+                .filter(|(stage, _)| stage.collides())
+                .map(|(_, count)| count)
+                .sum(),
+        }
+    }
+
+    /// Row of the CSV file, matching [`STATS_CSV_HEADER`]. The usages of the layouts are separated by semicolons
+    pub fn csv_row(&self) -> String {
+        let mut row = String::new();
+        write!(
+            row,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            escape(&self.instance),
+            self.prng_seed.map(|s| s.to_string()).unwrap_or_default(),
+            self.run_time_ms,
+            self.layout_usages.len(),
+            self.usage,
+            self.layout_usages.iter().join(";"),
+            self.n_placed,
+            self.n_unplaced,
+            self.n_samples,
+            self.n_cd_queries,
+            self.n_cd_collisions
+        )
+        .unwrap();
+        row
+    }
+
+    /// Record in the given format, without a trailing newline
+    pub fn record(&self, format: StatsFormat) -> String {
+        match format {
+            StatsFormat::Csv => self.csv_row(),
+            StatsFormat::JsonLines => {
+                serde_json::to_string(self).expect("could not serialize run stats")
+            }
+        }
+    }
+}
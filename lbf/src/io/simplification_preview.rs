@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use log::info;
+use svg::node::element::{Group, Title};
+use svg::Document;
+
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::entities::item::Item;
+use jagua_rs::fsize;
+use jagua_rs::geometry::geo_traits::Shape;
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
+use jagua_rs::geometry::primitives::point::Point;
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+use jagua_rs::io::json_instance::JsonInstance;
+use jagua_rs::io::parse_error::ParseError;
+use jagua_rs::io::parser::Parser;
+use jagua_rs::util::polygon_simplification::PolySimplConfig;
+
+use crate::io::svg_export;
+use crate::lbf_config::LBFConfig;
+
+/// Comparison between the shape of an item as defined in the input and the shape the solver works with
+pub struct SimplificationPreview {
+    pub item_id: usize,
+    /// The item parsed without simplification
+    pub original: Item,
+    /// The item parsed with the simplification of the configuration
+    pub simplified: Item,
+    /// Largest distance between a vertex of either contour and the other contour
+    pub max_deviation: fsize,
+}
+
+impl SimplificationPreview {
+    /// Relative change of the area by the simplification, positive if the simplified shape is larger
+    pub fn area_change(&self) -> fsize {
+        self.simplified.shape.area() / self.original.shape.area() - 1.0
+    }
+}
+
+/// Parses the items of the instance both with and without the simplification of the configuration.
+/// Shapes are not centered, so both remain in the coordinates of the input
+pub fn preview_simplification(
+    json_instance: &JsonInstance,
+    config: &LBFConfig,
+    assets_folder: PathBuf,
+) -> Result<Vec<SimplificationPreview>, ParseError> {
+    let parse = |poly_simpl_config: PolySimplConfig| {
+        Parser::new(
+            poly_simpl_config,
+            config.cde_config,
+            false,
+            assets_folder.clone(),
+            config.fidelity(),
+        )
+        .parse(json_instance)
+    };
+    let original = parse(PolySimplConfig::Disabled)?;
+    let simplified = parse(config.poly_simpl_config())?;
+
+    let previews = original
+        .items()
+        .iter()
+        .zip(simplified.items().iter())
+        .map(|((original, _), (simplified, _))| SimplificationPreview {
+            item_id: original.id,
+            max_deviation: fsize::max(
+                max_vertex_distance(&original.shape, &simplified.shape),
+                max_vertex_distance(&simplified.shape, &original.shape),
+            ),
+            original: original.clone(),
+            simplified: simplified.clone(),
+        })
+        .collect();
+    Ok(previews)
+}
+
+/// Logs the changes made by the simplification to every item
+pub fn log_previews(previews: &[SimplificationPreview]) {
+    for preview in previews {
+        info!(
+            "[PREVIEW] item {}: {} -> {} vertices, area {:+.3}%, max deviation: {:.3}",
+            preview.item_id,
+            preview.original.shape.number_of_points(),
+            preview.simplified.shape.number_of_points(),
+            preview.area_change() * 100.0,
+            preview.max_deviation
+        );
+    }
+}
+
+/// Draws the simplified contour of an item (red outline) over its original contour (grey)
+pub fn preview_to_svg(preview: &SimplificationPreview) -> Document {
+    let (original, simplified) = (&preview.original, &preview.simplified);
+    let vbox = AARectangle::bounding_rectangle(&original.shape.bbox(), &simplified.shape.bbox())
+        .scale(1.05);
+    let stroke_width = fsize::min(vbox.width(), vbox.height()) * 0.002;
+
+    let original_data = match &original.arc_shape {
+        Some(arc_shape) => svg_export::arc_polygon_data(arc_shape),
+        None => svg_export::simple_polygon_data(&original.shape),
+    };
+    let original_path = svg_export::data_to_path(
+        original_data,
+        &[
+            ("fill", "#D3D3D3"),
+            ("stroke", "black"),
+            ("stroke-width", &*format!("{}", stroke_width)),
+        ],
+    )
+    .add(Title::new(format!(
+        "original: {} vertices, area: {:.3}",
+        original.shape.number_of_points(),
+        original.shape.area()
+    )));
+
+    let simplified_path = svg_export::data_to_path(
+        svg_export::simple_polygon_data(&simplified.shape),
+        &[
+            ("fill", "none"),
+            ("stroke", "red"),
+            ("stroke-width", &*format!("{}", 2.0 * stroke_width)),
+        ],
+    )
+    .add(Title::new(format!(
+        "simplified: {} vertices, area: {:.3} ({:+.3}%), max deviation: {:.3}",
+        simplified.shape.number_of_points(),
+        simplified.shape.area(),
+        preview.area_change() * 100.0,
+        preview.max_deviation
+    )));
+
+    let group = Group::new()
+        .add(Title::new(format!("item #{}", preview.item_id)))
+        .add(original_path)
+        .add(simplified_path);
+
+    Document::new()
+        .set(
+            "viewBox",
+            (vbox.x_min, vbox.y_min, vbox.width(), vbox.height()),
+        )
+        .add(group)
+}
+
+/// Largest distance from a vertex of `from` to the contour of `to`
+fn max_vertex_distance(from: &SimplePolygon, to: &SimplePolygon) -> fsize {
+    let edges = to.edge_iter().collect_vec();
+    from.points
+        .iter()
+        .map(|p: &Point| {
+            edges
+                .iter()
+                .map(|e| p.distance(e.closest_point_on_edge(p)))
+                .fold(fsize::INFINITY, fsize::min)
+        })
+        .fold(0.0, fsize::max)
+}
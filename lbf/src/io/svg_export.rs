@@ -1,14 +1,17 @@
-use svg::node::element::path::Data;
+use itertools::Itertools;
+use svg::node::element::path::{Command, Data, Parameters};
 use svg::node::element::{Circle, Path};
 
 use jagua_rs::collision_detection::hazard::HazardEntity;
-use jagua_rs::collision_detection::quadtree::qt_hazard::QTHazPresence;
-use jagua_rs::collision_detection::quadtree::qt_node::QTNode;
+use jagua_rs::geometry::primitives::arc_polygon::{ArcPolygon, CircularArc};
 use jagua_rs::geometry::primitives::edge::Edge;
 use jagua_rs::geometry::primitives::point::Point;
 use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+use jagua_rs::prelude::{QTHazPresence, QTNode};
 use jagua_rs::{fsize, geometry};
 
+use crate::io;
+
 pub fn simple_polygon_data(s_poly: &SimplePolygon) -> Data {
     let mut data = Data::new().move_to::<(fsize, fsize)>(s_poly.get_point(0).into());
     for i in 1..s_poly.number_of_points() {
@@ -17,6 +20,134 @@ pub fn simple_polygon_data(s_poly: &SimplePolygon) -> Data {
     data.close()
 }
 
+/// Path data of a closed contour through the points
+pub fn points_data(points: &[Point]) -> Data {
+    let mut data = Data::new().move_to::<(fsize, fsize)>(points[0].into());
+    for p in &points[1..] {
+        data = data.line_to::<(fsize, fsize)>((*p).into());
+    }
+    data.close()
+}
+
+/// Leaves out the vertices of a closed contour which deviate less than `tolerance` from the simplified contour (Ramer-Douglas-Peucker).
+/// Unlike the simplification of the parser, the contour can grow or shrink, it is meant for drawing only.
+/// Contours which collapse to fewer than three points are replaced by their bounding box.
+pub fn simplify_contour(points: &[Point], tolerance: fsize) -> Vec<Point> {
+    let n = points.len();
+    if n <= 3 {
+        return points.to_vec();
+    }
+    //the contour is split in two at the first point and the point farthest from it
+    let far = (1..n)
+        .max_by(|&i, &j| {
+            let d_i = points[0].sq_distance(points[i]);
+            d_i.total_cmp(&points[0].sq_distance(points[j]))
+        })
+        .unwrap();
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[far] = true;
+    //sections between kept vertices, as indices in the contour, `n` being the first point again
+    let mut sections = vec![(0, far), (far, n)];
+    while let Some((start, end)) = sections.pop() {
+        let (a, b) = (points[start], points[end % n]);
+        let farthest = (start + 1..end)
+            .map(|i| (i, segment_distance(a, b, points[i])))
+            .max_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+        if let Some((i, d)) = farthest {
+            if d > tolerance {
+                keep[i] = true;
+                sections.push((start, i));
+                sections.push((i, end));
+            }
+        }
+    }
+    let simplified = points
+        .iter()
+        .zip(keep)
+        .filter_map(|(p, keep)| keep.then_some(*p))
+        .collect::<Vec<_>>();
+    match simplified.len() >= 3 {
+        true => simplified,
+        false => {
+            let (x_min, x_max) = points.iter().map(|p| p.0).minmax().into_option().unwrap();
+            let (y_min, y_max) = points.iter().map(|p| p.1).minmax().into_option().unwrap();
+            vec![
+                Point(x_min, y_min),
+                Point(x_max, y_min),
+                Point(x_max, y_max),
+                Point(x_min, y_max),
+            ]
+        }
+    }
+}
+
+/// Distance from `p` to the segment between `a` and `b`
+fn segment_distance(a: Point, b: Point, p: Point) -> fsize {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = match len_sq == 0.0 {
+        true => 0.0,
+        false => (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0),
+    };
+    p.distance(Point(a.0 + t * dx, a.1 + t * dy))
+}
+
+/// Path data of an arc polygon, with its arcs drawn exactly
+pub fn arc_polygon_data(a_poly: &ArcPolygon) -> Data {
+    let mut data = Data::new().move_to::<(fsize, fsize)>(a_poly.points[0].into());
+    for i in 0..a_poly.number_of_edges() {
+        let (start, end, bulge) = a_poly.get_edge(i);
+        data = match bulge == 0.0 {
+            true => data.line_to::<(fsize, fsize)>(end.into()),
+            false => {
+                let arc = CircularArc::from_bulge(start, end, bulge);
+                data.elliptical_arc_to((
+                    arc.radius,
+                    arc.radius,
+                    0.0,
+                    arc.is_large() as u8,
+                    (arc.sweep_angle > 0.0) as u8,
+                    end.0,
+                    end.1,
+                ))
+            }
+        };
+    }
+    data.close()
+}
+
+/// Path data with all coordinates rounded to `decimals` decimals, see [`round_decimals`](crate::io::round_decimals)
+pub fn round_data(data: &Data, decimals: u32) -> Data {
+    let round = |params: &Parameters| {
+        Parameters::from(
+            params
+                .iter()
+                .map(|&v| io::round_decimals(v, decimals))
+                .collect::<Vec<_>>(),
+        )
+    };
+    data.iter()
+        .map(|command| match command {
+            Command::Move(pos, params) => Command::Move(*pos, round(params)),
+            Command::Line(pos, params) => Command::Line(*pos, round(params)),
+            Command::HorizontalLine(pos, params) => Command::HorizontalLine(*pos, round(params)),
+            Command::VerticalLine(pos, params) => Command::VerticalLine(*pos, round(params)),
+            Command::QuadraticCurve(pos, params) => Command::QuadraticCurve(*pos, round(params)),
+            Command::SmoothQuadraticCurve(pos, params) => {
+                Command::SmoothQuadraticCurve(*pos, round(params))
+            }
+            Command::CubicCurve(pos, params) => Command::CubicCurve(*pos, round(params)),
+            Command::SmoothCubicCurve(pos, params) => {
+                Command::SmoothCubicCurve(*pos, round(params))
+            }
+            Command::EllipticalArc(pos, params) => Command::EllipticalArc(*pos, round(params)),
+            Command::Close => Command::Close,
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
 pub fn quad_tree_data(qt_root: &QTNode, irrelevant_hazards: &[HazardEntity]) -> (Data, Data, Data) {
     qt_node_data(
         qt_root,
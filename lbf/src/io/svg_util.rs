@@ -20,6 +20,12 @@ pub struct SvgDrawOptions {
     ///Draw the fail fast surrogate on top of each item
     #[serde(default)]
     pub surrogate: bool,
+    ///Draw a table of the placed parts (name, quantity and rotation) below the layout
+    #[serde(default)]
+    pub parts_list: bool,
+    ///Number of decimals of the coordinates of the contours and of the placements, full precision if undefined
+    #[serde(default)]
+    pub precision: Option<u32>,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Copy)]
@@ -84,6 +90,17 @@ impl SvgLayoutTheme {
     }
 }
 
+/// Level of detail of the drawing of a layout, see [`layout_to_svg_lod`](crate::io::layout_to_svg::layout_to_svg_lod)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelOfDetail {
+    /// Every vertex of every contour
+    #[default]
+    Full,
+    /// Meant to be shown `width` pixels wide: detail smaller than a pixel is left out of the contours
+    Thumbnail { width: u32 },
+}
+
 pub fn change_brightness(color: Color, fraction: fsize) -> Color {
     let Color(r, g, b) = color;
 
@@ -108,6 +125,12 @@ pub fn blend_colors(color_1: Color, color_2: Color) -> Color {
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Color(u8, u8, u8);
 
+impl Color {
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        (self.0, self.1, self.2)
+    }
+}
+
 impl Display for Color {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "#{:02X}{:02X}{:02X}", self.0, self.1, self.2)
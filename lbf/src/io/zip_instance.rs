@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use itertools::Itertools;
+use log::{info, warn};
+use zip::ZipArchive;
+
+use jagua_rs::io::json_instance::{JsonAsset, JsonInstance};
+
+/// Largest uncompressed size of a file read from an archive, guards against zip bombs in uploads
+pub const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Reads an instance from a zip archive containing the instance JSON together with the DXF and SVG files it references.
+/// The instance is the only `.json` file of the archive, it can be in a subfolder.
+/// The file paths of the items are resolved inside the archive, relative to the instance file,
+/// or by file name if the archive does not have the same folder structure (e.g. when it was zipped flat).
+/// The referenced files are embedded in the returned instance (see [`JsonInstance::assets`]), so it can be parsed without extracting the archive.
+/// Files which are not found in the archive are left to the assets folders of the `Parser`.
+pub fn zip_instance(archive: &[u8]) -> Result<JsonInstance, String> {
+    let mut archive = ZipArchive::new(Cursor::new(archive)).map_err(|err| err.to_string())?;
+    let entries = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| (normalize(name), name.to_string()))
+        .collect::<BTreeMap<_, _>>();
+
+    let instance_files = entries
+        .keys()
+        .filter(|name| {
+            Path::new(name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        })
+        .collect_vec();
+    let instance_file = match instance_files.as_slice() {
+        [instance_file] => (*instance_file).clone(),
+        [] => return Err("archive contains no instance (.json) file".into()),
+        _ => {
+            return Err(format!(
+                "archive contains multiple .json files, expected a single instance: {}",
+                instance_files.iter().join(", ")
+            ))
+        }
+    };
+    let mut json_instance: JsonInstance =
+        serde_json::from_slice(&read_entry(&mut archive, &entries[&instance_file])?)
+            .map_err(|err| format!("could not parse {}: {}", instance_file, err))?;
+
+    let instance_folder = instance_file
+        .rsplit_once('/')
+        .map_or("", |(folder, _)| folder);
+    let asset_files = json_instance
+        .items
+        .iter()
+        .flat_map(|item| [item.dxf.as_deref(), item.svg.as_deref()])
+        .flatten()
+        .filter(|file| !json_instance.assets.contains_key(*file))
+        .unique()
+        .map(str::to_string)
+        .collect_vec();
+    let mut n_embedded = 0;
+    for file in asset_files {
+        let entry = match find_entry(&entries, instance_folder, &file) {
+            Some(entry) => entry,
+            None => {
+                warn!("[ZIP] {} is not in the archive", file);
+                continue;
+            }
+        };
+        let contents = read_entry(&mut archive, entry)?;
+        json_instance
+            .assets
+            .insert(file, JsonAsset(contents.into()));
+        n_embedded += 1;
+    }
+    info!(
+        "[ZIP] instance {} from {} with {} DXF/SVG files",
+        json_instance.name, instance_file, n_embedded
+    );
+    Ok(json_instance)
+}
+
+/// Name of the entry with the file referenced by an item: at its path relative to the instance folder, or else the only entry with its file name
+fn find_entry<'a>(
+    entries: &'a BTreeMap<String, String>,
+    instance_folder: &str,
+    file: &str,
+) -> Option<&'a String> {
+    let path = normalize(&format!("{}/{}", instance_folder, file));
+    if let Some(entry) = entries.get(&path) {
+        return Some(entry);
+    }
+    let file_name = path.rsplit('/').next()?;
+    entries
+        .iter()
+        .filter(|(name, _)| name.rsplit('/').next() == Some(file_name))
+        .map(|(_, entry)| entry)
+        .exactly_one()
+        .ok()
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Vec<u8>, String> {
+    let entry = archive.by_name(name).map_err(|err| err.to_string())?;
+    if entry.size() > MAX_ENTRY_SIZE {
+        return Err(format!(
+            "{} is too large: {} bytes (uncompressed)",
+            name,
+            entry.size()
+        ));
+    }
+    let mut contents = vec![];
+    entry
+        .take(MAX_ENTRY_SIZE)
+        .read_to_end(&mut contents)
+        .map_err(|err| format!("could not read {}: {}", name, err))?;
+    Ok(contents)
+}
+
+/// Path with `/` separators and without `.`, `..` and empty components, for paths written on Windows or with a leading `./`
+fn normalize(path: &str) -> String {
+    let mut components: Vec<&str> = vec![];
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    components.join("/")
+}
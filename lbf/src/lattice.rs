@@ -0,0 +1,348 @@
+use itertools::Itertools;
+use log::info;
+
+use jagua_rs::collision_detection::hazard_filter;
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::placing_option::PlacingOption;
+use jagua_rs::entities::problems::problem::Problem;
+use jagua_rs::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+use jagua_rs::fsize;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_enums::AllowedRotation;
+use jagua_rs::geometry::geo_traits::{CollidesWith, Shape, Transformable};
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
+use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+use jagua_rs::geometry::transformation::Transformation;
+use jagua_rs::PI;
+
+/// Number of directions in which the second item of a pair is slid towards the first one
+const N_PAIR_DIRECTIONS: usize = 32;
+/// Number of horizontal offsets tried for the second lattice vector
+const N_ROW_OFFSETS: usize = 8;
+/// Number of coarse steps (per diameter) when sliding two shapes towards each other, before refining with bisection
+const N_SLIDE_STEPS: usize = 64;
+/// Number of bisection iterations to refine a contact position
+const N_BISECTION_STEPS: usize = 16;
+/// Clearance added to every contact position, as a fraction of the slide distance, to avoid false collisions due to rounding
+const CONTACT_MARGIN_FRACTION: fsize = 1e-4;
+
+/// A cell of congruent items which can be repeated over a lattice.
+/// The cell either contains a single item, or an interlocking pair of an item and its 180° rotation.
+#[derive(Clone, Debug)]
+pub struct LatticePattern {
+    pub item_id: usize,
+    /// Transformations of the items within a single cell
+    pub cell: Vec<DTransformation>,
+    /// First lattice vector (horizontal)
+    pub a: (fsize, fsize),
+    /// Second lattice vector
+    pub b: (fsize, fsize),
+}
+
+impl LatticePattern {
+    /// Searches for the densest lattice pattern of an item.
+    /// Contact positions between the shapes are found by sliding them towards each other,
+    /// which samples the boundary of the no-fit polygon of the item with itself (or its 180° rotation).
+    pub fn generate(item: &Item) -> Option<Self> {
+        //copies with their own attributes are not interchangeable
+        if !item.copy_attributes.is_empty() {
+            return None;
+        }
+        let base_rotations = match &item.allowed_rotation {
+            AllowedRotation::None => vec![0.0],
+            AllowedRotation::Continuous => vec![0.0, PI / 2.0],
+            AllowedRotation::Discrete(rotations) => rotations.clone(),
+            //the positions of the item are fixed, no pattern can be tiled
+            AllowedRotation::Explicit(_) => return None,
+        };
+
+        base_rotations
+            .iter()
+            .flat_map(|&r| {
+                let single = vec![DTransformation::new(r, (0.0, 0.0))];
+                let pair = rotation_allowed(&item.allowed_rotation, r + PI)
+                    .then(|| find_pair(&item.shape, r))
+                    .flatten();
+                [Some(single), pair].into_iter().flatten()
+            })
+            .filter_map(|cell| {
+                let (a, b) = find_lattice_vectors(&item.shape, &cell)?;
+                Some(LatticePattern {
+                    item_id: item.id,
+                    cell,
+                    a,
+                    b,
+                })
+            })
+            .max_by(|p1, p2| {
+                let (d1, d2) = (p1.density(item), p2.density(item));
+                d1.partial_cmp(&d2).unwrap()
+            })
+    }
+
+    /// Fraction of the area of a lattice cell covered by items
+    pub fn density(&self, item: &Item) -> fsize {
+        let cell_area = (self.a.0 * self.b.1 - self.a.1 * self.b.0).abs();
+        self.cell.len() as fsize * item.shape.area() / cell_area
+    }
+}
+
+/// Tiles the pattern over the bins of the problem, starting in the left-bottom corner.
+/// For strip packing problems the strip is widened to fit the entire demand.
+/// Returns the number of items placed, any remaining demand is left for the LBF heuristic.
+pub fn tile(problem: &mut Problem, item: &Item, pattern: &LatticePattern) -> usize {
+    let cell_shapes = pattern
+        .cell
+        .iter()
+        .map(|dt| item.shape.transform_clone(&dt.compose()))
+        .collect_vec();
+    let cell_bbox = cell_shapes
+        .iter()
+        .map(|s| s.bbox())
+        .reduce(|a, b| AARectangle::bounding_rectangle(&a, &b))
+        .expect("empty lattice cell");
+
+    if let Problem::SP(sp_problem) = problem {
+        //widen the strip so the whole demand fits in the lattice
+        let n_rows = ((sp_problem.strip_height() - cell_bbox.height()) / pattern.b.1).floor() + 1.0;
+        let n_missing = sp_problem.missing_item_qtys()[item.id].max(0) as fsize;
+        let n_cols = (n_missing / (n_rows.max(1.0) * pattern.cell.len() as fsize)).ceil();
+        //every column spans one horizontal lattice vector, rows are shifted by less than one
+        let required_width = (n_cols + 1.0) * pattern.a.0 + cell_bbox.width();
+        if required_width > sp_problem.strip_width() {
+            sp_problem.modify_strip_in_back(required_width);
+        }
+    }
+
+    let mut n_placed = 0;
+    loop {
+        let layout_idx = match &*problem {
+            Problem::SP(_) => problem.layout_indices().next(),
+            //open a new bin for every repetition of the lattice
+            Problem::BP(_) | Problem::Custom(_) => problem
+                .template_layout_indices_with_stock()
+                .find(|l| problem.get_layout(l).bin.accepts(item)),
+        };
+        let Some(layout_idx) = layout_idx else { break };
+        let n_placed_in_layout = tile_layout(problem, layout_idx, item, pattern, &cell_bbox);
+        n_placed += n_placed_in_layout;
+        let demand_met = problem.missing_item_qtys()[item.id] <= 0;
+        if n_placed_in_layout == 0 || demand_met || matches!(problem, Problem::SP(_)) {
+            break;
+        }
+    }
+
+    info!(
+        "[LATTICE] placed {} items with id {} in a lattice pattern ({} per cell, density: {:.3}%)",
+        n_placed,
+        item.id,
+        pattern.cell.len(),
+        pattern.density(item) * 100.0
+    );
+    n_placed
+}
+
+fn tile_layout(
+    problem: &mut Problem,
+    mut layout_idx: LayoutIndex,
+    item: &Item,
+    pattern: &LatticePattern,
+    cell_bbox: &AARectangle,
+) -> usize {
+    let bin_bbox = problem.get_layout(layout_idx).bin.bbox();
+    //translation which moves the cell to the left-bottom corner of the bin, keeping some margin from its boundary
+    let margin = cell_bbox.diameter() * CONTACT_MARGIN_FRACTION;
+    let origin = (
+        bin_bbox.x_min - cell_bbox.x_min + margin,
+        bin_bbox.y_min - cell_bbox.y_min + margin,
+    );
+
+    let n_rows = ((bin_bbox.height() - cell_bbox.height() - 2.0 * margin) / pattern.b.1).floor()
+        as isize
+        + 1;
+    let mut n_placed = 0;
+
+    //fill column by column, so the lattice grows from left to right
+    for i in 0.. {
+        let mut column_in_bin = false;
+        for j in 0..n_rows {
+            //shift every row back with whole lattice vectors, to keep the columns vertical
+            let shift = (j as fsize * pattern.b.0 / pattern.a.0).floor() as isize;
+            let k = i as isize - shift;
+            let translation = (
+                origin.0 + k as fsize * pattern.a.0 + j as fsize * pattern.b.0,
+                origin.1 + k as fsize * pattern.a.1 + j as fsize * pattern.b.1,
+            );
+            if translation.0 + cell_bbox.x_min > bin_bbox.x_max {
+                continue;
+            }
+            column_in_bin = true;
+
+            for dt in pattern.cell.iter() {
+                if problem.missing_item_qtys()[item.id] <= 0
+                    || !problem.get_layout(layout_idx).usage_allows(item)
+                {
+                    return n_placed;
+                }
+                let (tx, ty) = dt.translation();
+                let d_transf =
+                    DTransformation::new(dt.rotation(), (tx + translation.0, ty + translation.1));
+                let layout = problem.get_layout(layout_idx);
+                let irrel_hazards = match item.hazard_filter.as_ref() {
+                    None => vec![],
+                    Some(hf) => {
+                        hazard_filter::generate_irrelevant_hazards(hf, layout.cde().all_hazards())
+                    }
+                };
+                let shape = item.shape.transform_clone(&d_transf.compose());
+                if !layout.cde().poly_collides(&shape, &irrel_hazards) {
+                    let (l_idx, _) = problem.place_item(PlacingOption {
+                        layout_idx,
+                        item_id: item.id,
+                        d_transf,
+                    });
+                    //the first placement in a template layout opens a new layout
+                    layout_idx = l_idx;
+                    n_placed += 1;
+                }
+            }
+        }
+        if !column_in_bin {
+            break;
+        }
+    }
+    n_placed
+}
+
+pub(crate) fn rotation_allowed(allowed_rotation: &AllowedRotation, rotation: fsize) -> bool {
+    let normalize = |r: fsize| r.rem_euclid(2.0 * PI);
+    match allowed_rotation {
+        AllowedRotation::None => false,
+        AllowedRotation::Continuous => true,
+        AllowedRotation::Discrete(rotations) => rotations
+            .iter()
+            .any(|r| (normalize(*r) - normalize(rotation)).abs() < 1e-3),
+        AllowedRotation::Explicit(transfs) => transfs
+            .iter()
+            .any(|t| (normalize(t.rotation()) - normalize(rotation)).abs() < 1e-3),
+    }
+}
+
+/// Finds the tightest interlocking pair of the shape (rotated by `rotation`) and its 180° rotation,
+/// by sliding the second shape towards the first from a range of directions.
+fn find_pair(shape: &SimplePolygon, rotation: fsize) -> Option<Vec<DTransformation>> {
+    let first = DTransformation::new(rotation, (0.0, 0.0));
+    let second_rotation = rotation + PI;
+
+    (0..N_PAIR_DIRECTIONS)
+        .filter_map(|k| {
+            let angle = 2.0 * PI * k as fsize / N_PAIR_DIRECTIONS as fsize;
+            let direction = (angle.cos(), angle.sin());
+            let fixed = shape.transform_clone(&first.compose());
+            let moving =
+                shape.transform_clone(&DTransformation::new(second_rotation, (0.0, 0.0)).compose());
+            let t = slide_until_free(&fixed, &moving, direction)?;
+            let second = DTransformation::new(second_rotation, (t * direction.0, t * direction.1));
+            Some(vec![first, second])
+        })
+        .min_by(|c1, c2| {
+            let (a1, a2) = (cell_bbox_area(shape, c1), cell_bbox_area(shape, c2));
+            a1.partial_cmp(&a2).unwrap()
+        })
+}
+
+/// Finds the lattice vectors for a cell: a horizontal vector `a`, and a vector `b` pointing upwards
+/// (with a horizontal offset) which minimizes the area of the lattice cell.
+fn find_lattice_vectors(
+    shape: &SimplePolygon,
+    cell: &[DTransformation],
+) -> Option<((fsize, fsize), (fsize, fsize))> {
+    let dx = bisect_contact(shape.diameter() * 2.0, |t| {
+        cell_collides(shape, cell, (t, 0.0), &[(0.0, 0.0)])
+    })?;
+    let a = (dx, 0.0);
+
+    let b = (0..N_ROW_OFFSETS)
+        .filter_map(|k| {
+            let offset = dx * k as fsize / N_ROW_OFFSETS as fsize;
+            //the row above should neither collide with the cell, nor with its horizontal neighbors
+            let neighbors = [(0.0, 0.0), (-dx, 0.0), (dx, 0.0)];
+            let dy = bisect_contact(shape.diameter() * 2.0, |t| {
+                cell_collides(shape, cell, (offset, t), &neighbors)
+            })?;
+            Some((offset, dy))
+        })
+        .min_by(|b1, b2| b1.1.partial_cmp(&b2.1).unwrap())?;
+
+    Some((a, b))
+}
+
+/// Smallest distance the `moving` shape can be translated in `direction` without colliding with the `fixed` shape
+pub(crate) fn slide_until_free(
+    fixed: &SimplePolygon,
+    moving: &SimplePolygon,
+    direction: (fsize, fsize),
+) -> Option<fsize> {
+    bisect_contact(fixed.diameter() + moving.diameter(), |t| {
+        let translation = Transformation::from_translation((t * direction.0, t * direction.1));
+        polygons_collide(fixed, &moving.transform_clone(&translation))
+    })
+}
+
+/// Whether the cell, translated by `moved`, collides with copies of the cell at the given `offsets`
+fn cell_collides(
+    shape: &SimplePolygon,
+    cell: &[DTransformation],
+    moved: (fsize, fsize),
+    offsets: &[(fsize, fsize)],
+) -> bool {
+    let placed = |(ox, oy): (fsize, fsize)| {
+        cell.iter()
+            .map(|dt| {
+                let (tx, ty) = dt.translation();
+                let dt = DTransformation::new(dt.rotation(), (tx + ox, ty + oy));
+                shape.transform_clone(&dt.compose())
+            })
+            .collect_vec()
+    };
+    let moved_shapes = placed(moved);
+    offsets.iter().any(|offset| {
+        placed(*offset)
+            .iter()
+            .cartesian_product(moved_shapes.iter())
+            .any(|(s1, s2)| polygons_collide(s1, s2))
+    })
+}
+
+/// Finds the smallest `t` in `[0, max_t]` for which `collides(t)` is false:
+/// first with coarse steps, then refined by bisection between the last colliding and first free step.
+fn bisect_contact(max_t: fsize, collides: impl Fn(fsize) -> bool) -> Option<fsize> {
+    let step = max_t / N_SLIDE_STEPS as fsize;
+    let first_free = (1..=N_SLIDE_STEPS).find(|i| !collides(*i as fsize * step))?;
+    let (mut lower, mut upper) = ((first_free - 1) as fsize * step, first_free as fsize * step);
+    for _ in 0..N_BISECTION_STEPS {
+        let mid = (lower + upper) / 2.0;
+        match collides(mid) {
+            true => lower = mid,
+            false => upper = mid,
+        }
+    }
+    Some(upper + max_t * CONTACT_MARGIN_FRACTION)
+}
+
+pub(crate) fn polygons_collide(s1: &SimplePolygon, s2: &SimplePolygon) -> bool {
+    s1.bbox().collides_with(&s2.bbox())
+        && (s1
+            .edge_iter()
+            .cartesian_product(s2.edge_iter().collect_vec())
+            .any(|(e1, e2)| e1.collides_with(&e2))
+            || s1.collides_with(&s2.poi.center)
+            || s2.collides_with(&s1.poi.center))
+}
+
+fn cell_bbox_area(shape: &SimplePolygon, cell: &[DTransformation]) -> fsize {
+    cell.iter()
+        .map(|dt| shape.transform_clone(&dt.compose()).bbox())
+        .reduce(|a, b| AARectangle::bounding_rectangle(&a, &b))
+        .map_or(fsize::INFINITY, |bbox| bbox.area())
+}
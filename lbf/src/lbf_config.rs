@@ -1,23 +1,153 @@
+use std::sync::Arc;
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde::{Deserialize, Serialize};
 
+use jagua_rs::entities::instances::instance::Instance;
 use jagua_rs::fsize;
-use jagua_rs::util::config::{CDEConfig, SPSurrogateConfig};
+use jagua_rs::io::gcode::GcodeConfig;
+use jagua_rs::io::json_instance::JsonInstance;
+use jagua_rs::io::parser::MissingAssetPolicy;
+use jagua_rs::util::config::{CDEConfig, GeometricFidelity, Preset, StripGrowth};
+use jagua_rs::util::objectives::{Objective, ObjectiveWeights, WeightedObjective};
+use jagua_rs::util::polygon_simplification::PolySimplConfig;
 
+use crate::bin_assignment::BinAssignmentConfig;
+use crate::calibration::CalibrationConfig;
+use crate::cut_order::CutOrderConfig;
+use crate::exact_search::ExactSearchConfig;
+use crate::io::error::IoError;
+use crate::io::json_output::JsonFormat;
 use crate::io::svg_util::SvgDrawOptions;
+use crate::lbf_cost::{Gravity, PackingDirection, PlacementBias};
+use crate::samplers::coarse_to_fine::CoarseToFineConfig;
 
 /// Configuration for the LBF optimizer
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct LBFConfig {
     /// Configuration of the Collision Detection Engine
     pub cde_config: CDEConfig,
+    /// Configuration of the calibration which probes several quadtree depths and HPG resolutions before the run,
+    /// the fastest feasible one replaces those of `cde_config`. If undefined, `cde_config` is used as is
+    #[serde(default)]
+    pub cde_calibration: Option<CalibrationConfig>,
     /// Max deviation from the original polygon area as a fraction. If undefined, the algorithm will run without simplification
     pub poly_simpl_tolerance: Option<fsize>,
+    /// Max deviation of the internal geometry from the input geometry as a fraction of each shape's extent.
+    /// Drives arc tessellation and polygon simplification (overriding `poly_simpl_tolerance`), see [`GeometricFidelity`] for the resulting clearance bound.
+    /// If undefined, arcs are tessellated with the default fidelity
+    #[serde(default)]
+    pub geometric_fidelity: Option<GeometricFidelity>,
+    /// Clearances (between items, or between items and the bin boundary) smaller than this distance are flagged in the solution
+    #[serde(default)]
+    pub clearance_threshold: fsize,
+    /// Webs of the skeleton (the material of the bin left over after cutting) narrower than this width are flagged in the solution.
+    /// Items which touch share a cut and are not separated by a web. If undefined, webs are not checked
+    #[serde(default)]
+    pub min_web_width: Option<fsize>,
     /// Seed for the PRNG. If undefined, the algorithm will run in non-deterministic mode using entropy
     pub prng_seed: Option<u64>,
+    /// Every item draws its samples from its own PRNG stream, derived from the seed and the id of the item.
+    /// Adding an item to an instance then leaves the random streams of all other items unchanged.
+    /// Clusters of items sample from their own stream, derived from the id of the cluster (see [`Cluster`](crate::clusters::Cluster))
+    #[serde(default)]
+    pub item_prng_streams: bool,
     /// Total budget of samples per item per layout
     pub n_samples: usize,
     /// Fraction of `n_samples_per_item` used for the local search sampler, the rest is sampled uniformly.
     pub ls_frac: f32,
+    /// Items with at least this demand are first placed in an interlocking lattice pattern, the remainder is placed by LBF.
+    /// If undefined, no lattice patterns are generated
+    #[serde(default)]
+    pub lattice_min_qty: Option<usize>,
+    /// Items with an area below this fraction of the largest item's area are greedily combined in pairs,
+    /// which are placed as a single shape. Clusters are not part of the `bin_assignment`, they are searched in all layouts.
+    /// If undefined, no items are combined
+    #[serde(default)]
+    pub cluster_max_area_frac: Option<fsize>,
+    /// Number of colliding samples after which a cell of the Hazard Proximity Grid is considered too tight for an item.
+    /// Such cells are skipped when sampling the same item in the same layout, until the proximity of the cell changes.
+    /// If undefined, all eligible cells are always sampled
+    #[serde(default)]
+    pub infeasible_cell_threshold: Option<usize>,
+    /// Configuration of the exhaustive search used to place the last few items, instead of sampling.
+    /// If undefined, all items are placed by sampling
+    #[serde(default)]
+    pub exact_search: Option<ExactSearchConfig>,
+    /// Configuration of the area-based assignment of items to bins, which restricts in which bins each item is sampled.
+    /// If undefined, every item is sampled in all open bins and bin types in stock. Bin packing only
+    #[serde(default)]
+    pub bin_assignment: Option<BinAssignmentConfig>,
+    /// Adds a variant rotated by 90° of every non-square bin, which shares the stock of the original.
+    /// The heuristic then picks the orientation of each bin it opens. Bin packing only
+    #[serde(default)]
+    pub rotated_bins: bool,
+    /// Forbids items from being placed inside the holes of other items, for machines on which such parts would drop through
+    #[serde(default)]
+    pub forbid_part_in_part: bool,
+    /// Grid in degrees (e.g. 0.5) to which the sampled rotations of continuously rotating items are snapped.
+    /// The rotations of these items in the output are then exact multiples of the grid.
+    /// Lattices, clusters and the exact search use quarter turns, so the grid should divide 90°. If undefined, rotations are not snapped
+    #[serde(default)]
+    pub rotation_snap: Option<fsize>,
+    /// Configuration of the coarse-to-fine search for continuously rotating items: uniform samples on a coarse grid of rotations,
+    /// after which the best candidates are refined by local search. If undefined, rotations are sampled uniformly
+    #[serde(default)]
+    pub coarse_to_fine: Option<CoarseToFineConfig>,
+    /// Initial width of the strip as a multiple of its lower bound (total item area divided by the strip height).
+    /// If undefined, the strip starts at twice the lower bound (50% usage). Strip packing only
+    #[serde(default)]
+    pub strip_width_factor: Option<fsize>,
+    /// Policy by which the strip is extended when an item cannot be placed. Strip packing only
+    #[serde(default)]
+    pub strip_growth: StripGrowth,
+    /// Direction in which the items are packed, the side of the bin they are placed against first.
+    /// Strip packing only supports packing towards the left (the front of the strip)
+    #[serde(default)]
+    pub packing_direction: PackingDirection,
+    /// Vertical direction in which items are pulled, if the packing direction is horizontal
+    #[serde(default)]
+    pub gravity: Gravity,
+    /// Placements within this distance of the datum edges (the edge packed against, and the edge `gravity` pulls towards) are moved flush against them,
+    /// and the first item placed in a layout is moved into the datum corner. If undefined, placements are not aligned
+    #[serde(default)]
+    pub datum_tolerance: Option<fsize>,
+    /// The strip is never extended beyond this width, items which do not fit are left out of the solution.
+    /// If undefined, the strip is extended until all items are placed. Strip packing only
+    #[serde(default)]
+    pub strip_max_width: Option<fsize>,
+    /// Weights of the objectives on which solutions are evaluated, reported in the output.
+    /// If undefined, solutions are not evaluated
+    #[serde(default)]
+    pub objective_weights: Option<ObjectiveWeights>,
+    /// Configuration of the order in which the parts of each layout are cut, spreading the heat input of thermal cutting.
+    /// The order is written as `cut_order` of every layout in the solution. If undefined, no cut order is determined
+    #[serde(default)]
+    pub cut_order: Option<CutOrderConfig>,
+    /// Number of independent runs of the heuristic, each with its own PRNG stream.
+    /// Runs which are not dominated on the objectives are kept, the one with the highest score is the final solution.
+    /// If undefined, a single run is performed
+    #[serde(default)]
+    pub n_runs: Option<usize>,
+    /// Number of threads used to parse the instance, in a pool dedicated to this run.
+    /// If undefined, the global rayon pool is used
+    #[serde(default)]
+    pub n_threads: Option<usize>,
+    /// What to do with items of which the DXF or SVG file cannot be opened: fail, listing all missing files (default),
+    /// or leave them out of the instance with a warning
+    #[serde(default)]
+    pub missing_assets: MissingAssetPolicy,
+    /// Number of decimals of the translations in the JSON solution, rotations get as many more as the size of the items requires,
+    /// see [`round_transformations`](crate::io::json_output::round_transformations).
+    /// Rounding moves items by at most 1.21 units of the last decimal. If undefined, placements are written at full precision
+    #[serde(default)]
+    pub export_precision: Option<u32>,
+    /// Formatting of the JSON solution: indented (default), or compact on a single line for solutions with many placements
+    #[serde(default)]
+    pub json_format: JsonFormat,
+    /// Configuration of the G-code written with `--export-gcode`: travel optimization, feed rate and precision
+    #[serde(default)]
+    pub gcode: GcodeConfig,
     /// Optional SVG drawing options
     #[serde(default)]
     pub svg_draw_options: SvgDrawOptions,
@@ -26,21 +156,142 @@ pub struct LBFConfig {
 impl Default for LBFConfig {
     fn default() -> Self {
         Self {
-            cde_config: CDEConfig {
-                quadtree_depth: 5,
-                hpg_n_cells: 2000,
-                item_surrogate_config: SPSurrogateConfig {
-                    pole_coverage_goal: 0.9,
-                    max_poles: 10,
-                    n_ff_poles: 2,
-                    n_ff_piers: 0,
-                },
-            },
+            cde_config: CDEConfig::preset(Preset::Balanced),
+            cde_calibration: None,
             poly_simpl_tolerance: Some(0.001),
+            geometric_fidelity: None,
+            clearance_threshold: 0.0,
+            min_web_width: None,
             prng_seed: Some(0),
+            item_prng_streams: false,
             n_samples: 5000,
             ls_frac: 0.2,
+            lattice_min_qty: None,
+            cluster_max_area_frac: None,
+            infeasible_cell_threshold: None,
+            exact_search: None,
+            bin_assignment: None,
+            rotated_bins: false,
+            forbid_part_in_part: false,
+            rotation_snap: None,
+            coarse_to_fine: None,
+            strip_width_factor: None,
+            strip_growth: StripGrowth::default(),
+            packing_direction: PackingDirection::default(),
+            gravity: Gravity::default(),
+            datum_tolerance: None,
+            strip_max_width: None,
+            objective_weights: None,
+            cut_order: None,
+            n_runs: None,
+            n_threads: None,
+            missing_assets: MissingAssetPolicy::default(),
+            export_precision: None,
+            gcode: GcodeConfig::default(),
+            json_format: JsonFormat::default(),
             svg_draw_options: SvgDrawOptions::default(),
         }
     }
 }
+
+impl LBFConfig {
+    /// Configuration for the given preset, tuned on the bundled datasets.
+    /// The balanced preset is the default configuration.
+    ///
+    /// Mean usage over the 11 bundled instances and 5 seeds (runtime relative to balanced):
+    /// fast 58.7% (0.37x, not every item is placed on all instances), balanced 61.7%, quality 62.7% (7.9x).
+    pub fn preset(preset: Preset) -> Self {
+        let balanced = Self::default();
+        match preset {
+            Preset::Fast => Self {
+                cde_config: CDEConfig::preset(Preset::Fast),
+                n_samples: 1500,
+                ..balanced
+            },
+            Preset::Balanced => balanced,
+            Preset::Quality => Self {
+                cde_config: CDEConfig::preset(Preset::Quality),
+                n_samples: 10000,
+                n_runs: Some(4),
+                ..balanced
+            },
+        }
+    }
+
+    /// Towards where placements are biased, combining the packing direction and gravity
+    pub fn placement_bias(&self) -> PlacementBias {
+        PlacementBias {
+            direction: self.packing_direction,
+            gravity: self.gravity,
+        }
+    }
+
+    /// Polygon simplification configuration, derived from the geometric fidelity if defined
+    pub fn poly_simpl_config(&self) -> PolySimplConfig {
+        match (self.geometric_fidelity, self.poly_simpl_tolerance) {
+            (Some(fidelity), _) => fidelity.poly_simpl_config(),
+            (None, Some(tolerance)) => PolySimplConfig::Enabled { tolerance },
+            (None, None) => PolySimplConfig::Disabled,
+        }
+    }
+
+    /// Grid to which the rotations are snapped in radians, see `rotation_snap`
+    pub fn rotation_snap_radians(&self) -> Option<fsize> {
+        self.rotation_snap.map(|step| step.to_radians())
+    }
+
+    /// Checks whether the config can be used to solve the instance
+    pub fn validate_for(&self, json_instance: &JsonInstance) -> Result<(), IoError> {
+        self.validate_packing_direction(json_instance.strip.is_some())
+    }
+
+    /// Checks whether the config can be used to solve the parsed instance, see [`Self::validate_for`]
+    pub fn validate_for_instance(&self, instance: &Instance) -> Result<(), IoError> {
+        self.validate_packing_direction(matches!(instance, Instance::SP(_)))
+    }
+
+    fn validate_packing_direction(&self, strip_packing: bool) -> Result<(), IoError> {
+        //the strip is only extended and fitted in the back
+        if strip_packing && self.packing_direction != PackingDirection::Left {
+            return Err(IoError::InvalidConfig {
+                reason: format!(
+                    "strip packing only supports packing towards the left, got {:?}",
+                    self.packing_direction
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn fidelity(&self) -> GeometricFidelity {
+        self.geometric_fidelity.unwrap_or_default()
+    }
+
+    /// Objectives on which solutions are evaluated and reported, empty if `objective_weights` is undefined
+    pub fn objectives(&self) -> Vec<WeightedObjective> {
+        self.objective_weights
+            .map_or(vec![], |weights| weights.weighted_objectives())
+    }
+
+    /// Objectives on which runs are compared, falls back to usage if `objective_weights` is undefined
+    pub fn run_objectives(&self) -> Vec<WeightedObjective> {
+        match self.objectives().is_empty() {
+            true => vec![WeightedObjective {
+                objective: Objective::Usage,
+                weight: 1.0,
+            }],
+            false => self.objectives(),
+        }
+    }
+
+    /// Dedicated thread pool with `n_threads` threads, if defined
+    pub fn thread_pool(&self) -> Option<Arc<ThreadPool>> {
+        self.n_threads.map(|n_threads| {
+            let thread_pool = ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .expect("could not build thread pool");
+            Arc::new(thread_pool)
+        })
+    }
+}
@@ -1,25 +1,175 @@
 use ordered_float::NotNan;
+use serde::{Deserialize, Serialize};
 
 use jagua_rs::fsize;
 use jagua_rs::geometry::geo_traits::Shape;
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
 use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
 
-const X_MULTIPLIER: fsize = 10.0;
+const PRIMARY_MULTIPLIER: fsize = 10.0;
 
 /// The cost LBF assigned to a placing option.
-/// Weighted sum of the x_max and y_max of the shape, with the horizontal dimension being more important.
+/// Weighted sum of a primary and secondary cost of the shape, with the primary being more important.
+/// By default, these are the x_max and y_max of the shape (see [`PlacementBias`]).
 /// <br>
-/// A pure lexicographic comparison (always prioritizing x-axis) would lead to undesirable results due to the continuous nature of the values.
+/// A pure lexicographic comparison (always prioritizing the primary cost) would lead to undesirable results due to the continuous nature of the values.
 #[derive(PartialEq, PartialOrd, Copy, Clone, Debug, Eq, Ord)]
 pub struct LBFPlacingCost(NotNan<fsize>);
 
 impl LBFPlacingCost {
-    pub fn new(x_max: fsize, y_max: fsize) -> Self {
-        let cost = x_max * X_MULTIPLIER + y_max;
+    pub fn new(primary: fsize, secondary: fsize) -> Self {
+        let cost = primary * PRIMARY_MULTIPLIER + secondary;
         LBFPlacingCost(NotNan::new(cost).expect("cost is NaN"))
     }
 
-    pub fn from_shape(shape: &SimplePolygon) -> Self {
-        LBFPlacingCost::new(shape.bbox().x_max, shape.bbox().y_max)
+    /// Cost of a placement with bounding box `bbox`, in a bin with bounding box `bin_bbox`
+    pub fn from_bbox(bbox: &AARectangle, bias: PlacementBias, bin_bbox: &AARectangle) -> Self {
+        let (primary, secondary) = match bias.direction {
+            PackingDirection::Left => (
+                bbox.x_max,
+                bias.gravity.y_cost(bbox.y_min, bbox.y_max, bin_bbox),
+            ),
+            PackingDirection::Right => (
+                bin_bbox.x_max - bbox.x_min,
+                bias.gravity.y_cost(bbox.y_min, bbox.y_max, bin_bbox),
+            ),
+            PackingDirection::Down => (bbox.y_max, bbox.x_max),
+            PackingDirection::Point { x, y } => {
+                //distance to the corner of the bounding box furthest from the point
+                let (px, py) = relative_point(x, y, bin_bbox);
+                let dx = fsize::max(px - bbox.x_min, bbox.x_max - px);
+                let dy = fsize::max(py - bbox.y_min, bbox.y_max - py);
+                (dx.hypot(dy), 0.0)
+            }
+        };
+        LBFPlacingCost::new(primary, secondary)
     }
+
+    pub fn from_shape(shape: &SimplePolygon, bias: PlacementBias, bin_bbox: &AARectangle) -> Self {
+        LBFPlacingCost::from_bbox(&shape.bbox(), bias, bin_bbox)
+    }
+
+    /// Lower bound on the cost of a shape whose pole of inaccessibility (with radius `poi_radius`) is centered inside `bbox`
+    pub fn lower_bound(
+        bbox: &AARectangle,
+        poi_radius: fsize,
+        bias: PlacementBias,
+        bin_bbox: &AARectangle,
+    ) -> Self {
+        //the bounding box of the shape contains at least the pole
+        let y_cost = || {
+            bias.gravity
+                .y_cost(bbox.y_max - poi_radius, bbox.y_min + poi_radius, bin_bbox)
+        };
+        let (primary, secondary) = match bias.direction {
+            PackingDirection::Left => (bbox.x_min + poi_radius, y_cost()),
+            PackingDirection::Right => (bin_bbox.x_max - bbox.x_max + poi_radius, y_cost()),
+            PackingDirection::Down => (bbox.y_min + poi_radius, bbox.x_min + poi_radius),
+            PackingDirection::Point { x, y } => {
+                //the pole is at least as far from the point as the closest point of the bbox
+                let (px, py) = relative_point(x, y, bin_bbox);
+                let dx = fsize::max(0.0, fsize::max(bbox.x_min - px, px - bbox.x_max));
+                let dy = fsize::max(0.0, fsize::max(bbox.y_min - py, py - bbox.y_max));
+                (dx.hypot(dy) + poi_radius, 0.0)
+            }
+        };
+        LBFPlacingCost::new(primary, secondary)
+    }
+}
+
+/// Direction in which the items are packed, the side of the bin they are placed against first.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(tag = "direction", rename_all = "snake_case")]
+pub enum PackingDirection {
+    /// Items are packed towards the left edge, the vertical direction is determined by the [`Gravity`]
+    #[default]
+    Left,
+    /// Items are packed towards the right edge, the vertical direction is determined by the [`Gravity`]
+    Right,
+    /// Items are packed towards the bottom edge, and secondarily towards the left
+    Down,
+    /// Items are packed around a point, given relative to the bounding box of the bin:
+    /// (0, 0) is the bottom-left corner and (1, 1) the top-right corner
+    Point { x: fsize, y: fsize },
+}
+
+/// Vertical direction in which items are pulled, when they are packed in a horizontal [`PackingDirection`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Gravity {
+    /// Items are pulled towards the bottom edge
+    #[default]
+    BottomLeft,
+    /// Items are pulled towards the top edge
+    TopLeft,
+    /// Items are pulled towards the horizontal center line
+    Centered,
+}
+
+impl Gravity {
+    /// Vertical part of the cost of a shape spanning `[y_min, y_max]`, in a bin with bounding box `bin_bbox`.
+    /// Non-decreasing in the distance between the shape and the edge (or line) it is pulled towards
+    pub fn y_cost(&self, y_min: fsize, y_max: fsize, bin_bbox: &AARectangle) -> fsize {
+        match self {
+            Gravity::BottomLeft => y_max,
+            Gravity::TopLeft => bin_bbox.y_max - y_min,
+            Gravity::Centered => {
+                let center = (bin_bbox.y_min + bin_bbox.y_max) / 2.0;
+                fsize::max(y_max - center, center - y_min)
+            }
+        }
+    }
+
+    /// Unit vector pointing towards the edge the items are pulled to, zero if there is none
+    fn vertical(&self) -> (fsize, fsize) {
+        match self {
+            Gravity::BottomLeft => (0.0, -1.0),
+            Gravity::TopLeft => (0.0, 1.0),
+            Gravity::Centered => (0.0, 0.0),
+        }
+    }
+}
+
+/// Towards where placements are biased, both while sampling and in the [`LBFPlacingCost`].
+/// Combines the [`PackingDirection`] and [`Gravity`] of the config.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct PlacementBias {
+    pub direction: PackingDirection,
+    pub gravity: Gravity,
+}
+
+impl PlacementBias {
+    /// Unit vectors along which a placement is compacted, in order. Zero vectors are skipped
+    pub fn compaction_directions(&self) -> [(fsize, fsize); 3] {
+        let vertical = self.gravity.vertical();
+        match self.direction {
+            PackingDirection::Left => [(-1.0, 0.0), vertical, (-1.0, 0.0)],
+            PackingDirection::Right => [(1.0, 0.0), vertical, (1.0, 0.0)],
+            PackingDirection::Down => [(0.0, -1.0), (-1.0, 0.0), (0.0, -1.0)],
+            PackingDirection::Point { .. } => [(0.0, 0.0); 3],
+        }
+    }
+
+    /// Translation which moves a shape with bounding box `bbox` against the datum edges of the bin:
+    /// the edge the items are packed against, and the one of the secondary direction (if any)
+    pub fn datum_translation(&self, bbox: &AARectangle, bin_bbox: &AARectangle) -> (fsize, fsize) {
+        let dy = match self.gravity {
+            Gravity::BottomLeft => bin_bbox.y_min - bbox.y_min,
+            Gravity::TopLeft => bin_bbox.y_max - bbox.y_max,
+            Gravity::Centered => 0.0,
+        };
+        match self.direction {
+            PackingDirection::Left => (bin_bbox.x_min - bbox.x_min, dy),
+            PackingDirection::Right => (bin_bbox.x_max - bbox.x_max, dy),
+            PackingDirection::Down => (bin_bbox.x_min - bbox.x_min, bin_bbox.y_min - bbox.y_min),
+            PackingDirection::Point { .. } => (0.0, 0.0),
+        }
+    }
+}
+
+fn relative_point(x: fsize, y: fsize, bin_bbox: &AARectangle) -> (fsize, fsize) {
+    (
+        bin_bbox.x_min + x * bin_bbox.width(),
+        bin_bbox.y_min + y * bin_bbox.height(),
+    )
 }
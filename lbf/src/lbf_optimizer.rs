@@ -2,32 +2,52 @@ use std::cmp::{Ordering, Reverse};
 use std::time::Instant;
 
 use itertools::Itertools;
-use log::{debug, info};
+use log::{debug, info, warn};
 use ordered_float::NotNan;
 use rand::prelude::SmallRng;
 use rand::Rng;
 use thousands::Separable;
+use tracing::info_span;
 
+use jagua_rs::collision_detection::cd_engine::CDEngine;
+use jagua_rs::collision_detection::hazard::HazardEntity;
 use jagua_rs::collision_detection::hazard_filter;
 use jagua_rs::entities::instances::instance::Instance;
 use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
 use jagua_rs::entities::item::Item;
+use jagua_rs::entities::item_copy;
 use jagua_rs::entities::layout::Layout;
 use jagua_rs::entities::placing_option::PlacingOption;
 use jagua_rs::entities::problems::bin_packing::BPProblem;
 use jagua_rs::entities::problems::problem::Problem;
-use jagua_rs::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+use jagua_rs::entities::problems::problem_generic::{
+    LayoutIndex, ProblemGeneric, STRIP_LAYOUT_IDX,
+};
 use jagua_rs::entities::problems::strip_packing::SPProblem;
 use jagua_rs::entities::solution::Solution;
-use jagua_rs::fsize;
 use jagua_rs::geometry::convex_hull::convex_hull_from_points;
-use jagua_rs::geometry::geo_traits::{Shape, TransformableFrom};
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_enums::AllowedRotation;
+use jagua_rs::geometry::geo_traits::{Shape, Transformable, TransformableFrom};
 use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
-
+use jagua_rs::geometry::transformation::Transformation;
+use jagua_rs::{fsize, PI};
+
+use crate::bin_assignment::BinAssignment;
+use crate::clusters;
+use crate::exact_search;
+use crate::io::error::IoError;
+use crate::lattice;
+use crate::lattice::LatticePattern;
 use crate::lbf_config::LBFConfig;
-use crate::lbf_cost::LBFPlacingCost;
+use crate::lbf_cost::{LBFPlacingCost, PackingDirection, PlacementBias};
+use crate::samplers::coarse_to_fine;
+use crate::samplers::explicit_sampler;
 use crate::samplers::hpg_sampler::HPGSampler;
+use crate::samplers::infeasible_cells::InfeasibleCellCache;
+use crate::samplers::item_rng::ItemRngs;
 use crate::samplers::ls_sampler::LSSampler;
+use crate::samplers::sample_trace::{self, SampleOutcome};
 
 //limits the number of items to be placed, for debugging purposes
 pub const ITEM_LIMIT: usize = usize::MAX;
@@ -39,85 +59,275 @@ pub struct LBFOptimizer {
     /// SmallRng is a fast, non-cryptographic PRNG <https://rust-random.github.io/book/guide-rngs.html>
     pub rng: SmallRng,
     pub sample_counter: usize,
+    /// Cells of the Hazard Proximity Grid which proved too tight for an item during this run
+    pub cell_cache: InfeasibleCellCache,
+    /// PRNG streams per item, used instead of `rng` if `item_prng_streams` is enabled
+    pub item_rngs: Option<ItemRngs>,
+    /// The run is stopped once this many items are placed, `ITEM_LIMIT` by default
+    pub placement_limit: usize,
 }
 
 impl LBFOptimizer {
-    pub fn new(instance: Instance, config: LBFConfig, rng: SmallRng) -> Self {
+    /// Fails if the config cannot be used for the instance, see [`LBFConfig::validate_for_instance`]
+    pub fn new(instance: Instance, config: LBFConfig, rng: SmallRng) -> Result<Self, IoError> {
         assert!(config.n_samples > 0);
+        config.validate_for_instance(&instance)?;
         let problem = match instance.clone() {
             Instance::BP(bpi) => BPProblem::new(bpi.clone()).into(),
             Instance::SP(spi) => {
-                let strip_width = instance.item_area() * 2.0 / spi.strip_height; //initiate with 50% usage
+                //initiate with 50% usage, unless configured otherwise
+                let width_factor = config.strip_width_factor.unwrap_or(2.0);
+                let strip_width = instance.item_area() * width_factor / spi.strip_height;
+                let strip_width = match config.strip_max_width {
+                    Some(max_width) => strip_width.min(max_width),
+                    None => strip_width,
+                };
                 SPProblem::new(spi.clone(), strip_width, config.cde_config).into()
             }
         };
 
-        Self {
+        //the streams per item are derived from the first value of the main stream
+        let item_rngs = config
+            .item_prng_streams
+            .then(|| ItemRngs::new(rng.clone().gen()));
+
+        Ok(Self {
             instance,
             problem,
             config,
             rng,
+            item_rngs,
+            placement_limit: ITEM_LIMIT,
             sample_counter: 0,
-        }
+            cell_cache: InfeasibleCellCache::new(config.infeasible_cell_threshold),
+        })
     }
 
     pub fn solve(&mut self) -> Solution {
-        //sort the items by descending diameter of convex hull
+        let _span = info_span!("solve").entered();
+        //sort the items by descending shortfall cost per area (if defined), then by descending diameter of convex hull
         let sorted_item_indices = (0..self.instance.items().len())
             .sorted_by_cached_key(|i| {
                 let item = &self.instance.items()[*i].0;
                 let ch = SimplePolygon::new(convex_hull_from_points(item.shape.points.clone()));
                 let ch_diam = NotNan::new(ch.diameter()).expect("convex hull diameter is NaN");
-                Reverse(ch_diam)
+                (Reverse(shortfall_density(item)), Reverse(ch_diam))
             })
             .collect_vec();
 
         let start = Instant::now();
 
-        'outer: for item_index in sorted_item_indices {
-            let item = &self.instance.items()[item_index].0;
-            //place all items of this type
-            while self.problem.missing_item_qtys()[item_index] > 0 {
-                //find a position and insert it
-                match find_lbf_placement(
-                    &self.problem,
-                    item,
-                    &self.config,
-                    &mut self.rng,
-                    &mut self.sample_counter,
-                ) {
-                    Some(i_opt) => {
-                        let l_index = self.problem.place_item(i_opt);
-                        info!(
-                            "[LBF] placing item {}/{} with id {} at [{}] in Layout {:?}",
-                            self.problem.placed_item_qtys().sum::<usize>(),
-                            self.instance.total_item_qty(),
-                            i_opt.item_id,
-                            i_opt.d_transf,
-                            l_index
+        //copies locked at a position are placed before anything else
+        self.place_locked_copies();
+
+        //items in high demand are first placed in a lattice pattern
+        if let Some(lattice_min_qty) = self.config.lattice_min_qty {
+            for &item_index in sorted_item_indices.iter() {
+                let (item, qty) = &self.instance.items()[item_index];
+                if *qty >= lattice_min_qty {
+                    if let Some(pattern) = LatticePattern::generate(item) {
+                        lattice::tile(&mut self.problem, item, &pattern);
+                    }
+                }
+            }
+        }
+
+        //small items are combined into clusters, which are placed as a single shape
+        let mut clusters = match self.config.cluster_max_area_frac {
+            Some(max_area_frac) => {
+                let items = self
+                    .instance
+                    .items()
+                    .iter()
+                    .map(|(item, _)| item)
+                    .collect_vec();
+                let missing_qtys = self.problem.missing_item_qtys().to_vec();
+                clusters::generate_clusters(&items, &missing_qtys, max_area_frac)
+            }
+            None => vec![],
+        };
+
+        //clusters are placed before their members, any copies which are not part of a placed cluster are placed individually afterward
+        let units = sorted_item_indices
+            .iter()
+            .map(|i| PlacementUnit::Item(*i))
+            .chain((0..clusters.len()).map(PlacementUnit::Cluster))
+            .sorted_by_cached_key(|unit| {
+                let item = match unit {
+                    PlacementUnit::Item(i) => &self.instance.items()[*i].0,
+                    PlacementUnit::Cluster(c) => &clusters[*c].item,
+                };
+                let ch = SimplePolygon::new(convex_hull_from_points(item.shape.points.clone()));
+                let ch_diam = NotNan::new(ch.diameter()).expect("convex hull diameter is NaN");
+                (
+                    Reverse(shortfall_density(item)),
+                    Reverse(ch_diam),
+                    matches!(unit, PlacementUnit::Item(_)),
+                )
+            })
+            .collect_vec();
+
+        //items are assigned to bins based on their area, before they are placed
+        let mut assignment = match (&self.problem, self.config.bin_assignment) {
+            (Problem::BP(_), Some(assignment_config)) => Some(BinAssignment::first_fit_decreasing(
+                self.problem.view(),
+                assignment_config,
+                &[],
+            )),
+            _ => None,
+        };
+
+        let mut exact_search_done = false;
+
+        'outer: for unit in units {
+            //place all copies of this unit
+            loop {
+                if let Some(exact_config) = self.config.exact_search.as_ref() {
+                    //once only a few items remain (and no clusters), search for the best placement of all of them
+                    let n_missing = self.problem.missing_item_qtys().iter().sum::<isize>();
+                    let clusters_placed = clusters.iter().all(|c| c.qty == 0);
+                    if !exact_search_done
+                        && clusters_placed
+                        && n_missing > 0
+                        && n_missing as usize <= exact_config.max_items
+                    {
+                        exact_search_done = true;
+                        let remaining_items = sorted_item_indices
+                            .iter()
+                            .flat_map(|i| {
+                                let qty = self.problem.missing_item_qtys()[*i].max(0) as usize;
+                                std::iter::repeat_n(&self.instance.items()[*i].0, qty)
+                            })
+                            .collect_vec();
+                        exact_search::place_exact(
+                            &mut self.problem,
+                            &remaining_items,
+                            exact_config,
+                            self.config.placement_bias(),
                         );
-                        #[allow(clippy::absurd_extreme_comparisons)]
-                        if self.problem.placed_item_qtys().sum::<usize>() >= ITEM_LIMIT {
+                    }
+                }
+                let item = match unit {
+                    PlacementUnit::Item(i)
+                        if self.problem.missing_item_qtys()[i] > 0
+                            && unlocked_copy_missing(
+                                &self.problem,
+                                &self.instance.items()[i].0,
+                            ) =>
+                    {
+                        &self.instance.items()[i].0
+                    }
+                    PlacementUnit::Cluster(c) if clusters[c].qty > 0 => &clusters[c].item,
+                    _ => break,
+                };
+                let _span = match unit {
+                    PlacementUnit::Item(_) => info_span!("place_item", item_id = item.id),
+                    PlacementUnit::Cluster(c) => {
+                        let members = clusters[c].members.iter().map(|(id, _)| *id).collect_vec();
+                        info_span!("place_cluster", cluster_id = item.id, members = ?members)
+                    }
+                }
+                .entered();
+                let rng = match self.item_rngs.as_mut() {
+                    Some(item_rngs) => item_rngs.stream(item.id),
+                    None => &mut self.rng,
+                };
+                //find a position and insert it, clusters are not part of the assignment (which is made per item)
+                let placement = match (assignment.as_mut(), unit) {
+                    (Some(assignment), PlacementUnit::Item(_)) => assignment.find_placement(
+                        &self.problem,
+                        item,
+                        &self.config,
+                        rng,
+                        &mut self.sample_counter,
+                        &mut self.cell_cache,
+                    ),
+                    _ => find_lbf_placement(
+                        &self.problem,
+                        item,
+                        &self.config,
+                        rng,
+                        &mut self.sample_counter,
+                        &mut self.cell_cache,
+                    ),
+                };
+                match placement {
+                    Some(i_opt) => {
+                        let i_opts = match unit {
+                            PlacementUnit::Item(_) => vec![i_opt],
+                            PlacementUnit::Cluster(c) => {
+                                clusters[c].qty -= 1;
+                                clusters[c].decompose(&i_opt)
+                            }
+                        };
+                        let mut layout_idx = i_opt.layout_idx;
+                        for i_opt in i_opts {
+                            //all members of a cluster end up in the layout of the first one
+                            let i_opt = PlacingOption {
+                                layout_idx,
+                                ..i_opt
+                            };
+                            let (l_index, _) = self.problem.place_item(i_opt);
+                            layout_idx = l_index;
+                            if let Some(assignment) = assignment.as_mut() {
+                                assignment.register_placement(
+                                    self.problem.view(),
+                                    i_opt.item_id,
+                                    l_index,
+                                );
+                            }
+                            info!(
+                                "[LBF] placing item {}/{} with id {} at [{}] in Layout {:?}",
+                                self.problem.placed_item_qtys().sum::<usize>(),
+                                self.instance.total_item_qty(),
+                                i_opt.item_id,
+                                i_opt.d_transf,
+                                l_index
+                            );
+                        }
+                        if self.problem.placed_item_qtys().sum::<usize>() >= self.placement_limit {
                             break 'outer;
                         }
                     }
-                    None => {
-                        match &mut self.problem {
-                            Problem::BP(_) => break,
-                            Problem::SP(sp_problem) => {
-                                let new_width = sp_problem.strip_width() * 1.1;
-                                info!("[LBF] no placement found, extending strip width by 10% to {:.3}", new_width);
-                                sp_problem.modify_strip_in_back(new_width);
+                    None => match &mut self.problem {
+                        Problem::BP(_) | Problem::Custom(_) => break,
+                        Problem::SP(sp_problem) => {
+                            let max_width = self.config.strip_max_width.unwrap_or(fsize::MAX);
+                            if sp_problem.strip_width() >= max_width {
+                                warn!(
+                                    "[LBF] no placement found for item with id {}, strip is at its maximum width of {:.3}",
+                                    item.id, max_width
+                                );
+                                break;
                             }
+                            let new_width = self
+                                .config
+                                .strip_growth
+                                .grow(sp_problem.strip_width())
+                                .min(max_width);
+                            sp_problem.modify_strip_in_back(new_width);
+                            info!(
+                                "[LBF] no placement found, extending strip width to {:.3}",
+                                new_width
+                            );
                         }
-                    }
+                    },
                 }
             }
         }
         match &mut self.problem {
-            Problem::BP(_) => {}
+            Problem::BP(_) | Problem::Custom(_) => {}
             Problem::SP(sp_problem) => {
-                sp_problem.fit_strip();
+                //items restricted to a list of transformations (or locked copies) are positioned relative to the front of the strip
+                let fixed_items = self.instance.items().iter().any(|(item, _)| {
+                    matches!(item.allowed_rotation, AllowedRotation::Explicit(_))
+                        || item.copy_attributes.iter().any(|attr| attr.is_locked())
+                });
+                //as are all items when they are aligned to the front of the strip
+                match fixed_items || self.config.datum_tolerance.is_some() {
+                    true => sp_problem.fit_strip_in_back(),
+                    false => sp_problem.fit_strip(),
+                }
                 info!(
                     "[LBF] fitted strip width to {:.3}",
                     sp_problem.strip_width()
@@ -133,13 +343,154 @@ impl LBFOptimizer {
             self.sample_counter.separate_with_commas()
         );
 
+        //report the structure of the quadtrees, to judge whether the quadtree depth suits the instance
+        for layout in self.problem.layouts() {
+            let stats = layout.cde().quadtree_stats();
+            info!(
+                "[LBF] quadtree of layout {}: {} nodes ({} leaves) up to depth {}/{}, {:.1} hazards per leaf (max {}), {} saturated leaves, ~{} KiB",
+                layout.id(),
+                stats.n_nodes,
+                stats.n_leaves,
+                stats.max_depth(),
+                layout.cde().config().quadtree_depth,
+                stats.mean_hazards_per_leaf(),
+                stats.max_hazards_per_leaf(),
+                stats.n_saturated_leaves,
+                stats.memory_estimate / 1024
+            );
+            debug!(
+                "[LBF] quadtree of layout {}: nodes per depth {:?}, leaves per number of hazards {:?}",
+                layout.id(),
+                stats.depth_histogram,
+                stats.leaf_hazard_histogram
+            );
+        }
+
+        if self.cell_cache.is_enabled() {
+            info!(
+                "[LBF] {} cells of the hazard proximity grid were skipped as too tight for an item",
+                self.cell_cache.n_infeasible()
+            );
+        }
+
         info!(
-            "[LBF] solution contains {} items with a usage of {:.3}%",
+            "[LBF] solution contains {} items with a usage of {:.3}% ({:.3}% with the original geometry)",
             solution.n_items_placed(),
-            solution.usage * 100.0
+            solution.usage * 100.0,
+            solution.original_usage(&self.instance) * 100.0
         );
+
+        //report the items which were left out of a partial solution
+        for (item_id, qty) in solution
+            .missing_item_qtys(&self.instance)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, qty)| *qty > 0)
+        {
+            warn!(
+                "[LBF] {} copies of item with id {} could not be placed",
+                qty, item_id
+            );
+        }
         solution
     }
+
+    /// Places the copies of items which are locked at a position.
+    /// In bin packing, a copy is placed in the first open layout of its bin in which it fits, or in a new one.
+    /// Copies which collide at their position are left out.
+    fn place_locked_copies(&mut self) {
+        for (item, _) in self.instance.items() {
+            for (copy, attr) in item.copy_attributes.iter().enumerate() {
+                if !attr.is_locked() {
+                    continue;
+                }
+                let candidate_layouts = match &self.problem {
+                    Problem::SP(_) => vec![STRIP_LAYOUT_IDX],
+                    Problem::BP(_) | Problem::Custom(_) => {
+                        let bin_id = attr.bin_id.unwrap_or(0);
+                        self.problem
+                            .layout_indices()
+                            .chain(self.problem.template_layout_indices_with_stock())
+                            .filter(|l| {
+                                let layout = self.problem.get_layout(l);
+                                layout.bin.base_id() == bin_id
+                                    && layout.bin.accepts(item)
+                                    && layout.usage_allows(item)
+                            })
+                            .collect_vec()
+                    }
+                };
+                let p_opt = candidate_layouts.into_iter().find_map(|layout_idx| {
+                    let layout = self.problem.get_layout(layout_idx);
+                    let d_transf = attr.locked_internal_transf(item, &layout.bin).unwrap();
+                    let irrel_hazards = match item.hazard_filter.as_ref() {
+                        None => vec![],
+                        Some(hf) => hazard_filter::generate_irrelevant_hazards(
+                            hf,
+                            layout.cde().all_hazards(),
+                        ),
+                    };
+                    let shape = item.shape.transform_clone(&d_transf.compose());
+                    (!layout.cde().poly_collides(&shape, &irrel_hazards)).then_some(PlacingOption {
+                        layout_idx,
+                        item_id: item.id,
+                        d_transf,
+                    })
+                });
+                match p_opt {
+                    Some(p_opt) => {
+                        let (l_index, _) = self.problem.place_item(p_opt);
+                        info!(
+                            "[LBF] placing locked copy {} of item with id {} at [{}] in Layout {:?}",
+                            copy, item.id, p_opt.d_transf, l_index
+                        );
+                    }
+                    None => warn!(
+                        "[LBF] locked copy {} of item with id {} collides at its position and is left out",
+                        copy, item.id
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Shortfall cost of the item per unit of area.
+/// Items which are costly to leave out (relative to the space they take) are placed first, so the shortfall falls on the cheapest ones
+pub fn shortfall_density(item: &Item) -> NotNan<fsize> {
+    NotNan::new(item.shortfall_cost as fsize / item.shape.area()).expect("shortfall density is NaN")
+}
+
+/// Whether a copy of the item can still be placed in the layout by the heuristic.
+/// The bin has to accept the material of the item (see [`Bin::accepts`](jagua_rs::entities::bin::Bin::accepts)),
+/// the item must not push the layout over the maximum usage of the bin (see [`Layout::usage_allows`])
+/// and a copy has to be available for it (see [`item_copy::unlocked_copy_available`]).
+pub fn can_place_in(problem: &Problem, item: &Item, layout_idx: LayoutIndex) -> bool {
+    let layout = problem.get_layout(layout_idx);
+    let bin = &layout.bin;
+    bin.accepts(item)
+        && layout.usage_allows(item)
+        && (item.copy_attributes.is_empty()
+            || item_copy::unlocked_copy_available(
+                item,
+                &problem.placed_copies(item.id),
+                bin.base_id(),
+            ))
+}
+
+/// Whether a copy of the item which is not locked at a position is still missing
+fn unlocked_copy_missing(problem: &Problem, item: &Item) -> bool {
+    problem
+        .layout_indices()
+        .chain(problem.template_layout_indices_with_stock())
+        .any(|l| can_place_in(problem, item, l))
+}
+
+/// Unit which is placed by the LBF heuristic: either an item of the instance, or a cluster of small items
+#[derive(Clone, Copy, Debug)]
+enum PlacementUnit {
+    Item(usize),
+    Cluster(usize),
 }
 
 pub fn find_lbf_placement(
@@ -148,22 +499,118 @@ pub fn find_lbf_placement(
     config: &LBFConfig,
     rng: &mut impl Rng,
     sample_counter: &mut usize,
+    cell_cache: &mut InfeasibleCellCache,
 ) -> Option<PlacingOption> {
     //search all existing layouts and template layouts with remaining stock
     let existing_layouts = problem.layout_indices();
     let template_layouts = problem.template_layout_indices_with_stock();
 
-    //sequential search until a valid placement is found
-    for layout in existing_layouts.chain(template_layouts) {
+    find_lbf_placement_in(
+        problem,
+        existing_layouts.chain(template_layouts),
+        item,
+        config,
+        rng,
+        sample_counter,
+        cell_cache,
+    )
+}
+
+/// Searches the given layouts in order, until a valid placement is found.
+/// If this opens a new bin, its rotated variants are tried as well, see [`best_orientation`]
+pub fn find_lbf_placement_in(
+    problem: &Problem,
+    layouts: impl Iterator<Item = LayoutIndex>,
+    item: &Item,
+    config: &LBFConfig,
+    rng: &mut impl Rng,
+    sample_counter: &mut usize,
+    cell_cache: &mut InfeasibleCellCache,
+) -> Option<PlacingOption> {
+    //sequential search until a valid placement is found, in the layouts where a copy of the item can still be placed
+    for layout in layouts.filter(|l| can_place_in(problem, item, *l)) {
         debug!("searching in layout {:?}", layout);
-        if let Some(placing_opt) = sample_layout(problem, layout, item, config, rng, sample_counter)
-        {
+        if let Some(placing_opt) = sample_layout(
+            problem,
+            layout,
+            item,
+            config,
+            rng,
+            sample_counter,
+            cell_cache,
+        ) {
+            let placing_opt = match layout {
+                LayoutIndex::Real(_) => placing_opt,
+                LayoutIndex::Template(_) => best_orientation(
+                    problem,
+                    placing_opt,
+                    item,
+                    config,
+                    rng,
+                    sample_counter,
+                    cell_cache,
+                ),
+            };
             return Some(placing_opt);
         }
     }
     None
 }
 
+/// Compares a placement which opens a new bin with placements in the rotated variants of that bin (if any).
+/// The orientation in which the item reaches the least far into the bin, along the packing direction, is kept.
+fn best_orientation(
+    problem: &Problem,
+    placing_opt: PlacingOption,
+    item: &Item,
+    config: &LBFConfig,
+    rng: &mut impl Rng,
+    sample_counter: &mut usize,
+    cell_cache: &mut InfeasibleCellCache,
+) -> PlacingOption {
+    let base_id = problem.get_layout(placing_opt.layout_idx).bin.base_id();
+    let variants = problem
+        .template_layout_indices_with_stock()
+        .filter(|l| *l != placing_opt.layout_idx)
+        .filter(|l| problem.get_layout(l).bin.base_id() == base_id)
+        .filter(|l| can_place_in(problem, item, *l))
+        .collect_vec();
+    if variants.is_empty() {
+        return placing_opt;
+    }
+
+    let reach = |p_opt: &PlacingOption| {
+        let bin_bbox = problem.get_layout(p_opt.layout_idx).bin.bbox();
+        let bbox = item.shape.transform_clone(&p_opt.d_transf.compose()).bbox();
+        let reach = match config.packing_direction {
+            PackingDirection::Left => (bbox.x_max - bin_bbox.x_min) / bin_bbox.width(),
+            PackingDirection::Right => (bin_bbox.x_max - bbox.x_min) / bin_bbox.width(),
+            PackingDirection::Down => (bbox.y_max - bin_bbox.y_min) / bin_bbox.height(),
+            //no notion of depth, the orientation found first is kept
+            PackingDirection::Point { .. } => 0.0,
+        };
+        NotNan::new(reach).expect("reach is NaN")
+    };
+
+    let mut best = placing_opt;
+    for variant in variants {
+        if let Some(p_opt) = sample_layout(
+            problem,
+            variant,
+            item,
+            config,
+            rng,
+            sample_counter,
+            cell_cache,
+        ) {
+            if reach(&p_opt) < reach(&best) {
+                best = p_opt;
+            }
+        }
+    }
+    best
+}
+
 pub fn sample_layout(
     problem: &Problem,
     layout_idx: LayoutIndex,
@@ -171,15 +618,31 @@ pub fn sample_layout(
     config: &LBFConfig,
     rng: &mut impl Rng,
     sample_counter: &mut usize,
+    cell_cache: &mut InfeasibleCellCache,
 ) -> Option<PlacingOption> {
     let layout: &Layout = problem.get_layout(layout_idx);
     let cde = layout.cde();
+    let bin_bbox = layout.bin.bbox();
     let irrel_hazards = match item.hazard_filter.as_ref() {
         None => vec![],
         Some(hf) => hazard_filter::generate_irrelevant_hazards(hf, layout.cde().all_hazards()),
     };
 
+    //items restricted to a list of transformations are not sampled, all of them are tested
+    if let AllowedRotation::Explicit(_) = item.allowed_rotation {
+        return explicit_sampler::best_explicit_placement(
+            layout,
+            layout_idx,
+            item,
+            &irrel_hazards,
+            config.placement_bias(),
+            sample_counter,
+        );
+    }
+
     let surrogate = item.shape.surrogate();
+    //in rectangle mode, testing the full shape is cheaper than fail-fasting on the surrogate
+    let skip_surrogate = cde.rect_mode() && item.is_rectangular();
     //create a clone of the shape which will we can use to apply the transformations
     let mut buffer = {
         let mut buffer = (*item.shape).clone();
@@ -187,88 +650,288 @@ pub fn sample_layout(
         buffer
     };
 
-    let mut best: Option<(PlacingOption, LBFPlacingCost)> = None;
+    //continuously rotating items can be searched coarse-to-fine
+    let coarse_to_fine = match item.allowed_rotation {
+        AllowedRotation::Continuous => config.coarse_to_fine,
+        _ => None,
+    };
+    let rotation_snap = config.rotation_snap_radians();
+    let coarse_step = coarse_to_fine.map(|ctf| ctf.coarse_step(rotation_snap));
+    let n_candidates = coarse_to_fine.map_or(1, |ctf| ctf.n_candidates);
+
+    //best valid placements found by the uniform sampling, each with a different rotation, best first
+    let mut candidates: Vec<(PlacingOption, LBFPlacingCost)> = vec![];
 
     //calculate the number of uniform and local search samples
     let ls_sample_budget = (config.n_samples as f32 * config.ls_frac) as usize;
     let uni_sample_budget = config.n_samples - ls_sample_budget;
 
-    //uniform sampling within the valid cells of the Hazard Proximity Grid, tracking the best valid insertion option
-    let mut hpg_sampler = HPGSampler::new(item, layout)?;
+    //uniform sampling within the valid cells of the Hazard Proximity Grid, tracking the best valid insertion options
+    let mut hpg_sampler = HPGSampler::new(item, layout, cell_cache, config.placement_bias())?
+        .with_rotation_snap(coarse_step.or(rotation_snap));
+    let track_cells = cell_cache.is_enabled();
 
     for i in 0..uni_sample_budget {
         let transform = hpg_sampler.sample(rng);
-        if !cde.surrogate_collides(surrogate, &transform, &irrel_hazards) {
+        let obb_clear = !skip_surrogate && obb_clear(cde, item, &transform, &irrel_hazards);
+        if !obb_clear
+            && !skip_surrogate
+            && cde.surrogate_collides(surrogate, &transform, &irrel_hazards)
+        {
+            sample_trace::record(layout.bin.id, &transform, SampleOutcome::SurrogateRejected);
+            if track_cells {
+                hpg_sampler.register_outcome(true);
+            }
+        } else {
             //if no collision is detected on the surrogate, apply the transformation
             buffer.transform_from(&item.shape, &transform);
-            let cost = LBFPlacingCost::from_shape(&buffer);
-
-            //only validate the sample if it possibly can replace the current best
-            let worth_testing = match (best.as_ref(), &cost) {
-                (Some((_, best_cost)), cost) => {
-                    cost.partial_cmp(best_cost).unwrap() == Ordering::Less
+            let cost = LBFPlacingCost::from_shape(&buffer, config.placement_bias(), &bin_bbox);
+
+            //only validate the sample if it possibly can replace one of the current candidates
+            let same_rotation = coarse_step.and_then(|step| {
+                let rotation = transform.decompose().rotation();
+                candidates.iter().position(|(c, _)| {
+                    let diff = (c.d_transf.rotation() - rotation).rem_euclid(2.0 * PI);
+                    fsize::min(diff, 2.0 * PI - diff) < step / 2.0
+                })
+            });
+            let worth_testing = match (same_rotation, candidates.last()) {
+                (Some(idx), _) => cost < candidates[idx].1,
+                (None, Some((_, worst_cost))) if candidates.len() == n_candidates => {
+                    cost.partial_cmp(worst_cost).unwrap() == Ordering::Less
                 }
-                (None, _) => true,
+                _ => true,
             };
 
-            if worth_testing && !cde.poly_collides(&buffer, &irrel_hazards) {
-                //sample is valid and improves on the current best
+            let valid = worth_testing && (obb_clear || !cde.poly_collides(&buffer, &irrel_hazards));
+            sample_trace::record(
+                layout.bin.id,
+                &transform,
+                SampleOutcome::evaluated(worth_testing, valid),
+            );
+            if worth_testing && track_cells {
+                hpg_sampler.register_outcome(!valid);
+            }
+
+            if valid {
+                //sample is valid and improves on the current candidates
                 let p_opt = PlacingOption {
                     layout_idx,
                     item_id: item.id,
                     d_transf: transform.decompose(),
                 };
-                hpg_sampler.tighten(cost);
                 debug!(
                     "[UNI: {i}/{uni_sample_budget}] better: {} ",
                     &p_opt.d_transf
                 );
-
-                best = Some((p_opt, cost));
+                match same_rotation {
+                    Some(idx) => {
+                        candidates.remove(idx);
+                    }
+                    None if candidates.len() == n_candidates => {
+                        candidates.pop();
+                    }
+                    None => {}
+                }
+                let idx = candidates.partition_point(|(_, c)| *c <= cost);
+                candidates.insert(idx, (p_opt, cost));
+                //only cells which could still improve on the candidates have to be sampled
+                if candidates.len() == n_candidates {
+                    hpg_sampler.tighten(candidates.last().unwrap().1);
+                }
             }
         }
     }
 
     *sample_counter += hpg_sampler.n_samples;
+    if track_cells {
+        hpg_sampler.update_cache(layout, cell_cache);
+    }
 
-    //if a valid sample was found during the uniform sampling, perform local search around it
-    let (best_opt, best_cost) = best.as_mut()?;
+    if candidates.is_empty() {
+        return None;
+    }
 
     /*
     The local search samplers in a normal distribution.
     Throughout the course of the local search, the mean of the distribution is updated to the best found sample.
     And the standard deviation tightens, to focus the search around the best sample.
+    In the coarse-to-fine search, the budget is split over the candidates, which are each refined separately.
      */
 
-    let mut ls_sampler = LSSampler::from_defaults(item, &best_opt.d_transf, &layout.bin.bbox());
+    let ls_candidate_budget = ls_sample_budget / candidates.len();
+    let mut best: Option<(PlacingOption, LBFPlacingCost)> = None;
+    for (best_opt, best_cost) in candidates.iter_mut() {
+        let ls_sampler = match coarse_step {
+            Some(step) => {
+                coarse_to_fine::refinement_sampler(item, layout, &best_opt.d_transf, step)
+            }
+            None => LSSampler::from_defaults(item, &best_opt.d_transf, &layout.bin.bbox()),
+        };
+        let mut ls_sampler = ls_sampler.with_rotation_snap(rotation_snap);
+
+        for i in 0..ls_candidate_budget {
+            let d_transf = ls_sampler.sample(rng);
+            let transf = d_transf.compose();
+            let obb_clear = !skip_surrogate && obb_clear(cde, item, &transf, &irrel_hazards);
+            if obb_clear
+                || skip_surrogate
+                || !cde.surrogate_collides(surrogate, &transf, &irrel_hazards)
+            {
+                buffer.transform_from(&item.shape, &transf);
+                let cost = LBFPlacingCost::from_shape(&buffer, config.placement_bias(), &bin_bbox);
+
+                //only validate the sample if it possibly can replace the current best
+                let worth_testing = cost < *best_cost;
+                let valid =
+                    worth_testing && (obb_clear || !cde.poly_collides(&buffer, &irrel_hazards));
+                sample_trace::record(
+                    layout.bin.id,
+                    &transf,
+                    SampleOutcome::evaluated(worth_testing, valid),
+                );
 
-    for i in 0..ls_sample_budget {
-        let d_transf = ls_sampler.sample(rng);
-        let transf = d_transf.compose();
-        if !cde.surrogate_collides(surrogate, &transf, &irrel_hazards) {
-            buffer.transform_from(&item.shape, &transf);
-            let cost = LBFPlacingCost::from_shape(&buffer);
+                if valid {
+                    //sample is valid and improves on the current best
+                    let p_opt = PlacingOption {
+                        layout_idx,
+                        item_id: item.id,
+                        d_transf,
+                    };
+                    ls_sampler.shift_mean(&p_opt.d_transf);
+                    debug!(
+                        "[LS: {i}/{ls_candidate_budget}] better: {}",
+                        &p_opt.d_transf
+                    );
+                    (*best_opt, *best_cost) = (p_opt, cost);
+                }
+            } else {
+                sample_trace::record(layout.bin.id, &transf, SampleOutcome::SurrogateRejected);
+            }
+            let progress_pct = i as fsize / ls_candidate_budget as fsize;
+            ls_sampler.decay_stddev(progress_pct);
+        }
 
-            //only validate the sample if it possibly can replace the current best
-            let worth_testing = cost < *best_cost;
+        *sample_counter += ls_sampler.n_samples;
 
-            if worth_testing && !cde.poly_collides(&buffer, &irrel_hazards) {
-                //sample is valid and improves on the current best
-                let p_opt = PlacingOption {
-                    layout_idx,
-                    item_id: item.id,
-                    d_transf,
-                };
-                ls_sampler.shift_mean(&p_opt.d_transf);
-                debug!("[LS: {i}/{ls_sample_budget}] better: {}", &p_opt.d_transf);
-                (*best_opt, *best_cost) = (p_opt, cost);
-            }
+        if best.as_ref().is_none_or(|(_, c)| *best_cost < *c) {
+            best = Some((*best_opt, *best_cost));
+        }
+    }
+
+    let (p_opt, _) = best?;
+    match config.datum_tolerance {
+        None => Some(p_opt),
+        Some(tolerance) => Some(align_to_datum(
+            layout,
+            item,
+            p_opt,
+            tolerance,
+            config.placement_bias(),
+            &irrel_hazards,
+            &mut buffer,
+        )),
+    }
+}
+
+/// Moves a placement flush against the datum edges of the bin, see [`PlacementBias::datum_translation`].
+/// An edge is only snapped to if the gap is within `tolerance`, or if the layout is still empty (the first item starts in the datum corner).
+/// Along each axis the item is moved as far towards the edge as it can without colliding.
+fn align_to_datum(
+    layout: &Layout,
+    item: &Item,
+    p_opt: PlacingOption,
+    tolerance: fsize,
+    bias: PlacementBias,
+    irrel_hazards: &[HazardEntity],
+    buffer: &mut SimplePolygon,
+) -> PlacingOption {
+    let bin_bbox = layout.bin.bbox();
+    let first = layout.is_empty();
+    buffer.transform_from(&item.shape, &p_opt.d_transf.compose());
+    let bbox = buffer.bbox();
+
+    let (dx, dy) = bias.datum_translation(&bbox, &bin_bbox);
+
+    //exactly touching the bin is a collision, so the item is slid as close to the edge as possible
+    let mut d_transf = p_opt.d_transf;
+    for (gap_x, gap_y) in [(dx, 0.0), (0.0, dy)] {
+        let gap = fsize::max(gap_x.abs(), gap_y.abs());
+        if gap == 0.0 || (!first && gap > tolerance) {
+            continue;
         }
-        let progress_pct = i as fsize / ls_sample_budget as fsize;
-        ls_sampler.decay_stddev(progress_pct);
+        let (r, (tx, ty)) = (d_transf.rotation(), d_transf.translation());
+        let shifted =
+            |d: fsize| DTransformation::new(r, (tx + gap_x * d / gap, ty + gap_y * d / gap));
+        let d = exact_search::slide_to_contact(gap, |d| {
+            buffer.transform_from(&item.shape, &shifted(d).compose());
+            layout.cde().poly_collides(buffer, irrel_hazards)
+        });
+        d_transf = shifted(d);
+    }
+    if d_transf != p_opt.d_transf {
+        debug!("[DATUM] aligned {} to {}", &p_opt.d_transf, &d_transf);
     }
 
-    *sample_counter += ls_sampler.n_samples;
+    PlacingOption { d_transf, ..p_opt }
+}
+
+/// Whether the oriented bounding box of the transformed item already proves it does not collide with anything,
+/// in which case both the surrogate and the full collision check can be skipped.
+fn obb_clear(
+    cde: &CDEngine,
+    item: &Item,
+    transform: &Transformation,
+    irrel_hazards: &[HazardEntity],
+) -> bool {
+    let obb = item.shape.obb.transform_clone(transform);
+    cde.obb_excludes_collision(&obb, irrel_hazards)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use jagua_rs::io::parser::Parser;
+
+    use super::*;
+    use crate::io;
+    use crate::lbf_runs;
 
-    best.map(|(p_opt, _)| p_opt)
+    #[test]
+    fn test_strip_packing_direction() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/swim.json");
+        let json_instance = io::read_json_instance(Some(&path), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            Default::default(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+
+        //the strip is only extended and fitted in the back, other directions are rejected instead of panicking
+        for packing_direction in [PackingDirection::Right, PackingDirection::Down] {
+            let config = LBFConfig {
+                packing_direction,
+                ..config
+            };
+            let rng = SmallRng::seed_from_u64(0);
+            assert!(matches!(
+                LBFOptimizer::new(instance.clone(), config, rng.clone()),
+                Err(IoError::InvalidConfig { .. })
+            ));
+            assert!(matches!(
+                lbf_runs::solve_best(&instance, config, rng),
+                Err(IoError::InvalidConfig { .. })
+            ));
+        }
+        assert!(LBFOptimizer::new(instance, config, SmallRng::seed_from_u64(0)).is_ok());
+    }
 }
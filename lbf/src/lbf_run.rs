@@ -1,61 +1,188 @@
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use jagua_rs::io::json_instance::JsonInstance;
-use log::{error, warn};
+use log::warn;
 use rand::prelude::SmallRng;
 use rand::SeedableRng;
+use rayon::ThreadPool;
+use tracing::info_span;
 
-use crate::io::json_output::JsonOutput;
-use crate::io::layout_to_svg::s_layout_to_svg;
+use crate::cut_order;
+use crate::io::error::IoError;
+use crate::io::json_output::{self, JsonOutput};
+use crate::io::layout_to_svg::{s_layout_to_svg, s_layout_to_svg_lod};
+use crate::io::simplification_preview;
+use crate::io::svg_util::LevelOfDetail;
+use crate::io::zip_instance;
 use crate::lbf_config::LBFConfig;
-use crate::lbf_optimizer::LBFOptimizer;
+use crate::lbf_runs;
 use crate::{io, EPOCH};
-use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::fsize;
+use jagua_rs::io::asset_paths::AssetPaths;
+use jagua_rs::io::json_instance::JsonInstance;
 use jagua_rs::io::parser;
 use jagua_rs::io::parser::Parser;
-use jagua_rs::util::polygon_simplification::PolySimplConfig;
 
-pub fn solve_json(config_json: String, input_json: String, path_sol: String) -> Vec<Vec<String>> {
-    let config = if config_json.is_empty() {
-        warn!("No config file provided");
-        warn!(
-            "Falling back default config:\n{}",
-            serde_json::to_string(&LBFConfig::default()).unwrap()
-        );
-        LBFConfig::default()
-    } else {
-        serde_json::from_str(&config_json).unwrap_or_else(|err| {
-            error!("Config json could not be parsed: {}", err);
-            panic!();
-        })
-    };
+/// Width in pixels of the thumbnails of the layouts written by [`solve_json`]
+pub const THUMBNAIL_WIDTH: u32 = 320;
+
+/// Solves the instance in `input_json` with the config in `config_json` (the default one if empty),
+/// and writes the solution to `path_sol`. Returns the paths of the SVGs of the layouts, of the JSON solution
+/// and of a thumbnail of every layout (see [`layout_to_svg_lod`](crate::io::layout_to_svg::layout_to_svg_lod)), in that order.
+/// Fails if the config or instance is malformed, in which case nothing is written
+pub fn solve_json(
+    config_json: String,
+    input_json: String,
+    path_sol: String,
+) -> Result<Vec<Vec<String>>, IoError> {
+    solve_json_in_pool(config_json, input_json, path_sol, None)
+}
+
+/// Same as [`solve_json`], but parses the instance in the given thread pool instead of the global rayon pool.
+/// Allows a service to share one bounded pool between all requests.
+/// If no pool is given, the `n_threads` of the config is used to create one.
+pub fn solve_json_in_pool(
+    config_json: String,
+    input_json: String,
+    path_sol: String,
+    thread_pool: Option<Arc<ThreadPool>>,
+) -> Result<Vec<Vec<String>>, IoError> {
+    solve_json_report(config_json, input_json, path_sol, thread_pool).map(|report| report.files)
+}
 
-    let json_instance: JsonInstance;
-    let instance: Instance;
+/// Outcome of [`solve_json_report`]
+#[derive(Clone, Debug)]
+pub struct SolveReport {
+    /// Paths of the SVGs of the layouts, of the JSON solution and of the thumbnails of the layouts, as returned by [`solve_json`]
+    pub files: Vec<Vec<String>>,
+    /// Time spent in every phase of the job, in the order they were executed
+    pub phases: Vec<(&'static str, Duration)>,
+    /// Usage of the solution, see [`JsonSolution::usage`](jagua_rs::io::json_solution::JsonSolution::usage)
+    pub usage: fsize,
+    pub n_items_placed: usize,
+    pub n_items_demanded: usize,
+}
+
+/// Same as [`solve_json_in_pool`], but also reports the duration of its phases (`parse`, `solve`, `post_process` and `write`)
+/// and the usage achieved, for services which monitor their jobs
+pub fn solve_json_report(
+    config_json: String,
+    input_json: String,
+    path_sol: String,
+    thread_pool: Option<Arc<ThreadPool>>,
+) -> Result<SolveReport, IoError> {
+    let start = Instant::now();
+    let config = parse_config(&config_json)?;
+    let json_instance = io::read_json_instance(None, Some(&input_json))?;
+    solve_instance_report(config, json_instance, path_sol, thread_pool, start)
+}
 
-    json_instance = io::read_json_instance(None, Some(&input_json));
-    let poly_simpl_config = match config.poly_simpl_tolerance {
-        Some(tolerance) => PolySimplConfig::Enabled { tolerance },
-        None => PolySimplConfig::Disabled,
+/// Same as [`solve_json_report`], for an instance uploaded as a zip archive with the DXF and SVG files it references,
+/// see [`zip_instance`](crate::io::zip_instance::zip_instance)
+pub fn solve_zip_report(
+    config_json: String,
+    archive: &[u8],
+    path_sol: String,
+    thread_pool: Option<Arc<ThreadPool>>,
+) -> Result<SolveReport, IoError> {
+    let start = Instant::now();
+    let config = parse_config(&config_json)?;
+    let json_instance =
+        zip_instance::zip_instance(archive).map_err(|reason| IoError::InvalidFile {
+            path: PathBuf::from("<archive>"),
+            reason,
+        })?;
+    solve_instance_report(config, json_instance, path_sol, thread_pool, start)
+}
+
+fn solve_instance_report(
+    config: LBFConfig,
+    json_instance: JsonInstance,
+    path_sol: String,
+    thread_pool: Option<Arc<ThreadPool>>,
+    start: Instant,
+) -> Result<SolveReport, IoError> {
+    let mut phases = vec![];
+    let mut phase_start = start;
+    let mut end_phase = |phase: &'static str| {
+        phases.push((phase, phase_start.elapsed()));
+        phase_start = Instant::now();
     };
 
-    let parser = Parser::new(poly_simpl_config, config.cde_config, true, PathBuf::new());
-    instance = parser.parse(&json_instance);
+    config.validate_for(&json_instance)?;
+    let json_instance = parser::apply_missing_asset_policy(
+        &json_instance,
+        &AssetPaths::default(),
+        config.missing_assets,
+    )?
+    .into_owned();
+    let poly_simpl_config = config.poly_simpl_config();
+
+    let parser = Parser::new(
+        poly_simpl_config,
+        config.cde_config,
+        true,
+        PathBuf::new(),
+        config.fidelity(),
+    )
+    .with_rotated_bins(config.rotated_bins)
+    .with_part_in_part_forbidden(config.forbid_part_in_part);
+    let parser = match thread_pool.or_else(|| config.thread_pool()) {
+        Some(thread_pool) => parser.with_thread_pool(thread_pool),
+        None => parser,
+    };
+    let instance = parser.parse(&json_instance)?;
+    end_phase("parse");
 
     let rng = match config.prng_seed {
         Some(seed) => SmallRng::seed_from_u64(seed),
         None => SmallRng::from_entropy(),
     };
 
-    let mut optimizer = LBFOptimizer::new(instance.clone(), config, rng);
-    let solution = optimizer.solve();
+    let solution = lbf_runs::solve_best(&instance, config, rng)?;
+    end_phase("solve");
 
-    let json_output = JsonOutput {
+    let post_process_span = info_span!("post_process").entered();
+    let mut json_output = JsonOutput {
         instance: json_instance.clone(),
-        solution: parser::compose_json_solution(&solution, &instance, EPOCH.clone()),
-        config: config.clone(),
+        solution: parser::compose_json_solution(
+            &solution,
+            &instance,
+            *EPOCH,
+            config.clearance_threshold,
+            &config.objectives(),
+        ),
+        config,
     };
+    if let Some(step) = config.rotation_snap_radians() {
+        json_output::snap_rotations(&mut json_output.solution, &instance, step);
+    }
+    if let Some(decimals) = config.export_precision {
+        json_output::round_transformations(&mut json_output.solution, &instance, decimals);
+    }
+    if let Some(cut_order_config) = config.cut_order {
+        cut_order::assign_cut_orders(&mut json_output.solution, &instance, cut_order_config);
+    }
+    if let Some(min_web_width) = config.min_web_width {
+        parser::add_thin_webs(
+            &mut json_output.solution,
+            &solution,
+            &instance,
+            min_web_width,
+        );
+    }
+    json_output.solution.metadata = Some(json_output::solution_metadata(
+        &json_output.instance,
+        &json_output.config,
+    ));
+    io::log_clearances(&json_output.solution);
+    io::log_thin_webs(&json_output.solution);
+    drop(post_process_span);
+    end_phase("post_process");
 
+    let write_span = info_span!("write").entered();
     let json_sol_path: String = format!("{}sol_{}.json", path_sol, "web");
     io::write_json_output(&json_output, Path::new(&json_sol_path));
 
@@ -68,6 +195,67 @@ pub fn solve_json(config_json: String, input_json: String, path_sol: String) ->
         );
         svg_sol_paths.push(svg_path);
     }
+    let mut thumbnail_paths = Vec::new();
+    for (i, s_layout) in solution.layout_snapshots.iter().enumerate() {
+        let thumbnail_path = format!("{}thumb_{}_{}.svg", path_sol, "web", i);
+        let level = LevelOfDetail::Thumbnail {
+            width: THUMBNAIL_WIDTH,
+        };
+        io::write_svg(
+            &s_layout_to_svg_lod(s_layout, &instance, config.svg_draw_options, level),
+            Path::new(&thumbnail_path),
+        );
+        thumbnail_paths.push(thumbnail_path);
+    }
+    drop(write_span);
+    end_phase("write");
+
+    Ok(SolveReport {
+        files: vec![svg_sol_paths, vec![json_sol_path], thumbnail_paths],
+        phases,
+        usage: json_output.solution.usage,
+        n_items_placed: solution.n_items_placed(),
+        n_items_demanded: instance.total_item_qty(),
+    })
+}
+
+/// Writes an SVG per item of the instance, overlaying the contour simplified according to the config on the original one.
+/// Allows the simplification to be reviewed before solving. Returns the paths of the SVGs
+pub fn preview_json(
+    config_json: String,
+    input_json: String,
+    path_sol: String,
+) -> Result<Vec<String>, IoError> {
+    let config = parse_config(&config_json)?;
+    let json_instance = io::read_json_instance(None, Some(&input_json))?;
 
-    vec![svg_sol_paths.clone(), vec![json_sol_path.clone()]]
+    let previews =
+        simplification_preview::preview_simplification(&json_instance, &config, PathBuf::new())?;
+    simplification_preview::log_previews(&previews);
+
+    let svg_paths = previews
+        .iter()
+        .map(|preview| {
+            let svg_path = format!("{}preview_{}.svg", path_sol, preview.item_id);
+            io::write_svg(
+                &simplification_preview::preview_to_svg(preview),
+                Path::new(&svg_path),
+            );
+            svg_path
+        })
+        .collect();
+    Ok(svg_paths)
+}
+
+fn parse_config(config_json: &str) -> Result<LBFConfig, IoError> {
+    if config_json.is_empty() {
+        warn!("No config file provided");
+        warn!(
+            "Falling back default config:\n{}",
+            serde_json::to_string(&LBFConfig::default()).unwrap()
+        );
+        Ok(LBFConfig::default())
+    } else {
+        serde_json::from_str(config_json).map_err(|source| IoError::Json { path: None, source })
+    }
 }
@@ -0,0 +1,110 @@
+use itertools::Itertools;
+use log::info;
+use rand::prelude::SmallRng;
+use rand::SeedableRng;
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::solution::Solution;
+use jagua_rs::util::objectives::{ObjectiveVector, ParetoSet};
+
+use crate::calibration;
+use crate::io::error::IoError;
+use crate::lbf_config::LBFConfig;
+use crate::lbf_optimizer::LBFOptimizer;
+
+/// Runs the LBF heuristic `config.n_runs` times, each run with its own PRNG stream.
+/// The first run uses `rng` itself, so a single run is identical to solving with [`LBFOptimizer`] directly.
+/// If `cde_calibration` is configured, all runs use the configuration of the CDE selected by [`calibration::calibrate`].
+/// Returns all runs which are not dominated on the objectives of the config, see [`LBFConfig::run_objectives`].
+/// Fails if the config cannot be used for the instance, see [`LBFConfig::validate_for_instance`].
+pub fn solve_runs(
+    instance: &Instance,
+    config: LBFConfig,
+    rng: SmallRng,
+) -> Result<ParetoSet<Solution>, IoError> {
+    Ok(solve_runs_counted(instance, config, rng)?.0)
+}
+
+/// Same as [`solve_runs`], also returns the number of samples evaluated over all runs (excluding those of the calibration)
+pub fn solve_runs_counted(
+    instance: &Instance,
+    config: LBFConfig,
+    rng: SmallRng,
+) -> Result<(ParetoSet<Solution>, usize), IoError> {
+    let calibrated = config
+        .cde_calibration
+        .map(|calib_config| calibration::calibrate(instance, config, calib_config, &rng))
+        .transpose()?;
+    let (instance, config) = match &calibrated {
+        Some((calib_instance, calib_config)) => (calib_instance, *calib_config),
+        None => (instance, config),
+    };
+
+    let n_runs = config.n_runs.unwrap_or(1);
+    assert!(n_runs > 0, "at least one run is required");
+    let objectives = config.run_objectives();
+
+    //the streams of the other runs are derived from the one of the first
+    let mut seeder = rng.clone();
+    let mut pareto_set = ParetoSet::new();
+    let mut n_samples = 0;
+    for run in 0..n_runs {
+        let run_rng = match run {
+            0 => rng.clone(),
+            _ => SmallRng::from_rng(&mut seeder).expect("could not seed run"),
+        };
+        let mut optimizer = LBFOptimizer::new(instance.clone(), config, run_rng)?;
+        let solution = optimizer.solve();
+        n_samples += optimizer.sample_counter;
+        let vector = ObjectiveVector::evaluate(&solution, instance, &objectives);
+        let description = describe(&vector);
+        let kept = pareto_set.insert(vector, solution);
+        if n_runs > 1 {
+            info!(
+                "[RUNS] run {}/{}: {} ({})",
+                run + 1,
+                n_runs,
+                description,
+                if kept { "kept" } else { "dominated" }
+            );
+        }
+    }
+
+    if n_runs > 1 {
+        info!(
+            "[RUNS] {} runs on the pareto front, the one with the highest score is kept: {}",
+            pareto_set.len(),
+            describe(&pareto_set.best().unwrap().0)
+        );
+    }
+    Ok((pareto_set, n_samples))
+}
+
+/// Solution with the highest score of [`solve_runs`]
+pub fn solve_best(
+    instance: &Instance,
+    config: LBFConfig,
+    rng: SmallRng,
+) -> Result<Solution, IoError> {
+    Ok(solve_best_counted(instance, config, rng)?.0)
+}
+
+/// Same as [`solve_best`], also returns the number of samples evaluated over all runs, see [`solve_runs_counted`]
+pub fn solve_best_counted(
+    instance: &Instance,
+    config: LBFConfig,
+    rng: SmallRng,
+) -> Result<(Solution, usize), IoError> {
+    let (pareto_set, n_samples) = solve_runs_counted(instance, config, rng)?;
+    Ok((pareto_set.best().unwrap().1.clone(), n_samples))
+}
+
+fn describe(vector: &ObjectiveVector) -> String {
+    let values = vector
+        .objectives
+        .iter()
+        .zip(vector.values.iter())
+        .map(|(wo, v)| format!("{:?}: {:.3}", wo.objective, v))
+        .join(", ");
+    format!("{} (score {:.3})", values, vector.score())
+}
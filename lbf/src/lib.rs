@@ -2,11 +2,23 @@ use std::time::Instant;
 
 use once_cell::sync::Lazy;
 
+pub mod bin_assignment;
+pub mod calibration;
+pub mod clusters;
+pub mod cut_order;
+pub mod exact_search;
 pub mod io;
+pub mod lattice;
 pub mod lbf_config;
 pub mod lbf_cost;
 pub mod lbf_optimizer;
 pub mod lbf_run;
+pub mod lbf_runs;
+pub mod reference;
 pub mod samplers;
+pub mod serve;
+pub mod suggestions;
+pub mod sweep;
+pub mod verify;
 
 pub static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
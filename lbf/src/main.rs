@@ -1,25 +1,60 @@
+use std::fmt::Display;
 use std::fs;
-use std::fs::File;
-use std::io::BufReader;
+use std::net::TcpListener;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
-use clap::Parser as ClapParser;
-use jagua_rs::io::dxf_parse::DxfInstance;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser as ClapParser};
+use itertools::Itertools;
+use jagua_rs::io::asset_paths::AssetPaths;
+use jagua_rs::io::dxf_export::{self, DxfLayering};
+use jagua_rs::io::dxf_layers::DxfLayerMapping;
+use jagua_rs::io::gcode;
+use jagua_rs::io::geojson;
 use jagua_rs::io::json_instance::JsonInstance;
-use log::{error, warn};
+use log::{error, info, warn};
 use mimalloc::MiMalloc;
 use rand::prelude::SmallRng;
 use rand::SeedableRng;
+use rayon::ThreadPoolBuilder;
 
+use jagua_rs::collision_detection::cd_trace;
 use jagua_rs::entities::instances::instance::Instance;
 use jagua_rs::io::parser;
 use jagua_rs::io::parser::Parser;
+use jagua_rs::io::shape_cache::ShapeCache;
+use jagua_rs::util::config::{GeometricFidelity, Preset};
 use jagua_rs::util::polygon_simplification::PolySimplConfig;
-use lbf::io::cli::Cli;
-use lbf::io::json_output::JsonOutput;
+use lbf::cut_order;
+use lbf::io::cli::{
+    Cli, Command, InspectDxfArgs, PreviewSimplificationArgs, ServeArgs, SimplifyArgs, SolveArgs,
+    SweepArgs, VerifyArgs,
+};
+use lbf::io::csv_export;
+use lbf::io::dxf_folder;
+use lbf::io::dxf_inspect;
+use lbf::io::error::IoError;
+use lbf::io::heat_map::{self, HeatMap};
+use lbf::io::json_output::{self, JsonOutput};
+use lbf::io::label_export;
 use lbf::io::layout_to_svg::s_layout_to_svg;
+#[cfg(feature = "pdf")]
+use lbf::io::pdf_report;
+use lbf::io::placements;
+#[cfg(feature = "png")]
+use lbf::io::png_export;
+use lbf::io::repro;
+use lbf::io::run_stats::RunStats;
+use lbf::io::simplification_preview;
 use lbf::lbf_config::LBFConfig;
-use lbf::lbf_optimizer::LBFOptimizer;
+use lbf::lbf_runs;
+use lbf::reference;
+use lbf::samplers::sample_trace;
+use lbf::serve;
+use lbf::sweep::{self, SweepParameters};
+use lbf::verify;
 use lbf::{io, EPOCH};
 
 #[global_allocator]
@@ -29,147 +64,438 @@ static GLOBAL: MiMalloc = MiMalloc;
 fn main() {
     let args = Cli::parse();
     io::init_logger(args.log_level);
+    //flushes the recorded spans when main returns
+    let _flame_guard = args
+        .trace_flame
+        .as_ref()
+        .map(|path| or_exit(io::init_tracing(path, args.trace_level)));
 
-    let config = match args.config_file {
-        None => {
-            warn!("No config file provided, use --config-file to provide a custom config");
-            warn!(
-                "Falling back default config:\n{}",
-                serde_json::to_string(&LBFConfig::default()).unwrap()
-            );
-            LBFConfig::default()
+    match (args.command, args.solve_args) {
+        (Some(Command::Simplify(simplify_args)), _) => simplify(simplify_args),
+        (Some(Command::InspectDxf(inspect_args)), _) => inspect_dxf(inspect_args),
+        (Some(Command::PreviewSimplification(preview_args)), _) => {
+            preview_simplification(preview_args)
         }
-        Some(config_file) => {
-            let file = File::open(config_file).unwrap_or_else(|err| {
-                panic!("Config file could not be opened: {}", err);
-            });
-            let reader = BufReader::new(file);
-            serde_json::from_reader(reader).unwrap_or_else(|err| {
-                error!("Config file could not be parsed: {}", err);
-                error!("Omit the --config-file argument to use the default config");
-                panic!();
+        (Some(Command::Verify(verify_args)), _) => verify(verify_args),
+        (Some(Command::Sweep(sweep_args)), _) => sweep(sweep_args),
+        (Some(Command::Serve(serve_args)), _) => serve(serve_args),
+        (None, Some(solve_args)) => solve(solve_args),
+        (None, None) => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "either a subcommand or --input-file and --solution-folder must be provided",
+            )
+            .exit(),
+    }
+}
+
+/// Returns the value of a successful result, or logs the error and exits
+fn or_exit<T, E: Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1)
+    })
+}
+
+fn simplify(args: SimplifyArgs) {
+    let json_instance = or_exit(io::read_json_instance(
+        Some(args.input_file.as_path()),
+        None,
+    ));
+    let poly_simpl_config = PolySimplConfig::Enabled {
+        tolerance: args.tolerance,
+    };
+
+    let assets_folder = args
+        .input_file
+        .parent()
+        .expect("Could not get parent directory")
+        .to_path_buf();
+
+    //polygons are not centered, so the shapes remain in the coordinates of the input file
+    let parser = Parser::new(
+        poly_simpl_config,
+        LBFConfig::default().cde_config,
+        false,
+        assets_folder,
+        GeometricFidelity::default(),
+    );
+    let instance = or_exit(parser.parse(&json_instance));
+
+    let simplified_json_instance = parser::compose_json_instance(&json_instance, &instance);
+    io::write_json_instance(&simplified_json_instance, args.output_file.as_path());
+}
+
+fn inspect_dxf(args: InspectDxfArgs) {
+    let dxf_paths = match args.path.is_dir() {
+        true => fs::read_dir(&args.path)
+            .unwrap_or_else(|err| panic!("could not read folder: {}, {}", args.path.display(), err))
+            .map(|entry| entry.expect("could not read folder entry").path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("dxf"))
             })
-        }
+            .sorted()
+            .collect_vec(),
+        false => vec![args.path.clone()],
     };
 
-    let json_instance: JsonInstance;
-    let json_with_dxf_instance: JsonInstance;
-    let instance: Instance;
+    if let Some(svg_folder) = &args.svg_folder {
+        fs::create_dir_all(svg_folder)
+            .unwrap_or_else(|_| panic!("could not create svg folder: {:?}", svg_folder));
+    }
 
-    if args.input_file.to_str().unwrap().contains("dxf") {
-        println!(
-            "{} is a dxf json file",
-            args.input_file.as_path().to_string_lossy()
-        );
+    for dxf_path in dxf_paths {
+        let inspection = dxf_inspect::inspect_dxf(&dxf_path);
+        dxf_inspect::log_inspection(&inspection);
 
-        json_with_dxf_instance = io::read_json_instance(Some(args.input_file.as_path()), None);
-        let poly_simpl_config = match config.poly_simpl_tolerance {
-            Some(tolerance) => PolySimplConfig::Enabled { tolerance },
-            None => PolySimplConfig::Disabled,
-        };
+        if let (Some(svg_folder), Some(document)) = (
+            &args.svg_folder,
+            dxf_inspect::inspection_to_svg(&inspection),
+        ) {
+            let dxf_file_stem = dxf_path.file_stem().unwrap().to_str().unwrap();
+            let svg_path = svg_folder.join(format!("{}.svg", dxf_file_stem));
+            io::write_svg(&document, &svg_path);
+        }
+    }
+}
+
+fn preview_simplification(args: PreviewSimplificationArgs) {
+    let json_instance = or_exit(io::read_json_instance(
+        Some(args.input_file.as_path()),
+        None,
+    ));
+    let config = or_exit(read_config(args.config_file, args.preset));
+    let assets_folder = args
+        .input_file
+        .parent()
+        .expect("Could not get parent directory")
+        .to_path_buf();
+
+    let previews = or_exit(simplification_preview::preview_simplification(
+        &json_instance,
+        &config,
+        assets_folder,
+    ));
+    simplification_preview::log_previews(&previews);
+
+    fs::create_dir_all(&args.svg_folder)
+        .unwrap_or_else(|_| panic!("could not create svg folder: {:?}", args.svg_folder));
+    for preview in &previews {
+        let svg_path = args
+            .svg_folder
+            .join(format!("item_{}.svg", preview.item_id));
+        io::write_svg(&simplification_preview::preview_to_svg(preview), &svg_path);
+    }
+}
+
+fn verify(args: VerifyArgs) {
+    let dxf_layer_mapping = or_exit(read_dxf_layer_mapping(args.dxf_layers.as_deref()));
+    let (json_instance, assets_folder) = or_exit(read_instance(
+        &args.instance,
+        GeometricFidelity::default(),
+        &dxf_layer_mapping,
+    ));
+    //the solution is verified against the original geometry of the items, and the bins as defined in the input
+    let cde_config = LBFConfig::default().cde_config;
+    let instance = or_exit(
+        Parser::new(
+            PolySimplConfig::Disabled,
+            cde_config,
+            true,
+            assets_folder,
+            GeometricFidelity::default(),
+        )
+        .with_dxf_layer_mapping(dxf_layer_mapping)
+        .parse(&json_instance),
+    );
+    let json_layouts = or_exit(placements::read_solution_layouts(&args.solution, &instance));
+    let report = verify::verify_solution(&instance, &json_layouts, cde_config);
+    verify::log_report(&report);
+    if !report.is_feasible() {
+        std::process::exit(1);
+    }
+}
+
+fn sweep(args: SweepArgs) {
+    let base_config = or_exit(read_config(args.config_file, args.preset));
+    let parameters: SweepParameters = or_exit(io::read_json_file(&args.parameters));
+    let configs = or_exit(sweep::sweep_configs(&base_config, &parameters));
+    info!(
+        "[SWEEP] {} configs on {} instances",
+        configs.len(),
+        args.instance.len()
+    );
+
+    let mut results = vec![];
+    for input_file in &args.instance {
+        let (json_instance, assets_folder) = or_exit(read_instance(
+            input_file,
+            base_config.fidelity(),
+            &DxfLayerMapping::default(),
+        ));
+        for (_, config) in &configs {
+            or_exit(config.validate_for(&json_instance));
+        }
+        results.extend(or_exit(sweep::run_sweep(
+            &json_instance,
+            assets_folder,
+            &configs,
+            args.parallel,
+        )));
+    }
+    io::write_csv(&sweep::results_csv(&parameters, &results), &args.output);
+}
+
+fn serve(args: ServeArgs) {
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(args.n_threads)
+        .build()
+        .expect("could not build thread pool");
+    let listener = or_exit(TcpListener::bind((args.address, args.port)));
+    or_exit(serve::serve(
+        listener,
+        args.solution_folder,
+        Arc::new(thread_pool),
+    ));
+}
 
-        let parent_dir = args
-            .input_file
-            .as_path()
+/// Reads the JSON instance of an input file or folder, together with the folder against which the parser resolves its DXF files
+fn read_instance(
+    input_file: &Path,
+    fidelity: GeometricFidelity,
+    dxf_layer_mapping: &DxfLayerMapping,
+) -> Result<(JsonInstance, PathBuf), IoError> {
+    if input_file.is_dir() {
+        println!("{} is a folder of dxf files", input_file.to_string_lossy());
+        Ok((
+            dxf_folder::folder_instance(input_file, fidelity, dxf_layer_mapping)?,
+            input_file.to_path_buf(),
+        ))
+    } else if input_file.extension().is_some_and(|ext| ext == "geojson") {
+        println!("{} is a geojson file", input_file.to_string_lossy());
+        Ok((io::read_geojson_instance(input_file)?, PathBuf::new()))
+    } else if input_file.extension().is_some_and(|ext| ext == "xml") {
+        println!("{} is an esicup xml file", input_file.to_string_lossy());
+        Ok((io::read_esicup_instance(input_file)?, PathBuf::new()))
+    } else if input_file.extension().is_some_and(|ext| ext == "zip") {
+        println!("{} is a zip archive", input_file.to_string_lossy());
+        Ok((io::read_zip_instance(input_file)?, PathBuf::new()))
+    } else if input_file.to_string_lossy().contains("dxf") {
+        println!("{} is a dxf json file", input_file.to_string_lossy());
+        let parent_dir = input_file
             .parent()
             .expect("Could not get parent directory")
             .to_path_buf();
+        Ok((io::read_json_instance(Some(input_file), None)?, parent_dir))
+    } else if input_file.to_string_lossy().contains(".json") {
+        println!("{} is a regular json file", input_file.to_string_lossy());
+        Ok((
+            io::read_json_instance(Some(input_file), None)?,
+            PathBuf::new(),
+        ))
+    } else {
+        Err(IoError::InvalidFile {
+            path: input_file.to_path_buf(),
+            reason: "neither a directory, a json, geojson, xml nor zip file".into(),
+        })
+    }
+}
 
-        let parser = Parser::new(poly_simpl_config, config.cde_config, true, parent_dir);
-        instance = parser.parse(&json_with_dxf_instance);
+/// Reads the mapping of the layers of DXF files, the default mapping (all layers are outline layers) if no file is given
+fn read_dxf_layer_mapping(path: Option<&Path>) -> Result<DxfLayerMapping, IoError> {
+    match path {
+        Some(path) => io::read_json_file(path),
+        None => Ok(DxfLayerMapping::default()),
+    }
+}
 
-        json_instance = json_with_dxf_instance.clone();
-    } else if args.input_file.to_str().unwrap().contains(".json") {
-        println!(
-            "{} is a regular json file",
-            args.input_file.as_path().to_string_lossy()
-        );
-        json_instance = io::read_json_instance(Some(args.input_file.as_path()), None);
-        let poly_simpl_config = match config.poly_simpl_tolerance {
-            Some(tolerance) => PolySimplConfig::Enabled { tolerance },
-            None => PolySimplConfig::Disabled,
-        };
+fn solve(args: SolveArgs) {
+    let (input_file, mut config) = match &args.from_repro {
+        Some(bundle_path) => {
+            let repro = or_exit(repro::import_repro(
+                bundle_path,
+                &args.solution_folder.join("repro"),
+            ));
+            (repro.instance_path, repro.config)
+        }
+        None => (
+            args.input_file.clone().expect("no input file provided"),
+            or_exit(read_config(args.config_file.clone(), args.preset)),
+        ),
+    };
+    if args.export_repro.is_some() && config.prng_seed.is_none() {
+        //a bundled run is only reproducible if it is seeded
+        let seed = rand::random();
+        info!("[REPRO] no PRNG seed configured, using seed {}", seed);
+        config.prng_seed = Some(seed);
+    }
 
-        let parser = Parser::new(poly_simpl_config, config.cde_config, true, PathBuf::new());
-        instance = parser.parse(&json_instance);
-    } else {
-        error!(
-            "{} is neither a directory nor a regular file",
-            args.input_file.as_path().to_string_lossy()
-        );
-        panic!();
-    }
-
-    // let metadata = fs::metadata(args.input_file.as_path());
-    // if let Ok(metadata) = metadata {
-    //     if metadata.contains("dxf") {
-    //         println!("{} is a directory", args.input_file.as_path().to_string_lossy());
-
-    //         // TODO implement folder parsing - dxf
-    //         let entries = fs::read_dir(args.input_file.as_path()).unwrap();
-
-    //         for entry in entries {
-    //             let entry = entry.unwrap();
-    //             let path = entry.path();
-
-    //             // Doe iets met het bestand, bijvoorbeeld:
-    //             if path.is_file() && path.extension().map_or(false, |ext| ext == "dxf") {
-    //                 println!("Bestand gevonden: {}", path.display());
-
-    //                 // let dxf_instance = io::read_dxf_instance(path.as_path());
-
-    //                 let poly_simpl_config = match config.poly_simpl_tolerance {
-    //                     Some(tolerance) => PolySimplConfig::Enabled { tolerance },
-    //                     None => PolySimplConfig::Disabled,
-    //                 };
-
-    //                 // let parser = Parser::new(poly_simpl_config, config.cde_config, true);
-    //                 // instance = parser.parse(&dxf_instance);
-    //             }
-    //         }
-
-    //         json_instance = io::read_json_instance(args.input_file.as_path());
-    //         let poly_simpl_config = match config.poly_simpl_tolerance {
-    //             Some(tolerance) => PolySimplConfig::Enabled { tolerance },
-    //             None => PolySimplConfig::Disabled,
-    //         };
-
-    //         let parser = Parser::new(poly_simpl_config, config.cde_config, true);
-    //         instance = parser.parse(&json_instance);
-
-    //     } else if metadata.is_file() {
-    //         println!("{} is a regular file", args.input_file.as_path().to_string_lossy());
-    //         // let instance = json_parse(args.input_file.as_path(), config);
-    //         json_instance = io::read_json_instance(args.input_file.as_path());
-    //         let poly_simpl_config = match config.poly_simpl_tolerance {
-    //             Some(tolerance) => PolySimplConfig::Enabled { tolerance },
-    //             None => PolySimplConfig::Disabled,
-    //         };
-
-    //         let parser = Parser::new(poly_simpl_config, config.cde_config, true);
-    //         instance = parser.parse(&json_instance);
-    //     } else {
-    //         error!("{} is neither a directory nor a regular file", args.input_file.as_path().to_string_lossy());
-    //         panic!();
-    //     }
-    // } else {
-    //     error!("Could not define if input is file or folder");
-    //     panic!();
-    // }
+    let dxf_layer_mapping = or_exit(read_dxf_layer_mapping(args.dxf_layers.as_deref()));
+    let (json_instance, assets_folder) = or_exit(read_instance(
+        &input_file,
+        config.fidelity(),
+        &dxf_layer_mapping,
+    ));
+    or_exit(config.validate_for(&json_instance));
+    let asset_paths = args
+        .asset_folder
+        .iter()
+        .fold(AssetPaths::new(assets_folder), |paths, folder| {
+            paths.with_search_folder(folder.clone())
+        });
+    let json_instance = or_exit(parser::apply_missing_asset_policy(
+        &json_instance,
+        &asset_paths,
+        config.missing_assets,
+    ))
+    .into_owned();
+
+    let parser = Parser::new(
+        config.poly_simpl_config(),
+        config.cde_config,
+        true,
+        PathBuf::new(),
+        config.fidelity(),
+    )
+    .with_asset_paths(asset_paths.clone())
+    .with_dxf_layer_mapping(dxf_layer_mapping)
+    .with_rotated_bins(config.rotated_bins)
+    .with_part_in_part_forbidden(config.forbid_part_in_part);
+    let parser = match config.thread_pool() {
+        Some(thread_pool) => parser.with_thread_pool(thread_pool),
+        None => parser,
+    };
+    let parser = match &args.cache_folder {
+        Some(cache_folder) => {
+            parser.with_shape_cache(or_exit(ShapeCache::new(cache_folder.clone())))
+        }
+        None => parser,
+    };
+    let instance: Instance = or_exit(parser.parse(&json_instance));
+
+    if let Some(bundle_path) = &args.export_repro {
+        //a folder is bundled as a json instance next to its dxf files, which is recognized as such on import
+        let instance_file = match input_file.is_dir() {
+            true => PathBuf::from(format!(
+                "{}.dxf.json",
+                input_file.file_name().unwrap().to_string_lossy()
+            )),
+            false => input_file.clone(),
+        };
+        or_exit(repro::export_repro(
+            bundle_path,
+            &instance_file,
+            &json_instance,
+            &config,
+            &asset_paths,
+        ));
+    }
 
     let rng = match config.prng_seed {
         Some(seed) => SmallRng::seed_from_u64(seed),
         None => SmallRng::from_entropy(),
     };
 
-    let mut optimizer = LBFOptimizer::new(instance.clone(), config, rng);
-    let solution = optimizer.solve();
+    //the stats count the collision queries of the solve
+    let trace_collisions = args.trace_collisions || args.stats_file.is_some();
+    if trace_collisions {
+        cd_trace::reset();
+        cd_trace::enable();
+    }
+    if args.sample_heat_map {
+        sample_trace::take();
+        sample_trace::enable();
+    }
+
+    let start = Instant::now();
+    let (solution, n_samples) = or_exit(lbf_runs::solve_best_counted(&instance, config, rng));
+    let run_time = start.elapsed();
 
-    let json_output = JsonOutput {
+    if trace_collisions {
+        cd_trace::disable();
+    }
+    if let Some(stats_path) = &args.stats_file {
+        let stats = RunStats::new(
+            &json_instance.name,
+            config.prng_seed,
+            &solution,
+            run_time,
+            n_samples,
+            &cd_trace::counts(),
+        );
+        io::append_run_stats(&stats, stats_path);
+    }
+    if args.trace_collisions {
+        let trace = cd_trace::counts();
+        info!("[TRACE] {} collision queries, decided by:", trace.total());
+        for line in trace.to_string().lines() {
+            info!("[TRACE] {}", line);
+        }
+    }
+
+    if let Some(reference_folder) = &args.reference_folder {
+        match or_exit(reference::compare_to_reference(
+            reference_folder,
+            &json_instance.name,
+            &instance,
+            &solution,
+        )) {
+            Some(comparison) => reference::log_comparison(&json_instance.name, &comparison),
+            None => warn!(
+                "[REF] no reference solution for {} in {}",
+                json_instance.name,
+                reference_folder.display()
+            ),
+        }
+    }
+
+    let mut json_output = JsonOutput {
         instance: json_instance.clone(),
-        solution: parser::compose_json_solution(&solution, &instance, *EPOCH),
+        solution: parser::compose_json_solution(
+            &solution,
+            &instance,
+            *EPOCH,
+            config.clearance_threshold,
+            &config.objectives(),
+        ),
         config,
     };
+    if let Some(step) = config.rotation_snap_radians() {
+        json_output::snap_rotations(&mut json_output.solution, &instance, step);
+    }
+    if let Some(decimals) = config.export_precision {
+        json_output::round_transformations(&mut json_output.solution, &instance, decimals);
+    }
+    if let Some(cut_order_config) = config.cut_order {
+        cut_order::assign_cut_orders(&mut json_output.solution, &instance, cut_order_config);
+    }
+    if let Some(min_web_width) = config.min_web_width {
+        parser::add_thin_webs(
+            &mut json_output.solution,
+            &solution,
+            &instance,
+            min_web_width,
+        );
+    }
+    json_output.solution.metadata = Some(json_output::solution_metadata(
+        &json_output.instance,
+        &json_output.config,
+    ));
+    io::log_clearances(&json_output.solution);
+    io::log_thin_webs(&json_output.solution);
+    let feasible = match args.validate {
+        true => {
+            let (imported, violations) = or_exit(verify::validate_json_solution(
+                &instance,
+                &json_output.solution.layouts,
+                config.cde_config,
+            ));
+            verify::log_placement_violations(&imported, &violations);
+            violations.is_empty()
+        }
+        false => true,
+    };
 
     if !args.solution_folder.exists() {
         fs::create_dir_all(&args.solution_folder).unwrap_or_else(|_| {
@@ -180,13 +506,69 @@ fn main() {
         });
     }
 
-    let input_file_stem = args.input_file.file_stem().unwrap().to_str().unwrap();
+    let input_file_stem = input_file.file_stem().unwrap().to_str().unwrap();
 
     let solution_path = args
         .solution_folder
         .join(format!("sol_{}.json", input_file_stem));
     io::write_json_output(&json_output, Path::new(&solution_path));
 
+    if args.export_csv {
+        let csv_path = args
+            .solution_folder
+            .join(format!("sol_{}.csv", input_file_stem));
+        io::write_csv(
+            &csv_export::placements_csv(&json_output.solution, &instance),
+            &csv_path,
+        );
+    }
+
+    if args.export_labels {
+        let (template, extension) = match &args.label_template {
+            Some(template_file) => (
+                fs::read_to_string(template_file).unwrap_or_else(|_| {
+                    panic!("could not read label template: {:?}", template_file)
+                }),
+                template_file
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("txt"),
+            ),
+            None => (label_export::DEFAULT_LABEL_TEMPLATE.to_string(), "zpl"),
+        };
+        let labels_path = args
+            .solution_folder
+            .join(format!("sol_{}_labels.{}", input_file_stem, extension));
+        io::write_labels(
+            &label_export::labels(&solution, &instance, &template),
+            &labels_path,
+        );
+    }
+
+    if args.sample_heat_map {
+        sample_trace::disable();
+        let records = sample_trace::take();
+        let bins = solution
+            .layout_snapshots
+            .iter()
+            .map(|sl| &sl.bin)
+            .unique_by(|bin| bin.id);
+        for bin in bins {
+            let heat_map = HeatMap::new(bin, &records, heat_map::HEAT_MAP_RESOLUTION);
+            info!(
+                "[HEAT] bin {}: {} samples, {:.1}% rejected by a collision, {} outside the bin",
+                bin.id,
+                heat_map.total(),
+                heat_map.rejected_fraction() * 100.0,
+                heat_map.n_outside
+            );
+            let svg_path = args
+                .solution_folder
+                .join(format!("sol_{}_heat_{}.svg", input_file_stem, bin.id));
+            io::write_svg(&heat_map::heat_map_to_svg(bin, &heat_map), &svg_path);
+        }
+    }
+
     for (i, s_layout) in solution.layout_snapshots.iter().enumerate() {
         let svg_path = args
             .solution_folder
@@ -196,4 +578,108 @@ fn main() {
             Path::new(&svg_path),
         );
     }
+
+    #[cfg(feature = "png")]
+    if let Some(width) = args.export_png {
+        for (i, s_layout) in solution.layout_snapshots.iter().enumerate() {
+            let png = or_exit(png_export::s_layout_to_png(
+                s_layout,
+                &instance,
+                config.svg_draw_options,
+                width,
+            ));
+            let png_path = args
+                .solution_folder
+                .join(format!("sol_{}_{}.png", input_file_stem, i));
+            io::write_png(&png, &png_path);
+        }
+    }
+
+    #[cfg(feature = "pdf")]
+    if args.export_pdf {
+        let pdf_path = args
+            .solution_folder
+            .join(format!("sol_{}.pdf", input_file_stem));
+        io::write_pdf(
+            &pdf_report::solution_to_pdf(
+                &solution,
+                &instance,
+                &json_output.instance.name,
+                config.svg_draw_options,
+            ),
+            &pdf_path,
+        );
+    }
+
+    if args.export_geojson {
+        let geojson_path = args
+            .solution_folder
+            .join(format!("sol_{}.geojson", input_file_stem));
+        io::write_geojson(
+            &geojson::solution_to_geojson(&solution, &instance),
+            &geojson_path,
+        );
+    }
+
+    if args.export_dxf {
+        let layering = match args.dxf_layer_per_quality {
+            true => DxfLayering::PerQuality,
+            false => DxfLayering::PerItem,
+        };
+        for (i, s_layout) in solution.layout_snapshots.iter().enumerate() {
+            let dxf_path = args
+                .solution_folder
+                .join(format!("sol_{}_{}.dxf", input_file_stem, i));
+            io::write_dxf(
+                &dxf_export::layout_to_dxf(s_layout, &instance, layering),
+                &dxf_path,
+            );
+        }
+    }
+
+    if args.export_gcode {
+        let gcode_config = config.gcode;
+        for (i, s_layout) in solution.layout_snapshots.iter().enumerate() {
+            let cut_order = &json_output.solution.layouts[i].cut_order;
+            let part_order = (!cut_order.is_empty()).then_some(cut_order.as_slice());
+            let toolpath =
+                gcode::layout_toolpath(s_layout, &instance, gcode_config.travel, part_order);
+            info!(
+                "[GCODE] layout {}: {} contours, travel length {:.3}",
+                i,
+                toolpath.contours.len(),
+                toolpath.travel_distance()
+            );
+            let gcode_path = args
+                .solution_folder
+                .join(format!("sol_{}_{}.nc", input_file_stem, i));
+            io::write_gcode(
+                &gcode::toolpath_to_gcode(&toolpath, &instance, &gcode_config),
+                &gcode_path,
+            );
+        }
+    }
+
+    if !feasible {
+        error!("solution is infeasible, see the [VALIDATE] warnings above");
+        std::process::exit(1);
+    }
+}
+
+fn read_config(config_file: Option<PathBuf>, preset: Option<Preset>) -> Result<LBFConfig, IoError> {
+    match (config_file, preset) {
+        (None, Some(preset)) => {
+            info!("Using the {} preset", preset);
+            Ok(LBFConfig::preset(preset))
+        }
+        (None, None) => {
+            warn!("No config file provided, use --config-file to provide a custom config or --preset to select a preset");
+            warn!(
+                "Falling back default config:\n{}",
+                serde_json::to_string(&LBFConfig::default()).unwrap()
+            );
+            Ok(LBFConfig::default())
+        }
+        (Some(config_file), _) => io::read_json_file(&config_file),
+    }
 }
@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::solution::Solution;
+use jagua_rs::fsize;
+use jagua_rs::io::json_solution::{JsonContainer, JsonLayout};
+
+use crate::io::error::IoError;
+use crate::io::placements;
+
+/// Reference solution of an instance, such as the best known solution published in the literature
+pub struct ReferenceSolution {
+    /// File the solution was read from
+    pub path: PathBuf,
+    pub layouts: Vec<JsonLayout>,
+}
+
+/// Comparison of a solution against the reference solution of its instance, on their [`solution_value`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub value: fsize,
+    pub reference_value: fsize,
+    /// Relative difference between the value and the reference value, in percent. Positive if the solution is worse
+    pub gap: fsize,
+    pub n_placed: usize,
+    pub n_reference_placed: usize,
+}
+
+impl Comparison {
+    /// Whether the solution and the reference place the same number of items, without which the gap is meaningless
+    pub fn is_comparable(&self) -> bool {
+        self.n_placed == self.n_reference_placed
+    }
+}
+
+/// Path of the reference solution of an instance in a folder of reference solutions: `<folder>/<instance name>.json`
+pub fn reference_path(folder: &Path, instance_name: &str) -> PathBuf {
+    folder.join(format!("{}.json", instance_name))
+}
+
+/// Loads the reference solution of an instance from a folder of reference solutions, see [`reference_path`].
+/// Reference solutions are stored in our JSON schema (a bare `JsonSolution` or the output of `lbf`).
+/// Returns `None` if the folder contains no reference solution for the instance.
+/// The reference solution is not checked for feasibility, see [`verify_solution`](crate::verify::verify_solution) to do so.
+pub fn load_reference(
+    folder: &Path,
+    instance_name: &str,
+    instance: &Instance,
+) -> Result<Option<ReferenceSolution>, IoError> {
+    let path = reference_path(folder, instance_name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let layouts = placements::read_solution_layouts(&path, instance)?;
+    Ok(Some(ReferenceSolution { path, layouts }))
+}
+
+/// Compares a solution of the instance against a reference solution
+pub fn compare(
+    instance: &Instance,
+    solution: &Solution,
+    reference: &ReferenceSolution,
+) -> Result<Comparison, IoError> {
+    let value = solution_value(instance, solution);
+    let reference_value = reference
+        .layouts
+        .iter()
+        .map(|jl| match (instance, &jl.container) {
+            (Instance::SP(_), JsonContainer::Strip { width, .. }) => Ok(*width),
+            (Instance::BP(bp_instance), JsonContainer::Bin { index }) => bp_instance
+                .bins
+                .get(*index)
+                .map(|(bin, _)| bin.value as fsize)
+                .ok_or_else(|| format!("bin {} does not exist", index)),
+            _ => Err("container does not match the type of the instance".to_string()),
+        })
+        .sum::<Result<fsize, String>>()
+        .map_err(|reason| IoError::InvalidFile {
+            path: reference.path.clone(),
+            reason,
+        })?;
+
+    Ok(Comparison {
+        value,
+        reference_value,
+        gap: (value - reference_value) / fsize::max(reference_value, fsize::EPSILON) * 100.0,
+        n_placed: solution.placed_item_qtys.iter().sum(),
+        n_reference_placed: reference
+            .layouts
+            .iter()
+            .map(|jl| jl.placed_items.len())
+            .sum(),
+    })
+}
+
+/// Value on which solutions are compared: the width of the strip for strip packing, the total cost of the used bins for bin packing.
+/// Lower is better
+pub fn solution_value(instance: &Instance, solution: &Solution) -> fsize {
+    solution
+        .layout_snapshots
+        .iter()
+        .map(|sl| match instance {
+            Instance::SP(_) => sl.bin.bbox().width(),
+            Instance::BP(_) => sl.bin.value as fsize,
+        })
+        .sum()
+}
+
+/// Compares a solution against the reference solution of its instance in a folder of reference solutions.
+/// Returns `None` if the folder contains no reference solution for the instance
+pub fn compare_to_reference(
+    folder: &Path,
+    instance_name: &str,
+    instance: &Instance,
+    solution: &Solution,
+) -> Result<Option<Comparison>, IoError> {
+    load_reference(folder, instance_name, instance)?
+        .map(|reference| compare(instance, solution, &reference))
+        .transpose()
+}
+
+pub fn log_comparison(instance_name: &str, comparison: &Comparison) {
+    info!(
+        "[REF] {}: {:.3} vs. reference {:.3}, gap {:+.2}%",
+        instance_name, comparison.value, comparison.reference_value, comparison.gap
+    );
+    if !comparison.is_comparable() {
+        warn!(
+            "[REF] {}: {} items placed, but {} in the reference solution",
+            instance_name, comparison.n_placed, comparison.n_reference_placed
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::layout::Layout;
+use jagua_rs::fsize;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::PI;
+
+use crate::samplers::ls_sampler::{LSSampler, SD_ROT, SD_TRANSL};
+
+/// Configuration of the coarse-to-fine search for continuously rotating items.
+/// The uniform samples are restricted to a coarse grid of rotations, keeping the best placement per rotation.
+/// The local search budget is then split over the best of these candidates, refining both their rotation and translation.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct CoarseToFineConfig {
+    /// Number of rotations in the coarse grid, e.g. 8 for multiples of 45°
+    pub n_coarse_rotations: usize,
+    /// Number of candidates (each with a different coarse rotation) which are refined
+    pub n_candidates: usize,
+}
+
+impl CoarseToFineConfig {
+    /// Step of the coarse grid of rotations in radians.
+    /// If rotations are snapped (`rotation_snap`, in radians), the step is rounded to a multiple of it
+    pub fn coarse_step(&self, rotation_snap: Option<fsize>) -> fsize {
+        assert!(self.n_coarse_rotations > 0 && self.n_candidates > 0);
+        let step = 2.0 * PI / self.n_coarse_rotations as fsize;
+        match rotation_snap {
+            Some(snap) => fsize::max((step / snap).round(), 1.0) * snap,
+            None => step,
+        }
+    }
+}
+
+/// Creates a local search sampler around a candidate of the coarse phase.
+/// The rotation starts with a standard deviation of half a coarse step.
+/// The translation starts with the clearance around the candidate, as measured by the Hazard Proximity Grid
+/// (the proximity of the hazards at its pole of inaccessibility, minus the radius of the pole),
+/// bounded by the default range: tight spots are refined with small steps, open spots with larger ones.
+pub fn refinement_sampler(
+    item: &Item,
+    layout: &Layout,
+    candidate: &DTransformation,
+    coarse_step: fsize,
+) -> LSSampler {
+    let bbox = layout.bin.bbox();
+    let max_dim = fsize::max(bbox.width(), bbox.height());
+    let (sd_transl_max, sd_transl_min) = (SD_TRANSL.0 * max_dim, SD_TRANSL.1 * max_dim);
+
+    let poi = item.shape.poi.transform_clone(&candidate.compose());
+    let hpg = layout
+        .cde()
+        .haz_prox_grid()
+        .expect("layout has unflushed changes");
+    let clearance = hpg.hazard_proximity_at(poi.center, item.base_quality) - poi.radius;
+    let sd_transl = clearance.clamp(sd_transl_min, sd_transl_max);
+
+    let sd_rot = fsize::max(coarse_step / 2.0, SD_ROT.1);
+    LSSampler::new(
+        item,
+        candidate,
+        (sd_transl, sd_transl_min),
+        (sd_rot, SD_ROT.1),
+    )
+}
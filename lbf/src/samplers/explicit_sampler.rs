@@ -0,0 +1,70 @@
+use itertools::Itertools;
+use jagua_rs::collision_detection::hazard::HazardEntity;
+use jagua_rs::entities::bin::Bin;
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::layout::Layout;
+use jagua_rs::entities::placing_option::PlacingOption;
+use jagua_rs::entities::problems::problem_generic::LayoutIndex;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_enums::AllowedRotation;
+use jagua_rs::geometry::geo_traits::TransformableFrom;
+use jagua_rs::io::parser::absolute_to_internal_transform;
+
+use crate::lbf_cost::{LBFPlacingCost, PlacementBias};
+
+/// Transformations of an item with [`AllowedRotation::Explicit`], converted to the internal coordinates of `bin`.
+/// Returns `None` for items which can be placed anywhere.
+pub fn explicit_transformations(item: &Item, bin: &Bin) -> Option<Vec<DTransformation>> {
+    match &item.allowed_rotation {
+        AllowedRotation::Explicit(transfs) => Some(
+            transfs
+                .iter()
+                .map(|t| {
+                    absolute_to_internal_transform(t, &item.pretransform, &bin.pretransform)
+                        .decompose()
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Enumerates all allowed transformations of an item with [`AllowedRotation::Explicit`] in the layout, instead of sampling.
+/// Returns the valid one with the lowest cost, if any.
+pub fn best_explicit_placement(
+    layout: &Layout,
+    layout_idx: LayoutIndex,
+    item: &Item,
+    irrel_hazards: &[HazardEntity],
+    bias: PlacementBias,
+    sample_counter: &mut usize,
+) -> Option<PlacingOption> {
+    let transfs = explicit_transformations(item, &layout.bin)?;
+    let bin_bbox = layout.bin.bbox();
+    let mut buffer = (*item.shape).clone();
+    buffer.surrogate = None;
+
+    //all transformations are known upfront, so they are checked in a single batch
+    let composed = transfs.iter().map(|dt| dt.compose()).collect_vec();
+    let collides = layout
+        .cde()
+        .collides_any(&item.shape, &composed, irrel_hazards);
+
+    *sample_counter += transfs.len();
+    transfs
+        .into_iter()
+        .zip(composed.iter())
+        .zip(collides.iter().by_vals())
+        .filter(|(_, collides)| !collides)
+        .map(|((d_transf, transf), _)| {
+            buffer.transform_from(&item.shape, transf);
+            let p_opt = PlacingOption {
+                layout_idx,
+                item_id: item.id,
+                d_transf,
+            };
+            (p_opt, LBFPlacingCost::from_shape(&buffer, bias, &bin_bbox))
+        })
+        .min_by(|(_, c1), (_, c2)| c1.partial_cmp(c2).unwrap())
+        .map(|(p_opt, _)| p_opt)
+}
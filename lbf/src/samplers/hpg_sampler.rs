@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use itertools::Itertools;
 use log::debug;
 use rand::prelude::SliceRandom;
@@ -10,24 +12,37 @@ use jagua_rs::geometry::geo_traits::Shape;
 use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
 use jagua_rs::geometry::transformation::Transformation;
 
-use crate::lbf_cost::LBFPlacingCost;
+use crate::lbf_cost::{LBFPlacingCost, PlacementBias};
+use crate::samplers::infeasible_cells::{CellOutcome, InfeasibleCellCache};
 use crate::samplers::uniform_rect_sampler::UniformAARectSampler;
 
 /// Creates `Transformation` samples for a given item.
 /// Samples from the Hazard Proximity Grid uniformly, but only cells which could accommodate the item.
-/// Cells were a collision is guaranteed are discarded.
+/// Cells were a collision is guaranteed are discarded, as are cells which the [`InfeasibleCellCache`] deems too tight for the item.
 pub struct HPGSampler<'a> {
     pub item: &'a Item,
-    pub cell_samplers: Vec<UniformAARectSampler>,
+    /// Samplers for each eligible cell, together with the index of the cell in the grid
+    pub cell_samplers: Vec<(usize, UniformAARectSampler)>,
+    /// Outcome of the samples drawn so far, for each cell (by index in the grid)
+    pub cell_outcomes: HashMap<usize, CellOutcome>,
     pub cost_bound: LBFPlacingCost,
+    pub bias: PlacementBias,
+    pub bin_bbox: AARectangle,
     pub pretransform: Transformation,
     pub coverage_area: fsize,
     pub bin_bbox_area: fsize,
     pub n_samples: usize,
+    /// Index in the grid of the cell the last sample was drawn from
+    last_cell: Option<usize>,
 }
 
 impl<'a> HPGSampler<'a> {
-    pub fn new(item: &'a Item, layout: &Layout) -> Option<HPGSampler<'a>> {
+    pub fn new(
+        item: &'a Item,
+        layout: &Layout,
+        cell_cache: &InfeasibleCellCache,
+        bias: PlacementBias,
+    ) -> Option<HPGSampler<'a>> {
         let poi = &item.shape.poi;
         let bin_bbox = layout.bin.bbox();
 
@@ -35,22 +50,25 @@ impl<'a> HPGSampler<'a> {
         let pretransform = Transformation::from_translation((-poi.center.0, -poi.center.1));
 
         let hpg = layout.cde().haz_prox_grid().unwrap();
-        let all_cells = hpg.grid.cells.iter().flatten();
-        let eligible_cells = all_cells.filter(|c| c.could_accommodate_item(item));
+        let eligible_cells = hpg
+            .eligible_cells_indexed(poi.radius, item.base_quality)
+            .filter(|(i, c)| {
+                !cell_cache.is_infeasible(layout.id, item.id, *i, c, item.base_quality)
+            });
 
         //create samplers for all eligible cells
         let cell_samplers = eligible_cells
-            .filter_map(|c| {
+            .filter_map(|(i, c)| {
                 //map each eligible cell to a rectangle sampler, bounded by the layout's bbox.
                 //(at low densities, the cells could extend significantly beyond the layout's bbox)
-                AARectangle::from_intersection(&c.bbox, &bin_bbox)
+                AARectangle::from_intersection(&c.bbox, &bin_bbox).map(|bbox| (i, bbox))
             })
-            .map(|bbox| UniformAARectSampler::new(bbox, item))
+            .map(|(i, bbox)| (i, UniformAARectSampler::new(bbox, item)))
             .collect_vec();
 
-        let coverage_area = cell_samplers.iter().map(|s| s.bbox.area()).sum();
+        let coverage_area = cell_samplers.iter().map(|(_, s)| s.bbox.area()).sum();
 
-        let cost_bound = LBFPlacingCost::new(bin_bbox.x_max, bin_bbox.y_max);
+        let cost_bound = LBFPlacingCost::from_bbox(&bin_bbox, bias, &bin_bbox);
 
         match cell_samplers.is_empty() {
             true => {
@@ -66,22 +84,37 @@ impl<'a> HPGSampler<'a> {
                 Some(HPGSampler {
                     item,
                     cell_samplers,
+                    cell_outcomes: HashMap::new(),
                     cost_bound,
+                    bias,
                     pretransform,
                     coverage_area,
                     bin_bbox_area: bin_bbox.area(),
+                    bin_bbox,
                     n_samples: 0,
+                    last_cell: None,
                 })
             }
         }
     }
 
+    /// Restricts continuous rotations to the multiples of `step` (radians), if defined
+    pub fn with_rotation_snap(mut self, step: Option<fsize>) -> Self {
+        if let Some(step) = step {
+            for (_, cell_sampler) in self.cell_samplers.iter_mut() {
+                cell_sampler.uniform_r.snap(step);
+            }
+        }
+        self
+    }
+
     /// Samples a `Transformation`
     pub fn sample(&mut self, rng: &mut impl Rng) -> Transformation {
         self.n_samples += 1;
 
         //sample one of the eligible cells
-        let cell_sampler = self.cell_samplers.choose(rng).expect("no active samplers");
+        let (cell_idx, cell_sampler) = self.cell_samplers.choose(rng).expect("no active samplers");
+        self.last_cell = Some(*cell_idx);
 
         //from that cell, sample a transformation
         let sample = cell_sampler.sample(rng);
@@ -90,6 +123,36 @@ impl<'a> HPGSampler<'a> {
         self.pretransform.clone().transform_from_decomposed(&sample)
     }
 
+    /// Registers whether the last sampled `Transformation` collided with anything.
+    /// Samples which were never checked for collisions should not be registered.
+    pub fn register_outcome(&mut self, collides: bool) {
+        let cell_idx = self.last_cell.expect("no sample drawn yet");
+        let outcome = match (self.cell_outcomes.get(&cell_idx), collides) {
+            (Some(CellOutcome::Feasible), _) | (_, false) => CellOutcome::Feasible,
+            (Some(CellOutcome::Collisions(n)), true) => CellOutcome::Collisions(n + 1),
+            (None, true) => CellOutcome::Collisions(1),
+        };
+        self.cell_outcomes.insert(cell_idx, outcome);
+    }
+
+    /// Reports the outcomes of all sampled cells to the cache
+    pub fn update_cache(&self, layout: &Layout, cell_cache: &mut InfeasibleCellCache) {
+        let hpg = layout.cde().haz_prox_grid().unwrap();
+        for (&cell_idx, &outcome) in self.cell_outcomes.iter() {
+            let cell = hpg.grid.cells[cell_idx]
+                .as_ref()
+                .expect("sampled cell not in grid");
+            cell_cache.register(
+                layout.id,
+                self.item.id,
+                cell_idx,
+                cell,
+                self.item.base_quality,
+                outcome,
+            );
+        }
+    }
+
     /// Removes all cells that cannot possibly generate a `Transformation` which would be better than the current best solution.
     /// LBF specific
     pub fn tighten(&mut self, best: LBFPlacingCost) {
@@ -97,11 +160,13 @@ impl<'a> HPGSampler<'a> {
 
         if best < self.cost_bound {
             //remove all cells that are out of bounds, update the coverage area
-            self.cell_samplers.retain(|cell_sampler| {
+            self.cell_samplers.retain(|(_, cell_sampler)| {
                 //minimum cost that could be achieved by a cell
-                let min_cost = LBFPlacingCost::new(
-                    cell_sampler.bbox.x_min + poi_rad,
-                    cell_sampler.bbox.y_min + poi_rad,
+                let min_cost = LBFPlacingCost::lower_bound(
+                    &cell_sampler.bbox,
+                    poi_rad,
+                    self.bias,
+                    &self.bin_bbox,
                 );
 
                 match min_cost < best {
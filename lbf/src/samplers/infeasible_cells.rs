@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use jagua_rs::fsize;
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
+use jagua_rs::prelude::HPGCell;
+
+/// Remembers which cells of the Hazard Proximity Grid repeatedly failed to produce a collision-free sample for an item,
+/// so they can be skipped when the same item is sampled in the same layout again.
+/// <br>
+/// An entry is only valid as long as the cell itself (bounding box) and its proximity are unchanged.
+/// Placements far away leave both untouched, while a placement (or removal) which changes the closest hazard of the cell invalidates the entry.
+#[derive(Debug, Clone)]
+pub struct InfeasibleCellCache {
+    /// Number of colliding samples after which a cell is considered infeasible.
+    /// If undefined, the cache is disabled and no cell is ever considered infeasible
+    pub threshold: Option<usize>,
+    entries: HashMap<CellKey, CellEntry>,
+}
+
+/// (layout id, item id, index of the cell in the grid)
+type CellKey = (usize, usize, usize);
+
+#[derive(Debug, Clone)]
+struct CellEntry {
+    bbox: AARectangle,
+    proximity: fsize,
+    n_collisions: usize,
+}
+
+impl InfeasibleCellCache {
+    pub fn new(threshold: Option<usize>) -> Self {
+        Self {
+            threshold,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Whether the cell has been proven too tight for the item, and was not affected by any changes to the layout since
+    pub fn is_infeasible(
+        &self,
+        layout_id: usize,
+        item_id: usize,
+        cell_idx: usize,
+        cell: &HPGCell,
+        quality: Option<usize>,
+    ) -> bool {
+        match (
+            self.threshold,
+            self.entries.get(&(layout_id, item_id, cell_idx)),
+        ) {
+            (Some(threshold), Some(entry)) => {
+                entry.n_collisions >= threshold && entry.is_valid_for(cell, quality)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.threshold.is_some()
+    }
+
+    /// Registers the outcome of the samples drawn from a cell
+    pub fn register(
+        &mut self,
+        layout_id: usize,
+        item_id: usize,
+        cell_idx: usize,
+        cell: &HPGCell,
+        quality: Option<usize>,
+        outcome: CellOutcome,
+    ) {
+        if self.threshold.is_none() {
+            return;
+        }
+        let key = (layout_id, item_id, cell_idx);
+        match outcome {
+            CellOutcome::Feasible => {
+                self.entries.remove(&key);
+            }
+            CellOutcome::Collisions(n) => {
+                let entry = self.entries.entry(key).or_insert_with(|| CellEntry {
+                    bbox: cell.bbox.clone(),
+                    proximity: cell.hazard_proximity(quality),
+                    n_collisions: 0,
+                });
+                if !entry.is_valid_for(cell, quality) {
+                    //the cell changed since its last registration, start counting again
+                    entry.bbox = cell.bbox.clone();
+                    entry.proximity = cell.hazard_proximity(quality);
+                    entry.n_collisions = 0;
+                }
+                entry.n_collisions += n;
+            }
+        }
+    }
+
+    /// Number of cells currently considered infeasible, including ones which have since been invalidated
+    pub fn n_infeasible(&self) -> usize {
+        match self.threshold {
+            Some(threshold) => self
+                .entries
+                .values()
+                .filter(|e| e.n_collisions >= threshold)
+                .count(),
+            None => 0,
+        }
+    }
+}
+
+impl CellEntry {
+    fn is_valid_for(&self, cell: &HPGCell, quality: Option<usize>) -> bool {
+        self.bbox == cell.bbox && self.proximity == cell.hazard_proximity(quality)
+    }
+}
+
+/// Outcome of all samples drawn from a single cell during one search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellOutcome {
+    /// At least one sample was collision-free
+    Feasible,
+    /// All samples which were checked collided
+    Collisions(usize),
+}
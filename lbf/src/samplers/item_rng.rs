@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use rand::prelude::SmallRng;
+use rand::SeedableRng;
+
+/// Independent PRNG streams per item, derived from a base seed and the id of the item.
+/// The samples drawn for an item do not depend on how many samples were drawn for the other items,
+/// so adding an item to an instance leaves the random streams of all other items unchanged.
+#[derive(Debug, Clone)]
+pub struct ItemRngs {
+    seed: u64,
+    streams: HashMap<usize, SmallRng>,
+}
+
+impl ItemRngs {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Stream of the item, created on first use
+    pub fn stream(&mut self, item_id: usize) -> &mut SmallRng {
+        let seed = self.seed;
+        self.streams
+            .entry(item_id)
+            .or_insert_with(|| SmallRng::seed_from_u64(derive_seed(seed, item_id)))
+    }
+}
+
+/// Mixes the id of an item into a seed (SplitMix64), so the streams of consecutive ids are uncorrelated
+pub fn derive_seed(seed: u64, item_id: usize) -> u64 {
+    let mut z = seed ^ (item_id as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -62,6 +62,14 @@ impl LSSampler {
         Self::new(item, ref_transform, sd_transl_range, SD_ROT)
     }
 
+    /// Restricts continuous rotations to the multiples of `step` (radians), if defined
+    pub fn with_rotation_snap(mut self, step: Option<fsize>) -> Self {
+        if let Some(step) = step {
+            self.normal_r.snap(step);
+        }
+        self
+    }
+
     /// Shifts the mean of the normal distributions to the given reference transformation.
     pub fn shift_mean(&mut self, ref_transform: &DTransformation) {
         self.normal_x = Normal::new(ref_transform.translation().0, self.sd_transl).unwrap();
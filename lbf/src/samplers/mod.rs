@@ -1,4 +1,9 @@
+pub mod coarse_to_fine;
+pub mod explicit_sampler;
 pub mod hpg_sampler;
+pub mod infeasible_cells;
+pub mod item_rng;
 pub mod ls_sampler;
 pub mod rotation_distr;
+pub mod sample_trace;
 pub mod uniform_rect_sampler;
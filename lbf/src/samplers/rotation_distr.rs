@@ -16,6 +16,8 @@ pub trait RotationSampler {
 /// Samples a rotation from a uniform distribution over a given range or a discrete set of rotations.
 pub enum UniformRotDistr {
     Range(Uniform<fsize>),
+    /// Multiples of an angle (radians), the number of multiples is sampled uniformly
+    Grid(Uniform<u32>, fsize),
     Discrete(Vec<fsize>),
     None,
 }
@@ -24,6 +26,8 @@ pub enum UniformRotDistr {
 /// In case of discrete rotations the mean is always returned.
 pub enum NormalRotDistr {
     Range(Normal<fsize>),
+    /// Samples of the normal distribution, snapped to the nearest multiple of an angle (radians)
+    Grid(Normal<fsize>, fsize),
     Discrete(fsize),
     None,
 }
@@ -34,6 +38,17 @@ impl UniformRotDistr {
             AllowedRotation::None => UniformRotDistr::None,
             AllowedRotation::Continuous => UniformRotDistr::Range(Uniform::new(0.0, 2.0 * PI)),
             AllowedRotation::Discrete(a_o) => UniformRotDistr::Discrete(a_o.clone()),
+            AllowedRotation::Explicit(transfs) => {
+                UniformRotDistr::Discrete(transfs.iter().map(|t| t.rotation()).collect())
+            }
+        }
+    }
+
+    /// Restricts a continuous range of rotations to the multiples of `step` (radians)
+    pub fn snap(&mut self, step: fsize) {
+        if let UniformRotDistr::Range(_) = self {
+            let n_steps = (2.0 * PI / step).round() as u32;
+            *self = UniformRotDistr::Grid(Uniform::new(0, n_steps.max(1)), step);
         }
     }
 
@@ -41,6 +56,7 @@ impl UniformRotDistr {
         match self {
             UniformRotDistr::None => 0.0,
             UniformRotDistr::Range(u) => u.sample(rng),
+            UniformRotDistr::Grid(u, step) => u.sample(rng) as fsize * step,
             UniformRotDistr::Discrete(a_o) => *a_o.choose(rng).unwrap(),
         }
     }
@@ -53,13 +69,22 @@ impl NormalRotDistr {
             AllowedRotation::Continuous => {
                 NormalRotDistr::Range(Normal::new(r_ref, stddev).unwrap())
             }
-            AllowedRotation::Discrete(_) => NormalRotDistr::Discrete(r_ref),
+            AllowedRotation::Discrete(_) | AllowedRotation::Explicit(_) => {
+                NormalRotDistr::Discrete(r_ref)
+            }
+        }
+    }
+
+    /// Restricts a continuous range of rotations to the multiples of `step` (radians)
+    pub fn snap(&mut self, step: fsize) {
+        if let NormalRotDistr::Range(n) = self {
+            *self = NormalRotDistr::Grid(*n, step);
         }
     }
 
     pub fn set_mean(&mut self, mean: fsize) {
         match self {
-            NormalRotDistr::Range(n) => {
+            NormalRotDistr::Range(n) | NormalRotDistr::Grid(n, _) => {
                 *n = Normal::new(mean, n.std_dev()).unwrap();
             }
             NormalRotDistr::Discrete(_) | NormalRotDistr::None => {}
@@ -68,7 +93,7 @@ impl NormalRotDistr {
 
     pub fn set_stddev(&mut self, stddev: fsize) {
         match self {
-            NormalRotDistr::Range(n) => {
+            NormalRotDistr::Range(n) | NormalRotDistr::Grid(n, _) => {
                 *n = Normal::new(n.mean(), stddev).unwrap();
             }
             NormalRotDistr::Discrete(_) | NormalRotDistr::None => {}
@@ -79,7 +104,13 @@ impl NormalRotDistr {
         match self {
             NormalRotDistr::None => 0.0,
             NormalRotDistr::Range(n) => n.sample(rng),
+            NormalRotDistr::Grid(n, step) => snap_rotation(n.sample(rng), *step),
             NormalRotDistr::Discrete(r) => *r,
         }
     }
 }
+
+/// Rounds a rotation to the nearest multiple of `step` (both in radians)
+pub fn snap_rotation(rotation: fsize, step: fsize) -> fsize {
+    (rotation / step).round() * step
+}
@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use jagua_rs::geometry::primitives::point::Point;
+use jagua_rs::geometry::transformation::Transformation;
+
+/// Whether sampled placements are being recorded, disabled by default
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// All samples recorded since the last [`take`], over all runs of the process
+static RECORDS: Mutex<Vec<SampleRecord>> = Mutex::new(vec![]);
+
+/// What happened to a sampled placement
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SampleOutcome {
+    /// The surrogate of the item collides
+    SurrogateRejected,
+    /// The placement could not improve on the best ones found so far, so it was not checked for collisions
+    Skipped,
+    /// The surrogate is clear, but the item itself collides
+    PolyRejected,
+    /// The placement is feasible and improves on the best ones found so far
+    Valid,
+}
+
+impl SampleOutcome {
+    pub const ALL: [SampleOutcome; 4] = [
+        SampleOutcome::SurrogateRejected,
+        SampleOutcome::Skipped,
+        SampleOutcome::PolyRejected,
+        SampleOutcome::Valid,
+    ];
+
+    /// Outcome of a sample of which the surrogate is clear
+    pub fn evaluated(worth_testing: bool, valid: bool) -> Self {
+        match (worth_testing, valid) {
+            (false, _) => SampleOutcome::Skipped,
+            (true, false) => SampleOutcome::PolyRejected,
+            (true, true) => SampleOutcome::Valid,
+        }
+    }
+
+    /// Whether the sample was rejected by a collision
+    pub fn rejected(&self) -> bool {
+        matches!(
+            self,
+            SampleOutcome::SurrogateRejected | SampleOutcome::PolyRejected
+        )
+    }
+}
+
+/// A placement sampled by the uniform or local search sampler
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleRecord {
+    /// Id of the bin of the layout in which was sampled
+    pub bin_id: usize,
+    /// Position of the reference point of the item, in the internal coordinates of the bin
+    pub position: Point,
+    pub outcome: SampleOutcome,
+}
+
+/// Starts recording every sampled placement, to visualize where the samplers spend their effort (see [`heat_map`](crate::io::heat_map)).
+/// Recording locks a global buffer for every sample, and is meant for tuning only.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Removes and returns all recorded samples
+pub fn take() -> Vec<SampleRecord> {
+    std::mem::take(&mut *RECORDS.lock().unwrap())
+}
+
+#[inline(always)]
+pub(crate) fn record(bin_id: usize, transform: &Transformation, outcome: SampleOutcome) {
+    if is_enabled() {
+        let (x, y) = transform.decompose().translation();
+        RECORDS.lock().unwrap().push(SampleRecord {
+            bin_id,
+            position: Point(x, y),
+            outcome,
+        });
+    }
+}
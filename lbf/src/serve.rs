@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use rayon::ThreadPool;
+use serde::{Deserialize, Serialize};
+
+use crate::io::error::IoError;
+#[cfg(feature = "png")]
+use crate::io::png_export;
+use crate::lbf_run::{solve_json_in_pool, solve_zip_report};
+
+/// Largest request body that is accepted, instances with many DXF derived items can be a few megabytes
+pub const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// Longest request line or header line that is accepted
+pub const MAX_LINE_LENGTH: usize = 8 * 1024;
+
+/// Largest number of headers that is accepted in a request
+pub const MAX_HEADERS: usize = 100;
+
+/// Largest number of connections which are handled at the same time, further connections are refused with a 503
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// Time after which a connection of which nothing can be read or written is dropped
+pub const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time during which the unread part of a request is discarded after responding, see [`handle_connection`]
+const LINGER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Body of a solve request, the same as the one of the `/json` route of the GUI backend
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SolveRequest {
+    /// Config in JSON, the default config if empty
+    pub config: String,
+    /// Instance in JSON
+    pub input: String,
+}
+
+/// Shared by all connections of the server
+struct ServeState {
+    solution_folder: PathBuf,
+    thread_pool: Arc<ThreadPool>,
+    n_requests: AtomicUsize,
+    n_connections: AtomicUsize,
+}
+
+/// Serves the solver over HTTP on the listener, until the listener fails. Every connection is handled on its own thread,
+/// and serves a single request:
+/// - `POST /json` with a [`SolveRequest`] solves the instance, and responds with the paths of the SVGs of the layouts,
+///   of the JSON solution and of the thumbnails of the layouts (`[[svg, ...], [json], [thumbnail, ...]]`), as [`solve_json`](crate::lbf_run::solve_json) does.
+///   The files of every request are written to their own subfolder of the solution folder.
+/// - `POST /zip?config=<config>` with a zip archive of a JSON instance and the DXF and SVG files it references as body
+///   (see [`zip_instance`](crate::io::zip_instance::zip_instance)) does the same, with the (URL encoded) config in the query.
+/// - `GET /file?path=<path>` responds with the contents of a file in the solution folder.
+/// - `GET /png?path=<path>&width=<width>` responds with an SVG of the solution folder rasterized to a PNG image of `width` pixels wide
+///   ([`DEFAULT_PNG_WIDTH`](crate::io::png_export::DEFAULT_PNG_WIDTH) by default), for clients which cannot render SVG.
+///   Only available if built with the `png` feature.
+/// - `GET /health` responds with `ok`, once the server accepts connections.
+///
+/// All requests share the thread pool, so a single large instance cannot occupy every core.
+/// At most [`MAX_CONNECTIONS`] connections are handled at the same time, and connections which stall for [`IO_TIMEOUT`] are dropped.
+pub fn serve(
+    listener: TcpListener,
+    solution_folder: PathBuf,
+    thread_pool: Arc<ThreadPool>,
+) -> std::io::Result<()> {
+    fs::create_dir_all(&solution_folder)?;
+    let state = Arc::new(ServeState {
+        solution_folder: fs::canonicalize(&solution_folder)?,
+        thread_pool,
+        n_requests: AtomicUsize::new(0),
+        n_connections: AtomicUsize::new(0),
+    });
+    info!("[SERVE] listening on http://{}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                if let Err(err) = set_timeouts(&stream) {
+                    warn!("[SERVE] could not set timeouts of connection: {}", err);
+                    continue;
+                }
+                if state.n_connections.load(Ordering::Relaxed) >= MAX_CONNECTIONS {
+                    warn!(
+                        "[SERVE] refusing connection, {} already open",
+                        MAX_CONNECTIONS
+                    );
+                    let _ = write_response(&mut stream, &Response::text(503, "server busy"));
+                    continue;
+                }
+                state.n_connections.fetch_add(1, Ordering::Relaxed);
+                let state = Arc::clone(&state);
+                thread::spawn(move || {
+                    handle_connection(stream, &state);
+                    state.n_connections.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            Err(err) => warn!("[SERVE] could not accept connection: {}", err),
+        }
+    }
+    Ok(())
+}
+
+fn set_timeouts(stream: &TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))
+}
+
+/// A parsed HTTP request
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// A response to be written, always with `Connection: close`
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn new(status: u16, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    fn text(status: u16, text: impl Into<String>) -> Self {
+        Self::new(status, "text/plain; charset=utf-8", text.into())
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, state: &ServeState) {
+    let response = match read_request(&mut stream) {
+        Ok(request) => {
+            info!("[SERVE] {} {}", request.method, request.path);
+            //a panic while solving only fails its own request
+            panic::catch_unwind(AssertUnwindSafe(|| route(request, state))).unwrap_or_else(|_| {
+                error!("[SERVE] request panicked");
+                Response::text(500, "internal error")
+            })
+        }
+        Err(response) => response,
+    };
+    if let Err(err) = write_response(&mut stream, &response) {
+        warn!("[SERVE] could not write response: {}", err);
+    }
+    //closing with unread data resets the connection, which can discard the response before the client read it
+    let _ = stream.shutdown(Shutdown::Write);
+    let _ = stream.set_read_timeout(Some(LINGER_TIMEOUT));
+    let _ = std::io::copy(
+        &mut (&stream).take(MAX_LINE_LENGTH as u64),
+        &mut std::io::sink(),
+    );
+}
+
+fn route(request: Request, state: &ServeState) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => Response::text(200, "ok"),
+        ("POST", "/json") => solve(&request.body, state),
+        ("POST", "/zip") => {
+            let config = request.query.get("config").cloned().unwrap_or_default();
+            solve_zip(config, &request.body, state)
+        }
+        ("GET", "/file") => match request.query.get("path") {
+            Some(path) => file(Path::new(path), state),
+            None => Response::text(400, "missing path"),
+        },
+        #[cfg(feature = "png")]
+        ("GET", "/png") => match request.query.get("path") {
+            Some(path) => png(Path::new(path), request.query.get("width"), state),
+            None => Response::text(400, "missing path"),
+        },
+        (_, "/health" | "/json" | "/zip" | "/file" | "/png") => {
+            Response::text(405, "method not allowed")
+        }
+        _ => Response::text(404, "not found"),
+    }
+}
+
+fn solve(body: &[u8], state: &ServeState) -> Response {
+    let request: SolveRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(err) => return Response::text(400, format!("invalid request: {}", err)),
+    };
+    if request.input.is_empty() {
+        return Response::text(400, "JSON cannot be empty");
+    }
+
+    solve_in_request_folder(state, |path_sol, thread_pool| {
+        solve_json_in_pool(request.config, request.input, path_sol, thread_pool)
+    })
+}
+
+fn solve_zip(config: String, archive: &[u8], state: &ServeState) -> Response {
+    if archive.is_empty() {
+        return Response::text(400, "archive cannot be empty");
+    }
+    solve_in_request_folder(state, |path_sol, thread_pool| {
+        solve_zip_report(config, archive, path_sol, thread_pool).map(|report| report.files)
+    })
+}
+
+/// Solves in a new subfolder of the solution folder, and responds with the paths of the written files
+fn solve_in_request_folder(
+    state: &ServeState,
+    solve: impl FnOnce(String, Option<Arc<ThreadPool>>) -> Result<Vec<Vec<String>>, IoError>,
+) -> Response {
+    let request_id = state.n_requests.fetch_add(1, Ordering::Relaxed);
+    let request_folder = state.solution_folder.join(request_id.to_string());
+    if let Err(err) = fs::create_dir_all(&request_folder) {
+        error!(
+            "[SERVE] could not create {}: {}",
+            request_folder.display(),
+            err
+        );
+        return Response::text(500, "could not create solution folder");
+    }
+    let path_sol = format!("{}/", request_folder.display());
+
+    match solve(path_sol, Some(Arc::clone(&state.thread_pool))) {
+        Ok(files) if files[0].is_empty() => Response::text(422, "No solution found."),
+        Ok(files) => Response::new(
+            200,
+            "application/json",
+            serde_json::to_vec(&files).expect("could not serialize paths"),
+        ),
+        Err(err) => Response::text(400, err.to_string()),
+    }
+}
+
+fn file(path: &Path, state: &ServeState) -> Response {
+    //only the files written by the server are served
+    match fs::canonicalize(path) {
+        Ok(path) if path.starts_with(&state.solution_folder) && path.is_file() => {
+            let content_type = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("svg") => "image/svg+xml",
+                Some("json") => "application/json",
+                Some("png") => "image/png",
+                _ => "application/octet-stream",
+            };
+            match fs::read(&path) {
+                Ok(contents) => Response::new(200, content_type, contents),
+                Err(_) => Response::text(500, "could not read file"),
+            }
+        }
+        _ => Response::text(404, "not found"),
+    }
+}
+
+/// Rasterizes an SVG of the solution folder
+#[cfg(feature = "png")]
+fn png(path: &Path, width: Option<&String>, state: &ServeState) -> Response {
+    let width = match width.map(|w| w.parse()) {
+        None => png_export::DEFAULT_PNG_WIDTH,
+        Some(Ok(width)) => width,
+        Some(Err(_)) => return Response::text(400, "invalid width"),
+    };
+    match fs::canonicalize(path) {
+        Ok(path)
+            if path.starts_with(&state.solution_folder)
+                && path.is_file()
+                && path.extension().is_some_and(|ext| ext == "svg") =>
+        {
+            let svg = match fs::read(&path) {
+                Ok(svg) => svg,
+                Err(_) => return Response::text(500, "could not read file"),
+            };
+            match png_export::svg_to_png(&svg, width) {
+                Ok(png) => Response::new(200, "image/png", png),
+                Err(err) => Response::text(400, err),
+            }
+        }
+        _ => Response::text(404, "not found"),
+    }
+}
+
+/// Reads the request line, headers and body of a request, or the response to an invalid one
+fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    let bad_request = |reason: &str| Response::text(400, reason);
+    let mut reader = BufReader::new(stream);
+
+    let request_line = read_line(&mut reader)
+        .map_err(|_| bad_request("could not read request"))?
+        .ok_or_else(|| Response::text(414, "request line too long"))?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err(bad_request("invalid request line")),
+    };
+
+    let mut content_length = 0;
+    for n_headers in 0.. {
+        let header = read_line(&mut reader)
+            .map_err(|_| bad_request("could not read headers"))?
+            .ok_or_else(|| Response::text(431, "header too long"))?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if n_headers == MAX_HEADERS {
+            return Err(Response::text(431, "too many headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| bad_request("invalid Content-Length"))?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(Response::text(413, "request body too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad_request("could not read body"))?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect();
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query,
+        body,
+    })
+}
+
+/// Reads a line of at most [`MAX_LINE_LENGTH`] bytes, `None` if the line is longer
+fn read_line(reader: &mut impl BufRead) -> std::io::Result<Option<String>> {
+    let mut line = String::new();
+    reader
+        .take(MAX_LINE_LENGTH as u64 + 1)
+        .read_line(&mut line)?;
+    match line.len() > MAX_LINE_LENGTH {
+        true => Ok(None),
+        false => Ok(Some(line)),
+    }
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+/// Decodes the `%XX` escapes and `+` (space) of a query component
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => match s.get(i + 1..i + 3).map(|hex| u8::from_str_radix(hex, 16)) {
+                Some(Ok(byte)) => {
+                    decoded.push(byte);
+                    i += 3;
+                    continue;
+                }
+                _ => decoded.push(b'%'),
+            },
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,105 @@
+use itertools::Itertools;
+use rand::Rng;
+
+use jagua_rs::collision_detection::hazard_filter;
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::placing_option::PlacingOption;
+use jagua_rs::entities::problems::problem::Problem;
+use jagua_rs::entities::problems::problem_generic::{LayoutIndex, ProblemGeneric};
+use jagua_rs::geometry::geo_traits::TransformableFrom;
+use jagua_rs::geometry::transformation::Transformation;
+
+use crate::lbf_cost::{LBFPlacingCost, PlacementBias};
+use crate::samplers::explicit_sampler;
+use crate::samplers::hpg_sampler::HPGSampler;
+use crate::samplers::infeasible_cells::InfeasibleCellCache;
+
+/// A feasible placement offered to the user of an interactive editor, e.g. as a "snap here" target while dragging an item.
+#[derive(Clone, Debug)]
+pub struct PlacementSuggestion {
+    pub placing_option: PlacingOption,
+    /// Score of the placement, lower is better
+    pub cost: LBFPlacingCost,
+}
+
+/// Returns up to `k` feasible placements of `item` in a layout, best first.
+/// <br>
+/// `n_samples` transformations are drawn from the hazard proximity grid (or all allowed transformations are tried for
+/// items with a list of explicit transformations) and checked in a single batch.
+/// Unlike the LBF search, samples are not pruned by the best cost found so far, since the runner-ups are also of interest.
+/// To avoid offering `k` variations of the same spot, a suggestion is only kept if its translation is further
+/// than the radius of the item's pole of inaccessibility from all better ones.
+/// <br>
+/// Pending changes to the layout have to be flushed (see [`ProblemGeneric::flush_changes`]) beforehand.
+pub fn suggest_placements(
+    problem: &Problem,
+    layout_idx: LayoutIndex,
+    item: &Item,
+    k: usize,
+    n_samples: usize,
+    bias: PlacementBias,
+    rng: &mut impl Rng,
+) -> Vec<PlacementSuggestion> {
+    let layout = problem.get_layout(layout_idx);
+    let cde = layout.cde();
+    assert!(
+        cde.haz_prox_grid().is_ok(),
+        "layout has unflushed changes, flush the problem before requesting suggestions"
+    );
+    let irrel_hazards = match item.hazard_filter.as_ref() {
+        None => vec![],
+        Some(hf) => hazard_filter::generate_irrelevant_hazards(hf, cde.all_hazards()),
+    };
+
+    let transforms: Vec<Transformation> =
+        match explicit_sampler::explicit_transformations(item, &layout.bin) {
+            Some(d_transfs) => d_transfs.iter().map(|dt| dt.compose()).collect(),
+            None => {
+                let cell_cache = InfeasibleCellCache::new(None);
+                match HPGSampler::new(item, layout, &cell_cache, bias) {
+                    Some(mut sampler) => (0..n_samples).map(|_| sampler.sample(rng)).collect(),
+                    None => vec![],
+                }
+            }
+        };
+
+    let collides = cde.collides_any(&item.shape, &transforms, &irrel_hazards);
+
+    let bin_bbox = layout.bin.bbox();
+    let mut buffer = (*item.shape).clone();
+    buffer.surrogate = None;
+    let feasible = transforms
+        .iter()
+        .zip(collides.iter().by_vals())
+        .filter(|(_, collides)| !collides)
+        .map(|(transform, _)| {
+            buffer.transform_from(&item.shape, transform);
+            let cost = LBFPlacingCost::from_shape(&buffer, bias, &bin_bbox);
+            (transform.decompose(), cost)
+        })
+        .sorted_by_key(|(_, cost)| *cost);
+
+    let min_separation = item.shape.poi.radius;
+    let mut suggestions: Vec<PlacementSuggestion> = vec![];
+    for (d_transf, cost) in feasible {
+        if suggestions.len() >= k {
+            break;
+        }
+        let (x, y) = d_transf.translation();
+        let distinct = suggestions.iter().all(|s| {
+            let (sx, sy) = s.placing_option.d_transf.translation();
+            (x - sx).hypot(y - sy) > min_separation
+        });
+        if distinct {
+            suggestions.push(PlacementSuggestion {
+                placing_option: PlacingOption {
+                    layout_idx,
+                    item_id: item.id,
+                    d_transf,
+                },
+                cost,
+            });
+        }
+    }
+    suggestions
+}
@@ -0,0 +1,207 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use itertools::Itertools;
+use log::info;
+use rand::prelude::SmallRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::fsize;
+use jagua_rs::io::json_instance::JsonInstance;
+use jagua_rs::io::parser::Parser;
+
+use crate::io::csv_export::escape;
+use crate::io::error::IoError;
+use crate::lbf_config::LBFConfig;
+use crate::lbf_runs;
+use crate::reference;
+
+/// Values of the parameters of a sweep, keyed by the path of the parameter in the config.
+/// Nested parameters are separated by dots, e.g. `{"n_samples": [1000, 5000], "prng_seed": [0, 1, 2], "cde_config.quadtree_depth": [4, 5]}`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct SweepParameters(pub BTreeMap<String, Vec<Value>>);
+
+/// Outcome of a single run of a sweep
+#[derive(Clone, Debug)]
+pub struct SweepResult {
+    pub instance: String,
+    /// Value of every parameter, in the order of [`SweepParameters`]
+    pub values: Vec<Value>,
+    pub usage: fsize,
+    pub n_placed: usize,
+    pub n_demanded: usize,
+    /// Width of the strip or total cost of the used bins, see [`reference::solution_value`]
+    pub value: fsize,
+    pub run_time_ms: u128,
+}
+
+/// All configs of the cross-product of the parameters, applied to the base config, together with the values of the parameters
+pub fn sweep_configs(
+    base: &LBFConfig,
+    parameters: &SweepParameters,
+) -> Result<Vec<(Vec<Value>, LBFConfig)>, String> {
+    let base_json = serde_json::to_value(base).expect("could not serialize config");
+    let value_lists = parameters.0.values().map(|values| values.iter());
+    match parameters.0.is_empty() {
+        true => Ok(vec![(vec![], *base)]),
+        false => value_lists
+            .multi_cartesian_product()
+            .map(|values| {
+                let mut config_json = base_json.clone();
+                for (path, value) in parameters.0.keys().zip(values.iter()) {
+                    set_parameter(&mut config_json, path, (*value).clone())?;
+                }
+                let config: LBFConfig = serde_json::from_value(config_json)
+                    .map_err(|err| format!("invalid config: {}", err))?;
+                //unknown parameters are silently ignored on deserialization, so check that every value was applied
+                let applied = serde_json::to_value(config).expect("could not serialize config");
+                for (path, value) in parameters.0.keys().zip(values.iter()) {
+                    let found = path.split('.').try_fold(&applied, |v, key| v.get(key));
+                    if !found.is_some_and(|found| same_value(found, value)) {
+                        return Err(format!("{} is not a parameter of the config", path));
+                    }
+                }
+                Ok((values.into_iter().cloned().collect(), config))
+            })
+            .collect(),
+    }
+}
+
+/// Solves the instance with every config of the sweep, see [`sweep_configs`].
+/// The instance is parsed once for every distinct combination of the parameters which affect parsing.
+/// If `parallel`, the runs are distributed over the global rayon pool. Their run times then include contention for the cores.
+/// Every run uses the PRNG seed of its config, or entropy if it has none.
+/// Fails if the instance cannot be parsed or solved with one of the configs
+pub fn run_sweep(
+    json_instance: &JsonInstance,
+    assets_folder: PathBuf,
+    configs: &[(Vec<Value>, LBFConfig)],
+    parallel: bool,
+) -> Result<Vec<SweepResult>, IoError> {
+    let mut instances: HashMap<String, Instance> = HashMap::new();
+    for (_, config) in configs {
+        if let Entry::Vacant(entry) = instances.entry(parse_key(config)) {
+            let instance = Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                assets_folder.clone(),
+                config.fidelity(),
+            )
+            .with_rotated_bins(config.rotated_bins)
+            .with_part_in_part_forbidden(config.forbid_part_in_part)
+            .parse(json_instance)?;
+            entry.insert(instance);
+        }
+    }
+
+    let run = |(i, (values, config)): (usize, &(Vec<Value>, LBFConfig))| {
+        let instance = &instances[&parse_key(config)];
+        let rng = match config.prng_seed {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => SmallRng::from_entropy(),
+        };
+        let start = Instant::now();
+        let solution = lbf_runs::solve_best(instance, *config, rng)?;
+        let result = SweepResult {
+            instance: json_instance.name.clone(),
+            values: values.clone(),
+            usage: solution.usage,
+            n_placed: solution.placed_item_qtys.iter().sum(),
+            n_demanded: instance.total_item_qty(),
+            value: reference::solution_value(instance, &solution),
+            run_time_ms: start.elapsed().as_millis(),
+        };
+        info!(
+            "[SWEEP] run {}/{} ({}): usage {:.3}%, value {:.3}, {}ms",
+            i + 1,
+            configs.len(),
+            values.iter().join(", "),
+            result.usage * 100.0,
+            result.value,
+            result.run_time_ms
+        );
+        Ok(result)
+    };
+    match parallel {
+        true => configs.par_iter().enumerate().map(run).collect(),
+        false => configs.iter().enumerate().map(run).collect(),
+    }
+}
+
+/// Table of the results of a sweep, one row per run, with a column per parameter followed by the outcome of the run
+pub fn results_csv(parameters: &SweepParameters, results: &[SweepResult]) -> String {
+    let header = std::iter::once("instance")
+        .chain(parameters.0.keys().map(String::as_str))
+        .chain(["usage", "n_placed", "n_demanded", "value", "run_time_ms"])
+        .map(escape)
+        .join(",");
+    let mut csv = format!("{header}\n");
+    for result in results {
+        let values = result.values.iter().map(|value| match value {
+            Value::String(s) => s.clone(),
+            _ => value.to_string(),
+        });
+        let outcome = [
+            result.usage.to_string(),
+            result.n_placed.to_string(),
+            result.n_demanded.to_string(),
+            result.value.to_string(),
+            result.run_time_ms.to_string(),
+        ];
+        let row = std::iter::once(result.instance.clone())
+            .chain(values)
+            .map(|field| escape(&field))
+            .chain(outcome)
+            .join(",");
+        writeln!(csv, "{row}").unwrap();
+    }
+    csv
+}
+
+/// Sets the parameter at the dot separated `path` of a serialized config, creating the objects on the path if necessary
+fn set_parameter(config_json: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let mut target = config_json;
+    for key in path.split('.') {
+        let object = match target {
+            Value::Null => {
+                *target = Value::Object(Default::default());
+                target.as_object_mut().unwrap()
+            }
+            Value::Object(object) => object,
+            _ => return Err(format!("{} is not a parameter of the config", path)),
+        };
+        target = object.entry(key).or_insert(Value::Null);
+    }
+    *target = value;
+    Ok(())
+}
+
+/// Whether a value survived the round trip through the config, up to the precision of `fsize`
+fn same_value(found: &Value, value: &Value) -> bool {
+    match (found.as_f64(), value.as_f64()) {
+        (Some(a), Some(b)) => (a - b).abs() <= 1e-6 * f64::max(1.0, b.abs()),
+        _ => found == value,
+    }
+}
+
+/// Parameters of the config which affect the parsed instance
+fn parse_key(config: &LBFConfig) -> String {
+    serde_json::to_string(&(
+        config.cde_config,
+        config.poly_simpl_tolerance,
+        config.geometric_fidelity,
+        config.rotated_bins,
+        config.forbid_part_in_part,
+    ))
+    .expect("could not serialize config")
+}
@@ -0,0 +1,432 @@
+use itertools::Itertools;
+use log::{info, warn};
+
+use jagua_rs::collision_detection::hazard::HazardEntity;
+use jagua_rs::collision_detection::hazard_filter;
+use jagua_rs::entities::bin::Bin;
+use jagua_rs::entities::instances::instance::Instance;
+use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+use jagua_rs::entities::item::Item;
+use jagua_rs::entities::layout::{Layout, USAGE_TOLERANCE};
+use jagua_rs::entities::placed_item::PItemKey;
+use jagua_rs::entities::solution::Solution;
+use jagua_rs::fsize;
+use jagua_rs::geometry::d_transformation::DTransformation;
+use jagua_rs::geometry::geo_enums::AllowedRotation;
+use jagua_rs::geometry::geo_traits::{Shape, Transformable};
+use jagua_rs::geometry::primitives::aa_rectangle::AARectangle;
+use jagua_rs::geometry::transformation::Transformation;
+use jagua_rs::io::json_solution::{JsonContainer, JsonLayout};
+use jagua_rs::io::parse_error::ParseError;
+use jagua_rs::io::parser;
+use jagua_rs::util::config::CDEConfig;
+use jagua_rs::util::validation::{self, PlacementViolation, ViolationKind};
+use jagua_rs::PI;
+
+/// Tolerance on rotations (in radians) and translations (relative to the extent of the item) when checking them against the allowed ones
+const TRANSFORMATION_TOLERANCE: fsize = 1e-4;
+
+/// Reason why a solution is not feasible.
+/// Layouts are identified by their index in the solution, placed items by their index in the layout
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// A placement refers to an item which is not part of the instance
+    UnknownItem { layout: usize, index: usize },
+    /// A layout refers to a bin which is not part of the instance
+    UnknownBin { layout: usize, index: usize },
+    /// A layout refers to a strip in a bin packing instance, or to a bin in a strip packing instance
+    WrongContainer { layout: usize },
+    /// More copies of an item are placed than demanded
+    ExcessItems {
+        item: usize,
+        placed: usize,
+        demand: usize,
+    },
+    /// More copies of a bin are used than in stock
+    ExcessBins {
+        bin: usize,
+        used: usize,
+        stock: usize,
+    },
+    /// An item is placed in a bin of another material
+    WrongMaterial { layout: usize, placed_item: usize },
+    /// The items cover more of the bin than its maximum usage
+    MaxUsageExceeded {
+        layout: usize,
+        usage: fsize,
+        max_usage: fsize,
+    },
+    /// An item is placed with a rotation (in degrees), or at a fixed transformation, which it does not allow
+    TransformationNotAllowed {
+        layout: usize,
+        placed_item: usize,
+        rotation: fsize,
+    },
+    /// An item collides with a hazard which was present in the layout when it was placed
+    Collision {
+        layout: usize,
+        placed_item: usize,
+        hazard: HazardEntity,
+    },
+}
+
+/// Outcome of verifying a solution against an instance
+#[derive(Clone, Debug)]
+pub struct VerificationReport {
+    pub violations: Vec<Violation>,
+    /// Usage of every layout of the solution
+    pub layout_usages: Vec<fsize>,
+    /// Total area of the placed items divided by the total area of the used containers
+    pub usage: fsize,
+    /// Total number of placed items
+    pub n_placed: usize,
+    /// Total number of items demanded by the instance
+    pub n_demanded: usize,
+}
+
+impl VerificationReport {
+    pub fn is_feasible(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Verifies a solution (possibly produced by another tool) against an instance, with the collision detection engine as referee.
+/// The items of every layout are placed one by one in the order of the solution, and every item is checked against the hazards
+/// present at that moment: the exterior and holes of the bin, inferior quality zones, active temporary zones and the items placed before it.
+/// Layouts with unknown items or containers are not checked for collisions.
+pub fn verify_solution(
+    instance: &Instance,
+    json_layouts: &[JsonLayout],
+    cde_config: CDEConfig,
+) -> VerificationReport {
+    let mut violations = vec![];
+    let mut layout_usages = vec![];
+    let (mut item_area, mut container_area) = (0.0, 0.0);
+
+    for (l_idx, json_layout) in json_layouts.iter().enumerate() {
+        let unknown_items = json_layout
+            .placed_items
+            .iter()
+            .filter(|jpi| jpi.index >= instance.items().len())
+            .map(|jpi| Violation::UnknownItem {
+                layout: l_idx,
+                index: jpi.index,
+            })
+            .collect_vec();
+        let bin = layout_bin(instance, json_layout, l_idx, cde_config);
+        let bin = match (bin, unknown_items.is_empty()) {
+            (Ok(bin), true) => bin,
+            (bin, _) => {
+                violations.extend(bin.err());
+                violations.extend(unknown_items);
+                layout_usages.push(0.0);
+                continue;
+            }
+        };
+
+        let layout_item_area = json_layout
+            .placed_items
+            .iter()
+            .map(|jpi| instance.item(jpi.index).shape.area())
+            .sum::<fsize>();
+        if let Some(max_usage) = bin.max_usage {
+            let usage = layout_item_area / bin.area;
+            if usage > max_usage + USAGE_TOLERANCE {
+                violations.push(Violation::MaxUsageExceeded {
+                    layout: l_idx,
+                    usage,
+                    max_usage,
+                });
+            }
+        }
+
+        let mut layout = Layout::new(l_idx, bin);
+        for (pi_idx, jpi) in json_layout.placed_items.iter().enumerate() {
+            let item = instance.item(jpi.index);
+            if !layout.bin.accepts(item) {
+                violations.push(Violation::WrongMaterial {
+                    layout: l_idx,
+                    placed_item: pi_idx,
+                });
+            }
+            //the translations of a solution are in the units of the instance
+            let (tx, ty) = jpi.transformation.translation;
+            let abs_transf = DTransformation::new(
+                jpi.transformation.rotation,
+                (tx * instance.unit_scale(), ty * instance.unit_scale()),
+            );
+            if !transformation_allowed(item, &abs_transf) {
+                violations.push(Violation::TransformationNotAllowed {
+                    layout: l_idx,
+                    placed_item: pi_idx,
+                    rotation: abs_transf.rotation().to_degrees(),
+                });
+            }
+            let transf = parser::absolute_to_internal_transform(
+                &abs_transf,
+                &item.pretransform,
+                &layout.bin.pretransform,
+            );
+            violations.extend(
+                collisions(&layout, item, &transf)
+                    .into_iter()
+                    .map(|hazard| Violation::Collision {
+                        layout: l_idx,
+                        placed_item: pi_idx,
+                        hazard,
+                    }),
+            );
+            //an exact duplicate of a placed item collides with it, but cannot be registered as a second hazard
+            let d_transf = transf.decompose();
+            let duplicate = layout
+                .placed_items()
+                .values()
+                .any(|pi| pi.item_id == item.id && pi.d_transf == d_transf);
+            if !duplicate {
+                layout.place_item(item, jpi.copy, d_transf);
+            }
+        }
+        item_area += layout_item_area;
+        container_area += layout.bin.area;
+        layout_usages.push(layout_item_area / layout.bin.area);
+    }
+
+    //demand and stock over the entire solution
+    for (item, demand) in instance.items() {
+        let placed = json_layouts
+            .iter()
+            .flat_map(|jl| jl.placed_items.iter())
+            .filter(|jpi| jpi.index == item.id)
+            .count();
+        if placed > *demand {
+            violations.push(Violation::ExcessItems {
+                item: item.id,
+                placed,
+                demand: *demand,
+            });
+        }
+    }
+    if let Instance::BP(bp_instance) = instance {
+        for (bin, stock) in &bp_instance.bins {
+            let used = json_layouts
+                .iter()
+                .filter(
+                    |jl| matches!(jl.container, JsonContainer::Bin { index } if index == bin.id),
+                )
+                .count();
+            if used > *stock {
+                violations.push(Violation::ExcessBins {
+                    bin: bin.id,
+                    used,
+                    stock: *stock,
+                });
+            }
+        }
+    }
+
+    VerificationReport {
+        violations,
+        layout_usages,
+        usage: item_area / fsize::max(container_area, fsize::EPSILON),
+        n_placed: json_layouts.iter().map(|jl| jl.placed_items.len()).sum(),
+        n_demanded: instance.total_item_qty(),
+    }
+}
+
+/// Validates the layouts of a solution as they were written: imports them into a `Solution`
+/// and checks every placed item with [`validation::validate_solution`].
+/// Fails if the layouts cannot be imported, e.g. because they refer to unknown items, see [`verify_solution`] for those
+pub fn validate_json_solution(
+    instance: &Instance,
+    json_layouts: &[JsonLayout],
+    cde_config: CDEConfig,
+) -> Result<(Solution, Vec<PlacementViolation>), ParseError> {
+    let solution = parser::build_solution_from_json(instance, json_layouts, cde_config)?;
+    let violations = validation::validate_solution(instance, &solution);
+    Ok((solution, violations))
+}
+
+pub fn log_placement_violations(solution: &Solution, violations: &[PlacementViolation]) {
+    for violation in violations {
+        warn!(
+            "[VALIDATE] {}",
+            describe_placement_violation(solution, violation)
+        );
+    }
+    info!(
+        "[VALIDATE] solution is {}: {} violations by {} placed items",
+        match violations.is_empty() {
+            true => "feasible",
+            false => "infeasible",
+        },
+        violations.len(),
+        violations
+            .iter()
+            .map(|v| (v.layout, v.placed_item))
+            .unique()
+            .count()
+    );
+}
+
+/// Placed items are referred to by their index in the layout, as in the JSON solution they were imported from
+pub fn describe_placement_violation(solution: &Solution, violation: &PlacementViolation) -> String {
+    let placed_items = &solution.layout_snapshots[violation.layout].placed_items;
+    let index = |pk: PItemKey| placed_items.keys().position(|k| k == pk).unwrap();
+    let placed_item = format!(
+        "placed item {} (item {})",
+        index(violation.placed_item),
+        violation.item_id
+    );
+    let what = match violation.kind {
+        ViolationKind::Overlap { other } => format!(
+            "overlaps placed item {} (item {})",
+            index(other),
+            placed_items[other].item_id
+        ),
+        ViolationKind::OutsideBin => "extends outside the bin".to_string(),
+        ViolationKind::BinHole { hole } => format!("overlaps hole {} of the bin", hole),
+        ViolationKind::QualityZone { quality } => {
+            format!("overlaps a zone of insufficient quality {}", quality)
+        }
+    };
+    format!("layout {}: {} {}", violation.layout, placed_item, what)
+}
+
+pub fn log_report(report: &VerificationReport) {
+    for violation in &report.violations {
+        warn!("[VERIFY] {}", describe_violation(violation));
+    }
+    for (i, usage) in report.layout_usages.iter().enumerate() {
+        info!("[VERIFY] layout {}: usage {:.3}%", i, usage * 100.0);
+    }
+    info!(
+        "[VERIFY] solution is {}: {}/{} items placed, usage {:.3}%, {} violations",
+        match report.is_feasible() {
+            true => "feasible",
+            false => "infeasible",
+        },
+        report.n_placed,
+        report.n_demanded,
+        report.usage * 100.0,
+        report.violations.len()
+    );
+}
+
+pub fn describe_violation(violation: &Violation) -> String {
+    match violation {
+        Violation::UnknownItem { layout, index } => {
+            format!("layout {}: item {} does not exist", layout, index)
+        }
+        Violation::UnknownBin { layout, index } => {
+            format!("layout {}: bin {} does not exist", layout, index)
+        }
+        Violation::WrongContainer { layout } => format!(
+            "layout {}: container does not match the type of the instance",
+            layout
+        ),
+        Violation::ExcessItems {
+            item,
+            placed,
+            demand,
+        } => format!(
+            "item {}: {} copies placed, but only {} demanded",
+            item, placed, demand
+        ),
+        Violation::ExcessBins { bin, used, stock } => format!(
+            "bin {}: {} copies used, but only {} in stock",
+            bin, used, stock
+        ),
+        Violation::WrongMaterial {
+            layout,
+            placed_item,
+        } => format!(
+            "layout {}: placed item {} is of another material than the bin",
+            layout, placed_item
+        ),
+        Violation::MaxUsageExceeded {
+            layout,
+            usage,
+            max_usage,
+        } => format!(
+            "layout {}: usage {:.3} exceeds the maximum usage {:.3} of the bin",
+            layout, usage, max_usage
+        ),
+        Violation::TransformationNotAllowed {
+            layout,
+            placed_item,
+            rotation,
+        } => format!(
+            "layout {}: placed item {} has a transformation (rotation {:.3}°) which its item does not allow",
+            layout, placed_item, rotation
+        ),
+        Violation::Collision {
+            layout,
+            placed_item,
+            hazard,
+        } => format!(
+            "layout {}: placed item {} collides with {:?}",
+            layout, placed_item, hazard
+        ),
+    }
+}
+
+fn layout_bin(
+    instance: &Instance,
+    json_layout: &JsonLayout,
+    l_idx: usize,
+    cde_config: CDEConfig,
+) -> Result<Bin, Violation> {
+    match (instance, &json_layout.container) {
+        (Instance::BP(bp_instance), JsonContainer::Bin { index }) => bp_instance
+            .bins
+            .get(*index)
+            .map(|(bin, _)| bin.clone())
+            .ok_or(Violation::UnknownBin {
+                layout: l_idx,
+                index: *index,
+            }),
+        (Instance::SP(sp_instance), JsonContainer::Strip { width, .. }) => {
+            let width = *width * instance.unit_scale();
+            let strip_rect = AARectangle::new(0.0, 0.0, width, sp_instance.strip_height);
+            Ok(Bin::from_strip(strip_rect, cde_config))
+        }
+        _ => Err(Violation::WrongContainer { layout: l_idx }),
+    }
+}
+
+/// All hazards of the layout with which the item collides at the (internal) transformation
+fn collisions(layout: &Layout, item: &Item, transf: &Transformation) -> Vec<HazardEntity> {
+    let shape = item.shape.transform_clone(transf);
+    let irrelevant_hazards = item.hazard_filter.as_ref().map_or(vec![], |f| {
+        hazard_filter::generate_irrelevant_hazards(f, layout.cde().all_hazards())
+    });
+    let mut detected = vec![];
+    layout
+        .cde()
+        .collect_poly_collisions(&shape, &irrelevant_hazards, &mut detected);
+    detected
+}
+
+fn transformation_allowed(item: &Item, abs_transf: &DTransformation) -> bool {
+    let rotation_matches = |r1: fsize, r2: fsize| {
+        let diff = (r1 - r2).rem_euclid(2.0 * PI);
+        fsize::min(diff, 2.0 * PI - diff) < TRANSFORMATION_TOLERANCE
+    };
+    match &item.allowed_rotation {
+        AllowedRotation::Continuous => true,
+        AllowedRotation::None => rotation_matches(abs_transf.rotation(), 0.0),
+        AllowedRotation::Discrete(rotations) => rotations
+            .iter()
+            .any(|r| rotation_matches(abs_transf.rotation(), *r)),
+        AllowedRotation::Explicit(transformations) => {
+            let bbox = item.shape.bbox();
+            let max_offset = TRANSFORMATION_TOLERANCE * fsize::max(bbox.width(), bbox.height());
+            transformations.iter().any(|t| {
+                let ((x1, y1), (x2, y2)) = (t.translation(), abs_transf.translation());
+                rotation_matches(t.rotation(), abs_transf.rotation())
+                    && (x1 - x2).abs() <= max_offset
+                    && (y1 - y2).abs() <= max_offset
+            })
+        }
+    }
+}
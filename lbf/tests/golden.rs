@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod golden {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use rand::prelude::SmallRng;
+    use rand::SeedableRng;
+    use test_case::test_case;
+
+    use jagua_rs::io::parser;
+    use jagua_rs::io::parser::Parser;
+    use lbf::io;
+    use lbf::io::json_output::JsonOutput;
+    use lbf::io::layout_to_svg::s_layout_to_svg;
+    use lbf::lbf_config::LBFConfig;
+    use lbf::lbf_optimizer::LBFOptimizer;
+    use lbf::EPOCH;
+
+    /// Directory containing the golden files
+    const GOLDEN_DIR: &str = "tests/golden";
+    /// Set this environment variable to overwrite the golden files with the current output
+    const UPDATE_ENV_VAR: &str = "UPDATE_GOLDEN";
+    /// Number of decimals to which all numbers are rounded before comparing
+    const N_DECIMALS: usize = 2;
+
+    #[test_case("../assets/albano.json"; "albano")]
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_golden_output(instance_path: &str) {
+        let instance_path = Path::new(instance_path);
+        let name = instance_path.file_stem().unwrap().to_str().unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            prng_seed: Some(0),
+            ..LBFConfig::default()
+        };
+
+        let json_instance = io::read_json_instance(Some(instance_path), None).unwrap();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        let mut json_output = JsonOutput {
+            instance: json_instance,
+            solution: parser::compose_json_solution(
+                &solution,
+                &instance,
+                *EPOCH,
+                config.clearance_threshold,
+                &config.objectives(),
+            ),
+            config,
+        };
+        //the run time is the only part of the output which is not deterministic
+        json_output.solution.run_time_sec = 0;
+
+        compare_to_golden(
+            &format!("sol_{}.json", name),
+            &serde_json::to_string_pretty(&json_output).unwrap(),
+        );
+        for (i, s_layout) in solution.layout_snapshots.iter().enumerate() {
+            let svg = s_layout_to_svg(s_layout, &instance, config.svg_draw_options);
+            compare_to_golden(&format!("sol_{}_{}.svg", name, i), &svg.to_string());
+        }
+    }
+
+    /// Compares the normalized output to the golden file with the same name.
+    /// If the `UPDATE_GOLDEN` environment variable is set, the golden file is overwritten instead.
+    fn compare_to_golden(file_name: &str, output: &str) {
+        let path = Path::new(GOLDEN_DIR).join(file_name);
+        let output = normalize(output);
+
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            fs::create_dir_all(GOLDEN_DIR).unwrap();
+            fs::write(&path, output).unwrap();
+            return;
+        }
+
+        let golden = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "golden file {:?} not found, run with {}=1 to create it",
+                path, UPDATE_ENV_VAR
+            )
+        });
+        let golden = normalize(&golden);
+        if let Some((line_idx, (g, o))) = golden
+            .lines()
+            .zip(output.lines())
+            .enumerate()
+            .find(|(_, (g, o))| g != o)
+        {
+            panic!(
+                "output differs from golden file {:?} at line {}:\n  golden: {}\n  output: {}\nrun with {}=1 to update the golden files",
+                path,
+                line_idx + 1,
+                g,
+                o,
+                UPDATE_ENV_VAR
+            );
+        }
+        assert_eq!(
+            golden.lines().count(),
+            output.lines().count(),
+            "output has a different number of lines than golden file {:?}",
+            path
+        );
+    }
+
+    /// Rounds all decimal numbers to `N_DECIMALS` and normalizes line endings,
+    /// so the comparison is robust against floating point differences between platforms.
+    fn normalize(text: &str) -> String {
+        let mut normalized = String::with_capacity(text.len());
+        let mut number = String::new();
+        let mut chars = text.replace("\r\n", "\n").chars().collect::<Vec<_>>();
+        chars.push('\n');
+
+        for c in chars {
+            let continues_number = c.is_ascii_digit()
+                || (c == '.' && !number.is_empty() && !number.contains('.'))
+                || (c == '-' && number.is_empty());
+            if continues_number {
+                number.push(c);
+                continue;
+            }
+            match number.parse::<f64>() {
+                Ok(value) if number.contains('.') => {
+                    let rounded = format!("{:.*}", N_DECIMALS, value);
+                    //avoid distinguishing between -0.00 and 0.00
+                    match rounded.trim_start_matches('-').trim_matches(['0', '.']) {
+                        "" => normalized.push_str(&format!("{:.*}", N_DECIMALS, 0.0)),
+                        _ => normalized.push_str(&rounded),
+                    }
+                }
+                _ => normalized.push_str(&number),
+            }
+            number.clear();
+            normalized.push(c);
+        }
+        normalized.pop();
+        normalized
+    }
+}
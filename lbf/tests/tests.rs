@@ -1,19 +1,83 @@
 #[cfg(test)]
 mod tests {
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
 
+    use itertools::Itertools;
     use rand::prelude::IteratorRandom;
     use rand::prelude::SmallRng;
     use rand::{Rng, SeedableRng};
     use test_case::test_case;
 
+    use jagua_rs::collision_detection::cd_trace::{self, CDStage};
+    use jagua_rs::collision_detection::hazard::HazardEntity;
+    use jagua_rs::entities::instances::instance::Instance;
+    use jagua_rs::entities::instances::instance_generic::InstanceGeneric;
+    use jagua_rs::entities::item::Item;
+    use jagua_rs::entities::layout::Layout;
     use jagua_rs::entities::problems::problem_generic::LayoutIndex;
     use jagua_rs::entities::problems::problem_generic::ProblemGeneric;
-    use jagua_rs::io::parser::Parser;
+    use jagua_rs::fsize;
+    use jagua_rs::geometry::convex_hull::convex_hull_from_points;
+    use jagua_rs::geometry::d_transformation::DTransformation;
+    use jagua_rs::geometry::geo_traits::{CollidesWith, DistanceFrom, Shape, Transformable};
+    use jagua_rs::geometry::primitives::point::Point;
+    use jagua_rs::geometry::primitives::simple_polygon::SimplePolygon;
+    use jagua_rs::io::asset_paths::AssetPaths;
+    use jagua_rs::io::dxf_export::{self, DxfLayering};
+    use jagua_rs::io::dxf_layers::DxfLayerMapping;
+    use jagua_rs::io::dxf_metadata::DxfItemMetadata;
+    use jagua_rs::io::esicup::esicup_instance;
+    use jagua_rs::io::gcode::{self, GcodeConfig, TravelOptimization};
+    use jagua_rs::io::geojson;
+    use jagua_rs::io::json_instance::{
+        JsonInstance, JsonItem, JsonItemCopy, JsonPoly, JsonQualityZone, JsonShape, JsonSimplePoly,
+        JsonStrip, JsonSurrogateConfig, JsonTemporaryZone,
+    };
+    use jagua_rs::io::json_solution::{JsonContainer, JsonSolution, JsonTransformation};
+    use jagua_rs::io::parse_error::ParseError;
+    use jagua_rs::io::parser::{self, MissingAssetPolicy, Parser};
+    use jagua_rs::io::shape_cache::ShapeCache;
+    use jagua_rs::io::wkt;
+    use jagua_rs::util::config::{CDEConfig, GeometricFidelity, Preset, StripGrowth};
+    use jagua_rs::util::objectives::ObjectiveWeights;
     use jagua_rs::util::polygon_simplification::PolySimplConfig;
+    use jagua_rs::util::validation::{self, ViolationKind};
+    use jagua_rs::util::web_width;
+    use jagua_rs::PI;
+    use lbf::bin_assignment::BinAssignmentConfig;
+    use lbf::calibration::{self, CalibrationConfig};
+    use lbf::clusters;
+    use lbf::cut_order::{self, CutOrderConfig};
     use lbf::io;
+    use lbf::io::csv_export;
+    use lbf::io::dxf_folder;
+    use lbf::io::dxf_inspect;
+    use lbf::io::error::IoError;
+    use lbf::io::heat_map::{self, HeatMap};
+    use lbf::io::json_output::{self, JsonFormat, JsonOutput};
+    use lbf::io::label_export;
+    use lbf::io::layout_to_svg::{s_layout_to_svg, s_layout_to_svg_lod};
+    use lbf::io::placements;
+    use lbf::io::repro;
+    use lbf::io::run_stats::{RunStats, STATS_CSV_HEADER};
+    use lbf::io::simplification_preview;
+    use lbf::io::svg_export;
+    use lbf::io::svg_util::LevelOfDetail;
+    use lbf::io::zip_instance;
     use lbf::lbf_config::LBFConfig;
+    use lbf::lbf_cost::{Gravity, PackingDirection};
     use lbf::lbf_optimizer::LBFOptimizer;
+    use lbf::lbf_run;
+    use lbf::lbf_runs;
+    use lbf::reference;
+    use lbf::samplers::coarse_to_fine::CoarseToFineConfig;
+    use lbf::samplers::rotation_distr;
+    use lbf::samplers::sample_trace::{self, SampleOutcome};
+    use lbf::serve::{self, SolveRequest};
+    use lbf::suggestions;
+    use lbf::sweep::{self, SweepParameters};
+    use lbf::verify::{self, Violation};
 
     const N_ITEMS_TO_REMOVE: usize = 5;
 
@@ -31,18 +95,24 @@ mod tests {
     fn test_instance(instance_path: &str) {
         let instance = Path::new(instance_path);
         // parse the instance
-        let mut config = LBFConfig::default();
-        config.n_samples = 100;
-        let json_instance = io::read_json_instance(&instance);
-        let poly_simpl_config = match config.poly_simpl_tolerance {
-            Some(tolerance) => PolySimplConfig::Enabled { tolerance },
-            None => PolySimplConfig::Disabled,
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
         };
+        let json_instance = io::read_json_instance(Some(instance), None).unwrap();
+        let poly_simpl_config = config.poly_simpl_config();
 
-        let parser = Parser::new(poly_simpl_config, config.cde_config, true);
-        let instance = parser.parse(&json_instance);
+        let parser = Parser::new(
+            poly_simpl_config,
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
 
-        let mut optimizer = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0));
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
 
         let mut rng = SmallRng::seed_from_u64(0);
 
@@ -56,7 +126,7 @@ mod tests {
                 //pick random existing layout
                 let layout_index = LayoutIndex::Real(rng.gen_range(0..problem.layouts().len()));
                 let random_placed_item = problem
-                    .get_layout(&layout_index)
+                    .get_layout(layout_index)
                     .placed_items()
                     .iter()
                     .choose(&mut rng)
@@ -76,4 +146,4753 @@ mod tests {
             optimizer.solve();
         }
     }
+    #[test_case("../assets/shirts.json"; "shirts")]
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_parse_thread_count_independent(instance_path: &str) {
+        let config = LBFConfig::default();
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+
+        let shapes = |n_threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap();
+            let instance = pool.install(|| parser.parse(&json_instance).unwrap());
+            instance
+                .items()
+                .iter()
+                .map(|(item, qty)| (item.shape.points.clone(), *qty))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(shapes(1), shapes(4));
+    }
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/albano.json"; "albano")]
+    fn test_strip_max_width(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let strip_height = match &instance {
+            Instance::SP(spi) => spi.strip_height,
+            Instance::BP(_) => panic!("expected a strip packing instance"),
+        };
+
+        //start from the area lower bound and grow in small steps, up to a maximum which cannot fit all items
+        let lower_bound = instance.item_area() / strip_height;
+        let max_width = lower_bound * 1.1;
+        let config = LBFConfig {
+            n_samples: 100,
+            strip_width_factor: Some(1.0),
+            strip_growth: StripGrowth::Additive {
+                step: lower_bound * 0.02,
+            },
+            strip_max_width: Some(max_width),
+            ..config
+        };
+
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        let strip_width = solution.layout_snapshots[0].bin.bbox().width();
+        assert!(strip_width <= max_width);
+        assert!(!solution.is_complete(&instance));
+    }
+
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/albano.json"; "albano")]
+    fn test_shortfall_cost(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        //the small items, which are placed last by default, are the most expensive to leave out
+        let mut costly_json_instance = json_instance.clone();
+        let n_items = costly_json_instance.items.len();
+        for (i, json_item) in costly_json_instance.items.iter_mut().enumerate() {
+            json_item.shortfall_cost = Some(if i < n_items / 2 { 1 } else { 100 });
+        }
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let costly_instance = parser.parse(&costly_json_instance).unwrap();
+        let strip_height = match &instance {
+            Instance::SP(spi) => spi.strip_height,
+            Instance::BP(_) => panic!("expected a strip packing instance"),
+        };
+
+        //not all items fit in the strip
+        let max_width = instance.item_area() / strip_height * 0.8;
+        let config = LBFConfig {
+            n_samples: 100,
+            strip_max_width: Some(max_width),
+            ..config
+        };
+        let solve = |instance: &Instance| {
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+                .unwrap()
+                .solve()
+        };
+        let solution = solve(&instance);
+        let costly_solution = solve(&costly_instance);
+        assert!(!costly_solution.is_complete(&costly_instance));
+        assert!(
+            costly_solution.shortfall_cost(&costly_instance)
+                < solution.shortfall_cost(&costly_instance)
+        );
+
+        let json_solution = parser::compose_json_solution(
+            &costly_solution,
+            &costly_instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        assert_eq!(
+            json_solution.shortfall_cost,
+            Some(costly_solution.shortfall_cost(&costly_instance))
+        );
+        //instances without shortfall costs do not report them
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        assert_eq!(json_solution.shortfall_cost, None);
+    }
+
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    #[test_case("../assets/baldacci3.json"; "baldacci3")]
+    fn test_bin_assignment(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            bin_assignment: Some(BinAssignmentConfig { max_fill: 0.8 }),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        //every bin in the solution holds at least one item
+        assert!(solution
+            .layout_snapshots
+            .iter()
+            .all(|ls| !ls.placed_items.is_empty()));
+    }
+
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    #[test_case("../assets/baldacci5.json"; "baldacci5")]
+    fn test_infeasible_cell_cache(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        let solve = |infeasible_cell_threshold| {
+            let config = LBFConfig {
+                infeasible_cell_threshold,
+                ..config
+            };
+            let mut optimizer =
+                LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+            let solution = optimizer.solve();
+            (solution, optimizer.cell_cache.n_infeasible())
+        };
+
+        let (solution, n_infeasible) = solve(None);
+        let (cached_solution, cached_n_infeasible) = solve(Some(3));
+
+        //a disabled cache never skips cells, an enabled one does without leaving any items out
+        assert_eq!(n_infeasible, 0);
+        assert!(cached_n_infeasible > 0);
+        assert_eq!(
+            solution.is_complete(&instance),
+            cached_solution.is_complete(&instance)
+        );
+    }
+
+    #[test]
+    fn test_cluster_ids() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            cluster_max_area_frac: Some(0.5),
+            infeasible_cell_threshold: Some(3),
+            item_prng_streams: true,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        //clusters are numbered after the items, so they share no cache entries or PRNG streams with their members
+        let items = instance.items().iter().map(|(item, _)| item).collect_vec();
+        let missing_qtys = instance
+            .items()
+            .iter()
+            .map(|(_, qty)| *qty as isize)
+            .collect_vec();
+        let clusters = clusters::generate_clusters(&items, &missing_qtys, 0.5);
+        assert!(!clusters.is_empty());
+        for (i, cluster) in clusters.iter().enumerate() {
+            assert_eq!(cluster.item.id, items.len() + i);
+        }
+
+        //only the members of the clusters end up in the solution
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        assert!(solution
+            .layout_snapshots
+            .iter()
+            .flat_map(|ls| ls.placed_items.iter())
+            .all(|(_, pi)| pi.item_id < items.len()));
+    }
+
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_simplification_area_delta(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        //simplification only inflates items and only deflates bins
+        for (item, _) in instance.items() {
+            assert!(item.area_delta() >= -1e-3 * item.original_area);
+        }
+        if let Instance::BP(bp_instance) = &instance {
+            for (bin, _) in &bp_instance.bins {
+                assert!(bin.area_delta() <= 1e-3 * bin.original_area);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplification_bounded_deviation() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let fidelity = GeometricFidelity(0.01);
+        let parse = |simpl_config| {
+            let parser = Parser::new(
+                simpl_config,
+                CDEConfig::preset(Preset::Balanced),
+                true,
+                PathBuf::new(),
+                fidelity,
+            );
+            parser.parse(&json_instance).unwrap()
+        };
+        let original = parse(PolySimplConfig::Disabled);
+        let simplified = parse(fidelity.poly_simpl_config());
+
+        //the boundaries of the simplified items stay within the fidelity of the original ones
+        let mut n_removed = 0;
+        for ((orig, _), (simpl, _)) in original.items().iter().zip(simplified.items().iter()) {
+            let (orig, simpl) = (&orig.shape, &simpl.shape);
+            let bbox = orig.bbox();
+            let max_deviation = fidelity.max_deviation(fsize::max(bbox.width(), bbox.height()));
+            let deviation = |points: &[Point], shape: &SimplePolygon| {
+                points
+                    .iter()
+                    .map(|p| {
+                        shape
+                            .edge_iter()
+                            .map(|e| e.distance(p))
+                            .fold(fsize::INFINITY, fsize::min)
+                    })
+                    .fold(0.0, fsize::max)
+            };
+            assert!(deviation(&simpl.points, orig) <= max_deviation * (1.0 + 1e-6));
+            assert!(deviation(&orig.points, simpl) <= max_deviation * (1.0 + 1e-6));
+            n_removed += orig.number_of_points() - simpl.number_of_points();
+        }
+        assert!(n_removed > 0);
+    }
+
+    #[test]
+    fn test_convex_mode() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        //regular polygons of various sizes
+        let items = (0..20)
+            .map(|_| {
+                let n_vertices = rng.gen_range(3..9);
+                let radius = rng.gen_range(5.0..15.0);
+                let points = (0..n_vertices)
+                    .map(|i| {
+                        let angle = 2.0 * PI * i as fsize / n_vertices as fsize;
+                        (radius * angle.cos(), radius * angle.sin())
+                    })
+                    .collect();
+                JsonItem {
+                    demand: Some(rng.gen_range(1..4)),
+                    dxf: None,
+                    dxf_layer: None,
+                    dxf_block: None,
+                    svg: None,
+                    svg_id: None,
+                    allowed_orientations: None,
+                    allowed_transformations: None,
+                    copies: None,
+                    shape: Some(JsonShape::SimplePolygon(JsonSimplePoly(points))),
+                    value: None,
+                    shortfall_cost: None,
+                    base_quality: None,
+                    material: None,
+                    name: None,
+                    surrogate: None,
+                }
+            })
+            .collect();
+        let json_instance = JsonInstance {
+            name: "convex".to_string(),
+            items,
+            bins: None,
+            strip: Some(JsonStrip { height: 100.0 }),
+            units: None,
+            assets: Default::default(),
+        };
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        optimizer.solve();
+
+        let layout = &optimizer.problem.layouts()[0];
+        //every placed item is accepted by the pairwise convex check, when ignoring itself
+        for (_, pi) in layout.placed_items().iter() {
+            let stage = layout.cde().explain_poly_collision(&pi.shape, &[pi.into()]);
+            assert_eq!(stage, CDStage::ConvexAccepted);
+        }
+        //no two placed items intersect or contain each other
+        for ((_, pi_1), (_, pi_2)) in layout.placed_items().iter().tuple_combinations() {
+            let (s1, s2) = (&pi_1.shape, &pi_2.shape);
+            assert!(!s1
+                .edge_iter()
+                .cartesian_product(s2.edge_iter().collect_vec())
+                .any(|(e1, e2)| e1.collides_with(&e2)));
+            assert!(!s1.collides_with(&s2.poi.center) && !s2.collides_with(&s1.poi.center));
+        }
+    }
+
+    #[test_case("../assets/shirts.json"; "shirts")]
+    #[test_case("../assets/albano.json"; "albano")]
+    fn test_convex_pairs_in_mixed_instance(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        optimizer.solve();
+
+        //convex items are checked pairwise, and agree with collecting the collisions by edge intersection and containment
+        let convex_items = instance
+            .items()
+            .iter()
+            .filter(|(item, _)| item.shape.convex)
+            .collect_vec();
+        assert!(!convex_items.is_empty());
+        let mut rng = SmallRng::seed_from_u64(0);
+        let layout = &optimizer.problem.layouts()[0];
+        let bbox = layout.bin.outer.bbox();
+        let mut detected = vec![];
+        for (item, _) in convex_items {
+            for _ in 0..200 {
+                let transf = DTransformation::new(
+                    rng.gen_range(0.0..2.0 * PI),
+                    (
+                        rng.gen_range(bbox.x_min..bbox.x_max),
+                        rng.gen_range(bbox.y_min..bbox.y_max),
+                    ),
+                )
+                .compose();
+                let shape = item.shape.transform_clone(&transf);
+                let stage = layout.cde().explain_poly_collision(&shape, &[]);
+                if stage == CDStage::BBoxRejected {
+                    continue;
+                }
+                detected.clear();
+                layout
+                    .cde()
+                    .collect_poly_collisions(&shape, &[], &mut detected);
+                assert_eq!(stage.collides(), !detected.is_empty(), "{:?}", stage);
+            }
+        }
+    }
+
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/trousers.json"; "trousers")]
+    fn test_obb_pretest(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        optimizer.solve();
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let layout = &optimizer.problem.layouts()[0];
+        let bbox = layout.bin.bbox();
+        for (item, _) in instance.items() {
+            //the OBB encloses the shape
+            let obb = SimplePolygon::new(item.shape.obb.corners.to_vec());
+            assert!(obb.convex_contains_points(&item.shape.points));
+
+            //and is no larger than the rectangle aligned with any edge of the convex hull,
+            let hull = convex_hull_from_points(item.shape.points.clone());
+            let min_edge_aligned_area = (0..hull.len())
+                .map(|i| {
+                    let (p0, p1) = (hull[i], hull[(i + 1) % hull.len()]);
+                    let length = p0.distance(p1);
+                    let u = ((p1.0 - p0.0) / length, (p1.1 - p0.1) / length);
+                    let (u_proj, v_proj): (Vec<_>, Vec<_>) = hull
+                        .iter()
+                        .map(|p| (p.0 * u.0 + p.1 * u.1, p.1 * u.0 - p.0 * u.1))
+                        .unzip();
+                    let extent = |proj: Vec<fsize>| {
+                        proj.iter().cloned().fold(fsize::MIN, fsize::max)
+                            - proj.iter().cloned().fold(fsize::MAX, fsize::min)
+                    };
+                    extent(u_proj) * extent(v_proj)
+                })
+                .fold(fsize::MAX, fsize::min);
+            //up to the margin by which the OBB is grown
+            assert!(item.shape.obb.area() <= min_edge_aligned_area * 1.01);
+
+            //whenever the pre-test excludes a collision, the full check agrees
+            for _ in 0..1000 {
+                let d_transf = DTransformation::new(
+                    rng.gen_range(0.0..2.0 * PI),
+                    (
+                        rng.gen_range(bbox.x_min..bbox.x_max),
+                        rng.gen_range(bbox.y_min..bbox.y_max),
+                    ),
+                );
+                let shape = item.shape.transform_clone(&d_transf.compose());
+                if layout.cde().obb_excludes_collision(&shape.obb, &[]) {
+                    assert!(!layout.cde().poly_collides(&shape, &[]));
+                }
+            }
+        }
+    }
+
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_shape_cache(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig::default();
+        let parser = || {
+            Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                PathBuf::new(),
+                config.fidelity(),
+            )
+        };
+        let cache_folder = std::env::temp_dir().join(format!(
+            "lbf_test_shape_cache_{}_{}",
+            json_instance.name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&cache_folder);
+
+        //all shapes, surrogates and pretransforms of an instance
+        let shapes = |instance: &Instance| {
+            let items = instance.items().iter().map(|(item, qty)| {
+                let shape = &item.shape;
+                let poles = shape.surrogate().poles.clone();
+                (
+                    shape.points.clone(),
+                    poles,
+                    *item.pretransform.matrix(),
+                    *qty,
+                )
+            });
+            let bins = match instance {
+                Instance::BP(bpi) => bpi
+                    .bins
+                    .iter()
+                    .map(|(bin, _)| (bin.outer.points.clone(), *bin.pretransform.matrix()))
+                    .collect_vec(),
+                Instance::SP(_) => vec![],
+            };
+            (items.collect_vec(), bins)
+        };
+        let uncached = parser().parse(&json_instance).unwrap();
+        let n_shapes =
+            json_instance.items.len() + json_instance.bins.as_ref().map_or(0, |b| b.len());
+
+        let cold_parser = parser().with_shape_cache(ShapeCache::new(cache_folder.clone()).unwrap());
+        let cold = cold_parser.parse(&json_instance).unwrap();
+        let cold_cache = cold_parser.shape_cache().unwrap();
+        assert_eq!((cold_cache.n_hits(), cold_cache.n_misses()), (0, n_shapes));
+
+        let warm_parser = parser().with_shape_cache(ShapeCache::new(cache_folder.clone()).unwrap());
+        let warm = warm_parser.parse(&json_instance).unwrap();
+        let warm_cache = warm_parser.shape_cache().unwrap();
+        assert_eq!((warm_cache.n_hits(), warm_cache.n_misses()), (n_shapes, 0));
+
+        //restored shapes are identical to freshly preprocessed ones
+        assert_eq!(shapes(&uncached), shapes(&cold));
+        assert_eq!(shapes(&uncached), shapes(&warm));
+
+        //entries of which the stored key does not match are never restored, even if the hash does
+        for entry in std::fs::read_dir(&cache_folder).unwrap() {
+            let path = entry.unwrap().path();
+            let mut json: serde_json::Value =
+                serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+            json["key"] = serde_json::Value::String("other".into());
+            std::fs::write(&path, serde_json::to_vec(&json).unwrap()).unwrap();
+        }
+        let colliding_parser =
+            parser().with_shape_cache(ShapeCache::new(cache_folder.clone()).unwrap());
+        let colliding = colliding_parser.parse(&json_instance).unwrap();
+        let colliding_cache = colliding_parser.shape_cache().unwrap();
+        assert_eq!(
+            (colliding_cache.n_hits(), colliding_cache.n_misses()),
+            (0, n_shapes)
+        );
+        assert_eq!(shapes(&uncached), shapes(&colliding));
+
+        //the folder of a cache has to be creatable
+        let file = cache_folder.join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(ShapeCache::new(file.join("cache")).is_err());
+
+        std::fs::remove_dir_all(&cache_folder).unwrap();
+    }
+
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_runs_pareto_set(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 200,
+            objective_weights: Some(ObjectiveWeights {
+                usage: 1.0,
+                cut_length: 1e-4,
+                bin_cost: 1e-6,
+                ..ObjectiveWeights::default()
+            }),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        //a single run is identical to solving directly
+        let single = lbf_runs::solve_runs(&instance, config, SmallRng::seed_from_u64(0)).unwrap();
+        let direct = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single.best().unwrap().1.usage, direct.usage);
+
+        let config = LBFConfig {
+            n_runs: Some(4),
+            ..config
+        };
+        let pareto_set =
+            lbf_runs::solve_runs(&instance, config, SmallRng::seed_from_u64(0)).unwrap();
+        let entries = pareto_set.entries();
+        assert!(!entries.is_empty() && entries.len() <= 4);
+        for ((a, _), (b, _)) in entries.iter().tuple_combinations() {
+            assert!(!a.dominates(b) && !b.dominates(a));
+        }
+        let best_score = pareto_set.best().unwrap().0.score();
+        assert!(entries.iter().all(|(v, _)| v.score() <= best_score));
+    }
+
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_item_prng_streams(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        //an item which is sampled first (largest diameter), but fits in none of the bins
+        let mut extended_json_instance = json_instance.clone();
+        extended_json_instance.items.push(
+            serde_json::from_str(
+                r#"{"Demand": 1, "Shape": {"Type": "Rectangle", "Data": {"Width": 20000, "Height": 300}}}"#,
+            )
+            .unwrap(),
+        );
+        let extra_item_id = json_instance.items.len();
+        let config = LBFConfig {
+            n_samples: 500,
+            item_prng_streams: true,
+            ..LBFConfig::default()
+        };
+
+        let placements = |json_instance: &JsonInstance| {
+            let parser = Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                PathBuf::new(),
+                config.fidelity(),
+            );
+            let instance = parser.parse(json_instance).unwrap();
+            let solution = LBFOptimizer::new(instance, config, SmallRng::seed_from_u64(0))
+                .unwrap()
+                .solve();
+            solution
+                .layout_snapshots
+                .iter()
+                .map(|sl| {
+                    let placed_items = sl
+                        .placed_items
+                        .values()
+                        .map(|pi| (pi.item_id, pi.d_transf))
+                        .collect_vec();
+                    (sl.bin.id, placed_items)
+                })
+                .collect_vec()
+        };
+
+        let original = placements(&json_instance);
+        let extended = placements(&extended_json_instance);
+
+        //the extra item is never placed, and does not affect the samples of the other items
+        assert!(extended
+            .iter()
+            .all(|(_, pis)| pis.iter().all(|(id, _)| *id != extra_item_id)));
+        assert_eq!(original, extended);
+    }
+
+    #[test_case(Preset::Fast; "fast")]
+    #[test_case(Preset::Balanced; "balanced")]
+    #[test_case(Preset::Quality; "quality")]
+    fn test_preset(preset: Preset) {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig::preset(preset);
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let solution = lbf_runs::solve_best(&instance, config, SmallRng::seed_from_u64(0)).unwrap();
+        assert!(solution.is_complete(&instance));
+
+        //presets are plain configurations, which survive a round trip through a config file
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: LBFConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        assert_eq!(preset.to_string().parse::<Preset>(), Ok(preset));
+    }
+
+    #[test]
+    fn test_preset_effort() {
+        //each preset spends more effort than the previous one, see the tuning in `LBFConfig::preset`
+        let [fast, balanced, quality] =
+            [Preset::Fast, Preset::Balanced, Preset::Quality].map(LBFConfig::preset);
+        for (lower, higher) in [(fast, balanced), (balanced, quality)] {
+            assert!(lower.n_samples < higher.n_samples);
+            assert!(lower.cde_config.hpg_n_cells < higher.cde_config.hpg_n_cells);
+            let (lower_sc, higher_sc) = (
+                lower.cde_config.item_surrogate_config,
+                higher.cde_config.item_surrogate_config,
+            );
+            assert!(lower_sc.pole_coverage_goal < higher_sc.pole_coverage_goal);
+            assert!(lower_sc.max_poles < higher_sc.max_poles);
+        }
+        assert!(balanced.n_runs.unwrap_or(1) < quality.n_runs.unwrap_or(1));
+    }
+
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_cde_calibration(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            cde_calibration: Some(CalibrationConfig { n_placements: 20 }),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let calib_config = config.cde_calibration.unwrap();
+        let rng = SmallRng::seed_from_u64(0);
+
+        let (calib_instance, calib_lbf_config) =
+            calibration::calibrate(&instance, config, calib_config, &rng).unwrap();
+        //only the resolution of the quadtree and the HPG is tuned
+        assert_eq!(
+            calib_lbf_config.cde_config.item_surrogate_config,
+            config.cde_config.item_surrogate_config
+        );
+        assert_eq!(calib_lbf_config.n_samples, config.n_samples);
+        if let Instance::BP(bp_instance) = &calib_instance {
+            for (bin, _) in bp_instance.bins.iter() {
+                assert_eq!(bin.base_cde.config(), calib_lbf_config.cde_config);
+            }
+        }
+
+        //the remainder of the run uses the selected configuration
+        let solution = LBFOptimizer::new(calib_instance, calib_lbf_config, rng.clone())
+            .unwrap()
+            .solve();
+        assert!(solution.is_complete(&instance));
+    }
+
+    #[test]
+    fn test_item_surrogate_override() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        json_instance.items[0].surrogate = Some(JsonSurrogateConfig {
+            max_poles: Some(30),
+            pole_coverage_goal: Some(0.99),
+            ..JsonSurrogateConfig::default()
+        });
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        let default_config = config.cde_config.item_surrogate_config;
+        let overridden = &instance.item(0).surrogate_config;
+        assert_eq!(overridden.max_poles, 30);
+        assert_eq!(overridden.pole_coverage_goal, 0.99);
+        assert_eq!(overridden.n_ff_poles, default_config.n_ff_poles);
+        assert!(instance.item(0).shape.surrogate().poles.len() > default_config.max_poles);
+        assert!(instance.items()[1..]
+            .iter()
+            .all(|(item, _)| item.surrogate_config == default_config));
+
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        assert!(solution.is_complete(&instance));
+    }
+
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    #[test_case("../assets/swim.json"; "swim")]
+    fn test_explicit_transformations(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let item_id = 0;
+        let placements = |instance: &Instance| {
+            let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+                .unwrap()
+                .solve();
+            let item = instance.item(item_id);
+            solution
+                .layout_snapshots
+                .iter()
+                .flat_map(|sl| {
+                    sl.placed_items
+                        .values()
+                        .filter(|pi| pi.item_id == item_id)
+                        .map(|pi| {
+                            parser::internal_to_absolute_transform(
+                                &pi.d_transf,
+                                &item.pretransform,
+                                &sl.bin.pretransform,
+                            )
+                            .decompose()
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        };
+
+        //restrict the item to the positions it was placed at, and one far outside of any bin
+        let free_placements = placements(&parser.parse(&json_instance).unwrap());
+        assert!(!free_placements.is_empty());
+        let mut fixed_json_instance = json_instance.clone();
+        fixed_json_instance.items[item_id].allowed_orientations = None;
+        fixed_json_instance.items[item_id].allowed_transformations = Some(
+            free_placements
+                .iter()
+                .map(|dt| (dt.rotation(), dt.translation()))
+                .chain([(0.0, (1e6, 1e6))])
+                .map(|(rotation, translation)| JsonTransformation {
+                    rotation,
+                    translation,
+                })
+                .collect(),
+        );
+
+        let fixed_placements = placements(&parser.parse(&fixed_json_instance).unwrap());
+        assert!(!fixed_placements.is_empty());
+        for dt in fixed_placements {
+            assert!(free_placements.iter().any(|allowed| {
+                let (dx, dy) = (
+                    dt.translation().0 - allowed.translation().0,
+                    dt.translation().1 - allowed.translation().1,
+                );
+                (dt.rotation() - allowed.rotation()).abs() < 1e-3 && dx.hypot(dy) < 1e-2
+            }));
+        }
+    }
+
+    #[test]
+    fn test_copy_attributes() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let item_id = 0;
+        //bin id, copy and absolute transformation of every placed copy of the item
+        let placements = |instance: &Instance| {
+            let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+                .unwrap()
+                .solve();
+            let item = instance.item(item_id);
+            solution
+                .layout_snapshots
+                .iter()
+                .flat_map(|sl| {
+                    sl.placed_items
+                        .values()
+                        .filter(|pi| pi.item_id == item_id)
+                        .map(|pi| {
+                            let abs_transf = parser::internal_to_absolute_transform(
+                                &pi.d_transf,
+                                &item.pretransform,
+                                &sl.bin.pretransform,
+                            )
+                            .decompose();
+                            (sl.bin.id, pi.copy, abs_transf)
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        };
+
+        let free_placements = placements(&parser.parse(&json_instance).unwrap());
+        assert!(free_placements.len() >= 2);
+        assert!(free_placements.iter().all(|(_, copy, _)| copy.is_none()));
+
+        //lock copy 2 at the position of the first placement, and restrict copy 4 to the bin of the second
+        let (locked_bin, _, locked_transf) = free_placements[0];
+        let (restricted_bin, _, _) = free_placements[1];
+        let mut json_instance = json_instance.clone();
+        json_instance.items[item_id].copies = Some(vec![
+            JsonItemCopy {
+                copy: 2,
+                bin: Some(locked_bin),
+                transformation: Some(JsonTransformation {
+                    rotation: locked_transf.rotation(),
+                    translation: locked_transf.translation(),
+                }),
+            },
+            JsonItemCopy {
+                copy: 4,
+                bin: Some(restricted_bin),
+                transformation: None,
+            },
+        ]);
+        let instance = parser.parse(&json_instance).unwrap();
+        let copy_placements = placements(&instance);
+
+        //every copy is placed at most once
+        let copies = copy_placements
+            .iter()
+            .map(|(_, copy, _)| copy.expect("copy of item not tracked"))
+            .collect_vec();
+        assert_eq!(copies.len(), copies.iter().unique().count());
+        assert!(copies.iter().all(|c| *c < instance.item_qty(item_id)));
+
+        let (bin, _, transf) = copy_placements
+            .iter()
+            .find(|(_, copy, _)| *copy == Some(2))
+            .expect("locked copy is not placed");
+        assert_eq!(*bin, locked_bin);
+        assert!((transf.rotation() - locked_transf.rotation()).abs() < 1e-3);
+        let (dx, dy) = (
+            transf.translation().0 - locked_transf.translation().0,
+            transf.translation().1 - locked_transf.translation().1,
+        );
+        assert!(dx.hypot(dy) < 1e-2);
+        if let Some((bin, _, _)) = copy_placements.iter().find(|(_, copy, _)| *copy == Some(4)) {
+            assert_eq!(*bin, restricted_bin);
+        }
+    }
+
+    #[test_case("../assets/swim.json", Gravity::TopLeft; "swim_top_left")]
+    #[test_case("../assets/swim.json", Gravity::BottomLeft; "swim_bottom_left")]
+    #[test_case("../assets/shirts.json", Gravity::TopLeft; "shirts_top_left")]
+    fn test_gravity_datum(instance_path: &str, gravity: Gravity) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            gravity,
+            datum_tolerance: Some(1.0),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        assert!(solution.is_complete(&instance));
+
+        //the first item is placed flush in the datum corner
+        let layout = &solution.layout_snapshots[0];
+        let bin_bbox = layout.bin.bbox();
+        let eps = 1e-3 * bin_bbox.height();
+        let in_corner = layout.placed_items.values().any(|pi| {
+            let bbox = pi.shape.bbox();
+            let y_gap = match gravity {
+                Gravity::TopLeft => bin_bbox.y_max - bbox.y_max,
+                _ => bbox.y_min - bin_bbox.y_min,
+            };
+            bbox.x_min - bin_bbox.x_min < eps && y_gap < eps
+        });
+        assert!(in_corner);
+    }
+
+    #[test_case(PackingDirection::Left; "left")]
+    #[test_case(PackingDirection::Right; "right")]
+    #[test_case(PackingDirection::Down; "down")]
+    #[test_case(PackingDirection::Point { x: 0.5, y: 0.5 }; "center")]
+    fn test_packing_direction(direction: PackingDirection) {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            packing_direction: direction,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        assert!(solution.is_complete(&instance));
+
+        //the items in the emptiest bin are gathered on the side they are packed towards
+        let layout = solution
+            .layout_snapshots
+            .iter()
+            .min_by(|l1, l2| l1.usage.partial_cmp(&l2.usage).unwrap())
+            .unwrap();
+        let bin_bbox = layout.bin.bbox();
+        let total_area = layout
+            .placed_items
+            .values()
+            .map(|pi| pi.shape.area())
+            .sum::<fsize>();
+        let (cx, cy) = layout
+            .placed_items
+            .values()
+            .fold((0.0, 0.0), |(cx, cy), pi| {
+                let Point(x, y) = pi.shape.centroid();
+                let weight = pi.shape.area() / total_area;
+                (cx + x * weight, cy + y * weight)
+            });
+        //relative position of the center of mass in the bin
+        let (rx, ry) = (
+            (cx - bin_bbox.x_min) / bin_bbox.width(),
+            (cy - bin_bbox.y_min) / bin_bbox.height(),
+        );
+        match direction {
+            PackingDirection::Left => assert!(rx < 0.5),
+            PackingDirection::Right => assert!(rx > 0.5),
+            PackingDirection::Down => assert!(ry < 0.5),
+            PackingDirection::Point { x, y } => {
+                assert!((rx - x).abs() < 0.1 && (ry - y).abs() < 0.1)
+            }
+        }
+    }
+
+    #[test]
+    fn test_quality_zone_union() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let rect = |x_min: fsize, y_min: fsize, x_max: fsize, y_max: fsize| {
+            JsonSimplePoly(vec![
+                (x_min, y_min),
+                (x_max, y_min),
+                (x_max, y_max),
+                (x_min, y_max),
+            ])
+        };
+        //a zone with a hole, and two overlapping zones of the same quality
+        let zones = &mut json_instance.bins.as_mut().unwrap()[0].zones;
+        zones.push(JsonQualityZone {
+            quality: 2,
+            shape: JsonShape::Polygon(JsonPoly {
+                outer: rect(3000.0, 2600.0, 7400.0, 6800.0),
+                inner: vec![rect(3600.0, 3200.0, 6800.0, 6200.0)],
+            }),
+        });
+        for (x, y) in [(2000.0, 4000.0), (2500.0, 4500.0)] {
+            zones.push(JsonQualityZone {
+                quality: 3,
+                shape: JsonShape::SimplePolygon(rect(x, y, x + 1000.0, y + 1000.0)),
+            });
+        }
+
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            false,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let bin = match &instance {
+            Instance::BP(bpi) => &bpi.bins[0].0,
+            Instance::SP(_) => panic!("expected a bin packing instance"),
+        };
+        let zone_area = |quality: usize| {
+            let zone = bin.quality_zones[quality].as_ref().unwrap();
+            zone.zones.iter().map(|z| z.area()).sum::<fsize>()
+        };
+        //the zones cover exactly the area outside the hole, and the union of the overlapping zones
+        assert!(bin.quality_zones[2].as_ref().unwrap().zones.len() > 1);
+        assert!((zone_area(2) - (4400.0 * 4200.0 - 3200.0 * 3000.0)).abs() < 1.0);
+        assert!((zone_area(3) - (2.0 * 1000.0 * 1000.0 - 500.0 * 500.0)).abs() < 1.0);
+        //the zones which do not overlap are unchanged
+        assert_eq!(bin.quality_zones[1].as_ref().unwrap().zones.len(), 4);
+
+        //an item requiring full quality fits inside the hole, but not on the zone
+        let item = instance
+            .items()
+            .iter()
+            .map(|(item, _)| item)
+            .min_by(|i1, i2| {
+                let area = |item: &Item| item.shape.bbox().area();
+                area(i1).partial_cmp(&area(i2)).unwrap()
+            })
+            .unwrap();
+        let item_center = {
+            let bbox = item.shape.bbox();
+            (
+                (bbox.x_min + bbox.x_max) / 2.0,
+                (bbox.y_min + bbox.y_max) / 2.0,
+            )
+        };
+        let layout = Layout::new(0, bin.clone());
+        let collides_at = |(x, y): (fsize, fsize)| {
+            let transf =
+                DTransformation::new(0.0, (x - item_center.0, y - item_center.1)).compose();
+            let shape = item.shape.transform_clone(&transf);
+            layout.cde().poly_collides(&shape, &[])
+        };
+        assert!(!collides_at((5200.0, 4700.0)));
+        assert!(collides_at((3300.0, 4700.0)));
+    }
+
+    #[test]
+    fn test_part_in_part_forbidden() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let rect = |x_min: fsize, y_min: fsize, x_max: fsize, y_max: fsize| {
+            JsonSimplePoly(vec![
+                (x_min, y_min),
+                (x_max, y_min),
+                (x_max, y_max),
+                (x_min, y_max),
+            ])
+        };
+        //a frame, and a square which fits inside its hole
+        json_instance.items[0].shape = Some(JsonShape::Polygon(JsonPoly {
+            outer: rect(0.0, 0.0, 300.0, 300.0),
+            inner: vec![rect(50.0, 50.0, 250.0, 250.0)],
+        }));
+        json_instance.items[1].shape = Some(JsonShape::SimplePolygon(rect(0.0, 0.0, 100.0, 100.0)));
+
+        let square_in_frame_collides = |forbidden: bool| {
+            let config = LBFConfig::default();
+            let parser = Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                false,
+                PathBuf::new(),
+                config.fidelity(),
+            )
+            .with_part_in_part_forbidden(forbidden);
+            let instance = parser.parse(&json_instance).unwrap();
+            let bin = match &instance {
+                Instance::BP(bpi) => &bpi.bins[0].0,
+                Instance::SP(_) => panic!("expected a bin packing instance"),
+            };
+            let mut layout = Layout::new(0, bin.clone());
+            layout.place_item(
+                instance.item(0),
+                None,
+                DTransformation::new(0.0, (5050.0, 4550.0)),
+            );
+            let transf = DTransformation::new(0.0, (5150.0, 4650.0)).compose();
+            let square = instance.item(1).shape.transform_clone(&transf);
+            layout.cde().poly_collides(&square, &[])
+        };
+        assert!(!square_in_frame_collides(false));
+        assert!(square_in_frame_collides(true));
+    }
+
+    #[test]
+    fn test_invalid_holes() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let rect = |x_min: fsize, y_min: fsize, x_max: fsize, y_max: fsize| {
+            JsonSimplePoly(vec![
+                (x_min, y_min),
+                (x_max, y_min),
+                (x_max, y_max),
+                (x_min, y_max),
+            ])
+        };
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            false,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        //a hole outside the outer contour, one crossing it, and two overlapping holes would all result in a self-intersecting contour
+        let invalid_holes = [
+            vec![rect(400.0, 400.0, 500.0, 500.0)],
+            vec![rect(250.0, 100.0, 350.0, 200.0)],
+            vec![
+                rect(50.0, 50.0, 150.0, 150.0),
+                rect(100.0, 100.0, 200.0, 200.0),
+            ],
+        ];
+        for inner in invalid_holes {
+            let mut invalid = json_instance.clone();
+            invalid.items[0].shape = Some(JsonShape::Polygon(JsonPoly {
+                outer: rect(0.0, 0.0, 300.0, 300.0),
+                inner,
+            }));
+            let Err(ParseError::InvalidItem { item: 0, reason }) = parser.parse(&invalid) else {
+                panic!("invalid holes accepted");
+            };
+            assert!(reason.contains("hole"));
+        }
+    }
+
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_quadtree_stats(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let stats = |quadtree_depth: u8| {
+            let mut config = LBFConfig {
+                n_samples: 100,
+                ..LBFConfig::default()
+            };
+            config.cde_config.quadtree_depth = quadtree_depth;
+            let parser = Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                PathBuf::new(),
+                config.fidelity(),
+            );
+            let instance = parser.parse(&json_instance).unwrap();
+            let mut optimizer =
+                LBFOptimizer::new(instance, config, SmallRng::seed_from_u64(0)).unwrap();
+            optimizer.solve();
+            let cde = optimizer.problem.layouts()[0].cde();
+            (cde.quadtree_stats(), cde.number_of_nodes())
+        };
+
+        for depth in [3, 6] {
+            let (stats, n_nodes) = stats(depth);
+            assert_eq!(stats.n_nodes, n_nodes);
+            assert_eq!(stats.depth_histogram.iter().sum::<usize>(), stats.n_nodes);
+            assert_eq!(
+                stats.leaf_hazard_histogram.iter().sum::<usize>(),
+                stats.n_leaves
+            );
+            //every internal node has four children
+            assert_eq!(stats.n_nodes, 1 + 4 * (stats.n_nodes - stats.n_leaves));
+            assert!(stats.max_depth() <= depth as usize);
+            assert!(stats.n_saturated_leaves <= stats.n_leaves);
+        }
+        //a deeper tree resolves the hazards in more nodes
+        let (shallow, _) = stats(3);
+        let (deep, _) = stats(6);
+        assert!(deep.n_nodes > shallow.n_nodes);
+        assert!(deep.memory_estimate > shallow.memory_estimate);
+        assert!(deep.mean_hazards_per_leaf() < shallow.mean_hazards_per_leaf());
+    }
+
+    #[test]
+    fn test_collision_trace() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        //the counters are shared with the other tests running in parallel, so only lower bounds are checked
+        cd_trace::enable();
+        let before = cd_trace::counts();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        optimizer.solve();
+        let after = cd_trace::counts();
+        assert!(cd_trace::is_enabled());
+        assert!(after.total() > before.total());
+        //swim is neither rectangular nor convex, LBF filters most samples with the fail-fast surrogates
+        for stage in [CDStage::PoleRejected, CDStage::SurrogateAccepted] {
+            assert!(after.count(stage) > before.count(stage));
+        }
+
+        //the explained stage agrees with the outcome of the query
+        let mut rng = SmallRng::seed_from_u64(0);
+        let layout = &optimizer.problem.layouts()[0];
+        let bbox = layout.bin.outer.bbox();
+        for (item, _) in instance.items() {
+            for _ in 0..100 {
+                let transf = DTransformation::new(
+                    rng.gen_range(0.0..2.0 * PI),
+                    (
+                        rng.gen_range(bbox.x_min..bbox.x_max),
+                        rng.gen_range(bbox.y_min..bbox.y_max),
+                    ),
+                )
+                .compose();
+                let shape = item.shape.transform_clone(&transf);
+                let stage = layout.cde().explain_poly_collision(&shape, &[]);
+                assert_eq!(stage.collides(), layout.cde().poly_collides(&shape, &[]));
+                let surrogate_stage =
+                    layout
+                        .cde()
+                        .explain_surrogate_collision(item.shape.surrogate(), &transf, &[]);
+                //the surrogate is contained in the shape, so it never collides when the shape does not
+                if !stage.collides() {
+                    assert_eq!(surrogate_stage, CDStage::SurrogateAccepted);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_heat_map() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        //the recorder is shared with the other tests running in parallel, which might add samples of their own
+        sample_trace::enable();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        sample_trace::disable();
+        let records = sample_trace::take();
+        assert!(sample_trace::take().is_empty());
+        //swim is packed densely, the samplers hit placed items often
+        for outcome in [SampleOutcome::SurrogateRejected, SampleOutcome::Valid] {
+            assert!(records.iter().any(|r| r.outcome == outcome));
+        }
+
+        let bin = &solution.layout_snapshots[0].bin;
+        let heat_map = HeatMap::new(bin, &records, heat_map::HEAT_MAP_RESOLUTION);
+        assert_eq!(
+            heat_map.n_cols.max(heat_map.n_rows),
+            heat_map::HEAT_MAP_RESOLUTION
+        );
+        let n_bin_records = records.iter().filter(|r| r.bin_id == bin.id).count() as u64;
+        assert_eq!(heat_map.total() + heat_map.n_outside, n_bin_records);
+        assert_eq!(
+            SampleOutcome::ALL
+                .iter()
+                .map(|o| heat_map.count(*o))
+                .sum::<u64>(),
+            heat_map.total()
+        );
+        assert!(heat_map.rejected_fraction() > 0.0 && heat_map.rejected_fraction() < 1.0);
+
+        let svg = heat_map::heat_map_to_svg(bin, &heat_map).to_string();
+        assert!(svg.contains("<rect"));
+    }
+
+    #[test_case("../assets/swim.json"; "swim")]
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_placement_suggestions(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        optimizer.solve();
+
+        //make room in the first layout
+        let problem = &mut optimizer.problem;
+        let layout_idx = LayoutIndex::Real(0);
+        //half of the items are removed, emptying the layout would close it
+        let placed_items = problem.get_layout(layout_idx).placed_items();
+        let removed = placed_items
+            .iter()
+            .take(placed_items.len() / 2)
+            .map(|(key, pi)| (key, pi.item_id))
+            .collect_vec();
+        for (key, _) in &removed {
+            problem.remove_item(layout_idx, *key, true);
+        }
+        problem.flush_changes();
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        for (_, item_id) in removed {
+            let item = instance.item(item_id);
+            let k = 5;
+            let suggestions = suggestions::suggest_placements(
+                problem,
+                layout_idx,
+                item,
+                k,
+                500,
+                config.placement_bias(),
+                &mut rng,
+            );
+            //there is plenty of room after removing the items
+            assert!(!suggestions.is_empty());
+            assert!(suggestions.len() <= k);
+            assert!(suggestions
+                .iter()
+                .tuple_windows()
+                .all(|(a, b)| a.cost <= b.cost));
+
+            let layout = problem.get_layout(layout_idx);
+            let mut buffer = (*item.shape).clone();
+            for (i, s) in suggestions.iter().enumerate() {
+                assert_eq!(s.placing_option.item_id, item_id);
+                let transf = s.placing_option.d_transf.compose();
+                assert!(!layout.cde().surrogate_or_poly_collides(
+                    &item.shape,
+                    &transf,
+                    &mut buffer,
+                    &[]
+                ));
+                //suggestions are spread out
+                let (x, y) = s.placing_option.d_transf.translation();
+                for other in &suggestions[..i] {
+                    let (ox, oy) = other.placing_option.d_transf.translation();
+                    assert!((x - ox).hypot(y - oy) > item.shape.poi.radius);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bin_names() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let bins = json_instance.bins.as_mut().unwrap();
+        bins[0].name = Some("Sheet A 3mm steel".to_string());
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        for (json_layout, sl) in json_solution
+            .layouts
+            .iter()
+            .zip(solution.layout_snapshots.iter())
+        {
+            let JsonContainer::Bin { index } = json_layout.container else {
+                panic!("expected a bin")
+            };
+            let expected = json_instance.bins.as_ref().unwrap()[index].name.clone();
+            assert_eq!(json_layout.name, expected);
+
+            let svg = s_layout_to_svg(sl, &instance, config.svg_draw_options).to_string();
+            assert_eq!(
+                svg.contains("<title>Sheet A 3mm steel</title>"),
+                expected.is_some()
+            );
+        }
+        assert!(json_solution.layouts.iter().any(|l| l.name.is_some()));
+
+        //names are kept in the composed instance, unnamed bins are written without one
+        let composed = parser::compose_json_instance(&json_instance, &instance);
+        let composed_bins = composed.bins.unwrap();
+        assert_eq!(composed_bins[0].name.as_deref(), Some("Sheet A 3mm steel"));
+        assert!(composed_bins[1..].iter().all(|b| b.name.is_none()));
+    }
+
+    #[test_case(None; "lbf")]
+    #[test_case(Some(BinAssignmentConfig { max_fill: 0.8 }); "bin_assignment")]
+    fn test_materials(bin_assignment: Option<BinAssignmentConfig>) {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let materials = ["steel", "aluminium"];
+        for (i, bin) in json_instance.bins.as_mut().unwrap().iter_mut().enumerate() {
+            bin.material = Some(materials[i % 2].to_string());
+        }
+        //every third item can be placed in any bin
+        for (i, item) in json_instance.items.iter_mut().enumerate() {
+            item.material = materials.get(i % 3).map(|m| m.to_string());
+        }
+        let config = LBFConfig {
+            n_samples: 100,
+            bin_assignment,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        let mut n_placed = [0; 3];
+        for sl in &solution.layout_snapshots {
+            for pi in sl.placed_items.values() {
+                let item = instance.item(pi.item_id);
+                assert!(sl.bin.accepts(item));
+                n_placed[pi.item_id % 3] += 1;
+            }
+        }
+        assert!(n_placed.iter().all(|n| *n > 0));
+
+        //the solution is accepted when it is read back
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        let rebuilt =
+            parser::build_solution_from_json(&instance, &json_solution.layouts, config.cde_config)
+                .unwrap();
+        assert_eq!(
+            rebuilt.layout_snapshots.len(),
+            solution.layout_snapshots.len()
+        );
+    }
+
+    #[test_case(None; "lbf")]
+    #[test_case(Some(BinAssignmentConfig { max_fill: 0.8 }); "bin_assignment")]
+    fn test_max_usage(bin_assignment: Option<BinAssignmentConfig>) {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let max_usage = 0.4;
+        for bin in json_instance.bins.as_mut().unwrap() {
+            bin.max_usage = Some(max_usage);
+        }
+        let config = LBFConfig {
+            n_samples: 100,
+            bin_assignment,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        assert!(!solution.layout_snapshots.is_empty());
+        for sl in &solution.layout_snapshots {
+            assert!(sl.usage <= max_usage + 1e-6);
+        }
+
+        //the solution is accepted when it is read back
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        let rebuilt =
+            parser::build_solution_from_json(&instance, &json_solution.layouts, config.cde_config)
+                .unwrap();
+        assert_eq!(
+            rebuilt.layout_snapshots.len(),
+            solution.layout_snapshots.len()
+        );
+    }
+
+    #[test]
+    fn test_temporary_zones() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        //zones covering the entire bins, which become active after 3 items are placed
+        let max_items = 3;
+        for bin in json_instance.bins.as_mut().unwrap() {
+            let outer = match bin.shape.as_ref().unwrap() {
+                JsonShape::Polygon(jp) => jp.outer.clone(),
+                _ => panic!("expected polygon bins"),
+            };
+            bin.temporary_zones = vec![JsonTemporaryZone {
+                shape: JsonShape::SimplePolygon(outer),
+                active_from: Some(max_items),
+                active_until: None,
+            }];
+        }
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        assert!(!solution.layout_snapshots.is_empty());
+        let is_zone = |h: &HazardEntity| matches!(h, HazardEntity::TemporaryZone { .. });
+        for sl in &solution.layout_snapshots {
+            assert!(sl.placed_items.len() <= max_items);
+            let mut layout = Layout::from_snapshot(sl);
+            let zone_active = layout.cde().all_hazards().any(|h| is_zone(&h.entity));
+            assert_eq!(zone_active, sl.placed_items.len() == max_items);
+
+            //removing an item deactivates the zone again
+            let key = layout.placed_items().keys().next().unwrap();
+            layout.remove_item(key, true);
+            assert!(!layout.cde().all_hazards().any(|h| is_zone(&h.entity)));
+        }
+    }
+
+    #[test]
+    fn test_rotation_snap() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let step = 0.5;
+        let config = LBFConfig {
+            n_samples: 100,
+            rotation_snap: Some(step),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        let step_rad = config.rotation_snap_radians().unwrap();
+        for sl in &solution.layout_snapshots {
+            for pi in sl.placed_items.values() {
+                let r = pi.d_transf.rotation();
+                assert!((r - rotation_distr::snap_rotation(r, step_rad)).abs() < 1e-4);
+            }
+        }
+
+        let mut json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        json_output::snap_rotations(&mut json_solution, &instance, step_rad);
+        let rotations = json_solution
+            .layouts
+            .iter()
+            .flat_map(|l| l.placed_items.iter())
+            .map(|pi| pi.transformation.rotation)
+            .collect_vec();
+        assert!(!rotations.is_empty());
+        //recorded exactly on the grid
+        for r in rotations {
+            assert_eq!(r, rotation_distr::snap_rotation(r, step_rad));
+        }
+    }
+
+    #[test_case(None; "continuous")]
+    #[test_case(Some(0.5); "snapped")]
+    fn test_coarse_to_fine(rotation_snap: Option<fsize>) {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            rotation_snap,
+            coarse_to_fine: Some(CoarseToFineConfig {
+                n_coarse_rotations: 4,
+                n_candidates: 2,
+            }),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        let n_placed: usize = solution
+            .layout_snapshots
+            .iter()
+            .map(|sl| sl.placed_items.len())
+            .sum();
+        assert_eq!(n_placed, instance.total_item_qty());
+
+        //the refinement respects the snapping grid
+        if let Some(step_rad) = config.rotation_snap_radians() {
+            for sl in &solution.layout_snapshots {
+                for pi in sl.placed_items.values() {
+                    let r = pi.d_transf.rotation();
+                    assert!((r - rotation_distr::snap_rotation(r, step_rad)).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplification_preview() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig::default();
+        let previews = simplification_preview::preview_simplification(
+            &json_instance,
+            &config,
+            PathBuf::from("../assets"),
+        )
+        .unwrap();
+        assert_eq!(previews.len(), json_instance.items.len());
+
+        let tolerance = config.poly_simpl_tolerance.unwrap();
+        assert!(previews.iter().any(|p| {
+            p.simplified.shape.number_of_points() < p.original.shape.number_of_points()
+        }));
+        for preview in &previews {
+            //simplification only inflates items, within the tolerance
+            assert!(preview.area_change() >= 0.0);
+            assert!(preview.area_change() <= tolerance + 1e-4);
+            assert!(preview.max_deviation >= 0.0);
+            let svg = simplification_preview::preview_to_svg(preview).to_string();
+            assert_eq!(svg.matches("<path").count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_parts_list() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        for (i, item) in json_instance.items.iter_mut().enumerate() {
+            item.name = Some(format!("part-{i:03}"));
+        }
+        let mut config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        config.svg_draw_options.parts_list = true;
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        for sl in &solution.layout_snapshots {
+            let svg = s_layout_to_svg(sl, &instance, config.svg_draw_options).to_string();
+            assert!(svg.contains("parts_list"));
+            //every placed part is listed by name, and only those
+            for (i, _) in json_instance.items.iter().enumerate() {
+                let placed = sl.placed_items.values().any(|pi| pi.item_id == i);
+                assert_eq!(svg.contains(&format!("part-{i:03}")), placed);
+            }
+            assert!(svg.contains(&format!("usage: {:.1}%", sl.usage * 100.0)));
+        }
+    }
+
+    #[test]
+    fn test_placements_csv() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        json_instance.items[0].name = Some("bracket, \"left\"".to_string());
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+
+        let csv = csv_export::placements_csv(&json_solution, &instance);
+        let lines = csv.lines().collect_vec();
+        assert_eq!(lines[0], csv_export::CSV_HEADER);
+        let n_placed: usize = json_solution
+            .layouts
+            .iter()
+            .map(|l| l.placed_items.len())
+            .sum();
+        assert_eq!(lines.len(), n_placed + 1);
+
+        //names with separators or quotes are quoted
+        assert!(csv.contains("0,\"bracket, \"\"left\"\"\","));
+        for line in &lines[1..] {
+            assert!(line.ends_with(",false"));
+            let item_id: usize = line.split(',').next().unwrap().parse().unwrap();
+            if item_id != 0 {
+                assert_eq!(line.split(',').count(), 8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dxf_export() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_dxf_export_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        for (sl, json_layout) in solution
+            .layout_snapshots
+            .iter()
+            .zip(json_solution.layouts.iter())
+        {
+            let path = folder.join("layout.dxf");
+            dxf_export::layout_to_dxf(sl, &instance, DxfLayering::PerItem)
+                .save_file(&path)
+                .unwrap();
+            let drawing = dxf::Drawing::load_file(&path).unwrap();
+            let polylines = drawing
+                .entities()
+                .map(|e| match &e.specific {
+                    dxf::entities::EntityType::LwPolyline(lwp) => (e.common.layer.clone(), lwp),
+                    _ => panic!("only polylines are exported"),
+                })
+                .collect_vec();
+            let (bin_polylines, item_polylines): (Vec<_>, Vec<_>) = polylines
+                .into_iter()
+                .partition(|(layer, _)| layer == dxf_export::BIN_LAYER);
+            assert_eq!(bin_polylines.len(), 1 + sl.bin.holes.len());
+            assert_eq!(item_polylines.len(), json_layout.placed_items.len());
+
+            //every item is drawn at the absolute transformation of the solution, in the coordinates of the input
+            for ((layer, lwp), json_pi) in
+                item_polylines.iter().zip(json_layout.placed_items.iter())
+            {
+                assert_eq!(layer, &format!("ITEM_{}", json_pi.index));
+                let item = instance.item(json_pi.index);
+                let t = &json_pi.transformation;
+                let abs_transf = DTransformation::new(t.rotation, t.translation).compose();
+                let expected = item
+                    .shape
+                    .transform_clone(&item.pretransform.clone().inverse())
+                    .transform_clone(&abs_transf);
+                assert_eq!(lwp.vertices.len(), expected.number_of_points());
+                for (v, p) in lwp.vertices.iter().zip(expected.points.iter()) {
+                    assert!((v.x - p.0 as f64).abs() < 1e-2 && (v.y - p.1 as f64).abs() < 1e-2);
+                }
+            }
+        }
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn test_labels() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        let n_placed: usize = solution
+            .layout_snapshots
+            .iter()
+            .map(|sl| sl.placed_items.len())
+            .sum();
+
+        let zpl = label_export::labels(&solution, &instance, label_export::DEFAULT_LABEL_TEMPLATE);
+        assert_eq!(zpl.matches("^XA").count(), n_placed);
+        assert!(!zpl.contains('{'));
+
+        //sequence numbers restart at 1 on every sheet
+        let records = label_export::labels(&solution, &instance, "{sheet};{seq};{part}\n");
+        let records = records
+            .lines()
+            .map(|l| {
+                l.split(';')
+                    .map(|f| f.parse::<usize>().unwrap())
+                    .collect_vec()
+            })
+            .collect_vec();
+        assert_eq!(records.len(), n_placed);
+        for (sheet_idx, sl) in solution.layout_snapshots.iter().enumerate() {
+            let sheet_records = records
+                .iter()
+                .filter(|r| r[0] == sheet_idx + 1)
+                .collect_vec();
+            assert_eq!(sheet_records.len(), sl.placed_items.len());
+            for (seq_idx, r) in sheet_records.iter().enumerate() {
+                assert_eq!(r[1], seq_idx + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_svg_items() {
+        let folder = std::env::temp_dir().join(format!("lbf_test_svg_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("parts.svg"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- rounded plate, triangle and a curved part -->
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 200 100">
+  <defs><path d="M0 0 H500 V500 H0 Z"/></defs>
+  <g transform="translate(10, 20) scale(2)">
+    <path id="plate" d="M0,0 H30 V10 A10 10 0 0 1 20,20 H0 z"/>
+  </g>
+  <polygon id="triangle" points="100,0 140,0 100,30"/>
+  <path id="blob" d="M150 10c10-10 30-10 40 0Q200 30 170 40T150 10Z"/>
+</svg>"#,
+        )
+        .unwrap();
+        let json_instance: JsonInstance = serde_json::from_str(
+            r#"{
+                "Name": "svg",
+                "Items": [
+                    {"Demand": 2, "Svg": "parts.svg"},
+                    {"Demand": 2, "Svg": "parts.svg", "SvgId": "triangle"},
+                    {"Demand": 1, "Svg": "parts.svg", "SvgId": "blob"}
+                ],
+                "Strip": {"Height": 100}
+            }"#,
+        )
+        .unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            ..LBFConfig::default()
+        };
+        let parse = |center_polygons: bool| {
+            Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                center_polygons,
+                folder.clone(),
+                config.fidelity(),
+            )
+            .parse(&json_instance)
+            .unwrap()
+        };
+
+        //without centering, the shapes remain in the coordinates of the file
+        let instance = parse(false);
+        let (plate, triangle, blob) = (instance.item(0), instance.item(1), instance.item(2));
+        assert_eq!(plate.name.as_deref(), Some("parts"));
+        //largest contour, with the transformation of its group and the circular arc preserved
+        let plate_area = 4.0 * (30.0 * 20.0 - 100.0 + PI * 100.0 / 4.0);
+        assert!((plate.original_area - plate_area).abs() < 1e-3 * plate_area);
+        assert!(plate.arc_shape.is_some());
+        let bbox = plate.shape.bbox();
+        assert!((bbox.x_min - 10.0).abs() < 1e-3 && (bbox.x_max - 70.0).abs() < 0.1);
+        assert!((bbox.y_min - 20.0).abs() < 1e-3 && (bbox.y_max - 60.0).abs() < 1e-3);
+        assert!((triangle.original_area - 600.0).abs() < 1e-3);
+        assert!(triangle.arc_shape.is_none());
+        //curves are flattened, items are only inflated by the conversion
+        assert!(blob.arc_shape.is_none());
+        assert!(blob.shape.number_of_points() > 10);
+        for (item, _) in instance.items() {
+            assert!(item.shape.area() >= item.original_area * (1.0 - 1e-3));
+        }
+
+        let instance = parse(true);
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        assert_eq!(solution.n_items_placed(), 5);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_cut_order() {
+        //parts on a line: after every cut, the part furthest from the previous one is next
+        let centers = (0..6).map(|i| Point(i as fsize, 0.0)).collect_vec();
+        let config = CutOrderConfig { heat_memory: 1 };
+        let order = cut_order::cut_order(&centers, config);
+        assert_eq!(order, vec![0, 5, 1, 4, 2, 3]);
+        assert_eq!(cut_order::min_consecutive_distance(&centers, &order), 1.0);
+
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            cut_order: Some(CutOrderConfig { heat_memory: 3 }),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        let mut json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        assert!(json_solution.layouts.iter().all(|l| l.cut_order.is_empty()));
+        cut_order::assign_cut_orders(&mut json_solution, &instance, config.cut_order.unwrap());
+        for layout in &json_solution.layouts {
+            //every placed item is cut exactly once
+            let sorted = layout.cut_order.iter().copied().sorted().collect_vec();
+            assert_eq!(sorted, (0..layout.placed_items.len()).collect_vec());
+        }
+        let json = serde_json::to_string(&json_solution).unwrap();
+        assert!(json.contains("\"CutOrder\":[0,"));
+    }
+
+    #[test]
+    fn test_thin_webs() {
+        let square = |x: fsize| {
+            SimplePolygon::new(vec![
+                Point(x, 0.0),
+                Point(x + 1.0, 0.0),
+                Point(x + 1.0, 1.0),
+                Point(x, 1.0),
+            ])
+        };
+        let (p1, p2) = web_width::closest_points(&square(0.0), &square(1.5));
+        assert_eq!(p1.0, 1.0);
+        assert_eq!(p2.0, 1.5);
+
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let min_web_width = 20.0;
+        let config = LBFConfig {
+            n_samples: 100,
+            min_web_width: Some(min_web_width),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        let mut json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            min_web_width,
+            &[],
+        );
+        assert!(json_solution
+            .layouts
+            .iter()
+            .all(|l| l.statistics.thin_webs.is_empty()));
+        parser::add_thin_webs(&mut json_solution, &solution, &instance, min_web_width);
+        for layout in &json_solution.layouts {
+            let webs = &layout.statistics.thin_webs;
+            assert!(!webs.is_empty());
+            assert!(webs
+                .iter()
+                .all(|w| w.width > 0.0 && w.width < min_web_width));
+            //every thin web is also a clearance violation, touching items are not separated by a web
+            let n_touching = layout
+                .statistics
+                .clearance_violations
+                .iter()
+                .filter(|c| c.distance == 0.0)
+                .count();
+            assert_eq!(
+                webs.len() + n_touching,
+                layout.statistics.clearance_violations.len()
+            );
+        }
+        let json = serde_json::to_string(&json_solution).unwrap();
+        assert!(json.contains("\"ThinWebs\":[{\"PlacedItem\":"));
+    }
+
+    #[test]
+    fn test_dxf_curves() {
+        use dxf::entities::{Arc, Circle, Entity, EntityType, Line, Spline};
+        use dxf::enums::AcadVersion;
+        use dxf::{Drawing, Point as DxfPoint};
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut add = |layer: &str, specific: EntityType| {
+            let mut entity = Entity::new(specific);
+            entity.common.layer = layer.to_string();
+            drawing.add_entity(entity);
+        };
+        let p = |x: f64, y: f64| DxfPoint::new(x, y, 0.0);
+        //A: rectangle with a rounded corner from lines (one reversed) and an arc, with a circular hole
+        add("A", EntityType::Line(Line::new(p(5.0, 0.0), p(40.0, 0.0))));
+        add(
+            "A",
+            EntityType::Line(Line::new(p(40.0, 0.0), p(40.0, 20.0))),
+        );
+        add("A", EntityType::Circle(Circle::new(p(20.0, 10.0), 3.0)));
+        add(
+            "A",
+            EntityType::Line(Line::new(p(40.0, 20.0), p(0.0, 20.0))),
+        );
+        add("A", EntityType::Line(Line::new(p(0.0, 5.0), p(0.0, 20.0))));
+        add(
+            "A",
+            EntityType::Arc(Arc::new(p(5.0, 5.0), 5.0, 180.0, 270.0)),
+        );
+        //B: a circle
+        add("B", EntityType::Circle(Circle::new(p(0.0, 0.0), 10.0)));
+        //C: a closed cubic spline
+        let mut spline = Spline {
+            degree_of_curve: 3,
+            knot_values: vec![0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 4.0, 4.0, 4.0],
+            ..Default::default()
+        };
+        spline.control_points = [(0.0, 0.0), (20.0, -10.0), (40.0, 0.0), (40.0, 20.0)]
+            .into_iter()
+            .chain([(20.0, 30.0), (0.0, 20.0), (0.0, 0.0)])
+            .map(|(x, y)| p(x, y))
+            .collect();
+        add("C", EntityType::Spline(spline));
+        //D: a line which is not part of any contour
+        add(
+            "D",
+            EntityType::Line(Line::new(p(100.0, 0.0), p(110.0, 0.0))),
+        );
+
+        let folder = std::env::temp_dir().join(format!("lbf_test_dxf_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        drawing.save_file(folder.join("curves.dxf")).unwrap();
+
+        let json_instance: JsonInstance = serde_json::from_str(
+            r#"{
+                "Name": "dxf_curves",
+                "Items": [
+                    {"Demand": 1, "Dxf": "curves.dxf", "DxfLayer": "A"},
+                    {"Demand": 1, "Dxf": "curves.dxf", "DxfLayer": "B"},
+                    {"Demand": 1, "Dxf": "curves.dxf", "DxfLayer": "C"}
+                ],
+                "Strip": {"Height": 100}
+            }"#,
+        )
+        .unwrap();
+        let config = LBFConfig::default();
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            folder.clone(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+
+        //the connected lines and arc enclose more area than the circle, which becomes a hole of the plate
+        let plate = instance.item(0);
+        let plate_area = 40.0 * 20.0 - 25.0 + PI * 25.0 / 4.0 - PI * 9.0;
+        assert!((plate.original_area - plate_area).abs() < 1e-3 * plate_area);
+        assert!(plate.arc_shape.is_none());
+        let hole_center = Point(20.0, 10.0).transform_clone(&plate.pretransform);
+        assert!(!plate.shape.collides_with(&hole_center));
+        let disc = instance.item(1);
+        assert!((disc.original_area - PI * 100.0).abs() < 1e-3 * PI * 100.0);
+        assert!(disc.arc_shape.is_some());
+        //the spline is tessellated, it stays within the hull of its control points
+        let blob = instance.item(2);
+        assert!(blob.arc_shape.is_none());
+        assert!(blob.shape.number_of_points() > 10);
+        assert!(blob.original_area > 0.0 && blob.original_area < 40.0 * 40.0);
+        for (item, _) in instance.items() {
+            assert!(item.shape.area() >= item.original_area * (1.0 - 1e-3));
+            //flattened curves can lie inside the shape, by at most the deviation allowed by the fidelity
+            assert!(item.curve_deviation > 0.0);
+        }
+
+        //the reported lower bounds on the clearances account for that deviation
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            fsize::INFINITY,
+            &[],
+        );
+        let clearances = &json_solution.layouts[0].statistics.clearance_violations;
+        assert!(!clearances.is_empty());
+        for c in clearances {
+            assert!(c.lower_bound >= 0.0);
+            assert!(c.lower_bound < c.distance || c.distance == 0.0);
+        }
+
+        let inspection = dxf_inspect::inspect_dxf(&folder.join("curves.dxf"));
+        assert!(inspection.polygon.is_some());
+        assert_eq!(inspection.open_contours.len(), 1);
+        assert!(inspection.open_contours[0].contains("layer \"D\""));
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_dxf_folder() {
+        use dxf::entities::{Entity, EntityType, LwPolyline};
+        use dxf::enums::AcadVersion;
+        use dxf::{Drawing, LwPolylineVertex};
+
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_dxf_folder_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let save_rect = |file_name: &str, width: f64, height: f64| {
+            let mut drawing = Drawing::new();
+            drawing.header.version = AcadVersion::R2000;
+            let mut lw_polyline = LwPolyline {
+                vertices: [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+                    .into_iter()
+                    .map(|(x, y)| LwPolylineVertex {
+                        x,
+                        y,
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            lw_polyline.set_is_closed(true);
+            drawing.add_entity(Entity::new(EntityType::LwPolyline(lw_polyline)));
+            drawing.save_file(folder.join(file_name)).unwrap();
+        };
+        save_rect("sheet_x2.dxf", 100.0, 50.0);
+        save_rect("square_x3.dxf", 20.0, 20.0);
+        save_rect("plate.dxf", 30.0, 10.0);
+        std::fs::write(
+            folder.join(dxf_folder::MANIFEST_FILE),
+            r#"{"Name": "folder", "Items": [{"Dxf": "plate.dxf", "Demand": 4, "AllowedOrientations": [0, 90]}]}"#,
+        )
+        .unwrap();
+
+        let config = LBFConfig {
+            n_samples: 1000,
+            ..LBFConfig::default()
+        };
+        let json_instance =
+            dxf_folder::folder_instance(&folder, config.fidelity(), &DxfLayerMapping::default())
+                .unwrap();
+        assert_eq!(json_instance.name, "folder");
+        assert!(json_instance.strip.is_none());
+        let json_bins = json_instance.bins.as_ref().unwrap();
+        assert_eq!(json_bins.len(), 1);
+        assert_eq!(json_bins[0].name.as_deref(), Some("sheet"));
+        assert_eq!((json_bins[0].stock, json_bins[0].cost), (Some(2), 5000));
+        let item_demands = json_instance
+            .items
+            .iter()
+            .map(|item| (item.name.clone(), item.demand))
+            .collect_vec();
+        assert_eq!(
+            item_demands,
+            [(None, Some(4)), (Some("square".to_string()), Some(3))]
+        );
+
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            folder.clone(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let total_demand: usize = instance.items().iter().map(|(_, qty)| qty).sum();
+        assert_eq!(total_demand, 7);
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let n_placed: usize = solution
+            .layout_snapshots
+            .iter()
+            .map(|sl| sl.placed_items.len())
+            .sum();
+        assert_eq!(n_placed, 7);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_dxf_metadata() {
+        let metadata = DxfItemMetadata::from_layer_name("PART_qty5_rot90_val3").unwrap();
+        assert_eq!(metadata.demand, Some(5));
+        assert_eq!(metadata.value, Some(3));
+        assert_eq!(
+            metadata.allowed_orientations,
+            Some(vec![0.0, 90.0, 180.0, 270.0])
+        );
+        let metadata = DxfItemMetadata::from_layer_name("PART_rot0,180").unwrap();
+        assert_eq!(metadata.allowed_orientations, Some(vec![0.0, 180.0]));
+
+        //steps which define too many orientations, or do not divide a full turn, are rejected
+        assert!(DxfItemMetadata::from_layer_name("PART_rot1e-30").is_err());
+        assert!(DxfItemMetadata::from_layer_name("PART_rot0.01").is_err());
+        assert!(DxfItemMetadata::from_layer_name("PART_rot7").is_err());
+        assert!(DxfItemMetadata::from_layer_name("PART_rot0.1").is_ok());
+    }
+
+    #[test]
+    fn test_verify_solution() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+
+        let report = verify::verify_solution(&instance, &json_solution.layouts, config.cde_config);
+        assert!(report.is_feasible(), "{:?}", report.violations);
+        assert_eq!(report.n_placed, instance.total_item_qty());
+        assert!((report.usage - json_solution.usage).abs() < 1e-3);
+
+        //the same placements in the line-based format
+        let lines = json_solution
+            .layouts
+            .iter()
+            .enumerate()
+            .flat_map(|(i, jl)| {
+                let JsonContainer::Bin { index } = jl.container else {
+                    panic!("expected a bin")
+                };
+                jl.placed_items.iter().map(move |jpi| {
+                    let t = &jpi.transformation;
+                    format!(
+                        "{}, {}, {}, {}, {}, {}",
+                        i,
+                        index,
+                        jpi.index,
+                        t.translation.0,
+                        t.translation.1,
+                        t.rotation.to_degrees()
+                    )
+                })
+            })
+            .join("\n");
+        let line_layouts = placements::parse_line_placements(
+            &format!("# sheet bin item x y r\n{lines}"),
+            &instance,
+        )
+        .unwrap();
+        assert_eq!(line_layouts.len(), json_solution.layouts.len());
+        let line_report = verify::verify_solution(&instance, &line_layouts, config.cde_config);
+        assert!(line_report.is_feasible(), "{:?}", line_report.violations);
+
+        //an item moved on top of another one, and an extra copy of an item
+        let mut layouts = json_solution.layouts.clone();
+        layouts[0].placed_items[1].transformation =
+            layouts[0].placed_items[0].transformation.clone();
+        let extra_copy = layouts[0].placed_items[0].clone();
+        let demand = instance.item_qty(extra_copy.index);
+        layouts[0]
+            .placed_items
+            .extend(vec![extra_copy.clone(); demand]);
+        let report = verify::verify_solution(&instance, &layouts, config.cde_config);
+        assert!(!report.is_feasible());
+        assert!(report.violations.iter().any(|v| matches!(
+            v,
+            Violation::Collision {
+                layout: 0,
+                placed_item: 1,
+                ..
+            }
+        )));
+        assert!(report.violations.contains(&Violation::ExcessItems {
+            item: extra_copy.index,
+            placed: 2 * demand,
+            demand
+        }));
+    }
+
+    #[test]
+    fn test_rotated_bins() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let n_json_bins = json_instance.bins.as_ref().unwrap().len();
+        let config = LBFConfig {
+            n_samples: 100,
+            rotated_bins: true,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .with_rotated_bins(config.rotated_bins);
+        let instance = parser.parse(&json_instance).unwrap();
+        let Instance::BP(bp_instance) = &instance else {
+            panic!("expected a bin packing instance")
+        };
+        assert!(bp_instance.bins.len() > n_json_bins);
+        for (bin, _) in &bp_instance.bins[n_json_bins..] {
+            let base = &bp_instance.bins[bin.variant_of.unwrap()].0;
+            assert!((bin.bbox().width() - base.bbox().height()).abs() < 1e-3);
+            assert!((bin.area - base.area).abs() < 1e-3 * base.area);
+        }
+
+        let mut optimizer =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        let solution = optimizer.solve();
+
+        //a bin and its variant together are not used more often than the bin is in stock
+        let counts = solution
+            .layout_snapshots
+            .iter()
+            .map(|sl| sl.bin.base_id())
+            .counts();
+        for (base_id, count) in counts {
+            assert!(count <= bp_instance.bins[base_id].1);
+        }
+
+        //the solution refers to the bins of the input, and can be read back
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        for json_layout in &json_solution.layouts {
+            let JsonContainer::Bin { index } = json_layout.container else {
+                panic!("expected a bin")
+            };
+            assert!(index < n_json_bins);
+        }
+        let rebuilt =
+            parser::build_solution_from_json(&instance, &json_solution.layouts, config.cde_config)
+                .unwrap();
+        assert!((rebuilt.usage - solution.usage).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_solution_metadata() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig::default();
+        let metadata = json_output::solution_metadata(&json_instance, &config);
+
+        assert_eq!(metadata.solver_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.config_hash.len(), 64);
+        assert_eq!(metadata.instance_hash.len(), 64);
+        //hashes only depend on the content of the config and instance
+        assert_eq!(
+            metadata,
+            json_output::solution_metadata(&json_instance.clone(), &config.clone())
+        );
+
+        let other_config = LBFConfig {
+            n_samples: config.n_samples + 1,
+            ..config
+        };
+        let other_metadata = json_output::solution_metadata(&json_instance, &other_config);
+        assert_ne!(metadata.config_hash, other_metadata.config_hash);
+        assert_eq!(metadata.instance_hash, other_metadata.instance_hash);
+
+        let mut other_instance = json_instance.clone();
+        other_instance.items[0].demand = Some(other_instance.items[0].demand.unwrap_or(1) + 1);
+        let other_metadata = json_output::solution_metadata(&other_instance, &config);
+        assert_eq!(metadata.config_hash, other_metadata.config_hash);
+        assert_ne!(metadata.instance_hash, other_metadata.instance_hash);
+    }
+
+    #[test]
+    fn test_repro_bundle() {
+        let instance_path = Path::new("../assets/dxf/albano_dxf.json");
+        let json_instance = io::read_json_instance(Some(instance_path), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            prng_seed: Some(7),
+            ..LBFConfig::default()
+        };
+        let folder = std::env::temp_dir().join(format!("lbf_test_repro_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        let bundle_path = folder.join("bundle.zip");
+
+        repro::export_repro(
+            &bundle_path,
+            instance_path,
+            &json_instance,
+            &config,
+            &AssetPaths::from(instance_path.parent().unwrap()),
+        )
+        .unwrap();
+        let repro = repro::import_repro(&bundle_path, &folder.join("extracted")).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&repro.config).unwrap(),
+            serde_json::to_string(&config).unwrap()
+        );
+        let extracted_instance = io::read_json_instance(Some(&repro.instance_path), None).unwrap();
+        assert_eq!(
+            repro.manifest.metadata,
+            json_output::solution_metadata(&extracted_instance, &repro.config)
+        );
+
+        //the extracted run is identical to the original one
+        let solve = |instance_path: &Path, json_instance: &JsonInstance, config: LBFConfig| {
+            let parser = Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                instance_path.parent().unwrap().to_path_buf(),
+                config.fidelity(),
+            );
+            let instance = parser.parse(json_instance).unwrap();
+            let rng = SmallRng::seed_from_u64(config.prng_seed.unwrap());
+            let solution = LBFOptimizer::new(instance.clone(), config, rng)
+                .unwrap()
+                .solve();
+            solution
+                .layout_snapshots
+                .iter()
+                .flat_map(|sl| sl.placed_items.values().map(|pi| (pi.item_id, pi.d_transf)))
+                .collect_vec()
+        };
+        assert_eq!(
+            solve(instance_path, &json_instance, config),
+            solve(&repro.instance_path, &extracted_instance, repro.config)
+        );
+
+        //files outside of the assets folder are bundled under a generated relative path
+        let mut absolute_instance = json_instance.clone();
+        let dxf_path = absolute_instance.items[0].dxf.clone().unwrap();
+        let absolute_path = std::fs::canonicalize(instance_path.parent().unwrap().join(&dxf_path))
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        for item in absolute_instance.items.iter_mut() {
+            if item.dxf.as_ref() == Some(&dxf_path) {
+                item.dxf = Some(absolute_path.clone());
+            }
+        }
+        let absolute_bundle = folder.join("absolute.zip");
+        let asset_paths = AssetPaths::from(instance_path.parent().unwrap());
+        repro::export_repro(
+            &absolute_bundle,
+            instance_path,
+            &absolute_instance,
+            &config,
+            &asset_paths,
+        )
+        .unwrap();
+        let repro =
+            repro::import_repro(&absolute_bundle, &folder.join("extracted_absolute")).unwrap();
+        let extracted_instance = io::read_json_instance(Some(&repro.instance_path), None).unwrap();
+        let bundled_path = extracted_instance.items[0].dxf.as_ref().unwrap();
+        assert!(bundled_path.starts_with(repro::EXTERNAL_FOLDER));
+        assert_eq!(
+            solve(instance_path, &json_instance, config),
+            solve(&repro.instance_path, &extracted_instance, repro.config)
+        );
+
+        //missing files fail the export instead of panicking
+        let mut missing_instance = json_instance.clone();
+        missing_instance.items[0].dxf = Some("missing.dxf".into());
+        let result = repro::export_repro(
+            &folder.join("missing.zip"),
+            instance_path,
+            &missing_instance,
+            &config,
+            &asset_paths,
+        );
+        assert!(matches!(result, Err(IoError::Io { .. })));
+
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_parse_errors_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("broken.svg"),
+            r#"<svg><path d="M0 0 L10"/></svg>"#,
+        )
+        .unwrap();
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            folder.clone(),
+            config.fidelity(),
+        );
+
+        //malformed instances are rejected instead of crashing the process
+        let mut both_containers = json_instance.clone();
+        both_containers.strip = Some(JsonStrip { height: 100.0 });
+        assert!(matches!(
+            parser.parse(&both_containers),
+            Err(ParseError::InvalidInstance(_))
+        ));
+
+        let mut no_demand = json_instance.clone();
+        no_demand.items[3].demand = None;
+        assert!(matches!(
+            parser.parse(&no_demand),
+            Err(ParseError::InvalidItem { item: 3, .. })
+        ));
+
+        //as are shapes which do not describe a polygon
+        let degenerate_shapes = [
+            JsonShape::SimplePolygon(JsonSimplePoly(vec![(0.0, 0.0), (10.0, 10.0)])),
+            JsonShape::SimplePolygon(JsonSimplePoly(vec![(0.0, 0.0), (5.0, 5.0), (10.0, 10.0)])),
+            JsonShape::SimplePolygon(JsonSimplePoly(vec![
+                (0.0, 0.0),
+                (fsize::NAN, 0.0),
+                (10.0, 10.0),
+            ])),
+            JsonShape::Rectangle {
+                width: 0.0,
+                height: 10.0,
+            },
+        ];
+        for shape in degenerate_shapes {
+            let mut degenerate_item = json_instance.clone();
+            degenerate_item.items[1].shape = Some(shape.clone());
+            assert!(matches!(
+                parser.parse(&degenerate_item),
+                Err(ParseError::InvalidItem { item: 1, .. })
+            ));
+            let mut degenerate_bin = json_instance.clone();
+            degenerate_bin.bins.as_mut().unwrap()[2].shape = Some(shape);
+            assert!(matches!(
+                parser.parse(&degenerate_bin),
+                Err(ParseError::InvalidBin { bin: 2, .. })
+            ));
+        }
+
+        //all missing files are reported at once
+        let mut missing_files = json_instance.clone();
+        for (item, file) in [(0, "missing.svg"), (2, "also_missing.svg")] {
+            missing_files.items[item].shape = None;
+            missing_files.items[item].svg = Some(file.to_string());
+        }
+        let Err(ParseError::MissingAssets(missing)) = parser.parse(&missing_files) else {
+            panic!("missing files not reported");
+        };
+        assert_eq!(missing.iter().map(|m| m.item).collect_vec(), [0, 2]);
+        assert!(missing[1].path.ends_with("also_missing.svg"));
+
+        //or the items referring to them are left out
+        let skipped = parser::apply_missing_asset_policy(
+            &missing_files,
+            parser.asset_paths(),
+            MissingAssetPolicy::Skip,
+        )
+        .unwrap();
+        assert_eq!(skipped.items.len(), json_instance.items.len() - 2);
+        assert_eq!(
+            serde_json::to_value(&skipped.items[0]).unwrap(),
+            serde_json::to_value(&json_instance.items[1]).unwrap()
+        );
+        assert!(parser.parse(&skipped).is_ok());
+        assert!(matches!(
+            parser::apply_missing_asset_policy(
+                &missing_files,
+                parser.asset_paths(),
+                MissingAssetPolicy::Fail,
+            ),
+            Err(ParseError::MissingAssets(_))
+        ));
+
+        let mut broken_file = json_instance.clone();
+        broken_file.items[0].shape = None;
+        broken_file.items[0].svg = Some("broken.svg".to_string());
+        let err = parser.parse(&broken_file).unwrap_err();
+        assert!(matches!(err, ParseError::InvalidFile { .. }));
+        assert!(err.to_string().contains("broken.svg"));
+
+        //as are solutions which do not match the instance
+        let instance = parser.parse(&json_instance).unwrap();
+        let json_layouts = placements::parse_line_placements("0 999 0 0 0 0", &instance).unwrap();
+        assert!(matches!(
+            parser::build_solution_from_json(&instance, &json_layouts, config.cde_config),
+            Err(ParseError::InvalidSolution(_))
+        ));
+        let Err(err) = placements::parse_line_placements("0 0 0 0 0 0\n0 0 zero 0 0 0", &instance)
+        else {
+            panic!("malformed placement accepted");
+        };
+        assert!(err.starts_with("line 2"));
+        //every bin of baldacci1 is in stock once
+        let json_layouts =
+            placements::parse_line_placements("0 0 0 0 0 0\n1 0 1 0 0 0", &instance).unwrap();
+        let Err(ParseError::InvalidSolution(reason)) =
+            parser::build_solution_from_json(&instance, &json_layouts, config.cde_config)
+        else {
+            panic!("layouts exceeding the stock of a bin accepted");
+        };
+        assert!(reason.contains("stock"));
+
+        //and reported to callers of the library
+        let result = lbf_run::solve_json(
+            String::new(),
+            "{\"Name\": ".to_string(),
+            folder.to_string_lossy().to_string(),
+        );
+        assert!(matches!(result, Err(IoError::Json { path: None, .. })));
+        let result = lbf_run::solve_json(
+            String::new(),
+            serde_json::to_string(&no_demand).unwrap(),
+            folder.to_string_lossy().to_string(),
+        );
+        assert!(matches!(
+            result,
+            Err(IoError::Parse(ParseError::InvalidItem { item: 3, .. }))
+        ));
+        //strips are only packed towards the left
+        let right_config = LBFConfig {
+            packing_direction: PackingDirection::Right,
+            ..LBFConfig::default()
+        };
+        let result = lbf_run::solve_json(
+            serde_json::to_string(&right_config).unwrap(),
+            std::fs::read_to_string("../assets/swim.json").unwrap(),
+            folder.to_string_lossy().to_string(),
+        );
+        assert!(matches!(result, Err(IoError::InvalidConfig { .. })));
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_esicup_import() {
+        let esicup = |boards: &str| {
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<nesting xmlns="http://globalnest.fe.up.pt/nesting">
+  <name>tiny</name>
+  <problem>
+    <boards>{boards}</boards>
+    <lot>
+      <piece id="square" quantity="3">
+        <orientation><enumeration angle="0"/><enumeration angle="90"/></orientation>
+        <component idPolygon="polygon1" type="0" xOffset="2" yOffset="0"/>
+      </piece>
+      <piece id="triangle" quantity="2">
+        <orientation/>
+        <component idPolygon="polygon2" type="0" xOffset="0" yOffset="0"/>
+      </piece>
+    </lot>
+  </problem>
+  <polygons>
+    <polygon id="polygon0" nVertices="4">
+      <lines>
+        <segment n="1" x0="0" x1="10" y0="0" y1="0"/>
+        <segment n="2" x0="10" x1="10" y0="0" y1="40"/>
+        <segment n="3" x0="10" x1="0" y0="40" y1="40"/>
+        <segment n="4" x0="0" x1="0" y0="40" y1="0"/>
+      </lines>
+    </polygon>
+    <polygon id="polygon1" nVertices="4">
+      <lines>
+        <segment n="3" x0="4" x1="0" y0="4" y1="4"/>
+        <segment n="1" x0="0" x1="4" y0="0" y1="0"/>
+        <segment n="4" x0="0" x1="0" y0="4" y1="0"/>
+        <segment n="2" x0="4" x1="4" y0="0" y1="4"/>
+      </lines>
+    </polygon>
+    <polygon id="polygon2" nVertices="3">
+      <lines>
+        <segment n="1" x0="0" x1="6" y0="0" y1="0"/>
+        <segment n="2" x0="6" x1="0" y0="0" y1="3"/>
+        <segment n="3" x0="0" x1="0" y0="3" y1="0"/>
+      </lines>
+    </polygon>
+  </polygons>
+</nesting>"#
+            )
+        };
+
+        //a single board of quantity 1 is a strip, transposed so its length runs along the x-axis
+        let strip_board = r#"<piece id="board" quantity="1"><component idPolygon="polygon0" type="0" xOffset="0" yOffset="0"/></piece>"#;
+        let json_instance = esicup_instance(&esicup(strip_board)).unwrap();
+        assert_eq!(json_instance.name, "tiny");
+        assert_eq!(json_instance.strip.as_ref().unwrap().height, 10.0);
+        assert!(json_instance.bins.is_none());
+        let square = &json_instance.items[0];
+        assert_eq!(square.demand, Some(3));
+        assert_eq!(square.allowed_orientations, Some(vec![0.0, 270.0]));
+        let Some(JsonShape::SimplePolygon(JsonSimplePoly(points))) = &square.shape else {
+            panic!("square should be a simple polygon")
+        };
+        assert_eq!(points[..2], [(0.0, 2.0), (0.0, 6.0)]);
+        //an empty orientation allows any rotation
+        assert_eq!(json_instance.items[1].allowed_orientations, None);
+
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        assert_eq!(
+            solution.layout_snapshots[0].placed_items.len(),
+            instance.total_item_qty()
+        );
+
+        //any other board definition results in bins
+        let stocked_board = strip_board.replace(r#"quantity="1""#, r#"quantity="2""#);
+        let json_instance = esicup_instance(&esicup(&stocked_board)).unwrap();
+        assert!(json_instance.strip.is_none());
+        let bin = &json_instance.bins.as_ref().unwrap()[0];
+        assert_eq!((bin.stock, bin.cost), (Some(2), 400));
+
+        //pieces composed of multiple polygons are not supported
+        let composite_board = strip_board.replace(
+            "</piece>",
+            r#"<component idPolygon="polygon1" type="0" xOffset="0" yOffset="0"/></piece>"#,
+        );
+        let Err(err) = esicup_instance(&esicup(&composite_board)) else {
+            panic!("composite pieces should be rejected")
+        };
+        assert!(err.contains("2 polygons"));
+        assert!(esicup_instance("<nesting><lot>").is_err());
+    }
+
+    #[test]
+    fn test_reference_comparison() {
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_reference_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let mut json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        let no_reference =
+            reference::compare_to_reference(&folder, &json_instance.name, &instance, &solution);
+        assert!(no_reference.unwrap().is_none());
+
+        //a solution is as good as itself
+        let reference_path = reference::reference_path(&folder, &json_instance.name);
+        std::fs::write(
+            &reference_path,
+            serde_json::to_string(&json_solution).unwrap(),
+        )
+        .unwrap();
+        let comparison =
+            reference::compare_to_reference(&folder, &json_instance.name, &instance, &solution)
+                .unwrap()
+                .unwrap();
+        let cost = solution
+            .layout_snapshots
+            .iter()
+            .map(|sl| sl.bin.value as fsize)
+            .sum::<fsize>();
+        assert_eq!(comparison.value, cost);
+        assert_eq!(comparison.reference_value, cost);
+        assert_eq!(comparison.gap, 0.0);
+        assert!(comparison.is_comparable());
+
+        //a reference using fewer bins, but also placing fewer items
+        let dropped = json_solution.layouts.pop().unwrap();
+        std::fs::write(
+            &reference_path,
+            serde_json::to_string(&json_solution).unwrap(),
+        )
+        .unwrap();
+        let comparison =
+            reference::compare_to_reference(&folder, &json_instance.name, &instance, &solution)
+                .unwrap()
+                .unwrap();
+        assert!(comparison.gap > 0.0);
+        assert_eq!(
+            comparison.n_reference_placed + dropped.placed_items.len(),
+            comparison.n_placed
+        );
+        assert!(!comparison.is_comparable());
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_sweep() {
+        let parameters: SweepParameters = serde_json::from_str(
+            r#"{"n_samples": [50, 100], "prng_seed": [0, 1, 2], "cde_config.quadtree_depth": [4]}"#,
+        )
+        .unwrap();
+        let base_config = LBFConfig::default();
+        let configs = sweep::sweep_configs(&base_config, &parameters).unwrap();
+        assert_eq!(configs.len(), 6);
+        assert!(configs
+            .iter()
+            .all(|(_, config)| config.cde_config.quadtree_depth == 4));
+        assert_eq!(configs.iter().map(|(_, c)| c.prng_seed).unique().count(), 3);
+
+        //parameters which are not part of the config are rejected instead of ignored
+        let typo: SweepParameters = serde_json::from_str(r#"{"n_sample": [50]}"#).unwrap();
+        assert!(sweep::sweep_configs(&base_config, &typo).is_err());
+
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let seeds: SweepParameters =
+            serde_json::from_str(r#"{"n_samples": [100], "prng_seed": [0, 1]}"#).unwrap();
+        let configs = sweep::sweep_configs(&base_config, &seeds).unwrap();
+        let sequential = sweep::run_sweep(&json_instance, PathBuf::new(), &configs, false).unwrap();
+        let parallel = sweep::run_sweep(&json_instance, PathBuf::new(), &configs, true).unwrap();
+        assert_eq!(sequential.len(), 2);
+        for (s, p) in sequential.iter().zip(parallel.iter()) {
+            assert_eq!(s.values, p.values);
+            assert_eq!(
+                (s.usage, s.value, s.n_placed),
+                (p.usage, p.value, p.n_placed)
+            );
+        }
+
+        let csv = sweep::results_csv(&seeds, &sequential);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("instance,n_samples,prng_seed,usage,n_placed,n_demanded,value,run_time_ms")
+        );
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with(&format!("{},100,0,", json_instance.name)));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with(&format!("{},100,1,", json_instance.name)));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_wkt_shapes() {
+        let json_instance: JsonInstance = serde_json::from_str(
+            r#"{
+                "Name": "wkt",
+                "Items": [
+                    {"Demand": 2, "Shape": {"Type": "Wkt", "Data": "POLYGON ((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 2 8, 8 8, 8 2, 2 2))"}},
+                    {"Demand": 3, "Shape": {"Type": "Wkt", "Data": "SRID=4326;multipolygon z (((0 0 1, 6 0 1, 0 3 1, 0 0 1)))"}}
+                ],
+                "Objects": [
+                    {"Cost": 1, "Stock": 2, "Shape": {"Type": "Wkt", "Data": "POLYGON((0 0,40 0,40 20,0 20,0 0))"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            poly_simpl_tolerance: None,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        assert!((instance.item(0).shape.area() - 64.0).abs() < 1e-3);
+        assert!((instance.item(1).shape.area() - 9.0).abs() < 1e-3);
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        assert_eq!(
+            solution.placed_item_qtys.iter().sum::<usize>(),
+            instance.total_item_qty()
+        );
+
+        //the WKT is converted to the equivalent shape
+        assert!(matches!(
+            wkt::wkt_shape("POLYGON ((0 0, 1 0, 1 1, 0 0))"),
+            Ok(JsonShape::SimplePolygon(JsonSimplePoly(points))) if points.len() == 3
+        ));
+        assert!(matches!(
+            wkt::wkt_shape("MULTIPOLYGON (((0 0, 1 0, 1 1)), ((2 0, 3 0, 3 1)))"),
+            Ok(JsonShape::MultiPolygon(polys)) if polys.len() == 2
+        ));
+        assert!(wkt::wkt_shape("LINESTRING (0 0, 1 1)").is_err());
+        assert!(wkt::wkt_shape("POLYGON EMPTY").is_err());
+        assert!(wkt::wkt_shape("POLYGON ((0 0, 1 0, 1 1, 0 0)").is_err());
+
+        let mut invalid = json_instance.clone();
+        invalid.items[1].shape = Some(JsonShape::Wkt("POLYGON ((0 0, 1 0, 0 0))".into()));
+        let Err(ParseError::InvalidItem { item: 1, reason }) = parser.parse(&invalid) else {
+            panic!("invalid WKT should be rejected")
+        };
+        assert!(reason.contains("invalid WKT"));
+    }
+
+    #[test]
+    fn test_geojson() {
+        let json_instance = geojson::geojson_instance(
+            r#"{
+                "type": "FeatureCollection",
+                "Name": "parcels",
+                "features": [
+                    {"type": "Feature", "properties": {"Kind": "Bin", "Cost": 1, "Stock": 3},
+                     "geometry": {"type": "Polygon", "coordinates": [[[0, 0], [30, 0], [30, 20], [0, 20], [0, 0]], [[12, 8], [18, 8], [18, 12], [12, 12], [12, 8]]]}},
+                    {"type": "Feature", "properties": {"Demand": 4, "AllowedOrientations": [0, 90], "Name": "tile"},
+                     "geometry": {"type": "Polygon", "coordinates": [[[100, 100, 5], [108, 100, 5], [108, 104, 5], [100, 104, 5], [100, 100, 5]]]}},
+                    {"type": "Feature", "properties": null,
+                     "geometry": {"type": "MultiPolygon", "coordinates": [[[[0, 0], [6, 0], [0, 6], [0, 0]]]]}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(json_instance.name, "parcels");
+        assert_eq!(json_instance.items.len(), 2);
+        assert_eq!(json_instance.items[0].demand, Some(4));
+        assert_eq!(json_instance.items[1].demand, None);
+        let bins = json_instance.bins.as_ref().unwrap();
+        assert_eq!((bins.len(), bins[0].stock), (1, Some(3)));
+        assert!(matches!(&bins[0].shape, Some(JsonShape::Polygon(jp)) if jp.inner.len() == 1));
+
+        let mut json_instance = json_instance;
+        json_instance.items[1].demand = Some(2);
+        let config = LBFConfig {
+            n_samples: 100,
+            poly_simpl_tolerance: None,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+
+        //a feature for every bin and placed item, with the transformations of the JSON solution
+        let geojson = geojson::solution_to_geojson(&solution, &instance);
+        let features = geojson["features"].as_array().unwrap();
+        let n_placed = solution.placed_item_qtys.iter().sum::<usize>();
+        assert_eq!(features.len(), solution.layout_snapshots.len() + n_placed);
+        let bin_feature = &features[0];
+        assert_eq!(bin_feature["properties"]["Kind"], "Bin");
+        assert_eq!(bin_feature["geometry"]["coordinates"][1][0][0], 12.0);
+        let item_features = features
+            .iter()
+            .filter(|f| f["properties"]["Kind"] == "Item" && f["properties"]["Layout"] == 0)
+            .collect_vec();
+        for (feature, jpi) in item_features
+            .iter()
+            .zip(json_solution.layouts[0].placed_items.iter())
+        {
+            let properties = &feature["properties"];
+            assert_eq!(properties["Item"], jpi.index);
+            let rotation = properties["Rotation"].as_f64().unwrap() as fsize;
+            assert!((rotation - jpi.transformation.rotation.to_degrees()).abs() < 1e-3);
+            //the polygon is the item placed in the coordinates of the bin
+            let ring = feature["geometry"]["coordinates"][0].as_array().unwrap();
+            let points = ring
+                .iter()
+                .skip(1)
+                .map(|p| {
+                    Point(
+                        p[0].as_f64().unwrap() as fsize,
+                        p[1].as_f64().unwrap() as fsize,
+                    )
+                })
+                .collect_vec();
+            let area = SimplePolygon::new(points).area();
+            assert!((area - instance.item(jpi.index).shape.area()).abs() < 1e-2);
+            assert!(ring.iter().all(|p| {
+                let (x, y) = (p[0].as_f64().unwrap(), p[1].as_f64().unwrap());
+                (-1e-3..=30.001).contains(&x) && (-1e-3..=20.001).contains(&y)
+            }));
+        }
+
+        assert!(geojson::geojson_instance(r#"{"type": "Feature"}"#).is_err());
+        let Err(err) = geojson::geojson_instance(
+            r#"{"type": "FeatureCollection", "features": [{"type": "Feature", "geometry": {"type": "LineString", "coordinates": [[0, 0], [1, 1]]}}]}"#,
+        ) else {
+            panic!("line strings should be rejected")
+        };
+        assert!(err.starts_with("feature 0"));
+    }
+
+    #[test]
+    fn test_serve() {
+        use std::io::{Read, Write};
+        use std::net::{TcpListener, TcpStream};
+
+        let folder = std::env::temp_dir().join(format!("lbf_test_serve_{}", std::process::id()));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let server_folder = folder.clone();
+        std::thread::spawn(move || {
+            serve::serve(listener, server_folder, std::sync::Arc::new(thread_pool))
+        });
+
+        //sends a request and returns the status code and body of the response
+        let request = |method: &str, target: &str, body: &str| {
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(
+                stream,
+                "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+                method,
+                target,
+                body.len(),
+                body
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            let (head, body) = response.split_once("\r\n\r\n").unwrap();
+            let status: u16 = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+            (status, body.to_string())
+        };
+
+        assert_eq!(request("GET", "/health", ""), (200, "ok".to_string()));
+        assert_eq!(request("GET", "/unknown", "").0, 404);
+        assert_eq!(request("GET", "/json", "").0, 405);
+        assert_eq!(request("POST", "/json", "not json").0, 400);
+
+        let config = LBFConfig {
+            n_samples: 100,
+            prng_seed: Some(0),
+            ..LBFConfig::default()
+        };
+        let solve_request = SolveRequest {
+            config: serde_json::to_string(&config).unwrap(),
+            input: std::fs::read_to_string("../assets/baldacci1.json").unwrap(),
+        };
+        let (status, body) = request(
+            "POST",
+            "/json",
+            &serde_json::to_string(&solve_request).unwrap(),
+        );
+        assert_eq!(status, 200, "{}", body);
+        let files: Vec<Vec<String>> = serde_json::from_str(&body).unwrap();
+        assert!(!files[0].is_empty());
+        assert!(files[0].iter().all(|svg| svg.ends_with(".svg")));
+        //and a thumbnail of every layout
+        assert_eq!(files[2].len(), files[0].len());
+
+        //the solution can be fetched, other files cannot
+        let json_path = &files[1][0];
+        let (status, body) = request("GET", &format!("/file?path={}", json_path), "");
+        assert_eq!(status, 200);
+        let json_output: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_output["Name"], "Test1");
+        assert_eq!(
+            request("GET", "/file?path=..%2Fassets%2Fbaldacci1.json", "").0,
+            404
+        );
+
+        //a malformed instance fails its own request, not the server
+        let invalid_request = SolveRequest {
+            config: String::new(),
+            input: r#"{"Name": "invalid"}"#.to_string(),
+        };
+        let (status, _) = request(
+            "POST",
+            "/json",
+            &serde_json::to_string(&invalid_request).unwrap(),
+        );
+        assert_eq!(status, 400);
+        assert_eq!(request("GET", "/health", "").0, 200);
+
+        //oversized request lines and headers are rejected before they are read entirely
+        let long_target = format!("/health?{}", "a".repeat(serve::MAX_LINE_LENGTH));
+        assert_eq!(request("GET", &long_target, "").0, 414);
+        let response_to = |head: String| {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(head.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let long_header = format!(
+            "GET /health HTTP/1.1\r\nX-Long: {}\r\n\r\n",
+            "a".repeat(serve::MAX_LINE_LENGTH)
+        );
+        assert!(response_to(long_header).starts_with("HTTP/1.1 431"));
+        let many_headers = format!(
+            "GET /health HTTP/1.1\r\n{}\r\n",
+            "X-Header: a\r\n".repeat(serve::MAX_HEADERS + 1)
+        );
+        assert!(response_to(many_headers).starts_with("HTTP/1.1 431"));
+
+        //connections beyond the maximum are refused while the others stay open
+        let idle = (0..serve::MAX_CONNECTIONS)
+            .map(|_| TcpStream::connect(address).unwrap())
+            .collect_vec();
+        assert!(response_to(String::new()).starts_with("HTTP/1.1 503"));
+        drop(idle);
+        let recovered = (0..100).any(|_| {
+            std::thread::sleep(Duration::from_millis(50));
+            request("GET", "/health", "").0 == 200
+        });
+        assert!(recovered);
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn test_multi_polygon_items() {
+        //a ring with an island in its hole, and a separate square
+        let multi_polygon = "MULTIPOLYGON (((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 2 8, 8 8, 8 2, 2 2)), ((4 4, 6 4, 6 6, 4 6, 4 4)), ((20 0, 25 0, 25 5, 20 5, 20 0)))";
+        let json_instance: JsonInstance = serde_json::from_str(&format!(
+            r#"{{
+                "Name": "multi_polygon",
+                "Items": [
+                    {{"Demand": 2, "Shape": {{"Type": "Wkt", "Data": "{}"}}}},
+                    {{"Demand": 4, "Shape": {{"Type": "Rectangle", "Data": {{"Width": 4, "Height": 4}}}}}}
+                ],
+                "Strip": {{"Height": 12}}
+            }}"#,
+            multi_polygon
+        ))
+        .unwrap();
+        let config = LBFConfig {
+            n_samples: 1000,
+            prng_seed: Some(0),
+            ..LBFConfig::default()
+        };
+        let parser = |part_in_part_forbidden| {
+            Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                PathBuf::new(),
+                config.fidelity(),
+            )
+            .with_part_in_part_forbidden(part_in_part_forbidden)
+        };
+
+        let instance = parser(false).parse(&json_instance).unwrap();
+        let item = instance.item(0);
+        assert!((item.original_area - 93.0).abs() < 1e-3);
+        assert!(item.shape.area() >= 93.0 - 1e-2);
+        //every part belongs to the item, the space between them does not
+        let inside = |p: Point| {
+            item.shape
+                .collides_with(&p.transform_clone(&item.pretransform))
+        };
+        assert!(inside(Point(1.0, 5.0)));
+        assert!(inside(Point(5.0, 5.0)));
+        assert!(inside(Point(22.5, 2.5)));
+        assert!(!inside(Point(3.0, 5.0)));
+        assert!(!inside(Point(15.0, 5.0)));
+
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        let report = verify::verify_solution(&instance, &json_solution.layouts, config.cde_config);
+        assert!(report.is_feasible(), "{:?}", report.violations);
+        assert_eq!(report.n_placed, instance.total_item_qty());
+
+        //with part-in-part forbidden, the island is covered by the filled ring
+        let instance = parser(true).parse(&json_instance).unwrap();
+        let item = instance.item(0);
+        assert!((item.shape.area() - 125.0).abs() < 1e-2);
+
+        //parts which overlap are rejected
+        let overlapping: JsonInstance = serde_json::from_str(
+            r#"{
+                "Name": "overlapping",
+                "Items": [{"Demand": 1, "Shape": {"Type": "Wkt", "Data": "MULTIPOLYGON (((0 0, 10 0, 10 10, 0 10, 0 0)), ((5 5, 15 5, 15 15, 5 15, 5 5)))"}}],
+                "Strip": {"Height": 20}
+            }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            parser(false).parse(&overlapping),
+            Err(ParseError::InvalidItem { item: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_tracing_spans() {
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id};
+        use tracing::Subscriber;
+        use tracing_subscriber::layer::{Context, Layer};
+        use tracing_subscriber::prelude::*;
+
+        /// Counts the spans created, by name
+        struct SpanCounter(Arc<Mutex<HashMap<&'static str, usize>>>);
+
+        impl<S: Subscriber> Layer<S> for SpanCounter {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+                *self
+                    .0
+                    .lock()
+                    .unwrap()
+                    .entry(attrs.metadata().name())
+                    .or_default() += 1;
+            }
+        }
+
+        let counts = Arc::new(Mutex::new(HashMap::new()));
+        let subscriber = tracing_subscriber::registry().with(
+            SpanCounter(counts.clone()).with_filter(tracing_subscriber::filter::LevelFilter::DEBUG),
+        );
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        //only the spans of this thread are recorded, those of the items parsed on other threads are not
+        tracing::subscriber::with_default(subscriber, || {
+            let instance = Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                PathBuf::new(),
+                config.fidelity(),
+            )
+            .parse(&json_instance)
+            .unwrap();
+            LBFOptimizer::new(instance, config, SmallRng::seed_from_u64(0))
+                .unwrap()
+                .solve()
+        });
+
+        let counts = counts.lock().unwrap();
+        assert_eq!(counts.get("parse"), Some(&1));
+        assert_eq!(counts.get("solve"), Some(&1));
+        assert!(counts.get("place_item").is_some_and(|n| *n > 0));
+        assert!(counts.get("surrogate_collides").is_some_and(|n| *n > 0));
+    }
+
+    #[test]
+    fn test_svg_items_with_holes() {
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_svg_holes_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        //a frame with a square hole, containing a small island which is not part of the frame
+        std::fs::write(
+            folder.join("frame.svg"),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+  <path d="M0 0 H40 V40 H0 Z M5 5 V35 H35 V5 Z"/>
+  <rect x="18" y="18" width="4" height="4"/>
+</svg>"#,
+        )
+        .unwrap();
+        let json_instance: JsonInstance = serde_json::from_str(
+            r#"{
+                "Name": "svg_holes",
+                "Items": [
+                    {"Demand": 1, "Svg": "frame.svg"},
+                    {"Demand": 4, "Shape": {"Type": "Rectangle", "Data": {"Width": 10, "Height": 10}}}
+                ],
+                "Strip": {"Height": 41}
+            }"#,
+        )
+        .unwrap();
+        let config = LBFConfig {
+            n_samples: 1000,
+            prng_seed: Some(0),
+            ..LBFConfig::default()
+        };
+        let parser = |part_in_part_forbidden| {
+            Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                folder.clone(),
+                config.fidelity(),
+            )
+            .with_part_in_part_forbidden(part_in_part_forbidden)
+        };
+
+        let instance = parser(false).parse(&json_instance).unwrap();
+        let frame = instance.item(0);
+        assert!((frame.original_area - 700.0).abs() < 1e-3);
+        assert!(frame.shape.area() >= 700.0 - 1e-2);
+        let inside = |p: Point| {
+            frame
+                .shape
+                .collides_with(&p.transform_clone(&frame.pretransform))
+        };
+        assert!(inside(Point(2.0, 20.0)));
+        assert!(!inside(Point(10.0, 20.0)));
+
+        //the squares fit in the hole of the frame, next to it they would widen the strip to 50
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        assert_eq!(solution.n_items_placed(), 5);
+        assert!(solution.layout_snapshots[0].bin.bbox().width() < 45.0);
+
+        //with part-in-part forbidden, the hole is filled
+        let instance = parser(true).parse(&json_instance).unwrap();
+        assert!((instance.item(0).shape.area() - 1600.0).abs() < 1e-2);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_solve_report() {
+        let folder = std::env::temp_dir().join(format!("lbf_test_report_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            prng_seed: Some(0),
+            ..LBFConfig::default()
+        };
+        let report = lbf_run::solve_json_report(
+            serde_json::to_string(&config).unwrap(),
+            std::fs::read_to_string("../assets/baldacci1.json").unwrap(),
+            format!("{}/", folder.display()),
+            None,
+        )
+        .unwrap();
+
+        let phases = report.phases.iter().map(|(phase, _)| *phase).collect_vec();
+        assert_eq!(phases, ["parse", "solve", "post_process", "write"]);
+        assert!(report
+            .files
+            .iter()
+            .flatten()
+            .all(|f| Path::new(f).is_file()));
+        let json_output: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&report.files[1][0]).unwrap()).unwrap();
+        let usage = json_output["Solution"]["Usage"].as_f64().unwrap();
+        assert!(report.usage > 0.0 && (report.usage as f64 - usage).abs() < 1e-6);
+        assert!(report.n_items_placed > 0 && report.n_items_placed <= report.n_items_demanded);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_parse_reader() {
+        let mut json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        //every shape twice
+        let n_shapes = json_instance.items.len();
+        json_instance.items.extend(json_instance.items.clone());
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+
+        let eager = parser.parse(&json_instance).unwrap();
+        let json_str = serde_json::to_string(&json_instance).unwrap();
+        let streamed = parser.parse_reader(json_str.as_bytes()).unwrap();
+        assert_eq!(eager.items().len(), streamed.items().len());
+        for (a, b) in eager.items().iter().zip(streamed.items()) {
+            assert_eq!(a.1, b.1);
+            assert!((a.0.shape.area() - b.0.shape.area()).abs() < 1e-3);
+        }
+
+        //identical shapes are parsed once and shared by their items
+        for instance in [&eager, &streamed] {
+            for i in 0..n_shapes {
+                let (a, b) = (instance.item(i), instance.item(i + n_shapes));
+                assert!(std::sync::Arc::ptr_eq(&a.shape, &b.shape));
+            }
+        }
+
+        assert!(matches!(
+            parser.parse_reader(&json_str.as_bytes()[..json_str.len() / 2]),
+            Err(ParseError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_asset_paths() {
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_asset_paths_{}", std::process::id()));
+        let (local, shared) = (folder.join("local"), folder.join("shared"));
+        std::fs::create_dir_all(&local).unwrap();
+        std::fs::create_dir_all(&shared).unwrap();
+        let square = |side: u32| {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg"><polygon points="0,0 {side},0 {side},{side} 0,{side}"/></svg>"#
+            )
+        };
+        //the local file takes precedence over the one on the shared drive
+        std::fs::write(local.join("a.svg"), square(10)).unwrap();
+        std::fs::write(shared.join("a.svg"), square(20)).unwrap();
+        std::fs::write(shared.join("b.svg"), square(30)).unwrap();
+        std::env::set_var("LBF_TEST_SHARED_DRIVE", &shared);
+
+        let item = |svg: String| -> JsonItem {
+            serde_json::from_value(serde_json::json!({"Demand": 1, "Svg": svg})).unwrap()
+        };
+        let mut json_instance: JsonInstance = serde_json::from_str(
+            r#"{"Name": "asset_paths", "Items": [], "Strip": {"Height": 100}}"#,
+        )
+        .unwrap();
+        json_instance.items = vec![
+            item("a.svg".into()),
+            item("b.svg".into()),
+            item("${LBF_TEST_SHARED_DRIVE}/a.svg".into()),
+            item(shared.join("b.svg").to_string_lossy().into_owned()),
+        ];
+        let config = LBFConfig::default();
+        let asset_paths = AssetPaths::new(local.clone()).with_search_folder(shared.clone());
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .with_asset_paths(asset_paths.clone());
+
+        assert_eq!(asset_paths.resolve("a.svg").unwrap(), local.join("a.svg"));
+        assert_eq!(asset_paths.resolve("b.svg").unwrap(), shared.join("b.svg"));
+        let instance = parser.parse(&json_instance).unwrap();
+        let areas = (0..4)
+            .map(|i| instance.item(i).original_area.round())
+            .collect_vec();
+        assert_eq!(areas, [100.0, 900.0, 400.0, 900.0]);
+
+        //files which are nowhere to be found, and undefined variables, are reported as missing
+        json_instance.items = vec![
+            item("c.svg".into()),
+            item("${LBF_TEST_UNDEFINED_DRIVE}/a.svg".into()),
+        ];
+        let Err(ParseError::MissingAssets(missing)) = parser.parse(&json_instance) else {
+            panic!("missing files not reported");
+        };
+        assert_eq!(missing.len(), 2);
+        assert!(missing[0].source.to_string().contains("search folders"));
+        assert!(missing[1]
+            .source
+            .to_string()
+            .contains("LBF_TEST_UNDEFINED_DRIVE"));
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_units() {
+        use dxf::entities::{Entity, EntityType, LwPolyline};
+        use dxf::enums::{AcadVersion, Units as DxfUnits};
+        use dxf::{Drawing, LwPolylineVertex};
+
+        let folder = std::env::temp_dir().join(format!("lbf_test_units_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        //a square of one inch, drawn in millimeters
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.header.default_drawing_units = DxfUnits::Millimeters;
+        let mut lw_polyline = LwPolyline {
+            vertices: [(0.0, 0.0), (25.4, 0.0), (25.4, 25.4), (0.0, 25.4)]
+                .into_iter()
+                .map(|(x, y)| LwPolylineVertex {
+                    x,
+                    y,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        lw_polyline.set_is_closed(true);
+        drawing.add_entity(Entity::new(EntityType::LwPolyline(lw_polyline)));
+        drawing.save_file(folder.join("square_mm.dxf")).unwrap();
+
+        let json_instance: JsonInstance = serde_json::from_str(
+            r#"{
+                "Name": "units",
+                "Units": "Inch",
+                "Items": [
+                    {"Demand": 4, "Shape": {"Type": "Rectangle", "Data": {"Width": 2, "Height": 1}}},
+                    {"Demand": 3, "Dxf": "square_mm.dxf"}
+                ],
+                "Strip": {"Height": 10}
+            }"#,
+        )
+        .unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            folder.clone(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let Instance::SP(sp_instance) = &instance else {
+            panic!("expected a strip packing instance")
+        };
+
+        //parsed into millimeters, the units of the DXF file are honored
+        assert!((sp_instance.strip_height - 254.0).abs() < 1e-3);
+        assert!((instance.item(0).shape.area() - 2.0 * 25.4 * 25.4).abs() < 1e-1);
+        assert!((instance.item(1).shape.area() - 25.4 * 25.4).abs() < 1e-1);
+
+        //the solution is in inches again
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        let JsonContainer::Strip { width, height } = json_solution.layouts[0].container else {
+            panic!("expected a strip")
+        };
+        assert!((height - 10.0).abs() < 1e-3);
+        assert!(width > 1.0 && width < 10.0, "strip width {width}");
+        for jpi in &json_solution.layouts[0].placed_items {
+            let (x, y) = jpi.transformation.translation;
+            assert!(x > -1e-3 && x < width + 1e-3 && y > -1e-3 && y < 10.0 + 1e-3);
+        }
+        let report = verify::verify_solution(&instance, &json_solution.layouts, config.cde_config);
+        assert!(report.is_feasible(), "{:?}", report.violations);
+        let rebuilt =
+            parser::build_solution_from_json(&instance, &json_solution.layouts, config.cde_config)
+                .unwrap();
+        assert!((rebuilt.usage - solution.usage).abs() < 1e-3);
+
+        //the composed instance is in inches and parses into the same items
+        let composed = parser::compose_json_instance(&json_instance, &instance);
+        let Some(JsonShape::SimplePolygon(square)) = &composed.items[1].shape else {
+            panic!("expected a simple polygon")
+        };
+        assert!(square
+            .0
+            .iter()
+            .all(|(x, y)| *x < 1.0 + 1e-3 && *y < 1.0 + 1e-3));
+        let reparsed = parser.parse(&composed).unwrap();
+        assert!((reparsed.item(1).shape.area() - instance.item(1).shape.area()).abs() < 1e-1);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_dxf_layer_mapping() {
+        use dxf::entities::{Circle, Entity, EntityType, LwPolyline};
+        use dxf::enums::AcadVersion;
+        use dxf::{Drawing, LwPolylineVertex, Point as DxfPoint};
+
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_dxf_layers_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let rect = |layer: &str, (x, y): (f64, f64), (width, height): (f64, f64)| {
+            let mut lw_polyline = LwPolyline {
+                vertices: [
+                    (x, y),
+                    (x + width, y),
+                    (x + width, y + height),
+                    (x, y + height),
+                ]
+                .into_iter()
+                .map(|(x, y)| LwPolylineVertex {
+                    x,
+                    y,
+                    ..Default::default()
+                })
+                .collect(),
+                ..Default::default()
+            };
+            lw_polyline.set_is_closed(true);
+            let mut entity = Entity::new(EntityType::LwPolyline(lw_polyline));
+            entity.common.layer = layer.to_string();
+            entity
+        };
+        let circle = |layer: &str, (x, y): (f64, f64), radius: f64| {
+            let mut entity = Entity::new(EntityType::Circle(Circle::new(
+                DxfPoint::new(x, y, 0.0),
+                radius,
+            )));
+            entity.common.layer = layer.to_string();
+            entity
+        };
+        let save = |file_name: &str, entities: Vec<Entity>| {
+            let mut drawing = Drawing::new();
+            drawing.header.version = AcadVersion::R2000;
+            entities.into_iter().for_each(|e| {
+                drawing.add_entity(e);
+            });
+            drawing.save_file(folder.join(file_name)).unwrap();
+        };
+        //a title block around the part, which is the outline when all layers are read
+        save(
+            "plate_x2.dxf",
+            vec![
+                rect("NOTES", (-10.0, -10.0), (60.0, 40.0)),
+                rect("CUT", (0.0, 0.0), (40.0, 20.0)),
+                circle("HOLE", (10.0, 10.0), 5.0),
+            ],
+        );
+        save(
+            "sheet.dxf",
+            vec![
+                rect("cut", (0.0, 0.0), (200.0, 100.0)),
+                circle("hole", (150.0, 50.0), 10.0),
+                rect("DEFECT", (0.0, 0.0), (20.0, 20.0)),
+                rect("Q1", (100.0, 0.0), (30.0, 100.0)),
+            ],
+        );
+        let mapping: DxfLayerMapping = serde_json::from_str(
+            r#"{"Outline": ["CUT"], "Holes": ["HOLE"], "QualityZones": {"DEFECT": 0, "Q1": 1}}"#,
+        )
+        .unwrap();
+
+        let config = LBFConfig::default();
+        let json_instance =
+            dxf_folder::folder_instance(&folder, config.fidelity(), &mapping).unwrap();
+        let json_bin = &json_instance.bins.as_ref().unwrap()[0];
+        let Some(JsonShape::Polygon(bin_shape)) = &json_bin.shape else {
+            panic!("expected a bin with a hole")
+        };
+        assert_eq!(bin_shape.inner.len(), 1);
+        assert_eq!(
+            json_bin.zones.iter().map(|z| z.quality).collect_vec(),
+            [0, 1]
+        );
+
+        let parser = |mapping: DxfLayerMapping| {
+            Parser::new(
+                config.poly_simpl_config(),
+                config.cde_config,
+                true,
+                folder.clone(),
+                config.fidelity(),
+            )
+            .with_dxf_layer_mapping(mapping)
+        };
+        let instance = parser(mapping).parse(&json_instance).unwrap();
+        let hole_area = PI * 5.0 * 5.0;
+        assert!((instance.item(0).original_area - (800.0 - hole_area)).abs() < 1.0);
+        let Instance::BP(bp_instance) = &instance else {
+            panic!("expected a bin packing instance")
+        };
+        let bin = &bp_instance.bins[0].0;
+        assert_eq!(bin.holes.len(), 1);
+        assert_eq!(bin.quality_zones[0].as_ref().unwrap().zones.len(), 1);
+        assert_eq!(bin.quality_zones[1].as_ref().unwrap().zones.len(), 1);
+
+        //without a mapping, the title block is read as the outline of the item, with the part as its hole
+        let instance = parser(DxfLayerMapping::default())
+            .parse(&json_instance)
+            .unwrap();
+        assert!((instance.item(0).original_area - (2400.0 - 800.0)).abs() < 1.0);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_embedded_assets() {
+        use dxf::entities::{Entity, EntityType, LwPolyline};
+        use dxf::enums::AcadVersion;
+        use dxf::{Drawing, LwPolylineVertex};
+        use jagua_rs::io::json_instance::JsonAsset;
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut lw_polyline = LwPolyline {
+            vertices: [(0.0, 0.0), (30.0, 0.0), (30.0, 10.0), (0.0, 10.0)]
+                .into_iter()
+                .map(|(x, y)| LwPolylineVertex {
+                    x,
+                    y,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        lw_polyline.set_is_closed(true);
+        drawing.add_entity(Entity::new(EntityType::LwPolyline(lw_polyline)));
+        let mut dxf_bytes = vec![];
+        drawing.save(&mut dxf_bytes).unwrap();
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><polygon points="0,0 10,0 10,10 0,10"/></svg>"#;
+
+        let mut json_instance: JsonInstance = serde_json::from_str(
+            r#"{"Name": "embedded", "Items": [{"Demand": 2, "Dxf": "plate.dxf"}, {"Demand": 3, "Svg": "square.svg"}], "Strip": {"Height": 50}}"#,
+        )
+        .unwrap();
+        json_instance.assets = [
+            ("plate.dxf".to_string(), JsonAsset(dxf_bytes.clone().into())),
+            ("square.svg".to_string(), JsonAsset(svg.as_bytes().into())),
+        ]
+        .into_iter()
+        .collect();
+        //the files are base64 encoded in the JSON, and only exist there
+        let json = serde_json::to_string(&json_instance).unwrap();
+        assert!(!json.contains("<svg"));
+        let config = LBFConfig::default();
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::from("/nonexistent/assets"),
+            config.fidelity(),
+        );
+        let areas = |instance: &Instance| {
+            (0..2)
+                .map(|i| instance.item(i).original_area.round())
+                .collect_vec()
+        };
+        let read_back: JsonInstance = serde_json::from_str(&json).unwrap();
+        assert_eq!(areas(&parser.parse(&read_back).unwrap()), [300.0, 100.0]);
+        assert_eq!(
+            areas(&parser.parse_reader(json.as_bytes()).unwrap()),
+            [300.0, 100.0]
+        );
+
+        //as a data URL, as read by a browser
+        let encoded = serde_json::to_value(&json_instance.assets["square.svg"]).unwrap();
+        let data_url = format!("data:image/svg+xml;base64,{}", encoded.as_str().unwrap());
+        let asset: JsonAsset = serde_json::from_value(serde_json::json!(data_url)).unwrap();
+        assert_eq!(&*asset.0, svg.as_bytes());
+
+        //or passed to the parser instead of embedded in the instance
+        json_instance.assets.clear();
+        let asset_paths = AssetPaths::default()
+            .with_embedded_asset("plate.dxf".into(), dxf_bytes.into())
+            .with_embedded_asset("square.svg".into(), svg.as_bytes().into());
+        let instance = parser
+            .with_asset_paths(asset_paths)
+            .parse(&json_instance)
+            .unwrap();
+        assert_eq!(areas(&instance), [300.0, 100.0]);
+    }
+
+    #[test]
+    fn test_validate_solution() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        assert!(validation::validate_solution(&instance, &solution).is_empty());
+
+        //the written solution, imported back
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        let (_, violations) =
+            verify::validate_json_solution(&instance, &json_solution.layouts, config.cde_config)
+                .unwrap();
+        assert!(violations.is_empty(), "{:?}", violations);
+
+        //an item moved on top of another one, and an item moved outside the bin
+        let mut layouts = json_solution.layouts.clone();
+        layouts[0].placed_items[1].transformation =
+            layouts[0].placed_items[0].transformation.clone();
+        let last = layouts[0].placed_items.len() - 1;
+        layouts[0].placed_items[last].transformation.translation.0 += 1e5;
+        let (imported, violations) =
+            verify::validate_json_solution(&instance, &layouts, config.cde_config).unwrap();
+        let keys = imported.layout_snapshots[0]
+            .placed_items
+            .keys()
+            .collect_vec();
+        let kinds = |pk| {
+            violations
+                .iter()
+                .filter(|v| v.layout == 0 && v.placed_item == pk)
+                .map(|v| v.kind)
+                .collect_vec()
+        };
+        assert!(kinds(keys[0]).contains(&ViolationKind::Overlap { other: keys[1] }));
+        assert!(kinds(keys[1]).contains(&ViolationKind::Overlap { other: keys[0] }));
+        assert_eq!(kinds(keys[last]), [ViolationKind::OutsideBin]);
+        let description = verify::describe_placement_violation(&imported, &violations[0]);
+        assert!(
+            description.starts_with("layout 0: placed item "),
+            "{description}"
+        );
+    }
+    #[test]
+    fn test_zip_instance() {
+        use dxf::entities::{Entity, EntityType, LwPolyline};
+        use dxf::enums::AcadVersion;
+        use dxf::{Drawing, LwPolylineVertex};
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+        use zip::ZipWriter;
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut lw_polyline = LwPolyline {
+            vertices: [(0.0, 0.0), (30.0, 0.0), (30.0, 10.0), (0.0, 10.0)]
+                .into_iter()
+                .map(|(x, y)| LwPolylineVertex {
+                    x,
+                    y,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        lw_polyline.set_is_closed(true);
+        drawing.add_entity(Entity::new(EntityType::LwPolyline(lw_polyline)));
+        let mut dxf_bytes = vec![];
+        drawing.save(&mut dxf_bytes).unwrap();
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><polygon points="0,0 10,0 10,10 0,10"/></svg>"#;
+        let instance_json = r#"{"Name": "zipped", "Items": [{"Demand": 2, "Dxf": "./parts\\plate.dxf"}, {"Demand": 3, "Svg": "square.svg"}], "Strip": {"Height": 50}}"#;
+
+        let zip_files = |files: &[(&str, &[u8])]| {
+            let mut zip = ZipWriter::new(std::io::Cursor::new(vec![]));
+            for (name, contents) in files {
+                zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+                zip.write_all(contents).unwrap();
+            }
+            zip.finish().unwrap().into_inner()
+        };
+        //the DXF file is at its path relative to the instance, the SVG file is found by its name
+        let archive = zip_files(&[
+            ("nest/instance.json", instance_json.as_bytes()),
+            ("nest/parts/plate.dxf", &dxf_bytes),
+            ("square.svg", svg.as_bytes()),
+            ("nest/readme.txt", b"not referenced"),
+        ]);
+        let json_instance = zip_instance::zip_instance(&archive).unwrap();
+        assert_eq!(json_instance.name, "zipped");
+        assert_eq!(
+            json_instance.assets.keys().collect_vec(),
+            ["./parts\\plate.dxf", "square.svg"]
+        );
+        assert_eq!(&*json_instance.assets["square.svg"].0, svg.as_bytes());
+
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::from("/nonexistent/assets"),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        assert_eq!(
+            (0..2)
+                .map(|i| instance.item(i).original_area.round())
+                .collect_vec(),
+            [300.0, 100.0]
+        );
+
+        //from a file, and solved as an upload
+        let folder = std::env::temp_dir().join(format!("lbf_test_zip_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let zip_path = folder.join("nest.zip");
+        std::fs::write(&zip_path, &archive).unwrap();
+        assert_eq!(io::read_zip_instance(&zip_path).unwrap().assets.len(), 2);
+        let report = lbf_run::solve_zip_report(
+            serde_json::to_string(&config).unwrap(),
+            &archive,
+            format!("{}/", folder.display()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(report.n_items_placed, 5);
+
+        //the instance file should be unambiguous
+        let no_instance = zip_files(&[("square.svg", svg.as_bytes())]);
+        assert!(zip_instance::zip_instance(&no_instance).is_err());
+        let two_instances = zip_files(&[
+            ("a.json", instance_json.as_bytes()),
+            ("b.json", instance_json.as_bytes()),
+        ]);
+        assert!(matches!(
+            zip_instance::zip_instance(&two_instances),
+            Err(reason) if reason.contains("multiple")
+        ));
+        assert!(
+            lbf_run::solve_zip_report(String::new(), b"not a zip", String::new(), None).is_err()
+        );
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    fn test_export_precision() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let mut config = LBFConfig {
+            n_samples: 100,
+            export_precision: Some(2),
+            ..LBFConfig::default()
+        };
+        config.svg_draw_options.precision = Some(1);
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+
+        let mut rounded = json_solution.clone();
+        let rotation_decimals = json_output::round_transformations(&mut rounded, &instance, 2);
+        assert!(rotation_decimals > 2);
+        let max_radius = instance
+            .items()
+            .iter()
+            .flat_map(|(item, _)| {
+                let to_original = item.pretransform.clone().inverse();
+                item.shape
+                    .points
+                    .iter()
+                    .map(move |p| p.transform_clone(&to_original))
+            })
+            .map(|Point(x, y)| x.hypot(y))
+            .fold(0.0, fsize::max);
+        let transformations = |s: &JsonSolution| {
+            s.layouts
+                .iter()
+                .flat_map(|l| l.placed_items.iter().map(|pi| pi.transformation.clone()))
+                .collect_vec()
+        };
+        for (exact, rounded) in transformations(&json_solution)
+            .iter()
+            .zip(transformations(&rounded).iter())
+        {
+            let (x, y) = rounded.translation;
+            assert_eq!(io::round_decimals(x, 2), x);
+            assert_eq!(io::round_decimals(y, 2), y);
+            assert!((exact.translation.0 - x).abs() <= 0.005 + 1e-4);
+            assert!((exact.translation.1 - y).abs() <= 0.005 + 1e-4);
+            //the rotation moves no point of an item more than the translation
+            let rotation_shift = (exact.rotation - rounded.rotation).abs() * max_radius;
+            assert!(rotation_shift <= json_output::rounding_displacement(2));
+        }
+        //short numbers in the JSON, and never a negative zero
+        let json = serde_json::to_string(&transformations(&rounded)).unwrap();
+        assert!(!json.contains("-0.0,") && !json.contains("-0.0]"));
+        assert_eq!(io::round_decimals(-0.001, 2).to_string(), "0");
+        assert_eq!(io::round_decimals(1.23456, 3), 1.235);
+
+        //every coordinate of the contours and placements in the SVG has at most one decimal
+        let svg = s_layout_to_svg(
+            &solution.layout_snapshots[0],
+            &instance,
+            config.svg_draw_options,
+        )
+        .to_string();
+        let coordinates = svg
+            .split(['"', '(', ')'])
+            .filter(|attr| attr.starts_with('M') || attr.starts_with("translate"))
+            .flat_map(|attr| attr.split([' ', ',', 'M', 'L', 'z', 'A']))
+            .filter_map(|token| token.parse::<fsize>().ok())
+            .collect_vec();
+        assert!(coordinates.len() > 100);
+        assert!(coordinates.iter().all(|&v| io::round_decimals(v, 1) == v));
+    }
+    #[test]
+    fn test_gcode() {
+        use dxf::entities::{Entity, EntityType, LwPolyline};
+        use dxf::enums::AcadVersion;
+        use dxf::{Drawing, LwPolylineVertex};
+        use jagua_rs::io::json_instance::JsonAsset;
+
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let s_layout = &solution.layout_snapshots[0];
+        let n_parts = s_layout.placed_items.len();
+
+        //every part is cut once, nearest neighbour travels less than the placement order
+        let placement_order = gcode::layout_toolpath(
+            s_layout,
+            &instance,
+            TravelOptimization::PlacementOrder,
+            None,
+        );
+        let nearest = gcode::layout_toolpath(
+            s_layout,
+            &instance,
+            TravelOptimization::NearestNeighbour,
+            None,
+        );
+        for toolpath in [&placement_order, &nearest] {
+            let keys = toolpath
+                .contours
+                .iter()
+                .map(|c| c.placed_item)
+                .collect_vec();
+            assert_eq!(keys.iter().unique().count(), n_parts);
+        }
+        assert!(nearest.travel_distance() < placement_order.travel_distance());
+        assert!((nearest.cut_distance() - placement_order.cut_distance()).abs() < 1e-1);
+
+        //a given cut order is followed, only the pierce points are chosen
+        let mut json_solution = parser::compose_json_solution(
+            &solution,
+            &instance,
+            std::time::Instant::now(),
+            0.0,
+            &[],
+        );
+        cut_order::assign_cut_orders(
+            &mut json_solution,
+            &instance,
+            CutOrderConfig { heat_memory: 2 },
+        );
+        let order = &json_solution.layouts[0].cut_order;
+        let ordered = gcode::layout_toolpath(
+            s_layout,
+            &instance,
+            TravelOptimization::NearestNeighbour,
+            Some(order),
+        );
+        let keys = s_layout.placed_items.keys().collect_vec();
+        assert_eq!(
+            ordered.contours.iter().map(|c| c.placed_item).collect_vec(),
+            order.iter().map(|&i| keys[i]).collect_vec()
+        );
+
+        let gcode_config = GcodeConfig::default();
+        let program = gcode::toolpath_to_gcode(&nearest, &instance, &gcode_config);
+        let lines = program.lines().collect_vec();
+        assert_eq!(lines.iter().filter(|l| **l == "M3").count(), n_parts);
+        assert_eq!(lines.iter().filter(|l| **l == "M5").count(), n_parts);
+        assert_eq!(lines[lines.len() - 2..], ["G0 X0.000 Y0.000", "M2"]);
+        let first = nearest.contours[0].contour.points[0];
+        assert!(lines.contains(&&*format!("G0 X{:.3} Y{:.3}", first.0, first.1)));
+
+        //a slot with rounded ends is cut with arcs, of which the center is as far from the start as from the end
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut lw_polyline = LwPolyline {
+            vertices: [
+                (0.0, 0.0, 0.0),
+                (30.0, 0.0, 1.0),
+                (30.0, 10.0, 0.0),
+                (0.0, 10.0, 1.0),
+            ]
+            .into_iter()
+            .map(|(x, y, bulge)| LwPolylineVertex {
+                x,
+                y,
+                bulge,
+                ..Default::default()
+            })
+            .collect(),
+            ..Default::default()
+        };
+        lw_polyline.set_is_closed(true);
+        drawing.add_entity(Entity::new(EntityType::LwPolyline(lw_polyline)));
+        let mut dxf_bytes = vec![];
+        drawing.save(&mut dxf_bytes).unwrap();
+        let mut slot_instance: JsonInstance = serde_json::from_str(
+            r#"{"Name": "slots", "Items": [{"Demand": 2, "Dxf": "slot.dxf"}], "Strip": {"Height": 50}}"#,
+        )
+        .unwrap();
+        slot_instance.assets = [("slot.dxf".to_string(), JsonAsset(dxf_bytes.into()))]
+            .into_iter()
+            .collect();
+        let slot_instance = parser.parse(&slot_instance).unwrap();
+        let slot_solution =
+            LBFOptimizer::new(slot_instance.clone(), config, SmallRng::seed_from_u64(0))
+                .unwrap()
+                .solve();
+        let toolpath = gcode::layout_toolpath(
+            &slot_solution.layout_snapshots[0],
+            &slot_instance,
+            TravelOptimization::NearestNeighbour,
+            None,
+        );
+        let program = gcode::toolpath_to_gcode(&toolpath, &slot_instance, &gcode_config);
+        let coordinate = |line: &str, axis: char| -> fsize {
+            line.split(' ')
+                .find_map(|w| w.strip_prefix(axis))
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+        let mut position = Point(0.0, 0.0);
+        let mut n_arcs = 0;
+        for line in program.lines() {
+            if line.starts_with("G2 ") || line.starts_with("G3 ") {
+                let center = Point(
+                    position.0 + coordinate(line, 'I'),
+                    position.1 + coordinate(line, 'J'),
+                );
+                let end = Point(coordinate(line, 'X'), coordinate(line, 'Y'));
+                assert!((center.distance(position) - 5.0).abs() < 1e-2, "{line}");
+                assert!((center.distance(end) - 5.0).abs() < 1e-2, "{line}");
+                n_arcs += 1;
+            }
+            if line.starts_with('G') && line.contains(" X") {
+                position = Point(coordinate(line, 'X'), coordinate(line, 'Y'));
+            }
+        }
+        assert_eq!(n_arcs, 4);
+    }
+
+    #[test]
+    fn test_compact_json() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            json_format: JsonFormat::Compact,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let json_output = JsonOutput {
+            instance: json_instance,
+            solution: parser::compose_json_solution(
+                &solution,
+                &instance,
+                std::time::Instant::now(),
+                0.0,
+                &[],
+            ),
+            config,
+        };
+
+        let mut pretty = vec![];
+        json_output::write_json(&json_output, &mut pretty, JsonFormat::Pretty).unwrap();
+        let mut compact = vec![];
+        json_output::write_json(&json_output, &mut compact, JsonFormat::Compact).unwrap();
+        assert!(!compact.contains(&b'\n'));
+        assert!(compact.len() * 2 < pretty.len());
+
+        //both formats contain the same values
+        let pretty: serde_json::Value = serde_json::from_slice(&pretty).unwrap();
+        let compact: serde_json::Value = serde_json::from_slice(&compact).unwrap();
+        assert_eq!(pretty, compact);
+
+        //the written file follows the format of the config
+        let path =
+            std::env::temp_dir().join(format!("lbf_test_compact_json_{}.json", std::process::id()));
+        io::write_json_output(&json_output, &path);
+        let written = std::fs::read(&path).unwrap();
+        assert!(!written.contains(&b'\n'));
+        let written: serde_json::Value = serde_json::from_slice(&written).unwrap();
+        assert_eq!(written, compact);
+        let read_back: JsonOutput = serde_json::from_value(written).unwrap();
+        assert_eq!(read_back.config.json_format, JsonFormat::Compact);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_png_export() {
+        use lbf::io::png_export;
+        use resvg::tiny_skia::Pixmap;
+
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let s_layout = &solution.layout_snapshots[0];
+
+        let png =
+            png_export::s_layout_to_png(s_layout, &instance, config.svg_draw_options, 400).unwrap();
+        let pixmap = Pixmap::decode_png(&png).unwrap();
+        assert_eq!(pixmap.width(), 400);
+        //the drawing is not empty: the placed items are drawn over the white background
+        let n_colors = pixmap
+            .pixels()
+            .iter()
+            .map(|p| (p.red(), p.green(), p.blue()))
+            .unique()
+            .count();
+        assert!(n_colors > 2);
+
+        //the image has the aspect ratio of the SVG
+        let svg = s_layout_to_svg(s_layout, &instance, config.svg_draw_options).to_string();
+        let png = png_export::svg_to_png(svg.as_bytes(), 800).unwrap();
+        let large = Pixmap::decode_png(&png).unwrap();
+        assert_eq!(large.width(), 800);
+        assert!(large.height().abs_diff(2 * pixmap.height()) <= 2);
+
+        assert!(png_export::svg_to_png(svg.as_bytes(), 0).is_err());
+        assert!(png_export::svg_to_png(svg.as_bytes(), png_export::MAX_PNG_WIDTH + 1).is_err());
+        assert!(png_export::svg_to_png(b"not an svg", 400).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "pdf")]
+    fn test_pdf_report() {
+        use lbf::io::pdf_report;
+
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/baldacci1.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+
+        let pdf = pdf_report::solution_to_pdf(
+            &solution,
+            &instance,
+            &json_instance.name,
+            config.svg_draw_options,
+        );
+        assert!(pdf.starts_with(b"%PDF-"));
+        //the content streams are not compressed
+        let text = String::from_utf8_lossy(&pdf);
+        let n_pages: usize = text
+            .split("/Count ")
+            .nth(1)
+            .and_then(|s| s.split_whitespace().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        //a summary page (which continues when the item table is long) and a page per layout
+        assert!(n_pages > solution.layout_snapshots.len());
+        assert!(text.contains(&format!("(Nesting report: {})", json_instance.name)));
+        let n_placed = solution.placed_item_qtys.iter().sum::<usize>();
+        let n_demanded = solution.target_item_qtys.iter().sum::<usize>();
+        assert!(text.contains(&format!("(Items placed: {} of {})", n_placed, n_demanded)));
+        for i in 0..solution.layout_snapshots.len() {
+            assert!(text.contains(&format!("(Layout {}: ", i)));
+        }
+    }
+
+    #[test]
+    fn test_thumbnail_lod() {
+        let json_instance =
+            io::read_json_instance(Some(Path::new("../assets/swim.json")), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 100,
+            ..LBFConfig::default()
+        };
+        let instance = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        )
+        .parse(&json_instance)
+        .unwrap();
+        let solution = LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0))
+            .unwrap()
+            .solve();
+        let s_layout = &solution.layout_snapshots[0];
+
+        let full = s_layout_to_svg(s_layout, &instance, config.svg_draw_options).to_string();
+        let same = s_layout_to_svg_lod(
+            s_layout,
+            &instance,
+            config.svg_draw_options,
+            LevelOfDetail::Full,
+        )
+        .to_string();
+        assert_eq!(full, same);
+
+        let thumbnail = |width| {
+            s_layout_to_svg_lod(
+                s_layout,
+                &instance,
+                config.svg_draw_options,
+                LevelOfDetail::Thumbnail { width },
+            )
+            .to_string()
+        };
+        let (small, large) = (thumbnail(100), thumbnail(1000));
+        assert!(small.len() < large.len());
+        assert!(large.len() < full.len());
+        //every placed item is still drawn, without its title
+        let n_placed = s_layout.placed_items.len();
+        assert_eq!(small.matches("<use").count(), n_placed);
+        assert!(!small.contains("transf:"));
+
+        //vertices which deviate less than the tolerance are left out, contours which collapse become their bounding box
+        let square = [
+            Point(0.0, 0.0),
+            Point(5.0, 0.1),
+            Point(10.0, 0.0),
+            Point(10.0, 10.0),
+            Point(0.0, 10.0),
+        ];
+        assert_eq!(svg_export::simplify_contour(&square, 0.5).len(), 4);
+        assert_eq!(svg_export::simplify_contour(&square, 0.05), square);
+        let sliver = [
+            Point(0.0, 0.0),
+            Point(10.0, 0.1),
+            Point(20.0, 0.0),
+            Point(10.0, -0.1),
+        ];
+        assert_eq!(
+            svg_export::simplify_contour(&sliver, 1.0),
+            vec![
+                Point(0.0, -0.1),
+                Point(20.0, -0.1),
+                Point(20.0, 0.1),
+                Point(0.0, 0.1)
+            ]
+        );
+    }
+
+    #[test_case("../assets/baldacci1.json"; "baldacci1")]
+    fn test_run_stats(instance_path: &str) {
+        let json_instance = io::read_json_instance(Some(Path::new(instance_path)), None).unwrap();
+        let config = LBFConfig {
+            n_samples: 500,
+            n_runs: Some(2),
+            ..LBFConfig::default()
+        };
+        let parser = Parser::new(
+            config.poly_simpl_config(),
+            config.cde_config,
+            true,
+            PathBuf::new(),
+            config.fidelity(),
+        );
+        let instance = parser.parse(&json_instance).unwrap();
+
+        cd_trace::enable();
+        let (solution, n_samples) =
+            lbf_runs::solve_best_counted(&instance, config, SmallRng::seed_from_u64(0)).unwrap();
+        //the samples of both runs are counted
+        let mut first_run =
+            LBFOptimizer::new(instance.clone(), config, SmallRng::seed_from_u64(0)).unwrap();
+        first_run.solve();
+        assert!(n_samples > first_run.sample_counter);
+
+        let stats = RunStats::new(
+            &json_instance.name,
+            config.prng_seed,
+            &solution,
+            Duration::from_millis(1234),
+            n_samples,
+            &cd_trace::counts(),
+        );
+        assert_eq!(stats.run_time_ms, 1234);
+        assert_eq!(stats.layout_usages.len(), solution.layout_snapshots.len());
+        assert_eq!(stats.n_placed + stats.n_unplaced, instance.total_item_qty());
+        assert!(stats.n_cd_queries > 0);
+        assert!(stats.n_cd_collisions <= stats.n_cd_queries);
+
+        let folder =
+            std::env::temp_dir().join(format!("lbf_test_run_stats_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+
+        //the header of the CSV file is written once, every solve appends a row
+        let csv_path = folder.join("stats.csv");
+        io::append_run_stats(&stats, &csv_path);
+        io::append_run_stats(&stats, &csv_path);
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let lines = csv.lines().collect_vec();
+        assert_eq!(
+            lines,
+            [STATS_CSV_HEADER, &stats.csv_row(), &stats.csv_row()]
+        );
+        assert_eq!(
+            lines[1].split(',').count(),
+            STATS_CSV_HEADER.split(',').count()
+        );
+
+        let jsonl_path = folder.join("stats.jsonl");
+        io::append_run_stats(&stats, &jsonl_path);
+        io::append_run_stats(&stats, &jsonl_path);
+        let jsonl = std::fs::read_to_string(&jsonl_path).unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+        for line in jsonl.lines() {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(record["instance"], json_instance.name.as_str());
+            assert_eq!(record["n_samples"], n_samples);
+            assert_eq!(
+                record["layout_usages"].as_array().unwrap().len(),
+                stats.layout_usages.len()
+            );
+        }
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
 }